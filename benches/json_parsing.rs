@@ -0,0 +1,121 @@
+//! Compares the full and the partial JSON-RPC deserialization in the proxy
+//! hot path.
+//!
+//! Run with `cargo bench --bench json_parsing`.
+
+use {
+    rpc_proxy::utils::{
+        batch_json_rpc_request::MaybeBatchRequest,
+        partial_json_rpc::{MaybeBatchRequestMethod, PartialResponse},
+    },
+    std::{
+        hint::black_box,
+        time::{Duration, Instant},
+    },
+};
+
+const ITERATIONS: u32 = 10_000;
+
+fn bench(name: &str, mut f: impl FnMut()) -> Duration {
+    // Warming up the caches and the allocator
+    for _ in 0..ITERATIONS / 10 {
+        f();
+    }
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        f();
+    }
+    let per_iteration = start.elapsed() / ITERATIONS;
+    println!("{name:<40} {per_iteration:>12?}");
+    per_iteration
+}
+
+fn compare(name: &str, full: impl FnMut(), partial: impl FnMut()) {
+    let full = bench(&format!("{name} (full)"), full);
+    let partial = bench(&format!("{name} (partial)"), partial);
+    println!(
+        "{name:<40} {:>11.2}x\n",
+        full.as_secs_f64() / partial.as_secs_f64()
+    );
+}
+
+/// `eth_sendRawTransaction` request with the large calldata
+fn raw_transaction_request() -> Vec<u8> {
+    let transaction = format!("0x{}", "ab".repeat(32 * 1024));
+    serde_json::to_vec(&serde_json::json!({
+        "jsonrpc": "2.0",
+        "method": "eth_sendRawTransaction",
+        "params": [transaction],
+        "id": 1,
+    }))
+    .unwrap()
+}
+
+/// Batch of the `eth_call` requests
+fn batch_request() -> Vec<u8> {
+    let requests = (0..100)
+        .map(|id| {
+            serde_json::json!({
+                "jsonrpc": "2.0",
+                "method": "eth_call",
+                "params": [{
+                    "to": "0x0000000000000000000000000000000000000001",
+                    "data": format!("0x70a08231{:064x}", id),
+                }, "latest"],
+                "id": id,
+            })
+        })
+        .collect::<Vec<_>>();
+    serde_json::to_vec(&requests).unwrap()
+}
+
+/// `eth_getLogs` response with the many logs
+fn logs_response() -> Vec<u8> {
+    let logs = (0..1_000)
+        .map(|index| {
+            serde_json::json!({
+                "address": "0x0000000000000000000000000000000000000001",
+                "topics": [format!("0x{:064x}", index), format!("0x{:064x}", index + 1)],
+                "data": format!("0x{:0128x}", index),
+                "blockNumber": "0x10",
+                "transactionHash": format!("0x{:064x}", index),
+                "logIndex": format!("0x{index:x}"),
+                "removed": false,
+            })
+        })
+        .collect::<Vec<_>>();
+    serde_json::to_vec(&serde_json::json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "result": logs,
+    }))
+    .unwrap()
+}
+
+fn main() {
+    for (name, body) in [
+        ("eth_sendRawTransaction request", raw_transaction_request()),
+        ("eth_call batch request", batch_request()),
+    ] {
+        compare(
+            name,
+            || {
+                black_box(serde_json::from_slice::<MaybeBatchRequest>(black_box(&body)).unwrap());
+            },
+            || {
+                black_box(MaybeBatchRequestMethod::from_slice(black_box(&body)).unwrap());
+            },
+        );
+    }
+
+    let body = logs_response();
+    compare(
+        "eth_getLogs response",
+        || {
+            black_box(serde_json::from_slice::<jsonrpc::Response>(black_box(&body)).unwrap());
+        },
+        || {
+            black_box(serde_json::from_slice::<PartialResponse>(black_box(&body)).unwrap());
+        },
+    );
+}
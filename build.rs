@@ -3,5 +3,11 @@ use vergen::{vergen, Config};
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     vergen(Config::default())?;
 
+    // Vendored protoc to not require it in the build environments
+    std::env::set_var("PROTOC", protoc_bin_vendored::protoc_bin_path()?);
+    tonic_build::configure()
+        .build_client(false)
+        .compile_protos(&["proto/blockchain_api/v1/blockchain_api.proto"], &["proto"])?;
+
     Ok(())
 }
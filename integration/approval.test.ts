@@ -0,0 +1,87 @@
+import { getTestSetup } from './init';
+import { Interface } from 'ethers';
+
+describe('Wallet build approval', () => {
+  const { baseUrl, projectId, httpClient } = getTestSetup();
+
+  const baseUSDCContractAddress = '0x833589fCD6eDb6E08f4c7C32D4f71b54bdA02913';
+  const baseUSDC = `eip155:8453/erc20:${baseUSDCContractAddress}`;
+  const owner = '0x2aae531a81461f029cd55cb46703211c9227ba05';
+  const spender = '0x2aae531a81461f029cd55cb46703211c9227ba06';
+  const permit2Address = '0x000000000022D473030F116dDEE9F6B43aC78BA3';
+  const erc20Interface = new Interface([
+    'function approve(address spender, uint256 amount)',
+  ]);
+
+  it('should build an ERC20 approve transaction', async () => {
+    const payload = {
+      jsonrpc: '2.0',
+      id: 1,
+      method: 'wallet_buildApproval',
+      params: {
+        token: baseUSDC,
+        spender,
+        amount: '0x3e8',
+      },
+    };
+
+    const response = await httpClient.post(`${baseUrl}/v1/json-rpc?projectId=${projectId}`, payload);
+
+    expect(response.status).toBe(200);
+    const result = response.data.result;
+    expect(result.type).toBe('approve');
+    expect(result.transaction.chainId).toBe('eip155:8453');
+    expect(result.transaction.to.toLowerCase()).toBe(baseUSDCContractAddress.toLowerCase());
+    const decodedData = erc20Interface.decodeFunctionData('approve', result.transaction.data);
+    expect(decodedData[0].toLowerCase()).toBe(spender);
+    expect(decodedData[1]).toBe(BigInt(1000));
+  });
+
+  it('should build the Permit2 typed data', async () => {
+    const payload = {
+      jsonrpc: '2.0',
+      id: 1,
+      method: 'wallet_buildApproval',
+      params: {
+        token: baseUSDC,
+        spender,
+        amount: '0x3e8',
+        type: 'permit2',
+        owner,
+      },
+    };
+
+    const response = await httpClient.post(`${baseUrl}/v1/json-rpc?projectId=${projectId}`, payload);
+
+    expect(response.status).toBe(200);
+    const result = response.data.result;
+    expect(result.type).toBe('permit2');
+    expect(result.typedData.primaryType).toBe('PermitSingle');
+    expect(result.typedData.domain.chainId).toBe(8453);
+    expect(result.typedData.domain.verifyingContract.toLowerCase()).toBe(permit2Address.toLowerCase());
+    expect(result.typedData.message.spender.toLowerCase()).toBe(spender);
+    expect(result.typedData.message.details.amount).toBe('1000');
+    if (result.approvalTransaction) {
+      const decodedData = erc20Interface.decodeFunctionData('approve', result.approvalTransaction.data);
+      expect(decodedData[0].toLowerCase()).toBe(permit2Address.toLowerCase());
+    }
+  });
+
+  it('should require the owner for the Permit2 approval', async () => {
+    const payload = {
+      jsonrpc: '2.0',
+      id: 1,
+      method: 'wallet_buildApproval',
+      params: {
+        token: baseUSDC,
+        spender,
+        amount: '0x3e8',
+        type: 'permit2',
+      },
+    };
+
+    const response = await httpClient.post(`${baseUrl}/v1/json-rpc?projectId=${projectId}`, payload);
+
+    expect(response.status).toBe(400);
+  });
+});
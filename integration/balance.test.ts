@@ -230,4 +230,31 @@ describe('Account balance', () => {
       expect(typeof item.iconUrl).toBe('string')
     }
   })
+
+  it('balance filtered by the tokens', async () => {
+    // Native ETH and the USDC token on Base with the zero balance
+    const tokens = [
+      'eip155:1/slip44:60',
+      'eip155:8453/erc20:0x833589fcd6edb6e08f4c7c32d4f71b54bda02913',
+    ]
+    const endpoint = `/v1/account/${empty_eth_address}/balance`;
+    const queryParams = `?projectId=${projectId}&currency=${currency}&sv=${sdk_version}&tokens=${tokens.join(',')}`;
+    let resp = await httpClient.get(`${baseUrl}${endpoint}${queryParams}`, withOriginHeader());
+    expect(resp.status).toBe(200)
+    expect(resp.data.balances).toHaveLength(2)
+
+    const [native, usdc] = resp.data.balances
+    expect(native.chainId).toBe('eip155:1')
+    expect(native.address).toBeUndefined()
+    expect(usdc.chainId).toBe('eip155:8453')
+    expect(usdc.address.toLowerCase()).toBe('eip155:8453:0x833589fcd6edb6e08f4c7c32d4f71b54bda02913')
+    expect(Number(usdc.quantity.numeric)).toBe(0)
+  })
+
+  it('balance filtered by the invalid tokens', async () => {
+    const endpoint = `/v1/account/${fulfilled_eth_address}/balance`;
+    const queryParams = `?projectId=${projectId}&currency=${currency}&sv=${sdk_version}&tokens=eip155:1`;
+    let resp = await httpClient.get(`${baseUrl}${endpoint}${queryParams}`, withOriginHeader());
+    expect(resp.status).toBe(400)
+  })
 })
@@ -173,4 +173,26 @@ describe('Token conversion (single chain)', () => {
     expect(typeof resp.data.allowance).toBe('string')
 
   })
+
+  it('get batch allowances', async () => {
+    const spender = '0x111111125421ca6dc452d289314280a0f8842a65';
+    const checks = [
+      { tokenAddress: `${namespace}:${chainId}:0x111111111117dc0aa78b770fa6a738034120c302`, spender },
+      { tokenAddress: `${namespace}:${chainId}:0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48`, spender },
+      { tokenAddress: 'eip155:8453:0x833589fcd6edb6e08f4c7c32d4f71b54bda02913', spender },
+    ];
+    let resp: any = await httpClient.post(
+      `${baseUrl}/v1/convert/allowance/batch?projectId=${projectId}`,
+      {
+        userAddress: '0x2aae531a81461f029cd55cb46703211c9227ba05',
+        checks,
+      }
+    )
+    expect(resp.status).toBe(200)
+    expect(resp.data.allowances.length).toBe(checks.length)
+    for (const [index, item] of resp.data.allowances.entries()) {
+      expect(item.tokenAddress).toBe(checks[index].tokenAddress)
+      expect(typeof item.allowance).toBe('string')
+    }
+  })
 })
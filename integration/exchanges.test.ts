@@ -26,7 +26,7 @@ describe('Exchanges', () => {
   const nativeSOL = 'solana:5eykt4UsFv8P8NJdTREpY1vzqKqZKvdp/slip44:501';
   const unsupportedAsset = 'eip155:999/erc20:0x1234567890123456789012345678901234567890';
 
-  const supportedExchanges = ['binance', 'reown_test'];
+  const supportedExchanges = ['binance', 'okx', 'reown_test'];
 
   const defaultAmount = '100';
   const hexAmount = '0x64';
@@ -179,6 +179,126 @@ describe('Exchanges', () => {
     });
   });
 
+  describe('Get Exchange Assets', () => {
+    it('should get supported assets per exchange', async () => {
+      const payload = {
+        jsonrpc: '2.0',
+        id: 1,
+        method: 'reown_getExchangeAssets',
+        params: {}
+      };
+
+      const response = await httpClient.post(
+        `${baseUrl}/v1/json-rpc?projectId=${projectId}`,
+        payload
+      );
+
+      expect(response.status).toBe(200);
+      expect(response.data.result.exchanges).toBeInstanceOf(Array);
+      expect(response.data.result.exchanges.length).toBeGreaterThan(0);
+
+      for (const exchange of response.data.result.exchanges) {
+        expect(supportedExchanges).toContain(exchange.id);
+        expect(typeof exchange.name).toBe('string');
+        expect(exchange.assets.length).toBeGreaterThan(0);
+        for (const asset of exchange.assets) {
+          expect(typeof asset.asset).toBe('string');
+          expect(['string', 'undefined']).toContain(typeof asset.minAmount);
+          expect(['string', 'undefined']).toContain(typeof asset.maxAmount);
+          expect(['string', 'undefined']).toContain(typeof asset.fee);
+        }
+      }
+    });
+
+    it('should get assets filtered by includeOnly parameter', async () => {
+      const payload = {
+        jsonrpc: '2.0',
+        id: 1,
+        method: 'reown_getExchangeAssets',
+        params: {
+          includeOnly: ['reown_test']
+        }
+      };
+
+      const response = await httpClient.post(
+        `${baseUrl}/v1/json-rpc?projectId=${projectId}`,
+        payload
+      );
+
+      expect(response.status).toBe(200);
+      expect(response.data.result.exchanges).toHaveLength(1);
+      expect(response.data.result.exchanges[0].id).toBe('reown_test');
+      const assets = response.data.result.exchanges[0].assets.map((a: { asset: string }) => a.asset);
+      expect(assets).toContain(sepoliaETH);
+    });
+  });
+
+  describe('Exchange Webhooks', () => {
+    it('should reject webhook for unknown exchange', async () => {
+      const response = await httpClient.post(
+        `${baseUrl}/v1/exchanges/unknown-exchange/webhook`,
+        { externalOrderId: 'test-session-id', status: 20 }
+      );
+
+      expect(response.status).toBe(400);
+    });
+
+    it('should reject unsigned webhook', async () => {
+      const response = await httpClient.post(
+        `${baseUrl}/v1/exchanges/binance/webhook`,
+        { externalOrderId: 'test-session-id', status: 20 }
+      );
+
+      expect([400, 401]).toContain(response.status);
+    });
+  });
+
+  describe('Exchange Deposits', () => {
+    it('should return error for exchange without deposits support', async () => {
+      const payload = {
+        jsonrpc: '2.0',
+        id: 1,
+        method: 'reown_getExchangeDepositUrl',
+        params: {
+          exchangeId: 'reown_test',
+          asset: sepoliaETH,
+          amount: defaultAmount,
+          sender: sepoliaAddress
+        }
+      };
+
+      const response = await httpClient.post(
+        `${baseUrl}/v1/json-rpc?projectId=${projectId}`,
+        payload
+      );
+
+      expect(response.status).toBe(400);
+      expect(response.data.error).toBeDefined();
+      expect(response.data.error.message).toContain('Deposits are not supported');
+    });
+
+    it('should return error for unknown deposit session', async () => {
+      const payload = {
+        jsonrpc: '2.0',
+        id: 1,
+        method: 'reown_buildExchangeDeposit',
+        params: {
+          exchangeId: 'reown_test',
+          sessionId: 'a'.repeat(32)
+        }
+      };
+
+      const response = await httpClient.post(
+        `${baseUrl}/v1/json-rpc?projectId=${projectId}`,
+        payload
+      );
+
+      expect(response.status).toBe(400);
+      expect(response.data.error).toBeDefined();
+      expect(response.data.error.message).toContain('Session not found');
+    });
+  });
+
   describe('Get Exchange URL', () => {
     
     binanceTestFn('should generate pay URL for Binance with USDC on Base', async () => {
@@ -11,4 +11,20 @@ describe('Health', () => {
     expect(resp.data).toContain('features:')
     expect(resp.data).toContain('uptime:')
   })
+
+  it('reports the components status', async () => {
+    const { baseUrl, httpClient } = getTestSetup();
+    const resp: any = await httpClient.get(`${baseUrl}/health?verbose=true`)
+
+    expect(resp.status).toBe(200)
+    expect(resp.data.healthy).toBe(true)
+    expect(typeof resp.data.version).toBe('string')
+    const names = resp.data.components.map((component: any) => component.name)
+    expect(names).toContain('postgres')
+    expect(names).toContain('analytics')
+    for (const component of resp.data.components) {
+      expect(typeof component.ok).toBe('boolean')
+      expect(typeof component.critical).toBe('boolean')
+    }
+  })
 })
@@ -159,6 +159,21 @@ describe('Transactions history', () => {
     expect(first.metadata.status).toEqual(expect.stringMatching(/^ONRAMP_TRANSACTION_STATUS_/));
   })
 
+  it('onramp purchases merged into the history', async () => {
+    let resp: any = await httpClient.get(
+      `${baseUrl}/v1/account/${fulfilled_eth_address}/history?includeOnramp=true&projectId=${projectId}`,
+    )
+    expect(resp.status).toBe(200)
+    expect(typeof resp.data.data).toBe('object')
+
+    for (const item of resp.data.data) {
+      if (item.onramp) {
+        expect(item.onramp.provider).toBe('coinbase')
+        expect(item.onramp.status).toEqual(expect.stringMatching(/^ONRAMP_TRANSACTION_STATUS_/));
+      }
+    }
+  })
+
   it('onramp wrong provider', async () => {
     let resp: any = await httpClient.get(
       `${baseUrl}/v1/account/${fulfilled_eth_address}/history?onramp=some&projectId=${projectId}`,
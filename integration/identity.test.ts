@@ -49,8 +49,10 @@ describe('Identity', () => {
     )
     expect(resp.status).toBe(200)
     expect(resp.headers['cache-control']).toContain('public, max-age=')
+    // Results without the name are cached with the shorter negative TTL
     const age1 = +resp.headers['cache-control'].match(/max-age=(\d+)/)[1];
-    expect(age1).toBeGreaterThan(86397)
+    expect(age1).toBeLessThanOrEqual(3600)
+    expect(age1).toBeGreaterThan(3597)
     expect(resp.data.name).toBe(null)
 
     await new Promise(resolve => setTimeout(resolve, 2000));
@@ -59,8 +61,8 @@ describe('Identity', () => {
     )
     expect(resp2.status).toBe(200)
     const age2 = +resp2.headers['cache-control'].match(/max-age=(\d+)/)[1];
-    expect(age2).toBeLessThan(86400)
-    expect(age2).toBeGreaterThan(86395)
+    expect(age2).toBeLessThan(3600)
+    expect(age2).toBeGreaterThan(3595)
     expect(resp2.data.name).toBe(null)
   })
   it('solana address', async () => {
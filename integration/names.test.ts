@@ -305,6 +305,24 @@ describe('Account profile names', () => {
     expect(resp.data.length).toBe(0)
   })
 
+  it('names bulk forward lookup', async () => {
+    const notRegisteredName = `integration-test-${randomString}-free.${zone}`;
+    let resp: any = await httpClient.post(
+      `${baseUrl}/v1/profile/lookup`,
+      { names: [name, notRegisteredName] }
+    )
+    expect(resp.status).toBe(200)
+    expect(Object.keys(resp.data.names)).toEqual([name])
+    expect(resp.data.names[name][coin_type].address).toBe(address)
+
+    // Wrong name zone
+    resp = await httpClient.post(
+      `${baseUrl}/v1/profile/lookup`,
+      { names: [name, `integration-test-${randomString}.wrong.zone`] }
+    )
+    expect(resp.status).toBe(400)
+  })
+
   it('name reverse lookup (name found)', async () => {
     let resp: any = await httpClient.get(
       `${baseUrl}/v1/profile/reverse/${address}`
@@ -13,4 +13,18 @@ describe('Portfolio', () => {
     expect(first.name).toBeDefined()
     expect(first.symbol).toBeDefined()
   })
+
+  it('finds portfolio NFT holdings', async () => {
+    let resp: any = await httpClient.get(
+      `${baseUrl}/v1/account/0x2aae531a81461f029cd55cb46703211c9227ba05/portfolio?projectId=${projectId}&includeNfts=true`,
+    )
+    expect(resp.status).toBe(200)
+    expect(resp.data.data).toBeDefined()
+    expect(typeof resp.data.nfts.totalCount).toBe('number')
+    expect(typeof resp.data.nfts.estimatedFloorValue).toBe('number')
+    for (const collection of resp.data.nfts.collections) {
+      expect(collection.id).toBeDefined()
+      expect(typeof collection.count).toBe('number')
+    }
+  })
 })
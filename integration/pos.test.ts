@@ -157,6 +157,38 @@ describe('POS', () => {
       expect(params.input).toBeUndefined();
     });
 
+    it('should build a priced native transaction with the locked quote', async () => {
+      const payload: BuildTransactionRequest = {
+        jsonrpc: '2.0',
+        id: 1,
+        method: 'wc_pos_buildTransactions',
+        params: {
+          paymentIntents: [
+            {
+              asset: baseNative,
+              recipient: baseToAddress,
+              sender: baseFromAddress,
+              price: { amount: '0.01', currency: 'usd' },
+            }
+          ]
+        }
+      };
+
+      const response = await httpClient.post(`${baseUrl}/v1/json-rpc?projectId=${projectId}`, payload);
+
+      expect(response.status).toBe(200);
+      const result = (response.data as BuildTransactionResponse).result;
+      expect(result.transactions.length).toBe(1);
+      expect(result.quotes).toBeDefined();
+      const quote = result.quotes![0];
+      expect(quote.transactionId).toBe(result.transactions[0].id);
+      expect(quote.asset).toBe(baseNative);
+      expect(quote.price).toEqual({ amount: '0.01', currency: 'usd' });
+      expect(quote.assetPrice).toBeGreaterThan(0);
+      expect(Number(quote.amount)).toBeGreaterThan(0);
+      expect(quote.expiresAt).toBeGreaterThan(Math.floor(Date.now() / 1000));
+    });
+
 
     it('should not build a transaction with a recipient that is not a valid address', async () => {
       const payload = {
@@ -604,4 +636,98 @@ describe('POS', () => {
       expect(tron.assetNamespaces).toEqual(expect.arrayContaining(['trc20', 'slip44']));
     });
   });
+
+  describe('Fee estimation', () => {
+    it('should estimate fees for the payment intents', async () => {
+      const payload = {
+        jsonrpc: '2.0',
+        id: 1,
+        method: 'wc_pos_estimateFees',
+        params: {
+          paymentIntents: [
+            {
+              asset: baseUSDC,
+              amount: usdcAmount,
+              recipient: baseToAddress,
+              sender: baseFromAddress,
+            },
+            {
+              asset: baseNative,
+              amount: nativeAmount,
+              recipient: baseToAddress,
+              sender: baseFromAddress,
+            },
+          ],
+        },
+      };
+
+      const response = await httpClient.post(`${baseUrl}/v1/json-rpc?projectId=${projectId}`, payload);
+      expect(response.status).toBe(200);
+      const { fees } = response.data.result;
+      expect(fees.length).toBe(2);
+      for (const fee of fees) {
+        expect(fee.chainId).toBe(baseChainId);
+        expect(fee.feeAsset).toBe(`${baseChainId}/slip44:60`);
+        expect(BigInt(fee.amount)).toBeGreaterThan(BigInt(0));
+        expect(typeof fee.formattedAmount).toBe('string');
+        expect(fee.details.gasLimit).toBeDefined();
+      }
+      expect(fees[1].details.gasLimit).toBe('21000');
+    });
+  });
+
+  describe('Payment links', () => {
+    let paymentLinkId: string;
+
+    it('should create a payment link', async () => {
+      const response = await httpClient.post(
+        `${baseUrl}/v1/pos/payment-links?projectId=${projectId}`,
+        {
+          asset: baseUSDC,
+          amount: usdcAmount,
+          recipient: baseToAddress,
+          expiresAt: Math.floor(Date.now() / 1000) + 3600,
+        }
+      );
+      expect(response.status).toBe(200);
+      expect(typeof response.data.id).toBe('string');
+      expect(response.data.status).toBe('pending');
+      paymentLinkId = response.data.id;
+    });
+
+    it('should resolve the payment link', async () => {
+      const response = await httpClient.get(
+        `${baseUrl}/v1/pos/payment-links/${paymentLinkId}`
+      );
+      expect(response.status).toBe(200);
+      expect(response.data.asset).toBe(baseUSDC);
+      expect(response.data.amount).toBe(usdcAmount);
+      expect(response.data.recipient).toBe(baseToAddress);
+    });
+
+    it('should build the payment link transaction', async () => {
+      const response = await httpClient.post(
+        `${baseUrl}/v1/pos/payment-links/${paymentLinkId}/build?projectId=${projectId}`,
+        { sender: baseFromAddress }
+      );
+      expect(response.status).toBe(200);
+      expect(response.data.transactions.length).toBe(1);
+      expect(response.data.transactions[0].chainId).toBe(baseChainId);
+    });
+
+    it('should not check a transaction not built for the payment link', async () => {
+      const response = await httpClient.post(
+        `${baseUrl}/v1/pos/payment-links/${paymentLinkId}/check?projectId=${projectId}`,
+        { id: txIdBaseSepolia, sendResult: confirmedTxId }
+      );
+      expect(response.status).toBe(400);
+    });
+
+    it('should return 404 for an unknown payment link', async () => {
+      const response = await httpClient.get(
+        `${baseUrl}/v1/pos/payment-links/unknownLinkId`
+      );
+      expect(response.status).toBe(404);
+    });
+  });
 });
\ No newline at end of file
@@ -128,6 +128,19 @@ describe('Sessions/Permissions', () => {
     expect(resp.data.context).toBe(permissionContext)
   })
 
+  it('sign batch rejects an empty userOps list', async () => {
+    const payload = {
+      pci: new_pci,
+      userOps: [],
+    }
+
+    const resp = await httpClient.post(
+      `${baseUrl}/v1/sessions/${address}/sign-batch?projectId=${projectId}`,
+      payload,
+    )
+    expect(resp.status).toBe(400)
+  })
+
   it('revoke PCI permission', async () => {
     // Check PCI is exists
     let resp = await httpClient.get(
@@ -158,4 +171,42 @@ describe('Sessions/Permissions', () => {
     // Check revokedAt is fullfilled
     expect(typeof resp.data.pcis[0].revokedAt).toBe('number')
   })
+
+  it('revoke PCI permission by the account owner signature', async () => {
+    const wallet = ethers.Wallet.createRandom()
+    const ownerAddress = `eip155:1:${wallet.address}`
+    const permission = {
+      expiry: Math.floor(Date.now() / 1000) + 3600,
+      signer: {
+        type: 'k256',
+        data: '0x',
+      },
+      permissions: [contractCallPermission],
+      policies: [],
+    }
+    let resp = await httpClient.post(
+      `${baseUrl}/v1/sessions/${ownerAddress}?projectId=${projectId}`,
+      permission,
+    )
+    expect(resp.status).toBe(200)
+    const pci = resp.data.pci
+
+    const timestamp = Math.floor(Date.now() / 1000)
+    const message = `Revoke session permission\nAddress: ${ownerAddress}\nPCI: ${pci}\nTimestamp: ${timestamp}`
+
+    // Signature from another account is rejected
+    const wrongSignature = await ethers.Wallet.createRandom().signMessage(message)
+    resp = await httpClient.post(
+      `${baseUrl}/v1/sessions/${ownerAddress}/revoke?projectId=${projectId}`,
+      { pci, signature: wrongSignature, timestamp },
+    )
+    expect(resp.status).toBe(401)
+
+    const signature = await wallet.signMessage(message)
+    resp = await httpClient.post(
+      `${baseUrl}/v1/sessions/${ownerAddress}/revoke?projectId=${projectId}`,
+      { pci, signature, timestamp },
+    )
+    expect(resp.status).toBe(200)
+  })
 })
@@ -0,0 +1,107 @@
+import { getTestSetup } from './init'
+import { ethers } from 'ethers'
+
+describe('Subscriptions', () => {
+  const { baseUrl, projectId, httpClient } = getTestSetup()
+  const account = `eip155:1:${ethers.Wallet.createRandom().address}`
+  const recipient = `eip155:1:${ethers.Wallet.createRandom().address}`
+  const asset = 'eip155:1/slip44:60'
+  let pci: string
+  let subscriptionId: string
+
+  it('create subscription', async () => {
+    let resp = await httpClient.post(
+      `${baseUrl}/v1/sessions/${account}?projectId=${projectId}`,
+      {
+        expiry: Math.floor(Date.now() / 1000) + 86400,
+        signer: {
+          type: 'k256',
+          data: '0x',
+        },
+        permissions: [
+          {
+            type: 'contract-call',
+            data: {
+              address: '0x2E65BAfA07238666c3b239E94F32DaD3cDD6498D',
+            },
+          },
+        ],
+        policies: [],
+      },
+    )
+    expect(resp.status).toBe(200)
+    pci = resp.data.pci
+
+    const startAt = Math.floor(Date.now() / 1000) + 3600
+    resp = await httpClient.post(
+      `${baseUrl}/v1/subscriptions?projectId=${projectId}`,
+      {
+        account,
+        pci,
+        asset,
+        amount: '0.0001',
+        recipient,
+        interval: 86400,
+        startAt,
+        maxCharges: 12,
+      },
+    )
+    expect(resp.status).toBe(200)
+    expect(typeof resp.data.id).toBe('string')
+    expect(resp.data.status).toBe('active')
+    expect(resp.data.nextChargeAt).toBe(startAt)
+    expect(resp.data.chargesCount).toBe(0)
+    subscriptionId = resp.data.id
+  })
+
+  it('reject subscription with too short interval', async () => {
+    const resp = await httpClient.post(
+      `${baseUrl}/v1/subscriptions?projectId=${projectId}`,
+      {
+        account,
+        pci,
+        asset,
+        amount: '0.0001',
+        recipient,
+        interval: 60,
+      },
+    )
+    expect(resp.status).toBe(400)
+  })
+
+  it('get subscription and charges', async () => {
+    let resp = await httpClient.get(
+      `${baseUrl}/v1/subscriptions/${subscriptionId}?projectId=${projectId}`,
+    )
+    expect(resp.status).toBe(200)
+    expect(resp.data.id).toBe(subscriptionId)
+    expect(resp.data.pci).toBe(pci)
+
+    resp = await httpClient.get(
+      `${baseUrl}/v1/subscriptions/${subscriptionId}/charges?projectId=${projectId}`,
+    )
+    expect(resp.status).toBe(200)
+    expect(resp.data.charges).toEqual([])
+  })
+
+  it('cancel subscription', async () => {
+    let resp = await httpClient.post(
+      `${baseUrl}/v1/subscriptions/${subscriptionId}/cancel?projectId=${projectId}`,
+    )
+    expect(resp.status).toBe(200)
+    expect(resp.data.status).toBe('cancelled')
+    expect(typeof resp.data.cancelledAt).toBe('number')
+
+    resp = await httpClient.post(
+      `${baseUrl}/v1/subscriptions/${subscriptionId}/cancel?projectId=${projectId}`,
+    )
+    expect(resp.status).toBe(400)
+  })
+
+  it('unknown subscription', async () => {
+    const resp = await httpClient.get(
+      `${baseUrl}/v1/subscriptions/00000000-0000-4000-8000-000000000000?projectId=${projectId}`,
+    )
+    expect(resp.status).toBe(404)
+  })
+})
@@ -16,11 +16,17 @@ export interface JsonRpcError {
   data?: unknown;
 }
 
+export interface IntentPrice {
+  amount: string;
+  currency: string;
+}
+
 export interface PaymentIntent {
   asset: string;
-  amount: string;
+  amount?: string;
   recipient: string;
   sender: string;
+  price?: IntentPrice;
 }
 
 export interface BuildTransactionParams {
@@ -106,6 +112,16 @@ export type TransactionRpc = EvmTransactionRpc | SolanaTransactionRpc | TronTran
 
 export interface BuildTransactionResult {
   transactions: TransactionRpc[];
+  quotes?: PosQuote[];
+}
+
+export interface PosQuote {
+  transactionId: string;
+  asset: string;
+  amount: string;
+  price: IntentPrice;
+  assetPrice: number;
+  expiresAt: number;
 }
 
 export interface CheckTransactionResult {
@@ -0,0 +1,30 @@
+-- Per-project paymaster sponsorship policies
+CREATE TABLE sponsorship_policies (
+  project_id VARCHAR(255) PRIMARY KEY,
+
+  -- Maximum total gas limit of the sponsored user operation
+  max_gas_per_op BIGINT,
+  -- CAIP-2 chain IDs allowed for the sponsorship, NULL allows all chains
+  allowed_chains TEXT[],
+  -- Lowercased contract addresses allowed to be called, NULL allows all contracts
+  allowed_contracts TEXT[],
+  -- Monthly sponsored gas budget, NULL means unlimited
+  monthly_gas_budget BIGINT,
+
+  created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+  updated_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+-- Sponsored user operations spend tracking per project, month and chain
+CREATE TABLE sponsorship_usage (
+  project_id VARCHAR(255) NOT NULL,
+  period DATE NOT NULL,
+  chain_id VARCHAR(255) NOT NULL,
+
+  sponsored_ops BIGINT NOT NULL DEFAULT 0,
+  sponsored_gas BIGINT NOT NULL DEFAULT 0,
+
+  updated_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+
+  PRIMARY KEY (project_id, period, chain_id)
+);
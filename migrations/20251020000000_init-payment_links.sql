@@ -0,0 +1,26 @@
+-- Persistent pos payment intents shared as the payment links
+CREATE TABLE payment_links (
+  -- Short random ID used in the shareable payment URL
+  id VARCHAR(32) PRIMARY KEY,
+  project_id VARCHAR(255) NOT NULL,
+
+  -- CAIP-19 asset ID
+  asset VARCHAR(255) NOT NULL,
+  -- Amount in the asset units (not the smallest denomination)
+  amount VARCHAR(78) NOT NULL,
+  -- CAIP-10 recipient account
+  recipient VARCHAR(255) NOT NULL,
+  -- NULL means the payment link never expires
+  expires_at TIMESTAMPTZ,
+
+  -- The latest built pos transaction ID used to track the fulfillment
+  transaction_id TEXT,
+  -- The confirmed transaction hash, set when the payment is fulfilled
+  txid TEXT,
+  paid_at TIMESTAMPTZ,
+
+  created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+  updated_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX payment_links_project_id_idx ON payment_links (project_id);
@@ -0,0 +1,19 @@
+-- Payment intents of the built pos transactions used to reconcile
+-- the on-chain transferred amount when checking the transaction
+CREATE TABLE pos_payment_intents (
+  -- Encoded pos transaction ID returned by the build transactions method
+  transaction_id TEXT PRIMARY KEY,
+  project_id VARCHAR(255) NOT NULL,
+
+  -- CAIP-19 asset ID
+  asset VARCHAR(255) NOT NULL,
+  -- Amount in the asset units (not the smallest denomination)
+  amount VARCHAR(78) NOT NULL,
+  -- CAIP-10 recipient and sender accounts
+  recipient VARCHAR(255) NOT NULL,
+  sender VARCHAR(255) NOT NULL,
+
+  created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX pos_payment_intents_project_id_idx ON pos_payment_intents (project_id);
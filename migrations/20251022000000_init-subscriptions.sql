@@ -0,0 +1,59 @@
+-- Status enums for the recurring payments
+CREATE TYPE subscription_status AS ENUM ('active', 'cancelled', 'completed');
+CREATE TYPE subscription_charge_status AS ENUM ('submitted', 'failed');
+
+-- Recurring payments charged by the granted smart session permission
+CREATE TABLE subscriptions (
+  id CHAR(36) PRIMARY KEY,
+  project_id VARCHAR(255) NOT NULL,
+
+  -- CAIP-10 smart account that granted the session permission
+  account VARCHAR(255) NOT NULL,
+  -- Permission control identifier of the granted session permission
+  pci VARCHAR(255) NOT NULL,
+
+  -- CAIP-19 asset ID
+  asset VARCHAR(255) NOT NULL,
+  -- Amount charged every period in the asset units (not the smallest denomination)
+  amount VARCHAR(78) NOT NULL,
+  -- CAIP-10 recipient account
+  recipient VARCHAR(255) NOT NULL,
+
+  -- Charge period in seconds
+  interval_secs BIGINT NOT NULL,
+  next_charge_at TIMESTAMPTZ NOT NULL,
+  -- NULL means the subscription is charged until cancelled
+  max_charges INTEGER,
+  charges_count INTEGER NOT NULL DEFAULT 0,
+
+  status subscription_status NOT NULL DEFAULT 'active',
+  cancelled_at TIMESTAMPTZ,
+
+  created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+  updated_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+
+  locked_at TIMESTAMPTZ
+);
+
+CREATE INDEX subscriptions_project_id_idx ON subscriptions (project_id);
+
+CREATE INDEX subscriptions_active_due_idx
+  ON subscriptions (next_charge_at)
+  WHERE status = 'active';
+
+-- History of the subscription charges, one charge per the scheduled period
+CREATE TABLE subscription_charges (
+  id BIGSERIAL PRIMARY KEY,
+  subscription_id CHAR(36) NOT NULL REFERENCES subscriptions (id) ON DELETE CASCADE,
+  -- Scheduled time of the charged period
+  period_at TIMESTAMPTZ NOT NULL,
+
+  status subscription_charge_status NOT NULL,
+  -- Call ID of the submitted user operation
+  call_id TEXT,
+  failure_reason TEXT,
+
+  created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+
+  UNIQUE (subscription_id, period_at)
+);
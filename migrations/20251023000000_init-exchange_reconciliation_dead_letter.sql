@@ -0,0 +1,6 @@
+-- Transactions never resolved by the reconciler are moved to the dead-letter state
+ALTER TYPE exchange_transaction_status ADD VALUE IF NOT EXISTS 'dead_letter';
+
+-- Number of the reconciler status checks
+ALTER TABLE exchange_reconciliation_ledger
+  ADD COLUMN check_attempts INTEGER NOT NULL DEFAULT 0;
@@ -0,0 +1,7 @@
+-- Direction of the exchange transaction, funding the wallet from the exchange
+-- or depositing the wallet funds back to the exchange
+CREATE TYPE exchange_transaction_direction AS ENUM ('buy', 'deposit');
+
+ALTER TABLE exchange_reconciliation_ledger
+  ADD COLUMN direction exchange_transaction_direction NOT NULL DEFAULT 'buy',
+  ADD COLUMN sender VARCHAR(255);
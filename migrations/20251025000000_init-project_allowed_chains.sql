@@ -0,0 +1,11 @@
+-- Per-project chains allowlist, projects without entries are allowed to
+-- access all chains
+CREATE TABLE project_allowed_chains (
+  project_id VARCHAR(255) NOT NULL,
+  -- CAIP-2 chain ID
+  chain_id VARCHAR(255) NOT NULL,
+
+  created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+
+  PRIMARY KEY (project_id, chain_id)
+);
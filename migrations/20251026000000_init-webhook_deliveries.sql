@@ -0,0 +1,31 @@
+CREATE TYPE webhook_delivery_status AS ENUM ('pending', 'delivered', 'failed');
+
+-- Outbound webhook events queue, the deliveries are retried with the backoff
+-- until delivered or the maximum attempts are reached
+CREATE TABLE webhook_deliveries (
+  id CHAR(36) PRIMARY KEY,
+  project_id VARCHAR(255) NOT NULL,
+
+  -- Event type, e.g. `onramp.status_changed`
+  event_type VARCHAR(255) NOT NULL,
+  url TEXT NOT NULL,
+  payload JSONB NOT NULL,
+
+  status webhook_delivery_status NOT NULL DEFAULT 'pending',
+  attempts INTEGER NOT NULL DEFAULT 0,
+  next_attempt_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+  last_status_code INTEGER,
+  last_error TEXT,
+  delivered_at TIMESTAMPTZ,
+
+  created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+  updated_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+
+  locked_at TIMESTAMPTZ
+);
+
+CREATE INDEX webhook_deliveries_project_id_idx ON webhook_deliveries (project_id);
+
+CREATE INDEX webhook_deliveries_pending_due_idx
+  ON webhook_deliveries (next_attempt_at)
+  WHERE status = 'pending';
@@ -0,0 +1,11 @@
+-- Per-project source IP ranges allowlist, projects without entries are
+-- allowed to make requests from any IP address
+CREATE TABLE project_allowed_ips (
+  project_id VARCHAR(255) NOT NULL,
+  -- IP address or CIDR range, e.g. `203.0.113.7` or `2001:db8::/32`
+  ip_range CIDR NOT NULL,
+
+  created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+
+  PRIMARY KEY (project_id, ip_range)
+);
@@ -0,0 +1,14 @@
+-- Per-project geo-blocking policy evaluated after the global blocked
+-- countries. Projects with the allowed countries accept the requests only from
+-- these countries, the blocked countries are rejected in addition to the
+-- global ones.
+CREATE TABLE project_countries (
+  project_id VARCHAR(255) NOT NULL,
+  -- ISO 3166-1 alpha-2 country code, e.g. `DE`
+  country VARCHAR(2) NOT NULL,
+  policy VARCHAR(16) NOT NULL CHECK (policy IN ('allowed', 'blocked')),
+
+  created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+
+  PRIMARY KEY (project_id, country)
+);
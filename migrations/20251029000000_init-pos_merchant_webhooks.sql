@@ -0,0 +1,13 @@
+-- Per-project merchant callback URLs notified when the pos payments are
+-- confirmed, projects without the entry are not notified
+CREATE TABLE pos_merchant_webhooks (
+  project_id VARCHAR(255) PRIMARY KEY,
+  -- HTTPS callback URL of the signed webhook events
+  url TEXT NOT NULL,
+
+  created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+-- Confirmation time of the payment intent, set once so the merchant is
+-- notified only on the first confirmed check
+ALTER TABLE pos_payment_intents ADD COLUMN confirmed_at TIMESTAMPTZ;
@@ -0,0 +1,24 @@
+-- Postgres backend of the persistent storage (sessions permissions and the
+-- orchestrations status) used to migrate off the IRN. Expired entries are not
+-- returned and are purged by the sessions GC.
+CREATE TABLE persistent_storage_entries (
+  key TEXT PRIMARY KEY,
+  value BYTEA NOT NULL,
+  expires_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX persistent_storage_entries_expires_at_idx
+  ON persistent_storage_entries (expires_at);
+
+-- Hashmap fields, the expiration is per field the same way as in the IRN
+CREATE TABLE persistent_storage_map_entries (
+  key TEXT NOT NULL,
+  field TEXT NOT NULL,
+  value BYTEA NOT NULL,
+  expires_at TIMESTAMPTZ NOT NULL,
+
+  PRIMARY KEY (key, field)
+);
+
+CREATE INDEX persistent_storage_map_entries_expires_at_idx
+  ON persistent_storage_map_entries (expires_at);
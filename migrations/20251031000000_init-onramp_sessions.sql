@@ -0,0 +1,20 @@
+-- Onramp widget sessions to resume the interrupted purchases and to trace
+-- the specific purchase session by the support
+CREATE TABLE onramp_sessions (
+  -- Random ID returned with the widget URL and passed to the provider as
+  -- the external session ID
+  id VARCHAR(32) PRIMARY KEY,
+  project_id VARCHAR(255) NOT NULL,
+  wallet_address VARCHAR(255) NOT NULL,
+  service_provider VARCHAR(255) NOT NULL,
+
+  -- Selected quote session data the widget was created with
+  session_data JSONB NOT NULL,
+  widget_url TEXT NOT NULL,
+  expires_at TIMESTAMPTZ NOT NULL,
+
+  created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX onramp_sessions_project_id_wallet_address_idx
+  ON onramp_sessions (project_id, wallet_address, created_at DESC);
@@ -0,0 +1,12 @@
+-- Per-project public keys verifying the project JWTs, the tokens are signed by
+-- the project's private key. Projects without keys can't use the project JWTs.
+CREATE TABLE project_jwt_keys (
+  project_id VARCHAR(255) NOT NULL,
+  key_id VARCHAR(255) NOT NULL,
+  -- ES256 (P-256) public key in the PEM format
+  public_key TEXT NOT NULL,
+
+  created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+
+  PRIMARY KEY (project_id, key_id)
+);
@@ -0,0 +1,20 @@
+-- Cumulative spendings of the sessions permissions `spending-limit` policies,
+-- the spendings are reserved by the conditional upsert before the cosigning
+CREATE TABLE sessions_spending_usage (
+  -- CAIP-10 account address
+  address VARCHAR(255) NOT NULL,
+  pci VARCHAR(255) NOT NULL,
+  -- Index of the policy in the permission policies list
+  policy_index INTEGER NOT NULL,
+
+  -- Unix timestamp of the current limit period start
+  period_start BIGINT NOT NULL,
+  -- Spent amount of the token base units
+  spent NUMERIC(78, 0) NOT NULL,
+  -- Permission expiry, the usage is kept for the whole permission lifetime
+  expires_at TIMESTAMPTZ NOT NULL,
+
+  PRIMARY KEY (address, pci, policy_index)
+);
+
+CREATE INDEX sessions_spending_usage_expires_at_idx ON sessions_spending_usage (expires_at);
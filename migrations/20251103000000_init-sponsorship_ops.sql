@@ -0,0 +1,21 @@
+-- Sponsored user operations charged to the project sponsorship usage, the
+-- operation is charged once by its sender and nonce
+CREATE TABLE sponsorship_ops (
+  chain_id VARCHAR(255) NOT NULL,
+  sender VARCHAR(42) NOT NULL,
+  -- Decimal user operation nonce
+  nonce VARCHAR(78) NOT NULL,
+  project_id VARCHAR(255) NOT NULL,
+  -- Usage period the operation is charged to
+  period DATE NOT NULL,
+
+  -- Gas limits of the sponsored operation, replaced by the actual gas used
+  -- when the receipt is settled
+  charged_gas BIGINT NOT NULL,
+  settled BOOLEAN NOT NULL DEFAULT false,
+
+  created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+  updated_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+
+  PRIMARY KEY (chain_id, sender, nonce, project_id)
+);
@@ -4,4 +4,7 @@ use {serde::Deserialize, serde_piecewise_default::DeserializePiecewiseDefault};
 pub struct Config {
     pub s3_endpoint: Option<String>,
     pub export_bucket: Option<String>,
+    /// Newline-delimited JSON export to the `stdout` or the directory path
+    /// when the export bucket is not configured
+    pub local_export: Option<String>,
 }
@@ -0,0 +1,131 @@
+use {
+    serde::Serialize,
+    std::path::{Path, PathBuf},
+    tokio::{
+        fs::{self, File, OpenOptions},
+        io::{self, AsyncWriteExt},
+        sync::mpsc,
+    },
+    tracing::warn,
+    wc::analytics::{CollectionError, Collector},
+};
+
+const STDOUT_TARGET: &str = "stdout";
+
+/// Local analytics export destination for the deployments without AWS
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LocalExportTarget {
+    /// Records are written to the stdout prefixed by the data kind
+    Stdout,
+    /// Records are written to the hourly rotated files in the directory
+    Directory(PathBuf),
+}
+
+impl From<&str> for LocalExportTarget {
+    fn from(value: &str) -> Self {
+        if value.eq_ignore_ascii_case(STDOUT_TARGET) {
+            Self::Stdout
+        } else {
+            Self::Directory(PathBuf::from(value))
+        }
+    }
+}
+
+/// Newline-delimited JSON analytics collector, records are serialized on
+/// collection and written by the background task
+pub struct JsonLinesCollector {
+    sender: mpsc::Sender<String>,
+}
+
+impl JsonLinesCollector {
+    pub fn new(
+        target: LocalExportTarget,
+        export_name: &'static str,
+        queue_capacity: usize,
+    ) -> Self {
+        let (sender, receiver) = mpsc::channel(queue_capacity);
+        tokio::spawn(write_records(target, export_name, receiver));
+        Self { sender }
+    }
+}
+
+impl<T> Collector<T> for JsonLinesCollector
+where
+    T: Serialize + Send + 'static,
+{
+    fn collect(&self, data: T) -> Result<(), CollectionError> {
+        let record = match serde_json::to_string(&data) {
+            Ok(record) => record,
+            Err(err) => {
+                warn!(?err, "failed to serialize analytics record");
+                return Ok(());
+            }
+        };
+        self.sender.try_send(record).map_err(|err| match err {
+            mpsc::error::TrySendError::Full(_) => CollectionError::DataChannelOverflow,
+            mpsc::error::TrySendError::Closed(_) => CollectionError::DataChannelClosed,
+        })
+    }
+}
+
+async fn write_records(
+    target: LocalExportTarget,
+    export_name: &'static str,
+    mut receiver: mpsc::Receiver<String>,
+) {
+    let mut current_file: Option<(String, File)> = None;
+    while let Some(record) = receiver.recv().await {
+        let res = match &target {
+            LocalExportTarget::Stdout => {
+                io::stdout()
+                    .write_all(format!("{{\"{export_name}\":{record}}}\n").as_bytes())
+                    .await
+            }
+            LocalExportTarget::Directory(dir) => {
+                write_to_file(dir, export_name, &mut current_file, &record).await
+            }
+        };
+        if let Err(err) = res {
+            warn!(?err, export_name, "failed to write analytics record");
+        }
+    }
+}
+
+async fn write_to_file(
+    dir: &Path,
+    export_name: &str,
+    current_file: &mut Option<(String, File)>,
+    record: &str,
+) -> io::Result<()> {
+    let file_name = format!(
+        "{export_name}-{}.ndjson",
+        chrono::Utc::now().format("%Y-%m-%d-%H")
+    );
+    let file = match current_file {
+        Some((name, file)) if *name == file_name => file,
+        _ => {
+            fs::create_dir_all(dir).await?;
+            let file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(dir.join(&file_name))
+                .await?;
+            &mut current_file.insert((file_name, file)).1
+        }
+    };
+    file.write_all(format!("{record}\n").as_bytes()).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_target() {
+        assert_eq!(LocalExportTarget::from("stdout"), LocalExportTarget::Stdout);
+        assert_eq!(
+            LocalExportTarget::from("/var/log/analytics"),
+            LocalExportTarget::Directory(PathBuf::from("/var/log/analytics"))
+        );
+    }
+}
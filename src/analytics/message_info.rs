@@ -92,6 +92,7 @@ pub enum MessageSource {
     ProfileAttributesSigValidate,
     ProfileRegisterSigValidate,
     SessionCoSignSigValidate,
+    SessionRevokeSigValidate,
     WalletPrepareCalls,
     WalletSendPreparedCalls,
     WalletGetCallsStatus,
@@ -99,6 +100,9 @@ pub enum MessageSource {
     ChainAgnosticCheck,
     WalletBuildPosTx,
     WalletSendPosTx,
+    TokenMetadata,
+    WalletBuildApproval,
+    ConvertAllowance,
 }
 
 #[cfg(test)]
@@ -128,6 +132,9 @@ mod tests {
         let source = MessageSource::SessionCoSignSigValidate;
         assert_eq!(source.to_string(), "session_co_sign_sig_validate");
 
+        let source = MessageSource::SessionRevokeSigValidate;
+        assert_eq!(source.to_string(), "session_revoke_sig_validate");
+
         let source = MessageSource::ChainAgnosticCheck;
         assert_eq!(source.to_string(), "chain_agnostic_check");
 
@@ -136,6 +143,15 @@ mod tests {
 
         let source = MessageSource::WalletSendPosTx;
         assert_eq!(source.to_string(), "wallet_send_pos_tx");
+
+        let source = MessageSource::TokenMetadata;
+        assert_eq!(source.to_string(), "token_metadata");
+
+        let source = MessageSource::WalletBuildApproval;
+        assert_eq!(source.to_string(), "wallet_build_approval");
+
+        let source = MessageSource::ConvertAllowance;
+        assert_eq!(source.to_string(), "convert_allowance");
     }
 
     #[test]
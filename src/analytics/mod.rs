@@ -8,12 +8,23 @@ pub use {
     exchange_event_info::ExchangeEventInfo,
     history_lookup_info::HistoryLookupInfo,
     identity_lookup_info::IdentityLookupInfo,
+    local_export::LocalExportTarget,
     message_info::*,
     onramp_history_lookup_info::OnrampHistoryLookupInfo,
+    provider_call_info::ProviderCallInfo,
+    rate_limit_info::RateLimitedInfo,
 };
 use {
     aws_sdk_s3::Client as S3Client,
-    std::{net::IpAddr, sync::Arc, time::Duration},
+    serde::Serialize,
+    std::{
+        net::IpAddr,
+        sync::{
+            atomic::{AtomicI64, AtomicU64, Ordering},
+            Arc,
+        },
+        time::Duration,
+    },
     tap::TapFallible,
     tracing::{debug, info},
     wc::{
@@ -34,9 +45,12 @@ mod config;
 pub mod exchange_event_info;
 mod history_lookup_info;
 mod identity_lookup_info;
+mod local_export;
 mod message_info;
 mod onramp_history_lookup_info;
 pub mod pos_info;
+mod provider_call_info;
+mod rate_limit_info;
 
 const ANALYTICS_EXPORT_TIMEOUT: Duration = Duration::from_secs(30);
 const DATA_QUEUE_CAPACITY: usize = 8192;
@@ -52,6 +66,8 @@ enum DataKind {
     ChainAbstraction,
     ExchangeEvents,
     Pos,
+    RateLimits,
+    ProviderCalls,
 }
 
 impl DataKind {
@@ -67,10 +83,32 @@ impl DataKind {
             Self::ChainAbstraction => "chain_abstraction",
             Self::ExchangeEvents => "exchange_events",
             Self::Pos => "pos",
+            Self::RateLimits => "rate_limits",
+            Self::ProviderCalls => "provider_calls",
         }
     }
 }
 
+/// Serialized analytics batches of all the data kinds not exported yet
+static PENDING_BATCHES: AtomicI64 = AtomicI64::new(0);
+/// Failed exports since the last successful export
+static CONSECUTIVE_EXPORT_FAILURES: AtomicU64 = AtomicU64::new(0);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExporterStatus {
+    pub pending_batches: u64,
+    pub consecutive_export_failures: u64,
+}
+
+/// Backlog of the analytics exporter of the instance
+pub fn exporter_status() -> ExporterStatus {
+    ExporterStatus {
+        pending_batches: PENDING_BATCHES.load(Ordering::Relaxed).max(0) as u64,
+        consecutive_export_failures: CONSECUTIVE_EXPORT_FAILURES.load(Ordering::Relaxed),
+    }
+}
+
 #[derive(Clone, Copy)]
 struct Observer(DataKind);
 
@@ -94,6 +132,7 @@ where
                 "failed to serialize analytics batch"
             );
         } else {
+            PENDING_BATCHES.fetch_add(1, Ordering::Relaxed);
             tracing::debug!(
                 size,
                 elapsed,
@@ -138,7 +177,10 @@ where
 
         let elapsed = elapsed.as_millis() as u64;
 
+        // Failed batches are dropped by the exporter
+        PENDING_BATCHES.fetch_sub(1, Ordering::Relaxed);
         if let Err(err) = res {
+            CONSECUTIVE_EXPORT_FAILURES.fetch_add(1, Ordering::Relaxed);
             tracing::warn!(
                 ?err,
                 elapsed,
@@ -146,6 +188,7 @@ where
                 "analytics export failed"
             );
         } else {
+            CONSECUTIVE_EXPORT_FAILURES.store(0, Ordering::Relaxed);
             tracing::debug!(
                 elapsed,
                 data_kind = self.0.as_str(),
@@ -171,6 +214,8 @@ pub struct RPCAnalytics {
     exchange_events: ArcCollector<ExchangeEventInfo>,
     pos_build: ArcCollector<pos_info::PosBuildTxInfo>,
     pos_check: ArcCollector<pos_info::PosCheckTxInfo>,
+    rate_limits: ArcCollector<RateLimitedInfo>,
+    provider_calls: ArcCollector<ProviderCallInfo>,
     geoip_resolver: Option<Arc<MaxMindResolver>>,
 }
 
@@ -183,10 +228,41 @@ impl RPCAnalytics {
     ) -> anyhow::Result<Self> {
         if let Some(export_bucket) = config.export_bucket.as_deref() {
             Self::with_aws_export(s3_client, export_bucket, api_ip, geoip_resolver)
-        } else if config.export_bucket.as_deref().is_none() {
-            Ok(Self::with_noop_export())
+        } else if let Some(local_export) = config.local_export.as_deref() {
+            Ok(Self::with_local_export(local_export.into(), geoip_resolver))
         } else {
-            unreachable!()
+            Ok(Self::with_noop_export())
+        }
+    }
+
+    fn with_local_export(
+        target: LocalExportTarget,
+        geoip_resolver: Option<Arc<MaxMindResolver>>,
+    ) -> Self {
+        info!(?target, "initializing analytics with local export");
+
+        // Export names are the same as for the AWS export
+        let collector = |export_name: &'static str| {
+            local_export::JsonLinesCollector::new(target.clone(), export_name, DATA_QUEUE_CAPACITY)
+        };
+        Self {
+            messages: collector("rpc_requests").boxed_shared(),
+            identity_lookups: collector("identity_lookups").boxed_shared(),
+            history_lookups: collector("history_lookups").boxed_shared(),
+            onramp_history_lookups: collector("onramp-history_lookups").boxed_shared(),
+            balance_lookups: collector("balance_lookups").boxed_shared(),
+            name_registrations: collector("name_registrations").boxed_shared(),
+
+            chain_abstraction_funding: collector("funding_info").boxed_shared(),
+            chain_abstraction_bridging: collector("bridging_info").boxed_shared(),
+            chain_abstraction_initial_tx: collector("initial_tx").boxed_shared(),
+
+            exchange_events: collector("exchange_events").boxed_shared(),
+            pos_build: collector("pos_build").boxed_shared(),
+            pos_check: collector("pos_check").boxed_shared(),
+            rate_limits: collector("rate_limits").boxed_shared(),
+            provider_calls: collector("provider_calls").boxed_shared(),
+            geoip_resolver,
         }
     }
 
@@ -208,6 +284,8 @@ impl RPCAnalytics {
             exchange_events: analytics::noop_collector().boxed_shared(),
             pos_build: analytics::noop_collector().boxed_shared(),
             pos_check: analytics::noop_collector().boxed_shared(),
+            rate_limits: analytics::noop_collector().boxed_shared(),
+            provider_calls: analytics::noop_collector().boxed_shared(),
             geoip_resolver: None,
         }
     }
@@ -468,6 +546,48 @@ impl RPCAnalytics {
         .with_observer(observer)
         .boxed_shared();
 
+        let observer = Observer(DataKind::RateLimits);
+        let rate_limits = BatchCollector::new(
+            CollectorConfig {
+                data_queue_capacity: DATA_QUEUE_CAPACITY,
+                ..Default::default()
+            },
+            ParquetBatchFactory::new(Default::default()).with_observer(observer),
+            AwsExporter::new(AwsConfig {
+                export_prefix: "blockchain-api/rate-limits".to_owned(),
+                export_name: "rate_limits".to_owned(),
+                node_addr,
+                file_extension: "parquet".to_owned(),
+                bucket_name: export_bucket.to_owned(),
+                s3_client: s3_client.clone(),
+                upload_timeout: ANALYTICS_EXPORT_TIMEOUT,
+            })
+            .with_observer(observer),
+        )
+        .with_observer(observer)
+        .boxed_shared();
+
+        let observer = Observer(DataKind::ProviderCalls);
+        let provider_calls = BatchCollector::new(
+            CollectorConfig {
+                data_queue_capacity: DATA_QUEUE_CAPACITY,
+                ..Default::default()
+            },
+            ParquetBatchFactory::new(Default::default()).with_observer(observer),
+            AwsExporter::new(AwsConfig {
+                export_prefix: "blockchain-api/provider-calls".to_owned(),
+                export_name: "provider_calls".to_owned(),
+                node_addr,
+                file_extension: "parquet".to_owned(),
+                bucket_name: export_bucket.to_owned(),
+                s3_client: s3_client.clone(),
+                upload_timeout: ANALYTICS_EXPORT_TIMEOUT,
+            })
+            .with_observer(observer),
+        )
+        .with_observer(observer)
+        .boxed_shared();
+
         Ok(Self {
             messages,
             identity_lookups,
@@ -483,6 +603,8 @@ impl RPCAnalytics {
             exchange_events,
             pos_build,
             pos_check,
+            rate_limits,
+            provider_calls,
             geoip_resolver,
         })
     }
@@ -621,4 +743,24 @@ impl RPCAnalytics {
             );
         }
     }
+
+    pub fn rate_limited(&self, data: RateLimitedInfo) {
+        if let Err(err) = self.rate_limits.collect(data) {
+            tracing::warn!(
+                ?err,
+                data_kind = DataKind::RateLimits.as_str(),
+                "failed to collect analytics for rate limits"
+            );
+        }
+    }
+
+    pub fn provider_call(&self, data: ProviderCallInfo) {
+        if let Err(err) = self.provider_calls.collect(data) {
+            tracing::warn!(
+                ?err,
+                data_kind = DataKind::ProviderCalls.as_str(),
+                "failed to collect analytics for provider calls"
+            );
+        }
+    }
 }
@@ -28,6 +28,9 @@ pub struct OnrampHistoryLookupInfo {
     pub st: Option<String>,
 
     pub request_id: String,
+
+    /// Latest onramp widget session of the address
+    pub onramp_session_id: Option<String>,
 }
 
 impl OnrampHistoryLookupInfo {
@@ -51,6 +54,8 @@ impl OnrampHistoryLookupInfo {
         st: Option<String>,
 
         request_id: String,
+
+        onramp_session_id: Option<String>,
     ) -> Self {
         OnrampHistoryLookupInfo {
             transaction_id,
@@ -72,6 +77,8 @@ impl OnrampHistoryLookupInfo {
             st,
 
             request_id,
+
+            onramp_session_id,
         }
     }
 }
@@ -0,0 +1,48 @@
+use {
+    crate::providers::ProviderKind, parquet_derive::ParquetRecordWriter, serde::Serialize,
+    std::time::Duration,
+};
+
+#[derive(Debug, Clone, Serialize, ParquetRecordWriter)]
+#[serde(rename_all = "camelCase")]
+pub struct ProviderCallInfo {
+    pub timestamp: chrono::NaiveDateTime,
+
+    pub project_id: String,
+    pub chain_id: String,
+    pub provider: String,
+    pub method: String,
+
+    pub latency_secs: f64,
+    /// Provider response status, `None` for the timeouts and connection errors
+    pub status: Option<u16>,
+    pub error: Option<String>,
+    /// Index of the provider in the request's providers retry list
+    pub retry_index: u32,
+}
+
+impl ProviderCallInfo {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        project_id: String,
+        chain_id: String,
+        provider: &ProviderKind,
+        method: String,
+        latency: Duration,
+        status: Option<u16>,
+        error: Option<String>,
+        retry_index: usize,
+    ) -> Self {
+        Self {
+            timestamp: wc::analytics::time::now(),
+            project_id,
+            chain_id,
+            provider: provider.to_string(),
+            method,
+            latency_secs: latency.as_secs_f64(),
+            status,
+            error,
+            retry_index: retry_index as u32,
+        }
+    }
+}
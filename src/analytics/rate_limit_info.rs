@@ -0,0 +1,24 @@
+use {parquet_derive::ParquetRecordWriter, serde::Serialize};
+
+#[derive(Debug, Clone, Serialize, ParquetRecordWriter)]
+#[serde(rename_all = "camelCase")]
+pub struct RateLimitedInfo {
+    pub timestamp: chrono::NaiveDateTime,
+
+    pub project_id: String,
+    pub endpoint: String,
+    pub ip: String,
+    pub bucket: String,
+}
+
+impl RateLimitedInfo {
+    pub fn new(project_id: String, endpoint: String, ip: String, bucket: String) -> Self {
+        Self {
+            timestamp: wc::analytics::time::now(),
+            project_id,
+            endpoint,
+            ip,
+            bucket,
+        }
+    }
+}
@@ -0,0 +1,226 @@
+//! Quarantine of the chains with all providers failing.
+
+use {
+    crate::{
+        state::AppState,
+        validate_config::{check, ping_method, ping_provider},
+    },
+    chrono::{DateTime, Utc},
+    futures_util::future::join_all,
+    serde::Serialize,
+    std::{
+        collections::HashMap,
+        sync::{Arc, Mutex},
+        time::Duration,
+    },
+    tokio::time::{interval, MissedTickBehavior},
+    tracing::{debug, info, warn},
+};
+
+/// Providers of the chain probed by the single probe
+const MAX_PROBED_PROVIDERS: usize = 5;
+/// Timeout of the every single provider probe
+const PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QuarantinedChain {
+    pub chain_id: String,
+    pub since: DateTime<Utc>,
+}
+
+#[derive(Debug, Default)]
+struct ChainState {
+    consecutive_failures: u32,
+    quarantined_since: Option<DateTime<Utc>>,
+}
+
+/// Consecutive failed requests and the quarantine state by the chain
+#[derive(Debug)]
+pub struct ChainQuarantine {
+    /// Consecutive requests failed by all providers to quarantine the chain,
+    /// the chains are never quarantined when zero
+    threshold: u32,
+    chains: Mutex<HashMap<String, ChainState>>,
+}
+
+impl ChainQuarantine {
+    pub fn new(threshold: u32) -> Self {
+        Self {
+            threshold,
+            chains: Mutex::default(),
+        }
+    }
+
+    pub fn is_quarantined(&self, chain_id: &str) -> bool {
+        self.chains.lock().is_ok_and(|chains| {
+            chains
+                .get(chain_id)
+                .is_some_and(|chain| chain.quarantined_since.is_some())
+        })
+    }
+
+    /// Records the request failed by all providers, returns `true` when the
+    /// chain is quarantined by the failure
+    pub fn record_failure(&self, chain_id: &str) -> bool {
+        if self.threshold == 0 {
+            return false;
+        }
+        let Ok(mut chains) = self.chains.lock() else {
+            return false;
+        };
+        let chain = chains.entry(chain_id.to_owned()).or_default();
+        chain.consecutive_failures = chain.consecutive_failures.saturating_add(1);
+        if chain.quarantined_since.is_some() || chain.consecutive_failures < self.threshold {
+            return false;
+        }
+        chain.quarantined_since = Some(Utc::now());
+        true
+    }
+
+    /// Resets the consecutive failures of the chain by the successful request
+    pub fn record_success(&self, chain_id: &str) {
+        if let Ok(mut chains) = self.chains.lock() {
+            if chains
+                .get(chain_id)
+                .is_some_and(|chain| chain.quarantined_since.is_none())
+            {
+                chains.remove(chain_id);
+            }
+        }
+    }
+
+    /// Releases the quarantined chain, returns `false` when the chain is not
+    /// quarantined
+    pub fn release(&self, chain_id: &str) -> bool {
+        self.chains.lock().is_ok_and(|mut chains| {
+            chains
+                .remove(chain_id)
+                .is_some_and(|chain| chain.quarantined_since.is_some())
+        })
+    }
+
+    pub fn quarantined(&self) -> Vec<QuarantinedChain> {
+        let mut quarantined = self
+            .chains
+            .lock()
+            .map(|chains| {
+                chains
+                    .iter()
+                    .filter_map(|(chain_id, chain)| {
+                        chain.quarantined_since.map(|since| QuarantinedChain {
+                            chain_id: chain_id.clone(),
+                            since,
+                        })
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+        quarantined.sort_by(|a, b| a.chain_id.cmp(&b.chain_id));
+        quarantined
+    }
+}
+
+/// Probes the quarantined chains by the interval until the shutdown
+pub async fn run(state: Arc<AppState>, probe_interval: Duration) {
+    debug!("starting chain quarantine probes");
+    let mut probe_interval = interval(probe_interval);
+    probe_interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    loop {
+        probe_interval.tick().await;
+        let quarantined = state.chain_quarantine.quarantined();
+        join_all(
+            quarantined
+                .iter()
+                .map(|chain| probe(&state, &chain.chain_id)),
+        )
+        .await;
+    }
+}
+
+/// Releases the chain when any of its providers responds to the cheap RPC
+/// method. Chains of the namespaces without the known cheap method are
+/// released on the first probe for the proxy requests to probe them.
+async fn probe(state: &AppState, chain_id: &str) {
+    let namespace = chain_id.split(':').next().unwrap_or_default();
+    let Some(method) = ping_method(namespace) else {
+        release(state, chain_id);
+        return;
+    };
+
+    let providers = state.providers();
+    let providers =
+        match providers.get_rpc_provider_for_chain_id(chain_id, None, MAX_PROBED_PROVIDERS) {
+            Ok(providers) => providers,
+            Err(e) => {
+                warn!("Failed to get the providers to probe the chain {chain_id}: {e}");
+                return;
+            }
+        };
+    for provider in providers {
+        let provider_kind = provider.provider_kind();
+        let result = check(
+            &provider_kind.to_string(),
+            PROBE_TIMEOUT,
+            ping_provider(provider, chain_id, method),
+        )
+        .await;
+        if result.ok {
+            release(state, chain_id);
+            return;
+        }
+        debug!(
+            "Quarantined chain {chain_id} probe by {provider_kind} failed: {:?}",
+            result.error
+        );
+    }
+}
+
+fn release(state: &AppState, chain_id: &str) {
+    if state.chain_quarantine.release(chain_id) {
+        info!("Chain {chain_id} is released from the quarantine");
+        state
+            .metrics
+            .record_chain_quarantined(chain_id.to_owned(), false);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quarantines_after_consecutive_failures() {
+        let quarantine = ChainQuarantine::new(3);
+
+        assert!(!quarantine.record_failure("eip155:1"));
+        assert!(!quarantine.record_failure("eip155:1"));
+        // The consecutive failures are reset by the success
+        quarantine.record_success("eip155:1");
+        assert!(!quarantine.record_failure("eip155:1"));
+        assert!(!quarantine.record_failure("eip155:1"));
+        assert!(!quarantine.is_quarantined("eip155:1"));
+
+        assert!(quarantine.record_failure("eip155:1"));
+        assert!(!quarantine.record_failure("eip155:1"));
+        assert!(quarantine.is_quarantined("eip155:1"));
+        assert!(!quarantine.is_quarantined("eip155:10"));
+        assert_eq!(quarantine.quarantined()[0].chain_id, "eip155:1");
+
+        // Only the probe releases the quarantined chain
+        quarantine.record_success("eip155:1");
+        assert!(quarantine.is_quarantined("eip155:1"));
+        assert!(quarantine.release("eip155:1"));
+        assert!(!quarantine.release("eip155:1"));
+        assert!(quarantine.quarantined().is_empty());
+    }
+
+    #[test]
+    fn never_quarantines_with_zero_threshold() {
+        let quarantine = ChainQuarantine::new(0);
+        for _ in 0..100 {
+            assert!(!quarantine.record_failure("eip155:1"));
+        }
+        assert!(!quarantine.is_quarantined("eip155:1"));
+    }
+}
@@ -14,6 +14,18 @@ pub enum TxStatus {
     Pending,
     Succeeded,
     Failed,
+    /// Never resolved by the reconciler
+    #[sqlx(rename = "dead_letter")]
+    DeadLetter,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, sqlx::Type)]
+#[sqlx(type_name = "exchange_transaction_direction", rename_all = "lowercase")]
+pub enum TxDirection {
+    /// Funding the wallet from the exchange
+    Buy,
+    /// Depositing the wallet funds to the exchange
+    Deposit,
 }
 
 #[derive(Debug, FromRow, Clone)]
@@ -34,6 +46,8 @@ pub struct ExchangeTransaction {
     pub last_checked_at: Option<DateTime<Utc>>,
     pub completed_at: Option<DateTime<Utc>>,
     pub locked_at: Option<DateTime<Utc>>,
+    pub direction: TxDirection,
+    pub sender: Option<String>,
 }
 
 pub struct NewExchangeTransaction<'a> {
@@ -44,6 +58,8 @@ pub struct NewExchangeTransaction<'a> {
     pub amount: Option<f64>,
     pub recipient: Option<&'a str>,
     pub pay_url: Option<&'a str>,
+    pub direction: TxDirection,
+    pub sender: Option<&'a str>,
 }
 
 pub async fn insert_new(
@@ -52,10 +68,11 @@ pub async fn insert_new(
 ) -> Result<ExchangeTransaction, DatabaseError> {
     let query = r#"
         INSERT INTO exchange_reconciliation_ledger
-            (session_id, exchange_id, project_id, asset, amount, recipient, pay_url)
-        VALUES ($1, $2, $3, $4, $5, $6, $7)
+            (session_id, exchange_id, project_id, asset, amount, recipient, pay_url, direction, sender)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
         RETURNING id, session_id, exchange_id, project_id, asset, amount, recipient, pay_url, status,
-                  failure_reason, tx_hash, created_at, updated_at, last_checked_at, completed_at, locked_at
+                  failure_reason, tx_hash, created_at, updated_at, last_checked_at, completed_at, locked_at,
+                  direction, sender
     "#;
 
     let row = sqlx::query_as::<Postgres, ExchangeTransaction>(query)
@@ -66,6 +83,8 @@ pub async fn insert_new(
         .bind(tx.amount)
         .bind(tx.recipient)
         .bind(tx.pay_url)
+        .bind(tx.direction)
+        .bind(tx.sender)
         .fetch_one(executor)
         .await?;
     Ok(row)
@@ -93,7 +112,8 @@ pub async fn update_status(
             locked_at = NULL
         WHERE session_id = $1
         RETURNING id, session_id, exchange_id, project_id, asset, amount, recipient, pay_url, status,
-                  failure_reason, tx_hash, created_at, updated_at, last_checked_at, completed_at, locked_at
+                  failure_reason, tx_hash, created_at, updated_at, last_checked_at, completed_at, locked_at,
+                  direction, sender
     "#;
 
     let row = sqlx::query_as::<Postgres, ExchangeTransaction>(query)
@@ -124,6 +144,46 @@ pub async fn touch_non_terminal(
     Ok(())
 }
 
+pub async fn get_by_session_id(
+    executor: impl PgExecutor<'_>,
+    session_id: &str,
+) -> Result<Option<ExchangeTransaction>, DatabaseError> {
+    let query = r#"
+        SELECT id, session_id, exchange_id, project_id, asset, amount, recipient, pay_url, status,
+               failure_reason, tx_hash, created_at, updated_at, last_checked_at, completed_at, locked_at,
+               direction, sender
+        FROM exchange_reconciliation_ledger
+        WHERE session_id = $1
+    "#;
+
+    let row = sqlx::query_as::<Postgres, ExchangeTransaction>(query)
+        .bind(session_id)
+        .fetch_optional(executor)
+        .await?;
+    Ok(row)
+}
+
+/// Set the exchange deposit address as the recipient of the deposit
+/// transaction
+pub async fn set_recipient(
+    executor: impl PgExecutor<'_>,
+    session_id: &str,
+    recipient: &str,
+) -> Result<(), DatabaseError> {
+    let query = r#"
+        UPDATE exchange_reconciliation_ledger SET
+            recipient = $2,
+            updated_at = NOW()
+        WHERE session_id = $1
+    "#;
+    sqlx::query::<Postgres>(query)
+        .bind(session_id)
+        .bind(recipient)
+        .execute(executor)
+        .await?;
+    Ok(())
+}
+
 pub async fn claim_due_batch(
     executor: impl PgExecutor<'_>,
     max_claim: i64,
@@ -140,7 +200,7 @@ pub async fn claim_due_batch(
             FOR UPDATE SKIP LOCKED
         ), claimed AS (
             UPDATE exchange_reconciliation_ledger t
-            SET locked_at = NOW(), updated_at = NOW()
+            SET locked_at = NOW(), updated_at = NOW(), check_attempts = t.check_attempts + 1
             WHERE t.id IN (SELECT id FROM candidates)
             RETURNING t.*
         )
@@ -157,23 +217,30 @@ pub async fn claim_due_batch(
     Ok(rows)
 }
 
-pub async fn expire_old_pending(
+/// Move the pending transactions that are too old or exceeded the maximum
+/// status checks to the dead-letter state
+pub async fn dead_letter_unresolved(
     executor: impl PgExecutor<'_>,
     max_age_hours: i64,
+    max_check_attempts: i32,
 ) -> Result<u64, DatabaseError> {
     let query = r#"
         UPDATE exchange_reconciliation_ledger SET
-            status = 'failed'::exchange_transaction_status,
-            failure_reason = COALESCE(failure_reason, 'expired'),
+            status = 'dead_letter'::exchange_transaction_status,
+            failure_reason = COALESCE(
+                failure_reason,
+                CASE WHEN check_attempts >= $2 THEN 'max_attempts' ELSE 'expired' END
+            ),
             completed_at = NOW(),
             updated_at = NOW()
         WHERE status = 'pending'
-          AND created_at < NOW() - ($1 || ' hours')::INTERVAL
+          AND (created_at < NOW() - ($1 || ' hours')::INTERVAL OR check_attempts >= $2)
           AND (locked_at IS NULL OR locked_at < NOW() - INTERVAL '20 minutes')
     "#;
 
     let res = sqlx::query::<Postgres>(query)
         .bind(max_age_hours)
+        .bind(max_check_attempts)
         .execute(executor)
         .await?;
     Ok(res.rows_affected())
@@ -2,7 +2,7 @@ use {
     crate::database::{error::DatabaseError, types, utils},
     chrono::{DateTime, Utc},
     sqlx::{FromRow, PgPool, Postgres, Row},
-    std::collections::HashMap,
+    std::collections::{HashMap, HashSet},
     tracing::{error, instrument},
 };
 
@@ -14,6 +14,15 @@ struct RowAddress {
     created_at: DateTime<Utc>,
 }
 
+#[derive(sqlx::FromRow)]
+struct RowNameAddress {
+    name: String,
+    namespace: Option<types::SupportedNamespaces>,
+    chain_id: Option<String>,
+    address: Option<String>,
+    created_at: Option<DateTime<Utc>>,
+}
+
 #[derive(Debug, FromRow)]
 pub struct AccountNamesStats {
     pub count: i64,
@@ -111,6 +120,24 @@ pub async fn get_name(name: String, postgres: &PgPool) -> Result<types::Name, sq
         .await
 }
 
+/// Returns the registered names out of the given names in a single query
+#[instrument(skip(postgres))]
+pub async fn get_registered_names(
+    names: &[String],
+    postgres: &PgPool,
+) -> Result<HashSet<String>, sqlx::error::Error> {
+    let query = "
+      SELECT name
+        FROM names
+          WHERE name = ANY($1)
+    ";
+    let registered = sqlx::query_scalar::<Postgres, String>(query)
+        .bind(names)
+        .fetch_all(postgres)
+        .await?;
+    Ok(registered.into_iter().collect())
+}
+
 #[instrument(skip(postgres))]
 pub async fn get_names_by_address(
     address: String,
@@ -171,6 +198,48 @@ pub async fn get_addresses_by_name(
     Ok(result_map)
 }
 
+/// Returns the addresses of the registered names out of the given names in a
+/// single query, the names without addresses are mapped to the empty maps
+#[instrument(skip(postgres))]
+pub async fn get_addresses_by_names(
+    names: &[String],
+    postgres: &PgPool,
+) -> Result<HashMap<String, types::ENSIP11AddressesMap>, sqlx::error::Error> {
+    let query = "
+      SELECT n.name, a.namespace, a.chain_id, a.address, a.created_at
+        FROM names n
+        LEFT JOIN addresses a ON n.name = a.name
+          WHERE n.name = ANY($1)
+    ";
+    let rows_result = sqlx::query_as::<Postgres, RowNameAddress>(query)
+        .bind(names)
+        .fetch_all(postgres)
+        .await?;
+
+    let mut result = HashMap::<String, types::ENSIP11AddressesMap>::new();
+    for row in rows_result {
+        let addresses = result.entry(row.name).or_default();
+        let (Some(namespace), Some(chain_id), Some(address)) =
+            (row.namespace, row.chain_id, row.address)
+        else {
+            continue;
+        };
+        if namespace != types::SupportedNamespaces::Eip155 {
+            error!("Unsupported namespace: {namespace:?}");
+            continue;
+        }
+        addresses.insert(
+            chain_id.parse::<u32>().unwrap_or_default(),
+            types::Address {
+                address,
+                created_at: row.created_at,
+            },
+        );
+    }
+
+    Ok(result)
+}
+
 #[instrument(skip(postgres))]
 pub async fn get_names_by_address_and_namespace(
     address: String,
@@ -2,5 +2,16 @@ pub mod config;
 pub mod error;
 pub mod exchange_reconciliation;
 pub mod helpers;
+pub mod onramp_sessions;
+pub mod payment_links;
+pub mod pos_payment_intents;
+pub mod project_chains;
+pub mod project_countries;
+pub mod project_ips;
+pub mod project_jwt_keys;
+pub mod sessions_spending;
+pub mod sponsorship;
+pub mod subscriptions;
 pub mod types;
 pub mod utils;
+pub mod webhooks;
@@ -0,0 +1,89 @@
+use {
+    crate::database::error::DatabaseError,
+    chrono::{DateTime, Utc},
+    sqlx::{types::Json, FromRow, PgExecutor, Postgres},
+};
+
+#[derive(Debug, FromRow, Clone)]
+pub struct OnrampSession {
+    pub id: String,
+    pub project_id: String,
+    pub wallet_address: String,
+    pub service_provider: String,
+    pub session_data: Json<serde_json::Value>,
+    pub widget_url: String,
+    pub expires_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+}
+
+pub struct NewOnrampSession<'a> {
+    pub id: &'a str,
+    pub project_id: &'a str,
+    pub wallet_address: &'a str,
+    pub service_provider: &'a str,
+    pub session_data: serde_json::Value,
+    pub widget_url: &'a str,
+    pub expires_at: DateTime<Utc>,
+}
+
+pub async fn insert_onramp_session(
+    executor: impl PgExecutor<'_>,
+    session: NewOnrampSession<'_>,
+) -> Result<OnrampSession, DatabaseError> {
+    let query = r#"
+        INSERT INTO onramp_sessions
+            (id, project_id, wallet_address, service_provider, session_data, widget_url, expires_at)
+        VALUES ($1, $2, $3, $4, $5, $6, $7)
+        RETURNING id, project_id, wallet_address, service_provider, session_data, widget_url,
+                  expires_at, created_at
+    "#;
+    let row = sqlx::query_as::<Postgres, OnrampSession>(query)
+        .bind(session.id)
+        .bind(session.project_id)
+        .bind(session.wallet_address)
+        .bind(session.service_provider)
+        .bind(Json(session.session_data))
+        .bind(session.widget_url)
+        .bind(session.expires_at)
+        .fetch_one(executor)
+        .await?;
+    Ok(row)
+}
+
+pub async fn get_onramp_session(
+    executor: impl PgExecutor<'_>,
+    id: &str,
+) -> Result<Option<OnrampSession>, DatabaseError> {
+    let query = r#"
+        SELECT id, project_id, wallet_address, service_provider, session_data, widget_url,
+               expires_at, created_at
+        FROM onramp_sessions
+        WHERE id = $1
+    "#;
+    let row = sqlx::query_as::<Postgres, OnrampSession>(query)
+        .bind(id)
+        .fetch_optional(executor)
+        .await?;
+    Ok(row)
+}
+
+/// Latest session ID of the project wallet address
+pub async fn get_latest_session_id(
+    executor: impl PgExecutor<'_>,
+    project_id: &str,
+    wallet_address: &str,
+) -> Result<Option<String>, DatabaseError> {
+    let query = r#"
+        SELECT id
+        FROM onramp_sessions
+        WHERE project_id = $1 AND wallet_address = $2
+        ORDER BY created_at DESC
+        LIMIT 1
+    "#;
+    let id = sqlx::query_scalar::<Postgres, String>(query)
+        .bind(project_id)
+        .bind(wallet_address)
+        .fetch_optional(executor)
+        .await?;
+    Ok(id)
+}
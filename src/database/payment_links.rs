@@ -0,0 +1,107 @@
+use {
+    crate::database::error::DatabaseError,
+    chrono::{DateTime, Utc},
+    sqlx::{FromRow, PgExecutor, Postgres},
+};
+
+#[derive(Debug, FromRow, Clone)]
+pub struct PaymentLink {
+    pub id: String,
+    pub project_id: String,
+    pub asset: String,
+    pub amount: String,
+    pub recipient: String,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub transaction_id: Option<String>,
+    pub txid: Option<String>,
+    pub paid_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+pub struct NewPaymentLink<'a> {
+    pub id: &'a str,
+    pub project_id: &'a str,
+    pub asset: &'a str,
+    pub amount: &'a str,
+    pub recipient: &'a str,
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+pub async fn insert_payment_link(
+    executor: impl PgExecutor<'_>,
+    link: NewPaymentLink<'_>,
+) -> Result<PaymentLink, DatabaseError> {
+    let query = r#"
+        INSERT INTO payment_links (id, project_id, asset, amount, recipient, expires_at)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        RETURNING id, project_id, asset, amount, recipient, expires_at, transaction_id, txid, paid_at,
+                  created_at, updated_at
+    "#;
+    let row = sqlx::query_as::<Postgres, PaymentLink>(query)
+        .bind(link.id)
+        .bind(link.project_id)
+        .bind(link.asset)
+        .bind(link.amount)
+        .bind(link.recipient)
+        .bind(link.expires_at)
+        .fetch_one(executor)
+        .await?;
+    Ok(row)
+}
+
+pub async fn get_payment_link(
+    executor: impl PgExecutor<'_>,
+    id: &str,
+) -> Result<Option<PaymentLink>, DatabaseError> {
+    let query = r#"
+        SELECT id, project_id, asset, amount, recipient, expires_at, transaction_id, txid, paid_at,
+               created_at, updated_at
+        FROM payment_links
+        WHERE id = $1
+    "#;
+    let row = sqlx::query_as::<Postgres, PaymentLink>(query)
+        .bind(id)
+        .fetch_optional(executor)
+        .await?;
+    Ok(row)
+}
+
+/// Set the latest built transaction ID for the not yet paid payment link
+pub async fn set_transaction_id(
+    executor: impl PgExecutor<'_>,
+    id: &str,
+    transaction_id: &str,
+) -> Result<(), DatabaseError> {
+    let query = r#"
+        UPDATE payment_links
+        SET transaction_id = $2, updated_at = NOW()
+        WHERE id = $1 AND paid_at IS NULL
+    "#;
+    sqlx::query::<Postgres>(query)
+        .bind(id)
+        .bind(transaction_id)
+        .execute(executor)
+        .await?;
+    Ok(())
+}
+
+/// Mark the payment link as paid by the confirmed transaction,
+/// returns `false` if the payment link was already paid
+pub async fn mark_paid(
+    executor: impl PgExecutor<'_>,
+    id: &str,
+    txid: Option<&str>,
+) -> Result<bool, DatabaseError> {
+    let query = r#"
+        UPDATE payment_links
+        SET txid = $2, paid_at = NOW(), updated_at = NOW()
+        WHERE id = $1 AND paid_at IS NULL
+    "#;
+    let result = sqlx::query::<Postgres>(query)
+        .bind(id)
+        .bind(txid)
+        .execute(executor)
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
@@ -0,0 +1,135 @@
+use {
+    crate::database::error::DatabaseError,
+    chrono::{DateTime, Utc},
+    sqlx::{FromRow, PgExecutor, Postgres},
+};
+
+#[derive(Debug, FromRow, Clone)]
+pub struct PosPaymentIntent {
+    pub transaction_id: String,
+    pub project_id: String,
+    pub asset: String,
+    pub amount: String,
+    pub recipient: String,
+    pub sender: String,
+    pub created_at: DateTime<Utc>,
+}
+
+pub struct NewPosPaymentIntent<'a> {
+    pub transaction_id: &'a str,
+    pub project_id: &'a str,
+    pub asset: &'a str,
+    pub amount: &'a str,
+    pub recipient: &'a str,
+    pub sender: &'a str,
+}
+
+/// Insert the payment intent of the built transaction,
+/// the existing intent for the same transaction ID is kept
+pub async fn insert_payment_intent(
+    executor: impl PgExecutor<'_>,
+    intent: NewPosPaymentIntent<'_>,
+) -> Result<(), DatabaseError> {
+    let query = r#"
+        INSERT INTO pos_payment_intents (transaction_id, project_id, asset, amount, recipient, sender)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        ON CONFLICT (transaction_id) DO NOTHING
+    "#;
+    sqlx::query::<Postgres>(query)
+        .bind(intent.transaction_id)
+        .bind(intent.project_id)
+        .bind(intent.asset)
+        .bind(intent.amount)
+        .bind(intent.recipient)
+        .bind(intent.sender)
+        .execute(executor)
+        .await?;
+    Ok(())
+}
+
+pub async fn get_payment_intent(
+    executor: impl PgExecutor<'_>,
+    transaction_id: &str,
+) -> Result<Option<PosPaymentIntent>, DatabaseError> {
+    let query = r#"
+        SELECT transaction_id, project_id, asset, amount, recipient, sender, created_at
+        FROM pos_payment_intents
+        WHERE transaction_id = $1
+    "#;
+    let row = sqlx::query_as::<Postgres, PosPaymentIntent>(query)
+        .bind(transaction_id)
+        .fetch_optional(executor)
+        .await?;
+    Ok(row)
+}
+
+/// Mark the payment intent as confirmed, returns `false` when the intent is
+/// unknown or already confirmed
+pub async fn mark_payment_intent_confirmed(
+    executor: impl PgExecutor<'_>,
+    transaction_id: &str,
+) -> Result<bool, DatabaseError> {
+    let query = r#"
+        UPDATE pos_payment_intents
+        SET confirmed_at = NOW()
+        WHERE transaction_id = $1 AND confirmed_at IS NULL
+    "#;
+    let result = sqlx::query::<Postgres>(query)
+        .bind(transaction_id)
+        .execute(executor)
+        .await?;
+    Ok(result.rows_affected() == 1)
+}
+
+/// Get the merchant callback URL of the confirmed payments of the project
+pub async fn get_merchant_webhook_url(
+    executor: impl PgExecutor<'_>,
+    project_id: &str,
+) -> Result<Option<String>, DatabaseError> {
+    let query = r#"
+        SELECT url
+        FROM pos_merchant_webhooks
+        WHERE project_id = $1
+    "#;
+    let url = sqlx::query_scalar::<Postgres, String>(query)
+        .bind(project_id)
+        .fetch_optional(executor)
+        .await?;
+    Ok(url)
+}
+
+/// Set the merchant callback URL of the project, replacing the current one
+pub async fn set_merchant_webhook_url(
+    executor: impl PgExecutor<'_>,
+    project_id: &str,
+    url: &str,
+) -> Result<(), DatabaseError> {
+    let query = r#"
+        INSERT INTO pos_merchant_webhooks (project_id, url)
+        VALUES ($1, $2)
+        ON CONFLICT (project_id) DO UPDATE SET url = EXCLUDED.url
+    "#;
+    sqlx::query::<Postgres>(query)
+        .bind(project_id)
+        .bind(url)
+        .execute(executor)
+        .await?;
+    Ok(())
+}
+
+/// Delete the merchant callback URL of the project, returns `false` when the
+/// URL is not set
+pub async fn delete_merchant_webhook_url(
+    executor: impl PgExecutor<'_>,
+    project_id: &str,
+) -> Result<bool, DatabaseError> {
+    let query = r#"
+        DELETE FROM pos_merchant_webhooks
+        WHERE project_id = $1
+    "#;
+    let result = sqlx::query::<Postgres>(query)
+        .bind(project_id)
+        .execute(executor)
+        .await?;
+    Ok(result.rows_affected() == 1)
+}
@@ -0,0 +1,23 @@
+use {
+    crate::database::error::DatabaseError,
+    sqlx::{PgExecutor, Postgres},
+};
+
+/// Get the CAIP-2 chain IDs the project is restricted to, empty when the
+/// project is allowed to access all chains
+pub async fn get_allowed_chains(
+    executor: impl PgExecutor<'_>,
+    project_id: &str,
+) -> Result<Vec<String>, DatabaseError> {
+    let query = r#"
+        SELECT chain_id
+        FROM project_allowed_chains
+        WHERE project_id = $1
+        ORDER BY chain_id
+    "#;
+    let rows = sqlx::query_scalar::<Postgres, String>(query)
+        .bind(project_id)
+        .fetch_all(executor)
+        .await?;
+    Ok(rows)
+}
@@ -0,0 +1,87 @@
+use {
+    crate::database::error::DatabaseError,
+    sqlx::{PgExecutor, Postgres},
+};
+
+/// Project's countries policy evaluated after the global blocked countries
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProjectCountries {
+    /// Requests are accepted only from these countries when not empty
+    pub allowed: Vec<String>,
+    pub blocked: Vec<String>,
+}
+
+impl ProjectCountries {
+    /// Whether the requests from the country are allowed, the requests with
+    /// the unresolved location are rejected for the projects with the allowed
+    /// countries
+    pub fn is_allowed(&self, country: Option<&str>) -> bool {
+        let contains = |countries: &[String], country: &str| {
+            countries.iter().any(|c| c.eq_ignore_ascii_case(country))
+        };
+        match country {
+            Some(country) => {
+                !contains(&self.blocked, country)
+                    && (self.allowed.is_empty() || contains(&self.allowed, country))
+            }
+            None => self.allowed.is_empty(),
+        }
+    }
+}
+
+/// Get the project's allowed and blocked countries, empty when the project
+/// has no countries policy
+pub async fn get_project_countries(
+    executor: impl PgExecutor<'_>,
+    project_id: &str,
+) -> Result<ProjectCountries, DatabaseError> {
+    let query = r#"
+        SELECT country, policy
+        FROM project_countries
+        WHERE project_id = $1
+        ORDER BY country
+    "#;
+    let rows = sqlx::query_as::<Postgres, (String, String)>(query)
+        .bind(project_id)
+        .fetch_all(executor)
+        .await?;
+    let mut countries = ProjectCountries::default();
+    for (country, policy) in rows {
+        // The column constraint allows only the `allowed` and `blocked`
+        if policy == "allowed" {
+            countries.allowed.push(country);
+        } else {
+            countries.blocked.push(country);
+        }
+    }
+    Ok(countries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn countries_policy() {
+        let no_policy = ProjectCountries::default();
+        assert!(no_policy.is_allowed(Some("DE")));
+        assert!(no_policy.is_allowed(None));
+
+        let blocked = ProjectCountries {
+            allowed: vec![],
+            blocked: vec!["RU".to_owned()],
+        };
+        assert!(!blocked.is_allowed(Some("ru")));
+        assert!(blocked.is_allowed(Some("DE")));
+        assert!(blocked.is_allowed(None));
+
+        let allowed = ProjectCountries {
+            allowed: vec!["DE".to_owned(), "FR".to_owned()],
+            blocked: vec!["FR".to_owned()],
+        };
+        assert!(allowed.is_allowed(Some("DE")));
+        assert!(!allowed.is_allowed(Some("FR")));
+        assert!(!allowed.is_allowed(Some("US")));
+        assert!(!allowed.is_allowed(None));
+    }
+}
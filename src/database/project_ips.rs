@@ -0,0 +1,38 @@
+use {
+    crate::database::error::DatabaseError,
+    ipnet::IpNet,
+    sqlx::{PgExecutor, Postgres},
+    tracing::error,
+};
+
+/// Get the source IP ranges the project is restricted to, empty when the
+/// project is allowed to make requests from any IP address
+pub async fn get_allowed_ip_ranges(
+    executor: impl PgExecutor<'_>,
+    project_id: &str,
+) -> Result<Vec<IpNet>, DatabaseError> {
+    let query = r#"
+        SELECT ip_range::TEXT
+        FROM project_allowed_ips
+        WHERE project_id = $1
+        ORDER BY ip_range
+    "#;
+    let rows = sqlx::query_scalar::<Postgres, String>(query)
+        .bind(project_id)
+        .fetch_all(executor)
+        .await?;
+    // The column type validates the ranges, the CIDR text is always with the
+    // prefix length
+    let ranges = rows
+        .iter()
+        .filter_map(|range| {
+            range
+                .parse::<IpNet>()
+                .map_err(|e| {
+                    error!("Invalid allowed IP range {range} for project {project_id}: {e}")
+                })
+                .ok()
+        })
+        .collect();
+    Ok(ranges)
+}
@@ -0,0 +1,23 @@
+use {
+    crate::database::error::DatabaseError,
+    sqlx::{PgExecutor, Postgres},
+};
+
+/// Get the PEM public keys verifying the project JWTs, several keys are
+/// allowed for the keys rotation
+pub async fn get_public_keys(
+    executor: impl PgExecutor<'_>,
+    project_id: &str,
+) -> Result<Vec<String>, DatabaseError> {
+    let query = r#"
+        SELECT public_key
+        FROM project_jwt_keys
+        WHERE project_id = $1
+        ORDER BY key_id
+    "#;
+    let rows = sqlx::query_scalar::<Postgres, String>(query)
+        .bind(project_id)
+        .fetch_all(executor)
+        .await?;
+    Ok(rows)
+}
@@ -0,0 +1,118 @@
+use {
+    crate::database::error::DatabaseError,
+    sqlx::{PgExecutor, PgPool, Postgres},
+};
+
+/// Spending of the permission `spending-limit` policy to reserve
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpendingReservation {
+    pub policy_index: i32,
+    /// Decimal amounts of the token base units
+    pub amount: String,
+    pub limit: String,
+    /// Limit reset period in seconds, `0` when the limit is for the whole
+    /// permission lifetime
+    pub period: i64,
+}
+
+/// Adds the spendings to the permission policies usage when all of them are
+/// within the limits. The usage is checked and updated by the single
+/// conditional upsert, so the concurrent reservations can't exceed the
+/// limits. Returns `false` and reserves nothing when any limit is exceeded.
+pub async fn reserve_spendings(
+    postgres: &PgPool,
+    address: &str,
+    pci: &str,
+    reservations: &[SpendingReservation],
+    now: i64,
+    expires_at: i64,
+) -> Result<bool, DatabaseError> {
+    let query = r#"
+        INSERT INTO sessions_spending_usage
+            (address, pci, policy_index, period_start, spent, expires_at)
+        SELECT $1, $2, $3, $4, $5::NUMERIC, to_timestamp($8)
+        WHERE $5::NUMERIC <= $6::NUMERIC
+        ON CONFLICT (address, pci, policy_index) DO UPDATE SET
+            period_start = CASE
+                WHEN $7 > 0 AND $4 - sessions_spending_usage.period_start >= $7
+                THEN $4 - ($4 - sessions_spending_usage.period_start) % $7
+                ELSE sessions_spending_usage.period_start
+            END,
+            spent = CASE
+                WHEN $7 > 0 AND $4 - sessions_spending_usage.period_start >= $7 THEN 0
+                ELSE sessions_spending_usage.spent
+            END + EXCLUDED.spent,
+            expires_at = EXCLUDED.expires_at
+        WHERE CASE
+            WHEN $7 > 0 AND $4 - sessions_spending_usage.period_start >= $7 THEN 0
+            ELSE sessions_spending_usage.spent
+        END + EXCLUDED.spent <= $6::NUMERIC
+        RETURNING policy_index
+    "#;
+    let mut transaction = postgres.begin().await?;
+    for reservation in reservations {
+        let reserved = sqlx::query_scalar::<Postgres, i32>(query)
+            .bind(address)
+            .bind(pci)
+            .bind(reservation.policy_index)
+            .bind(now)
+            .bind(&reservation.amount)
+            .bind(&reservation.limit)
+            .bind(reservation.period)
+            .bind(expires_at)
+            .fetch_optional(&mut *transaction)
+            .await?;
+        if reserved.is_none() {
+            transaction.rollback().await?;
+            return Ok(false);
+        }
+    }
+    transaction.commit().await?;
+    Ok(true)
+}
+
+/// Subtracts the spendings reserved at `now` from the permission policies
+/// usage when the request wasn't cosigned. The usage of the limit period
+/// started after the reservation is kept as is.
+pub async fn release_spendings(
+    postgres: &PgPool,
+    address: &str,
+    pci: &str,
+    reservations: &[SpendingReservation],
+    now: i64,
+) -> Result<(), DatabaseError> {
+    let query = r#"
+        UPDATE sessions_spending_usage
+        SET spent = GREATEST(spent - $4::NUMERIC, 0)
+        WHERE address = $1
+            AND pci = $2
+            AND policy_index = $3
+            AND period_start <= $5
+            AND ($6 = 0 OR $5 - period_start < $6)
+    "#;
+    let mut transaction = postgres.begin().await?;
+    for reservation in reservations {
+        sqlx::query::<Postgres>(query)
+            .bind(address)
+            .bind(pci)
+            .bind(reservation.policy_index)
+            .bind(&reservation.amount)
+            .bind(now)
+            .bind(reservation.period)
+            .execute(&mut *transaction)
+            .await?;
+    }
+    transaction.commit().await?;
+    Ok(())
+}
+
+/// Removes the usage of the expired permissions, returns the number of the
+/// removed rows
+pub async fn delete_expired(executor: impl PgExecutor<'_>) -> Result<u64, DatabaseError> {
+    let query = r#"
+        DELETE FROM sessions_spending_usage
+        WHERE expires_at < NOW()
+    "#;
+    let result = sqlx::query::<Postgres>(query).execute(executor).await?;
+    Ok(result.rows_affected())
+}
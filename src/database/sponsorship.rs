@@ -0,0 +1,245 @@
+use {
+    crate::database::error::DatabaseError,
+    chrono::{DateTime, NaiveDate, Utc},
+    sqlx::{FromRow, PgExecutor, PgPool, Postgres},
+};
+
+#[derive(Debug, FromRow, Clone)]
+pub struct SponsorshipPolicy {
+    pub project_id: String,
+    pub max_gas_per_op: Option<i64>,
+    pub allowed_chains: Option<Vec<String>>,
+    pub allowed_contracts: Option<Vec<String>>,
+    pub monthly_gas_budget: Option<i64>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, FromRow, Clone)]
+pub struct SponsorshipUsage {
+    pub project_id: String,
+    pub period: NaiveDate,
+    pub chain_id: String,
+    pub sponsored_ops: i64,
+    pub sponsored_gas: i64,
+    pub updated_at: DateTime<Utc>,
+}
+
+pub async fn get_policy(
+    executor: impl PgExecutor<'_>,
+    project_id: &str,
+) -> Result<Option<SponsorshipPolicy>, DatabaseError> {
+    let query = r#"
+        SELECT project_id, max_gas_per_op, allowed_chains, allowed_contracts,
+               monthly_gas_budget, created_at, updated_at
+        FROM sponsorship_policies
+        WHERE project_id = $1
+    "#;
+    let row = sqlx::query_as::<Postgres, SponsorshipPolicy>(query)
+        .bind(project_id)
+        .fetch_optional(executor)
+        .await?;
+    Ok(row)
+}
+
+/// Get the project sponsorship usage for the period (first day of the month)
+/// for all chains
+pub async fn get_usage(
+    executor: impl PgExecutor<'_>,
+    project_id: &str,
+    period: NaiveDate,
+) -> Result<Vec<SponsorshipUsage>, DatabaseError> {
+    let query = r#"
+        SELECT project_id, period, chain_id, sponsored_ops, sponsored_gas, updated_at
+        FROM sponsorship_usage
+        WHERE project_id = $1 AND period = $2
+        ORDER BY chain_id
+    "#;
+    let rows = sqlx::query_as::<Postgres, SponsorshipUsage>(query)
+        .bind(project_id)
+        .bind(period)
+        .fetch_all(executor)
+        .await?;
+    Ok(rows)
+}
+
+/// Sponsored user operation identified by its sender and nonce
+#[derive(Debug, Clone)]
+pub struct SponsoredOp {
+    pub chain_id: String,
+    /// Lowercased sender address
+    pub sender: String,
+    /// Decimal nonce
+    pub nonce: String,
+}
+
+/// Charge the sponsored user operation gas to the project usage when it's
+/// within the monthly gas budget. The operation is charged once, sponsoring
+/// it again replaces the previously charged gas. The project budget checks
+/// are serialized by the advisory lock, so the concurrent sponsorships can't
+/// exceed the budget. Returns `false` and charges nothing when the budget is
+/// exceeded.
+pub async fn charge_op(
+    postgres: &PgPool,
+    project_id: &str,
+    period: NaiveDate,
+    op: &SponsoredOp,
+    gas: i64,
+    monthly_gas_budget: Option<i64>,
+) -> Result<bool, DatabaseError> {
+    let mut transaction = postgres.begin().await?;
+    sqlx::query::<Postgres>("SELECT pg_advisory_xact_lock(hashtext($1))")
+        .bind(format!("sponsorship/{project_id}"))
+        .execute(&mut *transaction)
+        .await?;
+
+    let query = r#"
+        SELECT period, charged_gas
+        FROM sponsorship_ops
+        WHERE chain_id = $1 AND sender = $2 AND nonce = $3 AND project_id = $4
+        FOR UPDATE
+    "#;
+    let charged = sqlx::query_as::<Postgres, (NaiveDate, i64)>(query)
+        .bind(&op.chain_id)
+        .bind(&op.sender)
+        .bind(&op.nonce)
+        .bind(project_id)
+        .fetch_optional(&mut *transaction)
+        .await?;
+    let (period, ops, gas_delta) = match charged {
+        Some((charged_period, charged_gas)) => (charged_period, 0, gas - charged_gas),
+        None => (period, 1, gas),
+    };
+
+    if let Some(monthly_gas_budget) = monthly_gas_budget {
+        let query = r#"
+            SELECT COALESCE(SUM(sponsored_gas), 0)::BIGINT
+            FROM sponsorship_usage
+            WHERE project_id = $1 AND period = $2
+        "#;
+        let spent = sqlx::query_scalar::<Postgres, i64>(query)
+            .bind(project_id)
+            .bind(period)
+            .fetch_one(&mut *transaction)
+            .await?;
+        if gas_delta > 0 && spent.saturating_add(gas_delta) > monthly_gas_budget {
+            transaction.rollback().await?;
+            return Ok(false);
+        }
+    }
+
+    let query = r#"
+        INSERT INTO sponsorship_ops (chain_id, sender, nonce, project_id, period, charged_gas)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        ON CONFLICT (chain_id, sender, nonce, project_id) DO UPDATE SET
+            charged_gas = EXCLUDED.charged_gas,
+            settled = false,
+            updated_at = NOW()
+    "#;
+    sqlx::query::<Postgres>(query)
+        .bind(&op.chain_id)
+        .bind(&op.sender)
+        .bind(&op.nonce)
+        .bind(project_id)
+        .bind(period)
+        .bind(gas)
+        .execute(&mut *transaction)
+        .await?;
+    add_usage(
+        &mut *transaction,
+        project_id,
+        period,
+        &op.chain_id,
+        ops,
+        gas_delta,
+    )
+    .await?;
+
+    transaction.commit().await?;
+    Ok(true)
+}
+
+/// Replace the charged gas limits of the project's sponsored user operation
+/// with the actual gas used from the receipt. Returns `false` when there is
+/// no unsettled operation charged to the project.
+pub async fn settle_op(
+    postgres: &PgPool,
+    project_id: &str,
+    op: &SponsoredOp,
+    actual_gas: i64,
+) -> Result<bool, DatabaseError> {
+    let mut transaction = postgres.begin().await?;
+    let query = r#"
+        SELECT period, charged_gas
+        FROM sponsorship_ops
+        WHERE chain_id = $1 AND sender = $2 AND nonce = $3 AND project_id = $4
+            AND NOT settled
+        FOR UPDATE
+    "#;
+    let charged = sqlx::query_as::<Postgres, (NaiveDate, i64)>(query)
+        .bind(&op.chain_id)
+        .bind(&op.sender)
+        .bind(&op.nonce)
+        .bind(project_id)
+        .fetch_optional(&mut *transaction)
+        .await?;
+    let Some((period, charged_gas)) = charged else {
+        transaction.rollback().await?;
+        return Ok(false);
+    };
+
+    let query = r#"
+        UPDATE sponsorship_ops
+        SET charged_gas = $5, settled = true, updated_at = NOW()
+        WHERE chain_id = $1 AND sender = $2 AND nonce = $3 AND project_id = $4
+    "#;
+    sqlx::query::<Postgres>(query)
+        .bind(&op.chain_id)
+        .bind(&op.sender)
+        .bind(&op.nonce)
+        .bind(project_id)
+        .bind(actual_gas)
+        .execute(&mut *transaction)
+        .await?;
+    add_usage(
+        &mut *transaction,
+        project_id,
+        period,
+        &op.chain_id,
+        0,
+        actual_gas - charged_gas,
+    )
+    .await?;
+
+    transaction.commit().await?;
+    Ok(true)
+}
+
+/// Add the sponsored user operations and gas to the project usage for the
+/// period, the gas is negative when the charged gas is refunded
+async fn add_usage(
+    executor: impl PgExecutor<'_>,
+    project_id: &str,
+    period: NaiveDate,
+    chain_id: &str,
+    ops: i64,
+    gas: i64,
+) -> Result<(), DatabaseError> {
+    let query = r#"
+        INSERT INTO sponsorship_usage (project_id, period, chain_id, sponsored_ops, sponsored_gas)
+        VALUES ($1, $2, $3, $4, $5)
+        ON CONFLICT (project_id, period, chain_id) DO UPDATE SET
+            sponsored_ops = sponsorship_usage.sponsored_ops + EXCLUDED.sponsored_ops,
+            sponsored_gas = sponsorship_usage.sponsored_gas + EXCLUDED.sponsored_gas,
+            updated_at = NOW()
+    "#;
+    sqlx::query::<Postgres>(query)
+        .bind(project_id)
+        .bind(period)
+        .bind(chain_id)
+        .bind(ops)
+        .bind(gas)
+        .execute(executor)
+        .await?;
+    Ok(())
+}
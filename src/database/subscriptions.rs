@@ -0,0 +1,242 @@
+use {
+    crate::database::error::DatabaseError,
+    chrono::{DateTime, Utc},
+    serde::Serialize,
+    sqlx::{FromRow, PgExecutor, Postgres},
+};
+
+const LOCK_EXPIRATION_MINUTES: i32 = 15;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, sqlx::Type)]
+#[serde(rename_all = "lowercase")]
+#[sqlx(type_name = "subscription_status", rename_all = "lowercase")]
+pub enum SubscriptionStatus {
+    Active,
+    Cancelled,
+    Completed,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, sqlx::Type)]
+#[serde(rename_all = "lowercase")]
+#[sqlx(type_name = "subscription_charge_status", rename_all = "lowercase")]
+pub enum ChargeStatus {
+    Submitted,
+    Failed,
+}
+
+#[derive(Debug, FromRow, Clone)]
+pub struct Subscription {
+    pub id: String,
+    pub project_id: String,
+    pub account: String,
+    pub pci: String,
+    pub asset: String,
+    pub amount: String,
+    pub recipient: String,
+    pub interval_secs: i64,
+    pub next_charge_at: DateTime<Utc>,
+    pub max_charges: Option<i32>,
+    pub charges_count: i32,
+    pub status: SubscriptionStatus,
+    pub cancelled_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, FromRow, Clone)]
+pub struct SubscriptionCharge {
+    pub id: i64,
+    pub subscription_id: String,
+    pub period_at: DateTime<Utc>,
+    pub status: ChargeStatus,
+    pub call_id: Option<String>,
+    pub failure_reason: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+pub struct NewSubscription<'a> {
+    pub id: &'a str,
+    pub project_id: &'a str,
+    pub account: &'a str,
+    pub pci: &'a str,
+    pub asset: &'a str,
+    pub amount: &'a str,
+    pub recipient: &'a str,
+    pub interval_secs: i64,
+    pub next_charge_at: DateTime<Utc>,
+    pub max_charges: Option<i32>,
+}
+
+pub struct NewSubscriptionCharge<'a> {
+    pub subscription_id: &'a str,
+    pub period_at: DateTime<Utc>,
+    pub status: ChargeStatus,
+    pub call_id: Option<&'a str>,
+    pub failure_reason: Option<&'a str>,
+}
+
+pub async fn insert_subscription(
+    executor: impl PgExecutor<'_>,
+    subscription: NewSubscription<'_>,
+) -> Result<Subscription, DatabaseError> {
+    let query = r#"
+        INSERT INTO subscriptions
+            (id, project_id, account, pci, asset, amount, recipient, interval_secs, next_charge_at,
+             max_charges)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+        RETURNING id, project_id, account, pci, asset, amount, recipient, interval_secs,
+                  next_charge_at, max_charges, charges_count, status, cancelled_at, created_at,
+                  updated_at
+    "#;
+    let row = sqlx::query_as::<Postgres, Subscription>(query)
+        .bind(subscription.id)
+        .bind(subscription.project_id)
+        .bind(subscription.account)
+        .bind(subscription.pci)
+        .bind(subscription.asset)
+        .bind(subscription.amount)
+        .bind(subscription.recipient)
+        .bind(subscription.interval_secs)
+        .bind(subscription.next_charge_at)
+        .bind(subscription.max_charges)
+        .fetch_one(executor)
+        .await?;
+    Ok(row)
+}
+
+pub async fn get_subscription(
+    executor: impl PgExecutor<'_>,
+    id: &str,
+) -> Result<Option<Subscription>, DatabaseError> {
+    let query = r#"
+        SELECT id, project_id, account, pci, asset, amount, recipient, interval_secs,
+               next_charge_at, max_charges, charges_count, status, cancelled_at, created_at,
+               updated_at
+        FROM subscriptions
+        WHERE id = $1
+    "#;
+    let row = sqlx::query_as::<Postgres, Subscription>(query)
+        .bind(id)
+        .fetch_optional(executor)
+        .await?;
+    Ok(row)
+}
+
+/// Cancel the active subscription, returns `None` if the subscription
+/// is not active anymore
+pub async fn cancel_subscription(
+    executor: impl PgExecutor<'_>,
+    id: &str,
+) -> Result<Option<Subscription>, DatabaseError> {
+    let query = r#"
+        UPDATE subscriptions
+        SET status = 'cancelled'::subscription_status, cancelled_at = NOW(), updated_at = NOW()
+        WHERE id = $1 AND status = 'active'
+        RETURNING id, project_id, account, pci, asset, amount, recipient, interval_secs,
+                  next_charge_at, max_charges, charges_count, status, cancelled_at, created_at,
+                  updated_at
+    "#;
+    let row = sqlx::query_as::<Postgres, Subscription>(query)
+        .bind(id)
+        .fetch_optional(executor)
+        .await?;
+    Ok(row)
+}
+
+/// Claim the batch of active subscriptions that are due to be charged
+pub async fn claim_due_batch(
+    executor: impl PgExecutor<'_>,
+    max_claim: i64,
+) -> Result<Vec<Subscription>, DatabaseError> {
+    let query = r#"
+        WITH candidates AS (
+            SELECT id FROM subscriptions
+            WHERE status = 'active'
+              AND next_charge_at <= NOW()
+              AND (locked_at IS NULL OR locked_at < NOW() - make_interval(mins => $2))
+            ORDER BY next_charge_at ASC
+            LIMIT $1
+            FOR UPDATE SKIP LOCKED
+        )
+        UPDATE subscriptions s
+        SET locked_at = NOW(), updated_at = NOW()
+        WHERE s.id IN (SELECT id FROM candidates)
+        RETURNING s.id, s.project_id, s.account, s.pci, s.asset, s.amount, s.recipient,
+                  s.interval_secs, s.next_charge_at, s.max_charges, s.charges_count, s.status,
+                  s.cancelled_at, s.created_at, s.updated_at
+    "#;
+    let rows = sqlx::query_as::<Postgres, Subscription>(query)
+        .bind(max_claim)
+        .bind(LOCK_EXPIRATION_MINUTES)
+        .fetch_all(executor)
+        .await?;
+    Ok(rows)
+}
+
+/// Insert the charge of the period, returns `false` if the period
+/// was already charged
+pub async fn insert_charge(
+    executor: impl PgExecutor<'_>,
+    charge: NewSubscriptionCharge<'_>,
+) -> Result<bool, DatabaseError> {
+    let query = r#"
+        INSERT INTO subscription_charges
+            (subscription_id, period_at, status, call_id, failure_reason)
+        VALUES ($1, $2, $3, $4, $5)
+        ON CONFLICT (subscription_id, period_at) DO NOTHING
+    "#;
+    let result = sqlx::query::<Postgres>(query)
+        .bind(charge.subscription_id)
+        .bind(charge.period_at)
+        .bind(charge.status)
+        .bind(charge.call_id)
+        .bind(charge.failure_reason)
+        .execute(executor)
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Move the subscription schedule to the next period and release the lock,
+/// the subscription is completed when the maximum charges count is reached
+pub async fn advance_schedule(
+    executor: impl PgExecutor<'_>,
+    id: &str,
+    charged: bool,
+) -> Result<(), DatabaseError> {
+    let query = r#"
+        UPDATE subscriptions SET
+            next_charge_at = next_charge_at + make_interval(secs => interval_secs),
+            charges_count = charges_count + CASE WHEN $2 THEN 1 ELSE 0 END,
+            status = CASE
+                WHEN $2 AND max_charges IS NOT NULL AND charges_count + 1 >= max_charges
+                    THEN 'completed'::subscription_status
+                ELSE status
+            END,
+            locked_at = NULL,
+            updated_at = NOW()
+        WHERE id = $1 AND status = 'active'
+    "#;
+    sqlx::query::<Postgres>(query)
+        .bind(id)
+        .bind(charged)
+        .execute(executor)
+        .await?;
+    Ok(())
+}
+
+pub async fn list_charges(
+    executor: impl PgExecutor<'_>,
+    subscription_id: &str,
+) -> Result<Vec<SubscriptionCharge>, DatabaseError> {
+    let query = r#"
+        SELECT id, subscription_id, period_at, status, call_id, failure_reason, created_at
+        FROM subscription_charges
+        WHERE subscription_id = $1
+        ORDER BY period_at DESC
+    "#;
+    let rows = sqlx::query_as::<Postgres, SubscriptionCharge>(query)
+        .bind(subscription_id)
+        .fetch_all(executor)
+        .await?;
+    Ok(rows)
+}
@@ -0,0 +1,146 @@
+use {
+    crate::database::error::DatabaseError,
+    chrono::{DateTime, Utc},
+    sqlx::{types::Json, FromRow, PgExecutor, Postgres},
+};
+
+const LOCK_EXPIRATION_MINUTES: i32 = 5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, sqlx::Type)]
+#[sqlx(type_name = "webhook_delivery_status", rename_all = "lowercase")]
+pub enum WebhookDeliveryStatus {
+    Pending,
+    Delivered,
+    Failed,
+}
+
+#[derive(Debug, FromRow, Clone)]
+pub struct WebhookDelivery {
+    pub id: String,
+    pub project_id: String,
+    pub event_type: String,
+    pub url: String,
+    pub payload: Json<serde_json::Value>,
+    pub status: WebhookDeliveryStatus,
+    pub attempts: i32,
+    pub next_attempt_at: DateTime<Utc>,
+    pub last_status_code: Option<i32>,
+    pub last_error: Option<String>,
+    pub delivered_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+pub struct NewWebhookDelivery<'a> {
+    pub id: &'a str,
+    pub project_id: &'a str,
+    pub event_type: &'a str,
+    pub url: &'a str,
+    pub payload: &'a serde_json::Value,
+}
+
+pub async fn insert_delivery(
+    executor: impl PgExecutor<'_>,
+    delivery: NewWebhookDelivery<'_>,
+) -> Result<(), DatabaseError> {
+    let query = r#"
+        INSERT INTO webhook_deliveries (id, project_id, event_type, url, payload)
+        VALUES ($1, $2, $3, $4, $5)
+    "#;
+    sqlx::query::<Postgres>(query)
+        .bind(delivery.id)
+        .bind(delivery.project_id)
+        .bind(delivery.event_type)
+        .bind(delivery.url)
+        .bind(Json(delivery.payload))
+        .execute(executor)
+        .await?;
+    Ok(())
+}
+
+/// Claim the batch of pending deliveries that are due to be attempted
+pub async fn claim_due_batch(
+    executor: impl PgExecutor<'_>,
+    max_claim: i64,
+) -> Result<Vec<WebhookDelivery>, DatabaseError> {
+    let query = r#"
+        WITH candidates AS (
+            SELECT id FROM webhook_deliveries
+            WHERE status = 'pending'
+              AND next_attempt_at <= NOW()
+              AND (locked_at IS NULL OR locked_at < NOW() - make_interval(mins => $2))
+            ORDER BY next_attempt_at ASC
+            LIMIT $1
+            FOR UPDATE SKIP LOCKED
+        )
+        UPDATE webhook_deliveries d
+        SET locked_at = NOW(), updated_at = NOW()
+        WHERE d.id IN (SELECT id FROM candidates)
+        RETURNING d.id, d.project_id, d.event_type, d.url, d.payload, d.status, d.attempts,
+                  d.next_attempt_at, d.last_status_code, d.last_error, d.delivered_at,
+                  d.created_at, d.updated_at
+    "#;
+    let rows = sqlx::query_as::<Postgres, WebhookDelivery>(query)
+        .bind(max_claim)
+        .bind(LOCK_EXPIRATION_MINUTES)
+        .fetch_all(executor)
+        .await?;
+    Ok(rows)
+}
+
+pub async fn mark_delivered(
+    executor: impl PgExecutor<'_>,
+    id: &str,
+    status_code: i32,
+) -> Result<(), DatabaseError> {
+    let query = r#"
+        UPDATE webhook_deliveries SET
+            status = 'delivered',
+            attempts = attempts + 1,
+            last_status_code = $2,
+            last_error = NULL,
+            delivered_at = NOW(),
+            locked_at = NULL,
+            updated_at = NOW()
+        WHERE id = $1
+    "#;
+    sqlx::query::<Postgres>(query)
+        .bind(id)
+        .bind(status_code)
+        .execute(executor)
+        .await?;
+    Ok(())
+}
+
+/// Record the failed attempt and release the lock, the delivery is retried at
+/// the `next_attempt_at` or failed permanently when it's not set
+pub async fn record_failed_attempt(
+    executor: impl PgExecutor<'_>,
+    id: &str,
+    status_code: Option<i32>,
+    error: &str,
+    next_attempt_at: Option<DateTime<Utc>>,
+) -> Result<(), DatabaseError> {
+    let query = r#"
+        UPDATE webhook_deliveries SET
+            status = CASE
+                WHEN $4::TIMESTAMPTZ IS NULL THEN 'failed'::webhook_delivery_status
+                ELSE status
+            END,
+            attempts = attempts + 1,
+            next_attempt_at = COALESCE($4, next_attempt_at),
+            last_status_code = $2,
+            last_error = $3,
+            locked_at = NULL,
+            updated_at = NOW()
+        WHERE id = $1
+    "#;
+    sqlx::query::<Postgres>(query)
+        .bind(id)
+        .bind(status_code)
+        .bind(error)
+        .bind(next_attempt_at)
+        .execute(executor)
+        .await?;
+    Ok(())
+}
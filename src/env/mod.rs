@@ -6,10 +6,12 @@ use {
         handlers::balance::Config as BalanceConfig,
         handlers::json_rpc::exchanges::Config as ExchangesConfig,
         names::Config as NamesConfig,
+        otel::OtelConfig,
         profiler::ProfilerConfig,
         project::{storage::Config as StorageConfig, Config as RegistryConfig},
         providers::{ProviderKind, ProvidersConfig, Weight},
         storage::irn::Config as IrnConfig,
+        usage::Config as UsageConfig,
         utils::{crypto::CaipNamespaces, rate_limit::RateLimitingConfig},
     },
     serde::de::DeserializeOwned,
@@ -79,6 +81,8 @@ pub struct Config {
     pub names: NamesConfig,
     pub balances: BalanceConfig,
     pub exchanges: ExchangesConfig,
+    pub usage: UsageConfig,
+    pub otel: OtelConfig,
 }
 
 impl Config {
@@ -96,6 +100,8 @@ impl Config {
             names: from_env("RPC_PROXY_NAMES_")?,
             balances: from_env("RPC_PROXY_BALANCES_")?,
             exchanges: from_env("RPC_PROXY_EXCHANGES_")?,
+            usage: from_env("RPC_PROXY_USAGE_")?,
+            otel: from_env("RPC_PROXY_OTEL_")?,
         })
     }
 }
@@ -108,6 +114,13 @@ pub trait ProviderConfig {
     fn supported_chains(self) -> HashMap<String, (String, Weight)>;
     fn supported_ws_chains(self) -> HashMap<String, (String, Weight)>;
     fn provider_kind(&self) -> ProviderKind;
+
+    /// Multipliers of the provider chains weights by the JSON-RPC method to
+    /// route the methods to the providers suited for them, e.g. `0.1` for
+    /// `eth_getLogs` when the provider logs queries are slow
+    fn method_weight_modifiers(&self) -> HashMap<String, f64> {
+        HashMap::new()
+    }
 }
 
 pub trait BalanceProviderConfig {
@@ -126,10 +139,12 @@ mod test {
             handlers::balance::Config as BalanceConfig,
             handlers::json_rpc::exchanges::Config as ExchangesConfig,
             names::Config as NamesConfig,
+            otel::OtelConfig,
             profiler::ProfilerConfig,
             project,
             providers::ProvidersConfig,
-            storage::irn::Config as IrnConfig,
+            storage::irn::{Config as IrnConfig, MigrationMode},
+            usage::Config as UsageConfig,
             utils::rate_limit::RateLimitingConfig,
         },
         std::net::Ipv4Addr,
@@ -145,8 +160,28 @@ mod test {
             ("RPC_PROXY_LOG_LEVEL", "TRACE"),
             ("RPC_PROXY_EXTERNAL_IP", "2.3.4.5"),
             ("RPC_PROXY_BLOCKED_COUNTRIES", "KP,IR,CU,SY"),
+            ("RPC_PROXY_GEO_RESTRICTIONS", "RU=eip155:1,RU=/v1/onramp"),
             ("RPC_PROXY_GEOIP_DB_BUCKET", "GEOIP_DB_BUCKET"),
             ("RPC_PROXY_GEOIP_DB_KEY", "GEOIP_DB_KEY"),
+            ("RPC_PROXY_VALIDATE_PROJECT_ALLOWLIST", "true"),
+            ("RPC_PROXY_SECRETS_REFRESH_INTERVAL_SECS", "300"),
+            ("RPC_PROXY_ADMIN_API_TOKEN", "ADMIN_API_TOKEN"),
+            ("RPC_PROXY_READINESS_NAMESPACES", "eip155"),
+            ("RPC_PROXY_WS_DRAIN_WINDOW_SECS", "10"),
+            ("RPC_PROXY_WS_MAX_MESSAGES_PER_SEC", "20"),
+            ("RPC_PROXY_WS_MAX_SUBSCRIPTIONS", "30"),
+            ("RPC_PROXY_WS_IDLE_TIMEOUT_SECS", "60"),
+            ("RPC_PROXY_SHADOW_BASE_URL", "SHADOW_BASE_URL"),
+            ("RPC_PROXY_SHADOW_PERCENTAGE", "5"),
+            ("RPC_PROXY_ETH_CALL_CACHE_MAX_CAPACITY", "10000"),
+            ("RPC_PROXY_ETH_CALL_CACHE_BLOCK_TTL_SECS", "600"),
+            ("RPC_PROXY_WEBHOOK_SIGNING_SECRET", "WEBHOOK_SIGNING_SECRET"),
+            ("RPC_PROXY_REQUIRE_PROJECT_SIGNATURE", "true"),
+            ("RPC_PROXY_GRPC_PORT", "345"),
+            ("RPC_PROXY_REQUEST_BODY_MAX_BYTES", "1000"),
+            ("RPC_PROXY_PAID_REQUEST_BODY_MAX_BYTES", "2000"),
+            ("RPC_PROXY_CHAIN_QUARANTINE_THRESHOLD", "5"),
+            ("RPC_PROXY_CHAIN_QUARANTINE_PROBE_INTERVAL_SECS", "30"),
             // Integration tests config.
             ("RPC_PROXY_TESTING_PROJECT_ID", "TESTING_PROJECT_ID"),
             // Registry config.
@@ -180,9 +215,22 @@ mod test {
                 "RPC_PROXY_STORAGE_RATE_LIMITING_CACHE_REDIS_ADDR_WRITE",
                 "redis://127.0.0.1/rate_limit/write",
             ),
+            ("RPC_PROXY_STORAGE_LOCAL_CACHE_MAX_CAPACITY", "5000"),
+            ("RPC_PROXY_STORAGE_LOCAL_CACHE_TTL", "20"),
+            ("RPC_PROXY_STORAGE_IDENTITY_CACHE_TTL", "3600"),
+            ("RPC_PROXY_STORAGE_IDENTITY_NEGATIVE_CACHE_TTL", "600"),
+            ("RPC_PROXY_STORAGE_IDENTITY_REFRESH_INTERVAL", "30"),
+            ("RPC_PROXY_STORAGE_BALANCE_CACHE_TTL", "5"),
+            ("RPC_PROXY_STORAGE_BUNDLER_GAS_PRICE_CACHE_TTL", "3"),
+            ("RPC_PROXY_STORAGE_CA_ROUTE_CACHE_TTL", "30"),
+            ("RPC_PROXY_STORAGE_SESSIONS_TTL", "86400"),
+            ("RPC_PROXY_STORAGE_ORCHESTRATIONS_TTL", "600"),
             // Analytics config.
             ("RPC_PROXY_ANALYTICS_S3_ENDPOINT", "s3://127.0.0.1"),
             ("RPC_PROXY_ANALYTICS_EXPORT_BUCKET", "EXPORT_BUCKET"),
+            ("RPC_PROXY_ANALYTICS_LOCAL_EXPORT", "stdout"),
+            // Profiler config
+            ("RPC_PROXY_PROFILER_SECRET", "PROFILER_SECRET"),
             // Providers config
             (
                 "RPC_PROXY_PROVIDER_CACHE_REDIS_ADDR",
@@ -196,16 +244,27 @@ mod test {
             ),
             ("RPC_PROXY_PROVIDER_COINBASE_API_KEY", "COINBASE_API_KEY"),
             ("RPC_PROXY_PROVIDER_COINBASE_APP_ID", "COINBASE_APP_ID"),
+            ("RPC_PROXY_PROVIDER_MOONPAY_API_KEY", "MOONPAY_API_KEY"),
+            (
+                "RPC_PROXY_PROVIDER_MOONPAY_SECRET_KEY",
+                "MOONPAY_SECRET_KEY",
+            ),
+            ("RPC_PROXY_PROVIDER_RAMP_HOST_API_KEY", "RAMP_HOST_API_KEY"),
+            ("RPC_PROXY_PROVIDER_RAMP_SECRET_KEY", "RAMP_SECRET_KEY"),
             ("RPC_PROXY_PROVIDER_ONE_INCH_API_KEY", "ONE_INCH_API_KEY"),
             ("RPC_PROXY_PROVIDER_ONE_INCH_REFERRER", "ONE_INCH_REFERRER"),
             ("RPC_PROXY_PROVIDER_LIFI_API_KEY", "LIFI_API_KEY"),
+            ("RPC_PROXY_PROVIDER_COINGECKO_API_KEY", "COINGECKO_API_KEY"),
             ("RPC_PROXY_PROVIDER_PIMLICO_API_KEY", "PIMLICO_API_KEY"),
+            ("RPC_PROXY_PROVIDER_BICONOMY_API_KEY", "BICONOMY_API_KEY"),
+            ("RPC_PROXY_PROVIDER_ALCHEMY_API_KEY", "ALCHEMY_API_KEY"),
             (
                 "RPC_PROXY_PROVIDER_SOLSCAN_API_V2_TOKEN",
                 "SOLSCAN_API_V2_TOKEN",
             ),
             ("RPC_PROXY_PROVIDER_TONCENTER_API_URL", "TONCENTER_API_URL"),
             ("RPC_PROXY_PROVIDER_TONCENTER_API_KEY", "TONCENTER_API_KEY"),
+            ("RPC_PROXY_PROVIDER_ESPLORA_API_URL", "ESPLORA_API_URL"),
             ("RPC_PROXY_PROVIDER_BUNGEE_API_KEY", "BUNGEE_API_KEY"),
             ("RPC_PROXY_PROVIDER_TENDERLY_API_KEY", "TENDERLY_KEY"),
             (
@@ -234,6 +293,19 @@ mod test {
                 "CALLSTATIC_API_KEY",
             ),
             ("RPC_PROXY_PROVIDER_BLAST_API_KEY", "BLAST_API_KEY"),
+            (
+                "RPC_PROXY_PROVIDER_UPSTREAM_FORWARDED_HEADERS",
+                "Syndica:solana-client,Quicknode:solana-client",
+            ),
+            ("RPC_PROXY_PROVIDER_UPSTREAM_TIMEOUT_MS", "10000"),
+            ("RPC_PROXY_PROVIDER_UPSTREAM_MAX_ATTEMPTS", "5"),
+            ("RPC_PROXY_PROVIDER_UPSTREAM_RETRY_BACKOFF_MS", "50"),
+            (
+                "RPC_PROXY_PROVIDER_UPSTREAM_CHAIN_RETRY_POLICIES",
+                "eip155:1=5000:3:100,eip155:10=:2",
+            ),
+            ("RPC_PROXY_PROVIDER_UPSTREAM_RETRY_BUDGET_PERCENTAGE", "20"),
+            ("RPC_PROXY_PROVIDER_UPSTREAM_RETRY_BUDGET_MIN_CONCURRENCY", "3"),
             // Postgres config.
             (
                 "RPC_PROXY_POSTGRES_URI",
@@ -248,15 +320,31 @@ mod test {
                 "RPC_PROXY_RATE_LIMITING_IP_WHITELIST",
                 "127.0.0.1,127.0.0.2",
             ),
+            ("RPC_PROXY_RATE_LIMITING_PROJECT_MAX_TOKENS", "1000"),
+            ("RPC_PROXY_RATE_LIMITING_PROJECT_REFILL_RATE", "100"),
+            ("RPC_PROXY_RATE_LIMITING_TIERS", "free:50:5,pro:500:50"),
             // IRN config.
             ("RPC_PROXY_IRN_NODES", "node1.id,node2.id"),
             ("RPC_PROXY_IRN_KEY", "key"),
             ("RPC_PROXY_IRN_NAMESPACE", "namespace"),
             ("RPC_PROXY_IRN_NAMESPACE_SECRET", "namespace"),
+            ("RPC_PROXY_IRN_MIGRATION_MODE", "dual_write"),
             // Names configuration
             ("RPC_PROXY_NAMES_ALLOWED_ZONES", "test1.id,test2.id"),
             // Account balances-related configuration
             ("RPC_PROXY_BALANCES_DENYLIST_PROJECT_IDS", "test_project_id"),
+            (
+                "RPC_PROXY_BALANCES_SCAM_TOKENS_FEED_URLS",
+                "https://scam-tokens.example/list.json",
+            ),
+            (
+                "RPC_PROXY_BALANCES_SCAM_TOKENS_OPT_OUT_PROJECT_IDS",
+                "test_project_id",
+            ),
+            (
+                "RPC_PROXY_BALANCES_SCAM_TOKENS_REFRESH_INTERVAL_SECS",
+                "600",
+            ),
             // Exchanges configuration
             (
                 "RPC_PROXY_EXCHANGES_COINBASE_PROJECT_ID",
@@ -275,10 +363,33 @@ mod test {
             ("RPC_PROXY_EXCHANGES_BINANCE_TOKEN", "BINANCE_TOKEN"),
             ("RPC_PROXY_EXCHANGES_BINANCE_KEY", "BINANCE_KEY"),
             ("RPC_PROXY_EXCHANGES_BINANCE_HOST", "BINANCE_HOST"),
+            (
+                "RPC_PROXY_EXCHANGES_BINANCE_WEBHOOK_PUBLIC_KEY",
+                "BINANCE_WEBHOOK_PUBLIC_KEY",
+            ),
+            (
+                "RPC_PROXY_EXCHANGES_COINBASE_WEBHOOK_SECRET",
+                "COINBASE_WEBHOOK_SECRET",
+            ),
+            ("RPC_PROXY_EXCHANGES_OKX_API_KEY", "OKX_API_KEY"),
+            ("RPC_PROXY_EXCHANGES_OKX_SECRET_KEY", "OKX_SECRET_KEY"),
+            ("RPC_PROXY_EXCHANGES_OKX_PASSPHRASE", "OKX_PASSPHRASE"),
+            ("RPC_PROXY_EXCHANGES_OKX_HOST", "OKX_HOST"),
+            ("RPC_PROXY_EXCHANGES_RECONCILER_INTERVAL_SECS", "300"),
             (
                 "RPC_PROXY_EXCHANGES_ALLOWED_PROJECT_IDS",
                 "test_project_id,test_project_id_2",
             ),
+            // Usage reporting config.
+            ("RPC_PROXY_USAGE_EXPORT_BUCKET", "USAGE_EXPORT_BUCKET"),
+            ("RPC_PROXY_USAGE_REPORT_INTERVAL_SECS", "30"),
+            // OpenTelemetry config.
+            (
+                "RPC_PROXY_OTEL_OTLP_ENDPOINT",
+                "http://localhost:4318/v1/traces",
+            ),
+            ("RPC_PROXY_OTEL_SERVICE_NAME", "blockchain-api-test"),
+            ("RPC_PROXY_OTEL_TRACE_SAMPLE_PERCENT", "10"),
         ];
 
         values.iter().for_each(set_env_var);
@@ -298,12 +409,34 @@ mod test {
                         "CU".to_owned(),
                         "SY".to_owned(),
                     ],
+                    geo_restrictions: vec!["RU=eip155:1".to_owned(), "RU=/v1/onramp".to_owned()],
                     s3_endpoint: None,
                     geoip_db_bucket: Some("GEOIP_DB_BUCKET".to_owned()),
                     geoip_db_key: Some("GEOIP_DB_KEY".to_owned()),
                     testing_project_id: Some("TESTING_PROJECT_ID".to_owned()),
                     validate_project_id: true,
                     skip_quota_chains: vec![],
+                    sessions_revoke_signature_required: false,
+                    sessions_passkey_user_verification_required: false,
+                    validate_project_allowlist: true,
+                    secrets_refresh_interval_secs: Some(300),
+                    admin_api_token: Some("ADMIN_API_TOKEN".to_owned()),
+                    readiness_namespaces: vec!["eip155".to_owned()],
+                    ws_drain_window_secs: 10,
+                    ws_max_messages_per_sec: 20,
+                    ws_max_subscriptions: 30,
+                    ws_idle_timeout_secs: 60,
+                    shadow_base_url: Some("SHADOW_BASE_URL".to_owned()),
+                    shadow_percentage: 5,
+                    eth_call_cache_max_capacity: 10000,
+                    eth_call_cache_block_ttl_secs: 600,
+                    webhook_signing_secret: Some("WEBHOOK_SIGNING_SECRET".to_owned()),
+                    require_project_signature: true,
+                    grpc_port: Some(345),
+                    request_body_max_bytes: 1000,
+                    paid_request_body_max_bytes: 2000,
+                    chain_quarantine_threshold: 5,
+                    chain_quarantine_probe_interval_secs: 30,
                 },
                 registry: project::Config {
                     api_url: Some("API_URL".to_owned()),
@@ -327,6 +460,16 @@ mod test {
                     rate_limiting_cache_redis_addr_write: Some(
                         "redis://127.0.0.1/rate_limit/write".to_owned()
                     ),
+                    local_cache_max_capacity: 5000,
+                    local_cache_ttl: 20,
+                    identity_cache_ttl: 3600,
+                    identity_negative_cache_ttl: 600,
+                    identity_refresh_interval: 30,
+                    balance_cache_ttl: 5,
+                    bundler_gas_price_cache_ttl: 3,
+                    ca_route_cache_ttl: 30,
+                    sessions_ttl: 86400,
+                    orchestrations_ttl: 600,
                 },
                 postgres: PostgresConfig {
                     uri: "postgres://postgres@localhost:5432/postgres".to_owned(),
@@ -335,8 +478,11 @@ mod test {
                 analytics: analytics::Config {
                     s3_endpoint: Some("s3://127.0.0.1".to_owned()),
                     export_bucket: Some("EXPORT_BUCKET".to_owned()),
+                    local_export: Some("stdout".to_owned()),
+                },
+                profiler: ProfilerConfig {
+                    secret: Some("PROFILER_SECRET".to_owned()),
                 },
-                profiler: ProfilerConfig {},
                 providers: ProvidersConfig {
                     prometheus_query_url: Some("PROMETHEUS_QUERY_URL".to_owned()),
                     prometheus_workspace_header: Some("PROMETHEUS_WORKSPACE_HEADER".to_owned()),
@@ -346,13 +492,21 @@ mod test {
                     zerion_api_key: "ZERION_API_KEY".to_owned(),
                     coinbase_api_key: Some("COINBASE_API_KEY".to_owned()),
                     coinbase_app_id: Some("COINBASE_APP_ID".to_owned()),
+                    moonpay_api_key: Some("MOONPAY_API_KEY".to_owned()),
+                    moonpay_secret_key: Some("MOONPAY_SECRET_KEY".to_owned()),
+                    ramp_host_api_key: Some("RAMP_HOST_API_KEY".to_owned()),
+                    ramp_secret_key: Some("RAMP_SECRET_KEY".to_owned()),
                     one_inch_api_key: Some("ONE_INCH_API_KEY".to_owned()),
                     one_inch_referrer: Some("ONE_INCH_REFERRER".to_owned()),
                     lifi_api_key: Some("LIFI_API_KEY".to_owned()),
+                    coingecko_api_key: Some("COINGECKO_API_KEY".to_owned()),
                     pimlico_api_key: "PIMLICO_API_KEY".to_string(),
+                    biconomy_api_key: Some("BICONOMY_API_KEY".to_owned()),
+                    alchemy_api_key: Some("ALCHEMY_API_KEY".to_owned()),
                     solscan_api_v2_token: "SOLSCAN_API_V2_TOKEN".to_string(),
                     toncenter_api_url: Some("TONCENTER_API_URL".to_string()),
                     toncenter_api_key: Some("TONCENTER_API_KEY".to_string()),
+                    esplora_api_url: Some("ESPLORA_API_URL".to_string()),
                     bungee_api_key: "BUNGEE_API_KEY".to_string(),
                     tenderly_api_key: "TENDERLY_KEY".to_string(),
                     tenderly_account_id: "TENDERLY_ACCOUNT_ID".to_string(),
@@ -360,6 +514,33 @@ mod test {
                     dune_sim_api_key: "DUNE_SIM_API_KEY".to_string(),
                     syndica_api_key: "SYNDICA_API_KEY".to_string(),
                     override_bundler_urls: None,
+                    override_localnet_url: None,
+                    override_api_proxy_url: None,
+                    chaos_latency_percentage: None,
+                    chaos_latency_ms: None,
+                    chaos_rate_limit_percentage: None,
+                    chaos_malformed_response_percentage: None,
+                    chaos_connection_reset_percentage: None,
+                    chaos_providers: None,
+                    upstream_pool_max_idle_per_host: None,
+                    upstream_pool_idle_timeout_secs: None,
+                    upstream_connect_timeout_ms: None,
+                    upstream_tcp_keepalive_secs: None,
+                    upstream_http2_adaptive_window: None,
+                    upstream_http2_prior_knowledge_providers: None,
+                    upstream_forwarded_headers: Some(vec![
+                        "Syndica:solana-client".to_owned(),
+                        "Quicknode:solana-client".to_owned(),
+                    ]),
+                    upstream_timeout_ms: Some(10000),
+                    upstream_max_attempts: Some(5),
+                    upstream_retry_backoff_ms: Some(50),
+                    upstream_chain_retry_policies: Some(vec![
+                        "eip155:1=5000:3:100".to_owned(),
+                        "eip155:10=:2".to_owned(),
+                    ]),
+                    upstream_retry_budget_percentage: Some(20),
+                    upstream_retry_budget_min_concurrency: Some(3),
                     allnodes_api_key: "ALLNODES_API_KEY".to_string(),
                     meld_api_key: "MELD_API_KEY".to_string(),
                     meld_api_url: "MELD_API_URL".to_string(),
@@ -371,18 +552,27 @@ mod test {
                     refill_interval_sec: Some(1),
                     refill_rate: Some(10),
                     ip_whitelist: Some(vec!["127.0.0.1".into(), "127.0.0.2".into()]),
+                    project_max_tokens: Some(1000),
+                    project_refill_rate: Some(100),
+                    tiers: Some(vec!["free:50:5".into(), "pro:500:50".into()]),
                 },
                 irn: IrnConfig {
                     nodes: Some(vec!["node1.id".to_owned(), "node2.id".to_owned()]),
                     key: Some("key".to_owned()),
                     namespace: Some("namespace".to_owned()),
                     namespace_secret: Some("namespace".to_owned()),
+                    migration_mode: Some(MigrationMode::DualWrite),
                 },
                 names: NamesConfig {
                     allowed_zones: Some(vec!["test1.id".to_owned(), "test2.id".to_owned()]),
                 },
                 balances: BalanceConfig {
                     denylist_project_ids: Some(vec!["test_project_id".to_owned()]),
+                    scam_tokens_feed_urls: Some(vec![
+                        "https://scam-tokens.example/list.json".to_owned()
+                    ]),
+                    scam_tokens_opt_out_project_ids: Some(vec!["test_project_id".to_owned()]),
+                    scam_tokens_refresh_interval_secs: Some(600),
                 },
                 exchanges: ExchangesConfig {
                     coinbase_project_id: Some("COINBASE_PROJECT_ID".to_owned()),
@@ -390,6 +580,13 @@ mod test {
                     binance_token: Some("BINANCE_TOKEN".to_owned()),
                     binance_key: Some("BINANCE_KEY".to_owned()),
                     binance_host: Some("BINANCE_HOST".to_owned()),
+                    binance_webhook_public_key: Some("BINANCE_WEBHOOK_PUBLIC_KEY".to_owned()),
+                    coinbase_webhook_secret: Some("COINBASE_WEBHOOK_SECRET".to_owned()),
+                    okx_api_key: Some("OKX_API_KEY".to_owned()),
+                    okx_secret_key: Some("OKX_SECRET_KEY".to_owned()),
+                    okx_passphrase: Some("OKX_PASSPHRASE".to_owned()),
+                    okx_host: Some("OKX_HOST".to_owned()),
+                    reconciler_interval_secs: Some(300),
                     coinbase_key_name: Some("COINBASE_KEY_NAME".to_owned()),
                     coinbase_key_secret: Some("COINBASE_KEY_SECRET".to_owned()),
                     internal_api_coinbase_credentials: Some(
@@ -400,6 +597,15 @@ mod test {
                         "test_project_id_2".to_owned(),
                     ]),
                 },
+                usage: UsageConfig {
+                    export_bucket: Some("USAGE_EXPORT_BUCKET".to_owned()),
+                    report_interval_secs: Some(30),
+                },
+                otel: OtelConfig {
+                    otlp_endpoint: Some("http://localhost:4318/v1/traces".to_owned()),
+                    service_name: Some("blockchain-api-test".to_owned()),
+                    trace_sample_percent: Some(10),
+                },
             }
         );
 
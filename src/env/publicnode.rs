@@ -7,12 +7,14 @@ use {
 #[derive(Debug)]
 pub struct PublicnodeConfig {
     pub supported_chains: HashMap<String, (String, Weight)>,
+    pub supported_ws_chains: HashMap<String, (String, Weight)>,
 }
 
 impl Default for PublicnodeConfig {
     fn default() -> Self {
         Self {
             supported_chains: default_supported_chains(),
+            supported_ws_chains: default_ws_supported_chains(),
         }
     }
 }
@@ -23,7 +25,7 @@ impl ProviderConfig for PublicnodeConfig {
     }
 
     fn supported_ws_chains(self) -> HashMap<String, (String, Weight)> {
-        HashMap::new()
+        self.supported_ws_chains
     }
 
     fn provider_kind(&self) -> crate::providers::ProviderKind {
@@ -266,3 +268,15 @@ fn default_supported_chains() -> HashMap<String, (String, Weight)> {
         //
     ])
 }
+
+fn default_ws_supported_chains() -> HashMap<String, (String, Weight)> {
+    // Keep in-sync with SUPPORTED_CHAINS.md
+
+    HashMap::from([
+        // Solana mainnet
+        (
+            "solana:5eykt4UsFv8P8NJdTREpY1vzqKqZKvdp".into(),
+            ("solana-rpc".into(), Weight::new(Priority::Normal).unwrap()),
+        ),
+    ])
+}
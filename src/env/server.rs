@@ -5,6 +5,9 @@ use {
     std::net::IpAddr,
 };
 
+/// Plan tier of the free projects, all other tiers are paid
+const FREE_PLAN_TIER: &str = "free";
+
 #[derive(DeserializePiecewiseDefault, Debug, Clone, PartialEq, Eq)]
 pub struct ServerConfig {
     pub host: String,
@@ -14,12 +17,84 @@ pub struct ServerConfig {
     pub external_ip: Option<IpAddr>,
     pub s3_endpoint: Option<String>,
     pub blocked_countries: Vec<String>,
+    /// Chains and endpoints restricted in the countries, the comma-separated
+    /// `COUNTRY=target` entries with the CAIP-2 chain ID or the endpoint path
+    /// prefix target
+    pub geo_restrictions: Vec<String>,
     pub geoip_db_bucket: Option<String>,
     pub geoip_db_key: Option<String>,
     pub testing_project_id: Option<String>,
     pub validate_project_id: bool,
     /// Contains CAIP-2 chain identifiers that should bypass quota validation.
     pub skip_quota_chains: Vec<String>,
+    /// Require the account owner signature to revoke session permissions,
+    /// `false` by default to accept the unsigned revocations of the existing
+    /// clients
+    pub sessions_revoke_signature_required: bool,
+    /// Require the user verification (biometrics or PIN) in the passkey
+    /// signatures of the session cosigning, the user presence is always
+    /// required
+    pub sessions_passkey_user_verification_required: bool,
+    /// Reject the requests with the origin, bundle ID or package name not
+    /// matching the project's allowlists
+    pub validate_project_allowlist: bool,
+    /// Interval of the secrets re-resolving for the keys rotation, the
+    /// secrets are resolved at startup only when not set
+    pub secrets_refresh_interval_secs: Option<u64>,
+    /// Bearer token of the admin endpoints on the private port, the admin
+    /// endpoints are rejected when not set
+    pub admin_api_token: Option<String>,
+    /// CAIP-2 namespaces that must have a reachable RPC provider for the
+    /// instance to be ready
+    pub readiness_namespaces: Vec<String>,
+    /// Maximum time to wait for the WebSocket sessions to be closed on the
+    /// shutdown
+    pub ws_drain_window_secs: u64,
+    /// Maximum client messages per second of the WebSocket session, the
+    /// messages over the limit are rejected. Unlimited by default, set to a
+    /// positive value to opt in
+    pub ws_max_messages_per_sec: u32,
+    /// Maximum concurrent subscriptions of the WebSocket session. Unlimited by
+    /// default, set to a positive value to opt in
+    pub ws_max_subscriptions: usize,
+    /// WebSocket session without the data messages in both directions is
+    /// closed after the timeout. Never closed by default, set to a positive
+    /// value to opt in
+    pub ws_idle_timeout_secs: u64,
+    /// Base URL of the secondary deployment the `/v1` requests are mirrored
+    /// to, mirroring is disabled when not set
+    pub shadow_base_url: Option<String>,
+    /// Percentage of the `/v1` requests mirrored to the shadow base URL
+    pub shadow_percentage: u8,
+    /// Maximum number of the cached `eth_call` results, the cache is
+    /// disabled when zero
+    pub eth_call_cache_max_capacity: u64,
+    /// Cache TTL of the `eth_call` results on the explicit block number or
+    /// hash
+    pub eth_call_cache_block_ttl_secs: u64,
+    /// HMAC-SHA256 key of the outbound webhooks signatures, the webhooks are
+    /// queued but not delivered when not set
+    pub webhook_signing_secret: Option<String>,
+    /// Require the project JWT or the request signed by the project key on
+    /// the sensitive endpoints, otherwise only the provided signatures are
+    /// validated
+    pub require_project_signature: bool,
+    /// Port of the gRPC interface of the proxy, balance, history and
+    /// identity endpoints, the gRPC server is disabled when not set
+    pub grpc_port: Option<u16>,
+    /// Maximum request body size of the RPC proxy and bundler endpoints for
+    /// the free plan and unknown projects. Disabled by default, set to a
+    /// positive value to opt in
+    pub request_body_max_bytes: usize,
+    /// Maximum request body size of the RPC proxy and bundler endpoints for
+    /// the paid plans. Disabled by default, set to a positive value to opt in
+    pub paid_request_body_max_bytes: usize,
+    /// Consecutive RPC proxy requests of the chain failed by all providers to
+    /// quarantine the chain. The chains are never quarantined by default, set
+    /// to a positive value to opt in
+    pub chain_quarantine_threshold: u32,
+    /// Interval of the quarantined chains providers probes
+    pub chain_quarantine_probe_interval_secs: u64,
 }
 
 impl Default for ServerConfig {
@@ -32,11 +107,33 @@ impl Default for ServerConfig {
             external_ip: None,
             s3_endpoint: None,
             blocked_countries: Vec::new(),
+            geo_restrictions: Vec::new(),
             geoip_db_bucket: None,
             geoip_db_key: None,
             testing_project_id: None,
             validate_project_id: true,
             skip_quota_chains: Vec::new(),
+            sessions_revoke_signature_required: false,
+            sessions_passkey_user_verification_required: false,
+            validate_project_allowlist: false,
+            secrets_refresh_interval_secs: None,
+            admin_api_token: None,
+            readiness_namespaces: vec!["eip155".to_owned(), "solana".to_owned()],
+            ws_drain_window_secs: 20,
+            ws_max_messages_per_sec: 0,
+            ws_max_subscriptions: 0,
+            ws_idle_timeout_secs: 0,
+            shadow_base_url: None,
+            shadow_percentage: 0,
+            eth_call_cache_max_capacity: 0,
+            eth_call_cache_block_ttl_secs: 300,
+            webhook_signing_secret: None,
+            require_project_signature: false,
+            grpc_port: None,
+            request_body_max_bytes: 0,
+            paid_request_body_max_bytes: 0,
+            chain_quarantine_threshold: 0,
+            chain_quarantine_probe_interval_secs: 10,
         }
     }
 }
@@ -47,4 +144,50 @@ impl ServerConfig {
             .map(Ok)
             .unwrap_or_else(utils::network::find_public_ip_addr)
     }
+
+    /// Whether any of the plan tiers request body limits is enabled
+    pub fn request_body_limited(&self) -> bool {
+        self.request_body_max_bytes > 0 || self.paid_request_body_max_bytes > 0
+    }
+
+    /// Maximum request body size of the project's plan tier, `None` when the
+    /// tier's limit is disabled
+    pub fn request_body_max_bytes(&self, tier: Option<&str>) -> Option<usize> {
+        let limit = match tier {
+            Some(tier) if tier != FREE_PLAN_TIER => self.paid_request_body_max_bytes,
+            _ => self.request_body_max_bytes,
+        };
+        (limit > 0).then_some(limit)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn request_body_max_bytes_by_tier() {
+        let config = ServerConfig::default();
+        assert!(!config.request_body_limited());
+        assert_eq!(config.request_body_max_bytes(None), None);
+        assert_eq!(config.request_body_max_bytes(Some("pro")), None);
+
+        let config = ServerConfig {
+            request_body_max_bytes: 1000,
+            paid_request_body_max_bytes: 2000,
+            ..Default::default()
+        };
+        assert!(config.request_body_limited());
+        assert_eq!(config.request_body_max_bytes(None), Some(1000));
+        assert_eq!(config.request_body_max_bytes(Some("free")), Some(1000));
+        assert_eq!(config.request_body_max_bytes(Some("pro")), Some(2000));
+
+        // Only the paid plans are limited
+        let config = ServerConfig {
+            paid_request_body_max_bytes: 2000,
+            ..Default::default()
+        };
+        assert_eq!(config.request_body_max_bytes(Some("free")), None);
+        assert_eq!(config.request_body_max_bytes(Some("pro")), Some(2000));
+    }
 }
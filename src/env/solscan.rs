@@ -5,18 +5,21 @@ use {
         utils::crypto::CaipNamespaces,
     },
     std::collections::HashMap,
+    url::Url,
 };
 
 pub struct SolScanConfig {
     pub api_key: String,
     pub supported_namespaces: HashMap<CaipNamespaces, Weight>,
+    pub api_proxy_url: Option<Url>,
 }
 
 impl SolScanConfig {
-    pub fn new(api_key: String) -> Self {
+    pub fn new(api_key: String, api_proxy_url: Option<Url>) -> Self {
         Self {
             api_key,
             supported_namespaces: default_supported_namespaces(),
+            api_proxy_url,
         }
     }
 }
@@ -10,11 +10,16 @@ use {
     axum::{response::IntoResponse, Json},
     cerberus::registry::RegistryError,
     hyper::StatusCode,
+    strum_macros::AsRefStr,
     tracing::log::error,
 };
 
 pub type RpcResult<T> = Result<T, RpcError>;
 
+const PLAN_UPGRADE_URL: &str = "https://cloud.reown.com";
+/// Prefix of the problem details type URI, followed by the error code
+const PROBLEM_TYPE_PREFIX: &str = "urn:reown:blockchain-api:error:";
+
 #[derive(Debug, thiserror::Error)]
 pub enum RpcError {
     #[error(transparent)]
@@ -115,8 +120,11 @@ pub enum RpcError {
     #[error("Only WebSocket connections are supported for GET method on this endpoint")]
     WebSocketConnectionExpected,
 
+    #[error("New WebSocket connections are not accepted while the server is shutting down")]
+    WebSocketShuttingDown,
+
     #[error(transparent)]
-    RateLimited(#[from] wc::rate_limit::RateLimitExceeded),
+    RateLimited(#[from] crate::utils::rate_limit::RateLimitExceeded),
 
     #[error("Invalid address")]
     InvalidAddress,
@@ -130,6 +138,45 @@ pub enum RpcError {
     #[error("Quota limit reached")]
     QuotaLimitReached,
 
+    #[error("Plan limit reached for the {0} tier")]
+    PlanLimitReached(String),
+
+    #[error("Invalid project JWT: {0}")]
+    InvalidProjectJwt(String),
+
+    #[error("Invalid project request signature: {0}")]
+    InvalidProjectSignature(String),
+
+    #[error("Chain is not allowed for the project: {0}")]
+    ChainNotAllowed(String),
+
+    #[error("Invalid admin API token")]
+    InvalidAdminToken,
+
+    #[error("Invalid profiler secret")]
+    InvalidProfilerSecret,
+
+    #[error("Profiling error: {0}")]
+    ProfilingError(String),
+
+    #[error("Origin is not allowed for the project: {0}")]
+    OriginNotAllowed(String),
+
+    #[error("Application is not allowed for the project: {0}")]
+    ApplicationNotAllowed(String),
+
+    #[error("IP address is not allowed for the project: {0}")]
+    IpNotAllowed(String),
+
+    #[error("Country is not allowed for the project: {0}")]
+    CountryNotAllowed(String),
+
+    #[error("Not available in the country: {0}")]
+    RestrictedInCountry(String),
+
+    #[error("Request body exceeds the limit of {0} bytes")]
+    RequestBodyTooLarge(usize),
+
     #[error("sqlx error: {0}")]
     SqlxError(#[from] sqlx::error::Error),
 
@@ -142,6 +189,9 @@ pub enum RpcError {
     #[error("Asset is not supported: {0}")]
     AssetNotSupported(String),
 
+    #[error("Token metadata is not found: {0}")]
+    TokenMetadataNotFound(String),
+
     // Conversion errors
     #[error("Failed to reach the conversion provider")]
     ConversionProviderError,
@@ -213,8 +263,8 @@ pub enum RpcError {
     #[error("Weighted providers index error: {0}")]
     WeightedProvidersIndex(String),
 
-    #[error("IRN client is not configured")]
-    IrnNotConfigured,
+    #[error("Persistent storage is not configured")]
+    StorageNotConfigured,
 
     #[error("Internal permissions get context error: {0}")]
     InternalGetSessionContextError(InternalGetSessionContextError),
@@ -255,6 +305,9 @@ pub enum RpcError {
     #[error("Cosigner unsupported permission: {0}")]
     CosignerUnsupportedPermission(String),
 
+    #[error("Unsupported policy in CoSigner: {0}")]
+    CosignerUnsupportedPolicy(String),
+
     #[error("ABI decoding error: {0}")]
     AbiDecodingError(String),
 
@@ -281,15 +334,191 @@ pub enum RpcError {
 
     #[error("Unsupported bundler name: {0}")]
     UnsupportedBundlerName(String),
+
+    #[error("User operation was not included within the timeout: {0}")]
+    UserOperationWaitTimeout(String),
+
+    #[error("Bundler error: {0}")]
+    BundlerError(String),
+
+    #[error("Sponsorship policy violation: {0}")]
+    SponsorshipPolicyViolation(String),
+
+    #[error("Database error: {0}")]
+    DatabaseError(#[from] crate::database::error::DatabaseError),
+
+    #[error("Payment link is not found: {0}")]
+    PaymentLinkNotFound(String),
+
+    #[error("Payment link is not payable: {0}")]
+    PaymentLinkNotPayable(String),
+
+    #[error("Payment transaction error: {0}")]
+    PaymentTransactionError(String),
+
+    #[error("Subscription is not found: {0}")]
+    SubscriptionNotFound(String),
+
+    #[error("Subscription is not active: {0}")]
+    SubscriptionNotActive(String),
+
+    #[error("Onramp session is not found: {0}")]
+    OnrampSessionNotFound(String),
+}
+
+/// Stable machine-readable error codes of the error responses. SDKs branch on
+/// the codes instead of the descriptions, so the existing codes must never be
+/// renamed or reused for the other errors.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize, AsRefStr)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+#[strum(serialize_all = "SCREAMING_SNAKE_CASE")]
+pub enum ErrorCode {
+    ChainUnsupported,
+    ChainNotAllowed,
+    OriginNotAllowed,
+    ApplicationNotAllowed,
+    IpNotAllowed,
+    CountryNotAllowed,
+    RestrictedInCountry,
+    RequestBodyTooLarge,
+    CurrencyUnsupported,
+    ProviderUnsupported,
+    AssetUnsupported,
+    AssetNotFound,
+    NamespaceUnsupported,
+    ProviderUnavailable,
+    InvalidParameter,
+    InvalidAddress,
+    InvalidSignature,
+    Unauthorized,
+    ProjectNotFound,
+    QuotaExceeded,
+    RateLimited,
+    InvalidName,
+    NameAlreadyRegistered,
+    NameNotFound,
+    PermissionNotFound,
+    PermissionDenied,
+    OrchestrationNotFound,
+    UserOperationTimeout,
+    SponsorshipPolicyViolation,
+    PaymentLinkNotFound,
+    PaymentFailed,
+    SubscriptionNotFound,
+    SubscriptionNotActive,
+    OnrampSessionNotFound,
+    WebsocketError,
+    ShuttingDown,
+    InternalError,
+}
+
+impl RpcError {
+    /// Machine-readable code of the error response
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            Self::UnsupportedChain(_) => ErrorCode::ChainUnsupported,
+            Self::ChainNotAllowed(_) => ErrorCode::ChainNotAllowed,
+            Self::OriginNotAllowed(_) => ErrorCode::OriginNotAllowed,
+            Self::ApplicationNotAllowed(_) => ErrorCode::ApplicationNotAllowed,
+            Self::IpNotAllowed(_) => ErrorCode::IpNotAllowed,
+            Self::CountryNotAllowed(_) => ErrorCode::CountryNotAllowed,
+            Self::RestrictedInCountry(_) => ErrorCode::RestrictedInCountry,
+            Self::RequestBodyTooLarge(_) => ErrorCode::RequestBodyTooLarge,
+            Self::UnsupportedCurrency(_) => ErrorCode::CurrencyUnsupported,
+            Self::UnsupportedProvider(_)
+            | Self::UnsupportedBundler(_)
+            | Self::UnsupportedBundlerName(_)
+            | Self::UnsupportedBundlerNameUrlParseError(_) => ErrorCode::ProviderUnsupported,
+            Self::AssetNotSupported(_) | Self::UnsupportedCoinType(_) => {
+                ErrorCode::AssetUnsupported
+            }
+            Self::TokenMetadataNotFound(_) => ErrorCode::AssetNotFound,
+            Self::UnsupportedNamespace(_) => ErrorCode::NamespaceUnsupported,
+            Self::ChainTemporarilyUnavailable(_)
+            | Self::BalanceTemporarilyUnavailable(_)
+            | Self::TransportError(_)
+            | Self::IdentityProviderError(_)
+            | Self::TransactionProviderError
+            | Self::OnRampProviderError
+            | Self::PortfolioProviderError
+            | Self::BalanceProviderError
+            | Self::FungiblePriceProviderError(_)
+            | Self::ConversionProviderError
+            | Self::SimulationProviderUnavailable
+            | Self::BundlerError(_) => ErrorCode::ProviderUnavailable,
+            Self::InvalidChainIdFormat(_)
+            | Self::InvalidParameter(_)
+            | Self::CryptoUitlsError(_)
+            | Self::ConversionInvalidParameter(_)
+            | Self::ConversionInvalidParameterWithCode(_, _)
+            | Self::InvalidScheme
+            | Self::SerdeJson(_)
+            | Self::WrongBase64Format(_)
+            | Self::KeyFormatError(_)
+            | Self::AbiDecodingError(_)
+            | Self::UnsupportedNameAttribute
+            | Self::RouteSolana(RouteSolanaError::Request(_)) => ErrorCode::InvalidParameter,
+            Self::InvalidAddress => ErrorCode::InvalidAddress,
+            Self::ExpiredTimestamp(_)
+            | Self::SignatureValidationError(_)
+            | Self::SignatureFormatError(_)
+            | Self::NameOwnerValidationError => ErrorCode::InvalidSignature,
+            Self::ProjectDataError(ProjectDataError::NotFound) => ErrorCode::ProjectNotFound,
+            Self::InvalidProjectJwt(_)
+            | Self::InvalidProjectSignature(_)
+            | Self::InvalidAdminToken
+            | Self::InvalidProfilerSecret
+            | Self::RegistryError(_)
+            | Self::Cerberus(_)
+            | Self::ProjectDataError(_) => ErrorCode::Unauthorized,
+            Self::QuotaLimitReached | Self::PlanLimitReached(_) => ErrorCode::QuotaExceeded,
+            Self::RateLimited(_) => ErrorCode::RateLimited,
+            Self::InvalidNameFormat(_) | Self::InvalidNameLength(_) | Self::InvalidNameZone(_) => {
+                ErrorCode::InvalidName
+            }
+            Self::NameAlreadyRegistered(_) => ErrorCode::NameAlreadyRegistered,
+            Self::NameNotRegistered(_) | Self::NameNotFound(_) | Self::NameByAddressNotFound => {
+                ErrorCode::NameNotFound
+            }
+            Self::PermissionNotFound(_, _) => ErrorCode::PermissionNotFound,
+            Self::RevokedPermission(_)
+            | Self::PermissionExpired(_)
+            | Self::CoSignerEmptyPermissions
+            | Self::CosignerPermissionDenied(_)
+            | Self::CosignerUnsupportedPermission(_)
+            | Self::CosignerUnsupportedPolicy(_) => ErrorCode::PermissionDenied,
+            Self::OrchestrationIdNotFound(_) => ErrorCode::OrchestrationNotFound,
+            Self::UserOperationWaitTimeout(_) => ErrorCode::UserOperationTimeout,
+            Self::SponsorshipPolicyViolation(_) => ErrorCode::SponsorshipPolicyViolation,
+            Self::PaymentLinkNotFound(_) => ErrorCode::PaymentLinkNotFound,
+            Self::PaymentLinkNotPayable(_) | Self::PaymentTransactionError(_) => {
+                ErrorCode::PaymentFailed
+            }
+            Self::SubscriptionNotFound(_) => ErrorCode::SubscriptionNotFound,
+            Self::SubscriptionNotActive(_) => ErrorCode::SubscriptionNotActive,
+            Self::OnrampSessionNotFound(_) => ErrorCode::OnrampSessionNotFound,
+            Self::WebSocketError(_) | Self::WebSocketConnectionExpected => {
+                ErrorCode::WebsocketError
+            }
+            Self::WebSocketShuttingDown => ErrorCode::ShuttingDown,
+            _ => ErrorCode::InternalError,
+        }
+    }
 }
 
 impl IntoResponse for RpcError {
     fn into_response(self) -> axum::response::Response {
-        let response =  match &self {
-            Self::WebSocketError(err) => (StatusCode::GONE, err.to_string()).into_response(),
+        let code = self.code();
+        let mut response =  match &self {
+            Self::WebSocketError(err) => (
+                StatusCode::GONE,
+                Json(new_error_response(code, "".to_string(), err.to_string())),
+            )
+                .into_response(),
             Self::UnsupportedChain(chain_id) => (
                 StatusCode::BAD_REQUEST,
                 Json(new_error_response(
+                    code,
                     "chainId".to_string(),
                     format!("We don't support the chainId you provided: {chain_id}. See the list of supported chains here: https://docs.reown.com/cloud/blockchain-api#supported-chains"),
                 )),
@@ -298,6 +527,7 @@ impl IntoResponse for RpcError {
             Self::UnsupportedCurrency(error_message) => (
                     StatusCode::BAD_REQUEST,
                     Json(new_error_response(
+                        code,
                         "currency".to_string(),
                         format!("Unsupported currency: {error_message}."),
                     )),
@@ -306,6 +536,7 @@ impl IntoResponse for RpcError {
             Self::UnsupportedBundlerName(error_message) => (
                     StatusCode::BAD_REQUEST,
                     Json(new_error_response(
+                        code,
                         "bundler_name".to_string(),
                         format!("Unsupported bundler name: {error_message}."),
                     )),
@@ -314,6 +545,7 @@ impl IntoResponse for RpcError {
             Self::UnsupportedBundlerNameUrlParseError(error_message) => (
                     StatusCode::BAD_REQUEST,
                     Json(new_error_response(
+                        code,
                         "bundler_name".to_string(),
                         format!("Unsupported bundler name: {error_message}."),
                     )),
@@ -322,6 +554,7 @@ impl IntoResponse for RpcError {
             Self::CryptoUitlsError(e) => (
                 StatusCode::BAD_REQUEST,
                 Json(new_error_response(
+                    code,
                     "".to_string(),
                     format!("Crypto utils error: {e}"),
                 )),
@@ -330,6 +563,7 @@ impl IntoResponse for RpcError {
             Self::ChainTemporarilyUnavailable(chain_id) => (
                 StatusCode::SERVICE_UNAVAILABLE,
                 Json(new_error_response(
+                    code,
                     "chainId".to_string(),
                     format!("Requested {chain_id} chain provider is temporarily unavailable"),
                 )),
@@ -338,6 +572,7 @@ impl IntoResponse for RpcError {
             Self::BalanceTemporarilyUnavailable(namespace) => (
                 StatusCode::SERVICE_UNAVAILABLE,
                 Json(new_error_response(
+                    code,
                     "chainId".to_string(),
                     format!("Requested namespace {namespace} balance provider is temporarily unavailable"),
                 )),
@@ -346,6 +581,7 @@ impl IntoResponse for RpcError {
             Self::InvalidChainIdFormat(chain_id) => (
                     StatusCode::BAD_REQUEST,
                     Json(new_error_response(
+                        code,
                         "chainId".to_string(),
                         format!("Requested {chain_id} has invalid format for the requested namespace"),
                     )),
@@ -354,6 +590,7 @@ impl IntoResponse for RpcError {
             Self::UnsupportedProvider(provider) => (
                 StatusCode::BAD_REQUEST,
                 Json(new_error_response(
+                    code,
                     "provider".to_string(),
                     format!("Provider {provider} is not supported"),
                 )),
@@ -362,14 +599,206 @@ impl IntoResponse for RpcError {
             Self::UnsupportedBundler(bundler) => (
                 StatusCode::BAD_REQUEST,
                 Json(new_error_response(
+                    code,
                     "bundler".to_string(),
                     format!("Bundler {bundler} is not supported"),
                 )),
             )
                 .into_response(),
+            Self::UserOperationWaitTimeout(user_op_hash) => (
+                StatusCode::REQUEST_TIMEOUT,
+                Json(new_error_response(
+                    code,
+                    "userOpHash".to_string(),
+                    format!("User operation {user_op_hash} was not included within the timeout"),
+                )),
+            )
+                .into_response(),
+            Self::BundlerError(e) => (
+                StatusCode::BAD_GATEWAY,
+                Json(new_error_response(
+                    code,
+                    "bundler".to_string(),
+                    format!("Bundler error: {e}"),
+                )),
+            )
+                .into_response(),
+            Self::InvalidProjectJwt(e) => (
+                StatusCode::UNAUTHORIZED,
+                Json(new_error_response(
+                    code,
+                    "authorization".to_string(),
+                    format!("Invalid project JWT: {e}"),
+                )),
+            )
+                .into_response(),
+            Self::InvalidProjectSignature(e) => (
+                StatusCode::UNAUTHORIZED,
+                Json(new_error_response(
+                    code,
+                    "authorization".to_string(),
+                    format!("Invalid project request signature: {e}"),
+                )),
+            )
+                .into_response(),
+            Self::InvalidAdminToken => (
+                StatusCode::UNAUTHORIZED,
+                Json(new_error_response(
+                    code,
+                    "authorization".to_string(),
+                    "Invalid admin API token".to_string(),
+                )),
+            )
+                .into_response(),
+            Self::InvalidProfilerSecret => (
+                StatusCode::UNAUTHORIZED,
+                Json(new_error_response(
+                    code,
+                    "authorization".to_string(),
+                    "Invalid profiler secret".to_string(),
+                )),
+            )
+                .into_response(),
+            Self::ProfilingError(e) => (
+                StatusCode::SERVICE_UNAVAILABLE,
+                Json(new_error_response(code, "".to_string(), e)),
+            )
+                .into_response(),
+            Self::ChainNotAllowed(chain_id) => (
+                StatusCode::FORBIDDEN,
+                Json(new_error_response(
+                    code,
+                    "chainId".to_string(),
+                    format!("Chain {chain_id} is not in the project's allowed chains list"),
+                )),
+            )
+                .into_response(),
+            Self::OriginNotAllowed(origin) => (
+                StatusCode::FORBIDDEN,
+                Json(new_error_response(
+                    code,
+                    "origin".to_string(),
+                    format!("Origin {origin} is not in the project's allowed origins list"),
+                )),
+            )
+                .into_response(),
+            Self::ApplicationNotAllowed(app_id) => (
+                StatusCode::FORBIDDEN,
+                Json(new_error_response(
+                    code,
+                    "application".to_string(),
+                    format!(
+                        "Application {app_id} is not in the project's allowed bundle IDs or \
+                         package names list"
+                    ),
+                )),
+            )
+                .into_response(),
+            Self::IpNotAllowed(ip) => (
+                StatusCode::FORBIDDEN,
+                Json(new_error_response(
+                    code,
+                    "ip".to_string(),
+                    format!("IP address {ip} is not in the project's allowed IP ranges list"),
+                )),
+            )
+                .into_response(),
+            Self::CountryNotAllowed(country) => (
+                StatusCode::FORBIDDEN,
+                Json(new_error_response(
+                    code,
+                    "country".to_string(),
+                    format!("Requests from the country {country} are not allowed for the project"),
+                )),
+            )
+                .into_response(),
+            Self::RestrictedInCountry(target) => (
+                StatusCode::FORBIDDEN,
+                Json(new_error_response(
+                    code,
+                    "country".to_string(),
+                    format!("{target} is not available in your country"),
+                )),
+            )
+                .into_response(),
+            Self::RequestBodyTooLarge(limit) => (
+                StatusCode::PAYLOAD_TOO_LARGE,
+                Json(new_error_response(
+                    code,
+                    "body".to_string(),
+                    format!(
+                        "Request body exceeds the limit of {limit} bytes of the project's plan"
+                    ),
+                )),
+            )
+                .into_response(),
+            Self::SponsorshipPolicyViolation(e) => (
+                StatusCode::FORBIDDEN,
+                Json(new_error_response(
+                    code,
+                    "sponsorship".to_string(),
+                    format!("Sponsorship policy violation: {e}"),
+                )),
+            )
+                .into_response(),
+            Self::PaymentLinkNotFound(id) => (
+                StatusCode::NOT_FOUND,
+                Json(new_error_response(
+                    code,
+                    "id".to_string(),
+                    format!("Payment link is not found: {id}"),
+                )),
+            )
+                .into_response(),
+            Self::PaymentLinkNotPayable(e) => (
+                StatusCode::BAD_REQUEST,
+                Json(new_error_response(
+                    code,
+                    "paymentLink".to_string(),
+                    format!("Payment link is not payable: {e}"),
+                )),
+            )
+                .into_response(),
+            Self::PaymentTransactionError(e) => (
+                StatusCode::BAD_REQUEST,
+                Json(new_error_response(
+                    code,
+                    "transaction".to_string(),
+                    format!("Payment transaction error: {e}"),
+                )),
+            )
+                .into_response(),
+            Self::OnrampSessionNotFound(id) => (
+                StatusCode::NOT_FOUND,
+                Json(new_error_response(
+                    code,
+                    "id".to_string(),
+                    format!("Onramp session is not found: {id}"),
+                )),
+            )
+                .into_response(),
+            Self::SubscriptionNotFound(id) => (
+                StatusCode::NOT_FOUND,
+                Json(new_error_response(
+                    code,
+                    "id".to_string(),
+                    format!("Subscription is not found: {id}"),
+                )),
+            )
+                .into_response(),
+            Self::SubscriptionNotActive(id) => (
+                StatusCode::BAD_REQUEST,
+                Json(new_error_response(
+                    code,
+                    "id".to_string(),
+                    format!("Subscription is not active: {id}"),
+                )),
+            )
+                .into_response(),
             Self::IdentityProviderError(e) => (
                 StatusCode::SERVICE_UNAVAILABLE,
                 Json(new_error_response(
+                    code,
                     "".to_string(),
                     format!("We failed to reach the identity provider with an error: {e}"),
                 )),
@@ -378,6 +807,7 @@ impl IntoResponse for RpcError {
             Self::InvalidScheme => (
                 StatusCode::BAD_REQUEST,
                 Json(new_error_response(
+                    code,
                     "scheme".to_string(),
                     "Invalid scheme used. Try http(s):// or ws(s)://".to_string(),
                 )),
@@ -386,14 +816,25 @@ impl IntoResponse for RpcError {
             Self::WebSocketConnectionExpected => (
                 StatusCode::UPGRADE_REQUIRED,
                 Json(new_error_response(
+                    code,
                     "".to_string(),
                     "Only WebSocket connections are supported for GET method on this endpoint".to_string(),
                 )),
             )
                 .into_response(),
+            Self::WebSocketShuttingDown => (
+                StatusCode::SERVICE_UNAVAILABLE,
+                Json(new_error_response(
+                    code,
+                    "".to_string(),
+                    "Server is shutting down, reconnect to retry".to_string(),
+                )),
+            )
+                .into_response(),
             Self::RegistryError(_) | Self::Cerberus(_) | Self::ProjectDataError(_) => (
                 StatusCode::UNAUTHORIZED,
                 Json(new_error_response(
+                    code,
                     "authentication".to_string(),
                     "We failed to authenticate your request".to_string(),
                 )),
@@ -402,6 +843,7 @@ impl IntoResponse for RpcError {
             Self::TransportError(_) => (
                 StatusCode::SERVICE_UNAVAILABLE,
                 Json(new_error_response(
+                    code,
                     "transport".to_string(),
                     "We failed to reach the provider for your request".to_string(),
                 )),
@@ -410,6 +852,7 @@ impl IntoResponse for RpcError {
             Self::InvalidAddress => (
                 StatusCode::BAD_REQUEST,
                 Json(new_error_response(
+                    code,
                     "address".to_string(),
                     "The address provided is invalid".to_string(),
                 )),
@@ -418,14 +861,28 @@ impl IntoResponse for RpcError {
             Self::QuotaLimitReached => (
                 StatusCode::TOO_MANY_REQUESTS,
                 Json(new_error_response(
+                    code,
                     "address".to_string(),
                     "Project's quota limit reached".to_string(),
                 )),
             )
                 .into_response(),
+            Self::PlanLimitReached(_) => (
+                StatusCode::TOO_MANY_REQUESTS,
+                Json(new_error_response(
+                    code,
+                    "projectId".to_string(),
+                    format!(
+                        "Project's plan limit reached, upgrade the plan at {PLAN_UPGRADE_URL} to \
+                         increase the limits"
+                    ),
+                )),
+            )
+                .into_response(),
             Self::InvalidParameter(e) => (
                 StatusCode::BAD_REQUEST,
                 Json(new_error_response(
+                    code,
                     "".to_string(),
                     format!("Invalid parameter: {e}"),
                 )),
@@ -436,6 +893,7 @@ impl IntoResponse for RpcError {
             Self::InvalidNameFormat(e) => (
                 StatusCode::BAD_REQUEST,
                 Json(new_error_response(
+                    code,
                     "name".to_string(),
                     format!("Invalid name format: {e}"),
                 )),
@@ -444,6 +902,7 @@ impl IntoResponse for RpcError {
             Self::InvalidNameLength(e) => (
                 StatusCode::BAD_REQUEST,
                 Json(new_error_response(
+                    code,
                     "name".to_string(),
                     format!("Invalid name length: {e}"),
                 )),
@@ -452,6 +911,7 @@ impl IntoResponse for RpcError {
             Self::InvalidNameZone(e) => (
                 StatusCode::BAD_REQUEST,
                 Json(new_error_response(
+                    code,
                     "name".to_string(),
                     format!("Name is not in the allowed zones: {e}"),
                 )),
@@ -460,6 +920,7 @@ impl IntoResponse for RpcError {
             Self::ConversionInvalidParameter(e) => (
                     StatusCode::BAD_REQUEST,
                     Json(new_error_response(
+                        code,
                         "".to_string(),
                         format!("Conversion parameter error: {e}"),
                     )),
@@ -476,14 +937,25 @@ impl IntoResponse for RpcError {
             Self::AssetNotSupported(e) => (
                 StatusCode::BAD_REQUEST,
                 Json(new_error_response(
+                    code,
                     "asset".to_string(),
                     format!("Asset is not supported: {e}"),
                 )),
             )
                 .into_response(),
+            Self::TokenMetadataNotFound(asset) => (
+                StatusCode::NOT_FOUND,
+                Json(new_error_response(
+                    code,
+                    "caip19".to_string(),
+                    format!("Token metadata is not found for the asset {asset}"),
+                )),
+            )
+                .into_response(),
             Self::UnsupportedCoinType(e) => (
                 StatusCode::BAD_REQUEST,
                 Json(new_error_response(
+                    code,
                     "coin_type".to_string(),
                     format!("Unsupported coin type: {e}"),
                 )),
@@ -492,6 +964,7 @@ impl IntoResponse for RpcError {
                 Self::UnsupportedNamespace(e) => (
                     StatusCode::BAD_REQUEST,
                     Json(new_error_response(
+                        code,
                         "address".to_string(),
                         format!("Unsupported namespace: {e}"),
                     )),
@@ -500,6 +973,7 @@ impl IntoResponse for RpcError {
             Self::UnsupportedNameAttribute => (
                 StatusCode::BAD_REQUEST,
                 Json(new_error_response(
+                    code,
                     "attributes".to_string(),
                     "Unsupported name attribute in payload".to_string(),
                 )),
@@ -508,6 +982,7 @@ impl IntoResponse for RpcError {
             Self::NameAlreadyRegistered(e) => (
                 StatusCode::BAD_REQUEST,
                 Json(new_error_response(
+                    code,
                     "name".to_string(),
                     format!("Name is already registered: {e}"),
                 )),
@@ -516,6 +991,7 @@ impl IntoResponse for RpcError {
             Self::NameNotRegistered(e) => (
                 StatusCode::BAD_REQUEST,
                 Json(new_error_response(
+                    code,
                     "name".to_string(),
                     format!("Name is not registered: {e}"),
                 )),
@@ -524,6 +1000,7 @@ impl IntoResponse for RpcError {
             Self::NameNotFound(e) => (
                 StatusCode::NOT_FOUND,
                 Json(new_error_response(
+                    code,
                     "name".to_string(),
                     format!("Name is not found in the database: {e}"),
                 )),
@@ -532,6 +1009,7 @@ impl IntoResponse for RpcError {
             Self::NameByAddressNotFound => (
                 StatusCode::NOT_FOUND,
                 Json(new_error_response(
+                    code,
                     "address".to_string(),
                     "No name for address is found".into(),
                 )),
@@ -540,6 +1018,7 @@ impl IntoResponse for RpcError {
             Self::ExpiredTimestamp(e) => (
                 StatusCode::BAD_REQUEST,
                 Json(new_error_response(
+                    code,
                     "timestamp".to_string(),
                     format!("Signature UNIXTIME timestamp is too old: {e}"),
                 )),
@@ -548,6 +1027,7 @@ impl IntoResponse for RpcError {
             Self::SignatureValidationError(e) => (
                 StatusCode::UNAUTHORIZED,
                 Json(new_error_response(
+                    code,
                     "signature".to_string(),
                     format!("Signature validation error: {e}"),
                 )),
@@ -556,6 +1036,7 @@ impl IntoResponse for RpcError {
             Self::NameOwnerValidationError => (
                 StatusCode::UNAUTHORIZED,
                 Json(new_error_response(
+                    code,
                     "address".to_string(),
                     "Name owner validation error".into(),
                 )),
@@ -564,6 +1045,7 @@ impl IntoResponse for RpcError {
             Self::SerdeJson(e) => (
                 StatusCode::UNPROCESSABLE_ENTITY,
                 Json(new_error_response(
+                    code,
                     "".to_string(),
                     format!("Deserialization error: {e}"),
                 )),
@@ -571,7 +1053,9 @@ impl IntoResponse for RpcError {
                 .into_response(),
             Self::RateLimited(e) => (
                 StatusCode::TOO_MANY_REQUESTS,
+                e.status.headers(chrono::Utc::now().timestamp() as u64),
                 Json(new_error_response(
+                    code,
                     "rate_limited".to_string(),
                     format!("Requests per second limit exceeded: {e}"),
                 )),
@@ -585,6 +1069,7 @@ impl IntoResponse for RpcError {
                 (
                 StatusCode::BAD_REQUEST,
                 Json(new_error_response(
+                    code,
                     "pci".to_string(),
                     format!("Permission for PCI is not found: {pci}"),
                 )),
@@ -594,6 +1079,7 @@ impl IntoResponse for RpcError {
             Self::RevokedPermission(pci) => (
                 StatusCode::BAD_REQUEST,
                 Json(new_error_response(
+                    code,
                     "pci".to_string(),
                     format!("Permission is revoked: {pci}"),
                 )),
@@ -602,6 +1088,7 @@ impl IntoResponse for RpcError {
             Self::PermissionExpired(pci) => (
                 StatusCode::BAD_REQUEST,
                 Json(new_error_response(
+                    code,
                     "pci".to_string(),
                     format!("Permission is expired: {pci}"),
                 )),
@@ -610,6 +1097,7 @@ impl IntoResponse for RpcError {
             Self::WrongBase64Format(e) => (
                 StatusCode::BAD_REQUEST,
                 Json(new_error_response(
+                    code,
                     "".to_string(),
                     format!("Wrong Base64 format: {e}"),
                 )),
@@ -618,6 +1106,7 @@ impl IntoResponse for RpcError {
             Self::KeyFormatError(e) => (
                 StatusCode::BAD_REQUEST,
                 Json(new_error_response(
+                    code,
                     "key".to_string(),
                     format!("Invalid key format: {e}"),
                 )),
@@ -626,6 +1115,7 @@ impl IntoResponse for RpcError {
             Self::SignatureFormatError(e) => (
                 StatusCode::BAD_REQUEST,
                 Json(new_error_response(
+                    code,
                     "signature".to_string(),
                     format!("Invalid signature format: {e}"),
                 )),
@@ -634,6 +1124,7 @@ impl IntoResponse for RpcError {
             Self::CoSignerEmptyPermissions => (
                 StatusCode::BAD_REQUEST,
                 Json(new_error_response(
+                    code,
                     "".to_string(),
                     "Permissions set is empty".to_string(),
                 )),
@@ -642,6 +1133,7 @@ impl IntoResponse for RpcError {
             Self::AbiDecodingError(e) => (
                 StatusCode::BAD_REQUEST,
                 Json(new_error_response(
+                    code,
                     "calldata".to_string(),
                     format!("ABI signature decoding error: {e}"),
                 )),
@@ -650,6 +1142,7 @@ impl IntoResponse for RpcError {
             Self::TransactionProviderError => (
                 StatusCode::SERVICE_UNAVAILABLE,
                 Json(new_error_response(
+                    code,
                     "".to_string(),
                     "Transaction provider is temporarily unavailable".to_string(),
                 )),
@@ -658,6 +1151,7 @@ impl IntoResponse for RpcError {
             Self::OnRampProviderError => (
                     StatusCode::SERVICE_UNAVAILABLE,
                     Json(new_error_response(
+                        code,
                         "".to_string(),
                         "OnRamp provider is temporarily unavailable".to_string(),
                     )),
@@ -666,6 +1160,7 @@ impl IntoResponse for RpcError {
             Self::PortfolioProviderError => (
                 StatusCode::SERVICE_UNAVAILABLE,
                 Json(new_error_response(
+                    code,
                     "".to_string(),
                     "Portfolio provider is temporarily unavailable".to_string(),
                 )),
@@ -674,6 +1169,7 @@ impl IntoResponse for RpcError {
             Self::BalanceProviderError => (
                 StatusCode::SERVICE_UNAVAILABLE,
                 Json(new_error_response(
+                    code,
                     "".to_string(),
                     "Balance provider is temporarily unavailable".to_string(),
                 )),
@@ -682,6 +1178,7 @@ impl IntoResponse for RpcError {
             Self::FungiblePriceProviderError(e) => (
                 StatusCode::SERVICE_UNAVAILABLE,
                 Json(new_error_response(
+                    code,
                     "".to_string(),
                     format!("Fungibles price provider is temporarily unavailable: {e}"),
                 )),
@@ -690,6 +1187,7 @@ impl IntoResponse for RpcError {
             Self::ConversionProviderError => (
                 StatusCode::SERVICE_UNAVAILABLE,
                 Json(new_error_response(
+                    code,
                     "".to_string(),
                     "Convertion provider is temporarily unavailable".to_string(),
                 )),
@@ -698,6 +1196,7 @@ impl IntoResponse for RpcError {
             Self::SimulationProviderUnavailable => (
                 StatusCode::SERVICE_UNAVAILABLE,
                 Json(new_error_response(
+                    code,
                     "".to_string(),
                     "Simulation provider is temporarily unavailable".to_string(),
                 )),
@@ -706,6 +1205,7 @@ impl IntoResponse for RpcError {
             Self::CosignerPermissionDenied(e) => (
                     StatusCode::UNAUTHORIZED,
                     Json(new_error_response(
+                        code,
                         "".to_string(),
                         format!("Cosigner permission denied: {e}"),
                     )),
@@ -714,28 +1214,50 @@ impl IntoResponse for RpcError {
             Self::CosignerUnsupportedPermission(e) => (
                 StatusCode::UNAUTHORIZED,
                 Json(new_error_response(
+                    code,
                     "".to_string(),
                     format!("Unsupported permission in CoSigner: {e}"),
                 )),
             )
                 .into_response(),
+            Self::CosignerUnsupportedPolicy(e) => (
+                StatusCode::UNAUTHORIZED,
+                Json(new_error_response(
+                    code,
+                    "".to_string(),
+                    format!("Unsupported policy in CoSigner: {e}"),
+                )),
+            )
+                .into_response(),
             Self::OrchestrationIdNotFound(id) => (
                 StatusCode::BAD_REQUEST,
                 Json(new_error_response(
+                    code,
                     "orchestrationId".to_string(),
                     format!("Orchestration ID is not found: {id}"),
                 )),
             )
                 .into_response(),
-            Self::RouteSolana(e) => e.into_response(),
+            Self::RouteSolana(RouteSolanaError::Request(e)) => (
+                StatusCode::BAD_REQUEST,
+                Json(new_error_response(code, "".to_string(), e.to_string())),
+            )
+                .into_response(),
             // Any other errors considering as 500
             _ => (
                 StatusCode::INTERNAL_SERVER_ERROR,
-                "Internal server error".to_string(),
+                Json(new_error_response(
+                    code,
+                    "".to_string(),
+                    "Internal server error".to_string(),
+                )),
             )
                 .into_response(),
         };
 
+        // Error code for the problem details middleware
+        response.extensions_mut().insert(code);
+
         // Log the server errors response status based on the status code
         match response.status() {
             StatusCode::INTERNAL_SERVER_ERROR => {
@@ -751,26 +1273,28 @@ impl IntoResponse for RpcError {
     }
 }
 
-#[derive(serde::Serialize)]
+#[derive(serde::Serialize, serde::Deserialize)]
 pub struct ErrorReason {
     pub field: String,
     pub description: String,
 }
 
-#[derive(serde::Serialize)]
+#[derive(serde::Serialize, serde::Deserialize)]
 pub struct ErrorResponse {
     pub status: String,
+    pub code: ErrorCode,
     pub reasons: Vec<ErrorReason>,
 }
 
-pub fn new_error_response(field: String, description: String) -> ErrorResponse {
+pub fn new_error_response(code: ErrorCode, field: String, description: String) -> ErrorResponse {
     ErrorResponse {
         status: "FAILED".to_string(),
+        code,
         reasons: vec![ErrorReason { field, description }],
     }
 }
 
-#[derive(serde::Serialize)]
+#[derive(serde::Serialize, serde::Deserialize)]
 pub struct ErrorResponseWithCode {
     pub code: String,
     pub message: String,
@@ -779,3 +1303,106 @@ pub struct ErrorResponseWithCode {
 pub fn new_error_response_with_code(code: String, message: String) -> ErrorResponseWithCode {
     ErrorResponseWithCode { code, message }
 }
+
+/// RFC 7807 problem details, served instead of the legacy error response to
+/// the clients accepting the `application/problem+json`
+#[derive(Debug, PartialEq, Eq, serde::Serialize)]
+pub struct ProblemDetails {
+    #[serde(rename = "type")]
+    pub problem_type: String,
+    pub title: String,
+    pub status: u16,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+    pub instance: String,
+    pub code: ErrorCode,
+}
+
+impl ProblemDetails {
+    /// Builds the problem details from the legacy error response body
+    pub fn new(status: StatusCode, code: ErrorCode, body: &[u8], instance: String) -> Self {
+        let detail = if let Ok(error) = serde_json::from_slice::<ErrorResponse>(body) {
+            Some(
+                error
+                    .reasons
+                    .into_iter()
+                    .map(|reason| reason.description)
+                    .collect::<Vec<_>>()
+                    .join("; "),
+            )
+        } else if let Ok(error) = serde_json::from_slice::<ErrorResponseWithCode>(body) {
+            Some(error.message)
+        } else {
+            None
+        };
+        Self {
+            problem_type: format!("{PROBLEM_TYPE_PREFIX}{}", code.as_ref()),
+            title: status
+                .canonical_reason()
+                .unwrap_or("Unknown error")
+                .to_owned(),
+            status: status.as_u16(),
+            detail: detail.filter(|detail| !detail.is_empty()),
+            instance,
+            code,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn problem_details_from_error_response() {
+        let body = serde_json::to_vec(&new_error_response(
+            ErrorCode::ChainUnsupported,
+            "chainId".to_owned(),
+            "Unsupported chain".to_owned(),
+        ))
+        .unwrap();
+        let problem = ProblemDetails::new(
+            StatusCode::BAD_REQUEST,
+            ErrorCode::ChainUnsupported,
+            &body,
+            "/v1".to_owned(),
+        );
+        assert_eq!(
+            serde_json::to_value(&problem).unwrap(),
+            serde_json::json!({
+                "type": "urn:reown:blockchain-api:error:CHAIN_UNSUPPORTED",
+                "title": "Bad Request",
+                "status": 400,
+                "detail": "Unsupported chain",
+                "instance": "/v1",
+                "code": "CHAIN_UNSUPPORTED",
+            })
+        );
+
+        let problem = ProblemDetails::new(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            ErrorCode::InternalError,
+            b"Internal server error",
+            "/v1".to_owned(),
+        );
+        assert_eq!(problem.detail, None);
+        assert_eq!(problem.title, "Internal Server Error");
+    }
+
+    #[tokio::test]
+    async fn plan_limit_reached_response() {
+        let response = RpcError::PlanLimitReached("free".to_owned()).into_response();
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body = serde_json::from_slice::<serde_json::Value>(&body).unwrap();
+        assert_eq!(body["code"], "QUOTA_EXCEEDED");
+        assert_eq!(body["reasons"][0]["field"], "projectId");
+        assert!(body["reasons"][0]["description"]
+            .as_str()
+            .unwrap()
+            .contains(PLAN_UPGRADE_URL));
+    }
+}
@@ -0,0 +1,478 @@
+//! gRPC interface of the proxy, balance, history and identity endpoints.
+
+use {
+    crate::handlers::{
+        balance::{BalanceItem, BalanceResponseBody},
+        history::{
+            HistoryResponseBody, HistoryTransaction, HistoryTransactionNFTInfo,
+            HistoryTransactionTransfer, HistoryTransactionURLandContentTypeItem,
+        },
+        identity::IdentityResponse as IdentityResponseBody,
+    },
+    axum::{
+        body::{to_bytes, Body, Bytes},
+        extract::ConnectInfo,
+        http::{header, HeaderMap, HeaderName, HeaderValue, Method, Request, StatusCode},
+        Router,
+    },
+    proto::{
+        blockchain_api_server::{BlockchainApi, BlockchainApiServer},
+        BalanceRequest, BalanceResponse, HistoryRequest, HistoryResponse, IdentityRequest,
+        IdentityResponse, ProxyRequest, ProxyResponse,
+    },
+    serde::de::DeserializeOwned,
+    std::{future::Future, net::SocketAddr},
+    tonic::{Code, Status},
+    tower::ServiceExt,
+    tracing::info,
+    url::{Position, Url},
+};
+
+pub mod proto {
+    #![allow(clippy::all)]
+    tonic::include_proto!("blockchain_api.v1");
+}
+
+/// Maximum size of the handlers responses
+const RESPONSE_BODY_MAX_BYTES: usize = 32 * 1024 * 1024;
+const X_FORWARDED_FOR: HeaderName = HeaderName::from_static("x-forwarded-for");
+
+/// Serve the gRPC interface until the shutdown signal
+pub async fn serve(
+    router: Router,
+    addr: SocketAddr,
+    shutdown: impl Future<Output = ()>,
+) -> Result<(), std::io::Error> {
+    info!("Starting gRPC server on {addr}");
+    tonic::transport::Server::builder()
+        .add_service(BlockchainApiServer::new(BlockchainApiService { router }))
+        .serve_with_shutdown(addr, shutdown)
+        .await
+        .map_err(std::io::Error::other)
+}
+
+pub struct BlockchainApiService {
+    router: Router,
+}
+
+/// Request metadata passed to the HTTP handlers
+struct RequestContext {
+    headers: HeaderMap,
+    remote_addr: SocketAddr,
+}
+
+impl RequestContext {
+    fn from_request<T>(request: tonic::Request<T>) -> (Self, T) {
+        let peer_addr = request.remote_addr();
+        let remote_addr = peer_addr.unwrap_or_else(|| SocketAddr::from(([0, 0, 0, 0], 0)));
+        let (metadata, _, message) = request.into_parts();
+        let mut headers = HeaderMap::new();
+        for (name, value) in metadata.into_headers().iter() {
+            if !is_grpc_protocol_header(name) && !is_client_address_header(name) {
+                headers.append(name.clone(), value.clone());
+            }
+        }
+        // The client address is the connection peer, the handlers read it
+        // from the forwarded header
+        if let Some(peer_addr) = peer_addr {
+            headers.insert(
+                X_FORWARDED_FOR,
+                HeaderValue::from_str(&peer_addr.ip().to_string())
+                    .expect("IP address is a valid header value"),
+            );
+        }
+        (
+            Self {
+                headers,
+                remote_addr,
+            },
+            message,
+        )
+    }
+}
+
+/// gRPC protocol headers are not passed to the HTTP handlers
+fn is_grpc_protocol_header(name: &HeaderName) -> bool {
+    *name == header::CONTENT_TYPE || *name == header::TE || name.as_str().starts_with("grpc-")
+}
+
+/// Client address and geolocation headers are set by the load balancer for
+/// the HTTP clients only, the gRPC clients metadata can't be trusted for them
+fn is_client_address_header(name: &HeaderName) -> bool {
+    let name = name.as_str();
+    matches!(
+        name,
+        "x-forwarded-for" | "forwarded" | "x-real-ip" | "true-client-ip"
+    ) || name.starts_with("cf-")
+        || name.starts_with("cloudfront-viewer-")
+}
+
+/// Endpoint URL with the percent-encoded path segments and the query
+/// parameters, the parameters without the value are skipped
+fn endpoint_url(path: &[&str], query: &[(&str, Option<&str>)]) -> Url {
+    let mut url = Url::parse("http://localhost").expect("valid base URL");
+    url.path_segments_mut()
+        .expect("base URL")
+        .pop_if_empty()
+        .extend(path);
+    {
+        let mut query_pairs = url.query_pairs_mut();
+        for (name, value) in query {
+            if let Some(value) = value {
+                query_pairs.append_pair(name, value);
+            }
+        }
+    }
+    url
+}
+
+/// gRPC status code of the handler error response status
+fn status_code(status: StatusCode) -> Code {
+    match status {
+        StatusCode::BAD_REQUEST | StatusCode::UNPROCESSABLE_ENTITY => Code::InvalidArgument,
+        StatusCode::UNAUTHORIZED => Code::Unauthenticated,
+        StatusCode::FORBIDDEN => Code::PermissionDenied,
+        StatusCode::NOT_FOUND => Code::NotFound,
+        StatusCode::TOO_MANY_REQUESTS => Code::ResourceExhausted,
+        StatusCode::NOT_IMPLEMENTED => Code::Unimplemented,
+        StatusCode::BAD_GATEWAY | StatusCode::SERVICE_UNAVAILABLE => Code::Unavailable,
+        StatusCode::REQUEST_TIMEOUT | StatusCode::GATEWAY_TIMEOUT => Code::DeadlineExceeded,
+        _ => Code::Internal,
+    }
+}
+
+fn decode<T: DeserializeOwned>(body: &[u8]) -> Result<T, Status> {
+    serde_json::from_slice(body)
+        .map_err(|e| Status::internal(format!("Failed to decode the handler response: {e}")))
+}
+
+impl BlockchainApiService {
+    /// Dispatch the request to the HTTP router, returns the successful
+    /// response body
+    async fn dispatch(
+        &self,
+        context: RequestContext,
+        method: Method,
+        url: &Url,
+        body: Body,
+    ) -> Result<Bytes, Status> {
+        let mut request = Request::builder()
+            .method(method)
+            .uri(&url[Position::BeforePath..])
+            .body(body)
+            .map_err(|e| Status::invalid_argument(e.to_string()))?;
+        *request.headers_mut() = context.headers;
+        request
+            .extensions_mut()
+            .insert(ConnectInfo(context.remote_addr));
+
+        let response = match self.router.clone().oneshot(request).await {
+            Ok(response) => response,
+            Err(infallible) => match infallible {},
+        };
+        let status = response.status();
+        let body = to_bytes(response.into_body(), RESPONSE_BODY_MAX_BYTES)
+            .await
+            .map_err(|e| Status::internal(format!("Failed to read the handler response: {e}")))?;
+        if status.is_success() {
+            Ok(body)
+        } else {
+            Err(Status::new(
+                status_code(status),
+                String::from_utf8_lossy(&body),
+            ))
+        }
+    }
+}
+
+#[tonic::async_trait]
+impl BlockchainApi for BlockchainApiService {
+    async fn proxy(
+        &self,
+        request: tonic::Request<ProxyRequest>,
+    ) -> Result<tonic::Response<ProxyResponse>, Status> {
+        let (mut context, request) = RequestContext::from_request(request);
+        let url = endpoint_url(
+            &["v1"],
+            &[
+                ("chainId", Some(request.chain_id.as_str())),
+                ("projectId", Some(request.project_id.as_str())),
+                ("providerId", request.provider_id.as_deref()),
+            ],
+        );
+        context.headers.insert(
+            header::CONTENT_TYPE,
+            HeaderValue::from_static("application/json"),
+        );
+        let body = self
+            .dispatch(context, Method::POST, &url, Body::from(request.body))
+            .await?;
+        Ok(tonic::Response::new(ProxyResponse {
+            body: body.to_vec(),
+        }))
+    }
+
+    async fn get_balance(
+        &self,
+        request: tonic::Request<BalanceRequest>,
+    ) -> Result<tonic::Response<BalanceResponse>, Status> {
+        let (context, request) = RequestContext::from_request(request);
+        let url = endpoint_url(
+            &["v1", "account", &request.address, "balance"],
+            &[
+                ("projectId", Some(request.project_id.as_str())),
+                ("currency", Some(request.currency.as_str())),
+                ("chainId", request.chain_id.as_deref()),
+                ("forceUpdate", request.force_update.as_deref()),
+                ("tokens", request.tokens.as_deref()),
+            ],
+        );
+        let body = self
+            .dispatch(context, Method::GET, &url, Body::empty())
+            .await?;
+        let response = decode::<BalanceResponseBody>(&body)?;
+        Ok(tonic::Response::new(response.into()))
+    }
+
+    async fn get_history(
+        &self,
+        request: tonic::Request<HistoryRequest>,
+    ) -> Result<tonic::Response<HistoryResponse>, Status> {
+        let (context, request) = RequestContext::from_request(request);
+        let include_onramp = request
+            .include_onramp
+            .map(|include_onramp| include_onramp.to_string());
+        let url = endpoint_url(
+            &["v1", "account", &request.address, "history"],
+            &[
+                ("projectId", Some(request.project_id.as_str())),
+                ("currency", request.currency.as_deref()),
+                ("chainId", request.chain_id.as_deref()),
+                ("cursor", request.cursor.as_deref()),
+                ("onramp", request.onramp.as_deref()),
+                ("includeOnramp", include_onramp.as_deref()),
+            ],
+        );
+        let body = self
+            .dispatch(context, Method::GET, &url, Body::empty())
+            .await?;
+        let response = decode::<HistoryResponseBody>(&body)?;
+        Ok(tonic::Response::new(response.into()))
+    }
+
+    async fn get_identity(
+        &self,
+        request: tonic::Request<IdentityRequest>,
+    ) -> Result<tonic::Response<IdentityResponse>, Status> {
+        let (context, request) = RequestContext::from_request(request);
+        let use_cache = request.use_cache.map(|use_cache| use_cache.to_string());
+        let url = endpoint_url(
+            &["v1", "identity", &request.address],
+            &[
+                ("projectId", Some(request.project_id.as_str())),
+                ("useCache", use_cache.as_deref()),
+            ],
+        );
+        let body = self
+            .dispatch(context, Method::GET, &url, Body::empty())
+            .await?;
+        let response = decode::<IdentityResponseBody>(&body)?;
+        Ok(tonic::Response::new(IdentityResponse {
+            name: response.name,
+            avatar: response.avatar,
+        }))
+    }
+}
+
+impl From<BalanceResponseBody> for BalanceResponse {
+    fn from(body: BalanceResponseBody) -> Self {
+        Self {
+            balances: body.balances.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl From<BalanceItem> for proto::Balance {
+    fn from(item: BalanceItem) -> Self {
+        Self {
+            name: item.name,
+            symbol: item.symbol,
+            chain_id: item.chain_id,
+            address: item.address,
+            value: item.value,
+            price: item.price,
+            quantity: Some(proto::BalanceQuantity {
+                decimals: item.quantity.decimals,
+                numeric: item.quantity.numeric,
+            }),
+            icon_url: item.icon_url,
+        }
+    }
+}
+
+impl From<HistoryResponseBody> for HistoryResponse {
+    fn from(body: HistoryResponseBody) -> Self {
+        Self {
+            data: body.data.into_iter().map(Into::into).collect(),
+            next: body.next,
+        }
+    }
+}
+
+impl From<HistoryTransaction> for proto::Transaction {
+    fn from(transaction: HistoryTransaction) -> Self {
+        let metadata = transaction.metadata;
+        Self {
+            id: transaction.id,
+            metadata: Some(proto::TransactionMetadata {
+                operation_type: metadata.operation_type,
+                hash: metadata.hash,
+                mined_at: metadata.mined_at,
+                sent_from: metadata.sent_from,
+                sent_to: metadata.sent_to,
+                status: metadata.status,
+                nonce: metadata.nonce as u64,
+                application: metadata.application.map(|application| {
+                    proto::TransactionApplication {
+                        name: application.name,
+                        icon_url: application.icon_url,
+                    }
+                }),
+                chain: metadata.chain,
+            }),
+            transfers: transaction
+                .transfers
+                .unwrap_or_default()
+                .into_iter()
+                .map(Into::into)
+                .collect(),
+            onramp: transaction.onramp.map(|onramp| proto::TransactionOnramp {
+                provider: onramp.provider,
+                status: onramp.status,
+                fiat_amount: onramp.fiat_amount.map(|fiat_amount| proto::FiatAmount {
+                    value: fiat_amount.value,
+                    currency: fiat_amount.currency,
+                }),
+            }),
+        }
+    }
+}
+
+impl From<HistoryTransactionTransfer> for proto::TransactionTransfer {
+    fn from(transfer: HistoryTransactionTransfer) -> Self {
+        Self {
+            fungible_info: transfer
+                .fungible_info
+                .map(|fungible_info| proto::FungibleInfo {
+                    name: fungible_info.name,
+                    symbol: fungible_info.symbol,
+                    icon_url: fungible_info.icon.map(|icon| icon.url),
+                }),
+            nft_info: transfer.nft_info.map(Into::into),
+            direction: transfer.direction,
+            quantity: transfer.quantity.numeric,
+            value: transfer.value,
+            price: transfer.price,
+        }
+    }
+}
+
+impl From<HistoryTransactionNFTInfo> for proto::NftInfo {
+    fn from(nft_info: HistoryTransactionNFTInfo) -> Self {
+        Self {
+            name: nft_info.name,
+            content: nft_info.content.map(|content| proto::NftContent {
+                preview: content.preview.map(Into::into),
+                detail: content.detail.map(Into::into),
+            }),
+            is_spam: nft_info.flags.is_spam,
+        }
+    }
+}
+
+impl From<HistoryTransactionURLandContentTypeItem> for proto::NftContentItem {
+    fn from(item: HistoryTransactionURLandContentTypeItem) -> Self {
+        Self {
+            url: item.url,
+            content_type: item.content_type,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builds_endpoint_url() {
+        let url = endpoint_url(
+            &["v1", "account", "0x1/../x", "balance"],
+            &[
+                ("projectId", Some("project")),
+                ("currency", Some("usd")),
+                ("chainId", None),
+                ("forceUpdate", Some("eip155:1:0x1,eip155:10:0x2")),
+            ],
+        );
+        assert_eq!(
+            &url[Position::BeforePath..],
+            "/v1/account/0x1%2F..%2Fx/balance?projectId=project&currency=usd&forceUpdate=eip155%3A1%3A0x1%2Ceip155%3A10%3A0x2"
+        );
+    }
+
+    #[test]
+    fn maps_status_codes() {
+        assert_eq!(status_code(StatusCode::BAD_REQUEST), Code::InvalidArgument);
+        assert_eq!(status_code(StatusCode::UNAUTHORIZED), Code::Unauthenticated);
+        assert_eq!(status_code(StatusCode::FORBIDDEN), Code::PermissionDenied);
+        assert_eq!(
+            status_code(StatusCode::TOO_MANY_REQUESTS),
+            Code::ResourceExhausted
+        );
+        assert_eq!(
+            status_code(StatusCode::GATEWAY_TIMEOUT),
+            Code::DeadlineExceeded
+        );
+        assert_eq!(
+            status_code(StatusCode::INTERNAL_SERVER_ERROR),
+            Code::Internal
+        );
+    }
+
+    #[test]
+    fn skips_grpc_protocol_and_client_address_headers() {
+        let mut metadata = tonic::metadata::MetadataMap::new();
+        metadata.insert("grpc-timeout", "1S".parse().unwrap());
+        metadata.insert("content-type", "application/grpc".parse().unwrap());
+        metadata.insert("origin", "https://example.com".parse().unwrap());
+        metadata.insert("x-forwarded-for", "203.0.113.7".parse().unwrap());
+        metadata.insert("forwarded", "for=203.0.113.7".parse().unwrap());
+        metadata.insert("x-real-ip", "203.0.113.7".parse().unwrap());
+        metadata.insert("cf-ipcountry", "US".parse().unwrap());
+        let request = tonic::Request::from_parts(metadata, Default::default(), ());
+
+        let (context, _) = RequestContext::from_request(request);
+        assert_eq!(context.headers.len(), 1);
+        assert_eq!(context.headers[header::ORIGIN], "https://example.com");
+        assert!(!context.headers.contains_key("x-forwarded-for"));
+        assert_eq!(context.remote_addr, SocketAddr::from(([0, 0, 0, 0], 0)));
+    }
+
+    #[test]
+    fn forwards_peer_address() {
+        let peer_addr = SocketAddr::from(([198, 51, 100, 1], 50051));
+        let mut metadata = tonic::metadata::MetadataMap::new();
+        metadata.insert("x-forwarded-for", "203.0.113.7".parse().unwrap());
+        let mut request = tonic::Request::from_parts(metadata, Default::default(), ());
+        request
+            .extensions_mut()
+            .insert(tonic::transport::server::TcpConnectInfo {
+                local_addr: None,
+                remote_addr: Some(peer_addr),
+            });
+
+        let (context, _) = RequestContext::from_request(request);
+        assert_eq!(context.remote_addr, peer_addr);
+        assert_eq!(context.headers["x-forwarded-for"], "198.51.100.1");
+    }
+}
@@ -8,18 +8,27 @@ use {
         providers::TokenMetadataCacheProvider,
         state::AppState,
         storage::{error::StorageError, KeyValueStorage},
-        utils::{crypto, network},
+        token_metadata_backfill,
+        utils::{
+            crypto::{self, Caip19Asset, SOLANA_NATIVE_TOKEN_ADDRESS},
+            network,
+        },
     },
+    async_graphql::SimpleObject,
     async_trait::async_trait,
     axum::{
         extract::{ConnectInfo, Path, Query, State},
         Json,
     },
-    deadpool_redis::{redis::AsyncCommands, Pool},
-    ethers::{abi::Address, types::H160},
+    deadpool_redis::{
+        redis::{self, AsyncCommands},
+        Pool,
+    },
+    ethers::{abi::Address, types::H160, utils::to_checksum},
+    futures_util::future::join_all,
     hyper::HeaderMap,
     serde::{Deserialize, Serialize},
-    std::{net::SocketAddr, sync::Arc, time::Duration},
+    std::{net::SocketAddr, str::FromStr, sync::Arc, time::Duration},
     tap::TapFallible,
     tracing::log::{debug, error},
     wc::metrics::{future_metrics, FutureExt},
@@ -30,7 +39,12 @@ pub const H160_EMPTY_ADDRESS: H160 = H160::repeat_byte(0xee);
 
 const PROVIDER_MAX_CALLS: usize = 2;
 const METADATA_CACHE_TTL: u64 = 60 * 60 * 24; // 1 day
-const BALANCE_CACHE_TTL: Duration = Duration::from_secs(10); // 10 seconds
+/// Asset namespace of the native tokens in the CAIP-19 asset IDs
+const NATIVE_ASSET_NAMESPACE: &str = "slip44";
+/// Asset namespace of the ERC-20 tokens in the CAIP-19 asset IDs
+pub const ERC20_ASSET_NAMESPACE: &str = "erc20";
+/// Maximum number of the assets in the balance tokens filter
+const MAX_FILTER_TOKENS: usize = 20;
 
 // List of SDK versions that should return an empty balance response
 // to fix the issue of redundant calls in SDK versions
@@ -41,6 +55,14 @@ pub struct Config {
     /// List of project ids that are not allowed to use the balance RPC call
     /// An empty balances list will be returned for the project ids in the denylist
     pub denylist_project_ids: Option<Vec<String>>,
+    /// URLs of the scam tokens feeds, the token lists or the CAIP-10
+    /// addresses JSON arrays, the denylisted tokens are dropped from the
+    /// balance, history and fungible price responses
+    pub scam_tokens_feed_urls: Option<Vec<String>>,
+    /// Projects responded with the denylisted scam tokens
+    pub scam_tokens_opt_out_project_ids: Option<Vec<String>>,
+    /// Interval of the scam tokens feeds refresh
+    pub scam_tokens_refresh_interval_secs: Option<u64>,
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -51,6 +73,9 @@ pub struct BalanceQueryParams {
     pub chain_id: Option<String>,
     /// Comma separated list of CAIP-10 contract addresses to force update the balance
     pub force_update: Option<String>,
+    /// Comma separated list of CAIP-19 asset IDs to respond with the balances
+    /// of these assets only
+    pub tokens: Option<String>,
     #[serde(flatten)]
     pub sdk_info: SdkInfoParams,
 }
@@ -61,7 +86,7 @@ pub struct BalanceResponseBody {
     pub balances: Vec<BalanceItem>,
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, SimpleObject)]
 #[serde(rename_all = "camelCase")]
 pub struct BalanceItem {
     pub name: String,
@@ -77,7 +102,7 @@ pub struct BalanceItem {
     pub icon_url: String,
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, SimpleObject)]
 #[serde(rename_all = "camelCase")]
 pub struct BalanceQuantity {
     pub decimals: String,
@@ -112,19 +137,61 @@ pub async fn set_cached_balance(
     cache: &Option<Arc<dyn KeyValueStorage<BalanceResponseBody>>>,
     address: &str,
     item: &BalanceResponseBody,
+    ttl: Duration,
 ) {
     if let Some(cache) = cache {
         cache
-            .set(
-                &address_balance_cache_key(address),
-                item,
-                Some(BALANCE_CACHE_TTL),
-            )
+            .set(&address_balance_cache_key(address), item, Some(ttl))
             .await
             .unwrap_or_else(|e| error!("Failed to set balance cache: {e}"));
     }
 }
 
+pub async fn invalidate_cached_balance(
+    cache: &Option<Arc<dyn KeyValueStorage<BalanceResponseBody>>>,
+    address: &str,
+) -> Result<(), StorageError> {
+    if let Some(cache) = cache {
+        cache.del(&address_balance_cache_key(address)).await?;
+    }
+    Ok(())
+}
+
+/// Providers cache the EVM addresses in the lowercase and checksum formats
+pub fn address_variants(address: &str) -> Vec<String> {
+    let mut variants = vec![address.to_owned()];
+    if let Ok(h160) = H160::from_str(address) {
+        variants.push(format!("{h160:#x}"));
+        variants.push(to_checksum(&h160, None));
+    }
+    variants.sort();
+    variants.dedup();
+    variants
+}
+
+/// Token metadata cache keys of the CAIP-19 asset in all the address formats
+/// cached by the providers
+pub fn token_metadata_cache_keys(asset: &Caip19Asset) -> Result<Vec<String>, RpcError> {
+    let chain_id = asset.chain_id();
+    let token_address = if asset.asset_namespace() == NATIVE_ASSET_NAMESPACE {
+        match chain_id.namespace() {
+            "eip155" => format!("{H160_EMPTY_ADDRESS:#x}"),
+            "solana" => SOLANA_NATIVE_TOKEN_ADDRESS.to_owned(),
+            namespace => {
+                return Err(RpcError::InvalidParameter(format!(
+                    "Native token metadata is not cached for the {namespace} namespace"
+                )))
+            }
+        }
+    } else {
+        asset.asset_reference().to_owned()
+    };
+    Ok(address_variants(&token_address)
+        .into_iter()
+        .map(|token_address| format!("{chain_id}:{token_address}"))
+        .collect())
+}
+
 pub async fn handler(
     state: State<Arc<AppState>>,
     query: Query<BalanceQueryParams>,
@@ -148,7 +215,7 @@ async fn handler_internal(
     let project_id = query.project_id.clone();
 
     // Check the denylist for the project id
-    if let Some(denylist_project_ids) = &state.config.balances.denylist_project_ids {
+    if let Some(denylist_project_ids) = &state.reloadable_config().balances.denylist_project_ids {
         if denylist_project_ids.contains(&project_id) {
             return Ok(Json(BalanceResponseBody { balances: vec![] }));
         }
@@ -200,10 +267,30 @@ async fn handler_internal(
         return Ok(Json(BalanceResponseBody { balances: vec![] }));
     }
 
+    let assets = query
+        .tokens
+        .as_deref()
+        .map(parse_tokens_filter)
+        .transpose()?;
+
     // Get the cached balance and return it if found except if force_update is needed
     if query.force_update.is_none() {
         if let Some(cached_balance) = get_cached_balance(&state.balance_cache, &address).await {
-            return Ok(Json(cached_balance));
+            let mut response = match &assets {
+                Some(assets) => BalanceResponseBody {
+                    balances: filter_balances_by_assets(
+                        &state,
+                        &address,
+                        &query.currency,
+                        assets,
+                        cached_balance.balances,
+                    )
+                    .await,
+                },
+                None => cached_balance,
+            };
+            filter_scam_tokens(&state, &project_id, &mut response.balances);
+            return Ok(Json(response));
         }
     }
 
@@ -223,7 +310,7 @@ async fn handler_internal(
     }
 
     let providers = state
-        .providers
+        .providers()
         .get_balance_provider_for_namespace(&namespace, PROVIDER_MAX_CALLS)?;
 
     let mut balance_response = None;
@@ -233,7 +320,7 @@ async fn handler_internal(
             .get_balance(
                 address.clone(),
                 query.clone().0,
-                &state.providers.token_metadata_cache,
+                &state.providers().token_metadata_cache,
                 state.metrics.clone(),
             )
             .await;
@@ -256,6 +343,24 @@ async fn handler_internal(
         RpcError::BalanceTemporarilyUnavailable(namespace.to_string()),
     )?;
 
+    // Enqueue the tokens with the missing metadata for the backfill, the
+    // providers are using the backfilled metadata from the cache
+    for balance in &response.balances {
+        let Some(token_address) = &balance.address else {
+            continue;
+        };
+        if token_metadata_backfill::is_incomplete(
+            Some(&balance.name),
+            Some(&balance.symbol),
+            Some(&balance.icon_url),
+        ) && state.token_metadata_backfill.enqueue(token_address)
+        {
+            state
+                .metrics
+                .add_token_metadata_backfill_enqueued("balance");
+        }
+    }
+
     {
         // Filling the request_id from the `propagate_x_request_id` middleware
         let request_id = headers
@@ -373,8 +478,8 @@ async fn handler_internal(
             }
             // Appending the token item to the response if it's not in
             // the balance response due to the zero balance
-            let get_price_info_provider = state
-                .providers
+            let repository = state.providers();
+            let get_price_info_provider = repository
                 .fungible_price_providers
                 .get(&namespace)
                 .ok_or_else(|| RpcError::UnsupportedNamespace(namespace))?;
@@ -384,7 +489,7 @@ async fn handler_internal(
                     &chain_id.clone(),
                     format!("{contract_address:#x}").as_str(),
                     &query.currency,
-                    &state.providers.token_metadata_cache,
+                    &repository.token_metadata_cache,
                     state.metrics.clone(),
                 )
                 .await
@@ -422,19 +527,228 @@ async fn handler_internal(
         }
     }
 
+    // The balances of all the assets are cached, the requested assets are
+    // filtered for the response only
+    let filtered_response = match &assets {
+        Some(assets) => Some(BalanceResponseBody {
+            balances: filter_balances_by_assets(
+                &state,
+                &address,
+                &query.currency,
+                assets,
+                response.balances.clone(),
+            )
+            .await,
+        }),
+        None => None,
+    };
+
     // Spawn a background task to update the balance cache without blocking
     {
         tokio::spawn({
             let address_key = address.clone();
             let response = response.clone();
             async move {
-                set_cached_balance(&state.balance_cache, &address_key, &response).await;
+                set_cached_balance(
+                    &state.balance_cache,
+                    &address_key,
+                    &response,
+                    state.config.storage.balance_cache_ttl(),
+                )
+                .await;
             }
         });
     }
+    // The balances are cached for all projects regardless of the scam tokens
+    // filtering opt-out
+    let mut response = filtered_response.unwrap_or(response);
+    filter_scam_tokens(&state, &project_id, &mut response.balances);
     Ok(Json(response))
 }
 
+/// Drops the balances of the denylisted scam tokens unless the project opted
+/// out, the native tokens are never dropped
+fn filter_scam_tokens(state: &AppState, project_id: &str, balances: &mut Vec<BalanceItem>) {
+    let Some(scam_tokens) = state
+        .scam_tokens
+        .for_project(&state.reloadable_config().balances, project_id)
+    else {
+        return;
+    };
+    let count = balances.len();
+    balances.retain(|balance| {
+        balance
+            .address
+            .as_deref()
+            .is_none_or(|address| !scam_tokens.is_scam(address))
+    });
+    let filtered = count - balances.len();
+    if filtered > 0 {
+        state.metrics.add_scam_tokens_filtered("balance", filtered);
+    }
+}
+
+/// Parse the comma separated CAIP-19 asset IDs of the balance tokens filter
+fn parse_tokens_filter(tokens: &str) -> Result<Vec<Caip19Asset>, RpcError> {
+    let mut assets = Vec::new();
+    for token in tokens
+        .split(',')
+        .map(str::trim)
+        .filter(|token| !token.is_empty())
+    {
+        let asset = Caip19Asset::parse(token)
+            .map_err(|e| RpcError::InvalidParameter(format!("Invalid CAIP-19 asset ID: {e}")))?;
+        if !assets.contains(&asset) {
+            assets.push(asset);
+        }
+    }
+    if assets.is_empty() || assets.len() > MAX_FILTER_TOKENS {
+        return Err(RpcError::InvalidParameter(format!(
+            "Tokens filter must contain from 1 to {MAX_FILTER_TOKENS} CAIP-19 asset IDs"
+        )));
+    }
+    Ok(assets)
+}
+
+/// Whether the balance item is of the CAIP-19 asset, the native tokens
+/// balances have no contract address
+fn is_balance_of_asset(balance: &BalanceItem, asset: &Caip19Asset) -> bool {
+    let chain_id = asset.chain_id();
+    if asset.asset_namespace() == NATIVE_ASSET_NAMESPACE {
+        return balance.address.is_none()
+            && balance.chain_id.as_deref() == Some(chain_id.to_string().as_str());
+    }
+    let asset_address = format!("{chain_id}:{}", asset.asset_reference());
+    balance.address.as_deref().is_some_and(|address| {
+        // EVM addresses are cached by the providers in the different cases
+        if chain_id.namespace() == "eip155" {
+            address.eq_ignore_ascii_case(&asset_address)
+        } else {
+            address == asset_address
+        }
+    })
+}
+
+/// Balances of the requested assets in the requested order. The EVM assets
+/// omitted by the provider due to the zero balance are looked up by the RPC
+/// calls, the assets failed to look up are omitted from the response.
+async fn filter_balances_by_assets(
+    state: &AppState,
+    address: &str,
+    currency: &SupportedCurrencies,
+    assets: &[Caip19Asset],
+    balances: Vec<BalanceItem>,
+) -> Vec<BalanceItem> {
+    let balances = &balances;
+    let lookups = assets.iter().map(|asset| async move {
+        if let Some(balance) = balances
+            .iter()
+            .find(|balance| is_balance_of_asset(balance, asset))
+        {
+            return Some(balance.clone());
+        }
+        lookup_asset_balance(state, address, currency, asset)
+            .await
+            .tap_err(|e| debug!("Failed to look up the balance of {asset} for {address}: {e}"))
+            .ok()
+    });
+    join_all(lookups).await.into_iter().flatten().collect()
+}
+
+/// Balance of the EVM native or ERC-20 asset by the RPC call through the
+/// proxy
+async fn lookup_asset_balance(
+    state: &AppState,
+    address: &str,
+    currency: &SupportedCurrencies,
+    asset: &Caip19Asset,
+) -> Result<BalanceItem, RpcError> {
+    let chain_id = asset.chain_id();
+    let contract_address = match asset.asset_namespace() {
+        _ if chain_id.namespace() != "eip155" => {
+            return Err(RpcError::InvalidParameter(format!(
+                "Balance lookup is not supported for the asset {asset}"
+            )))
+        }
+        NATIVE_ASSET_NAMESPACE => H160_EMPTY_ADDRESS,
+        ERC20_ASSET_NAMESPACE => asset
+            .asset_reference()
+            .parse::<Address>()
+            .map_err(|_| RpcError::InvalidAddress)?,
+        _ => {
+            return Err(RpcError::InvalidParameter(format!(
+                "Balance lookup is not supported for the asset {asset}"
+            )))
+        }
+    };
+    let rpc_project_id = state
+        .config
+        .server
+        .testing_project_id
+        .as_ref()
+        .ok_or_else(|| {
+            RpcError::InvalidConfiguration(
+                "Missing testing project id in the configuration for the balance RPC lookups"
+                    .to_string(),
+            )
+        })?;
+    let wallet = address
+        .parse::<Address>()
+        .map_err(|_| RpcError::InvalidAddress)?;
+    let rpc_balance = crypto::get_erc20_balance(
+        &chain_id.to_string(),
+        contract_address,
+        wallet,
+        rpc_project_id,
+        MessageSource::Balance,
+        None,
+    )
+    .await?;
+
+    let repository = state.providers();
+    let price_provider = repository
+        .fungible_price_providers
+        .get(&crypto::CaipNamespaces::Eip155)
+        .ok_or(RpcError::UnsupportedNamespace(
+            crypto::CaipNamespaces::Eip155,
+        ))?;
+    let price_info = price_provider
+        .get_price(
+            chain_id.reference(),
+            format!("{contract_address:#x}").as_str(),
+            currency,
+            &repository.token_metadata_cache,
+            state.metrics.clone(),
+        )
+        .await?;
+    let token_info = price_info
+        .fungibles
+        .first()
+        .ok_or(RpcError::BalanceProviderError)?;
+
+    Ok(BalanceItem {
+        name: token_info.name.clone(),
+        symbol: token_info.symbol.clone(),
+        chain_id: Some(chain_id.to_string()),
+        address: if contract_address == H160_EMPTY_ADDRESS {
+            None
+        } else {
+            Some(format!("{chain_id}:{}", asset.asset_reference()))
+        },
+        value: Some(crypto::convert_token_amount_to_value(
+            rpc_balance,
+            token_info.price,
+            token_info.decimals,
+        )),
+        price: token_info.price,
+        quantity: BalanceQuantity {
+            decimals: token_info.decimals.to_string(),
+            numeric: crypto::format_token_amount(rpc_balance, token_info.decimals),
+        },
+        icon_url: token_info.icon_url.clone(),
+    })
+}
+
 pub struct TokenMetadataCache {
     cache_pool: Option<Arc<Pool>>,
 }
@@ -475,6 +789,38 @@ impl TokenMetadataCache {
         }
         Ok(None)
     }
+
+    async fn mget_cache(&self, keys: &[String]) -> Result<Vec<Option<String>>, StorageError> {
+        if keys.is_empty() {
+            return Ok(Vec::new());
+        }
+        if let Some(redis_pool) = &self.cache_pool {
+            let mut cache = redis_pool.get().await.map_err(|e| {
+                StorageError::Connection(format!("Error when getting the Redis pool instance {e}"))
+            })?;
+            let values = redis::cmd("MGET")
+                .arg(keys)
+                .query_async(&mut cache)
+                .await
+                .map_err(|e| StorageError::Connection(format!("Error when getting cache: {e}")))?;
+            return Ok(values);
+        }
+        Ok(vec![None; keys.len()])
+    }
+
+    #[allow(dependency_on_unit_never_type_fallback)]
+    async fn del_cache(&self, key: &str) -> Result<(), StorageError> {
+        if let Some(redis_pool) = &self.cache_pool {
+            let mut cache = redis_pool.get().await.map_err(|e| {
+                StorageError::Connection(format!("Error when getting the Redis pool instance {e}"))
+            })?;
+            cache
+                .del(key)
+                .await
+                .map_err(|e| StorageError::Connection(format!("Error when deleting cache: {e}")))?;
+        }
+        Ok(())
+    }
 }
 
 #[async_trait]
@@ -493,6 +839,26 @@ impl TokenMetadataCacheProvider for TokenMetadataCache {
         Ok(None)
     }
 
+    async fn get_metadata_batch(
+        &self,
+        caip10_token_addresses: &[String],
+    ) -> Result<Vec<Option<TokenMetadataCacheItem>>, RpcError> {
+        let keys = caip10_token_addresses
+            .iter()
+            .map(|caip10_token_address| self.token_metadata_cache_key(caip10_token_address))
+            .collect::<Vec<_>>();
+        self.mget_cache(&keys)
+            .await?
+            .into_iter()
+            .map(|value| {
+                value
+                    .map(|value| serde_json::from_str::<TokenMetadataCacheItem>(&value))
+                    .transpose()
+                    .map_err(RpcError::from)
+            })
+            .collect()
+    }
+
     async fn set_metadata(
         &self,
         caip10_token_address: &str,
@@ -506,4 +872,84 @@ impl TokenMetadataCacheProvider for TokenMetadataCache {
         .await?;
         Ok(())
     }
+
+    async fn delete_metadata(&self, caip10_token_address: &str) -> Result<(), RpcError> {
+        self.del_cache(&self.token_metadata_cache_key(caip10_token_address))
+            .await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_token_metadata_cache_keys() {
+        let asset = Caip19Asset::parse("eip155:1/erc20:0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48")
+            .unwrap();
+        assert_eq!(
+            token_metadata_cache_keys(&asset).unwrap(),
+            vec![
+                "eip155:1:0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48",
+                "eip155:1:0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48",
+            ]
+        );
+
+        let asset = Caip19Asset::parse("eip155:10/slip44:60").unwrap();
+        assert_eq!(
+            token_metadata_cache_keys(&asset).unwrap(),
+            vec!["eip155:10:0xeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeee"]
+        );
+
+        let asset = Caip19Asset::parse("bip122:000000000019d6689c085ae165831e93/slip44:0").unwrap();
+        assert!(token_metadata_cache_keys(&asset).is_err());
+    }
+
+    #[test]
+    fn test_parse_tokens_filter() {
+        let assets = parse_tokens_filter(
+            "eip155:1/slip44:60, eip155:1/erc20:0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48,eip155:1/slip44:60",
+        )
+        .unwrap();
+        assert_eq!(
+            assets,
+            vec![
+                Caip19Asset::parse("eip155:1/slip44:60").unwrap(),
+                Caip19Asset::parse("eip155:1/erc20:0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48")
+                    .unwrap(),
+            ]
+        );
+        assert!(parse_tokens_filter("").is_err());
+        assert!(parse_tokens_filter("eip155:1").is_err());
+    }
+
+    #[test]
+    fn test_is_balance_of_asset() {
+        let balance = |chain_id: &str, address: Option<&str>| BalanceItem {
+            name: "Token".to_owned(),
+            symbol: "TKN".to_owned(),
+            chain_id: Some(chain_id.to_owned()),
+            address: address.map(ToOwned::to_owned),
+            value: None,
+            price: 0.0,
+            quantity: BalanceQuantity {
+                decimals: "18".to_owned(),
+                numeric: "0".to_owned(),
+            },
+            icon_url: String::new(),
+        };
+        let usdc = Caip19Asset::parse("eip155:1/erc20:0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48")
+            .unwrap();
+        let eth = Caip19Asset::parse("eip155:1/slip44:60").unwrap();
+
+        let usdc_balance = balance(
+            "eip155:1",
+            Some("eip155:1:0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48"),
+        );
+        assert!(is_balance_of_asset(&usdc_balance, &usdc));
+        assert!(!is_balance_of_asset(&usdc_balance, &eth));
+        assert!(is_balance_of_asset(&balance("eip155:1", None), &eth));
+        assert!(!is_balance_of_asset(&balance("eip155:10", None), &eth));
+    }
 }
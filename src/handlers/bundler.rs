@@ -1,8 +1,10 @@
 use {
     crate::{
         error::RpcError,
-        providers::SupportedBundlerOps,
+        handlers::sponsorship,
+        providers::{BundlerOpsProvider, SupportedBundlerOps},
         state::AppState,
+        storage::KeyValueStorage,
         utils::{
             crypto::{self, disassemble_caip2},
             simple_request_json::SimpleRequestJson,
@@ -15,8 +17,12 @@ use {
         Json,
     },
     serde::{Deserialize, Serialize},
-    std::sync::Arc,
-    tracing::info,
+    serde_json::json,
+    std::{
+        sync::Arc,
+        time::{Duration, SystemTime, UNIX_EPOCH},
+    },
+    tracing::{error, info},
     url::Url,
     wc::metrics::{future_metrics, FutureExt},
 };
@@ -38,6 +44,38 @@ pub struct BundlerJsonRpcRequest {
     pub params: serde_json::Value,
 }
 
+/// User operation gas price result cached per chain
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CachedGasPrice {
+    pub result: serde_json::Value,
+    /// Unix timestamp in milliseconds
+    pub cached_at: u64,
+}
+
+impl CachedGasPrice {
+    fn new(result: serde_json::Value) -> Self {
+        Self {
+            result,
+            cached_at: unix_millis(),
+        }
+    }
+
+    fn age(&self) -> Duration {
+        Duration::from_millis(unix_millis().saturating_sub(self.cached_at))
+    }
+}
+
+fn unix_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+fn gas_price_cache_key(evm_chain_id: &str) -> String {
+    format!("bundler_gas_price/{evm_chain_id}")
+}
+
 pub async fn handler(
     state: State<Arc<AppState>>,
     query_params: Query<BundlerQueryParams>,
@@ -59,32 +97,9 @@ async fn handler_internal(
         .await?;
     let evm_chain_id = disassemble_caip2(&query_params.chain_id)?.1;
     info!("bundler endpoint bundler: {:?}", query_params.bundler);
-    let result = match query_params.bundler {
-        None => {
-            state
-                .providers
-                .bundler_ops_provider
-                .bundler_rpc_call(
-                    &evm_chain_id,
-                    request_payload.id,
-                    request_payload.jsonrpc,
-                    &request_payload.method,
-                    request_payload.params,
-                )
-                .await?
-        }
-        Some(bundler) if bundler == "pimlico" => {
-            state
-                .providers
-                .bundler_ops_provider
-                .bundler_rpc_call(
-                    &evm_chain_id,
-                    request_payload.id,
-                    request_payload.jsonrpc,
-                    &request_payload.method,
-                    request_payload.params,
-                )
-                .await?
+    let result = match query_params.bundler.as_deref() {
+        None | Some("pimlico") => {
+            bundler_provider_call(&state, &query_params, &evm_chain_id, request_payload).await?
         }
         Some(unsafe_bundler) => {
             let url = unsafe_bundler
@@ -118,6 +133,9 @@ async fn handler_internal(
                 SupportedBundlerOps::EthGetUserOperationReceipt => {
                     "eth_getUserOperationReceipt".into()
                 }
+                SupportedBundlerOps::EthGetUserOperationByHash => {
+                    "eth_getUserOperationByHash".into()
+                }
                 SupportedBundlerOps::EthEstimateUserOperationGas => {
                     "eth_estimateUserOperationGas".into()
                 }
@@ -149,3 +167,129 @@ async fn handler_internal(
 
     Ok(Json(result).into_response())
 }
+
+/// Call the configured bundler providers, enforcing the project's paymaster
+/// sponsorship policy for the sponsorship operations and settling the
+/// sponsored gas by the user operation receipts
+async fn bundler_provider_call(
+    state: &AppState,
+    query_params: &BundlerQueryParams,
+    evm_chain_id: &str,
+    request_payload: BundlerJsonRpcRequest,
+) -> Result<serde_json::Value, RpcError> {
+    if request_payload.method == SupportedBundlerOps::PimlicoGetUserOperationGasPrice {
+        if let Some(cache) = &state.bundler_gas_price_cache {
+            return cached_gas_price_call(state, cache.as_ref(), evm_chain_id, request_payload)
+                .await;
+        }
+    }
+
+    let is_sponsorship_op = sponsorship::is_sponsorship_op(&request_payload.method);
+    let policy = if is_sponsorship_op {
+        sponsorship::check_sponsorship_policy(
+            state,
+            &query_params.project_id,
+            &query_params.chain_id,
+            &request_payload.params,
+        )
+        .await?
+    } else {
+        None
+    };
+
+    let result = state
+        .providers()
+        .bundler_ops_provider
+        .bundler_rpc_call(
+            evm_chain_id,
+            request_payload.id,
+            request_payload.jsonrpc,
+            &request_payload.method,
+            request_payload.params.clone(),
+        )
+        .await?;
+
+    if is_sponsorship_op {
+        sponsorship::charge_sponsored_user_op(
+            state,
+            &query_params.project_id,
+            &query_params.chain_id,
+            policy.as_ref(),
+            &request_payload.params,
+            &result,
+        )
+        .await?;
+    } else if request_payload.method == SupportedBundlerOps::EthGetUserOperationReceipt {
+        if let Err(e) = sponsorship::settle_sponsored_user_op(
+            state,
+            &query_params.project_id,
+            &query_params.chain_id,
+            &result,
+        )
+        .await
+        {
+            error!("Failed to settle the sponsored user operation: {e}");
+        }
+    }
+
+    Ok(result)
+}
+
+/// Serve the user operation gas price from the per chain cache, as it's
+/// requested for nearly every user operation preparation
+async fn cached_gas_price_call(
+    state: &AppState,
+    cache: &dyn KeyValueStorage<CachedGasPrice>,
+    evm_chain_id: &str,
+    request_payload: BundlerJsonRpcRequest,
+) -> Result<serde_json::Value, RpcError> {
+    let cache_key = gas_price_cache_key(evm_chain_id);
+    match cache.get(&cache_key).await {
+        Ok(Some(cached)) => {
+            state
+                .metrics
+                .add_bundler_gas_price_cache_lookup(evm_chain_id, true);
+            state
+                .metrics
+                .add_bundler_gas_price_cache_age(evm_chain_id, cached.age());
+            return Ok(json!({
+                "jsonrpc": request_payload.jsonrpc,
+                "id": request_payload.id,
+                "result": cached.result,
+            }));
+        }
+        Ok(None) => {}
+        Err(e) => error!("Failed to get the bundler gas price cache: {e}"),
+    }
+    state
+        .metrics
+        .add_bundler_gas_price_cache_lookup(evm_chain_id, false);
+
+    let response = state
+        .providers()
+        .bundler_ops_provider
+        .bundler_rpc_call(
+            evm_chain_id,
+            request_payload.id,
+            request_payload.jsonrpc,
+            &request_payload.method,
+            request_payload.params,
+        )
+        .await?;
+
+    // Errors are not cached to retry them on the next request
+    if let Some(result) = response.get("result") {
+        if let Err(e) = cache
+            .set(
+                &cache_key,
+                &CachedGasPrice::new(result.clone()),
+                Some(state.config.storage.bundler_gas_price_cache_ttl()),
+            )
+            .await
+        {
+            error!("Failed to set the bundler gas price cache: {e}");
+        }
+    }
+
+    Ok(response)
+}
@@ -0,0 +1,141 @@
+use {
+    crate::{
+        error::RpcError,
+        json_rpc::JSON_RPC_VERSION,
+        providers::{BundlerOpsProvider, SupportedBundlerOps},
+        state::AppState,
+        utils::crypto::disassemble_caip2,
+    },
+    alloy::{primitives::B256, rpc::json_rpc::Id},
+    axum::{
+        extract::{Query, State},
+        response::{IntoResponse, Response},
+        Json,
+    },
+    serde::{Deserialize, Serialize},
+    std::{
+        str::FromStr,
+        sync::Arc,
+        time::{Duration, Instant},
+    },
+    tracing::debug,
+    wc::metrics::{future_metrics, FutureExt},
+};
+
+/// Default time to wait for the user operation inclusion
+const DEFAULT_WAIT_TIMEOUT_SECS: u64 = 30;
+/// Maximum allowed time to wait for the user operation inclusion
+const MAX_WAIT_TIMEOUT_SECS: u64 = 60;
+/// Interval between the user operation receipt checks
+const POLLING_INTERVAL: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct WaitQueryParams {
+    pub project_id: String,
+    pub chain_id: String,
+    pub user_op_hash: String,
+    /// Timeout in seconds
+    pub timeout: Option<u64>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct WaitResponse {
+    pub user_op_hash: B256,
+    pub transaction_hash: B256,
+    pub success: bool,
+    /// Raw user operation receipt from the bundler
+    pub receipt: serde_json::Value,
+}
+
+pub async fn handler(
+    state: State<Arc<AppState>>,
+    query_params: Query<WaitQueryParams>,
+) -> Result<Response, RpcError> {
+    handler_internal(state, query_params)
+        .with_metrics(future_metrics!("handler_task", "name" => "bundler_wait"))
+        .await
+}
+
+#[tracing::instrument(skip(state), level = "debug")]
+async fn handler_internal(
+    State(state): State<Arc<AppState>>,
+    Query(query_params): Query<WaitQueryParams>,
+) -> Result<Response, RpcError> {
+    state
+        .validate_project_access_and_quota(&query_params.project_id)
+        .await?;
+    let evm_chain_id = disassemble_caip2(&query_params.chain_id)?.1;
+    let user_op_hash = B256::from_str(&query_params.user_op_hash)
+        .map_err(|e| RpcError::InvalidParameter(format!("Invalid userOpHash: {e}")))?;
+    let timeout = Duration::from_secs(
+        query_params
+            .timeout
+            .unwrap_or(DEFAULT_WAIT_TIMEOUT_SECS)
+            .min(MAX_WAIT_TIMEOUT_SECS),
+    );
+
+    let started_at = Instant::now();
+    loop {
+        if let Some(receipt) =
+            get_user_operation_receipt(&state, &evm_chain_id, user_op_hash).await?
+        {
+            let transaction_hash = receipt
+                .get("receipt")
+                .and_then(|receipt| receipt.get("transactionHash"))
+                .and_then(serde_json::Value::as_str)
+                .and_then(|hash| B256::from_str(hash).ok())
+                .ok_or_else(|| {
+                    RpcError::BundlerError("Receipt is missing the transaction hash".into())
+                })?;
+            let success = receipt
+                .get("success")
+                .and_then(serde_json::Value::as_bool)
+                .unwrap_or_default();
+
+            return Ok(Json(WaitResponse {
+                user_op_hash,
+                transaction_hash,
+                success,
+                receipt,
+            })
+            .into_response());
+        }
+
+        if started_at.elapsed() + POLLING_INTERVAL > timeout {
+            return Err(RpcError::UserOperationWaitTimeout(user_op_hash.to_string()));
+        }
+        debug!("User operation {user_op_hash} is not included yet, waiting");
+        tokio::time::sleep(POLLING_INTERVAL).await;
+    }
+}
+
+/// Requests the user operation receipt from the bundler, returns `None` if the
+/// user operation is not included yet
+async fn get_user_operation_receipt(
+    state: &AppState,
+    evm_chain_id: &str,
+    user_op_hash: B256,
+) -> Result<Option<serde_json::Value>, RpcError> {
+    let response = state
+        .providers()
+        .bundler_ops_provider
+        .bundler_rpc_call(
+            evm_chain_id,
+            Id::Number(1),
+            JSON_RPC_VERSION.clone(),
+            &SupportedBundlerOps::EthGetUserOperationReceipt,
+            serde_json::json!([user_op_hash]),
+        )
+        .await?;
+
+    if let Some(error) = response.get("error") {
+        return Err(RpcError::BundlerError(error.to_string()));
+    }
+
+    Ok(response
+        .get("result")
+        .filter(|result| !result.is_null())
+        .cloned())
+}
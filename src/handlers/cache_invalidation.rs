@@ -0,0 +1,62 @@
+use {
+    super::{
+        balance::{address_variants, invalidate_cached_balance, token_metadata_cache_keys},
+        identity::identity_cache_key,
+    },
+    crate::{error::RpcError, state::AppState, utils::crypto::Caip19Asset},
+    axum::{
+        extract::{Path, State},
+        http::StatusCode,
+    },
+    ethers::types::H160,
+    std::{str::FromStr, sync::Arc},
+    tracing::info,
+};
+
+/// Purges the cached identity of the address, served on the private port only
+pub async fn identity_handler(
+    State(state): State<Arc<AppState>>,
+    Path(address): Path<String>,
+) -> Result<StatusCode, RpcError> {
+    let address = H160::from_str(&address).map_err(|_| RpcError::InvalidAddress)?;
+    if let Some(cache) = &state.identity_cache {
+        cache.del(&identity_cache_key(&address)).await?;
+    }
+    info!("Invalidated the identity cache for {address:?}");
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn project_data_handler(
+    State(state): State<Arc<AppState>>,
+    Path(project_id): Path<String>,
+) -> Result<StatusCode, RpcError> {
+    state.registry.invalidate_project_data(&project_id).await?;
+    info!("Invalidated the project data cache for {project_id}");
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn balance_handler(
+    State(state): State<Arc<AppState>>,
+    Path(address): Path<String>,
+) -> Result<StatusCode, RpcError> {
+    for address in address_variants(&address) {
+        invalidate_cached_balance(&state.balance_cache, &address).await?;
+    }
+    info!("Invalidated the balance cache for {address}");
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Purges the cached token metadata of the CAIP-19 asset
+pub async fn token_metadata_handler(
+    State(state): State<Arc<AppState>>,
+    Path(asset_id): Path<String>,
+) -> Result<StatusCode, RpcError> {
+    let asset = Caip19Asset::parse(&asset_id)
+        .map_err(|e| RpcError::InvalidParameter(format!("Invalid CAIP-19 asset ID: {e}")))?;
+    let token_metadata_cache = state.providers().token_metadata_cache.clone();
+    for cache_key in token_metadata_cache_keys(&asset)? {
+        token_metadata_cache.delete_metadata(&cache_key).await?;
+    }
+    info!("Invalidated the token metadata cache for {asset_id}");
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -6,6 +6,7 @@ use {
             tenderly::{AssetChangeType, TokenStandard},
             SimulationProvider,
         },
+        state::AppState,
         utils::{crypto::get_erc20_balance, token_amount::TokenAmount},
         Metrics,
     },
@@ -14,9 +15,9 @@ use {
     ethers::{types::H160 as EthersH160, utils::keccak256},
     serde::{Deserialize, Serialize},
     std::{cmp::Ordering, collections::HashMap, sync::Arc},
-    tracing::debug,
+    tracing::{debug, error},
     yttrium::chain_abstraction::{
-        api::prepare::Eip155OrSolanaAddress,
+        api::prepare::{Eip155OrSolanaAddress, PrepareRequest, PrepareResponse},
         solana::{self, SolanaRpcClient},
     },
 };
@@ -48,6 +49,71 @@ pub struct StorageBridgingItem {
     amount_expected: U256,
     status: BridgingStatus,
     error_reason: Option<String>,
+    /// Cached route of the orchestration to invalidate on the status change
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    route_cache_key: Option<String>,
+}
+
+/// Cache key of the route by the canonical hash of the project request
+pub fn route_cache_key(project_id: &str, request: &PrepareRequest) -> Result<String, RpcError> {
+    let request = serde_json::to_string(request)?;
+    Ok(format!(
+        "ca_route/{}",
+        sha256::digest(format!("{project_id}:{request}"))
+    ))
+}
+
+/// Cached route response of the identical request. Routes are cached as JSON
+/// as the responses are untagged enums of the alloy types.
+pub async fn get_cached_route(state: &AppState, key: &str) -> Option<PrepareResponse> {
+    let cache = state.ca_route_cache.as_ref()?;
+    if state.config.storage.ca_route_cache_ttl == 0 {
+        return None;
+    }
+    let cached = match cache.get(key).await {
+        Ok(cached) => cached,
+        Err(e) => {
+            error!("Failed to get the cached chain abstraction route: {e}");
+            None
+        }
+    };
+    let response = cached.and_then(|cached| {
+        serde_json::from_str(&cached)
+            .map_err(|e| error!("Failed to deserialize the cached chain abstraction route: {e}"))
+            .ok()
+    });
+    state.metrics.add_ca_route_cache_lookup(response.is_some());
+    response
+}
+
+pub async fn set_cached_route(state: &AppState, key: &str, response: &PrepareResponse) {
+    let Some(cache) = &state.ca_route_cache else {
+        return;
+    };
+    let ttl = state.config.storage.ca_route_cache_ttl();
+    if ttl.is_zero() {
+        return;
+    }
+    let result = match serde_json::to_string(response) {
+        Ok(serialized) => cache.set(key, &serialized, Some(ttl)).await,
+        Err(e) => {
+            error!("Failed to serialize the chain abstraction route: {e}");
+            return;
+        }
+    };
+    if let Err(e) = result {
+        error!("Failed to cache the chain abstraction route: {e}");
+    }
+}
+
+/// Drop the cached route of the orchestration to not serve it after the
+/// orchestration is completed or failed
+pub async fn invalidate_cached_route(state: &AppState, key: Option<&str>) {
+    if let (Some(cache), Some(key)) = (&state.ca_route_cache, key) {
+        if let Err(e) = cache.del(key).await {
+            error!("Failed to invalidate the cached chain abstraction route: {e}");
+        }
+    }
 }
 
 /// Bridging status
@@ -1,9 +1,9 @@
 use {
     super::{
         assets::NATIVE_TOKEN_ADDRESS, check_bridging_for_erc20_transfer, convert_amount,
-        find_supported_bridging_asset, get_assets_changes_from_simulation,
-        nonce_manager::NonceManager, BridgingStatus, StorageBridgingItem, BRIDGING_FEE_SLIPPAGE,
-        STATUS_POLLING_INTERVAL,
+        find_supported_bridging_asset, get_assets_changes_from_simulation, get_cached_route,
+        nonce_manager::NonceManager, route_cache_key, set_cached_route, BridgingStatus,
+        StorageBridgingItem, BRIDGING_FEE_SLIPPAGE, STATUS_POLLING_INTERVAL,
     },
     crate::{
         analytics::{
@@ -14,7 +14,6 @@ use {
         handlers::{chain_agnostic::lifi::caip2_to_lifi_chain_id, self_provider, SdkInfoParams},
         metrics::{ChainAbstractionNoBridgingNeededType, ChainAbstractionTransactionType},
         state::AppState,
-        storage::irn::OperationType,
         utils::{
             crypto::{
                 convert_alloy_address_to_h160, decode_erc20_transfer_data, get_erc20_balance,
@@ -27,10 +26,9 @@ use {
     alloy::primitives::{Address, Bytes, U256, U64},
     axum::{
         extract::{ConnectInfo, Query, State},
-        response::{IntoResponse, Response},
         Json,
     },
-    hyper::{HeaderMap, StatusCode},
+    hyper::HeaderMap,
     serde::{Deserialize, Serialize},
     serde_json::json,
     std::{
@@ -231,6 +229,13 @@ async fn handler_internal(
         .validate_project_access_and_quota(query_params.project_id.as_ref())
         .await?;
 
+    // Serve the identical requests within the cache TTL without repeating the
+    // simulations and the bridging quotes
+    let route_cache_key = route_cache_key(query_params.project_id.as_ref(), &request_payload)?;
+    if let Some(response) = get_cached_route(&state, &route_cache_key).await {
+        return Ok(Json(response));
+    }
+
     let provider_pool = self_provider::SelfProviderPool {
         state: state.0.clone(),
         connect_info: connect_info.0,
@@ -264,7 +269,7 @@ async fn handler_internal(
         let asset_transfer_contract = NATIVE_TOKEN_ADDRESS;
         let asset_transfer_receiver = first_call.to;
         let simulation_result = get_assets_changes_from_simulation(
-            state.providers.simulation_provider.clone(),
+            state.providers().simulation_provider.clone(),
             request_payload.transaction.chain_id.clone(),
             request_payload.transaction.from,
             first_call.to,
@@ -334,7 +339,7 @@ async fn handler_internal(
                     // Get the ERC20 transfer gas estimation for the token contract
                     // and chain_id, or simulate the transaction to get the gas used
                     let gas_used = match state
-                        .providers
+                        .providers()
                         .simulation_provider
                         .get_cached_gas_estimation(
                             &request_payload.transaction.chain_id.clone(),
@@ -346,7 +351,7 @@ async fn handler_internal(
                         Some(gas) => gas,
                         None => {
                             let simulation_result = get_assets_changes_from_simulation(
-                                state.providers.simulation_provider.clone(),
+                                state.providers().simulation_provider.clone(),
                                 request_payload.transaction.chain_id.clone(),
                                 request_payload.transaction.from,
                                 first_call.to,
@@ -376,7 +381,7 @@ async fn handler_internal(
                                 let initial_chain_id = request_payload.transaction.chain_id.clone();
                                 tokio::spawn(async move {
                                     state
-                                        .providers
+                                        .providers()
                                         .simulation_provider
                                         .set_cached_gas_estimation(
                                             &initial_chain_id,
@@ -405,7 +410,7 @@ async fn handler_internal(
                     );
 
                     let simulation_result = get_assets_changes_from_simulation(
-                        state.providers.simulation_provider.clone(),
+                        state.providers().simulation_provider.clone(),
                         request_payload.transaction.chain_id.clone(),
                         request_payload.transaction.from,
                         first_call.to,
@@ -646,7 +651,7 @@ async fn handler_internal(
         Eip155OrSolanaAddress::Eip155(bridge_contract) if !query_params.use_lifi => {
             // Get Quotes for the bridging
             let quotes = state
-                .providers
+                .providers()
                 .chain_orchestrator_provider
                 .get_bridging_quotes(
                     bridge_chain_id.clone(),
@@ -721,7 +726,7 @@ async fn handler_internal(
 
             // Get quotes for updated topup amount
             let quotes = state
-                .providers
+                .providers()
                 .chain_orchestrator_provider
                 .get_bridging_quotes(
                     bridge_chain_id.clone(),
@@ -775,7 +780,7 @@ async fn handler_internal(
 
             // Build bridging transaction
             let bridge_tx = state
-                .providers
+                .providers()
                 .chain_orchestrator_provider
                 .build_bridging_tx(best_route.clone(), state.metrics.clone())
                 .await?;
@@ -785,7 +790,7 @@ async fn handler_internal(
             // Check for the allowance
             if let Some(approval_data) = bridge_tx.approval_data {
                 let allowance = state
-                    .providers
+                    .providers()
                     .chain_orchestrator_provider
                     .check_allowance(
                         format!("eip155:{}", bridge_tx.chain_id),
@@ -799,7 +804,7 @@ async fn handler_internal(
                 // Check if the approval transaction injection is needed
                 if approval_data.minimum_approval_amount >= allowance {
                     let approval_tx = state
-                        .providers
+                        .providers()
                         .chain_orchestrator_provider
                         .build_approval_tx(
                             format!("eip155:{}", bridge_tx.chain_id),
@@ -864,7 +869,7 @@ async fn handler_internal(
             // Skip the simulation if the bridging transaction is a native token transfer
             if routes.len() != 1 || bridging_transaction.gas_limit.is_zero() {
                 let simulation_results = state
-                    .providers
+                    .providers()
                     .simulation_provider
                     .simulate_bundled_transactions(
                         routes.clone(),
@@ -1134,18 +1139,19 @@ async fn handler_internal(
         amount_expected: asset_transfer_value, // The total transfer amount expected
         status: BridgingStatus::Pending,
         error_reason: None,
+        route_cache_key: Some(route_cache_key.clone()),
     };
-    let irn_client = state.irn.as_ref().ok_or(RpcError::IrnNotConfigured)?;
-    let irn_call_start = SystemTime::now();
+    let irn_client = state
+        .persistent_storage
+        .as_ref()
+        .ok_or(RpcError::StorageNotConfigured)?;
     irn_client
         .set(
             orchestration_id.clone(),
             serde_json::to_string(&bridging_status_item)?.into(),
+            state.config.storage.orchestrations_ttl(),
         )
         .await?;
-    state
-        .metrics
-        .add_irn_latency(irn_call_start, OperationType::Set);
 
     // Analytics
     {
@@ -1224,8 +1230,8 @@ async fn handler_internal(
             asset_transfer_contract.to_string(),
         ));
 
-    return Ok(Json(PrepareResponse::Success(
-        PrepareResponseSuccess::Available(PrepareResponseAvailable {
+    let response = PrepareResponse::Success(PrepareResponseSuccess::Available(
+        PrepareResponseAvailable {
             orchestration_id,
             initial_transaction: Transaction {
                 from: request_payload.transaction.from,
@@ -1260,8 +1266,11 @@ async fn handler_internal(
                     decimals: initial_tx_token_decimals,
                 },
             },
-        }),
-    )));
+        },
+    ));
+    set_cached_route(&state, &route_cache_key, &response).await;
+
+    Ok(Json(response))
 }
 
 fn construct_metrics_bridging_route(
@@ -1311,12 +1320,3 @@ pub enum RouteSolanaRequestError {
     #[error("Malformed Solana account: {0}")]
     MalformedSolanaAccount(SolanaParsePubkeyError),
 }
-
-impl RouteSolanaError {
-    pub fn into_response(&self) -> Response {
-        match self {
-            Self::Internal(_e) => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
-            Self::Request(e) => (StatusCode::BAD_REQUEST, e.to_string()).into_response(),
-        }
-    }
-}
@@ -1,7 +1,10 @@
 use {
-    super::{BridgingStatus, StorageBridgingItem, BRIDGING_TIMEOUT, STATUS_POLLING_INTERVAL},
+    super::{
+        invalidate_cached_route, BridgingStatus, StorageBridgingItem, BRIDGING_TIMEOUT,
+        STATUS_POLLING_INTERVAL,
+    },
     crate::{
-        analytics::MessageSource, error::RpcError, state::AppState, storage::irn::OperationType,
+        analytics::MessageSource, error::RpcError, state::AppState,
         utils::crypto::get_erc20_balance,
     },
     alloy::primitives::U256,
@@ -38,19 +41,18 @@ async fn handler_internal(
         .validate_project_access_and_quota(query_params.project_id.as_ref())
         .await?;
 
-    let irn_client = state.irn.as_ref().ok_or(RpcError::IrnNotConfigured)?;
+    let irn_client = state
+        .persistent_storage
+        .as_ref()
+        .ok_or(RpcError::StorageNotConfigured)?;
 
     // Get the bridging request status from the IRN
-    let irn_call_start = SystemTime::now();
     let irn_result = irn_client
         .get(query_params.orchestration_id.clone())
         .await?
         .ok_or(RpcError::OrchestrationIdNotFound(
             query_params.orchestration_id.clone(),
         ))?;
-    state
-        .metrics
-        .add_irn_latency(irn_call_start, OperationType::Get);
     let mut bridging_status_item = serde_json::from_slice::<StorageBridgingItem>(&irn_result)?;
 
     // Return without checking the balance if the status is completed or errored
@@ -85,16 +87,14 @@ async fn handler_internal(
     if U256::from_be_bytes(wallet_balance.into()) >= bridging_status_item.amount_expected {
         // The balance was fullfilled, update the status to completed
         bridging_status_item.status = BridgingStatus::Completed;
-        let irn_call_start = SystemTime::now();
         irn_client
             .set(
                 query_params.orchestration_id,
                 serde_json::to_vec(&bridging_status_item)?,
+                state.config.storage.orchestrations_ttl(),
             )
             .await?;
-        state
-            .metrics
-            .add_irn_latency(irn_call_start, OperationType::Set);
+        invalidate_cached_route(&state, bridging_status_item.route_cache_key.as_deref()).await;
 
         return Ok(Json(StatusResponse::Completed(StatusResponseCompleted {
             created_at: bridging_status_item.created_at,
@@ -123,16 +123,14 @@ async fn handler_internal(
     {
         bridging_status_item.status = BridgingStatus::Error;
         bridging_status_item.error_reason = Some("Bridging timeout".to_string());
-        let irn_call_start = SystemTime::now();
         irn_client
             .set(
                 query_params.orchestration_id,
                 serde_json::to_vec(&bridging_status_item)?,
+                state.config.storage.orchestrations_ttl(),
             )
             .await?;
-        state
-            .metrics
-            .add_irn_latency(irn_call_start, OperationType::Set);
+        invalidate_cached_route(&state, bridging_status_item.route_cache_key.as_deref()).await;
 
         return Ok(Json(StatusResponse::Error(StatusResponseError {
             created_at: bridging_status_item.created_at,
@@ -45,7 +45,7 @@ async fn handler_internal(
         .await?;
 
     let response = state
-        .providers
+        .providers()
         .conversion_provider
         .get_allowance(query.0, state.metrics.clone())
         .await
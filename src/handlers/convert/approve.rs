@@ -60,7 +60,7 @@ async fn handler_internal(
         .await?;
 
     let response = state
-        .providers
+        .providers()
         .conversion_provider
         .build_approve_tx(query.0, state.metrics.clone())
         .await
@@ -0,0 +1,289 @@
+use {
+    crate::{
+        analytics::MessageSource,
+        error::RpcError,
+        handlers::{self_provider::SelfProviderPool, SdkInfoParams},
+        state::AppState,
+        utils::crypto::{allowanceCall, disassemble_caip10, CaipNamespaces},
+    },
+    alloy::{
+        primitives::{address, Address, U256},
+        sol,
+        sol_types::SolCall,
+    },
+    axum::{
+        extract::{ConnectInfo, Query, State},
+        Json,
+    },
+    futures_util::future::try_join_all,
+    hyper::HeaderMap,
+    serde::{Deserialize, Serialize},
+    std::{collections::HashMap, net::SocketAddr, str::FromStr, sync::Arc},
+    tracing::error,
+    wc::metrics::{future_metrics, FutureExt},
+};
+
+/// Canonical Multicall3 contract address, the same on all the EVM chains
+const MULTICALL3_ADDRESS: Address = address!("cA11bde05977b3631167028862bE2a173976CA11");
+
+/// Maximum number of the allowance checks per request
+const MAX_CHECKS: usize = 100;
+
+sol! {
+    #[sol(rpc)]
+    interface IMulticall3 {
+        struct Call3 {
+            address target;
+            bool allowFailure;
+            bytes callData;
+        }
+
+        struct Result {
+            bool success;
+            bytes returnData;
+        }
+
+        function aggregate3(Call3[] calldata calls)
+            external
+            payable
+            returns (Result[] memory returnData);
+    }
+}
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchAllowanceQueryParams {
+    pub project_id: String,
+    #[serde(flatten)]
+    pub sdk_info: SdkInfoParams,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchAllowanceRequestBody {
+    /// Owner address, the same on all of the checked chains
+    pub user_address: Address,
+    pub checks: Vec<AllowanceCheck>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct AllowanceCheck {
+    /// CAIP-10 address of the ERC-20 token
+    pub token_address: String,
+    pub spender: Address,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchAllowanceResponseBody {
+    /// Allowances in the order of the requested checks
+    pub allowances: Vec<AllowanceResult>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct AllowanceResult {
+    pub token_address: String,
+    pub spender: Address,
+    pub allowance: String,
+}
+
+pub async fn handler(
+    state: State<Arc<AppState>>,
+    connect_info: ConnectInfo<SocketAddr>,
+    query: Query<BatchAllowanceQueryParams>,
+    headers: HeaderMap,
+    Json(request_payload): Json<BatchAllowanceRequestBody>,
+) -> Result<Json<BatchAllowanceResponseBody>, RpcError> {
+    handler_internal(state, connect_info, query, headers, request_payload)
+        .with_metrics(future_metrics!("handler_task", "name" => "conversion_batch_allowance"))
+        .await
+}
+
+#[tracing::instrument(skip_all, level = "debug")]
+async fn handler_internal(
+    State(state): State<Arc<AppState>>,
+    ConnectInfo(connect_info): ConnectInfo<SocketAddr>,
+    Query(query): Query<BatchAllowanceQueryParams>,
+    headers: HeaderMap,
+    request_payload: BatchAllowanceRequestBody,
+) -> Result<Json<BatchAllowanceResponseBody>, RpcError> {
+    state
+        .validate_project_access_and_quota(&query.project_id)
+        .await?;
+
+    if request_payload.checks.len() > MAX_CHECKS {
+        return Err(RpcError::ConversionInvalidParameter(format!(
+            "the number of the allowance checks exceeds {MAX_CHECKS}"
+        )));
+    }
+    let checks_by_chain = group_checks_by_chain(&request_payload.checks)?;
+
+    let provider_pool = SelfProviderPool {
+        state,
+        connect_info,
+        headers,
+        project_id: query.project_id.as_str().into(),
+        sdk_info: query.sdk_info,
+        session_id: None,
+    };
+    let chains_allowances = try_join_all(checks_by_chain.iter().map(|(chain_id, checks)| {
+        get_chain_allowances(
+            &provider_pool,
+            chain_id,
+            request_payload.user_address,
+            checks,
+        )
+    }))
+    .await?;
+
+    let mut allowances = vec![None; request_payload.checks.len()];
+    for (checks, chain_allowances) in checks_by_chain.values().zip(chains_allowances) {
+        for ((index, _, _), allowance) in checks.iter().zip(chain_allowances) {
+            allowances[*index] = Some(allowance);
+        }
+    }
+
+    Ok(Json(BatchAllowanceResponseBody {
+        allowances: request_payload
+            .checks
+            .into_iter()
+            .zip(allowances)
+            .map(|(check, allowance)| AllowanceResult {
+                token_address: check.token_address,
+                spender: check.spender,
+                allowance: allowance.unwrap_or_default().to_string(),
+            })
+            .collect(),
+    }))
+}
+
+/// Group the checks by the CAIP-2 chain ID with their indexes in the request,
+/// token and spender addresses
+fn group_checks_by_chain(
+    checks: &[AllowanceCheck],
+) -> Result<HashMap<String, Vec<(usize, Address, Address)>>, RpcError> {
+    let mut checks_by_chain = HashMap::<_, Vec<_>>::new();
+    for (index, check) in checks.iter().enumerate() {
+        let (namespace, chain_id, token_address) = disassemble_caip10(&check.token_address)?;
+        if namespace != CaipNamespaces::Eip155 {
+            return Err(RpcError::ConversionInvalidParameter(format!(
+                "token {} is not an EVM token",
+                check.token_address
+            )));
+        }
+        let token_address = Address::from_str(&token_address).map_err(|_| {
+            RpcError::ConversionInvalidParameter(format!(
+                "token {} address is invalid",
+                check.token_address
+            ))
+        })?;
+        checks_by_chain
+            .entry(format!("{namespace}:{chain_id}"))
+            .or_default()
+            .push((index, token_address, check.spender));
+    }
+    Ok(checks_by_chain)
+}
+
+/// Allowances of the chain checks resolved by the single Multicall3 call
+/// through the proxy
+async fn get_chain_allowances(
+    provider_pool: &SelfProviderPool,
+    chain_id: &str,
+    owner: Address,
+    checks: &[(usize, Address, Address)],
+) -> Result<Vec<U256>, RpcError> {
+    let provider = provider_pool.get_provider(chain_id.to_owned(), MessageSource::ConvertAllowance);
+    let multicall = IMulticall3::new(MULTICALL3_ADDRESS, &provider);
+    let calls = checks
+        .iter()
+        .map(|(_, token_address, spender)| IMulticall3::Call3 {
+            target: *token_address,
+            allowFailure: true,
+            callData: allowanceCall {
+                _owner: owner,
+                _spender: *spender,
+            }
+            .abi_encode()
+            .into(),
+        })
+        .collect();
+    let results = multicall
+        .aggregate3(calls)
+        .call()
+        .await
+        .map_err(|e| {
+            error!("Failed to call the multicall allowances on {chain_id}: {e}");
+            RpcError::ConversionProviderError
+        })?
+        .returnData;
+
+    checks
+        .iter()
+        .zip(results)
+        .map(|((_, token_address, _), result)| {
+            // Failed calls are the tokens not implementing the ERC-20
+            let allowance = if result.success {
+                allowanceCall::abi_decode_returns(&result.returnData, true).ok()
+            } else {
+                None
+            };
+            allowance.map(|allowance| allowance._0).ok_or_else(|| {
+                RpcError::ConversionInvalidParameter(format!(
+                    "token {chain_id}:{token_address} allowance is not available"
+                ))
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn checks_are_grouped_by_chain() {
+        let spender = address!("111111125421cA6dc452d289314280a0f8842A65");
+        let checks = vec![
+            AllowanceCheck {
+                token_address: "eip155:1:0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48".to_owned(),
+                spender,
+            },
+            AllowanceCheck {
+                token_address: "eip155:8453:0x833589fCD6eDb6E08f4c7C32D4f71b54bdA02913".to_owned(),
+                spender,
+            },
+            AllowanceCheck {
+                token_address: "eip155:1:0xdAC17F958D2ee523a2206206994597C13D831ec7".to_owned(),
+                spender,
+            },
+        ];
+
+        let checks_by_chain = group_checks_by_chain(&checks).unwrap();
+        assert_eq!(checks_by_chain.len(), 2);
+        assert_eq!(
+            checks_by_chain["eip155:1"]
+                .iter()
+                .map(|(index, _, _)| *index)
+                .collect::<Vec<_>>(),
+            vec![0, 2]
+        );
+        assert_eq!(
+            checks_by_chain["eip155:8453"][0].1,
+            address!("833589fCD6eDb6E08f4c7C32D4f71b54bdA02913")
+        );
+    }
+
+    #[test]
+    fn non_evm_tokens_are_rejected() {
+        let checks = vec![AllowanceCheck {
+            token_address:
+                "solana:5eykt4UsFv8P8NJdTREpY1vzqKqZKvdp:EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v"
+                    .to_owned(),
+            spender: Address::ZERO,
+        }];
+        assert!(group_checks_by_chain(&checks).is_err());
+    }
+}
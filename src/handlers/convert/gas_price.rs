@@ -46,7 +46,7 @@ async fn handler_internal(
         .await?;
 
     let response = state
-        .providers
+        .providers()
         .conversion_provider
         .get_gas_price(query.0, state.metrics.clone())
         .await
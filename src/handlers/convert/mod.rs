@@ -1,5 +1,6 @@
 pub mod allowance;
 pub mod approve;
+pub mod batch_allowance;
 pub mod gas_price;
 pub mod quotes;
 pub mod tokens;
@@ -57,7 +57,7 @@ async fn handler_internal(
         .await?;
 
     let response = state
-        .providers
+        .providers()
         .conversion_provider
         .get_tokens_list(query.0, state.metrics.clone())
         .await
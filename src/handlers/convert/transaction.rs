@@ -52,6 +52,9 @@ pub struct ConvertTx {
 pub struct ConvertTxEip155 {
     pub gas: String,
     pub gas_price: String,
+    /// Native token value of the transaction
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<String>,
 }
 
 pub async fn handler(
@@ -73,7 +76,7 @@ async fn handler_internal(
         .await?;
 
     let response = state
-        .providers
+        .providers()
         .conversion_provider
         .build_convert_tx(request_payload, state.metrics.clone())
         .await
@@ -70,6 +70,17 @@ async fn handler_internal(
         return Err(RpcError::InvalidAddress);
     };
 
+    // Scam tokens are responded as unknown to not show their prices
+    if let Some(scam_tokens) = state
+        .scam_tokens
+        .for_project(&state.reloadable_config().balances, &project_id)
+    {
+        if scam_tokens.is_scam(address) {
+            state.metrics.add_scam_tokens_filtered("fungible_price", 1);
+            return Ok(Json(PriceResponseBody { fungibles: vec![] }).into_response());
+        }
+    }
+
     let (mut namespace, chain_id, address) = crypto::disassemble_caip10(address)?;
     if !crypto::is_address_valid(&address, &namespace) {
         return Err(RpcError::InvalidAddress);
@@ -80,8 +91,8 @@ async fn handler_internal(
         namespace = crypto::CaipNamespaces::Rootstock;
     }
 
-    let provider = state
-        .providers
+    let providers = state.providers();
+    let provider = providers
         .fungible_price_providers
         .get(&namespace)
         .ok_or_else(|| RpcError::UnsupportedNamespace(namespace))?;
@@ -91,7 +102,7 @@ async fn handler_internal(
             &chain_id,
             &address,
             &query.currency,
-            &state.providers.token_metadata_cache,
+            &providers.token_metadata_cache,
             state.metrics.clone(),
         )
         .await
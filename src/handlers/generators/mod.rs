@@ -1,4 +1,4 @@
-use serde::Deserialize;
+use {onrampurl::OnRampUrlProvider, serde::Deserialize};
 
 pub mod onrampurl;
 
@@ -6,4 +6,6 @@ pub mod onrampurl;
 #[serde(rename_all = "camelCase")]
 pub struct GeneratorQueryParams {
     pub project_id: String,
+    /// Onramp widget provider of the generated URL, Coinbase Pay by default
+    pub provider: Option<OnRampUrlProvider>,
 }
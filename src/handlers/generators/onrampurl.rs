@@ -6,8 +6,10 @@ use {
         response::{IntoResponse, Response},
         Json,
     },
+    base64::{engine::general_purpose::STANDARD, Engine},
     hyper::{HeaderMap, StatusCode},
-    serde::{Deserialize, Serialize},
+    openssl::{hash::MessageDigest, pkey::PKey, sign::Signer},
+    serde::{de::DeserializeOwned, Deserialize, Serialize},
     std::{fmt, net::SocketAddr, sync::Arc},
     tracing::log::{debug, error},
     url::Url,
@@ -60,6 +62,49 @@ pub struct DestinationWallet {
     pub supported_networks: Option<Vec<String>>,
 }
 
+/// Request of the MoonPay buy widget URL
+/// https://dev.moonpay.com/docs/on-ramp-configure-user-journey-params
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct MoonPayURLRequest {
+    #[serde(skip_deserializing)]
+    pub api_key: String,
+    pub wallet_address: String,
+    pub currency_code: Option<String>,
+    pub base_currency_code: Option<String>,
+    pub base_currency_amount: Option<f64>,
+    pub email: Option<String>,
+    pub external_customer_id: Option<String>,
+    pub redirect_url: Option<String>,
+}
+
+/// Request of the Ramp Network widget URL
+/// https://docs.ramp.network/configuration
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RampURLRequest {
+    #[serde(skip_deserializing)]
+    pub host_api_key: String,
+    pub user_address: String,
+    pub swap_asset: Option<String>,
+    pub swap_amount: Option<String>,
+    pub fiat_currency: Option<String>,
+    pub fiat_value: Option<f64>,
+    pub user_email_address: Option<String>,
+    pub host_app_name: Option<String>,
+    pub final_url: Option<String>,
+}
+
+/// Onramp widget provider of the generated URL
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum OnRampUrlProvider {
+    #[default]
+    Coinbase,
+    Moonpay,
+    Ramp,
+}
+
 #[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct OnRampURLResponse {
@@ -68,6 +113,8 @@ pub struct OnRampURLResponse {
 
 const CB_PAY_HOST: &str = "https://pay.coinbase.com";
 const CB_PAY_PATH: &str = "/buy/select-asset";
+const MOONPAY_HOST: &str = "https://buy.moonpay.com";
+const RAMP_HOST: &str = "https://app.ramp.network";
 
 pub async fn handler(
     state: State<Arc<AppState>>,
@@ -95,31 +142,57 @@ async fn handler_internal(
         .validate_project_access_and_quota(&query_params.project_id)
         .await?;
 
-    let cb_app_id = match state.config.providers.clone().coinbase_app_id {
-        Some(app_id) => app_id,
-        None => {
-            error!("Coinbase App ID is not configured");
-            return Ok((StatusCode::INTERNAL_SERVER_ERROR, "").into_response());
+    let providers = &state.config.providers;
+    let provider = query_params.provider.unwrap_or_default();
+    let on_ramp_url = match provider {
+        OnRampUrlProvider::Coinbase => {
+            let Some(cb_app_id) = providers.coinbase_app_id.clone() else {
+                error!("Coinbase App ID is not configured");
+                return Ok((StatusCode::INTERNAL_SERVER_ERROR, "").into_response());
+            };
+            let mut parameters = match deserialize_body::<OnRampURLRequest>(&body) {
+                Ok(parameters) => parameters,
+                Err(response) => return Ok(response),
+            };
+            parameters.app_id = cb_app_id;
+            generate_on_ramp_url(CB_PAY_HOST, CB_PAY_PATH, parameters)
         }
-    };
-
-    let mut parameters = match serde_json::from_slice::<OnRampURLRequest>(&body) {
-        Ok(parameters) => parameters,
-        Err(e) => {
-            debug!("Error deserializing request body: {e}");
-            return Ok((
-                StatusCode::BAD_REQUEST,
-                "Error deserializing request body: {}",
-            )
-                .into_response());
+        OnRampUrlProvider::Moonpay => {
+            let (Some(api_key), Some(secret_key)) = (
+                providers.moonpay_api_key.clone(),
+                providers.moonpay_secret_key.as_deref(),
+            ) else {
+                error!("MoonPay API keys are not configured");
+                return Ok((StatusCode::INTERNAL_SERVER_ERROR, "").into_response());
+            };
+            let mut parameters = match deserialize_body::<MoonPayURLRequest>(&body) {
+                Ok(parameters) => parameters,
+                Err(response) => return Ok(response),
+            };
+            parameters.api_key = api_key;
+            generate_moonpay_url(MOONPAY_HOST, secret_key, parameters)
+        }
+        OnRampUrlProvider::Ramp => {
+            let (Some(host_api_key), Some(secret_key)) = (
+                providers.ramp_host_api_key.clone(),
+                providers.ramp_secret_key.as_deref(),
+            ) else {
+                error!("Ramp Network API keys are not configured");
+                return Ok((StatusCode::INTERNAL_SERVER_ERROR, "").into_response());
+            };
+            let mut parameters = match deserialize_body::<RampURLRequest>(&body) {
+                Ok(parameters) => parameters,
+                Err(response) => return Ok(response),
+            };
+            parameters.host_api_key = host_api_key;
+            generate_ramp_url(RAMP_HOST, secret_key, parameters)
         }
     };
-    parameters.app_id = cb_app_id;
 
-    let on_ramp_url = match generate_on_ramp_url(CB_PAY_HOST, CB_PAY_PATH, parameters) {
+    let on_ramp_url = match on_ramp_url {
         Ok(on_ramp_url) => on_ramp_url,
         Err(e) => {
-            error!("Error generating on-ramp URL: {e}");
+            error!("Error generating {provider:?} on-ramp URL: {e}");
             return Ok((StatusCode::INTERNAL_SERVER_ERROR, "").into_response());
         }
     };
@@ -127,6 +200,101 @@ async fn handler_internal(
     Ok(Json(OnRampURLResponse { url: on_ramp_url }).into_response())
 }
 
+fn deserialize_body<T: DeserializeOwned>(body: &[u8]) -> Result<T, Response> {
+    serde_json::from_slice::<T>(body).map_err(|e| {
+        debug!("Error deserializing request body: {e}");
+        (
+            StatusCode::BAD_REQUEST,
+            "Error deserializing request body: {}",
+        )
+            .into_response()
+    })
+}
+
+/// HMAC-SHA256 signature of the data by the secret key
+fn hmac_sha256(secret_key: &str, data: &str) -> Result<Vec<u8>, anyhow::Error> {
+    let pkey = PKey::hmac(secret_key.as_bytes())?;
+    let mut signer = Signer::new(MessageDigest::sha256(), &pkey)?;
+    signer.update(data.as_bytes())?;
+    Ok(signer.sign_to_vec()?)
+}
+
+/// MoonPay widget URL signed by the base64-encoded signature of the query
+/// string including the leading `?`
+pub fn generate_moonpay_url(
+    host: &str,
+    secret_key: &str,
+    parameters: MoonPayURLRequest,
+) -> Result<String, anyhow::Error> {
+    let mut url = Url::parse(host)?;
+    {
+        let mut query = url.query_pairs_mut();
+        query
+            .append_pair("apiKey", &parameters.api_key)
+            .append_pair("walletAddress", &parameters.wallet_address);
+        let optional = [
+            ("currencyCode", parameters.currency_code),
+            ("baseCurrencyCode", parameters.base_currency_code),
+            (
+                "baseCurrencyAmount",
+                parameters
+                    .base_currency_amount
+                    .map(|amount| amount.to_string()),
+            ),
+            ("email", parameters.email),
+            ("externalCustomerId", parameters.external_customer_id),
+            ("redirectURL", parameters.redirect_url),
+        ];
+        for (name, value) in optional {
+            if let Some(value) = value {
+                query.append_pair(name, &value);
+            }
+        }
+    }
+
+    let query = format!("?{}", url.query().unwrap_or_default());
+    let signature = STANDARD.encode(hmac_sha256(secret_key, &query)?);
+    url.query_pairs_mut().append_pair("signature", &signature);
+    Ok(url.to_string())
+}
+
+/// Ramp Network widget URL signed by the base64-encoded signature of the
+/// query string without the leading `?`
+pub fn generate_ramp_url(
+    host: &str,
+    secret_key: &str,
+    parameters: RampURLRequest,
+) -> Result<String, anyhow::Error> {
+    let mut url = Url::parse(host)?;
+    {
+        let mut query = url.query_pairs_mut();
+        query
+            .append_pair("hostApiKey", &parameters.host_api_key)
+            .append_pair("userAddress", &parameters.user_address);
+        let optional = [
+            ("swapAsset", parameters.swap_asset),
+            ("swapAmount", parameters.swap_amount),
+            ("fiatCurrency", parameters.fiat_currency),
+            (
+                "fiatValue",
+                parameters.fiat_value.map(|value| value.to_string()),
+            ),
+            ("userEmailAddress", parameters.user_email_address),
+            ("hostAppName", parameters.host_app_name),
+            ("finalUrl", parameters.final_url),
+        ];
+        for (name, value) in optional {
+            if let Some(value) = value {
+                query.append_pair(name, &value);
+            }
+        }
+    }
+
+    let signature = STANDARD.encode(hmac_sha256(secret_key, url.query().unwrap_or_default())?);
+    url.query_pairs_mut().append_pair("signature", &signature);
+    Ok(url.to_string())
+}
+
 pub fn generate_on_ramp_url(
     host: &str,
     path: &str,
@@ -222,3 +390,72 @@ fn ensure_generate_on_ramp_url() {
         partner_user_id
     );
 }
+
+#[test]
+fn ensure_generate_moonpay_url() {
+    let secret_key = "MOONPAY_TEST_SECRET_KEY";
+    let parameters = MoonPayURLRequest {
+        api_key: "pk_test_key".to_string(),
+        wallet_address: "0x1234567890123456789012345678901234567890".to_string(),
+        currency_code: Some("eth".to_string()),
+        base_currency_code: None,
+        base_currency_amount: Some(50.5),
+        email: None,
+        external_customer_id: None,
+        redirect_url: Some("https://example.com/done?x=1".to_string()),
+    };
+
+    let url =
+        Url::parse(&generate_moonpay_url(MOONPAY_HOST, secret_key, parameters).unwrap()).unwrap();
+    assert_eq!(url.host_str().unwrap(), "buy.moonpay.com");
+    let (signed, _) = url.query().unwrap().rsplit_once("&signature=").unwrap();
+    assert_eq!(
+        signed,
+        "apiKey=pk_test_key&walletAddress=0x1234567890123456789012345678901234567890\
+         &currencyCode=eth&baseCurrencyAmount=50.5\
+         &redirectURL=https%3A%2F%2Fexample.com%2Fdone%3Fx%3D1"
+    );
+    let signature = url
+        .query_pairs()
+        .find(|(key, _)| key == "signature")
+        .unwrap()
+        .1;
+    assert_eq!(
+        STANDARD.decode(signature.as_bytes()).unwrap(),
+        hmac_sha256(secret_key, &format!("?{signed}")).unwrap()
+    );
+}
+
+#[test]
+fn ensure_generate_ramp_url() {
+    let secret_key = "RAMP_TEST_SECRET_KEY";
+    let parameters = RampURLRequest {
+        host_api_key: "ramp_test_key".to_string(),
+        user_address: "0x1234567890123456789012345678901234567890".to_string(),
+        swap_asset: Some("ETH_USDC".to_string()),
+        swap_amount: None,
+        fiat_currency: Some("EUR".to_string()),
+        fiat_value: Some(100.0),
+        user_email_address: None,
+        host_app_name: Some("Test App".to_string()),
+        final_url: None,
+    };
+
+    let url = Url::parse(&generate_ramp_url(RAMP_HOST, secret_key, parameters).unwrap()).unwrap();
+    assert_eq!(url.host_str().unwrap(), "app.ramp.network");
+    let (signed, _) = url.query().unwrap().rsplit_once("&signature=").unwrap();
+    assert_eq!(
+        signed,
+        "hostApiKey=ramp_test_key&userAddress=0x1234567890123456789012345678901234567890\
+         &swapAsset=ETH_USDC&fiatCurrency=EUR&fiatValue=100&hostAppName=Test+App"
+    );
+    let signature = url
+        .query_pairs()
+        .find(|(key, _)| key == "signature")
+        .unwrap()
+        .1;
+    assert_eq!(
+        STANDARD.decode(signature.as_bytes()).unwrap(),
+        hmac_sha256(secret_key, signed).unwrap()
+    );
+}
@@ -0,0 +1,276 @@
+//! GraphQL endpoint over the account data.
+
+use {
+    super::{
+        balance::{self, BalanceItem, BalanceQueryParams},
+        history::{self, HistoryQueryParams, HistoryResponseBody},
+        identity::{self, IdentityQueryParams, IdentityResponse},
+        portfolio::{self, PortfolioPosition, PortfolioQueryParams, PortfolioResponseBody},
+        SdkInfoParams, SupportedCurrencies,
+    },
+    crate::{error::RpcError, state::AppState},
+    async_graphql::{Context, EmptyMutation, EmptySubscription, ErrorExtensions, Object, Schema},
+    axum::{
+        body::to_bytes,
+        extract::{ConnectInfo, Path, Query, State},
+        response::{IntoResponse, Response},
+        Json,
+    },
+    hyper::HeaderMap,
+    once_cell::sync::Lazy,
+    serde::{de::DeserializeOwned, Deserialize},
+    std::{net::SocketAddr, sync::Arc},
+    wc::metrics::{future_metrics, FutureExt},
+};
+
+const MAX_QUERY_DEPTH: usize = 8;
+const MAX_QUERY_COMPLEXITY: usize = 256;
+const DEFAULT_CURRENCY: &str = "usd";
+/// Maximum size of the handlers responses decoded by the resolvers
+const RESPONSE_BODY_MAX_BYTES: usize = 32 * 1024 * 1024;
+
+pub type AccountSchema = Schema<QueryRoot, EmptyMutation, EmptySubscription>;
+
+static SCHEMA: Lazy<AccountSchema> = Lazy::new(|| {
+    Schema::build(QueryRoot, EmptyMutation, EmptySubscription)
+        .limit_depth(MAX_QUERY_DEPTH)
+        .limit_complexity(MAX_QUERY_COMPLEXITY)
+        .finish()
+});
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct GraphqlQueryParams {
+    pub project_id: String,
+    #[serde(flatten)]
+    pub sdk_info: SdkInfoParams,
+}
+
+/// Request context of the resolvers
+struct RequestContext {
+    state: Arc<AppState>,
+    query: GraphqlQueryParams,
+    connect_info: SocketAddr,
+    headers: HeaderMap,
+}
+
+pub async fn handler(
+    state: State<Arc<AppState>>,
+    connect_info: ConnectInfo<SocketAddr>,
+    query: Query<GraphqlQueryParams>,
+    headers: HeaderMap,
+    Json(request): Json<async_graphql::Request>,
+) -> Result<Json<async_graphql::Response>, RpcError> {
+    handler_internal(state, connect_info, query, headers, request)
+        .with_metrics(future_metrics!("handler_task", "name" => "graphql"))
+        .await
+}
+
+#[tracing::instrument(skip_all, level = "debug")]
+async fn handler_internal(
+    State(state): State<Arc<AppState>>,
+    ConnectInfo(connect_info): ConnectInfo<SocketAddr>,
+    Query(query): Query<GraphqlQueryParams>,
+    headers: HeaderMap,
+    request: async_graphql::Request,
+) -> Result<Json<async_graphql::Response>, RpcError> {
+    state
+        .validate_project_access_and_quota(&query.project_id)
+        .await?;
+
+    let request = request.data(RequestContext {
+        state,
+        query,
+        connect_info,
+        headers,
+    });
+    Ok(Json(SCHEMA.execute(request).await))
+}
+
+/// GraphQL error of the handler error, the internal errors details are not
+/// exposed the same way as for the REST endpoints
+fn graphql_error(e: RpcError) -> async_graphql::Error {
+    let code = e.code();
+    let message = e.to_string();
+    let message = if e.into_response().status().is_server_error() {
+        "Internal server error".to_owned()
+    } else {
+        message
+    };
+    async_graphql::Error::new(message)
+        .extend_with(|_, extensions| extensions.set("code", code.as_ref()))
+}
+
+async fn decode<T: DeserializeOwned>(response: Response) -> async_graphql::Result<T> {
+    let body = to_bytes(response.into_body(), RESPONSE_BODY_MAX_BYTES).await?;
+    Ok(serde_json::from_slice(&body)?)
+}
+
+pub struct QueryRoot;
+
+#[Object]
+impl QueryRoot {
+    /// Account data by the address
+    async fn account(&self, address: String) -> Account {
+        Account { address }
+    }
+}
+
+pub struct Account {
+    address: String,
+}
+
+#[Object]
+impl Account {
+    async fn address(&self) -> &str {
+        &self.address
+    }
+
+    /// Resolved identity of the address
+    async fn identity(
+        &self,
+        ctx: &Context<'_>,
+        use_cache: Option<bool>,
+    ) -> async_graphql::Result<IdentityResponse> {
+        let context = ctx.data_unchecked::<RequestContext>();
+        let response = identity::handler(
+            State(context.state.clone()),
+            ConnectInfo(context.connect_info),
+            Query(IdentityQueryParams {
+                project_id: context.query.project_id.clone(),
+                use_cache,
+                client_id: None,
+                sender: None,
+                sdk_info: context.query.sdk_info.clone(),
+            }),
+            context.headers.clone(),
+            Path(self.address.clone()),
+        )
+        .await
+        .map_err(graphql_error)?;
+        decode(response).await
+    }
+
+    /// Fungible balances, the currency defaults to `usd`
+    async fn balances(
+        &self,
+        ctx: &Context<'_>,
+        currency: Option<String>,
+        chain_id: Option<String>,
+    ) -> async_graphql::Result<Vec<BalanceItem>> {
+        let context = ctx.data_unchecked::<RequestContext>();
+        let currency = serde_json::from_value::<SupportedCurrencies>(serde_json::Value::String(
+            currency.unwrap_or_else(|| DEFAULT_CURRENCY.to_owned()),
+        ))
+        .map_err(|e| graphql_error(RpcError::InvalidParameter(format!("Invalid currency: {e}"))))?;
+        let Json(response) = balance::handler(
+            State(context.state.clone()),
+            Query(BalanceQueryParams {
+                project_id: context.query.project_id.clone(),
+                currency,
+                chain_id,
+                force_update: None,
+                tokens: None,
+                sdk_info: context.query.sdk_info.clone(),
+            }),
+            ConnectInfo(context.connect_info),
+            context.headers.clone(),
+            Path(self.address.clone()),
+        )
+        .await
+        .map_err(graphql_error)?;
+        Ok(response.balances)
+    }
+
+    /// Transactions history page, the next page is requested by the cursor
+    async fn history(
+        &self,
+        ctx: &Context<'_>,
+        currency: Option<String>,
+        chain_id: Option<String>,
+        cursor: Option<String>,
+    ) -> async_graphql::Result<HistoryResponseBody> {
+        let context = ctx.data_unchecked::<RequestContext>();
+        let response = history::handler(
+            State(context.state.clone()),
+            ConnectInfo(context.connect_info),
+            Query(HistoryQueryParams {
+                currency,
+                project_id: context.query.project_id.clone(),
+                chain_id,
+                cursor,
+                onramp: None,
+                include_onramp: None,
+                sdk_info: context.query.sdk_info.clone(),
+            }),
+            context.headers.clone(),
+            Path(self.address.clone()),
+        )
+        .await
+        .map_err(graphql_error)?;
+        decode(response).await
+    }
+
+    /// Portfolio positions
+    async fn portfolio(
+        &self,
+        ctx: &Context<'_>,
+        currency: Option<String>,
+    ) -> async_graphql::Result<Vec<PortfolioPosition>> {
+        let context = ctx.data_unchecked::<RequestContext>();
+        let response = portfolio::handler(
+            State(context.state.clone()),
+            ConnectInfo(context.connect_info),
+            Query(PortfolioQueryParams {
+                project_id: context.query.project_id.clone(),
+                currency,
+                include_nfts: None,
+            }),
+            context.headers.clone(),
+            Path(self.address.clone()),
+        )
+        .await
+        .map_err(graphql_error)?;
+        let response = decode::<PortfolioResponseBody>(response).await?;
+        Ok(response.data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn resolves_requested_fields_only() {
+        // Account data fields are not requested, so the resolvers without the
+        // request context are not called
+        let response = SCHEMA
+            .execute(r#"{ account(address: "0x1") { address } }"#)
+            .await;
+        assert!(response.errors.is_empty());
+        assert_eq!(
+            response.data.into_json().unwrap(),
+            serde_json::json!({ "account": { "address": "0x1" } })
+        );
+    }
+
+    #[tokio::test]
+    async fn limits_query_complexity() {
+        let accounts = (0..MAX_QUERY_COMPLEXITY)
+            .map(|i| format!(r#"a{i}: account(address: "0x{i:x}") {{ address }}"#))
+            .collect::<Vec<_>>()
+            .join(" ");
+        let response = SCHEMA.execute(format!("{{ {accounts} }}")).await;
+        assert_eq!(response.errors.len(), 1);
+        assert!(response.errors[0].message.contains("too complex"));
+    }
+
+    #[test]
+    fn hides_internal_errors() {
+        let error = graphql_error(RpcError::InvalidAddress);
+        assert_eq!(error.message, RpcError::InvalidAddress.to_string());
+
+        let error = graphql_error(RpcError::InvalidConfiguration("secret".to_owned()));
+        assert_eq!(error.message, "Internal server error");
+    }
+}
@@ -1,11 +1,84 @@
 use {
-    crate::state::AppState,
-    axum::{extract::State, response::IntoResponse},
+    crate::{
+        analytics,
+        state::AppState,
+        storage::{irn::MigrationMode, PersistentStorage},
+        validate_config::{check, ping_method, ping_provider, CheckResult},
+    },
+    axum::{
+        extract::{Query, State},
+        response::{IntoResponse, Response},
+        Json,
+    },
     hyper::StatusCode,
-    std::sync::Arc,
+    serde::{Deserialize, Serialize},
+    std::{sync::Arc, time::Duration},
 };
 
-pub async fn handler(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+/// Timeout of the every single readiness check
+const READINESS_CHECK_TIMEOUT: Duration = Duration::from_secs(2);
+/// Key missing in the persistent storage read by the IRN readiness check
+const READINESS_CHECK_KEY: &str = "readiness-check";
+/// Timeout of the sampled provider call of the verbose health check
+const PROVIDER_CHECK_TIMEOUT: Duration = Duration::from_secs(5);
+/// Consecutive failed analytics exports to report the exporter as unhealthy
+const MAX_ANALYTICS_EXPORT_FAILURES: u64 = 3;
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReadinessResponse {
+    pub ready: bool,
+    pub checks: Vec<CheckResult>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HealthQueryParams {
+    pub verbose: Option<bool>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HealthResponse {
+    pub healthy: bool,
+    pub version: String,
+    pub commit_hash: String,
+    pub uptime_secs: u64,
+    pub components: Vec<ComponentStatus>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ComponentStatus {
+    #[serde(flatten)]
+    pub check: CheckResult,
+    /// Failed critical component makes the health check respond with 503
+    pub critical: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub details: Option<serde_json::Value>,
+}
+
+impl ComponentStatus {
+    fn critical(check: CheckResult) -> Self {
+        Self {
+            check,
+            critical: true,
+            details: None,
+        }
+    }
+}
+
+/// Liveness probe, responds as soon as the router is up. The verbose health
+/// check reports the dependencies status and responds with 503 while any of
+/// the critical dependencies is down.
+pub async fn handler(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<HealthQueryParams>,
+) -> Response {
+    if query.verbose.unwrap_or(false) {
+        return verbose_handler(state).await.into_response();
+    }
+
     (
         StatusCode::OK,
         format!(
@@ -16,4 +89,189 @@ pub async fn handler(State(state): State<Arc<AppState>>) -> impl IntoResponse {
             state.uptime.elapsed().as_secs()
         ),
     )
+        .into_response()
+}
+
+async fn verbose_handler(state: Arc<AppState>) -> impl IntoResponse {
+    let mut components = vec![ComponentStatus::critical(postgres_check(&state).await)];
+
+    if let Some(redis) = &state.project_data_redis {
+        let mut component = ComponentStatus::critical(
+            check("redis/project_data", READINESS_CHECK_TIMEOUT, async {
+                redis.ping().await.map_err(|e| e.to_string())
+            })
+            .await,
+        );
+        component.details = serde_json::to_value(redis.pools_status()).ok();
+        components.push(component);
+    }
+
+    if let Some(irn_check) = irn_check(&state).await {
+        components.push(ComponentStatus::critical(irn_check));
+    }
+
+    let exporter_status = analytics::exporter_status();
+    let analytics_ok = exporter_status.consecutive_export_failures < MAX_ANALYTICS_EXPORT_FAILURES;
+    components.push(ComponentStatus {
+        check: CheckResult {
+            name: "analytics".to_owned(),
+            ok: analytics_ok,
+            error: (!analytics_ok).then(|| "analytics export is failing".to_owned()),
+        },
+        critical: false,
+        details: serde_json::to_value(exporter_status).ok(),
+    });
+
+    for namespace in &state.config.server.readiness_namespaces {
+        components.push(provider_check(&state, namespace).await);
+    }
+
+    let healthy = components
+        .iter()
+        .all(|component| component.check.ok || !component.critical);
+    let status = if healthy {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    (
+        status,
+        Json(HealthResponse {
+            healthy,
+            version: state.compile_info.build().version().to_string(),
+            commit_hash: state.compile_info.git().short_hash().to_string(),
+            uptime_secs: state.uptime.elapsed().as_secs(),
+            components,
+        }),
+    )
+}
+
+/// Calls the cheap RPC method of the provider for the random chain of the
+/// namespace. Namespaces without the known cheap method are taken from the
+/// routing state as in the readiness probe.
+async fn provider_check(state: &AppState, namespace: &str) -> ComponentStatus {
+    let name = format!("provider/{namespace}");
+    let providers = state.providers();
+    let (Some(method), Some(chain_id)) = (
+        ping_method(namespace),
+        providers.sample_rpc_chain(namespace),
+    ) else {
+        let ok = providers.is_rpc_namespace_available(namespace);
+        return ComponentStatus::critical(CheckResult {
+            name,
+            ok,
+            error: (!ok).then(|| "no reachable providers".to_owned()),
+        });
+    };
+
+    let provider = match providers.get_rpc_provider_for_chain_id(&chain_id, None, 1) {
+        Ok(provider) => provider.into_iter().next(),
+        Err(e) => {
+            return ComponentStatus::critical(CheckResult {
+                name,
+                ok: false,
+                error: Some(format!("{chain_id}: {e}")),
+            })
+        }
+    };
+    let Some(provider) = provider else {
+        return ComponentStatus::critical(CheckResult {
+            name,
+            ok: false,
+            error: Some(format!("{chain_id}: no providers")),
+        });
+    };
+
+    let details = serde_json::json!({
+        "chainId": chain_id,
+        "provider": provider.provider_kind().to_string(),
+    });
+    let mut component = ComponentStatus::critical(
+        check(
+            &name,
+            PROVIDER_CHECK_TIMEOUT,
+            ping_provider(provider, &chain_id, method),
+        )
+        .await,
+    );
+    component.details = Some(details);
+    component
+}
+
+async fn postgres_check(state: &AppState) -> CheckResult {
+    check("postgres", READINESS_CHECK_TIMEOUT, async {
+        sqlx::query("SELECT 1")
+            .execute(&state.postgres)
+            .await
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    })
+    .await
+}
+
+/// The IRN is read for the missing key in all of the migration modes except
+/// the Postgres only
+async fn irn_check(state: &AppState) -> Option<CheckResult> {
+    let storage = state.persistent_storage.as_ref()?;
+    if state.config.irn.nodes.is_none()
+        || state.config.irn.migration_mode == Some(MigrationMode::Postgres)
+    {
+        return None;
+    }
+
+    Some(
+        check("irn", READINESS_CHECK_TIMEOUT, async {
+            storage
+                .get(READINESS_CHECK_KEY.to_owned())
+                .await
+                .map(|_| ())
+                .map_err(|e| e.to_string())
+        })
+        .await,
+    )
+}
+
+/// Readiness probe, responds with 503 until the startup is completed and
+/// while the Postgres, Redis, IRN or all providers of a critical namespace are
+/// unreachable. Providers reachability is taken from the routing state to not
+/// spend the providers quota on the probes.
+pub async fn ready_handler(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    let mut checks = vec![CheckResult {
+        name: "startup".to_owned(),
+        ok: state.is_started(),
+        error: (!state.is_started()).then(|| "startup is in progress".to_owned()),
+    }];
+
+    checks.push(postgres_check(&state).await);
+
+    if let Some(redis) = &state.project_data_redis {
+        checks.push(
+            check("redis", READINESS_CHECK_TIMEOUT, async {
+                redis.ping().await.map_err(|e| e.to_string())
+            })
+            .await,
+        );
+    }
+
+    if let Some(irn_check) = irn_check(&state).await {
+        checks.push(irn_check);
+    }
+
+    let providers = state.providers();
+    for namespace in &state.config.server.readiness_namespaces {
+        let ok = providers.is_rpc_namespace_available(namespace);
+        checks.push(CheckResult {
+            name: format!("providers/{namespace}"),
+            ok,
+            error: (!ok).then(|| "no reachable providers".to_owned()),
+        });
+    }
+
+    let ready = checks.iter().all(|check| check.ok);
+    let status = if ready {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    (status, Json(ReadinessResponse { ready, checks }))
 }
@@ -2,19 +2,23 @@ use {
     super::{SdkInfoParams, ROOTSTOCK_MAINNET_CHAIN_ID, ROOTSTOCK_TESTNET_CHAIN_ID},
     crate::{
         analytics::{HistoryLookupInfo, OnrampHistoryLookupInfo},
+        database::onramp_sessions,
         error::RpcError,
         providers::ProviderKind,
         state::AppState,
+        token_metadata_backfill,
         utils::{crypto, network},
     },
+    async_graphql::SimpleObject,
     axum::{
-        extract::{ConnectInfo, MatchedPath, Path, Query, State},
+        extract::{ConnectInfo, Path, Query, State},
         response::{IntoResponse, Response},
         Json,
     },
+    chrono::{DateTime, FixedOffset},
     hyper::HeaderMap,
     serde::{Deserialize, Serialize},
-    std::{net::SocketAddr, sync::Arc},
+    std::{cmp::Reverse, net::SocketAddr, sync::Arc},
     tap::TapFallible,
     tracing::log::{debug, error},
     wc::metrics::{future_metrics, FutureExt},
@@ -28,26 +32,47 @@ pub struct HistoryQueryParams {
     pub chain_id: Option<String>,
     pub cursor: Option<String>,
     pub onramp: Option<String>,
+    /// Merge the onramp purchases into the chain transactions history
+    pub include_onramp: Option<bool>,
     #[serde(flatten)]
     pub sdk_info: SdkInfoParams,
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, SimpleObject)]
 #[serde(rename_all = "camelCase")]
 pub struct HistoryResponseBody {
     pub data: Vec<HistoryTransaction>,
     pub next: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, SimpleObject)]
 #[serde(rename_all = "camelCase")]
 pub struct HistoryTransaction {
     pub id: String,
     pub metadata: HistoryTransactionMetadata,
     pub transfers: Option<Vec<HistoryTransactionTransfer>>,
+    /// Onramp purchase of the transaction
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub onramp: Option<HistoryTransactionOnramp>,
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Hash, Clone)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Hash, Clone, SimpleObject)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryTransactionOnramp {
+    pub provider: String,
+    /// Purchase status by the onramp provider
+    pub status: String,
+    /// Fiat amount paid for the purchase
+    pub fiat_amount: Option<HistoryTransactionFiatAmount>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Hash, Clone, SimpleObject)]
+pub struct HistoryTransactionFiatAmount {
+    pub value: String,
+    pub currency: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Hash, Clone, SimpleObject)]
 #[serde(rename_all = "camelCase")]
 pub struct HistoryTransactionMetadata {
     pub operation_type: String,
@@ -61,14 +86,14 @@ pub struct HistoryTransactionMetadata {
     pub chain: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Hash, Clone)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Hash, Clone, SimpleObject)]
 #[serde(rename_all = "camelCase")]
 pub struct HistoryTransactionMetadataApplication {
     pub name: Option<String>,
     pub icon_url: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, SimpleObject)]
 pub struct HistoryTransactionTransfer {
     pub fungible_info: Option<HistoryTransactionFungibleInfo>,
     pub nft_info: Option<HistoryTransactionNFTInfo>,
@@ -78,42 +103,46 @@ pub struct HistoryTransactionTransfer {
     pub price: Option<f64>,
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Hash, Clone)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Hash, Clone, SimpleObject)]
 pub struct HistoryTransactionFungibleInfo {
     pub name: Option<String>,
     pub symbol: Option<String>,
     pub icon: Option<HistoryTransactionURLItem>,
+    /// CAIP-10 token address for the metadata backfill
+    #[serde(skip)]
+    #[graphql(skip)]
+    pub address: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Hash, Clone)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Hash, Clone, SimpleObject)]
 pub struct HistoryTransactionURLItem {
     pub url: String,
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Hash, Clone)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Hash, Clone, SimpleObject)]
 pub struct HistoryTransactionTransferQuantity {
     pub numeric: String,
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Hash, Clone)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Hash, Clone, SimpleObject)]
 pub struct HistoryTransactionNFTInfo {
     pub name: Option<String>,
     pub content: Option<HistoryTransactionNFTContent>,
     pub flags: HistoryTransactionNFTInfoFlags,
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Hash, Clone)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Hash, Clone, SimpleObject)]
 pub struct HistoryTransactionNFTInfoFlags {
     pub is_spam: bool,
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Hash, Clone)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Hash, Clone, SimpleObject)]
 pub struct HistoryTransactionNFTContent {
     pub preview: Option<HistoryTransactionURLandContentTypeItem>,
     pub detail: Option<HistoryTransactionURLandContentTypeItem>,
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Hash, Clone)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Hash, Clone, SimpleObject)]
 pub struct HistoryTransactionURLandContentTypeItem {
     pub url: String,
     pub content_type: Option<String>,
@@ -123,11 +152,10 @@ pub async fn handler(
     state: State<Arc<AppState>>,
     connect_info: ConnectInfo<SocketAddr>,
     query: Query<HistoryQueryParams>,
-    path: MatchedPath,
     headers: HeaderMap,
     address: Path<String>,
 ) -> Result<Response, RpcError> {
-    handler_internal(state, connect_info, query, path, headers, address)
+    handler_internal(state, connect_info, query, headers, address)
         .with_metrics(future_metrics!("handler_task", "name" => "transactions"))
         .await
 }
@@ -137,7 +165,6 @@ async fn handler_internal(
     state: State<Arc<AppState>>,
     connect_info: ConnectInfo<SocketAddr>,
     query: Query<HistoryQueryParams>,
-    _path: MatchedPath,
     headers: HeaderMap,
     Path(address): Path<String>,
 ) -> Result<Response, RpcError> {
@@ -174,18 +201,18 @@ async fn handler_internal(
 
     let latency_tracker_start = std::time::SystemTime::now();
     let history_provider_kind: ProviderKind;
-    let response: HistoryResponseBody = if let Some(onramp) = query.onramp.clone() {
+    let mut response: HistoryResponseBody = if let Some(onramp) = query.onramp.clone() {
         if onramp == "coinbase" && namespace == crypto::CaipNamespaces::Eip155 {
             // We don't want to validate the quota for the onramp
             state.validate_project_access(&project_id).await?;
             history_provider_kind = ProviderKind::Coinbase;
             state
-                .providers
+                .providers()
                 .coinbase_pay_provider
                 .get_transactions(
                     address.clone(),
                     query.clone().0,
-                    &state.providers.token_metadata_cache,
+                    &state.providers().token_metadata_cache,
                     state.metrics.clone(),
                 )
                 .await
@@ -197,23 +224,61 @@ async fn handler_internal(
         }
     } else {
         state.validate_project_access_and_quota(&project_id).await?;
-        let provider = state
-            .providers
+        let providers = state.providers();
+        let provider = providers
             .history_providers
             .get(&namespace)
             .ok_or_else(|| RpcError::UnsupportedNamespace(namespace))?;
         history_provider_kind = provider.provider_kind();
-        provider
-            .get_transactions(
+
+        // Onramp purchases are only available for the EVM addresses and
+        // always requested from the first page, the purchases are matched
+        // to the chain transactions of the requested page
+        let include_onramp =
+            query.include_onramp.unwrap_or(false) && namespace == crypto::CaipNamespaces::Eip155;
+        let onramp_query = HistoryQueryParams {
+            cursor: None,
+            ..query.0.clone()
+        };
+        let (chain_history, onramp_history) = tokio::join!(
+            provider.get_transactions(
                 address.clone(),
                 query.0.clone(),
-                &state.providers.token_metadata_cache,
+                &providers.token_metadata_cache,
                 state.metrics.clone(),
-            )
-            .await
-            .tap_err(|e| {
-                error!("Failed to call transactions history with {e}");
-            })?
+            ),
+            async {
+                if !include_onramp {
+                    return None;
+                }
+                Some(
+                    providers
+                        .coinbase_pay_provider
+                        .get_transactions(
+                            address.clone(),
+                            onramp_query,
+                            &providers.token_metadata_cache,
+                            state.metrics.clone(),
+                        )
+                        .await,
+                )
+            }
+        );
+        let mut response = chain_history.tap_err(|e| {
+            error!("Failed to call transactions history with {e}");
+        })?;
+        match onramp_history {
+            Some(Ok(onramp_history)) => merge_onramp_purchases(
+                &mut response,
+                onramp_history.data,
+                query.chain_id.as_deref(),
+                query.cursor.is_none(),
+            ),
+            // The chain history is served without the onramp purchases
+            Some(Err(e)) => error!("Failed to call coinbase transactions history with {e}"),
+            None => {}
+        }
+        response
     };
 
     let latency_tracker = latency_tracker_start
@@ -221,6 +286,9 @@ async fn handler_internal(
         .unwrap_or(std::time::Duration::from_secs(0));
     state.metrics.add_history_lookup(&history_provider_kind);
 
+    complete_fungibles_metadata(&state, &mut response).await;
+    filter_scam_tokens(&state, &project_id, &mut response);
+
     let origin = headers
         .get("origin")
         .map(|v| v.to_str().unwrap_or("invalid_header").to_string());
@@ -240,6 +308,17 @@ async fn handler_internal(
     // Analytics schema exception for Coinbase Onramp
     match history_provider_kind {
         ProviderKind::Coinbase => {
+            // Purchases are linked to the latest onramp session of the address
+            let onramp_session_id = onramp_sessions::get_latest_session_id(
+                &state.postgres,
+                &project_id,
+                &address.to_lowercase(),
+            )
+            .await
+            .unwrap_or_else(|e| {
+                error!("Failed to get the latest onramp session: {e}");
+                None
+            });
             for transaction in response.clone().data {
                 state
                     .analytics
@@ -274,6 +353,7 @@ async fn handler_internal(
                         query.sdk_info.sv.clone(),
                         query.sdk_info.st.clone(),
                         request_id.to_string(),
+                        onramp_session_id.clone(),
                     ));
             }
         }
@@ -342,3 +422,228 @@ async fn handler_internal(
 
     Ok(Json(response).into_response())
 }
+
+/// Merges the onramp purchases into the chain transactions history page. The
+/// purchases are attached to the chain transactions by the hash, the
+/// purchases without the chain transaction, such as the pending ones, are
+/// added to the first page when they are not older than the page.
+fn merge_onramp_purchases(
+    response: &mut HistoryResponseBody,
+    purchases: Vec<HistoryTransaction>,
+    chain_id: Option<&str>,
+    is_first_page: bool,
+) {
+    let oldest_mined_at = response
+        .data
+        .iter()
+        .filter_map(mined_at)
+        .min()
+        .filter(|_| response.next.is_some());
+    let mut added = false;
+    for purchase in purchases {
+        if chain_id.is_some_and(|chain_id| purchase.metadata.chain.as_deref() != Some(chain_id)) {
+            continue;
+        }
+        let transaction = response.data.iter_mut().find(|transaction| {
+            !purchase.metadata.hash.is_empty()
+                && transaction
+                    .metadata
+                    .hash
+                    .eq_ignore_ascii_case(&purchase.metadata.hash)
+        });
+        if let Some(transaction) = transaction {
+            transaction.onramp = purchase.onramp;
+            continue;
+        }
+        let is_on_page = match (oldest_mined_at, mined_at(&purchase)) {
+            (Some(oldest_mined_at), Some(purchase_mined_at)) => {
+                purchase_mined_at >= oldest_mined_at
+            }
+            _ => true,
+        };
+        if is_first_page && (purchase.metadata.hash.is_empty() || is_on_page) {
+            response.data.push(purchase);
+            added = true;
+        }
+    }
+    if added {
+        response
+            .data
+            .sort_by_key(|transaction| Reverse(mined_at(transaction)));
+    }
+}
+
+fn mined_at(transaction: &HistoryTransaction) -> Option<DateTime<FixedOffset>> {
+    DateTime::parse_from_rfc3339(&transaction.metadata.mined_at).ok()
+}
+
+/// Fills the missing transfers metadata from the token metadata cache and
+/// enqueues the tokens missing in the cache for the backfill
+async fn complete_fungibles_metadata(state: &AppState, response: &mut HistoryResponseBody) {
+    let metadata_cache = state.providers().token_metadata_cache.clone();
+    let fungibles = response
+        .data
+        .iter_mut()
+        .filter_map(|transaction| transaction.transfers.as_mut())
+        .flatten()
+        .filter_map(|transfer| transfer.fungible_info.as_mut());
+    for fungible_info in fungibles {
+        let Some(token_address) = fungible_info.address.clone() else {
+            continue;
+        };
+        if !is_fungible_info_incomplete(fungible_info) {
+            continue;
+        }
+        match metadata_cache.get_metadata(&token_address).await {
+            Ok(Some(metadata)) => {
+                if fungible_info.name.as_deref().is_none_or(str::is_empty)
+                    && !metadata.name.is_empty()
+                {
+                    fungible_info.name = Some(metadata.name);
+                }
+                if fungible_info.symbol.as_deref().is_none_or(str::is_empty)
+                    && !metadata.symbol.is_empty()
+                {
+                    fungible_info.symbol = Some(metadata.symbol);
+                }
+                if fungible_info
+                    .icon
+                    .as_ref()
+                    .is_none_or(|icon| icon.url.is_empty())
+                    && !metadata.icon_url.is_empty()
+                {
+                    fungible_info.icon = Some(HistoryTransactionURLItem {
+                        url: metadata.icon_url,
+                    });
+                }
+            }
+            Ok(None) => {}
+            Err(e) => {
+                error!("Error getting token metadata: {e:?}");
+                continue;
+            }
+        }
+        if is_fungible_info_incomplete(fungible_info)
+            && state.token_metadata_backfill.enqueue(&token_address)
+        {
+            state
+                .metrics
+                .add_token_metadata_backfill_enqueued("history");
+        }
+    }
+}
+
+/// Drops the transfers of the denylisted scam tokens unless the project opted
+/// out, the transactions left without the transfers are dropped as well
+fn filter_scam_tokens(state: &AppState, project_id: &str, response: &mut HistoryResponseBody) {
+    let Some(scam_tokens) = state
+        .scam_tokens
+        .for_project(&state.reloadable_config().balances, project_id)
+    else {
+        return;
+    };
+    let mut filtered = 0;
+    response.data.retain_mut(|transaction| {
+        let Some(transfers) = transaction.transfers.as_mut() else {
+            return true;
+        };
+        let count = transfers.len();
+        transfers.retain(|transfer| {
+            transfer
+                .fungible_info
+                .as_ref()
+                .and_then(|fungible_info| fungible_info.address.as_deref())
+                .is_none_or(|address| !scam_tokens.is_scam(address))
+        });
+        filtered += count - transfers.len();
+        count == transfers.len() || !transfers.is_empty()
+    });
+    if filtered > 0 {
+        state.metrics.add_scam_tokens_filtered("history", filtered);
+    }
+}
+
+fn is_fungible_info_incomplete(fungible_info: &HistoryTransactionFungibleInfo) -> bool {
+    token_metadata_backfill::is_incomplete(
+        fungible_info.name.as_deref(),
+        fungible_info.symbol.as_deref(),
+        fungible_info.icon.as_ref().map(|icon| icon.url.as_str()),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn transaction(id: &str, hash: &str, mined_at: &str, chain: &str) -> HistoryTransaction {
+        HistoryTransaction {
+            id: id.to_owned(),
+            metadata: HistoryTransactionMetadata {
+                operation_type: "receive".to_owned(),
+                hash: hash.to_owned(),
+                mined_at: mined_at.to_owned(),
+                sent_from: "0x1".to_owned(),
+                sent_to: "0x2".to_owned(),
+                status: "confirmed".to_owned(),
+                nonce: 0,
+                application: None,
+                chain: Some(chain.to_owned()),
+            },
+            transfers: None,
+            onramp: None,
+        }
+    }
+
+    fn purchase(id: &str, hash: &str, mined_at: &str, chain: &str) -> HistoryTransaction {
+        HistoryTransaction {
+            onramp: Some(HistoryTransactionOnramp {
+                provider: "coinbase".to_owned(),
+                status: "ONRAMP_TRANSACTION_STATUS_SUCCESS".to_owned(),
+                fiat_amount: Some(HistoryTransactionFiatAmount {
+                    value: "100".to_owned(),
+                    currency: "USD".to_owned(),
+                }),
+            }),
+            ..transaction(id, hash, mined_at, chain)
+        }
+    }
+
+    fn ids(response: &HistoryResponseBody) -> Vec<&str> {
+        response
+            .data
+            .iter()
+            .map(|transaction| transaction.id.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn merges_onramp_purchases() {
+        let mut response = HistoryResponseBody {
+            data: vec![
+                transaction("a", "0xAA", "2024-03-03T00:00:00Z", "eip155:1"),
+                transaction("b", "0xbb", "2024-03-01T00:00:00Z", "eip155:1"),
+            ],
+            next: Some("cursor".to_owned()),
+        };
+        let purchases = vec![
+            purchase("pending", "", "2024-03-04T00:00:00Z", "eip155:1"),
+            purchase("matched", "0xaa", "2024-03-02T23:59:00.5Z", "eip155:1"),
+            purchase("unmatched", "0xcc", "2024-03-02T00:00:00Z", "eip155:1"),
+            purchase("older", "0xdd", "2024-02-01T00:00:00Z", "eip155:1"),
+            purchase("other-chain", "", "2024-03-04T00:00:00Z", "eip155:10"),
+        ];
+
+        merge_onramp_purchases(&mut response, purchases.clone(), Some("eip155:1"), true);
+        assert_eq!(ids(&response), vec!["pending", "a", "unmatched", "b"]);
+        assert_eq!(response.data[1].onramp, purchases[1].onramp);
+
+        // Only the matched purchases are merged into the next pages
+        let mut response = HistoryResponseBody {
+            data: vec![transaction("c", "0xdd", "2024-02-01T00:00:00Z", "eip155:1")],
+            next: None,
+        };
+        merge_onramp_purchases(&mut response, purchases.clone(), None, false);
+        assert_eq!(ids(&response), vec!["c"]);
+        assert_eq!(response.data[0].onramp, purchases[3].onramp);
+    }
+}
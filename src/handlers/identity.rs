@@ -8,6 +8,7 @@ use {
         state::AppState,
         utils::{crypto, network},
     },
+    async_graphql::SimpleObject,
     async_trait::async_trait,
     axum::{
         body::to_bytes,
@@ -35,10 +36,6 @@ use {
     wc::metrics::{self, enum_ordinalize::Ordinalize, future_metrics, Enum, FutureExt},
 };
 
-const CACHE_TTL: u64 = 60 * 60 * 24;
-const CACHE_TTL_DELTA: TimeDelta = TimeDelta::seconds(CACHE_TTL as i64);
-const CACHE_TTL_STD: Duration = Duration::from_secs(CACHE_TTL);
-
 const SELF_PROVIDER_ERROR_PREFIX: &str = "SelfProviderError: ";
 const EMPTY_RPC_RESPONSE: &str = "0x";
 pub const ETHEREUM_MAINNET: &str = "eip155:1";
@@ -143,7 +140,8 @@ fn record_identity_lookup_analytics(
 pub struct IdentityQueryParams {
     pub project_id: String,
     /// Optional flag to control the cache to fetch the data from the provider
-    /// or serve from the cache where applicable
+    /// or serve from the cache where applicable, `false` forces the refresh
+    /// of the cached result
     pub use_cache: Option<bool>,
     /// Client ID for analytics
     pub client_id: Option<String>,
@@ -153,17 +151,35 @@ pub struct IdentityQueryParams {
     pub sdk_info: SdkInfoParams,
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Hash, Clone)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Hash, Clone, SimpleObject)]
 #[serde(rename_all = "camelCase")]
 pub struct IdentityResponse {
-    name: Option<String>,
-    avatar: Option<String>,
+    pub name: Option<String>,
+    pub avatar: Option<String>,
     // Preferred saving the resolved_at time instead of relying on Redis cache TTL because
     // getting the current TTL requires a second command & round trip to Redis
     // Optional to support DB migration, can switch to required in the future
+    #[graphql(skip)]
     resolved_at: Option<DateTime<Utc>>,
 }
 
+impl IdentityResponse {
+    /// Whether neither the name nor the avatar is resolved
+    fn is_empty(&self) -> bool {
+        self.name.is_none() && self.avatar.is_none()
+    }
+}
+
+/// Cache TTL of the identity result, the results without the name and avatar
+/// are cached for the shorter time to pick up the new names sooner
+fn identity_cache_ttl(state: &AppState, response: &IdentityResponse) -> Duration {
+    if response.is_empty() {
+        state.config.storage.identity_negative_cache_ttl()
+    } else {
+        state.config.storage.identity_cache_ttl()
+    }
+}
+
 pub async fn handler(
     state: State<Arc<AppState>>,
     connect_info: ConnectInfo<SocketAddr>,
@@ -252,8 +268,10 @@ async fn handler_internal(
     );
 
     let now = Utc::now();
+    let cache_ttl =
+        TimeDelta::from_std(identity_cache_ttl(&state, &res)).unwrap_or(TimeDelta::zero());
     let ttl_secs = res.resolved_at
-        .map(|resolved_at| ttl_from_resolved_at(resolved_at, now))
+        .map(|resolved_at| ttl_from_resolved_at(resolved_at, now, cache_ttl))
         // Only happens during initial rollout when `resolved_at` is None, so we don't need to go overboard on the cache
         .unwrap_or(TimeDelta::hours(1))
         .num_seconds();
@@ -262,8 +280,16 @@ async fn handler_internal(
     Ok(([(CACHE_CONTROL, cache_control)], Json(res)).into_response())
 }
 
-fn ttl_from_resolved_at(resolved_at: DateTime<Utc>, now: DateTime<Utc>) -> TimeDelta {
-    let expires = resolved_at + CACHE_TTL_DELTA;
+pub fn identity_cache_key(address: &H160) -> String {
+    format!("{}-v1", to_checksum(address, None))
+}
+
+fn ttl_from_resolved_at(
+    resolved_at: DateTime<Utc>,
+    now: DateTime<Utc>,
+    cache_ttl: TimeDelta,
+) -> TimeDelta {
+    let expires = resolved_at + cache_ttl;
     (expires - now).max(TimeDelta::zero())
 }
 
@@ -296,35 +322,46 @@ async fn lookup_identity(
     headers: HeaderMap,
 ) -> Result<(IdentityLookupSource, IdentityResponse), RpcError> {
     let address_with_checksum = to_checksum(&address, None);
-    let cache_record_key = format!("{address_with_checksum}-v1");
-
-    // Check if we should enable cache control for allow listed Project ID
-    // The cache is enabled by default
-    let enable_cache = if let Some(use_cache) = query.use_cache {
-        if let Some(ref testing_project_id) = state.config.server.testing_project_id {
-            if crypto::constant_time_eq(testing_project_id, &query.project_id) {
-                use_cache
-            } else {
-                return Err(RpcError::InvalidParameter(format!(
-                    "The project ID {} is not allowed to use `use_cache` parameter",
-                    query.project_id
-                )));
-            }
-        } else {
-            return Err(RpcError::InvalidParameter(
-                "Use of `use_cache` parameter is disabled".into(),
-            ));
-        }
+    let cache_record_key = identity_cache_key(&address);
+
+    // Forced refreshes skip the cached result and are limited to one per
+    // address within the refresh interval, the refreshes above the limit are
+    // served from the cache. The testing project is not limited.
+    let force_refresh = if query.use_cache == Some(false) {
+        let is_testing_project =
+            state
+                .config
+                .server
+                .testing_project_id
+                .as_ref()
+                .is_some_and(|testing_project_id| {
+                    crypto::constant_time_eq(testing_project_id, &query.project_id)
+                });
+        is_testing_project
+            || state
+                .identity_refreshes
+                .entry(cache_record_key.clone())
+                .or_insert(())
+                .await
+                .is_fresh()
     } else {
-        true
+        false
     };
 
-    if enable_cache {
+    if !force_refresh {
         if let Some(cache) = &state.identity_cache {
             debug!("Checking cache for identity");
             let cache_start = SystemTime::now();
             let value = cache.get(&cache_record_key).await?;
             state.metrics.add_identity_lookup_cache_latency(cache_start);
+            // Results cached with the previous TTLs are refreshed when expired
+            // by the current TTLs
+            let value = value.filter(|response| {
+                response.resolved_at.is_none_or(|resolved_at| {
+                    TimeDelta::from_std(identity_cache_ttl(&state, response))
+                        .is_ok_and(|ttl| resolved_at + ttl > Utc::now())
+                })
+            });
             if let Some(response) = value {
                 return Ok((IdentityLookupSource::Cache, response));
             }
@@ -367,28 +404,30 @@ async fn lookup_identity(
         }
     }
 
-    if enable_cache {
-        if let Some(cache) = &state.identity_cache {
-            debug!("Saving to cache");
-            let cache = cache.clone();
-            let res = res.clone();
-            // Do not block on cache write.
-            tokio::spawn(async move {
-                let cache_start = SystemTime::now();
-                cache
-                    .set(&cache_record_key, &res, Some(CACHE_TTL_STD))
-                    .await
-                    .tap_err(|err| {
-                        warn!(
-                            "failed to cache identity lookup (cache_key:{cache_record_key}): \
-                             {err:?}"
-                        )
-                    })
-                    .ok();
-                state.metrics.add_identity_lookup_cache_latency(cache_start);
-                debug!("Setting cache success");
-            });
-        }
+    if let Some(cache) = &state.identity_cache {
+        debug!("Saving to cache");
+        let cache = cache.clone();
+        let res = res.clone();
+        // Do not block on cache write.
+        tokio::spawn(async move {
+            let cache_start = SystemTime::now();
+            cache
+                .set(
+                    &cache_record_key,
+                    &res,
+                    Some(identity_cache_ttl(&state, &res)),
+                )
+                .await
+                .tap_err(|err| {
+                    warn!(
+                        "failed to cache identity lookup (cache_key:{cache_record_key}): \
+                         {err:?}"
+                    )
+                })
+                .ok();
+            state.metrics.add_identity_lookup_cache_latency(cache_start);
+            debug!("Setting cache success");
+        });
     }
 
     Ok((resolved_by, res))
@@ -679,17 +718,19 @@ mod tests {
 
     use super::*;
 
+    const CACHE_TTL: TimeDelta = TimeDelta::seconds(60 * 60 * 24);
+
     #[test]
     fn full_ttl_when_resolved_now() {
         let now = Utc::now();
-        assert_eq!(ttl_from_resolved_at(now, now), CACHE_TTL_DELTA);
+        assert_eq!(ttl_from_resolved_at(now, now, CACHE_TTL), CACHE_TTL);
     }
 
     #[test]
     fn expires_now() {
         let now = Utc::now();
         assert_eq!(
-            ttl_from_resolved_at(now - CACHE_TTL_DELTA, now),
+            ttl_from_resolved_at(now - CACHE_TTL, now, CACHE_TTL),
             TimeDelta::zero()
         );
     }
@@ -698,7 +739,7 @@ mod tests {
     fn expires_past() {
         let now = Utc::now();
         assert_eq!(
-            ttl_from_resolved_at(now - CACHE_TTL_DELTA - TimeDelta::days(1), now),
+            ttl_from_resolved_at(now - CACHE_TTL - TimeDelta::days(1), now, CACHE_TTL),
             TimeDelta::zero()
         );
     }
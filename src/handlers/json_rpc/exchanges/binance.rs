@@ -1,14 +1,20 @@
 use {
     crate::handlers::json_rpc::exchanges::{
-        BuyTransactionStatus, ExchangeError, ExchangeProvider, Feature, FeatureType,
-        GetBuyStatusParams, GetBuyStatusResponse, GetBuyUrlParams,
+        validate_webhook_timestamp, BuyTransactionStatus, ExchangeAsset, ExchangeError,
+        ExchangeProvider, ExchangeWebhookEvent, Feature, FeatureType, GetBuyStatusParams,
+        GetBuyStatusResponse, GetBuyUrlParams, GetDepositStatusResponse, GetDepositUrlParams,
     },
     crate::state::AppState,
     crate::utils::crypto::Caip19Asset,
     axum::extract::State,
     base64::{engine::general_purpose::STANDARD, Engine},
+    hyper::HeaderMap,
     once_cell::sync::Lazy,
-    openssl::{hash::MessageDigest, pkey::PKey, sign::Signer},
+    openssl::{
+        hash::MessageDigest,
+        pkey::PKey,
+        sign::{Signer, Verifier},
+    },
     serde::{Deserialize, Serialize},
     std::collections::HashMap,
     std::sync::Arc,
@@ -19,6 +25,9 @@ pub struct BinanceExchange;
 
 const PRE_ORDER_PATH: &str = "/papi/v1/ramp/connect/buy/pre-order";
 const QUERY_ORDER_DETAILS_PATH: &str = "/papi/v1/ramp/connect/order";
+const CRYPTO_NETWORK_LIST_PATH: &str = "/papi/v1/ramp/connect/crypto-network";
+const SELL_PRE_ORDER_PATH: &str = "/papi/v1/ramp/connect/sell/pre-order";
+const SELL_QUERY_ORDER_DETAILS_PATH: &str = "/papi/v1/ramp/connect/sell/order";
 const FALLBACK_MERCHANT_NAME: &str = " ";
 
 // CAIP-19 asset mappings to Binance assets
@@ -123,6 +132,27 @@ impl From<usize> for BinanceOrderStatus {
     }
 }
 
+impl From<BinanceOrderStatus> for BuyTransactionStatus {
+    fn from(status: BinanceOrderStatus) -> Self {
+        match status {
+            BinanceOrderStatus::OnRampCompleted | BinanceOrderStatus::Completed => {
+                BuyTransactionStatus::Success
+            }
+            BinanceOrderStatus::Init
+            | BinanceOrderStatus::OnRampProcessing
+            | BinanceOrderStatus::OffRampProcessing
+            | BinanceOrderStatus::WithdrawInit
+            | BinanceOrderStatus::WithdrawProcessing => BuyTransactionStatus::InProgress,
+            BinanceOrderStatus::OffRampFailed
+            | BinanceOrderStatus::WithdrawAbandoned
+            | BinanceOrderStatus::OnRampFailed
+            | BinanceOrderStatus::WithdrawFailed
+            | BinanceOrderStatus::FailedReserved => BuyTransactionStatus::Failed,
+            BinanceOrderStatus::Unknown(_) => BuyTransactionStatus::Unknown,
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct PreOrderRequest {
@@ -192,6 +222,35 @@ struct PreOrderResponseData {
     link_expire_time: u64,
 }
 
+/// Sell (off-ramp) order, the user confirms the order at Binance and
+/// deposits the crypto to the address provided by Binance
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SellPreOrderRequest {
+    /// The unique order id from the partner side. Supports only letters and numbers.
+    pub external_order_id: String,
+
+    /// Crypto currency to sell
+    pub crypto_currency: String,
+
+    /// Crypto network the deposit is sent on
+    pub network: String,
+
+    /// Requested crypto amount. Fraction is 8
+    pub requested_amount: String,
+
+    /// The original client IP
+    pub client_ip: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SellOrderDetailsResponse {
+    status: usize,
+    deposit_address: Option<String>,
+    deposit_tx_hash: Option<String>,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 enum AmountType {
     Fiat = 1,
@@ -211,6 +270,25 @@ struct QueryOrderDetailsResponse {
     withdraw_tx_hash: Option<String>,
 }
 
+/// Order status callback sent by Binance Connect
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct WebhookPayload {
+    external_order_id: String,
+    status: usize,
+    withdraw_tx_hash: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CryptoNetworkResponse {
+    crypto_currency: String,
+    network: String,
+    withdraw_fee: Option<f64>,
+    withdraw_min: Option<f64>,
+    withdraw_max: Option<f64>,
+}
+
 /// Base response structure for Binance API responses
 #[derive(Debug, Deserialize, Serialize)]
 struct BinanceResponse<T> {
@@ -238,6 +316,13 @@ impl ExchangeProvider for BinanceExchange {
         self.map_asset_to_binance_format(asset).is_ok()
     }
 
+    fn supported_assets(&self) -> Vec<String> {
+        CAIP19_TO_BINANCE_CRYPTO
+            .keys()
+            .map(|asset| asset.to_string())
+            .collect()
+    }
+
     fn is_enabled(&self, _feature_type: &FeatureType, _project_features: &[Feature]) -> bool {
         true
     }
@@ -248,10 +333,11 @@ impl BinanceExchange {
         &self,
         state: &Arc<AppState>,
     ) -> Result<(String, String, String, String), ExchangeError> {
-        let client_id = state.config.exchanges.binance_client_id.clone();
-        let key = state.config.exchanges.binance_key.clone();
-        let token = state.config.exchanges.binance_token.clone();
-        let host = state.config.exchanges.binance_host.clone();
+        let config = &state.reloadable_config().exchanges;
+        let client_id = config.binance_client_id.clone();
+        let key = config.binance_key.clone();
+        let token = config.binance_token.clone();
+        let host = config.binance_host.clone();
 
         match (client_id, key, token, host) {
             (Some(client_id), Some(key), Some(token), Some(host)) => {
@@ -458,29 +544,164 @@ impl BinanceExchange {
 
         let binance_status: BinanceOrderStatus = response.status.into();
 
-        let status = match binance_status {
-            BinanceOrderStatus::OnRampCompleted | BinanceOrderStatus::Completed => {
-                BuyTransactionStatus::Success
-            }
-            BinanceOrderStatus::Init
-            | BinanceOrderStatus::OnRampProcessing
-            | BinanceOrderStatus::OffRampProcessing
-            | BinanceOrderStatus::WithdrawInit
-            | BinanceOrderStatus::WithdrawProcessing => BuyTransactionStatus::InProgress,
-            BinanceOrderStatus::OffRampFailed
-            | BinanceOrderStatus::WithdrawAbandoned
-            | BinanceOrderStatus::OnRampFailed
-            | BinanceOrderStatus::WithdrawFailed
-            | BinanceOrderStatus::FailedReserved => BuyTransactionStatus::Failed,
-            BinanceOrderStatus::Unknown(_) => BuyTransactionStatus::Unknown,
-        };
-
         Ok(GetBuyStatusResponse {
-            status,
+            status: binance_status.into(),
             tx_hash: response.withdraw_tx_hash,
         })
     }
 
+    pub async fn get_deposit_url(
+        &self,
+        state: State<Arc<AppState>>,
+        params: GetDepositUrlParams,
+    ) -> Result<String, ExchangeError> {
+        let (crypto_currency, network) = self
+            .map_asset_to_binance_format(&params.asset)
+            .map_err(|e| ExchangeError::ValidationError(e.to_string()))?;
+
+        let request = SellPreOrderRequest {
+            external_order_id: params.session_id,
+            crypto_currency,
+            network,
+            requested_amount: params.amount.to_string(),
+            client_ip: None,
+        };
+
+        let data: PreOrderResponseData = self
+            .send_post_request(&state, SELL_PRE_ORDER_PATH, &request)
+            .await?;
+        Ok(data.link)
+    }
+
+    pub async fn get_deposit_status(
+        &self,
+        state: State<Arc<AppState>>,
+        params: GetBuyStatusParams,
+    ) -> Result<GetDepositStatusResponse, ExchangeError> {
+        let request = QueryOrderDetailsRequest {
+            external_order_id: params.session_id,
+        };
+
+        let response: SellOrderDetailsResponse = self
+            .send_post_request(&state, SELL_QUERY_ORDER_DETAILS_PATH, &request)
+            .await?;
+
+        debug!("get_deposit_status response: {:?}", response);
+
+        let binance_status: BinanceOrderStatus = response.status.into();
+
+        Ok(GetDepositStatusResponse {
+            status: binance_status.into(),
+            tx_hash: response.deposit_tx_hash,
+            deposit_address: response.deposit_address,
+        })
+    }
+
+    pub async fn get_assets(
+        &self,
+        state: State<Arc<AppState>>,
+    ) -> Result<Vec<ExchangeAsset>, ExchangeError> {
+        let networks: Vec<CryptoNetworkResponse> = self
+            .send_post_request(&state, CRYPTO_NETWORK_LIST_PATH, &serde_json::json!({}))
+            .await?;
+
+        let assets = self
+            .supported_assets()
+            .into_iter()
+            .map(|asset| {
+                let limits = Caip19Asset::parse(&asset)
+                    .ok()
+                    .and_then(|caip19| self.map_asset_to_binance_format(&caip19).ok())
+                    .and_then(|(crypto, network)| {
+                        networks
+                            .iter()
+                            .find(|n| n.crypto_currency == crypto && n.network == network)
+                    });
+                match limits {
+                    Some(limits) => ExchangeAsset {
+                        asset,
+                        min_amount: limits.withdraw_min.map(|v| v.to_string()),
+                        max_amount: limits.withdraw_max.map(|v| v.to_string()),
+                        fee: limits.withdraw_fee.map(|v| v.to_string()),
+                    },
+                    None => ExchangeAsset::without_limits(asset),
+                }
+            })
+            .collect();
+        Ok(assets)
+    }
+
+    /// Callbacks are signed by Binance with the same scheme as the requests,
+    /// the signature is verified with the Binance Connect public key
+    pub fn parse_webhook(
+        &self,
+        state: &Arc<AppState>,
+        headers: &HeaderMap,
+        body: &[u8],
+    ) -> Result<ExchangeWebhookEvent, ExchangeError> {
+        let config = state.reloadable_config();
+        let public_key = config
+            .exchanges
+            .binance_webhook_public_key
+            .as_ref()
+            .ok_or_else(|| {
+                ExchangeError::ConfigurationError("Webhook is not configured".to_string())
+            })?;
+
+        let header = |name: &str| {
+            headers
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .ok_or_else(|| {
+                    ExchangeError::WebhookAuthenticationError(format!("Missing {name} header"))
+                })
+        };
+        let timestamp = header("X-Tesla-Timestamp")?;
+        let signature = header("X-Tesla-Signature")?;
+
+        let timestamp_ms = timestamp.parse::<u64>().map_err(|_| {
+            ExchangeError::WebhookAuthenticationError("Invalid timestamp".to_string())
+        })?;
+        validate_webhook_timestamp(timestamp_ms / 1000)?;
+
+        let key_bytes = STANDARD.decode(public_key).map_err(|e| {
+            ExchangeError::ConfigurationError(format!("Failed to decode public key: {e}"))
+        })?;
+        let pkey = PKey::public_key_from_der(&key_bytes).map_err(|e| {
+            ExchangeError::ConfigurationError(format!("Failed to parse public key: {e}"))
+        })?;
+        let signature = STANDARD.decode(signature).map_err(|_| {
+            ExchangeError::WebhookAuthenticationError("Invalid signature encoding".to_string())
+        })?;
+
+        let mut verifier = Verifier::new(MessageDigest::sha256(), &pkey)
+            .map_err(|e| ExchangeError::InternalError(format!("Failed to create verifier: {e}")))?;
+        verifier
+            .update(&[body, timestamp.as_bytes()].concat())
+            .map_err(|e| ExchangeError::InternalError(format!("Failed to update verifier: {e}")))?;
+        let is_valid = verifier
+            .verify(&signature)
+            .map_err(|e| ExchangeError::InternalError(format!("Failed to verify: {e}")))?;
+        if !is_valid {
+            return Err(ExchangeError::WebhookAuthenticationError(
+                "Invalid signature".to_string(),
+            ));
+        }
+
+        let payload: WebhookPayload = serde_json::from_slice(body)
+            .map_err(|e| ExchangeError::ValidationError(format!("Invalid webhook payload: {e}")))?;
+        debug!("Binance webhook payload: {:?}", payload);
+        let binance_status: BinanceOrderStatus = payload.status.into();
+
+        Ok(ExchangeWebhookEvent {
+            session_id: payload.external_order_id,
+            status: GetBuyStatusResponse {
+                status: binance_status.into(),
+                tx_hash: payload.withdraw_tx_hash,
+            },
+        })
+    }
+
     pub async fn create_pre_order(
         &self,
         state: &Arc<AppState>,
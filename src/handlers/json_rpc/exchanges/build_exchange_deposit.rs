@@ -0,0 +1,201 @@
+use {
+    crate::{
+        database::exchange_reconciliation::TxDirection,
+        handlers::{
+            json_rpc::{
+                exchanges::{
+                    get_enabled_features, get_exchange_by_id, get_feature_type,
+                    is_feature_enabled_for_project_id,
+                    transactions::{get as get_transaction, set_deposit_address},
+                    ExchangeError, Feature, FeatureType, GetBuyStatusParams,
+                },
+                pos::{
+                    build_transactions::build_transaction, BuildPosTxsError, PaymentIntent,
+                    SupportedNamespaces, TransactionRpc,
+                },
+            },
+            SdkInfoParams,
+        },
+        state::AppState,
+        utils::crypto::Caip19Asset,
+    },
+    axum::{
+        extract::{Query, State},
+        Json,
+    },
+    serde::{Deserialize, Serialize},
+    std::{str::FromStr, sync::Arc},
+    thiserror::Error,
+    tracing::debug,
+    wc::metrics::{future_metrics, FutureExt},
+};
+
+const MAX_SESSION_ID_LENGTH: usize = 50;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BuildExchangeDepositRequest {
+    pub exchange_id: String,
+    pub session_id: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BuildExchangeDepositResponse {
+    /// CAIP-10 exchange deposit address
+    pub deposit_address: String,
+    pub transaction: TransactionRpc,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QueryParams {
+    #[serde(flatten)]
+    pub sdk_info: SdkInfoParams,
+    pub source: Option<String>,
+}
+
+#[derive(Error, Debug)]
+pub enum BuildExchangeDepositError {
+    #[error("Validation error: {0}")]
+    ValidationError(String),
+
+    #[error("Exchange not found: {0}")]
+    ExchangeNotFound(String),
+
+    #[error("Session not found: {0}")]
+    SessionNotFound(String),
+
+    #[error("Deposit address is not available: {0}")]
+    DepositAddressNotAvailable(String),
+
+    #[error("Unable to build the deposit transaction: {0}")]
+    BuildTransaction(#[source] BuildPosTxsError),
+
+    #[error("Internal error: {0}")]
+    InternalError(String),
+}
+
+impl BuildExchangeDepositError {
+    pub fn is_internal(&self) -> bool {
+        match self {
+            BuildExchangeDepositError::InternalError(_) => true,
+            BuildExchangeDepositError::BuildTransaction(e) => e.is_internal(),
+            _ => false,
+        }
+    }
+}
+
+pub async fn handler(
+    state: State<Arc<AppState>>,
+    project_id: String,
+    query: Query<QueryParams>,
+    Json(request): Json<BuildExchangeDepositRequest>,
+) -> Result<BuildExchangeDepositResponse, BuildExchangeDepositError> {
+    let feature_type = get_feature_type(query.source.as_deref());
+    let project_features = get_enabled_features(state.clone(), &project_id)
+        .await
+        .map_err(|e| BuildExchangeDepositError::InternalError(e.to_string()))?;
+
+    is_feature_enabled_for_project_id(state.clone(), &project_id, &project_features, &feature_type)
+        .await
+        .map_err(|e| BuildExchangeDepositError::ValidationError(e.to_string()))?;
+    handler_internal(state, project_id, request, &project_features, &feature_type)
+        .with_metrics(future_metrics!("handler_task", "name" => "pay_build_exchange_deposit"))
+        .await
+}
+
+/// Build the transaction sending the wallet funds to the exchange deposit
+/// address, the address is available once the deposit is confirmed at the
+/// exchange using the deposit URL
+async fn handler_internal(
+    state: State<Arc<AppState>>,
+    project_id: String,
+    request: BuildExchangeDepositRequest,
+    project_features: &[Feature],
+    feature_type: &FeatureType,
+) -> Result<BuildExchangeDepositResponse, BuildExchangeDepositError> {
+    let exchange = get_exchange_by_id(&request.exchange_id, feature_type, project_features)
+        .map_err(|e| BuildExchangeDepositError::ExchangeNotFound(e.to_string()))?;
+
+    if request.session_id.is_empty() || request.session_id.len() > MAX_SESSION_ID_LENGTH {
+        return Err(BuildExchangeDepositError::ValidationError(
+            "Invalid session ID".to_string(),
+        ));
+    }
+
+    let deposit = get_transaction(&state, &request.session_id)
+        .await
+        .map_err(|e| BuildExchangeDepositError::InternalError(e.to_string()))?
+        .filter(|tx| {
+            tx.direction == TxDirection::Deposit
+                && tx.exchange_id == request.exchange_id
+                && tx.project_id.as_deref() == Some(project_id.as_str())
+        })
+        .ok_or_else(|| BuildExchangeDepositError::SessionNotFound(request.session_id.clone()))?;
+    let (Some(asset), Some(amount), Some(sender)) = (deposit.asset, deposit.amount, deposit.sender)
+    else {
+        return Err(BuildExchangeDepositError::InternalError(
+            "Incomplete exchange deposit transaction".to_string(),
+        ));
+    };
+
+    let status = exchange
+        .get_deposit_status(
+            state.clone(),
+            GetBuyStatusParams {
+                project_id: project_id.clone(),
+                session_id: request.session_id.clone(),
+            },
+        )
+        .await
+        .map_err(|e| match e {
+            ExchangeError::ValidationError(msg) => BuildExchangeDepositError::ValidationError(msg),
+            _ => {
+                debug!(
+                    error = %e,
+                    session_id = %request.session_id,
+                    exchange_id = %request.exchange_id,
+                    "Internal error, unable to get exchange deposit status"
+                );
+                BuildExchangeDepositError::InternalError(format!(
+                    "Unable to get exchange deposit status: {e:?}"
+                ))
+            }
+        })?;
+    let deposit_address = status.deposit_address.ok_or_else(|| {
+        BuildExchangeDepositError::DepositAddressNotAvailable(
+            "The deposit must be confirmed at the exchange first".to_string(),
+        )
+    })?;
+
+    let caip19_asset = Caip19Asset::parse(&asset)
+        .map_err(|e| BuildExchangeDepositError::InternalError(e.to_string()))?;
+    let namespace = SupportedNamespaces::from_str(caip19_asset.chain_id().namespace())
+        .map_err(|e| BuildExchangeDepositError::ValidationError(e.to_string()))?;
+    let deposit_address = format!("{}:{}", caip19_asset.chain_id(), deposit_address);
+
+    let transaction = build_transaction(
+        state.clone(),
+        project_id,
+        namespace,
+        PaymentIntent {
+            asset,
+            amount: amount.to_string(),
+            recipient: deposit_address.clone(),
+            sender,
+            price: None,
+        },
+    )
+    .await
+    .map_err(BuildExchangeDepositError::BuildTransaction)?;
+
+    set_deposit_address(&state, &request.session_id, &deposit_address)
+        .await
+        .map_err(|e| BuildExchangeDepositError::InternalError(e.to_string()))?;
+
+    Ok(BuildExchangeDepositResponse {
+        deposit_address,
+        transaction,
+    })
+}
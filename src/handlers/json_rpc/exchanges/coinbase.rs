@@ -1,15 +1,18 @@
 use {
     crate::handlers::json_rpc::exchanges::{
-        BuyTransactionStatus, ExchangeError, ExchangeProvider, Feature, FeatureType,
-        GetBuyStatusParams, GetBuyStatusResponse, GetBuyUrlParams,
+        validate_webhook_timestamp, BuyTransactionStatus, ExchangeError, ExchangeProvider,
+        ExchangeWebhookEvent, Feature, FeatureType, GetBuyStatusParams, GetBuyStatusResponse,
+        GetBuyUrlParams, GetDepositStatusResponse, GetDepositUrlParams,
     },
     crate::state::AppState,
-    crate::utils::crypto::Caip19Asset,
+    crate::utils::crypto::{constant_time_eq, Caip19Asset},
     axum::extract::State,
     base64::engine::general_purpose::STANDARD,
     base64::prelude::*,
     ed25519_dalek::{Signer, SigningKey},
+    hyper::HeaderMap,
     once_cell::sync::Lazy,
+    openssl::{hash::MessageDigest, pkey::PKey},
     rand::RngCore,
     serde::{Deserialize, Serialize},
     std::collections::HashMap,
@@ -22,12 +25,14 @@ use {
 };
 
 const COINBASE_ONE_CLICK_BUY_URL: &str = "https://pay.coinbase.com/buy/select-asset";
+const COINBASE_SELL_URL: &str = "https://pay.coinbase.com/v3/sell/input";
 const DEFAULT_PAYMENT_METHOD: &str = "CRYPTO_ACCOUNT";
 const COINBASE_API_HOST: &str = "api.developer.coinbase.com";
 const CREDENTIALS_URL: &str = "https://api.reown.com/internal/v1/coinbase-dwe";
 const DEFAULT_ST: &str = "blockchain-api";
 const DEFAULT_SV: &str = "1.0.0";
 const JWT_EXPIRY_SECONDS: usize = 120;
+const WEBHOOK_SIGNATURE_HEADER: &str = "X-Hook0-Signature";
 
 // CAIP-19 asset mappings to Coinbase assets
 static CAIP19_TO_COINBASE_CRYPTO: Lazy<HashMap<&str, &str>> = Lazy::new(|| {
@@ -149,6 +154,40 @@ struct OnrampTransaction {
     payment_total_usd: Option<CurrencyAmount>,
 }
 
+#[derive(Debug, Deserialize)]
+struct SellTransactionStatusResponse {
+    transactions: Vec<OfframpTransaction>,
+}
+
+#[derive(Debug, Deserialize)]
+enum CoinbaseSellTransactionStatus {
+    #[serde(rename = "TRANSACTION_STATUS_STARTED")]
+    Started,
+    #[serde(rename = "TRANSACTION_STATUS_SUCCESS")]
+    Success,
+    #[serde(rename = "TRANSACTION_STATUS_FAILED")]
+    Failed,
+    #[serde(other)]
+    Unknown,
+}
+
+/// Offramp transaction, the `to_address` is the Coinbase deposit address
+/// the user sends the sold crypto to
+#[derive(Debug, Deserialize)]
+struct OfframpTransaction {
+    status: CoinbaseSellTransactionStatus,
+    to_address: Option<String>,
+    tx_hash: Option<String>,
+}
+
+/// Onramp transaction event sent by the Coinbase webhook
+#[derive(Debug, Deserialize)]
+struct WebhookPayload {
+    status: CoinbaseTransactionStatus,
+    tx_hash: Option<String>,
+    partner_user_ref: Option<String>,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 struct SessionTokenAddresses {
     address: String,
@@ -186,6 +225,13 @@ impl ExchangeProvider for CoinbaseExchange {
         CAIP19_TO_COINBASE_CRYPTO.contains_key(asset.to_string().as_str())
     }
 
+    fn supported_assets(&self) -> Vec<String> {
+        CAIP19_TO_COINBASE_CRYPTO
+            .keys()
+            .map(|asset| asset.to_string())
+            .collect()
+    }
+
     fn is_enabled(&self, feature_type: &FeatureType, project_features: &[Feature]) -> bool {
         if feature_type != &FeatureType::FundWallet {
             return false;
@@ -398,21 +444,7 @@ impl CoinbaseExchange {
         match response.transactions.first() {
             Some(transaction) => {
                 let tx_hash = transaction.tx_hash.clone();
-
-                let status = match &transaction.status {
-                    CoinbaseTransactionStatus::Success => {
-                        if tx_hash.as_ref().is_none_or(String::is_empty) {
-                            // It's possible that the transaction is successful
-                            // but the tx_hash is not available yet.
-                            BuyTransactionStatus::InProgress
-                        } else {
-                            BuyTransactionStatus::Success
-                        }
-                    }
-                    CoinbaseTransactionStatus::InProgress => BuyTransactionStatus::InProgress,
-                    CoinbaseTransactionStatus::Failed => BuyTransactionStatus::Failed,
-                    CoinbaseTransactionStatus::Created => BuyTransactionStatus::InProgress,
-                };
+                let status = to_buy_transaction_status(&transaction.status, &tx_hash);
 
                 Ok(GetBuyStatusResponse { status, tx_hash })
             }
@@ -422,6 +454,189 @@ impl CoinbaseExchange {
             }),
         }
     }
+
+    pub async fn get_deposit_url(
+        &self,
+        state: State<Arc<AppState>>,
+        params: GetDepositUrlParams,
+    ) -> Result<String, ExchangeError> {
+        let credentials = fetch_coinbase_credentials(&state, &params.project_id).await?;
+
+        let (crypto, network) = self.map_asset_to_coinbase_format(&params.asset)?;
+
+        let session_token = self
+            .generate_session_token(&state, &credentials, &params.asset, &params.sender)
+            .await?;
+
+        let mut url = Url::parse(COINBASE_SELL_URL)
+            .map_err(|e| ExchangeError::InternalError(format!("Failed to parse URL: {e}")))?;
+
+        url.query_pairs_mut()
+            .append_pair("sessionToken", &session_token)
+            .append_pair("partnerUserId", &params.session_id)
+            .append_pair("defaultAsset", &crypto)
+            .append_pair("defaultNetwork", &network)
+            .append_pair("presetCryptoAmount", &params.amount.to_string());
+
+        if should_add_client_ip(&params.user_ip) {
+            url.query_pairs_mut()
+                .append_pair("clientIp", &params.user_ip.to_string());
+        }
+
+        Ok(url.to_string())
+    }
+
+    pub async fn get_deposit_status(
+        &self,
+        state: State<Arc<AppState>>,
+        params: GetBuyStatusParams,
+    ) -> Result<GetDepositStatusResponse, ExchangeError> {
+        let credentials = fetch_coinbase_credentials(&state, &params.project_id).await?;
+
+        let res = self
+            .send_get_request(
+                &state,
+                &credentials,
+                &format!("/onramp/v1/sell/user/{}/transactions", params.session_id),
+            )
+            .await?;
+        let response: SellTransactionStatusResponse = res.json().await.map_err(|e| {
+            debug!("Error parsing sell transaction status response: {:?}", e);
+            ExchangeError::InternalError(e.to_string())
+        })?;
+
+        debug!("get_deposit_status response: {:?}", response);
+
+        match response.transactions.into_iter().next() {
+            Some(transaction) => {
+                let status = match transaction.status {
+                    CoinbaseSellTransactionStatus::Started => BuyTransactionStatus::InProgress,
+                    CoinbaseSellTransactionStatus::Success => BuyTransactionStatus::Success,
+                    CoinbaseSellTransactionStatus::Failed => BuyTransactionStatus::Failed,
+                    CoinbaseSellTransactionStatus::Unknown => BuyTransactionStatus::Unknown,
+                };
+                Ok(GetDepositStatusResponse {
+                    status,
+                    tx_hash: transaction.tx_hash,
+                    deposit_address: transaction.to_address,
+                })
+            }
+            None => Ok(GetDepositStatusResponse {
+                status: BuyTransactionStatus::Unknown,
+                tx_hash: None,
+                deposit_address: None,
+            }),
+        }
+    }
+
+    /// Webhook events are delivered by Hook0 and signed with the
+    /// subscription secret, the signature header has the
+    /// `t=<timestamp>,h=<header names>,v1=<hex signature>` format
+    pub fn parse_webhook(
+        &self,
+        state: &Arc<AppState>,
+        headers: &HeaderMap,
+        body: &[u8],
+    ) -> Result<ExchangeWebhookEvent, ExchangeError> {
+        let config = state.reloadable_config();
+        let secret = config
+            .exchanges
+            .coinbase_webhook_secret
+            .as_ref()
+            .ok_or_else(|| {
+                ExchangeError::ConfigurationError("Webhook is not configured".to_string())
+            })?;
+
+        let signature_header = headers
+            .get(WEBHOOK_SIGNATURE_HEADER)
+            .and_then(|v| v.to_str().ok())
+            .ok_or_else(|| {
+                ExchangeError::WebhookAuthenticationError(format!(
+                    "Missing {WEBHOOK_SIGNATURE_HEADER} header"
+                ))
+            })?;
+        let mut timestamp = None;
+        let mut header_names = "";
+        let mut signature = None;
+        for part in signature_header.split(',') {
+            match part.split_once('=') {
+                Some(("t", value)) => timestamp = Some(value),
+                Some(("h", value)) => header_names = value,
+                Some(("v1", value)) => signature = Some(value),
+                _ => {}
+            }
+        }
+        let (Some(timestamp), Some(signature)) = (timestamp, signature) else {
+            return Err(ExchangeError::WebhookAuthenticationError(
+                "Invalid signature header".to_string(),
+            ));
+        };
+        validate_webhook_timestamp(timestamp.parse::<u64>().map_err(|_| {
+            ExchangeError::WebhookAuthenticationError("Invalid timestamp".to_string())
+        })?)?;
+
+        let header_values = header_names
+            .split_whitespace()
+            .map(|name| {
+                headers
+                    .get(name)
+                    .and_then(|v| v.to_str().ok())
+                    .unwrap_or_default()
+            })
+            .collect::<Vec<_>>()
+            .join(".");
+        let mut signed_payload =
+            format!("{timestamp}.{header_names}.{header_values}.").into_bytes();
+        signed_payload.extend_from_slice(body);
+
+        let pkey = PKey::hmac(secret.as_bytes())
+            .map_err(|e| ExchangeError::InternalError(format!("Failed to create HMAC key: {e}")))?;
+        let mut signer = openssl::sign::Signer::new(MessageDigest::sha256(), &pkey)
+            .map_err(|e| ExchangeError::InternalError(format!("Failed to create signer: {e}")))?;
+        let expected_signature = signer
+            .sign_oneshot_to_vec(&signed_payload)
+            .map_err(|e| ExchangeError::InternalError(format!("Failed to sign data: {e}")))?;
+        if !constant_time_eq(hex::encode(expected_signature), signature) {
+            return Err(ExchangeError::WebhookAuthenticationError(
+                "Invalid signature".to_string(),
+            ));
+        }
+
+        let payload: WebhookPayload = serde_json::from_slice(body)
+            .map_err(|e| ExchangeError::ValidationError(format!("Invalid webhook payload: {e}")))?;
+        debug!("Coinbase webhook payload: {:?}", payload);
+        let session_id = payload.partner_user_ref.ok_or_else(|| {
+            ExchangeError::ValidationError("Missing partner user reference".to_string())
+        })?;
+
+        Ok(ExchangeWebhookEvent {
+            session_id,
+            status: GetBuyStatusResponse {
+                status: to_buy_transaction_status(&payload.status, &payload.tx_hash),
+                tx_hash: payload.tx_hash,
+            },
+        })
+    }
+}
+
+fn to_buy_transaction_status(
+    status: &CoinbaseTransactionStatus,
+    tx_hash: &Option<String>,
+) -> BuyTransactionStatus {
+    match status {
+        CoinbaseTransactionStatus::Success => {
+            if tx_hash.as_ref().is_none_or(String::is_empty) {
+                // It's possible that the transaction is successful
+                // but the tx_hash is not available yet.
+                BuyTransactionStatus::InProgress
+            } else {
+                BuyTransactionStatus::Success
+            }
+        }
+        CoinbaseTransactionStatus::InProgress => BuyTransactionStatus::InProgress,
+        CoinbaseTransactionStatus::Failed => BuyTransactionStatus::Failed,
+        CoinbaseTransactionStatus::Created => BuyTransactionStatus::InProgress,
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -549,8 +764,8 @@ async fn fetch_coinbase_credentials(
     state: &Arc<AppState>,
     project_id: &str,
 ) -> Result<CoinbaseCredentials, ExchangeError> {
-    let jwt_token = state
-        .config
+    let config = state.reloadable_config();
+    let jwt_token = config
         .exchanges
         .internal_api_coinbase_credentials
         .as_ref()
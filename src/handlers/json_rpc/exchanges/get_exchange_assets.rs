@@ -0,0 +1,164 @@
+use {
+    crate::handlers::json_rpc::exchanges::{
+        get_enabled_features, get_feature_type, is_feature_enabled_for_project_id, ExchangeAsset,
+        ExchangeType, Feature, FeatureType,
+    },
+    crate::{handlers::SdkInfoParams, state::AppState},
+    axum::{
+        extract::{Query, State},
+        Json,
+    },
+    futures_util::future::join_all,
+    serde::{Deserialize, Serialize},
+    std::{sync::Arc, time::Duration},
+    strum::IntoEnumIterator,
+    thiserror::Error,
+    tracing::{debug, error},
+    wc::metrics::{future_metrics, FutureExt},
+};
+
+const EXCHANGE_ASSETS_CACHE_TTL: Duration = Duration::from_secs(60 * 60);
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetExchangeAssetsRequest {
+    #[serde(default)]
+    pub include_only: Option<Vec<String>>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetExchangeAssetsResponse {
+    pub exchanges: Vec<ExchangeAssets>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExchangeAssets {
+    pub id: String,
+    pub name: String,
+    pub assets: Vec<ExchangeAsset>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QueryParams {
+    #[serde(flatten)]
+    pub sdk_info: SdkInfoParams,
+    pub source: Option<String>,
+}
+
+#[derive(Error, Debug)]
+pub enum GetExchangeAssetsError {
+    #[error("Validation error: {0}")]
+    ValidationError(String),
+
+    #[error("Internal error")]
+    InternalError(GetExchangeAssetsInternalError),
+}
+
+#[derive(Error, Debug)]
+pub enum GetExchangeAssetsInternalError {
+    #[error("Unable to get enabled features: {0}")]
+    UnableToGetEnabledFeatures(String),
+}
+
+impl GetExchangeAssetsError {
+    pub fn is_internal(&self) -> bool {
+        matches!(self, GetExchangeAssetsError::InternalError(_))
+    }
+}
+
+pub async fn handler(
+    state: State<Arc<AppState>>,
+    project_id: String,
+    query: Query<QueryParams>,
+    Json(request): Json<GetExchangeAssetsRequest>,
+) -> Result<GetExchangeAssetsResponse, GetExchangeAssetsError> {
+    let feature_type = get_feature_type(query.source.as_deref());
+    let project_features = get_enabled_features(state.clone(), &project_id)
+        .await
+        .map_err(|e| {
+            GetExchangeAssetsError::InternalError(
+                GetExchangeAssetsInternalError::UnableToGetEnabledFeatures(e.to_string()),
+            )
+        })?;
+
+    is_feature_enabled_for_project_id(state.clone(), &project_id, &project_features, &feature_type)
+        .await
+        .map_err(|e| GetExchangeAssetsError::ValidationError(e.to_string()))?;
+    handler_internal(state, request, &project_features, &feature_type)
+        .with_metrics(future_metrics!("handler_task", "name" => "pay_get_exchange_assets"))
+        .await
+}
+
+async fn handler_internal(
+    state: State<Arc<AppState>>,
+    request: GetExchangeAssetsRequest,
+    project_features: &[Feature],
+    feature_type: &FeatureType,
+) -> Result<GetExchangeAssetsResponse, GetExchangeAssetsError> {
+    let exchanges = ExchangeType::iter()
+        .filter(|e| e.is_enabled(feature_type, project_features))
+        .filter(|e| {
+            request
+                .include_only
+                .as_ref()
+                .is_none_or(|include_only| include_only.contains(&e.to_exchange().id))
+        })
+        .map(|exchange| {
+            let state = state.clone();
+            let exchange_info = exchange.to_exchange();
+            async move {
+                ExchangeAssets {
+                    assets: get_exchange_assets(state, exchange, &exchange_info.id).await,
+                    id: exchange_info.id,
+                    name: exchange_info.name,
+                }
+            }
+        });
+
+    Ok(GetExchangeAssetsResponse {
+        exchanges: join_all(exchanges).await,
+    })
+}
+
+fn exchange_assets_cache_key(exchange_id: &str) -> String {
+    format!("exchange_assets/{exchange_id}")
+}
+
+/// Get the exchange assets from the cache or the exchange.
+/// Falls back to the assets without limits if the exchange is unavailable.
+async fn get_exchange_assets(
+    state: State<Arc<AppState>>,
+    exchange: ExchangeType,
+    exchange_id: &str,
+) -> Vec<ExchangeAsset> {
+    let cache_key = exchange_assets_cache_key(exchange_id);
+    if let Some(cache) = &state.exchange_assets_cache {
+        if let Ok(Some(assets)) = cache.get(&cache_key).await {
+            return assets;
+        }
+    }
+
+    match exchange.get_assets(state.clone()).await {
+        Ok(assets) => {
+            if let Some(cache) = &state.exchange_assets_cache {
+                cache
+                    .set(&cache_key, &assets, Some(EXCHANGE_ASSETS_CACHE_TTL))
+                    .await
+                    .unwrap_or_else(|e| error!("Failed to set exchange assets cache: {e}"));
+            }
+            assets
+        }
+        Err(e) => {
+            debug!(exchange_id, "Unable to get exchange assets limits: {e}");
+            exchange
+                .provider()
+                .supported_assets()
+                .into_iter()
+                .map(ExchangeAsset::without_limits)
+                .collect()
+        }
+    }
+}
@@ -0,0 +1,166 @@
+use {
+    crate::handlers::json_rpc::exchanges::{
+        get_enabled_features, get_exchange_by_id, get_feature_type,
+        is_feature_enabled_for_project_id,
+        transactions::{
+            mark_failed as mark_transaction_failed, mark_succeeded as mark_transaction_succeeded,
+            touch_pending as touch_pending_transaction,
+        },
+        BuyTransactionStatus, ExchangeError, Feature, FeatureType, GetBuyStatusParams,
+    },
+    crate::{handlers::SdkInfoParams, state::AppState},
+    axum::{
+        extract::{Query, State},
+        Json,
+    },
+    serde::{Deserialize, Serialize},
+    std::sync::Arc,
+    thiserror::Error,
+    tracing::debug,
+    wc::metrics::{future_metrics, FutureExt},
+};
+
+const MAX_SESSION_ID_LENGTH: usize = 50;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetExchangeDepositStatusRequest {
+    pub exchange_id: String,
+    pub session_id: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetExchangeDepositStatusResponse {
+    pub status: BuyTransactionStatus,
+    pub tx_hash: Option<String>,
+    pub deposit_address: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QueryParams {
+    #[serde(flatten)]
+    pub sdk_info: SdkInfoParams,
+    pub source: Option<String>,
+}
+
+#[derive(Error, Debug)]
+pub enum GetExchangeDepositStatusError {
+    #[error("Validation error: {0}")]
+    ValidationError(String),
+
+    #[error("Exchange not found: {0}")]
+    ExchangeNotFound(String),
+
+    #[error("Internal error: {0}")]
+    InternalError(String),
+}
+
+impl GetExchangeDepositStatusError {
+    pub fn is_internal(&self) -> bool {
+        matches!(self, GetExchangeDepositStatusError::InternalError(_))
+    }
+}
+
+pub async fn handler(
+    state: State<Arc<AppState>>,
+    project_id: String,
+    query: Query<QueryParams>,
+    Json(request): Json<GetExchangeDepositStatusRequest>,
+) -> Result<GetExchangeDepositStatusResponse, GetExchangeDepositStatusError> {
+    let feature_type = get_feature_type(query.source.as_deref());
+    let project_features = get_enabled_features(state.clone(), &project_id)
+        .await
+        .map_err(|e| GetExchangeDepositStatusError::InternalError(e.to_string()))?;
+
+    is_feature_enabled_for_project_id(state.clone(), &project_id, &project_features, &feature_type)
+        .await
+        .map_err(|e| GetExchangeDepositStatusError::ValidationError(e.to_string()))?;
+    handler_internal(state, project_id, request, &project_features, &feature_type)
+        .with_metrics(future_metrics!("handler_task", "name" => "pay_get_exchange_deposit_status"))
+        .await
+}
+
+async fn handler_internal(
+    state: State<Arc<AppState>>,
+    project_id: String,
+    request: GetExchangeDepositStatusRequest,
+    project_features: &[Feature],
+    feature_type: &FeatureType,
+) -> Result<GetExchangeDepositStatusResponse, GetExchangeDepositStatusError> {
+    let exchange = get_exchange_by_id(&request.exchange_id, feature_type, project_features)
+        .map_err(|e| GetExchangeDepositStatusError::ExchangeNotFound(e.to_string()))?;
+
+    if request.session_id.is_empty() || request.session_id.len() > MAX_SESSION_ID_LENGTH {
+        return Err(GetExchangeDepositStatusError::ValidationError(
+            "Invalid session ID".to_string(),
+        ));
+    }
+
+    let result = exchange
+        .get_deposit_status(
+            state.clone(),
+            GetBuyStatusParams {
+                project_id,
+                session_id: request.session_id.clone(),
+            },
+        )
+        .await;
+
+    match result {
+        Ok(response) => {
+            match response.status {
+                BuyTransactionStatus::Success => {
+                    let _ = mark_transaction_succeeded(
+                        &state,
+                        &request.session_id,
+                        &request.exchange_id,
+                        response.tx_hash.as_deref(),
+                    )
+                    .await;
+                }
+                BuyTransactionStatus::Failed => {
+                    let _ = mark_transaction_failed(
+                        &state,
+                        &request.session_id,
+                        &request.exchange_id,
+                        Some("provider_failed"),
+                        response.tx_hash.as_deref(),
+                    )
+                    .await;
+                }
+                _ => {
+                    let _ = touch_pending_transaction(
+                        &state,
+                        &request.exchange_id,
+                        &request.session_id,
+                    )
+                    .await;
+                }
+            }
+
+            Ok(GetExchangeDepositStatusResponse {
+                status: response.status,
+                tx_hash: response.tx_hash,
+                deposit_address: response.deposit_address,
+            })
+        }
+        Err(e) => match e {
+            ExchangeError::ValidationError(msg) => {
+                Err(GetExchangeDepositStatusError::ValidationError(msg))
+            }
+            _ => {
+                debug!(
+                    error = %e,
+                    session_id = %request.session_id,
+                    exchange_id = %request.exchange_id,
+                    "Internal error, unable to get exchange deposit status"
+                );
+                Err(GetExchangeDepositStatusError::InternalError(format!(
+                    "Unable to get exchange deposit status: {e:?}"
+                )))
+            }
+        },
+    }
+}
@@ -0,0 +1,205 @@
+use {
+    crate::{
+        database::exchange_reconciliation::{NewExchangeTransaction, TxDirection},
+        handlers::{
+            json_rpc::exchanges::{
+                get_enabled_features, get_exchange_by_id, get_feature_type,
+                is_feature_enabled_for_project_id, transactions::create as create_transaction,
+                ExchangeError, Feature, FeatureType, GetDepositUrlParams,
+            },
+            SdkInfoParams,
+        },
+        state::AppState,
+        utils::{
+            crypto::{disassemble_caip10, Caip19Asset},
+            network::get_forwarded_ip,
+        },
+    },
+    axum::{
+        extract::{ConnectInfo, Query, State},
+        Json,
+    },
+    hyper::HeaderMap,
+    serde::{Deserialize, Serialize},
+    std::{net::SocketAddr, sync::Arc},
+    thiserror::Error,
+    tracing::debug,
+    uuid::Uuid,
+    wc::metrics::{future_metrics, FutureExt},
+};
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetExchangeDepositUrlRequest {
+    pub exchange_id: String,
+    pub asset: String,
+    pub amount: String,
+    /// CAIP-10 address the funds are deposited from
+    pub sender: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetExchangeDepositUrlResponse {
+    pub url: String,
+    pub session_id: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QueryParams {
+    #[serde(flatten)]
+    pub sdk_info: SdkInfoParams,
+    pub source: Option<String>,
+}
+
+#[derive(Error, Debug)]
+pub enum GetExchangeDepositUrlError {
+    #[error("Validation error: {0}")]
+    ValidationError(String),
+
+    #[error("Exchange not found: {0}")]
+    ExchangeNotFound(String),
+
+    #[error("Internal error: {0}")]
+    InternalError(String),
+}
+
+impl GetExchangeDepositUrlError {
+    pub fn is_internal(&self) -> bool {
+        matches!(self, GetExchangeDepositUrlError::InternalError(_))
+    }
+}
+
+pub async fn handler(
+    state: State<Arc<AppState>>,
+    project_id: String,
+    connect_info: ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+    query: Query<QueryParams>,
+    Json(request): Json<GetExchangeDepositUrlRequest>,
+) -> Result<GetExchangeDepositUrlResponse, GetExchangeDepositUrlError> {
+    let feature_type = get_feature_type(query.source.as_deref());
+    let project_features = get_enabled_features(state.clone(), &project_id)
+        .await
+        .map_err(|e| GetExchangeDepositUrlError::InternalError(e.to_string()))?;
+
+    is_feature_enabled_for_project_id(state.clone(), &project_id, &project_features, &feature_type)
+        .await
+        .map_err(|e| GetExchangeDepositUrlError::ValidationError(e.to_string()))?;
+    handler_internal(
+        state,
+        project_id,
+        connect_info,
+        headers,
+        request,
+        &project_features,
+        &feature_type,
+    )
+    .with_metrics(future_metrics!("handler_task", "name" => "pay_get_exchange_deposit_url"))
+    .await
+}
+
+async fn handler_internal(
+    state: State<Arc<AppState>>,
+    project_id: String,
+    connect_info: ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+    request: GetExchangeDepositUrlRequest,
+    project_features: &[Feature],
+    feature_type: &FeatureType,
+) -> Result<GetExchangeDepositUrlResponse, GetExchangeDepositUrlError> {
+    let exchange = get_exchange_by_id(&request.exchange_id, feature_type, project_features)
+        .map_err(|e| GetExchangeDepositUrlError::ExchangeNotFound(e.to_string()))?;
+
+    let asset = Caip19Asset::parse(&request.asset)
+        .map_err(|e| GetExchangeDepositUrlError::ValidationError(e.to_string()))?;
+
+    let (namespace, chain_id, address) = disassemble_caip10(&request.sender)
+        .map_err(|e| GetExchangeDepositUrlError::ValidationError(e.to_string()))?;
+    if namespace.to_string() != asset.chain_id().namespace()
+        || chain_id != asset.chain_id().reference()
+    {
+        return Err(GetExchangeDepositUrlError::ValidationError(format!(
+            "Invalid sender. CAIP-10 chain must match asset chain: {}",
+            asset.chain_id()
+        )));
+    }
+
+    if !exchange.is_asset_supported(&asset) {
+        return Err(GetExchangeDepositUrlError::ValidationError(format!(
+            "Asset {} is not supported by exchange {}",
+            asset, request.exchange_id
+        )));
+    }
+
+    let amount = request
+        .amount
+        .parse::<f64>()
+        .ok()
+        .filter(|amount| *amount > 0.0)
+        .ok_or_else(|| {
+            GetExchangeDepositUrlError::ValidationError(format!(
+                "Invalid amount. Expected a positive number: {}",
+                request.amount
+            ))
+        })?;
+
+    // Removing dashes from the session id because binance only accepts alphanumeric characters
+    let session_id = Uuid::new_v4().to_string().replace("-", "");
+
+    let result = exchange
+        .get_deposit_url(
+            state.clone(),
+            GetDepositUrlParams {
+                project_id: project_id.clone(),
+                asset,
+                amount,
+                sender: address,
+                session_id: session_id.clone(),
+                user_ip: get_forwarded_ip(&headers).unwrap_or_else(|| connect_info.0.ip()),
+            },
+        )
+        .await;
+
+    match result {
+        Ok(url) => {
+            create_transaction(
+                &state,
+                NewExchangeTransaction {
+                    session_id: &session_id,
+                    exchange_id: &request.exchange_id,
+                    project_id: Some(&project_id),
+                    asset: Some(&request.asset),
+                    amount: Some(amount),
+                    recipient: None,
+                    pay_url: Some(&url),
+                    direction: TxDirection::Deposit,
+                    sender: Some(&request.sender),
+                },
+            )
+            .await
+            .map_err(|e| {
+                debug!(error = %e, "Failed to persist exchange deposit transaction");
+                GetExchangeDepositUrlError::InternalError(
+                    "Failed to persist exchange deposit transaction".into(),
+                )
+            })?;
+            Ok(GetExchangeDepositUrlResponse { url, session_id })
+        }
+        Err(e) => match e {
+            ExchangeError::ValidationError(msg) => {
+                Err(GetExchangeDepositUrlError::ValidationError(msg))
+            }
+            _ => {
+                debug!(
+                    error = %e,
+                    "Internal error, unable to get exchange deposit URL"
+                );
+                Err(GetExchangeDepositUrlError::InternalError(format!(
+                    "Unable to get exchange deposit URL: {e:?}"
+                )))
+            }
+        },
+    }
+}
@@ -1,6 +1,6 @@
 use {
     crate::{
-        database::exchange_reconciliation::NewExchangeTransaction,
+        database::exchange_reconciliation::{NewExchangeTransaction, TxDirection},
         handlers::{
             json_rpc::exchanges::{
                 get_enabled_features, get_exchange_by_id, get_feature_type,
@@ -181,6 +181,8 @@ async fn handler_internal(
                     amount: Some(amount),
                     recipient: Some(&address),
                     pay_url: Some(&url),
+                    direction: TxDirection::Buy,
+                    sender: None,
                 },
             )
             .await
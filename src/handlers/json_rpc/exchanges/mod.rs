@@ -5,6 +5,7 @@ use {
     },
     axum::extract::State,
     cerberus::project::{Feature, ProjectDataRequest},
+    hyper::HeaderMap,
     serde::{Deserialize, Serialize},
     std::{net::IpAddr, sync::Arc},
     strum::{EnumProperty, IntoEnumIterator},
@@ -14,16 +15,23 @@ use {
 };
 
 pub mod binance;
+pub mod build_exchange_deposit;
 pub mod coinbase;
+pub mod get_exchange_assets;
 pub mod get_exchange_buy_status;
+pub mod get_exchange_deposit_status;
+pub mod get_exchange_deposit_url;
 pub mod get_exchange_url;
 pub mod get_exchanges;
+pub mod okx;
 pub mod reconciler;
 pub mod test_exchange;
 pub mod transactions;
+pub mod webhook;
 
 use binance::BinanceExchange;
 use coinbase::CoinbaseExchange;
+use okx::OkxExchange;
 use test_exchange::TestExchange;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Display, AsRefStr, EnumProperty)]
@@ -54,6 +62,13 @@ pub struct Config {
     pub binance_token: Option<String>,
     pub binance_key: Option<String>,
     pub binance_host: Option<String>,
+    pub binance_webhook_public_key: Option<String>,
+    pub coinbase_webhook_secret: Option<String>,
+    pub okx_api_key: Option<String>,
+    pub okx_secret_key: Option<String>,
+    pub okx_passphrase: Option<String>,
+    pub okx_host: Option<String>,
+    pub reconciler_interval_secs: Option<u64>,
     pub allowed_project_ids: Option<Vec<String>>,
 }
 
@@ -65,6 +80,31 @@ pub struct Exchange {
     pub image_url: Option<String>,
 }
 
+/// Asset supported by an exchange along with its limits and fee estimate,
+/// amounts are in the asset units
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ExchangeAsset {
+    pub asset: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_amount: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_amount: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fee: Option<String>,
+}
+
+impl ExchangeAsset {
+    pub fn without_limits(asset: impl Into<String>) -> Self {
+        Self {
+            asset: asset.into(),
+            min_amount: None,
+            max_amount: None,
+            fee: None,
+        }
+    }
+}
+
 pub struct GetBuyUrlParams {
     pub project_id: String,
     pub asset: Caip19Asset,
@@ -95,11 +135,55 @@ pub struct GetBuyStatusResponse {
     pub tx_hash: Option<String>,
 }
 
+pub struct GetDepositUrlParams {
+    pub project_id: String,
+    pub asset: Caip19Asset,
+    pub amount: f64,
+    /// Address the funds are deposited from
+    pub sender: String,
+    pub session_id: String,
+    pub user_ip: IpAddr,
+}
+
+/// Status of the deposit to the exchange along with the deposit address,
+/// which is available once the deposit is confirmed at the exchange
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetDepositStatusResponse {
+    pub status: BuyTransactionStatus,
+    pub tx_hash: Option<String>,
+    pub deposit_address: Option<String>,
+}
+
+/// Transaction status update received from the exchange webhook
+#[derive(Debug)]
+pub struct ExchangeWebhookEvent {
+    pub session_id: String,
+    pub status: GetBuyStatusResponse,
+}
+
+/// Maximum age of the signed webhook timestamp to prevent replays
+pub const WEBHOOK_MAX_AGE_SECS: u64 = 300;
+
+pub fn validate_webhook_timestamp(timestamp_secs: u64) -> Result<(), ExchangeError> {
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_err(|_| ExchangeError::InternalError("Failed to get current time".to_string()))?
+        .as_secs();
+    if now.abs_diff(timestamp_secs) > WEBHOOK_MAX_AGE_SECS {
+        return Err(ExchangeError::WebhookAuthenticationError(
+            "Webhook timestamp is expired".to_string(),
+        ));
+    }
+    Ok(())
+}
+
 pub trait ExchangeProvider {
     fn id(&self) -> &'static str;
     fn name(&self) -> &'static str;
     fn image_url(&self) -> Option<&'static str>;
     fn is_asset_supported(&self, asset: &Caip19Asset) -> bool;
+    fn supported_assets(&self) -> Vec<String>;
     fn to_exchange(&self) -> Exchange {
         Exchange {
             id: self.id().to_string(),
@@ -115,6 +199,7 @@ pub trait ExchangeProvider {
 pub enum ExchangeType {
     Binance,
     Coinbase,
+    Okx,
     ReownTest,
 }
 
@@ -140,6 +225,9 @@ pub enum ExchangeError {
 
     #[error("Exchange internal error: {0}")]
     InternalError(String),
+
+    #[error("Webhook authentication error: {0}")]
+    WebhookAuthenticationError(String),
 }
 
 impl ExchangeType {
@@ -147,6 +235,7 @@ impl ExchangeType {
         match self {
             ExchangeType::Binance => Box::new(BinanceExchange),
             ExchangeType::Coinbase => Box::new(CoinbaseExchange),
+            ExchangeType::Okx => Box::new(OkxExchange),
             ExchangeType::ReownTest => Box::new(TestExchange),
         }
     }
@@ -167,6 +256,7 @@ impl ExchangeType {
         match self {
             ExchangeType::Binance => BinanceExchange.get_buy_url(state, params).await,
             ExchangeType::Coinbase => CoinbaseExchange.get_buy_url(state, params).await,
+            ExchangeType::Okx => OkxExchange.get_buy_url(state, params).await,
             ExchangeType::ReownTest => TestExchange.get_buy_url(state, params),
         }
     }
@@ -179,10 +269,73 @@ impl ExchangeType {
         match self {
             ExchangeType::Binance => BinanceExchange.get_buy_status(state, params).await,
             ExchangeType::Coinbase => CoinbaseExchange.get_buy_status(state, params).await,
+            ExchangeType::Okx => OkxExchange.get_buy_status(state, params).await,
             ExchangeType::ReownTest => TestExchange.get_buy_status(state, params).await,
         }
     }
 
+    /// Url to confirm the deposit of the wallet funds to the exchange
+    pub async fn get_deposit_url(
+        &self,
+        state: State<Arc<AppState>>,
+        params: GetDepositUrlParams,
+    ) -> Result<String, ExchangeError> {
+        match self {
+            ExchangeType::Binance => BinanceExchange.get_deposit_url(state, params).await,
+            ExchangeType::Coinbase => CoinbaseExchange.get_deposit_url(state, params).await,
+            ExchangeType::Okx | ExchangeType::ReownTest => Err(ExchangeError::ValidationError(
+                format!("Deposits are not supported for {}", self.provider().id()),
+            )),
+        }
+    }
+
+    pub async fn get_deposit_status(
+        &self,
+        state: State<Arc<AppState>>,
+        params: GetBuyStatusParams,
+    ) -> Result<GetDepositStatusResponse, ExchangeError> {
+        match self {
+            ExchangeType::Binance => BinanceExchange.get_deposit_status(state, params).await,
+            ExchangeType::Coinbase => CoinbaseExchange.get_deposit_status(state, params).await,
+            ExchangeType::Okx | ExchangeType::ReownTest => Err(ExchangeError::ValidationError(
+                format!("Deposits are not supported for {}", self.provider().id()),
+            )),
+        }
+    }
+
+    /// Supported assets with the limits and fees reported by the exchange
+    pub async fn get_assets(
+        &self,
+        state: State<Arc<AppState>>,
+    ) -> Result<Vec<ExchangeAsset>, ExchangeError> {
+        match self {
+            ExchangeType::Binance => BinanceExchange.get_assets(state).await,
+            ExchangeType::Okx => OkxExchange.get_assets(state).await,
+            ExchangeType::Coinbase | ExchangeType::ReownTest => Ok(self
+                .provider()
+                .supported_assets()
+                .into_iter()
+                .map(ExchangeAsset::without_limits)
+                .collect()),
+        }
+    }
+
+    /// Authenticate and parse the transaction status webhook of the exchange
+    pub fn parse_webhook(
+        &self,
+        state: &Arc<AppState>,
+        headers: &HeaderMap,
+        body: &[u8],
+    ) -> Result<ExchangeWebhookEvent, ExchangeError> {
+        match self {
+            ExchangeType::Binance => BinanceExchange.parse_webhook(state, headers, body),
+            ExchangeType::Coinbase => CoinbaseExchange.parse_webhook(state, headers, body),
+            ExchangeType::Okx | ExchangeType::ReownTest => Err(ExchangeError::ValidationError(
+                format!("Webhooks are not supported for {}", self.provider().id()),
+            )),
+        }
+    }
+
     pub fn is_asset_supported(&self, asset: &Caip19Asset) -> bool {
         self.provider().is_asset_supported(asset)
     }
@@ -191,6 +344,7 @@ impl ExchangeType {
         match self {
             ExchangeType::Binance => true,
             ExchangeType::Coinbase => true,
+            ExchangeType::Okx => true,
             ExchangeType::ReownTest => false,
         }
     }
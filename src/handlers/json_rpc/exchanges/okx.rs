@@ -0,0 +1,438 @@
+use {
+    crate::handlers::json_rpc::exchanges::{
+        BuyTransactionStatus, ExchangeAsset, ExchangeError, ExchangeProvider, Feature, FeatureType,
+        GetBuyStatusParams, GetBuyStatusResponse, GetBuyUrlParams,
+    },
+    crate::state::AppState,
+    crate::utils::crypto::Caip19Asset,
+    axum::extract::State,
+    base64::{engine::general_purpose::STANDARD, Engine},
+    chrono::{SecondsFormat, Utc},
+    once_cell::sync::Lazy,
+    openssl::{hash::MessageDigest, pkey::PKey, sign::Signer},
+    serde::{Deserialize, Serialize},
+    std::collections::HashMap,
+    std::sync::Arc,
+    tracing::debug,
+    url::form_urlencoded,
+};
+
+pub struct OkxExchange;
+
+const CREATE_ORDER_PATH: &str = "/api/v5/fiat/onramp/create-order";
+const QUERY_ORDER_PATH: &str = "/api/v5/fiat/onramp/order";
+const CURRENCIES_PATH: &str = "/api/v5/asset/currencies";
+const SUCCESS_CODE: &str = "0";
+
+// CAIP-19 asset mappings to OKX currencies
+static CAIP19_TO_OKX_CRYPTO: Lazy<HashMap<&str, &str>> = Lazy::new(|| {
+    HashMap::from([
+        (
+            "eip155:1/erc20:0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48",
+            "USDC",
+        ), // USDC on Ethereum
+        (
+            "eip155:137/erc20:0x3c499c542cEF5E3811e1192ce70d8cC03d5c3359",
+            "USDC",
+        ), // USDC on Polygon
+        (
+            "eip155:8453/erc20:0x833589fCD6eDb6E08f4c7C32D4f71b54bdA02913",
+            "USDC",
+        ), // USDC on Base
+        (
+            "eip155:42161/erc20:0xaf88d065e77c8cC2239327C5EDb3A432268e5831",
+            "USDC",
+        ), // USDC on Arbitrum
+        ("eip155:1/slip44:60", "ETH"), // Native ETH
+        ("solana:5eykt4UsFv8P8NJdTREpY1vzqKqZKvdp/slip44:501", "SOL"), // Native SOL
+        (
+            "eip155:1/erc20:0xdAC17F958D2ee523a2206206994597C13D831ec7",
+            "USDT",
+        ), // USDT on Ethereum
+        (
+            "eip155:42161/erc20:0xFd086bC7CD5C481DCC9C85ebE478A1C0b69FCbb9",
+            "USDT",
+        ), // USDT on Arbitrum
+        (
+            "eip155:10/erc20:0x94b008aA00579c1307B0EF2c499aD98a8ce58e58",
+            "USDT",
+        ), // USDT on Optimism
+        (
+            "eip155:137/erc20:0xc2132D05D31c914a87C6611C10748AEb04B58e8F",
+            "USDT",
+        ), // USDT on Polygon
+        (
+            "solana:5eykt4UsFv8P8NJdTREpY1vzqKqZKvdp/token:EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v",
+            "USDC",
+        ), // USDC on Solana
+        (
+            "solana:5eykt4UsFv8P8NJdTREpY1vzqKqZKvdp/token:Es9vMFrzaCERmJfrF4H2FYD4KCoNkY11McCe8BenwNYB",
+            "USDT",
+        ), // USDT on Solana
+    ])
+});
+
+// CAIP-2 chain ID mappings to OKX networks
+static CHAIN_ID_TO_OKX_NETWORK: Lazy<HashMap<&str, &str>> = Lazy::new(|| {
+    HashMap::from([
+        ("eip155:1", "ERC20"),                                 // Ethereum
+        ("eip155:137", "Polygon"),                             // Polygon
+        ("eip155:8453", "Base"),                               // Base
+        ("eip155:42161", "Arbitrum One"),                      // Arbitrum
+        ("eip155:10", "Optimism"),                             // Optimism
+        ("solana:5eykt4UsFv8P8NJdTREpY1vzqKqZKvdp", "Solana"), // Solana
+    ])
+});
+
+#[derive(Debug, Deserialize, PartialEq, Eq, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+enum OkxOrderStatus {
+    Init,
+    Pending,
+    Processing,
+    Withdrawing,
+    Success,
+    Failed,
+    Cancelled,
+    Expired,
+    #[serde(other)]
+    Unknown,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CreateOrderRequest {
+    /// The unique order id from the partner side
+    client_order_id: String,
+    /// Crypto currency to buy
+    crypto_ccy: String,
+    /// Chain in the OKX `{ccy}-{network}` format
+    chain: String,
+    /// Requested crypto amount
+    crypto_amount: String,
+    /// Wallet address receiving the withdrawal
+    to_addr: String,
+    /// The original client IP
+    #[serde(skip_serializing_if = "Option::is_none")]
+    client_ip: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CreateOrderResponseData {
+    pay_url: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct QueryOrderResponseData {
+    state: OkxOrderStatus,
+    tx_id: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CurrencyResponseData {
+    ccy: String,
+    chain: String,
+    min_wd: Option<String>,
+    max_wd: Option<String>,
+    min_fee: Option<String>,
+}
+
+/// Base response structure for OKX API responses
+#[derive(Debug, Deserialize)]
+struct OkxResponse<T> {
+    code: String,
+    msg: Option<String>,
+    #[serde(default = "Vec::new")]
+    data: Vec<T>,
+}
+
+struct OkxCredentials {
+    api_key: String,
+    secret_key: String,
+    passphrase: String,
+    host: String,
+}
+
+impl ExchangeProvider for OkxExchange {
+    fn id(&self) -> &'static str {
+        "okx"
+    }
+
+    fn name(&self) -> &'static str {
+        "OKX"
+    }
+
+    fn image_url(&self) -> Option<&'static str> {
+        Some("https://pay-assets.reown.com/okx_128_128.webp")
+    }
+
+    fn is_asset_supported(&self, asset: &Caip19Asset) -> bool {
+        self.map_asset_to_okx_format(asset).is_ok()
+    }
+
+    fn supported_assets(&self) -> Vec<String> {
+        CAIP19_TO_OKX_CRYPTO
+            .keys()
+            .map(|asset| asset.to_string())
+            .collect()
+    }
+
+    fn is_enabled(&self, _feature_type: &FeatureType, _project_features: &[Feature]) -> bool {
+        true
+    }
+}
+
+impl OkxExchange {
+    fn get_api_credentials(&self, state: &Arc<AppState>) -> Result<OkxCredentials, ExchangeError> {
+        let config = &state.reloadable_config().exchanges;
+        match (
+            config.okx_api_key.clone(),
+            config.okx_secret_key.clone(),
+            config.okx_passphrase.clone(),
+            config.okx_host.clone(),
+        ) {
+            (Some(api_key), Some(secret_key), Some(passphrase), Some(host)) => Ok(OkxCredentials {
+                api_key,
+                secret_key,
+                passphrase,
+                host,
+            }),
+            _ => Err(ExchangeError::ConfigurationError(
+                "Exchange is not available".to_string(),
+            )),
+        }
+    }
+
+    /// OKX signs `timestamp + method + request_path + body` with HMAC-SHA256
+    fn generate_signature(
+        &self,
+        timestamp: &str,
+        method: &str,
+        request_path: &str,
+        body: &str,
+        secret_key: &str,
+    ) -> Result<String, ExchangeError> {
+        let pkey = PKey::hmac(secret_key.as_bytes())
+            .map_err(|e| ExchangeError::InternalError(format!("Failed to create HMAC key: {e}")))?;
+        let mut signer = Signer::new(MessageDigest::sha256(), &pkey)
+            .map_err(|e| ExchangeError::InternalError(format!("Failed to create signer: {e}")))?;
+        signer
+            .update(format!("{timestamp}{method}{request_path}{body}").as_bytes())
+            .map_err(|e| ExchangeError::InternalError(format!("Failed to update signer: {e}")))?;
+        let signature = signer
+            .sign_to_vec()
+            .map_err(|e| ExchangeError::InternalError(format!("Failed to sign data: {e}")))?;
+
+        Ok(STANDARD.encode(signature))
+    }
+
+    async fn send_request<R>(
+        &self,
+        state: &Arc<AppState>,
+        method: reqwest::Method,
+        request_path: &str,
+        body: Option<String>,
+    ) -> Result<R, ExchangeError>
+    where
+        R: serde::de::DeserializeOwned + std::fmt::Debug,
+    {
+        self.send_list_request(state, method, request_path, body)
+            .await?
+            .into_iter()
+            .next()
+            .ok_or_else(|| ExchangeError::InternalError("No data returned from OKX".to_string()))
+    }
+
+    async fn send_list_request<R>(
+        &self,
+        state: &Arc<AppState>,
+        method: reqwest::Method,
+        request_path: &str,
+        body: Option<String>,
+    ) -> Result<Vec<R>, ExchangeError>
+    where
+        R: serde::de::DeserializeOwned + std::fmt::Debug,
+    {
+        let credentials = self.get_api_credentials(state)?;
+        let timestamp = Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true);
+        let body = body.unwrap_or_default();
+        let signature = self.generate_signature(
+            &timestamp,
+            method.as_str(),
+            request_path,
+            &body,
+            &credentials.secret_key,
+        )?;
+
+        let url = format!("{}{request_path}", credentials.host);
+        let mut request = state
+            .http_client
+            .request(method, url)
+            .header("Content-Type", "application/json")
+            .header("OK-ACCESS-KEY", &credentials.api_key)
+            .header("OK-ACCESS-SIGN", signature)
+            .header("OK-ACCESS-TIMESTAMP", timestamp)
+            .header("OK-ACCESS-PASSPHRASE", &credentials.passphrase);
+        if !body.is_empty() {
+            request = request.body(body);
+        }
+
+        let response = request
+            .send()
+            .await
+            .map_err(|e| ExchangeError::InternalError(e.to_string()))?;
+
+        debug!("OKX response: {:?}", response);
+        let status = response.status();
+        if !status.is_success() {
+            let error_body = response.text().await.unwrap_or_default();
+            let message =
+                format!("OKX API request failed with status: {status}, body: {error_body}");
+            debug!("OKX API request failed: {}", message);
+            return Err(ExchangeError::InternalError(message));
+        }
+
+        let parsed_response: OkxResponse<R> = response.json().await.map_err(|e| {
+            debug!("Unable to parse OKX response: {}", e);
+            ExchangeError::InternalError(format!("Failed to parse OKX response: {e}"))
+        })?;
+        debug!("Parsed response: {:?}", parsed_response);
+        if parsed_response.code != SUCCESS_CODE {
+            return Err(ExchangeError::InternalError(format!(
+                "OKX API request failed with code: {}, message: {}",
+                parsed_response.code,
+                parsed_response.msg.unwrap_or_default()
+            )));
+        }
+
+        Ok(parsed_response.data)
+    }
+
+    pub fn map_asset_to_okx_format(
+        &self,
+        asset: &Caip19Asset,
+    ) -> Result<(String, String), ExchangeError> {
+        let full_caip19 = asset.to_string();
+        let chain_id = asset.chain_id().to_string();
+
+        let crypto = CAIP19_TO_OKX_CRYPTO
+            .get(full_caip19.as_str())
+            .ok_or_else(|| {
+                ExchangeError::ValidationError(format!("Unsupported asset: {full_caip19}"))
+            })?
+            .to_string();
+
+        let network = CHAIN_ID_TO_OKX_NETWORK
+            .get(chain_id.as_str())
+            .ok_or_else(|| {
+                ExchangeError::ValidationError(format!("Unsupported chain ID: {chain_id}"))
+            })?;
+
+        let chain = format!("{crypto}-{network}");
+        Ok((crypto, chain))
+    }
+
+    pub async fn get_buy_url(
+        &self,
+        state: State<Arc<AppState>>,
+        params: GetBuyUrlParams,
+    ) -> Result<String, ExchangeError> {
+        let (crypto_ccy, chain) = self.map_asset_to_okx_format(&params.asset)?;
+
+        let request = CreateOrderRequest {
+            client_order_id: params.session_id,
+            crypto_ccy,
+            chain,
+            crypto_amount: params.amount.to_string(),
+            to_addr: params.recipient,
+            client_ip: Some(params.user_ip.to_string()),
+        };
+        let body = serde_json::to_string(&request).map_err(|e| {
+            ExchangeError::GetPayUrlError(format!("Failed to serialize request body: {e}"))
+        })?;
+
+        let data: CreateOrderResponseData = self
+            .send_request(&state, reqwest::Method::POST, CREATE_ORDER_PATH, Some(body))
+            .await
+            .map_err(|e| ExchangeError::GetPayUrlError(e.to_string()))?;
+        Ok(data.pay_url)
+    }
+
+    pub async fn get_buy_status(
+        &self,
+        state: State<Arc<AppState>>,
+        params: GetBuyStatusParams,
+    ) -> Result<GetBuyStatusResponse, ExchangeError> {
+        // The query string is part of the signed request path
+        let query = form_urlencoded::Serializer::new(String::new())
+            .append_pair("clientOrderId", &params.session_id)
+            .finish();
+        let request_path = format!("{QUERY_ORDER_PATH}?{query}");
+
+        let response: QueryOrderResponseData = self
+            .send_request(&state, reqwest::Method::GET, &request_path, None)
+            .await?;
+
+        debug!("get_buy_status response: {:?}", response);
+
+        let status = match response.state {
+            OkxOrderStatus::Success => {
+                if response.tx_id.as_ref().is_none_or(String::is_empty) {
+                    // The order is completed but the withdrawal
+                    // is not broadcasted yet
+                    BuyTransactionStatus::InProgress
+                } else {
+                    BuyTransactionStatus::Success
+                }
+            }
+            OkxOrderStatus::Init
+            | OkxOrderStatus::Pending
+            | OkxOrderStatus::Processing
+            | OkxOrderStatus::Withdrawing => BuyTransactionStatus::InProgress,
+            OkxOrderStatus::Failed | OkxOrderStatus::Cancelled | OkxOrderStatus::Expired => {
+                BuyTransactionStatus::Failed
+            }
+            OkxOrderStatus::Unknown => BuyTransactionStatus::Unknown,
+        };
+
+        Ok(GetBuyStatusResponse {
+            status,
+            tx_hash: response.tx_id,
+        })
+    }
+
+    pub async fn get_assets(
+        &self,
+        state: State<Arc<AppState>>,
+    ) -> Result<Vec<ExchangeAsset>, ExchangeError> {
+        let currencies: Vec<CurrencyResponseData> = self
+            .send_list_request(&state, reqwest::Method::GET, CURRENCIES_PATH, None)
+            .await?;
+
+        let assets = self
+            .supported_assets()
+            .into_iter()
+            .map(|asset| {
+                let limits = Caip19Asset::parse(&asset)
+                    .ok()
+                    .and_then(|caip19| self.map_asset_to_okx_format(&caip19).ok())
+                    .and_then(|(ccy, chain)| {
+                        currencies.iter().find(|c| c.ccy == ccy && c.chain == chain)
+                    });
+                match limits {
+                    Some(limits) => ExchangeAsset {
+                        asset,
+                        min_amount: limits.min_wd.clone(),
+                        max_amount: limits.max_wd.clone(),
+                        fee: limits.min_fee.clone(),
+                    },
+                    None => ExchangeAsset::without_limits(asset),
+                }
+            })
+            .collect();
+        Ok(assets)
+    }
+}
@@ -1,14 +1,13 @@
 use {
     super::{
-        binance::BinanceExchange,
-        coinbase::CoinbaseExchange,
         transactions::{mark_failed, mark_succeeded, touch_pending},
-        ExchangeType, GetBuyStatusParams,
+        ExchangeType, GetBuyStatusParams, GetBuyStatusResponse,
     },
     crate::{
-        database::exchange_reconciliation as db,
+        database::exchange_reconciliation::{self as db, ExchangeTransaction, TxDirection},
         handlers::json_rpc::exchanges::BuyTransactionStatus,
-        metrics::ExchangeReconciliationQueryType, state::AppState,
+        metrics::{ExchangeReconcilerResult, ExchangeReconciliationQueryType},
+        state::AppState,
     },
     axum::extract::State,
     std::{
@@ -17,177 +16,213 @@ use {
     },
     tokio::time::{interval, MissedTickBehavior},
     tracing::{debug, warn},
+    uuid::Uuid,
 };
 
-const POLL_INTERVAL: Duration = Duration::from_secs(600); // 10 minutes
+const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(600); // 10 minutes
 
 const CLAIM_BATCH_SIZE: i64 = 200;
-const EXPIRE_PENDING_AFTER_HOURS: i64 = 12;
+const DEAD_LETTER_AFTER_HOURS: i64 = 12;
+const MAX_CHECK_ATTEMPTS: i32 = 30;
+
+const LEADER_LOCK_KEY: &str = "exchange_reconciler/leader";
 
 pub async fn run(state: Arc<AppState>) {
-    debug!("starting");
-    let mut poll = interval(POLL_INTERVAL);
+    let poll_interval = state
+        .config
+        .exchanges
+        .reconciler_interval_secs
+        .map(Duration::from_secs)
+        .unwrap_or(DEFAULT_POLL_INTERVAL);
+    let instance_id = Uuid::new_v4().to_string();
+    debug!(?poll_interval, instance_id, "starting");
+
+    let mut poll = interval(poll_interval);
     poll.set_missed_tick_behavior(MissedTickBehavior::Delay);
     loop {
         poll.tick().await;
-        debug!("polling new batch");
-        let fetch_started = Instant::now();
-        let claim_start = Instant::now();
-        match db::claim_due_batch(&state.postgres, CLAIM_BATCH_SIZE).await {
-            Ok(mut rows) => {
-                state.metrics.add_exchange_reconciliation_query_latency(
-                    ExchangeReconciliationQueryType::ClaimDueBatch,
-                    claim_start,
-                );
-                state
-                    .metrics
-                    .add_exchange_reconciler_fetch_batch_latency(fetch_started);
-                debug!("fetched {} exchange transactions", rows.len());
-                if rows.is_empty() {
-                    continue;
-                }
+        let run_started = Instant::now();
+        let is_leader = is_leader(&state, &instance_id, poll_interval).await;
+        if is_leader {
+            reconcile(&state).await;
+        } else {
+            debug!("not the leader, skipping the run");
+        }
+        state
+            .metrics
+            .add_exchange_reconciler_run(is_leader, run_started);
+    }
+}
+
+/// Only the replica holding the lock runs the reconciliation. The lock
+/// outlives the poll interval, so the leader keeps it between the runs.
+/// Every replica is the leader when the lock storage is not configured.
+async fn is_leader(state: &Arc<AppState>, instance_id: &str, poll_interval: Duration) -> bool {
+    let Some(lock_storage) = &state.lock_storage else {
+        return true;
+    };
+    match lock_storage
+        .acquire_lock(LEADER_LOCK_KEY, instance_id, poll_interval * 2)
+        .await
+    {
+        Ok(acquired) => acquired,
+        Err(e) => {
+            warn!(error = %e, "failed to acquire the reconciler leader lock");
+            false
+        }
+    }
+}
+
+async fn reconcile(state: &Arc<AppState>) {
+    debug!("polling new batch");
+    let fetch_started = Instant::now();
+    let claim_start = Instant::now();
+    match db::claim_due_batch(&state.postgres, CLAIM_BATCH_SIZE).await {
+        Ok(rows) => {
+            state.metrics.add_exchange_reconciliation_query_latency(
+                ExchangeReconciliationQueryType::ClaimDueBatch,
+                claim_start,
+            );
+            state
+                .metrics
+                .add_exchange_reconciler_fetch_batch_latency(fetch_started);
+            debug!("fetched {} exchange transactions", rows.len());
+
+            if !rows.is_empty() {
                 debug!("processing {} exchange transactions", rows.len());
                 let mut rate = interval(Duration::from_millis(200));
                 rate.set_missed_tick_behavior(MissedTickBehavior::Delay);
 
                 let process_started = Instant::now();
-                for row in rows.drain(..) {
+                for row in rows {
                     rate.tick().await;
-
-                    let exchange_id = row.exchange_id.as_str();
-                    let internal_id = &row.session_id;
-
-                    let project_id = match row.project_id.as_ref() {
-                        Some(id) => id,
-                        None => {
-                            warn!(
-                                exchange_id,
-                                internal_id, "missing project_id for exchange transaction"
-                            );
-                            debug!(exchange_id, internal_id, "marking transaction as failed");
-                            if let Err(err) = mark_failed(
-                                &state,
-                                internal_id,
-                                exchange_id,
-                                Some("provider_failed"),
-                                None,
-                            )
-                            .await
-                            {
-                                warn!(exchange_id, internal_id, error = %err, "failed to mark failed");
-                            }
-                            continue;
-                        }
-                    };
-
-                    debug!(
-                        "processing exchange transaction {} on {}",
-                        internal_id, exchange_id
-                    );
-                    let res = match ExchangeType::from_id(exchange_id) {
-                        Some(ExchangeType::Coinbase) => {
-                            CoinbaseExchange
-                                .get_buy_status(
-                                    State(state.clone()),
-                                    GetBuyStatusParams {
-                                        project_id: project_id.to_owned(),
-                                        session_id: internal_id.to_owned(),
-                                    },
-                                )
-                                .await
-                        }
-                        Some(ExchangeType::Binance) => {
-                            BinanceExchange
-                                .get_buy_status(
-                                    State(state.clone()),
-                                    GetBuyStatusParams {
-                                        project_id: project_id.to_owned(),
-                                        session_id: internal_id.to_owned(),
-                                    },
-                                )
-                                .await
-                        }
-                        _ => {
-                            warn!(exchange_id, "unknown exchange id for reconciliation");
-                            debug!(exchange_id, internal_id, "marking transaction as failed");
-                            if let Err(err) = mark_failed(
-                                &state,
-                                internal_id,
-                                exchange_id,
-                                Some("provider_failed"),
-                                None,
-                            )
-                            .await
-                            {
-                                warn!(exchange_id, internal_id, error = %err, "failed to mark failed");
-                            }
-                            continue;
-                        }
-                    };
-
-                    match res {
-                        Ok(status) => match status.status {
-                            BuyTransactionStatus::Success => {
-                                debug!(
-                                    exchange_id,
-                                    internal_id, "marking transaction as succeeded"
-                                );
-                                if let Err(err) = mark_succeeded(
-                                    &state,
-                                    internal_id,
-                                    exchange_id,
-                                    status.tx_hash.as_deref(),
-                                )
-                                .await
-                                {
-                                    warn!(exchange_id, internal_id, error = %err, "failed to mark succeeded");
-                                }
-                            }
-                            BuyTransactionStatus::Failed => {
-                                debug!(exchange_id, internal_id, "marking transaction as failed");
-                                if let Err(err) = mark_failed(
-                                    &state,
-                                    internal_id,
-                                    exchange_id,
-                                    Some("provider_failed"),
-                                    status.tx_hash.as_deref(),
-                                )
-                                .await
-                                {
-                                    warn!(exchange_id, internal_id, error = %err, "failed to mark failed");
-                                }
-                            }
-                            _ => {
-                                if let Err(err) =
-                                    touch_pending(&state, exchange_id, internal_id).await
-                                {
-                                    warn!(exchange_id, internal_id, error = %err, "failed to touch pending");
-                                }
-                            }
-                        },
-                        Err(err) => {
-                            debug!(exchange_id, internal_id, error = %err, "reconciler provider check failed");
-                            if let Err(err) = touch_pending(&state, exchange_id, internal_id).await
-                            {
-                                warn!(exchange_id, internal_id, error = %err, "failed to touch pending after provider error");
-                            }
-                        }
-                    }
+                    let result = reconcile_transaction(state, &row).await;
+                    state.metrics.add_exchange_reconciler_result(result, 1);
                 }
-
                 state
                     .metrics
                     .add_exchange_reconciler_process_batch_latency(process_started);
-                let expire_start = Instant::now();
-                let _ = db::expire_old_pending(&state.postgres, EXPIRE_PENDING_AFTER_HOURS).await;
-                state.metrics.add_exchange_reconciliation_query_latency(
-                    ExchangeReconciliationQueryType::ExpireOldPending,
-                    expire_start,
-                );
             }
-            Err(e) => {
-                warn!(error = %e, "failed to claim exchange transactions");
+        }
+        Err(e) => {
+            warn!(error = %e, "failed to claim exchange transactions");
+        }
+    }
+
+    let dead_letter_start = Instant::now();
+    match db::dead_letter_unresolved(&state.postgres, DEAD_LETTER_AFTER_HOURS, MAX_CHECK_ATTEMPTS)
+        .await
+    {
+        Ok(count) => {
+            state.metrics.add_exchange_reconciliation_query_latency(
+                ExchangeReconciliationQueryType::DeadLetterUnresolved,
+                dead_letter_start,
+            );
+            if count > 0 {
+                debug!("moved {} exchange transactions to the dead-letter", count);
+                state
+                    .metrics
+                    .add_exchange_reconciler_result(ExchangeReconcilerResult::DeadLetter, count);
+            }
+        }
+        Err(e) => {
+            warn!(error = %e, "failed to dead-letter unresolved exchange transactions");
+        }
+    }
+}
+
+async fn reconcile_transaction(
+    state: &Arc<AppState>,
+    row: &ExchangeTransaction,
+) -> ExchangeReconcilerResult {
+    let exchange_id = row.exchange_id.as_str();
+    let internal_id = row.session_id.as_str();
+
+    let Some(project_id) = row.project_id.as_ref() else {
+        warn!(
+            exchange_id,
+            internal_id, "missing project_id for exchange transaction"
+        );
+        fail_transaction(state, exchange_id, internal_id, None).await;
+        return ExchangeReconcilerResult::Failed;
+    };
+
+    let exchange = match ExchangeType::from_id(exchange_id) {
+        Some(exchange) if exchange.is_transaction_storage_enabled() => exchange,
+        _ => {
+            warn!(exchange_id, "unknown exchange id for reconciliation");
+            fail_transaction(state, exchange_id, internal_id, None).await;
+            return ExchangeReconcilerResult::Failed;
+        }
+    };
+
+    debug!(
+        "processing exchange transaction {} on {}",
+        internal_id, exchange_id
+    );
+    let params = GetBuyStatusParams {
+        project_id: project_id.to_owned(),
+        session_id: internal_id.to_owned(),
+    };
+    let res = match row.direction {
+        TxDirection::Buy => exchange.get_buy_status(State(state.clone()), params).await,
+        TxDirection::Deposit => exchange
+            .get_deposit_status(State(state.clone()), params)
+            .await
+            .map(|status| GetBuyStatusResponse {
+                status: status.status,
+                tx_hash: status.tx_hash,
+            }),
+    };
+
+    match res {
+        Ok(status) => match status.status {
+            BuyTransactionStatus::Success => {
+                debug!(exchange_id, internal_id, "marking transaction as succeeded");
+                if let Err(err) =
+                    mark_succeeded(state, internal_id, exchange_id, status.tx_hash.as_deref()).await
+                {
+                    warn!(exchange_id, internal_id, error = %err, "failed to mark succeeded");
+                }
+                ExchangeReconcilerResult::Succeeded
+            }
+            BuyTransactionStatus::Failed => {
+                fail_transaction(state, exchange_id, internal_id, status.tx_hash.as_deref()).await;
+                ExchangeReconcilerResult::Failed
+            }
+            _ => {
+                if let Err(err) = touch_pending(state, exchange_id, internal_id).await {
+                    warn!(exchange_id, internal_id, error = %err, "failed to touch pending");
+                }
+                ExchangeReconcilerResult::Pending
+            }
+        },
+        Err(err) => {
+            debug!(exchange_id, internal_id, error = %err, "reconciler provider check failed");
+            if let Err(err) = touch_pending(state, exchange_id, internal_id).await {
+                warn!(exchange_id, internal_id, error = %err, "failed to touch pending after provider error");
             }
+            ExchangeReconcilerResult::ProviderError
         }
     }
 }
+
+async fn fail_transaction(
+    state: &Arc<AppState>,
+    exchange_id: &str,
+    internal_id: &str,
+    tx_hash: Option<&str>,
+) {
+    debug!(exchange_id, internal_id, "marking transaction as failed");
+    if let Err(err) = mark_failed(
+        state,
+        internal_id,
+        exchange_id,
+        Some("provider_failed"),
+        tx_hash,
+    )
+    .await
+    {
+        warn!(exchange_id, internal_id, error = %err, "failed to mark failed");
+    }
+}
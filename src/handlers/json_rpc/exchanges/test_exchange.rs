@@ -54,6 +54,13 @@ impl ExchangeProvider for TestExchange {
         CAIP_19_SUPPORTED_ASSETS.contains(asset)
     }
 
+    fn supported_assets(&self) -> Vec<String> {
+        CAIP_19_SUPPORTED_ASSETS
+            .iter()
+            .map(|asset| asset.to_string())
+            .collect()
+    }
+
     fn is_enabled(&self, _feature_type: &FeatureType, _project_features: &[Feature]) -> bool {
         true
     }
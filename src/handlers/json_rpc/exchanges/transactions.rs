@@ -4,7 +4,8 @@ use {
         database::{
             error::DatabaseError,
             exchange_reconciliation::{
-                self as exchange_transactions, NewExchangeTransaction, TxStatus,
+                self as exchange_transactions, ExchangeTransaction, NewExchangeTransaction,
+                TxStatus,
             },
         },
         handlers::json_rpc::exchanges::ExchangeType,
@@ -169,3 +170,30 @@ pub async fn touch_pending(
     db_tx.commit().await?;
     Ok(())
 }
+
+pub async fn get(
+    state: &Arc<AppState>,
+    session_id: &str,
+) -> Result<Option<ExchangeTransaction>, DatabaseError> {
+    let q_start = Instant::now();
+    let row = exchange_transactions::get_by_session_id(&state.postgres, session_id).await?;
+    state.metrics.add_exchange_reconciliation_query_latency(
+        ExchangeReconciliationQueryType::GetBySessionId,
+        q_start,
+    );
+    Ok(row)
+}
+
+pub async fn set_deposit_address(
+    state: &Arc<AppState>,
+    session_id: &str,
+    deposit_address: &str,
+) -> Result<(), DatabaseError> {
+    let q_start = Instant::now();
+    exchange_transactions::set_recipient(&state.postgres, session_id, deposit_address).await?;
+    state.metrics.add_exchange_reconciliation_query_latency(
+        ExchangeReconciliationQueryType::SetRecipient,
+        q_start,
+    );
+    Ok(())
+}
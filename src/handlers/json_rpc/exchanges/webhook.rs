@@ -0,0 +1,94 @@
+use {
+    crate::{
+        database::error::DatabaseError,
+        error::RpcError,
+        handlers::json_rpc::exchanges::{
+            transactions::{mark_failed, mark_succeeded, touch_pending},
+            BuyTransactionStatus, ExchangeError, ExchangeType,
+        },
+        state::AppState,
+    },
+    axum::{
+        body::Bytes,
+        extract::{Path, State},
+        http::StatusCode,
+        response::{IntoResponse, Response},
+    },
+    hyper::HeaderMap,
+    std::sync::Arc,
+    tracing::{debug, warn},
+    wc::metrics::{future_metrics, FutureExt},
+};
+
+/// Transaction status callbacks from the exchanges, updating the stored
+/// exchange transaction without waiting for the reconciler
+pub async fn handler(
+    state: State<Arc<AppState>>,
+    exchange_id: Path<String>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<Response, RpcError> {
+    handler_internal(state, exchange_id, headers, body)
+        .with_metrics(future_metrics!("handler_task", "name" => "exchanges_webhook"))
+        .await
+}
+
+#[tracing::instrument(skip(state, headers, body), level = "debug")]
+async fn handler_internal(
+    State(state): State<Arc<AppState>>,
+    Path(exchange_id): Path<String>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<Response, RpcError> {
+    let exchange = ExchangeType::from_id(&exchange_id)
+        .ok_or_else(|| RpcError::InvalidParameter(format!("Unknown exchange: {exchange_id}")))?;
+
+    let event = exchange
+        .parse_webhook(&state, &headers, &body)
+        .map_err(|e| match e {
+            ExchangeError::WebhookAuthenticationError(e) => RpcError::SignatureValidationError(e),
+            e => RpcError::InvalidParameter(e.to_string()),
+        })?;
+    debug!(
+        exchange_id,
+        session_id = event.session_id,
+        "Received exchange webhook"
+    );
+
+    let result = match event.status.status {
+        BuyTransactionStatus::Success => {
+            mark_succeeded(
+                &state,
+                &event.session_id,
+                &exchange_id,
+                event.status.tx_hash.as_deref(),
+            )
+            .await
+        }
+        BuyTransactionStatus::Failed => {
+            mark_failed(
+                &state,
+                &event.session_id,
+                &exchange_id,
+                Some("provider_failed"),
+                event.status.tx_hash.as_deref(),
+            )
+            .await
+        }
+        _ => touch_pending(&state, &exchange_id, &event.session_id).await,
+    };
+
+    match result {
+        Ok(()) => Ok(StatusCode::OK.into_response()),
+        // Acknowledging the events of unknown sessions to stop the retries
+        Err(DatabaseError::SqlxError(sqlx::Error::RowNotFound)) => {
+            warn!(
+                exchange_id,
+                session_id = event.session_id,
+                "Exchange webhook for unknown session"
+            );
+            Ok(StatusCode::OK.into_response())
+        }
+        Err(e) => Err(e.into()),
+    }
+}
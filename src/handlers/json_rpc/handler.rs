@@ -1,15 +1,21 @@
 use {
     super::{
         exchanges::{
+            build_exchange_deposit::{self, BuildExchangeDepositError},
+            get_exchange_assets::{self, GetExchangeAssetsError},
             get_exchange_buy_status::{self, GetExchangeBuyStatusError},
+            get_exchange_deposit_status::{self, GetExchangeDepositStatusError},
+            get_exchange_deposit_url::{self, GetExchangeDepositUrlError},
             get_exchange_url::{self, GetExchangeUrlError},
             get_exchanges::{self, GetExchangesError},
         },
         pos::{self, BuildPosTxsError, CheckPosTxError, SupportedNetworksError},
         wallet::{
+            build_approval::{self, BuildApprovalError},
             get_assets::{self, GetAssetsError},
             get_calls_status::QueryParams as CallStatusQueryParams,
             get_calls_status::{self, GetCallsStatusError},
+            get_capabilities,
             prepare_calls::{self, PrepareCallsError},
             send_prepared_calls::{self, SendPreparedCallsError},
         },
@@ -19,7 +25,7 @@ use {
         handlers::SdkInfoParams,
         json_rpc::{ErrorResponse, JsonRpcError, JsonRpcRequest, JsonRpcResponse, JsonRpcResult},
         state::AppState,
-        utils::{cors, cors::CORS_ALLOWED_ORIGINS, simple_request_json::SimpleRequestJson},
+        utils::{cors, simple_request_json::SimpleRequestJson},
     },
     axum::extract::{ConnectInfo, Query},
     axum::response::{IntoResponse, Response},
@@ -85,7 +91,13 @@ pub async fn json_rpc_with_dynamic_cors(
     // - For selected PAY_* methods: echo Origin only if it's allowed for the project
     // - For all other methods: allow all origins
     match method_name.as_ref() {
-        PAY_GET_EXCHANGES | PAY_GET_EXCHANGE_URL | PAY_GET_EXCHANGE_BUY_STATUS => {
+        PAY_GET_EXCHANGES
+        | PAY_GET_EXCHANGE_URL
+        | PAY_GET_EXCHANGE_BUY_STATUS
+        | PAY_GET_EXCHANGE_ASSETS
+        | PAY_GET_EXCHANGE_DEPOSIT_URL
+        | PAY_BUILD_EXCHANGE_DEPOSIT
+        | PAY_GET_EXCHANGE_DEPOSIT_STATUS => {
             if let Some(origin) = headers
                 .get(hyper::header::ORIGIN)
                 .and_then(|v| v.to_str().ok())
@@ -130,93 +142,7 @@ async fn is_origin_allowed_for_project(
     let Ok(project) = state.registry.project_data(project_id).await else {
         return false;
     };
-
-    let origin_lc = origin.to_ascii_lowercase();
-
-    // Allow default allowed origins by default
-    if CORS_ALLOWED_ORIGINS
-        .iter()
-        .any(|o| o.eq_ignore_ascii_case(&origin_lc))
-    {
-        return true;
-    }
-    // Parse origin URL details if possible
-    let parsed_origin = url::Url::parse(origin).ok();
-    let origin_host = parsed_origin
-        .as_ref()
-        .and_then(|u| u.host_str().map(|h| h.to_ascii_lowercase()));
-    let origin_scheme = parsed_origin
-        .as_ref()
-        .map(|u| u.scheme().to_ascii_lowercase());
-    let origin_effective_port: Option<u16> = {
-        fn default_port_for_scheme(s: &str) -> Option<u16> {
-            match s {
-                "http" => Some(80),
-                "https" => Some(443),
-                _ => None,
-            }
-        }
-        match (&parsed_origin, &origin_scheme) {
-            (Some(u), Some(s)) => u.port().or_else(|| default_port_for_scheme(s)),
-            _ => None,
-        }
-    };
-
-    // Single-pass matcher over allowed entries
-    let origin_allowed = project.data.allowed_origins.iter().any(|entry| {
-        let entry_lc = entry.trim().to_ascii_lowercase();
-
-        // Fast path: exact origin string match
-        if entry_lc == origin_lc {
-            return true;
-        }
-
-        // Full origin pattern with scheme
-        if let Some((scheme_pat, rest)) = entry_lc.split_once("://") {
-            // Scheme must match
-            if origin_scheme.as_deref() != Some(scheme_pat) {
-                return false;
-            }
-
-            // Extract host[:port] (ignore any path if present)
-            let host_port = rest.split('/').next().unwrap_or("");
-            if host_port.is_empty() {
-                return false;
-            }
-            let (host_pat, port_pat_opt) = host_port
-                .split_once(':')
-                .map(|(h, p)| (h, Some(p)))
-                .unwrap_or((host_port, None));
-
-            let Some(ref host_lc) = origin_host else {
-                return false;
-            };
-            if !cors::host_matches_pattern(host_pat, host_lc) {
-                return false;
-            }
-
-            // If port is specified in entry, it must match effective origin port
-            if let Some(port_s) = port_pat_opt {
-                if let Ok(port_num) = port_s.parse::<u16>() {
-                    return origin_effective_port.is_some_and(|p| p == port_num);
-                }
-                return false;
-            }
-            return true;
-        }
-
-        // Host-only entry (wildcard supported)
-        if let Some(ref host_lc) = origin_host {
-            return cors::host_matches_pattern(&entry_lc, host_lc);
-        }
-        false
-    });
-
-    if origin_allowed {
-        return true;
-    }
-
-    false
+    cors::is_origin_allowed(&project.data.allowed_origins, origin)
 }
 
 #[tracing::instrument(skip(state), level = "debug")]
@@ -228,7 +154,9 @@ async fn handler_internal(
     request: JsonRpcRequest,
 ) -> Response {
     let start = Instant::now();
-    let method = request.method.as_ref().to_string();
+    let method = known_method(&request.method)
+        .unwrap_or(UNKNOWN_METHOD)
+        .to_string();
 
     let result = handle_rpc(
         state.clone(),
@@ -285,12 +213,66 @@ async fn handler_internal(
 pub const WALLET_PREPARE_CALLS: &str = "wallet_prepareCalls";
 pub const WALLET_SEND_PREPARED_CALLS: &str = "wallet_sendPreparedCalls";
 pub const WALLET_GET_CALLS_STATUS: &str = "wallet_getCallsStatus";
+pub const WALLET_GET_CAPABILITIES: &str = "wallet_getCapabilities";
+pub const WALLET_BUILD_APPROVAL: &str = "wallet_buildApproval";
 pub const PAY_GET_EXCHANGES: &str = "reown_getExchanges";
 pub const PAY_GET_EXCHANGE_URL: &str = "reown_getExchangePayUrl";
 pub const PAY_GET_EXCHANGE_BUY_STATUS: &str = "reown_getExchangeBuyStatus";
+pub const PAY_GET_EXCHANGE_ASSETS: &str = "reown_getExchangeAssets";
+pub const PAY_GET_EXCHANGE_DEPOSIT_URL: &str = "reown_getExchangeDepositUrl";
+pub const PAY_BUILD_EXCHANGE_DEPOSIT: &str = "reown_buildExchangeDeposit";
+pub const PAY_GET_EXCHANGE_DEPOSIT_STATUS: &str = "reown_getExchangeDepositStatus";
 pub const POS_BUILD_TRANSACTIONS: &str = "wc_pos_buildTransactions";
 pub const POS_CHECK_TRANSACTION: &str = "wc_pos_checkTransaction";
 pub const POS_SUPPORTED_NETWORKS: &str = "wc_pos_supportedNetworks";
+pub const POS_ESTIMATE_FEES: &str = "wc_pos_estimateFees";
+
+/// Methods served by the handler
+const METHODS: &[&str] = &[
+    WALLET_PREPARE_CALLS,
+    WALLET_SEND_PREPARED_CALLS,
+    WALLET_GET_CALLS_STATUS,
+    WALLET_GET_CAPABILITIES,
+    WALLET_BUILD_APPROVAL,
+    wallet_service_api::WALLET_GET_ASSETS,
+    PAY_GET_EXCHANGES,
+    PAY_GET_EXCHANGE_URL,
+    PAY_GET_EXCHANGE_BUY_STATUS,
+    PAY_GET_EXCHANGE_ASSETS,
+    PAY_GET_EXCHANGE_DEPOSIT_URL,
+    PAY_BUILD_EXCHANGE_DEPOSIT,
+    PAY_GET_EXCHANGE_DEPOSIT_STATUS,
+    POS_BUILD_TRANSACTIONS,
+    POS_CHECK_TRANSACTION,
+    POS_SUPPORTED_NETWORKS,
+    POS_ESTIMATE_FEES,
+];
+
+/// Method label of the unknown methods, so the metrics and rate limiting keys
+/// are bounded
+const UNKNOWN_METHOD: &str = "unknown";
+
+fn known_method(method: &str) -> Option<&'static str> {
+    METHODS.iter().copied().find(|known| *known == method)
+}
+
+/// Rate limiting endpoint of the JSON-RPC request body, each known method is
+/// limited by its own token bucket, so the expensive methods are not sharing
+/// the bucket with the cheap ones. The unknown methods and the malformed
+/// requests are limited by the path bucket.
+pub fn rate_limit_endpoint(path: &str, body: &[u8]) -> String {
+    #[derive(Deserialize)]
+    struct MethodOnly {
+        method: String,
+    }
+    match serde_json::from_slice::<MethodOnly>(body)
+        .ok()
+        .and_then(|request| known_method(&request.method))
+    {
+        Some(method) => format!("{path}:{method}"),
+        None => path.to_owned(),
+    }
+}
 
 #[derive(Debug, Error)]
 enum Error {
@@ -306,6 +288,9 @@ enum Error {
     #[error("{WALLET_GET_CALLS_STATUS}: {0}")]
     GetCallsStatus(GetCallsStatusError),
 
+    #[error("{WALLET_BUILD_APPROVAL}: {0}")]
+    BuildApproval(BuildApprovalError),
+
     #[error("{PAY_GET_EXCHANGES}: {0}")]
     GetExchanges(GetExchangesError),
 
@@ -318,6 +303,18 @@ enum Error {
     #[error("{PAY_GET_EXCHANGE_BUY_STATUS}: {0}")]
     GetExchangeBuyStatus(GetExchangeBuyStatusError),
 
+    #[error("{PAY_GET_EXCHANGE_ASSETS}: {0}")]
+    GetExchangeAssets(GetExchangeAssetsError),
+
+    #[error("{PAY_GET_EXCHANGE_DEPOSIT_URL}: {0}")]
+    GetExchangeDepositUrl(GetExchangeDepositUrlError),
+
+    #[error("{PAY_BUILD_EXCHANGE_DEPOSIT}: {0}")]
+    BuildExchangeDeposit(BuildExchangeDepositError),
+
+    #[error("{PAY_GET_EXCHANGE_DEPOSIT_STATUS}: {0}")]
+    GetExchangeDepositStatus(GetExchangeDepositStatusError),
+
     #[error("{POS_BUILD_TRANSACTIONS}: {0}")]
     PosBuildTransactions(#[source] BuildPosTxsError),
 
@@ -327,6 +324,9 @@ enum Error {
     #[error("{POS_SUPPORTED_NETWORKS}: {0}")]
     PosSupportedNetworks(#[source] SupportedNetworksError),
 
+    #[error("{POS_ESTIMATE_FEES}: {0}")]
+    PosEstimateFees(#[source] BuildPosTxsError),
+
     #[error("Method not found")]
     MethodNotFound,
 
@@ -354,10 +354,16 @@ impl Error {
             Error::GetExchanges(_) => -6,
             Error::GetUrl(_) => -7,
             Error::GetExchangeBuyStatus(_) => -8,
+            Error::GetExchangeAssets(_) => -9,
+            Error::GetExchangeDepositUrl(_) => -10,
+            Error::BuildExchangeDeposit(_) => -11,
+            Error::GetExchangeDepositStatus(_) => -12,
+            Error::BuildApproval(_) => -13,
             // -18900 to -18999 reserved for POS
             Error::PosBuildTransactions(e) => e.to_json_rpc_error_code(),
             Error::PosCheckTransaction(e) => e.to_json_rpc_error_code(),
             Error::PosSupportedNetworks(e) => e.to_json_rpc_error_code(),
+            Error::PosEstimateFees(e) => e.to_json_rpc_error_code(),
             Error::MethodNotFound => -32601,
             Error::InvalidParams(_) => -32602,
             Error::Internal(_) => -32000,
@@ -371,12 +377,18 @@ impl Error {
             Error::SendPreparedCalls(e) => e.is_internal(),
             Error::GetCallsStatus(e) => e.is_internal(),
             Error::GetAssets(e) => e.is_internal(),
+            Error::BuildApproval(e) => e.is_internal(),
             Error::GetExchanges(e) => e.is_internal(),
             Error::GetUrl(e) => e.is_internal(),
             Error::GetExchangeBuyStatus(e) => e.is_internal(),
+            Error::GetExchangeAssets(e) => e.is_internal(),
+            Error::GetExchangeDepositUrl(e) => e.is_internal(),
+            Error::BuildExchangeDeposit(e) => e.is_internal(),
+            Error::GetExchangeDepositStatus(e) => e.is_internal(),
             Error::PosBuildTransactions(e) => e.is_internal(),
             Error::PosCheckTransaction(e) => e.is_internal(),
             Error::PosSupportedNetworks(e) => e.is_internal(),
+            Error::PosEstimateFees(e) => e.is_internal(),
             Error::MethodNotFound => false,
             Error::InvalidParams(_) => false,
             Error::Internal(_) => true,
@@ -436,6 +448,30 @@ async fn handle_rpc(
             .map_err(Error::GetCallsStatus)?,
         )
         .map_err(|e| Error::Internal(InternalError::SerializeResponse(e))),
+        WALLET_GET_CAPABILITIES => serde_json::to_value(
+            &get_capabilities::handler(
+                state,
+                project_id,
+                serde_json::from_value(params).map_err(Error::InvalidParams)?,
+            )
+            .await,
+        )
+        .map_err(|e| Error::Internal(InternalError::SerializeResponse(e))),
+        WALLET_BUILD_APPROVAL => serde_json::to_value(
+            &build_approval::handler(
+                state,
+                project_id,
+                serde_json::from_value(params).map_err(Error::InvalidParams)?,
+                connect_info,
+                headers,
+                Query(build_approval::QueryParams {
+                    sdk_info: query.sdk_info,
+                }),
+            )
+            .await
+            .map_err(Error::BuildApproval)?,
+        )
+        .map_err(|e| Error::Internal(InternalError::SerializeResponse(e))),
         wallet_service_api::WALLET_GET_ASSETS => serde_json::to_value(
             &get_assets::handler(
                 state,
@@ -499,6 +535,64 @@ async fn handle_rpc(
             .map_err(Error::GetExchangeBuyStatus)?,
         )
         .map_err(|e| Error::Internal(InternalError::SerializeResponse(e))),
+        PAY_GET_EXCHANGE_ASSETS => serde_json::to_value(
+            &get_exchange_assets::handler(
+                state,
+                project_id,
+                Query(get_exchange_assets::QueryParams {
+                    sdk_info: query.sdk_info,
+                    source: query.source,
+                }),
+                Json(serde_json::from_value(params).map_err(Error::InvalidParams)?),
+            )
+            .await
+            .map_err(Error::GetExchangeAssets)?,
+        )
+        .map_err(|e| Error::Internal(InternalError::SerializeResponse(e))),
+        PAY_GET_EXCHANGE_DEPOSIT_URL => serde_json::to_value(
+            &get_exchange_deposit_url::handler(
+                state,
+                project_id,
+                connect_info,
+                headers,
+                Query(get_exchange_deposit_url::QueryParams {
+                    sdk_info: query.sdk_info,
+                    source: query.source,
+                }),
+                Json(serde_json::from_value(params).map_err(Error::InvalidParams)?),
+            )
+            .await
+            .map_err(Error::GetExchangeDepositUrl)?,
+        )
+        .map_err(|e| Error::Internal(InternalError::SerializeResponse(e))),
+        PAY_BUILD_EXCHANGE_DEPOSIT => serde_json::to_value(
+            &build_exchange_deposit::handler(
+                state,
+                project_id,
+                Query(build_exchange_deposit::QueryParams {
+                    sdk_info: query.sdk_info,
+                    source: query.source,
+                }),
+                Json(serde_json::from_value(params).map_err(Error::InvalidParams)?),
+            )
+            .await
+            .map_err(Error::BuildExchangeDeposit)?,
+        )
+        .map_err(|e| Error::Internal(InternalError::SerializeResponse(e))),
+        PAY_GET_EXCHANGE_DEPOSIT_STATUS => serde_json::to_value(
+            &get_exchange_deposit_status::handler(
+                state,
+                project_id,
+                Query(get_exchange_deposit_status::QueryParams {
+                    sdk_info: query.sdk_info,
+                    source: query.source,
+                }),
+                Json(serde_json::from_value(params).map_err(Error::InvalidParams)?),
+            )
+            .await
+            .map_err(Error::GetExchangeDepositStatus)?,
+        )
+        .map_err(|e| Error::Internal(InternalError::SerializeResponse(e))),
         POS_BUILD_TRANSACTIONS => serde_json::to_value(
             &pos::build_transactions::handler(
                 state,
@@ -525,6 +619,40 @@ async fn handle_rpc(
                 .map_err(Error::PosSupportedNetworks)?,
         )
         .map_err(|e| Error::Internal(InternalError::SerializeResponse(e))),
+        POS_ESTIMATE_FEES => serde_json::to_value(
+            &pos::estimate_fees::handler(
+                state,
+                project_id,
+                serde_json::from_value(params).map_err(Error::InvalidParams)?,
+            )
+            .await
+            .map_err(Error::PosEstimateFees)?,
+        )
+        .map_err(|e| Error::Internal(InternalError::SerializeResponse(e))),
         _ => Err(Error::MethodNotFound),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rate_limit_endpoint_by_method() {
+        assert_eq!(
+            rate_limit_endpoint(
+                "/v1/wallet",
+                br#"{"jsonrpc":"2.0","id":1,"method":"wallet_prepareCalls","params":[]}"#
+            ),
+            "/v1/wallet:wallet_prepareCalls"
+        );
+        assert_eq!(
+            rate_limit_endpoint(
+                "/v1/wallet",
+                br#"{"jsonrpc":"2.0","id":1,"method":"random_method","params":[]}"#
+            ),
+            "/v1/wallet"
+        );
+        assert_eq!(rate_limit_endpoint("/v1/wallet", b"not json"), "/v1/wallet");
+    }
+}
@@ -0,0 +1,931 @@
+use {
+    super::{
+        AssetNamespaceType, BuildPosTxsError, CheckPosTxError, CheckTransactionResult,
+        ExecutionError, FeeEstimate, PaymentIntent, RpcError, SupportedNamespace,
+        TransactionBuilder, TransactionId, TransactionRpc, TransactionStatus, TransferredAmount,
+        ValidatedPaymentIntent, ValidationError,
+    },
+    crate::{
+        state::AppState,
+        utils::crypto::{Caip19Asset, Caip2ChainId},
+    },
+    alloy::primitives::{utils::parse_units, U256},
+    async_trait::async_trait,
+    axum::extract::State,
+    base64::{engine::general_purpose, Engine as _},
+    bech32::{hrp, segwit, Hrp},
+    serde::{de::DeserializeOwned, Deserialize},
+    std::{collections::HashMap, sync::Arc},
+    strum::{EnumIter, IntoEnumIterator},
+    strum_macros::{Display, EnumString},
+    tracing::debug,
+};
+
+const BITCOIN_SIGN_PSBT_METHOD: &str = "signPsbt";
+/// Esplora indexer serving the `/api` and `/testnet/api` paths of the networks
+const DEFAULT_ESPLORA_API_URL: &str = "https://blockstream.info";
+const DEFAULT_CHECK_IN: usize = 10_000;
+const NAMESPACE_NAME: &str = "bip122";
+
+const MAINNET_CHAIN_REFERENCE: &str = "000000000019d6689c085ae165831e93";
+const TESTNET_CHAIN_REFERENCE: &str = "000000000933ea01ad0ee984209779ba";
+
+/// Confirmation target in blocks for the fee estimation
+const FEE_ESTIMATION_TARGET_BLOCKS: u16 = 6;
+/// Fallback fee rate when the indexer can't estimate it
+const DEFAULT_FEE_RATE_SAT_VB: u64 = 2;
+const MIN_FEE_RATE_SAT_VB: u64 = 1;
+const DUST_LIMIT_SATS: u64 = 546;
+const BTC_DECIMALS: u8 = 8;
+const NATIVE_SLIP44: u32 = 0;
+
+/// Transaction virtual size estimations used for the fee calculation
+const TX_OVERHEAD_VBYTES: u64 = 11;
+/// Using the largest (P2TR/P2WSH) output size to cover any recipient type
+const OUTPUT_VBYTES: u64 = 43;
+const P2WPKH_INPUT_VBYTES: u64 = 68;
+const P2TR_INPUT_VBYTES: u64 = 58;
+
+const TX_VERSION: u32 = 2;
+/// Input sequence signaling the replace-by-fee
+const SEQUENCE_RBF: u32 = 0xffff_fffd;
+const PSBT_MAGIC: &[u8] = b"psbt\xff";
+const PSBT_GLOBAL_UNSIGNED_TX: u8 = 0x00;
+const PSBT_IN_WITNESS_UTXO: u8 = 0x01;
+const PSBT_SEPARATOR: u8 = 0x00;
+
+#[derive(Debug, Clone, PartialEq, EnumString, Display, EnumIter)]
+#[strum(serialize_all = "lowercase")]
+pub enum AssetNamespace {
+    Slip44,
+}
+
+impl AssetNamespaceType for AssetNamespace {
+    fn is_native(&self) -> bool {
+        matches!(self, AssetNamespace::Slip44)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Network {
+    Mainnet,
+    Testnet,
+}
+
+impl Network {
+    fn from_chain_id(chain_id: &Caip2ChainId) -> Result<Self, ValidationError> {
+        match chain_id.reference() {
+            MAINNET_CHAIN_REFERENCE => Ok(Network::Mainnet),
+            TESTNET_CHAIN_REFERENCE => Ok(Network::Testnet),
+            reference => Err(ValidationError::InvalidAsset(format!(
+                "Unsupported Bitcoin network: {reference}"
+            ))),
+        }
+    }
+
+    fn bech32_hrp(&self) -> Hrp {
+        match self {
+            Network::Mainnet => hrp::BC,
+            Network::Testnet => hrp::TB,
+        }
+    }
+
+    fn esplora_api_path(&self) -> &'static str {
+        match self {
+            Network::Mainnet => "/api",
+            Network::Testnet => "/testnet/api",
+        }
+    }
+
+    fn p2pkh_version(&self) -> u8 {
+        match self {
+            Network::Mainnet => 0x00,
+            Network::Testnet => 0x6f,
+        }
+    }
+
+    fn p2sh_version(&self) -> u8 {
+        match self {
+            Network::Mainnet => 0x05,
+            Network::Testnet => 0xc4,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum AddressType {
+    P2pkh,
+    P2sh,
+    P2wpkh,
+    P2wsh,
+    P2tr,
+}
+
+#[derive(Debug, Clone)]
+struct BitcoinAddress {
+    address_type: AddressType,
+    script_pubkey: Vec<u8>,
+}
+
+#[derive(Debug, Clone)]
+struct Utxo {
+    /// Transaction ID in the internal (reversed) byte order
+    txid: [u8; 32],
+    vout: u32,
+    value: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct EsploraUtxo {
+    txid: String,
+    vout: u32,
+    /// Amount in sats
+    value: u64,
+    status: EsploraTxStatus,
+}
+
+#[derive(Debug, Deserialize)]
+struct EsploraTxStatus {
+    confirmed: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct EsploraTransaction {
+    status: EsploraTxStatus,
+    #[serde(default)]
+    vout: Vec<EsploraTxOutput>,
+}
+
+#[derive(Debug, Deserialize)]
+struct EsploraTxOutput {
+    /// Output script in hex
+    scriptpubkey: String,
+    /// Amount in sats
+    value: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct SignPsbtResult {
+    txid: Option<String>,
+}
+
+fn get_esplora_url(state: &AppState, network: Network, path: &str) -> String {
+    let base_url = state
+        .config
+        .providers
+        .esplora_api_url
+        .as_deref()
+        .unwrap_or(DEFAULT_ESPLORA_API_URL)
+        .trim_end_matches('/');
+    format!("{base_url}{}{path}", network.esplora_api_path())
+}
+
+/// Call the Esplora indexer API, `None` when the resource is not found
+async fn call_esplora<T: DeserializeOwned>(
+    state: &State<Arc<AppState>>,
+    network: Network,
+    path: &str,
+) -> Result<Option<T>, RpcError> {
+    let url = get_esplora_url(state, network, path);
+    let response = state
+        .http_client
+        .get(&url)
+        .send()
+        .await
+        .map_err(|e| RpcError::Internal(format!("Failed to send request: {e}")))?;
+
+    let status = response.status();
+    if status == reqwest::StatusCode::NOT_FOUND {
+        return Ok(None);
+    }
+    if !status.is_success() {
+        let error_body = response
+            .text()
+            .await
+            .unwrap_or_else(|_| "Unable to read error body".to_string());
+        debug!("Bitcoin Esplora API {status} error: {error_body}, path: {path}");
+        return Err(RpcError::Internal(format!(
+            "HTTP {status} error: {error_body}"
+        )));
+    }
+
+    response.json().await.map(Some).map_err(|e| {
+        debug!("Failed to parse Bitcoin Esplora API response: {e} path: {path}");
+        RpcError::InvalidResponse(format!("Failed to parse response: {e}"))
+    })
+}
+
+/// Get the confirmed UTXOs of the address from the indexer, the outputs
+/// spent by the mempool transactions are excluded by the indexer
+async fn get_utxos(
+    state: &State<Arc<AppState>>,
+    network: Network,
+    address: &str,
+) -> Result<Vec<Utxo>, BuildPosTxsError> {
+    let utxos: Vec<EsploraUtxo> = call_esplora(state, network, &format!("/address/{address}/utxo"))
+        .await
+        .map_err(BuildPosTxsError::Rpc)?
+        .unwrap_or_default();
+
+    utxos
+        .into_iter()
+        .filter(|utxo| utxo.status.confirmed)
+        .map(|utxo| {
+            let mut txid: [u8; 32] = hex::decode(&utxo.txid)
+                .ok()
+                .and_then(|bytes| bytes.try_into().ok())
+                .ok_or_else(|| {
+                    BuildPosTxsError::Rpc(RpcError::InvalidResponse(format!(
+                        "Invalid UTXO txid: {}",
+                        utxo.txid
+                    )))
+                })?;
+            txid.reverse();
+            Ok(Utxo {
+                txid,
+                vout: utxo.vout,
+                value: utxo.value,
+            })
+        })
+        .collect()
+}
+
+async fn estimate_fee_rate(
+    state: &State<Arc<AppState>>,
+    network: Network,
+) -> Result<u64, BuildPosTxsError> {
+    // Fee rates in sat/vB by the confirmation target in blocks
+    let estimates: HashMap<String, f64> = call_esplora(state, network, "/fee-estimates")
+        .await
+        .map_err(BuildPosTxsError::Rpc)?
+        .unwrap_or_default();
+
+    let fee_rate = estimates
+        .get(&FEE_ESTIMATION_TARGET_BLOCKS.to_string())
+        .map(|fee_rate| fee_rate.ceil() as u64)
+        .unwrap_or(DEFAULT_FEE_RATE_SAT_VB);
+    Ok(fee_rate.max(MIN_FEE_RATE_SAT_VB))
+}
+
+async fn get_transaction(
+    state: &State<Arc<AppState>>,
+    network: Network,
+    txid: &str,
+) -> Result<Option<EsploraTransaction>, RpcError> {
+    call_esplora(state, network, &format!("/tx/{txid}")).await
+}
+
+/// Decode the Bitcoin address into its type and the output script
+fn parse_address(address: &str, network: Network) -> Result<BitcoinAddress, ValidationError> {
+    // The checksum variant and the witness program length of the version are
+    // validated by the segwit decoding
+    if let Ok((hrp, version, program)) = segwit::decode(address) {
+        if hrp != network.bech32_hrp() {
+            return Err(ValidationError::InvalidAddress(format!(
+                "Address {address} is not for the {network:?} network"
+            )));
+        }
+        let version = version.to_u8();
+        let address_type = match (version, program.len()) {
+            (0, 20) => AddressType::P2wpkh,
+            (0, 32) => AddressType::P2wsh,
+            (1, 32) => AddressType::P2tr,
+            _ => {
+                return Err(ValidationError::InvalidAddress(format!(
+                    "Unsupported witness program: {address}"
+                )))
+            }
+        };
+        // OP_0 for the witness v0 or OP_1 for the taproot
+        let version_opcode = if version == 0 { 0x00 } else { 0x50 + version };
+        let mut script_pubkey = vec![version_opcode, program.len() as u8];
+        script_pubkey.extend_from_slice(&program);
+        return Ok(BitcoinAddress {
+            address_type,
+            script_pubkey,
+        });
+    }
+
+    let bytes = bs58::decode(address)
+        .with_check(None)
+        .into_vec()
+        .map_err(|e| {
+            ValidationError::InvalidAddress(format!("Failed to decode Bitcoin address: {e}"))
+        })?;
+    if bytes.len() != 21 {
+        return Err(ValidationError::InvalidAddress(format!(
+            "Invalid Bitcoin address payload: {address}"
+        )));
+    }
+    let (version, hash) = (bytes[0], &bytes[1..]);
+    if version == network.p2pkh_version() {
+        // OP_DUP OP_HASH160 <hash> OP_EQUALVERIFY OP_CHECKSIG
+        let mut script_pubkey = vec![0x76, 0xa9, 0x14];
+        script_pubkey.extend_from_slice(hash);
+        script_pubkey.extend_from_slice(&[0x88, 0xac]);
+        Ok(BitcoinAddress {
+            address_type: AddressType::P2pkh,
+            script_pubkey,
+        })
+    } else if version == network.p2sh_version() {
+        // OP_HASH160 <hash> OP_EQUAL
+        let mut script_pubkey = vec![0xa9, 0x14];
+        script_pubkey.extend_from_slice(hash);
+        script_pubkey.push(0x87);
+        Ok(BitcoinAddress {
+            address_type: AddressType::P2sh,
+            script_pubkey,
+        })
+    } else {
+        Err(ValidationError::InvalidAddress(format!(
+            "Address {address} is not for the {network:?} network"
+        )))
+    }
+}
+
+#[derive(Debug)]
+struct CoinSelection {
+    inputs: Vec<Utxo>,
+    /// Change amount, zero if the change is dust
+    change: u64,
+    fee: u64,
+}
+
+/// Select UTXOs (largest first) to cover the amount and the fee
+fn select_utxos(
+    mut utxos: Vec<Utxo>,
+    amount: u64,
+    fee_rate: u64,
+    input_vbytes: u64,
+) -> Result<CoinSelection, BuildPosTxsError> {
+    utxos.sort_by(|a, b| b.value.cmp(&a.value));
+
+    // The amount and the fee rate are coming from the request and the
+    // provider, the overflow is rejected instead of wrapping around
+    let fee_and_total = |vbytes: u64| {
+        let fee = vbytes.checked_mul(fee_rate)?;
+        Some((fee, amount.checked_add(fee)?))
+    };
+    let overflow = || {
+        BuildPosTxsError::Validation(ValidationError::InvalidAmount(format!(
+            "Amount of {amount} sats with the fee rate of {fee_rate} sat/vB is too large"
+        )))
+    };
+
+    let mut selected = Vec::new();
+    let mut total: u64 = 0;
+    for utxo in utxos {
+        total = total.saturating_add(utxo.value);
+        selected.push(utxo);
+
+        let inputs_vbytes = selected.len() as u64 * input_vbytes;
+        let (fee_with_change, required) =
+            fee_and_total(TX_OVERHEAD_VBYTES + inputs_vbytes + 2 * OUTPUT_VBYTES)
+                .ok_or_else(overflow)?;
+        if total >= required {
+            let change = total - required;
+            if change >= DUST_LIMIT_SATS {
+                return Ok(CoinSelection {
+                    inputs: selected,
+                    change,
+                    fee: fee_with_change,
+                });
+            }
+        }
+        let (_, required) = fee_and_total(TX_OVERHEAD_VBYTES + inputs_vbytes + OUTPUT_VBYTES)
+            .ok_or_else(overflow)?;
+        if total >= required {
+            // The leftover below the dust limit is going to the fee
+            return Ok(CoinSelection {
+                inputs: selected,
+                change: 0,
+                fee: total - amount,
+            });
+        }
+    }
+
+    Err(BuildPosTxsError::Execution(
+        ExecutionError::InsufficientFunds(format!(
+            "Balance of {total} sats is not enough to pay {amount} sats and the fee"
+        )),
+    ))
+}
+
+fn write_compact_size(buf: &mut Vec<u8>, value: u64) {
+    match value {
+        0..=0xfc => buf.push(value as u8),
+        0xfd..=0xffff => {
+            buf.push(0xfd);
+            buf.extend_from_slice(&(value as u16).to_le_bytes());
+        }
+        0x10000..=0xffff_ffff => {
+            buf.push(0xfe);
+            buf.extend_from_slice(&(value as u32).to_le_bytes());
+        }
+        _ => {
+            buf.push(0xff);
+            buf.extend_from_slice(&value.to_le_bytes());
+        }
+    }
+}
+
+/// Serialize the unsigned transaction in the legacy (non-witness) format
+fn serialize_unsigned_tx(inputs: &[Utxo], outputs: &[(u64, Vec<u8>)]) -> Vec<u8> {
+    let mut tx = Vec::new();
+    tx.extend_from_slice(&TX_VERSION.to_le_bytes());
+    write_compact_size(&mut tx, inputs.len() as u64);
+    for input in inputs {
+        tx.extend_from_slice(&input.txid);
+        tx.extend_from_slice(&input.vout.to_le_bytes());
+        // Empty scriptSig
+        write_compact_size(&mut tx, 0);
+        tx.extend_from_slice(&SEQUENCE_RBF.to_le_bytes());
+    }
+    write_compact_size(&mut tx, outputs.len() as u64);
+    for (value, script_pubkey) in outputs {
+        tx.extend_from_slice(&value.to_le_bytes());
+        write_compact_size(&mut tx, script_pubkey.len() as u64);
+        tx.extend_from_slice(script_pubkey);
+    }
+    // Locktime
+    tx.extend_from_slice(&0u32.to_le_bytes());
+    tx
+}
+
+/// Build the BIP-174 PSBT for the unsigned transaction spending the
+/// segwit UTXOs of the `input_script_pubkey`
+fn build_psbt(inputs: &[Utxo], input_script_pubkey: &[u8], outputs: &[(u64, Vec<u8>)]) -> Vec<u8> {
+    let unsigned_tx = serialize_unsigned_tx(inputs, outputs);
+
+    let mut psbt = PSBT_MAGIC.to_vec();
+    write_compact_size(&mut psbt, 1);
+    psbt.push(PSBT_GLOBAL_UNSIGNED_TX);
+    write_compact_size(&mut psbt, unsigned_tx.len() as u64);
+    psbt.extend_from_slice(&unsigned_tx);
+    psbt.push(PSBT_SEPARATOR);
+
+    for input in inputs {
+        let mut witness_utxo = input.value.to_le_bytes().to_vec();
+        write_compact_size(&mut witness_utxo, input_script_pubkey.len() as u64);
+        witness_utxo.extend_from_slice(input_script_pubkey);
+
+        write_compact_size(&mut psbt, 1);
+        psbt.push(PSBT_IN_WITNESS_UTXO);
+        write_compact_size(&mut psbt, witness_utxo.len() as u64);
+        psbt.extend_from_slice(&witness_utxo);
+        psbt.push(PSBT_SEPARATOR);
+    }
+
+    for _ in outputs {
+        psbt.push(PSBT_SEPARATOR);
+    }
+    psbt
+}
+
+fn parse_btc_amount(amount: &str) -> Result<u64, BuildPosTxsError> {
+    let parsed_value: U256 = parse_units(amount, BTC_DECIMALS)
+        .map_err(|e| {
+            BuildPosTxsError::Validation(ValidationError::InvalidAmount(format!(
+                "Unable to parse amount with {BTC_DECIMALS} decimals: {e}"
+            )))
+        })?
+        .into();
+    u64::try_from(parsed_value).map_err(|_| {
+        BuildPosTxsError::Validation(ValidationError::InvalidAmount(format!(
+            "Amount is too large: {amount}"
+        )))
+    })
+}
+
+pub struct BitcoinTransactionBuilder;
+
+#[async_trait]
+impl TransactionBuilder<AssetNamespace> for BitcoinTransactionBuilder {
+    fn namespace(&self) -> &'static str {
+        NAMESPACE_NAME
+    }
+
+    async fn validate_and_build(
+        &self,
+        state: State<Arc<AppState>>,
+        project_id: String,
+        params: PaymentIntent,
+    ) -> Result<TransactionRpc, BuildPosTxsError> {
+        let validated_params = ValidatedPaymentIntent::validate_params(&params)?;
+        self.build(state, project_id, validated_params).await
+    }
+
+    async fn build(
+        &self,
+        state: State<Arc<AppState>>,
+        _project_id: String,
+        params: ValidatedPaymentIntent<AssetNamespace>,
+    ) -> Result<TransactionRpc, BuildPosTxsError> {
+        match params.namespace {
+            AssetNamespace::Slip44 => build_btc_transfer(state, params).await,
+        }
+    }
+}
+
+struct PreparedTransfer {
+    sender: BitcoinAddress,
+    recipient: BitcoinAddress,
+    amount: u64,
+    fee_rate: u64,
+    selection: CoinSelection,
+}
+
+/// Validate the payment intent addresses and amount and select the UTXOs
+async fn prepare_btc_transfer(
+    state: &State<Arc<AppState>>,
+    params: &ValidatedPaymentIntent<AssetNamespace>,
+) -> Result<PreparedTransfer, BuildPosTxsError> {
+    let chain_id = params.asset.chain_id();
+    let network = Network::from_chain_id(chain_id).map_err(BuildPosTxsError::Validation)?;
+
+    let recipient = parse_address(&params.recipient_address, network).map_err(|e| {
+        BuildPosTxsError::Validation(ValidationError::InvalidRecipient(e.to_string()))
+    })?;
+    let sender = parse_address(&params.sender_address, network)
+        .map_err(|e| BuildPosTxsError::Validation(ValidationError::InvalidSender(e.to_string())))?;
+    // Only segwit sender addresses are supported since the PSBT is
+    // using the witness UTXO for the inputs
+    let input_vbytes = match sender.address_type {
+        AddressType::P2wpkh => P2WPKH_INPUT_VBYTES,
+        AddressType::P2tr => P2TR_INPUT_VBYTES,
+        _ => {
+            return Err(BuildPosTxsError::Validation(
+                ValidationError::InvalidSender(
+                    "Only native segwit and taproot sender addresses are supported".to_string(),
+                ),
+            ))
+        }
+    };
+
+    let amount = parse_btc_amount(&params.amount)?;
+    if amount < DUST_LIMIT_SATS {
+        return Err(BuildPosTxsError::Validation(
+            ValidationError::InvalidAmount(format!(
+                "Amount must be at least {DUST_LIMIT_SATS} sats"
+            )),
+        ));
+    }
+
+    let utxos = get_utxos(state, network, &params.sender_address).await?;
+    let fee_rate = estimate_fee_rate(state, network).await?;
+    let selection = select_utxos(utxos, amount, fee_rate, input_vbytes)?;
+
+    debug!(
+        "bitcoin transaction amount: {amount}, fee rate: {fee_rate}, selection: {:?}",
+        selection
+    );
+
+    Ok(PreparedTransfer {
+        sender,
+        recipient,
+        amount,
+        fee_rate,
+        selection,
+    })
+}
+
+async fn build_btc_transfer(
+    state: State<Arc<AppState>>,
+    params: ValidatedPaymentIntent<AssetNamespace>,
+) -> Result<TransactionRpc, BuildPosTxsError> {
+    let chain_id = params.asset.chain_id();
+    let PreparedTransfer {
+        sender,
+        recipient,
+        amount,
+        selection: CoinSelection { inputs, change, .. },
+        ..
+    } = prepare_btc_transfer(&state, &params).await?;
+
+    let mut outputs = vec![(amount, recipient.script_pubkey)];
+    if change > 0 {
+        outputs.push((change, sender.script_pubkey.clone()));
+    }
+    let psbt = build_psbt(&inputs, &sender.script_pubkey, &outputs);
+
+    let sign_inputs = (0..inputs.len())
+        .map(|index| {
+            serde_json::json!({
+                "address": params.sender_address,
+                "index": index,
+            })
+        })
+        .collect::<Vec<_>>();
+
+    Ok(TransactionRpc {
+        id: TransactionId::new(chain_id).to_string(),
+        chain_id: chain_id.to_string(),
+        method: BITCOIN_SIGN_PSBT_METHOD.to_string(),
+        params: serde_json::json!({
+            "account": params.sender_address,
+            "psbt": general_purpose::STANDARD.encode(psbt),
+            "signInputs": sign_inputs,
+            "broadcast": true,
+        }),
+    })
+}
+
+pub async fn estimate_fees(
+    state: State<Arc<AppState>>,
+    _project_id: &str,
+    params: ValidatedPaymentIntent<AssetNamespace>,
+) -> Result<FeeEstimate, BuildPosTxsError> {
+    let PreparedTransfer {
+        fee_rate,
+        selection,
+        ..
+    } = prepare_btc_transfer(&state, &params).await?;
+
+    FeeEstimate::new(
+        params.asset.chain_id(),
+        NATIVE_SLIP44,
+        BTC_DECIMALS,
+        U256::from(selection.fee),
+        serde_json::json!({
+            "feeRate": fee_rate.to_string(),
+            "inputs": selection.inputs.len(),
+        }),
+    )
+}
+
+pub async fn check_transaction(
+    state: State<Arc<AppState>>,
+    _project_id: &str,
+    response: &str,
+    chain_id: &Caip2ChainId,
+) -> Result<CheckTransactionResult, CheckPosTxError> {
+    let sign_result: SignPsbtResult = serde_json::from_str(response).map_err(|e| {
+        CheckPosTxError::Validation(ValidationError::InvalidWalletResponse(format!(
+            "Invalid wallet response: {e}"
+        )))
+    })?;
+    let txid = sign_result
+        .txid
+        .filter(|txid| txid.len() == 64 && hex::decode(txid).is_ok())
+        .ok_or_else(|| {
+            CheckPosTxError::Validation(ValidationError::InvalidWalletResponse(
+                "Wallet response is missing the broadcasted transaction ID".to_string(),
+            ))
+        })?;
+
+    let network = Network::from_chain_id(chain_id).map_err(CheckPosTxError::Validation)?;
+    let transaction = get_transaction(&state, network, &txid)
+        .await
+        .map_err(CheckPosTxError::Rpc)?;
+
+    let confirmed = transaction.is_some_and(|transaction| transaction.status.confirmed);
+
+    if confirmed {
+        Ok(CheckTransactionResult {
+            status: TransactionStatus::Confirmed,
+            check_in: None,
+            txid: Some(txid),
+            expected_amount: None,
+            actual_amount: None,
+        })
+    } else {
+        Ok(CheckTransactionResult {
+            status: TransactionStatus::Pending,
+            check_in: Some(DEFAULT_CHECK_IN),
+            txid: Some(txid),
+            expected_amount: None,
+            actual_amount: None,
+        })
+    }
+}
+
+/// Get the amount transferred to the recipient by the transaction outputs
+pub async fn get_transferred_amount(
+    state: &State<Arc<AppState>>,
+    _project_id: &str,
+    txid: &str,
+    asset: &Caip19Asset,
+    recipient: &str,
+) -> Result<TransferredAmount, CheckPosTxError> {
+    let chain_id = asset.chain_id();
+    let network = Network::from_chain_id(chain_id).map_err(CheckPosTxError::Validation)?;
+    let recipient_script = hex::encode(
+        parse_address(recipient, network)
+            .map_err(CheckPosTxError::Validation)?
+            .script_pubkey,
+    );
+
+    let transaction = get_transaction(state, network, txid)
+        .await
+        .map_err(CheckPosTxError::Rpc)?
+        .ok_or_else(|| {
+            CheckPosTxError::Rpc(RpcError::InvalidResponse(format!(
+                "Transaction {txid} is not found"
+            )))
+        })?;
+
+    let amount = transaction
+        .vout
+        .iter()
+        .filter(|output| output.scriptpubkey.eq_ignore_ascii_case(&recipient_script))
+        .map(|output| output.value)
+        .sum::<u64>();
+
+    Ok(TransferredAmount {
+        amount: U256::from(amount),
+        decimals: BTC_DECIMALS,
+    })
+}
+
+pub fn get_namespace_info() -> SupportedNamespace {
+    SupportedNamespace {
+        name: NAMESPACE_NAME.to_string(),
+        methods: vec![BITCOIN_SIGN_PSBT_METHOD.to_string()],
+        events: vec![],
+        capabilities: None,
+        asset_namespaces: AssetNamespace::iter()
+            .map(|x| x.to_string().to_ascii_lowercase())
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_addresses() {
+        let p2wpkh = parse_address(
+            "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4",
+            Network::Mainnet,
+        )
+        .unwrap();
+        assert_eq!(p2wpkh.address_type, AddressType::P2wpkh);
+        assert_eq!(
+            hex::encode(p2wpkh.script_pubkey),
+            "0014751e76e8199196d454941c45d1b3a323f1433bd6"
+        );
+
+        let p2tr = parse_address(
+            "bc1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vqzk5jj0",
+            Network::Mainnet,
+        )
+        .unwrap();
+        assert_eq!(p2tr.address_type, AddressType::P2tr);
+        assert_eq!(&p2tr.script_pubkey[..2], &[0x51, 0x20]);
+
+        let p2pkh = parse_address("1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa", Network::Mainnet).unwrap();
+        assert_eq!(p2pkh.address_type, AddressType::P2pkh);
+        assert_eq!(
+            hex::encode(p2pkh.script_pubkey),
+            "76a91462e907b15cbf27d5425399ebf6f0fb50ebb88f1888ac"
+        );
+
+        // Testnet address on the mainnet
+        assert!(parse_address(
+            "tb1qw508d6qejxtdg4y5r3zarvary0c5xw7kxpjzsx",
+            Network::Mainnet
+        )
+        .is_err());
+        // Broken checksum
+        assert!(parse_address(
+            "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t5",
+            Network::Mainnet
+        )
+        .is_err());
+    }
+
+    fn utxo(value: u64) -> Utxo {
+        Utxo {
+            txid: [0x11; 32],
+            vout: 1,
+            value,
+        }
+    }
+
+    #[test]
+    fn selects_utxos_with_change() {
+        let selection = select_utxos(
+            vec![utxo(50_000), utxo(100_000)],
+            60_000,
+            1,
+            P2WPKH_INPUT_VBYTES,
+        )
+        .unwrap();
+        assert_eq!(selection.inputs.len(), 1);
+        assert_eq!(selection.inputs[0].value, 100_000);
+        // (11 + 68 + 2 * 43) vB at 1 sat/vB
+        assert_eq!(selection.fee, 165);
+        assert_eq!(selection.change, 39_835);
+
+        let selection = select_utxos(
+            vec![utxo(30_000), utxo(40_000)],
+            60_000,
+            2,
+            P2WPKH_INPUT_VBYTES,
+        )
+        .unwrap();
+        assert_eq!(selection.inputs.len(), 2);
+        // (11 + 2 * 68 + 2 * 43) vB at 2 sat/vB
+        assert_eq!(selection.fee, 466);
+        assert_eq!(selection.change, 9_534);
+    }
+
+    #[test]
+    fn selects_utxos_without_dust_change() {
+        // The change of 335 sats is dust, the leftover is going to the fee
+        let selection = select_utxos(vec![utxo(60_500)], 60_000, 1, P2WPKH_INPUT_VBYTES).unwrap();
+        assert_eq!(selection.inputs.len(), 1);
+        assert_eq!(selection.change, 0);
+        assert_eq!(selection.fee, 500);
+    }
+
+    #[test]
+    fn rejects_insufficient_funds() {
+        let result = select_utxos(
+            vec![utxo(30_000), utxo(30_000)],
+            60_000,
+            1,
+            P2WPKH_INPUT_VBYTES,
+        );
+        assert!(matches!(
+            result,
+            Err(BuildPosTxsError::Execution(
+                ExecutionError::InsufficientFunds(_)
+            ))
+        ));
+        assert!(matches!(
+            select_utxos(vec![], 60_000, 1, P2WPKH_INPUT_VBYTES),
+            Err(BuildPosTxsError::Execution(
+                ExecutionError::InsufficientFunds(_)
+            ))
+        ));
+    }
+
+    #[test]
+    fn rejects_overflowing_amount() {
+        assert!(matches!(
+            select_utxos(vec![utxo(u64::MAX)], u64::MAX - 10, 1, P2WPKH_INPUT_VBYTES),
+            Err(BuildPosTxsError::Validation(
+                ValidationError::InvalidAmount(_)
+            ))
+        ));
+        assert!(matches!(
+            select_utxos(vec![utxo(100_000)], 60_000, u64::MAX, P2WPKH_INPUT_VBYTES),
+            Err(BuildPosTxsError::Validation(
+                ValidationError::InvalidAmount(_)
+            ))
+        ));
+    }
+
+    #[test]
+    fn serializes_unsigned_tx_and_psbt() {
+        let script_pubkey = hex::decode("0014751e76e8199196d454941c45d1b3a323f1433bd6").unwrap();
+        let inputs = [utxo(100_000)];
+        let outputs = [(60_000, script_pubkey.clone())];
+
+        let unsigned_tx = concat!(
+            // Version
+            "02000000",
+            // Inputs
+            "01",
+            "1111111111111111111111111111111111111111111111111111111111111111",
+            "01000000",
+            "00",
+            "fdffffff",
+            // Outputs
+            "01",
+            "60ea000000000000",
+            "16",
+            "0014751e76e8199196d454941c45d1b3a323f1433bd6",
+            // Locktime
+            "00000000",
+        );
+        assert_eq!(
+            hex::encode(serialize_unsigned_tx(&inputs, &outputs)),
+            unsigned_tx
+        );
+
+        let psbt = [
+            // Magic and the global unsigned transaction
+            "70736274ff",
+            "010052",
+            unsigned_tx,
+            "00",
+            // Input witness UTXO
+            "0101",
+            "1f",
+            "a086010000000000",
+            "16",
+            "0014751e76e8199196d454941c45d1b3a323f1433bd6",
+            "00",
+            // Output
+            "00",
+        ]
+        .concat();
+        assert_eq!(
+            hex::encode(build_psbt(&inputs, &script_pubkey, &outputs)),
+            psbt
+        );
+    }
+}
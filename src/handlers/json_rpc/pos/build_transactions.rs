@@ -1,5 +1,6 @@
 use {
     super::{
+        quote::{lock_quote, quote_asset_amount, PosQuote, QUOTE_LOCK_TTL},
         BuildPosTxsError, BuildTransactionParams, BuildTransactionResult, PaymentIntent,
         SupportedNamespaces, TransactionBuilder, TransactionRpc, ValidationError,
     },
@@ -7,29 +8,76 @@ use {
         analytics::pos_info::{
             PosBuildTxInfo, PosBuildTxNew, PosBuildTxRequest, PosBuildTxResponse,
         },
+        database::pos_payment_intents::{insert_payment_intent, NewPosPaymentIntent},
         handlers::json_rpc::pos::{
-            evm::EvmTransactionBuilder, solana::SolanaTransactionBuilder,
-            tron::TronTransactionBuilder,
+            bitcoin::BitcoinTransactionBuilder, evm::EvmTransactionBuilder,
+            solana::SolanaTransactionBuilder, tron::TronTransactionBuilder,
         },
         state::AppState,
         utils::crypto::Caip19Asset,
     },
     axum::extract::State,
+    chrono::Utc,
     futures_util::future::try_join_all,
     std::{str::FromStr, sync::Arc},
 };
 
+/// Build the transaction for the intent, the priced intent amount is quoted
+/// and the quote is locked for the built transaction.
+/// Returns the intent with the quoted amount along with the transaction.
 async fn build_transaction_for_intent(
     state: State<Arc<AppState>>,
     project_id: String,
     intent: PaymentIntent,
-) -> Result<TransactionRpc, BuildPosTxsError> {
+) -> Result<(PaymentIntent, TransactionRpc, Option<PosQuote>), BuildPosTxsError> {
     let asset = Caip19Asset::parse(&intent.asset)
         .map_err(|e| BuildPosTxsError::Validation(ValidationError::InvalidAsset(e.to_string())))?;
 
     let namespace = SupportedNamespaces::from_str(asset.chain_id().namespace())
         .map_err(|e| BuildPosTxsError::Validation(ValidationError::InvalidAsset(e.to_string())))?;
 
+    let (intent, asset_price) = match &intent.price {
+        Some(price) => {
+            let quote = quote_asset_amount(&state, &asset, price).await?;
+            (
+                PaymentIntent {
+                    amount: quote.amount,
+                    ..intent
+                },
+                Some(quote.asset_price),
+            )
+        }
+        None => (intent, None),
+    };
+
+    let transaction =
+        build_transaction(state.clone(), project_id, namespace, intent.clone()).await?;
+
+    let quote = match (&intent.price, asset_price) {
+        (Some(price), Some(asset_price)) => {
+            let quote = PosQuote {
+                transaction_id: transaction.id.clone(),
+                asset: intent.asset.clone(),
+                amount: intent.amount.clone(),
+                price: price.clone(),
+                asset_price,
+                expires_at: Utc::now().timestamp() + QUOTE_LOCK_TTL.as_secs() as i64,
+            };
+            lock_quote(&state, &quote).await?;
+            Some(quote)
+        }
+        _ => None,
+    };
+
+    Ok((intent, transaction, quote))
+}
+
+pub async fn build_transaction(
+    state: State<Arc<AppState>>,
+    project_id: String,
+    namespace: SupportedNamespaces,
+    intent: PaymentIntent,
+) -> Result<TransactionRpc, BuildPosTxsError> {
     match namespace {
         SupportedNamespaces::Eip155 => {
             let builder = EvmTransactionBuilder;
@@ -43,6 +91,10 @@ async fn build_transaction_for_intent(
             let builder = TronTransactionBuilder;
             builder.validate_and_build(state, project_id, intent).await
         }
+        SupportedNamespaces::Bip122 => {
+            let builder = BitcoinTransactionBuilder;
+            builder.validate_and_build(state, project_id, intent).await
+        }
     }
 }
 
@@ -64,18 +116,49 @@ pub async fn handler(
             "<serde_error>".to_string()
         })
     });
-    let intents = params.payment_intents.clone();
-
+    let params_len = params.payment_intents.len();
     let futures = params.payment_intents.into_iter().map(|intent| {
         let state = state.clone();
         let project_id = project_id.clone();
         async move { build_transaction_for_intent(state, project_id, intent).await }
     });
 
-    let transactions = try_join_all(futures).await?;
-    let response = BuildTransactionResult { transactions };
+    let mut intents = Vec::with_capacity(params_len);
+    let mut transactions = Vec::with_capacity(params_len);
+    let mut quotes = Vec::new();
+    for (intent, transaction, quote) in try_join_all(futures).await? {
+        intents.push(intent);
+        transactions.push(transaction);
+        quotes.extend(quote);
+    }
+    let response = BuildTransactionResult {
+        transactions,
+        quotes,
+    };
 
     for (intent, tx) in intents.iter().zip(response.transactions.iter()) {
+        // Persisting the intent is best-effort and only used to reconcile
+        // the transferred amount when checking the transaction
+        if let Err(e) = insert_payment_intent(
+            &state.postgres,
+            NewPosPaymentIntent {
+                transaction_id: &tx.id,
+                project_id: &project_id,
+                asset: &intent.asset,
+                amount: &intent.amount,
+                recipient: &intent.recipient,
+                sender: &intent.sender,
+            },
+        )
+        .await
+        {
+            tracing::warn!(
+                ?e,
+                tx_id = tx.id,
+                "Failed to persist the pos payment intent"
+            );
+        }
+
         let tx_params_string = serde_json::to_string(&tx.params).unwrap_or_else(|e| {
             tracing::warn!(
                 ?e,
@@ -1,21 +1,196 @@
 use {
     super::{
-        CheckPosTxError, CheckTransactionParams, CheckTransactionResult, SupportedNamespaces,
-        TransactionId, ValidationError,
+        CheckPosTxError, CheckTransactionParams, CheckTransactionResult, InternalError,
+        SupportedNamespaces, TransactionId, TransactionStatus, ValidationError,
     },
     crate::{
         analytics::pos_info::PosCheckTxInfo,
+        database::pos_payment_intents::{
+            get_merchant_webhook_url, get_payment_intent, mark_payment_intent_confirmed,
+            PosPaymentIntent,
+        },
+        error::RpcError,
+        handlers::json_rpc::pos::quote::{get_locked_quote, quote_asset_amount, PosQuote},
         handlers::json_rpc::pos::{
-            evm::check_transaction as evm_check_transaction,
-            solana::check_transaction as solana_check_transaction,
-            tron::check_transaction as tron_check_transaction,
+            bitcoin::{
+                check_transaction as bitcoin_check_transaction,
+                get_transferred_amount as bitcoin_get_transferred_amount,
+            },
+            evm::{
+                check_transaction as evm_check_transaction,
+                get_transferred_amount as evm_get_transferred_amount,
+            },
+            solana::{
+                check_transaction as solana_check_transaction,
+                get_transferred_amount as solana_get_transferred_amount,
+            },
+            tron::{
+                check_transaction as tron_check_transaction,
+                get_transferred_amount as tron_get_transferred_amount,
+            },
         },
         state::AppState,
+        utils::crypto::{disassemble_caip10_with_namespace, Caip19Asset},
+        webhooks,
+    },
+    alloy::primitives::{
+        utils::{format_units, parse_units},
+        U256,
     },
     axum::extract::State,
-    std::{str::FromStr, sync::Arc},
+    serde::Serialize,
+    std::{cmp::Ordering, str::FromStr, sync::Arc},
 };
 
+/// Webhook event type of the merchant notification of the confirmed payment
+const PAYMENT_CONFIRMED_EVENT: &str = "pos.payment_confirmed";
+
+/// Merchant notification data of the confirmed payment
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PaymentConfirmedEvent<'a> {
+    transaction_id: &'a str,
+    txid: &'a str,
+    asset: &'a str,
+    recipient: &'a str,
+    sender: &'a str,
+    expected_amount: Option<&'a str>,
+    actual_amount: Option<&'a str>,
+}
+
+/// Compare the on-chain transferred amount with the payment intent persisted
+/// when building the transaction and update the status on the mismatch,
+/// returns the reconciled payment intent
+async fn reconcile_transferred_amount(
+    state: &State<Arc<AppState>>,
+    project_id: &str,
+    transaction_id: &TransactionId,
+    namespace: &SupportedNamespaces,
+    txid: &str,
+    result: &mut CheckTransactionResult,
+) -> Result<Option<PosPaymentIntent>, CheckPosTxError> {
+    let intent = get_payment_intent(&state.postgres, &transaction_id.to_string())
+        .await
+        .map_err(|e| {
+            CheckPosTxError::Internal(InternalError::Internal(format!(
+                "Failed to get the payment intent: {e}"
+            )))
+        })?;
+    let Some(intent) = intent.filter(|intent| intent.project_id == project_id) else {
+        return Ok(None);
+    };
+
+    let asset = Caip19Asset::parse(&intent.asset)
+        .map_err(|e| CheckPosTxError::Validation(ValidationError::InvalidAsset(e.to_string())))?;
+    let (_, _, recipient) = disassemble_caip10_with_namespace::<SupportedNamespaces>(
+        &intent.recipient,
+    )
+    .map_err(|e| CheckPosTxError::Validation(ValidationError::InvalidRecipient(e.to_string())))?;
+
+    let transferred = match namespace {
+        SupportedNamespaces::Eip155 => {
+            evm_get_transferred_amount(project_id, txid, &asset, &recipient).await
+        }
+        SupportedNamespaces::Solana => {
+            solana_get_transferred_amount(project_id, txid, &asset, &recipient).await
+        }
+        SupportedNamespaces::Tron => {
+            tron_get_transferred_amount(state, project_id, txid, &asset, &recipient).await
+        }
+        SupportedNamespaces::Bip122 => {
+            bitcoin_get_transferred_amount(state, project_id, txid, &asset, &recipient).await
+        }
+    }?;
+
+    // The locked quote amount is honored until the quote expires,
+    // the late payments are evaluated against the current asset price
+    let mut expected_amount = intent.amount;
+    let locked_quote = get_locked_quote(state, &intent.transaction_id)
+        .await
+        .map_err(|e| {
+            CheckPosTxError::Internal(InternalError::Internal(format!(
+                "Failed to get the locked quote: {e}"
+            )))
+        })?;
+    if let Some(quote) = locked_quote.filter(PosQuote::is_expired) {
+        expected_amount = quote_asset_amount(state, &asset, &quote.price)
+            .await
+            .map_err(|e| {
+                CheckPosTxError::Internal(InternalError::Internal(format!(
+                    "Failed to reprice the expired quote: {e}"
+                )))
+            })?
+            .amount;
+    }
+
+    let expected: U256 = parse_units(&expected_amount, transferred.decimals)
+        .map_err(|e| {
+            CheckPosTxError::Validation(ValidationError::InvalidAmount(format!(
+                "Unable to parse the payment intent amount: {e}"
+            )))
+        })?
+        .into();
+    let format = |amount: U256| {
+        format_units(amount, transferred.decimals).map_err(|e| {
+            CheckPosTxError::Internal(InternalError::Internal(format!(
+                "Failed to format the transferred amount: {e}"
+            )))
+        })
+    };
+
+    result.status = match transferred.amount.cmp(&expected) {
+        Ordering::Less => TransactionStatus::Underpaid,
+        Ordering::Greater => TransactionStatus::Overpaid,
+        Ordering::Equal => TransactionStatus::Confirmed,
+    };
+    result.expected_amount = Some(format(expected)?);
+    result.actual_amount = Some(format(transferred.amount)?);
+
+    Ok(Some(intent))
+}
+
+/// Mark the payment intent as confirmed and enqueue the notification to the
+/// merchant callback URL of the project in the same database transaction, so
+/// the merchant is notified only once per intent
+async fn notify_payment_confirmed(
+    state: &AppState,
+    intent: &PosPaymentIntent,
+    txid: &str,
+    result: &CheckTransactionResult,
+) -> Result<(), RpcError> {
+    let mut db_tx = state.postgres.begin().await?;
+    if !mark_payment_intent_confirmed(&mut *db_tx, &intent.transaction_id).await? {
+        return Ok(());
+    }
+
+    // Deliveries are only dispatched when the webhooks signing secret is set
+    let url = match state.config.server.webhook_signing_secret {
+        Some(_) => get_merchant_webhook_url(&mut *db_tx, &intent.project_id).await?,
+        None => None,
+    };
+    if let Some(url) = url {
+        let data = serde_json::to_value(PaymentConfirmedEvent {
+            transaction_id: &intent.transaction_id,
+            txid,
+            asset: &intent.asset,
+            recipient: &intent.recipient,
+            sender: &intent.sender,
+            expected_amount: result.expected_amount.as_deref(),
+            actual_amount: result.actual_amount.as_deref(),
+        })?;
+        webhooks::enqueue(
+            &mut *db_tx,
+            &intent.project_id,
+            PAYMENT_CONFIRMED_EVENT,
+            &url,
+            &data,
+        )
+        .await?;
+    }
+    db_tx.commit().await?;
+    Ok(())
+}
+
 pub async fn handler(
     state: State<Arc<AppState>>,
     project_id: String,
@@ -31,7 +206,7 @@ pub async fn handler(
             CheckPosTxError::Validation(ValidationError::InvalidTransactionId(e.to_string()))
         })?;
 
-    let result = match namespace {
+    let mut result = match namespace {
         SupportedNamespaces::Eip155 => {
             evm_check_transaction(
                 state.clone(),
@@ -59,8 +234,42 @@ pub async fn handler(
             )
             .await
         }
+        SupportedNamespaces::Bip122 => {
+            bitcoin_check_transaction(
+                state.clone(),
+                &project_id,
+                &send_result,
+                transaction_id.chain_id(),
+            )
+            .await
+        }
     }?;
 
+    if let (TransactionStatus::Confirmed, Some(txid)) = (&result.status, result.txid.clone()) {
+        // The amount reconciliation is best-effort, the transaction is reported
+        // as confirmed when the intent is unknown or the amount can't be fetched
+        match reconcile_transferred_amount(
+            &state,
+            &project_id,
+            &transaction_id,
+            &namespace,
+            &txid,
+            &mut result,
+        )
+        .await
+        {
+            Ok(Some(intent)) if matches!(result.status, TransactionStatus::Confirmed) => {
+                if let Err(e) = notify_payment_confirmed(&state, &intent, &txid, &result).await {
+                    tracing::warn!(?e, txid, "Failed to notify the merchant of the pos payment");
+                }
+            }
+            Ok(_) => {}
+            Err(e) => {
+                tracing::warn!(?e, txid, "Failed to reconcile the pos transferred amount");
+            }
+        }
+    }
+
     let check_in = result.check_in;
     let txid = result.txid.clone();
 
@@ -111,12 +111,15 @@ impl ValidationError {
 pub enum ExecutionError {
     #[error("Unable to estimate gas: {0}")]
     GasEstimation(String),
+    #[error("Insufficient funds: {0}")]
+    InsufficientFunds(String),
 }
 
 impl ExecutionError {
     pub fn to_json_rpc_error_code(&self) -> i32 {
         match self {
             ExecutionError::GasEstimation(_) => -18920,
+            ExecutionError::InsufficientFunds(_) => -18921,
         }
     }
 }
@@ -0,0 +1,87 @@
+use {
+    super::{
+        bitcoin, evm, quote::quote_asset_amount, solana, tron, BuildPosTxsError,
+        EstimateFeesParams, EstimateFeesResult, FeeEstimate, PaymentIntent, SupportedNamespaces,
+        ValidatedPaymentIntent, ValidationError,
+    },
+    crate::{state::AppState, utils::crypto::Caip19Asset},
+    axum::extract::State,
+    futures_util::future::try_join_all,
+    std::{str::FromStr, sync::Arc},
+};
+
+async fn estimate_fee_for_intent(
+    state: State<Arc<AppState>>,
+    project_id: String,
+    intent: PaymentIntent,
+) -> Result<FeeEstimate, BuildPosTxsError> {
+    let asset = Caip19Asset::parse(&intent.asset)
+        .map_err(|e| BuildPosTxsError::Validation(ValidationError::InvalidAsset(e.to_string())))?;
+
+    let namespace = SupportedNamespaces::from_str(asset.chain_id().namespace())
+        .map_err(|e| BuildPosTxsError::Validation(ValidationError::InvalidAsset(e.to_string())))?;
+
+    // Fees of the priced intents are estimated for the currently quoted amount
+    let intent = match &intent.price {
+        Some(price) => PaymentIntent {
+            amount: quote_asset_amount(&state, &asset, price).await?.amount,
+            ..intent
+        },
+        None => intent,
+    };
+
+    match namespace {
+        SupportedNamespaces::Eip155 => {
+            evm::estimate_fees(
+                &project_id,
+                ValidatedPaymentIntent::validate_params(&intent)?,
+            )
+            .await
+        }
+        SupportedNamespaces::Solana => {
+            solana::estimate_fees(
+                &project_id,
+                ValidatedPaymentIntent::validate_params(&intent)?,
+            )
+            .await
+        }
+        SupportedNamespaces::Tron => {
+            tron::estimate_fees(
+                state,
+                &project_id,
+                ValidatedPaymentIntent::validate_params(&intent)?,
+            )
+            .await
+        }
+        SupportedNamespaces::Bip122 => {
+            bitcoin::estimate_fees(
+                state,
+                &project_id,
+                ValidatedPaymentIntent::validate_params(&intent)?,
+            )
+            .await
+        }
+    }
+}
+
+#[tracing::instrument(skip(state), level = "debug")]
+pub async fn handler(
+    state: State<Arc<AppState>>,
+    project_id: String,
+    params: EstimateFeesParams,
+) -> Result<EstimateFeesResult, BuildPosTxsError> {
+    if params.payment_intents.is_empty() {
+        return Err(BuildPosTxsError::Validation(
+            ValidationError::InvalidRequest("No payment intents found".to_string()),
+        ));
+    }
+
+    let futures = params.payment_intents.into_iter().map(|intent| {
+        let state = state.clone();
+        let project_id = project_id.clone();
+        async move { estimate_fee_for_intent(state, project_id, intent).await }
+    });
+
+    let fees = try_join_all(futures).await?;
+    Ok(EstimateFeesResult { fees })
+}
@@ -1,10 +1,15 @@
 use {
     super::{
-        AssetNamespaceType, BuildPosTxsError, CheckPosTxError, CheckTransactionResult,
-        ExecutionError, InternalError, PaymentIntent, SupportedNamespace, TransactionBuilder,
-        TransactionId, TransactionRpc, TransactionStatus, ValidatedPaymentIntent, ValidationError,
+        sum_token_transfers, AssetNamespaceType, BuildPosTxsError, CheckPosTxError,
+        CheckTransactionResult, ExecutionError, FeeEstimate, InternalError, PaymentIntent,
+        ReceiptLog, SupportedNamespace, TransactionBuilder, TransactionId, TransactionRpc,
+        TransactionStatus, TransferredAmount, ValidatedPaymentIntent, ValidationError,
+    },
+    crate::{
+        analytics::MessageSource,
+        state::AppState,
+        utils::crypto::{get_gas_estimate, Caip19Asset, Caip2ChainId},
     },
-    crate::{analytics::MessageSource, state::AppState, utils::crypto::Caip2ChainId},
     alloy::{
         primitives::{utils::parse_units, Address, TxHash, U256},
         providers::{Provider, ProviderBuilder},
@@ -13,7 +18,8 @@ use {
     },
     async_trait::async_trait,
     axum::extract::State,
-    std::sync::Arc,
+    serde::Deserialize,
+    std::{str::FromStr, sync::Arc},
     strum::{EnumIter, IntoEnumIterator},
     strum_macros::{Display, EnumString},
     tracing::debug,
@@ -24,6 +30,8 @@ const ETH_SEND_TRANSACTION_METHOD: &str = "eth_sendTransaction";
 const BASE_URL: &str = "https://rpc.walletconnect.org/v1";
 const DEFAULT_CHECK_IN: usize = 1000;
 const NAMESPACE_NAME: &str = "eip155";
+const NATIVE_SLIP44: u32 = 60;
+const NATIVE_DECIMALS: u8 = 18;
 
 sol! {
     #[sol(rpc)]
@@ -48,6 +56,17 @@ impl AssetNamespaceType for AssetNamespace {
 
 pub struct EvmTransactionBuilder;
 
+#[derive(Debug, Deserialize)]
+struct RawTransaction {
+    to: Option<Address>,
+    value: U256,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawTransactionReceipt {
+    logs: Vec<ReceiptLog>,
+}
+
 #[derive(Debug)]
 struct EvmTxBuilder {
     to: Address,
@@ -199,6 +218,71 @@ impl TransactionBuilder<AssetNamespace> for EvmTransactionBuilder {
     }
 }
 
+pub async fn estimate_fees(
+    project_id: &str,
+    params: ValidatedPaymentIntent<AssetNamespace>,
+) -> Result<FeeEstimate, BuildPosTxsError> {
+    let chain_id = params.asset.chain_id();
+    let to = params.recipient_address.parse::<Address>().map_err(|e| {
+        BuildPosTxsError::Validation(ValidationError::InvalidRecipient(e.to_string()))
+    })?;
+    let from = params
+        .sender_address
+        .parse::<Address>()
+        .map_err(|e| BuildPosTxsError::Validation(ValidationError::InvalidSender(e.to_string())))?;
+    let provider = get_provider(chain_id, project_id).map_err(BuildPosTxsError::Internal)?;
+
+    let gas_limit = match params.namespace {
+        AssetNamespace::Slip44 => {
+            parse_ether_amount(&params.amount)?;
+            NATIVE_GAS_LIMIT
+        }
+        AssetNamespace::Erc20 => {
+            let token_address = params
+                .asset
+                .asset_reference()
+                .parse::<Address>()
+                .map_err(|e| {
+                    BuildPosTxsError::Validation(ValidationError::InvalidAsset(e.to_string()))
+                })?;
+            let token_amount =
+                get_erc20_transfer_amount(&provider, token_address, &params.amount).await?;
+            let transfer_calldata =
+                create_erc20_transfer_calldata(token_address, &provider, to, token_amount).await?;
+            get_gas_estimate(
+                chain_id.reference(),
+                from,
+                token_address,
+                U256::ZERO,
+                transfer_calldata.into_input().unwrap_or_default(),
+                &provider,
+            )
+            .await
+            .map_err(|e| {
+                BuildPosTxsError::Execution(ExecutionError::GasEstimation(e.to_string()))
+            })?
+        }
+    };
+
+    let fees = provider
+        .estimate_eip1559_fees(None)
+        .await
+        .map_err(|e| BuildPosTxsError::Execution(ExecutionError::GasEstimation(e.to_string())))?;
+    let fee = U256::from(gas_limit) * U256::from(fees.max_fee_per_gas);
+
+    FeeEstimate::new(
+        chain_id,
+        NATIVE_SLIP44,
+        NATIVE_DECIMALS,
+        fee,
+        serde_json::json!({
+            "gasLimit": gas_limit.to_string(),
+            "maxFeePerGas": fees.max_fee_per_gas.to_string(),
+            "maxPriorityFeePerGas": fees.max_priority_fee_per_gas.to_string(),
+        }),
+    )
+}
+
 fn parse_ether_amount(amount: &str) -> Result<U256, BuildPosTxsError> {
     let value = parse_units(amount, "ether").map_err(|e| {
         BuildPosTxsError::Validation(ValidationError::InvalidAmount(format!(
@@ -309,20 +393,105 @@ pub async fn check_transaction(
             status,
             check_in: Some(DEFAULT_CHECK_IN),
             txid: Some(txid.to_string()),
+            expected_amount: None,
+            actual_amount: None,
         }),
-        TransactionStatus::Confirmed => Ok(CheckTransactionResult {
+        TransactionStatus::Confirmed
+        | TransactionStatus::Underpaid
+        | TransactionStatus::Overpaid => Ok(CheckTransactionResult {
             status,
             check_in: None,
             txid: Some(txid.to_string()),
+            expected_amount: None,
+            actual_amount: None,
         }),
         TransactionStatus::Failed => Ok(CheckTransactionResult {
             status,
             check_in: None,
             txid: None,
+            expected_amount: None,
+            actual_amount: None,
         }),
     }
 }
 
+/// Get the amount of the asset transferred to the recipient by the transaction
+pub async fn get_transferred_amount(
+    project_id: &str,
+    txid: &str,
+    asset: &Caip19Asset,
+    recipient: &str,
+) -> Result<TransferredAmount, CheckPosTxError> {
+    let provider = get_provider(asset.chain_id(), project_id).map_err(CheckPosTxError::Internal)?;
+    let txhash = txid.parse::<TxHash>().map_err(|e| {
+        CheckPosTxError::Validation(ValidationError::InvalidWalletResponse(format!(
+            "Invalid transaction hash: {e}"
+        )))
+    })?;
+    let recipient = recipient
+        .parse::<Address>()
+        .map_err(|e| CheckPosTxError::Validation(ValidationError::InvalidAddress(e.to_string())))?;
+    let namespace = AssetNamespace::from_str(asset.asset_namespace())
+        .map_err(|e| CheckPosTxError::Validation(ValidationError::InvalidAsset(e.to_string())))?;
+
+    match namespace {
+        AssetNamespace::Slip44 => {
+            let transaction = provider
+                .raw_request::<_, Option<RawTransaction>>(
+                    "eth_getTransactionByHash".into(),
+                    (txhash,),
+                )
+                .await
+                .map_err(|e| CheckPosTxError::Internal(InternalError::RpcError(e.to_string())))?
+                .ok_or_else(|| {
+                    CheckPosTxError::Internal(InternalError::RpcError(format!(
+                        "Transaction {txid} is not found"
+                    )))
+                })?;
+            let amount = if transaction.to == Some(recipient) {
+                transaction.value
+            } else {
+                U256::ZERO
+            };
+            Ok(TransferredAmount {
+                amount,
+                decimals: NATIVE_DECIMALS,
+            })
+        }
+        AssetNamespace::Erc20 => {
+            let token_address = asset.asset_reference().parse::<Address>().map_err(|e| {
+                CheckPosTxError::Validation(ValidationError::InvalidAsset(e.to_string()))
+            })?;
+            let receipt = provider
+                .raw_request::<_, Option<RawTransactionReceipt>>(
+                    "eth_getTransactionReceipt".into(),
+                    (txhash,),
+                )
+                .await
+                .map_err(|e| CheckPosTxError::Internal(InternalError::RpcError(e.to_string())))?
+                .ok_or_else(|| {
+                    CheckPosTxError::Internal(InternalError::RpcError(format!(
+                        "Transaction {txid} receipt is not found"
+                    )))
+                })?;
+            let decimals = ERC20Token::new(token_address, &provider)
+                .decimals()
+                .call()
+                .await
+                .map_err(|e| CheckPosTxError::Internal(InternalError::RpcError(e.to_string())))?
+                ._0;
+            Ok(TransferredAmount {
+                amount: sum_token_transfers(
+                    &receipt.logs,
+                    &token_address.to_string(),
+                    &recipient.to_string(),
+                ),
+                decimals,
+            })
+        }
+    }
+}
+
 pub fn get_namespace_info() -> SupportedNamespace {
     SupportedNamespace {
         name: NAMESPACE_NAME.to_string(),
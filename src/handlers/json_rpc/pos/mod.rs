@@ -1,7 +1,10 @@
+pub mod bitcoin;
 pub mod build_transactions;
 pub mod check_transaction;
 pub mod errors;
+pub mod estimate_fees;
 pub mod evm;
+pub mod quote;
 pub mod solana;
 pub mod supported_networks;
 pub mod tron;
@@ -10,6 +13,7 @@ pub use errors::{
     BuildPosTxsError, CheckPosTxError, ExecutionError, InternalError, RpcError,
     SupportedNetworksError, TransactionIdError, ValidationError,
 };
+pub use quote::{IntentPrice, PosQuote};
 
 use {
     crate::{
@@ -19,6 +23,7 @@ use {
             CaipNamespaces, NamespaceValidator,
         },
     },
+    alloy::primitives::{utils::format_units, U256},
     axum::extract::State,
     base64::{engine::general_purpose, Engine as _},
     serde::{Deserialize, Serialize},
@@ -30,6 +35,9 @@ use {
 
 const TRANSACTION_ID_DELIMITER: &str = "|";
 const TRANSACTION_ID_VERSION: &str = "v1";
+/// keccak256("Transfer(address,address,uint256)")
+const ERC20_TRANSFER_EVENT_TOPIC: &str =
+    "0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef";
 
 #[derive(Debug, Clone, PartialEq, EnumString, Deserialize, Serialize)]
 #[strum(serialize_all = "lowercase")]
@@ -37,6 +45,7 @@ pub enum SupportedNamespaces {
     Eip155,
     Solana,
     Tron,
+    Bip122,
 }
 
 impl NamespaceValidator for SupportedNamespaces {
@@ -45,6 +54,7 @@ impl NamespaceValidator for SupportedNamespaces {
             SupportedNamespaces::Eip155 => is_address_valid(address, &CaipNamespaces::Eip155),
             SupportedNamespaces::Solana => is_address_valid(address, &CaipNamespaces::Solana),
             SupportedNamespaces::Tron => true,
+            SupportedNamespaces::Bip122 => true,
         }
     }
 }
@@ -79,15 +89,71 @@ pub struct BuildTransactionParams {
 #[serde(rename_all = "camelCase")]
 pub struct PaymentIntent {
     pub asset: String,
+    /// Amount in the asset units, quoted by the server when the `price` is set
+    #[serde(default)]
     pub amount: String,
     pub recipient: String,
     pub sender: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub price: Option<IntentPrice>,
 }
 
 #[derive(Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct BuildTransactionResult {
     pub transactions: Vec<TransactionRpc>,
+    /// Quotes locked for the transactions of the priced payment intents
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub quotes: Vec<PosQuote>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EstimateFeesParams {
+    pub payment_intents: Vec<PaymentIntent>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EstimateFeesResult {
+    pub fees: Vec<FeeEstimate>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FeeEstimate {
+    pub chain_id: String,
+    /// CAIP-19 native asset the fee is paid in
+    pub fee_asset: String,
+    /// Estimated fee in the smallest units of the fee asset
+    pub amount: String,
+    /// Estimated fee in the fee asset units
+    pub formatted_amount: String,
+    /// Namespace specific fee components
+    pub details: Value,
+}
+
+impl FeeEstimate {
+    pub fn new(
+        chain_id: &Caip2ChainId,
+        native_slip44: u32,
+        decimals: u8,
+        amount: U256,
+        details: Value,
+    ) -> Result<Self, BuildPosTxsError> {
+        let formatted_amount = format_units(amount, decimals).map_err(|e| {
+            BuildPosTxsError::Internal(InternalError::Internal(format!(
+                "Failed to format the fee amount: {e}"
+            )))
+        })?;
+        Ok(Self {
+            chain_id: chain_id.to_string(),
+            fee_asset: format!("{chain_id}/slip44:{native_slip44}"),
+            amount: amount.to_string(),
+            formatted_amount,
+            details,
+        })
+    }
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -106,6 +172,10 @@ pub enum TransactionStatus {
     Pending,
     Confirmed,
     Failed,
+    /// Confirmed with the transferred amount less than the payment intent amount
+    Underpaid,
+    /// Confirmed with the transferred amount greater than the payment intent amount
+    Overpaid,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -123,6 +193,51 @@ pub struct CheckTransactionResult {
     pub check_in: Option<usize>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub txid: Option<String>,
+    /// The payment intent amount in the asset units
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expected_amount: Option<String>,
+    /// The transferred to the recipient amount in the asset units
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub actual_amount: Option<String>,
+}
+
+/// Amount transferred to the payment recipient by the confirmed transaction
+#[derive(Debug, Clone)]
+pub struct TransferredAmount {
+    /// Amount in the smallest units of the asset
+    pub amount: U256,
+    pub decimals: u8,
+}
+
+/// EVM compatible transaction receipt log
+#[derive(Debug, Clone, Deserialize)]
+pub struct ReceiptLog {
+    pub address: String,
+    pub topics: Vec<String>,
+    pub data: String,
+}
+
+/// Normalize the hex address or the 32 bytes topic to the lowercase 20 bytes
+/// address without the prefix
+fn normalize_hex_address(value: &str) -> String {
+    let value = value.trim_start_matches("0x").to_ascii_lowercase();
+    value[value.len().saturating_sub(40)..].to_string()
+}
+
+/// Sum the amounts of the ERC20 compatible `Transfer` events of the token to
+/// the recipient
+pub fn sum_token_transfers(logs: &[ReceiptLog], token: &str, recipient: &str) -> U256 {
+    let token = normalize_hex_address(token);
+    let recipient = normalize_hex_address(recipient);
+    logs.iter()
+        .filter(|log| normalize_hex_address(&log.address) == token)
+        .filter(|log| {
+            log.topics.len() == 3
+                && log.topics[0].eq_ignore_ascii_case(ERC20_TRANSFER_EVENT_TOPIC)
+                && normalize_hex_address(&log.topics[2]) == recipient
+        })
+        .filter_map(|log| U256::from_str_radix(log.data.trim_start_matches("0x"), 16).ok())
+        .fold(U256::ZERO, |total, amount| total.saturating_add(amount))
 }
 #[async_trait::async_trait]
 pub trait TransactionBuilder<T: AssetNamespaceType> {
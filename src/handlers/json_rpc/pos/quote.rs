@@ -0,0 +1,220 @@
+use {
+    super::{BuildPosTxsError, InternalError, SupportedNamespaces, ValidationError},
+    crate::{
+        handlers::{chain_agnostic::assets::NATIVE_TOKEN_ADDRESS, SupportedCurrencies},
+        state::AppState,
+        storage::error::StorageError,
+        utils::crypto::{
+            Caip19Asset, CaipNamespaces, NATIVE_ASSET_ADDRESS, SOLANA_NATIVE_TOKEN_ADDRESS,
+        },
+    },
+    chrono::Utc,
+    serde::{Deserialize, Serialize},
+    std::{str::FromStr, time::Duration},
+};
+
+/// Time the quoted asset amount is honored after the transaction is built
+pub const QUOTE_LOCK_TTL: Duration = Duration::from_secs(60 * 15); // 15 minutes
+/// Time the expired quote is kept to reprice the late payments
+const QUOTE_RETENTION: Duration = Duration::from_secs(60 * 60 * 24); // 1 day
+/// Maximum number of fractional digits of the quoted asset amount
+const QUOTE_AMOUNT_MAX_PRECISION: u8 = 8;
+
+/// Price of the payment intent in the fiat currency,
+/// the asset amount is quoted by the server
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct IntentPrice {
+    pub amount: String,
+    pub currency: SupportedCurrencies,
+}
+
+/// Asset amount quoted for the intent price
+#[derive(Debug, Clone)]
+pub struct AssetQuote {
+    /// Amount in the asset units
+    pub amount: String,
+    /// Asset price in the intent price currency
+    pub asset_price: f64,
+}
+
+/// Quote locked for the built transaction
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PosQuote {
+    pub transaction_id: String,
+    /// CAIP-19 asset ID
+    pub asset: String,
+    /// Locked amount in the asset units
+    pub amount: String,
+    pub price: IntentPrice,
+    /// Asset price in the intent price currency captured at the build time
+    pub asset_price: f64,
+    /// Unix timestamp in seconds until the locked amount is honored
+    pub expires_at: i64,
+}
+
+impl PosQuote {
+    pub fn is_expired(&self) -> bool {
+        self.expires_at < Utc::now().timestamp()
+    }
+}
+
+fn quote_key(transaction_id: &str) -> String {
+    format!("pos_quote/{transaction_id}")
+}
+
+/// Get the asset price and decimals from the fungible price provider
+async fn get_asset_price(
+    state: &AppState,
+    asset: &Caip19Asset,
+    currency: &SupportedCurrencies,
+) -> Result<(f64, u8), BuildPosTxsError> {
+    let namespace = SupportedNamespaces::from_str(asset.chain_id().namespace())
+        .map_err(|e| BuildPosTxsError::Validation(ValidationError::InvalidAsset(e.to_string())))?;
+    let is_native = asset.asset_namespace() == "slip44";
+    let (caip_namespace, address) = match namespace {
+        SupportedNamespaces::Eip155 if is_native => {
+            (CaipNamespaces::Eip155, NATIVE_TOKEN_ADDRESS.to_string())
+        }
+        SupportedNamespaces::Eip155 => {
+            (CaipNamespaces::Eip155, asset.asset_reference().to_string())
+        }
+        SupportedNamespaces::Solana if is_native => (
+            CaipNamespaces::Solana,
+            SOLANA_NATIVE_TOKEN_ADDRESS.to_string(),
+        ),
+        SupportedNamespaces::Solana => {
+            (CaipNamespaces::Solana, asset.asset_reference().to_string())
+        }
+        SupportedNamespaces::Bip122 if is_native => {
+            (CaipNamespaces::Bip122, NATIVE_ASSET_ADDRESS.to_string())
+        }
+        SupportedNamespaces::Tron | SupportedNamespaces::Bip122 => {
+            return Err(BuildPosTxsError::Validation(ValidationError::InvalidAsset(
+                format!("Price quotes are not supported for the asset: {asset}"),
+            )))
+        }
+    };
+
+    let providers = state.providers();
+    let provider = providers
+        .fungible_price_providers
+        .get(&caip_namespace)
+        .ok_or_else(|| {
+            BuildPosTxsError::Internal(InternalError::Internal(format!(
+                "No price provider for the namespace: {caip_namespace}"
+            )))
+        })?;
+    let response = provider
+        .get_price(
+            asset.chain_id().reference(),
+            &address,
+            currency,
+            &providers.token_metadata_cache,
+            state.metrics.clone(),
+        )
+        .await
+        .map_err(|e| {
+            BuildPosTxsError::Internal(InternalError::RpcError(format!(
+                "Failed to get the asset price: {e}"
+            )))
+        })?;
+
+    let fungible = response
+        .fungibles
+        .first()
+        .filter(|fungible| fungible.price.is_finite() && fungible.price > 0.0)
+        .ok_or_else(|| {
+            BuildPosTxsError::Validation(ValidationError::InvalidAsset(format!(
+                "No price available for the asset: {asset}"
+            )))
+        })?;
+
+    Ok((fungible.price, fungible.decimals))
+}
+
+/// Convert the price amount to the asset amount rounded up to the precision,
+/// so the rounding is never in favor of the payer
+fn quoted_amount(
+    price_amount: &str,
+    asset_price: f64,
+    decimals: u8,
+) -> Result<String, BuildPosTxsError> {
+    let price_amount = price_amount
+        .parse::<f64>()
+        .ok()
+        .filter(|amount| amount.is_finite() && *amount > 0.0)
+        .ok_or_else(|| {
+            BuildPosTxsError::Validation(ValidationError::InvalidAmount(format!(
+                "Invalid price amount: {price_amount}"
+            )))
+        })?;
+
+    let precision = decimals.min(QUOTE_AMOUNT_MAX_PRECISION);
+    let scale = 10f64.powi(precision.into());
+    // Tolerating the floating point noise below the smallest unit before rounding up
+    let amount = (price_amount / asset_price * scale - 1e-6).ceil() / scale;
+    Ok(format!("{amount:.0$}", precision as usize))
+}
+
+/// Quote the asset amount for the intent price with the current asset price
+pub async fn quote_asset_amount(
+    state: &AppState,
+    asset: &Caip19Asset,
+    price: &IntentPrice,
+) -> Result<AssetQuote, BuildPosTxsError> {
+    let (asset_price, decimals) = get_asset_price(state, asset, &price.currency).await?;
+    Ok(AssetQuote {
+        amount: quoted_amount(&price.amount, asset_price, decimals)?,
+        asset_price,
+    })
+}
+
+/// Store the quote for the built transaction
+pub async fn lock_quote(state: &AppState, quote: &PosQuote) -> Result<(), BuildPosTxsError> {
+    let cache = state.pos_quote_cache.as_ref().ok_or_else(|| {
+        BuildPosTxsError::Internal(InternalError::Internal(
+            "Quote storage is not configured".to_string(),
+        ))
+    })?;
+    cache
+        .set(
+            &quote_key(&quote.transaction_id),
+            quote,
+            Some(QUOTE_LOCK_TTL + QUOTE_RETENTION),
+        )
+        .await
+        .map_err(|e| {
+            BuildPosTxsError::Internal(InternalError::Internal(format!(
+                "Failed to lock the quote: {e}"
+            )))
+        })
+}
+
+/// Get the quote locked for the built transaction
+pub async fn get_locked_quote(
+    state: &AppState,
+    transaction_id: &str,
+) -> Result<Option<PosQuote>, StorageError> {
+    match state.pos_quote_cache.as_ref() {
+        Some(cache) => cache.get(&quote_key(transaction_id)).await,
+        None => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quoted_amount_rounding() {
+        assert_eq!(quoted_amount("10", 2500.0, 18).unwrap(), "0.00400000");
+        // Rounded up at the maximum precision
+        assert_eq!(quoted_amount("10", 3000.0, 18).unwrap(), "0.00333334");
+        // Limited by the asset decimals
+        assert_eq!(quoted_amount("10", 3.0, 6).unwrap(), "3.333334");
+        assert!(quoted_amount("0", 3000.0, 18).is_err());
+        assert!(quoted_amount("abc", 3000.0, 18).is_err());
+    }
+}
@@ -1,10 +1,15 @@
 use {
     super::{
-        AssetNamespaceType, BuildPosTxsError, CheckPosTxError, CheckTransactionResult,
+        AssetNamespaceType, BuildPosTxsError, CheckPosTxError, CheckTransactionResult, FeeEstimate,
         InternalError, PaymentIntent, SupportedNamespace, TransactionBuilder, TransactionId,
-        TransactionRpc, TransactionStatus, ValidatedPaymentIntent, ValidationError,
+        TransactionRpc, TransactionStatus, TransferredAmount, ValidatedPaymentIntent,
+        ValidationError,
+    },
+    crate::{
+        analytics::MessageSource,
+        state::AppState,
+        utils::crypto::{Caip19Asset, Caip2ChainId},
     },
-    crate::{analytics::MessageSource, state::AppState, utils::crypto::Caip2ChainId},
     alloy::primitives::{utils::parse_units, U256},
     async_trait::async_trait,
     axum::extract::State,
@@ -31,12 +36,42 @@ const SPL_TOKEN_2022_ID: &str = "TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb";
 const BASE_URL: &str = "https://rpc.walletconnect.org/v1";
 const DEFAULT_CHECK_IN: usize = 400;
 const NAMESPACE_NAME: &str = "solana";
+const NATIVE_SLIP44: u32 = 501;
+const NATIVE_DECIMALS: u8 = 9;
+const LAMPORTS_PER_SIGNATURE: u64 = 5_000;
+/// Default compute unit limit of the instruction the priority fee is paid for
+const DEFAULT_COMPUTE_UNIT_LIMIT: u64 = 200_000;
+const MICRO_LAMPORTS_PER_LAMPORT: u64 = 1_000_000;
 
 #[derive(Debug, Deserialize)]
 struct SignedTransactionResult {
     signature: String,
 }
 
+/// Token balances of the transaction status meta
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TokenBalancesMeta {
+    #[serde(default)]
+    pre_token_balances: Vec<TokenBalance>,
+    #[serde(default)]
+    post_token_balances: Vec<TokenBalance>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TokenBalance {
+    mint: String,
+    owner: Option<String>,
+    ui_token_amount: TokenAmount,
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenAmount {
+    amount: String,
+    decimals: u8,
+}
+
 #[derive(Debug, Clone, PartialEq, EnumString, Display, EnumIter)]
 #[strum(serialize_all = "lowercase")]
 pub enum AssetNamespace {
@@ -174,6 +209,70 @@ async fn build_spl_transfer(
     })
 }
 
+pub async fn estimate_fees(
+    project_id: &str,
+    params: ValidatedPaymentIntent<AssetNamespace>,
+) -> Result<FeeEstimate, BuildPosTxsError> {
+    if params.namespace != AssetNamespace::Token {
+        return Err(BuildPosTxsError::Validation(ValidationError::InvalidAsset(
+            "Unsupported asset namespace".to_string(),
+        )));
+    }
+
+    let sender_pubkey = Pubkey::from_str(&params.sender_address)
+        .map_err(|e| BuildPosTxsError::Validation(ValidationError::InvalidSender(e.to_string())))?;
+    let recipient_pubkey = Pubkey::from_str(&params.recipient_address).map_err(|e| {
+        BuildPosTxsError::Validation(ValidationError::InvalidRecipient(e.to_string()))
+    })?;
+    let mint_pubkey = Pubkey::from_str(params.asset.asset_reference())
+        .map_err(|e| BuildPosTxsError::Validation(ValidationError::InvalidAsset(e.to_string())))?;
+
+    let chain_id = params.asset.chain_id();
+    let (decimals, _) = get_token_decimals(&mint_pubkey, chain_id, project_id).await?;
+    parse_token_amount(&params.amount, decimals)?;
+
+    let sender_ata = get_associated_token_address(&sender_pubkey, &mint_pubkey);
+    let recipient_ata = get_associated_token_address(&recipient_pubkey, &mint_pubkey);
+
+    let rpc_client = create_rpc_client(chain_id, project_id).map_err(BuildPosTxsError::Internal)?;
+    let mut prioritization_fees = rpc_client
+        .get_recent_prioritization_fees(&[sender_ata, recipient_ata])
+        .await
+        .map_err(|e| {
+            BuildPosTxsError::Internal(InternalError::Internal(format!(
+                "Failed to fetch recent prioritization fees: {}",
+                e
+            )))
+        })?
+        .into_iter()
+        .map(|fee| fee.prioritization_fee)
+        .collect::<Vec<_>>();
+    prioritization_fees.sort_unstable();
+
+    // Using the median of the recent prioritization fees per compute unit
+    let compute_unit_price = prioritization_fees
+        .get(prioritization_fees.len() / 2)
+        .copied()
+        .unwrap_or_default();
+    let priority_fee = compute_unit_price
+        .saturating_mul(DEFAULT_COMPUTE_UNIT_LIMIT)
+        .div_ceil(MICRO_LAMPORTS_PER_LAMPORT);
+    let fee = LAMPORTS_PER_SIGNATURE.saturating_add(priority_fee);
+
+    FeeEstimate::new(
+        chain_id,
+        NATIVE_SLIP44,
+        NATIVE_DECIMALS,
+        U256::from(fee),
+        serde_json::json!({
+            "baseFee": LAMPORTS_PER_SIGNATURE.to_string(),
+            "priorityFee": priority_fee.to_string(),
+            "computeUnitPrice": compute_unit_price.to_string(),
+            "computeUnitLimit": DEFAULT_COMPUTE_UNIT_LIMIT.to_string(),
+        }),
+    )
+}
+
 async fn get_token_decimals(
     mint_pubkey: &Pubkey,
     chain_id: &Caip2ChainId,
@@ -343,20 +442,108 @@ pub async fn check_transaction(
             status,
             check_in: Some(DEFAULT_CHECK_IN),
             txid: Some(signature.to_string()),
+            expected_amount: None,
+            actual_amount: None,
         }),
-        TransactionStatus::Confirmed => Ok(CheckTransactionResult {
+        TransactionStatus::Confirmed
+        | TransactionStatus::Underpaid
+        | TransactionStatus::Overpaid => Ok(CheckTransactionResult {
             status,
             check_in: None,
             txid: Some(signature.to_string()),
+            expected_amount: None,
+            actual_amount: None,
         }),
         TransactionStatus::Failed => Ok(CheckTransactionResult {
             status,
             check_in: None,
             txid: None,
+            expected_amount: None,
+            actual_amount: None,
         }),
     }
 }
 
+/// Get the amount of the token transferred to the recipient by the transaction
+/// from the token balances change of the recipient
+pub async fn get_transferred_amount(
+    project_id: &str,
+    signature: &str,
+    asset: &Caip19Asset,
+    recipient: &str,
+) -> Result<TransferredAmount, CheckPosTxError> {
+    if asset.asset_namespace() != AssetNamespace::Token.to_string() {
+        return Err(CheckPosTxError::Validation(ValidationError::InvalidAsset(
+            "Unsupported asset namespace".to_string(),
+        )));
+    }
+    let parsed_signature = Signature::from_str(signature).map_err(|e| {
+        CheckPosTxError::Validation(ValidationError::InvalidWalletResponse(format!(
+            "Invalid signature: {}",
+            e
+        )))
+    })?;
+
+    let rpc_client =
+        create_rpc_client(asset.chain_id(), project_id).map_err(CheckPosTxError::Internal)?;
+    let config = RpcTransactionConfig {
+        commitment: Some(CommitmentConfig::confirmed()),
+        max_supported_transaction_version: Some(0),
+        ..Default::default()
+    };
+    let tx = rpc_client
+        .get_transaction_with_config(&parsed_signature, config)
+        .await
+        .map_err(|e| CheckPosTxError::Internal(InternalError::RpcError(e.to_string())))?;
+    let meta = tx.transaction.meta.ok_or_else(|| {
+        CheckPosTxError::Internal(InternalError::RpcError(
+            "Transaction meta is missing".to_string(),
+        ))
+    })?;
+    let meta: TokenBalancesMeta = serde_json::to_value(&meta)
+        .and_then(serde_json::from_value)
+        .map_err(|e| {
+            CheckPosTxError::Internal(InternalError::Internal(format!(
+                "Failed to parse token balances: {}",
+                e
+            )))
+        })?;
+
+    let mint = asset.asset_reference();
+    let recipient_balances = |balances: &[TokenBalance]| {
+        balances
+            .iter()
+            .filter(|balance| balance.mint == mint && balance.owner.as_deref() == Some(recipient))
+            .collect::<Vec<_>>()
+    };
+    let sum_balances = |balances: &[&TokenBalance]| {
+        balances
+            .iter()
+            .filter_map(|balance| U256::from_str(&balance.ui_token_amount.amount).ok())
+            .fold(U256::ZERO, |total, amount| total.saturating_add(amount))
+    };
+    let pre_balances = recipient_balances(&meta.pre_token_balances);
+    let post_balances = recipient_balances(&meta.post_token_balances);
+
+    let decimals = match post_balances.first() {
+        Some(balance) => balance.ui_token_amount.decimals,
+        None => {
+            let mint_pubkey = Pubkey::from_str(mint).map_err(|e| {
+                CheckPosTxError::Validation(ValidationError::InvalidAsset(e.to_string()))
+            })?;
+            get_token_decimals(&mint_pubkey, asset.chain_id(), project_id)
+                .await
+                .map_err(|e| CheckPosTxError::Internal(InternalError::RpcError(e.to_string())))?
+                .0
+        }
+    };
+
+    Ok(TransferredAmount {
+        amount: sum_balances(&post_balances).saturating_sub(sum_balances(&pre_balances)),
+        decimals,
+    })
+}
+
 pub fn get_namespace_info() -> SupportedNamespace {
     SupportedNamespace {
         name: NAMESPACE_NAME.to_string(),
@@ -1,5 +1,6 @@
 use {
     super::{
+        bitcoin::get_namespace_info as bitcoin_get_namespace_info,
         evm::get_namespace_info as evm_get_namespace_info,
         solana::get_namespace_info as solana_get_namespace_info,
         tron::get_namespace_info as tron_get_namespace_info, SupportedNetworksError,
@@ -19,6 +20,7 @@ pub async fn handler(
             evm_get_namespace_info(),
             solana_get_namespace_info(),
             tron_get_namespace_info(),
+            bitcoin_get_namespace_info(),
         ],
     })
 }
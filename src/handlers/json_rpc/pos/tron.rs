@@ -1,11 +1,16 @@
 use {
     super::{
-        AssetNamespaceType, BuildPosTxsError, CheckPosTxError, CheckTransactionResult,
-        ExecutionError, InternalError, PaymentIntent, RpcError, SupportedNamespace,
-        TransactionBuilder, TransactionId, TransactionRpc, TransactionStatus,
-        ValidatedPaymentIntent, ValidationError,
+        sum_token_transfers, AssetNamespaceType, BuildPosTxsError, CheckPosTxError,
+        CheckTransactionResult, ExecutionError, FeeEstimate, InternalError, PaymentIntent,
+        ReceiptLog, RpcError, SupportedNamespace, TransactionBuilder, TransactionId,
+        TransactionRpc, TransactionStatus, TransferredAmount, ValidatedPaymentIntent,
+        ValidationError,
+    },
+    crate::{
+        analytics::MessageSource,
+        state::AppState,
+        utils::crypto::{Caip19Asset, Caip2ChainId},
     },
-    crate::{analytics::MessageSource, state::AppState, utils::crypto::Caip2ChainId},
     alloy::{
         primitives::{utils::parse_units, Address as EthAddress, U256},
         sol,
@@ -27,6 +32,12 @@ const DEFAULT_CHECK_IN: usize = 400;
 const FEE_MARGIN_BPS: u16 = 2000;
 const BPS_DEN: u128 = 10_000;
 const NAMESPACE_NAME: &str = "tron";
+const NATIVE_SLIP44: u32 = 195;
+const NATIVE_DECIMALS: u8 = 6;
+/// Approximate bandwidth in bytes consumed by the TRC20 transfer transaction
+const TRC20_TRANSFER_BANDWIDTH: u128 = 345;
+/// Bandwidth price in sun per byte when it's paid by burning TRX
+const BANDWIDTH_PRICE_SUN: u128 = 1000;
 
 sol! {
     function transfer(address to, uint256 value) external returns (bool);
@@ -94,6 +105,8 @@ struct JsonRpcError {
 #[derive(Debug, Deserialize)]
 struct TransactionReceipt {
     status: Option<String>,
+    #[serde(default)]
+    logs: Vec<ReceiptLog>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -330,6 +343,21 @@ async fn estimate_trc20_fee_limit(
     project_id: &str,
     params: &BuildTransactionParams,
 ) -> Result<String, BuildPosTxsError> {
+    let (gas_value, price_value) = estimate_energy(state, chain_id, project_id, params).await?;
+
+    let fee_limit =
+        compute_fee_limit(gas_value, price_value).map_err(BuildPosTxsError::Internal)?;
+
+    Ok(format!("0x{:x}", fee_limit))
+}
+
+/// Estimate the energy consumption and the energy price in sun
+async fn estimate_energy(
+    state: &State<Arc<AppState>>,
+    chain_id: &Caip2ChainId,
+    project_id: &str,
+    params: &BuildTransactionParams,
+) -> Result<(u128, u128), BuildPosTxsError> {
     let gas_estimate = estimate_gas(state, chain_id, project_id, params.clone())
         .await
         .map_err(BuildPosTxsError::Rpc)?;
@@ -354,10 +382,7 @@ async fn estimate_trc20_fee_limit(
             )))
         })?;
 
-    let fee_limit =
-        compute_fee_limit(gas_value, price_value).map_err(BuildPosTxsError::Internal)?;
-
-    Ok(format!("0x{:x}", fee_limit))
+    Ok((gas_value, price_value))
 }
 
 fn compute_fee_limit(gas_estimate: u128, gas_price: u128) -> Result<u128, InternalError> {
@@ -417,11 +442,12 @@ impl TransactionBuilder<AssetNamespace> for TronTransactionBuilder {
     }
 }
 
-async fn build_trc20_transfer(
-    state: State<Arc<AppState>>,
-    params: ValidatedPaymentIntent<AssetNamespace>,
+/// Prepare the TRC20 transfer transaction parameters for the payment intent
+async fn prepare_trc20_transfer(
+    state: &State<Arc<AppState>>,
+    params: &ValidatedPaymentIntent<AssetNamespace>,
     project_id: &str,
-) -> Result<TransactionRpc, BuildPosTxsError> {
+) -> Result<BuildTransactionParams, BuildPosTxsError> {
     let to_eth = tron_base58_to_eth_address(&params.recipient_address).map_err(|e| {
         BuildPosTxsError::Validation(ValidationError::InvalidRecipient(e.to_string()))
     })?;
@@ -453,15 +479,23 @@ async fn build_trc20_transfer(
     let to_address = tron_b58_to_hex41(params.asset.asset_reference())
         .map_err(|e| BuildPosTxsError::Validation(ValidationError::InvalidAsset(e.to_string())))?;
 
-    let build_params = BuildTransactionParams {
-        from: from_address.clone(),
-        to: to_address.clone(),
-        data: data_hex.clone(),
+    Ok(BuildTransactionParams {
+        from: from_address,
+        to: to_address,
+        data: data_hex,
         gas: None,
         value: "0x0".to_string(),
         token_id: 0,
         token_value: 0,
-    };
+    })
+}
+
+async fn build_trc20_transfer(
+    state: State<Arc<AppState>>,
+    params: ValidatedPaymentIntent<AssetNamespace>,
+    project_id: &str,
+) -> Result<TransactionRpc, BuildPosTxsError> {
+    let build_params = prepare_trc20_transfer(&state, &params, project_id).await?;
 
     let fee_limit =
         estimate_trc20_fee_limit(&state, params.asset.chain_id(), project_id, &build_params)
@@ -502,6 +536,41 @@ async fn build_trc20_transfer(
     })
 }
 
+pub async fn estimate_fees(
+    state: State<Arc<AppState>>,
+    project_id: &str,
+    params: ValidatedPaymentIntent<AssetNamespace>,
+) -> Result<FeeEstimate, BuildPosTxsError> {
+    if params.namespace != AssetNamespace::Trc20 {
+        return Err(BuildPosTxsError::Validation(ValidationError::InvalidAsset(
+            "Unsupported asset namespace".to_string(),
+        )));
+    }
+
+    let chain_id = params.asset.chain_id();
+    let build_params = prepare_trc20_transfer(&state, &params, project_id).await?;
+    let (energy, energy_price) =
+        estimate_energy(&state, chain_id, project_id, &build_params).await?;
+
+    let energy_fee = energy.checked_mul(energy_price).ok_or_else(|| {
+        BuildPosTxsError::Internal(InternalError::Internal("energy fee overflow".to_string()))
+    })?;
+    let bandwidth_fee = TRC20_TRANSFER_BANDWIDTH * BANDWIDTH_PRICE_SUN;
+
+    FeeEstimate::new(
+        chain_id,
+        NATIVE_SLIP44,
+        NATIVE_DECIMALS,
+        U256::from(energy_fee) + U256::from(bandwidth_fee),
+        serde_json::json!({
+            "energy": energy.to_string(),
+            "energyPrice": energy_price.to_string(),
+            "bandwidth": TRC20_TRANSFER_BANDWIDTH.to_string(),
+            "bandwidthPrice": BANDWIDTH_PRICE_SUN.to_string(),
+        }),
+    )
+}
+
 fn parse_token_amount(amount: &str, decimals: u8) -> Result<U256, BuildPosTxsError> {
     let parsed_value = parse_units(amount, decimals).map_err(|e| {
         BuildPosTxsError::Validation(ValidationError::InvalidAmount(format!(
@@ -610,6 +679,49 @@ pub async fn get_transaction_status(
     Ok(TransactionStatus::Pending)
 }
 
+/// Get the amount of the TRC20 token transferred to the recipient by the
+/// transaction
+pub async fn get_transferred_amount(
+    state: &State<Arc<AppState>>,
+    project_id: &str,
+    txid: &str,
+    asset: &Caip19Asset,
+    recipient: &str,
+) -> Result<TransferredAmount, CheckPosTxError> {
+    if asset.asset_namespace() != AssetNamespace::Trc20.to_string() {
+        return Err(CheckPosTxError::Validation(ValidationError::InvalidAsset(
+            "Unsupported asset namespace".to_string(),
+        )));
+    }
+    let chain_id = asset.chain_id();
+    let receipt = get_transaction_receipt(state, chain_id, project_id, txid)
+        .await
+        .map_err(CheckPosTxError::Rpc)?
+        .ok_or_else(|| {
+            CheckPosTxError::Rpc(RpcError::InvalidResponse(format!(
+                "Transaction {txid} receipt is not found"
+            )))
+        })?;
+
+    let token =
+        tron_b58_to_eth_hex(asset.asset_reference()).map_err(CheckPosTxError::Validation)?;
+    let recipient_hex = tron_b58_to_eth_hex(recipient).map_err(CheckPosTxError::Validation)?;
+    let decimals = fetch_trc20_decimals(
+        state,
+        chain_id,
+        project_id,
+        recipient,
+        asset.asset_reference(),
+    )
+    .await
+    .map_err(|e| CheckPosTxError::Internal(InternalError::RpcError(e.to_string())))?;
+
+    Ok(TransferredAmount {
+        amount: sum_token_transfers(&receipt.logs, &token, &recipient_hex),
+        decimals,
+    })
+}
+
 fn tron_base58_to_eth_address(b58: &str) -> Result<EthAddress, ValidationError> {
     let bytes = bs58::decode(b58).with_check(None).into_vec().map_err(|e| {
         ValidationError::InvalidAddress(format!("Failed to decode TRON address: {}", e))
@@ -674,16 +786,24 @@ pub async fn check_transaction(
             status,
             check_in: Some(DEFAULT_CHECK_IN),
             txid: Some(signed_tx.tx_id),
+            expected_amount: None,
+            actual_amount: None,
         }),
-        TransactionStatus::Confirmed => Ok(CheckTransactionResult {
+        TransactionStatus::Confirmed
+        | TransactionStatus::Underpaid
+        | TransactionStatus::Overpaid => Ok(CheckTransactionResult {
             status,
             check_in: None,
             txid: Some(signed_tx.tx_id),
+            expected_amount: None,
+            actual_amount: None,
         }),
         TransactionStatus::Failed => Ok(CheckTransactionResult {
             status,
             check_in: None,
             txid: None,
+            expected_amount: None,
+            actual_amount: None,
         }),
     }
 }
@@ -0,0 +1,387 @@
+use crate::{
+    analytics::MessageSource,
+    handlers::{self_provider::SelfProviderPool, SdkInfoParams},
+    state::AppState,
+    utils::crypto::{approveCall, Caip19Asset},
+};
+use alloy::{
+    primitives::{address, aliases::U160, Address, Bytes, U256},
+    sol,
+    sol_types::SolCall,
+};
+use axum::extract::{ConnectInfo, Query, State};
+use hyper::HeaderMap;
+use serde::{Deserialize, Serialize};
+use std::{
+    net::SocketAddr,
+    str::FromStr,
+    sync::Arc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use thiserror::Error;
+use wc::metrics::{future_metrics, FutureExt};
+
+/// Canonical Permit2 contract address, the same on all the EVM chains
+pub const PERMIT2_ADDRESS: Address = address!("000000000022D473030F116dDEE9F6B43aC78BA3");
+
+/// Default Permit2 allowance expiration
+const DEFAULT_EXPIRATION: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+/// Default deadline of the Permit2 signature
+const DEFAULT_SIG_DEADLINE: Duration = Duration::from_secs(30 * 60);
+/// Maximum value of the Permit2 `uint48` expiration
+const MAX_EXPIRATION: u64 = (1 << 48) - 1;
+
+sol! {
+    #[sol(rpc)]
+    interface IPermit2 {
+        function allowance(address user, address token, address spender)
+            external
+            view
+            returns (uint160 amount, uint48 expiration, uint48 nonce);
+    }
+
+    #[sol(rpc)]
+    interface ERC20Allowance {
+        function allowance(address owner, address spender) external view returns (uint256);
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ApprovalType {
+    #[default]
+    Approve,
+    Permit2,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BuildApprovalRequest {
+    /// CAIP-19 asset ID of the ERC-20 token
+    pub token: String,
+    pub spender: Address,
+    pub amount: U256,
+    #[serde(default, rename = "type")]
+    pub approval_type: ApprovalType,
+    /// Token owner, required for the Permit2 nonce lookup
+    pub owner: Option<Address>,
+    /// Permit2 allowance expiration timestamp
+    pub expiration: Option<u64>,
+    /// Permit2 signature deadline timestamp
+    pub sig_deadline: Option<u64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum BuildApprovalResult {
+    #[serde(rename_all = "camelCase")]
+    Approve { transaction: ApprovalTransaction },
+    #[serde(rename_all = "camelCase")]
+    Permit2 {
+        /// ERC-20 approval of the Permit2 contract, omitted when the current
+        /// allowance of the Permit2 contract is sufficient
+        approval_transaction: Option<ApprovalTransaction>,
+        /// EIP-712 typed data of the `PermitSingle` to sign by the owner
+        typed_data: serde_json::Value,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApprovalTransaction {
+    /// CAIP-2 chain ID
+    pub chain_id: String,
+    pub to: Address,
+    pub data: Bytes,
+    pub value: U256,
+}
+
+#[derive(Error, Debug)]
+pub enum BuildApprovalError {
+    #[error("Invalid token: {0}")]
+    InvalidToken(String),
+
+    #[error("Amount exceeds the uint160 Permit2 allowance amount")]
+    InvalidAmount,
+
+    #[error("Invalid expiration: {0}")]
+    InvalidExpiration(u64),
+
+    #[error("Owner is required for the Permit2 approval")]
+    MissingOwner,
+
+    #[error("Internal error: {0}")]
+    Internal(String),
+}
+
+impl BuildApprovalError {
+    pub fn is_internal(&self) -> bool {
+        matches!(self, BuildApprovalError::Internal(_))
+    }
+}
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct QueryParams {
+    #[serde(flatten)]
+    pub sdk_info: SdkInfoParams,
+}
+
+pub async fn handler(
+    state: State<Arc<AppState>>,
+    project_id: String,
+    request: BuildApprovalRequest,
+    connect_info: ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+    query: Query<QueryParams>,
+) -> Result<BuildApprovalResult, BuildApprovalError> {
+    handler_internal(state, project_id, request, connect_info, headers, query)
+        .with_metrics(future_metrics!("handler_task", "name" => "wallet_build_approval"))
+        .await
+}
+
+#[tracing::instrument(skip(state), level = "debug")]
+async fn handler_internal(
+    State(state): State<Arc<AppState>>,
+    project_id: String,
+    request: BuildApprovalRequest,
+    ConnectInfo(connect_info): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+    Query(query): Query<QueryParams>,
+) -> Result<BuildApprovalResult, BuildApprovalError> {
+    let (asset, token, chain_id) = parse_token(&request.token)?;
+
+    if request.approval_type == ApprovalType::Approve {
+        return Ok(BuildApprovalResult::Approve {
+            transaction: approve_transaction(&asset, token, request.spender, request.amount),
+        });
+    }
+
+    let owner = request.owner.ok_or(BuildApprovalError::MissingOwner)?;
+    let amount = U160::checked_from(request.amount).ok_or(BuildApprovalError::InvalidAmount)?;
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_err(|e| BuildApprovalError::Internal(e.to_string()))?;
+    let expiration = request
+        .expiration
+        .unwrap_or((now + DEFAULT_EXPIRATION).as_secs());
+    if expiration > MAX_EXPIRATION {
+        return Err(BuildApprovalError::InvalidExpiration(expiration));
+    }
+    let sig_deadline = request
+        .sig_deadline
+        .unwrap_or((now + DEFAULT_SIG_DEADLINE).as_secs());
+
+    let provider_pool = SelfProviderPool {
+        state,
+        connect_info,
+        headers,
+        project_id: project_id.as_str().into(),
+        sdk_info: query.sdk_info,
+        session_id: None,
+    };
+    let provider = provider_pool.get_provider(
+        asset.chain_id().to_string(),
+        MessageSource::WalletBuildApproval,
+    );
+    let permit2 = IPermit2::new(PERMIT2_ADDRESS, &provider);
+    let erc20 = ERC20Allowance::new(token, &provider);
+    let permit2_allowance = permit2.allowance(owner, token, request.spender);
+    let erc20_allowance = erc20.allowance(owner, PERMIT2_ADDRESS);
+    let (permit2_allowance, erc20_allowance) =
+        tokio::try_join!(permit2_allowance.call(), erc20_allowance.call())
+            .map_err(|e| BuildApprovalError::Internal(format!("Allowance call failed: {e}")))?;
+
+    // Permit2 contract is approved once for the max amount, so the following
+    // approvals are the signatures only
+    let approval_transaction = (erc20_allowance._0 < request.amount)
+        .then(|| approve_transaction(&asset, token, PERMIT2_ADDRESS, U256::MAX));
+
+    Ok(BuildApprovalResult::Permit2 {
+        approval_transaction,
+        typed_data: permit_single_typed_data(
+            chain_id,
+            PermitDetails {
+                token,
+                amount,
+                expiration,
+                nonce: permit2_allowance.nonce.to::<u64>(),
+            },
+            request.spender,
+            sig_deadline,
+        ),
+    })
+}
+
+/// Parses the ERC-20 token CAIP-19 asset ID into the asset, the token address
+/// and the EVM chain ID
+fn parse_token(token: &str) -> Result<(Caip19Asset, Address, u64), BuildApprovalError> {
+    let asset =
+        Caip19Asset::parse(token).map_err(|e| BuildApprovalError::InvalidToken(e.to_string()))?;
+    if asset.chain_id().namespace() != "eip155" || asset.asset_namespace() != "erc20" {
+        return Err(BuildApprovalError::InvalidToken(
+            "Only the eip155 ERC-20 tokens are supported".to_string(),
+        ));
+    }
+    let address = Address::from_str(asset.asset_reference())
+        .map_err(|e| BuildApprovalError::InvalidToken(e.to_string()))?;
+    let chain_id = asset
+        .chain_id()
+        .reference()
+        .parse::<u64>()
+        .map_err(|e| BuildApprovalError::InvalidToken(e.to_string()))?;
+    Ok((asset, address, chain_id))
+}
+
+fn approve_transaction(
+    asset: &Caip19Asset,
+    token: Address,
+    spender: Address,
+    amount: U256,
+) -> ApprovalTransaction {
+    ApprovalTransaction {
+        chain_id: asset.chain_id().to_string(),
+        to: token,
+        data: approveCall {
+            _spender: spender,
+            _value: amount,
+        }
+        .abi_encode()
+        .into(),
+        value: U256::ZERO,
+    }
+}
+
+struct PermitDetails {
+    token: Address,
+    amount: U160,
+    expiration: u64,
+    nonce: u64,
+}
+
+/// EIP-712 typed data of the Permit2 `PermitSingle` in the
+/// `eth_signTypedData_v4` format
+fn permit_single_typed_data(
+    chain_id: u64,
+    details: PermitDetails,
+    spender: Address,
+    sig_deadline: u64,
+) -> serde_json::Value {
+    serde_json::json!({
+        "types": {
+            "EIP712Domain": [
+                { "name": "name", "type": "string" },
+                { "name": "chainId", "type": "uint256" },
+                { "name": "verifyingContract", "type": "address" },
+            ],
+            "PermitDetails": [
+                { "name": "token", "type": "address" },
+                { "name": "amount", "type": "uint160" },
+                { "name": "expiration", "type": "uint48" },
+                { "name": "nonce", "type": "uint48" },
+            ],
+            "PermitSingle": [
+                { "name": "details", "type": "PermitDetails" },
+                { "name": "spender", "type": "address" },
+                { "name": "sigDeadline", "type": "uint256" },
+            ],
+        },
+        "primaryType": "PermitSingle",
+        "domain": {
+            "name": "Permit2",
+            "chainId": chain_id,
+            "verifyingContract": PERMIT2_ADDRESS,
+        },
+        "message": {
+            "details": {
+                "token": details.token,
+                "amount": details.amount.to_string(),
+                "expiration": details.expiration,
+                "nonce": details.nonce,
+            },
+            "spender": spender,
+            "sigDeadline": sig_deadline.to_string(),
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, alloy::primitives::hex};
+
+    const USDC: &str = "eip155:1/erc20:0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48";
+
+    #[test]
+    fn encodes_approve_calldata() {
+        let (asset, token, chain_id) = parse_token(USDC).unwrap();
+        assert_eq!(chain_id, 1);
+
+        let spender = address!("1111111111111111111111111111111111111111");
+        let transaction = approve_transaction(&asset, token, spender, U256::from(1_000_000));
+        assert_eq!(transaction.chain_id, "eip155:1");
+        assert_eq!(transaction.to, token);
+        assert_eq!(transaction.value, U256::ZERO);
+        assert_eq!(
+            hex::encode(&transaction.data),
+            concat!(
+                "095ea7b3",
+                "0000000000000000000000001111111111111111111111111111111111111111",
+                "00000000000000000000000000000000000000000000000000000000000f4240",
+            )
+        );
+    }
+
+    #[test]
+    fn rejects_non_erc20_tokens() {
+        assert!(parse_token("eip155:1/slip44:60").is_err());
+        assert!(parse_token("solana:5eykt4UsFv8P8NJdTREpY1vzqKqZKvdp/token:abc").is_err());
+    }
+
+    #[test]
+    fn builds_permit_single_typed_data() {
+        let (_, token, chain_id) = parse_token(USDC).unwrap();
+        let spender = address!("1111111111111111111111111111111111111111");
+        let typed_data = permit_single_typed_data(
+            chain_id,
+            PermitDetails {
+                token,
+                amount: U160::from(1_000_000),
+                expiration: 1_700_000_000,
+                nonce: 3,
+            },
+            spender,
+            1_600_000_000,
+        );
+        assert_eq!(typed_data["primaryType"], "PermitSingle");
+        assert_eq!(typed_data["domain"]["chainId"], 1);
+        assert_eq!(
+            typed_data["domain"]["verifyingContract"],
+            serde_json::json!(PERMIT2_ADDRESS)
+        );
+        assert_eq!(typed_data["message"]["details"]["amount"], "1000000");
+        assert_eq!(typed_data["message"]["details"]["nonce"], 3);
+        assert_eq!(typed_data["message"]["sigDeadline"], "1600000000");
+    }
+
+    #[test]
+    fn deserializes_request() {
+        let request = serde_json::from_value::<BuildApprovalRequest>(serde_json::json!({
+            "token": USDC,
+            "spender": "0x1111111111111111111111111111111111111111",
+            "amount": "0xf4240",
+        }))
+        .unwrap();
+        assert_eq!(request.approval_type, ApprovalType::Approve);
+        assert_eq!(request.amount, U256::from(1_000_000));
+
+        let request = serde_json::from_value::<BuildApprovalRequest>(serde_json::json!({
+            "token": USDC,
+            "spender": "0x1111111111111111111111111111111111111111",
+            "amount": "0xf4240",
+            "type": "permit2",
+            "owner": "0x2222222222222222222222222222222222222222",
+        }))
+        .unwrap();
+        assert_eq!(request.approval_type, ApprovalType::Permit2);
+    }
+}
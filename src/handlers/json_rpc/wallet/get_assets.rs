@@ -6,11 +6,17 @@ use crate::{
         SdkInfoParams, SupportedCurrencies,
     },
     state::AppState,
+    utils::crypto::{disassemble_caip10, CaipNamespaces},
+};
+use alloy::primitives::{
+    address,
+    utils::{parse_units, ParseUnits},
+    Address, U256,
 };
-use alloy::primitives::{address, Address, U256};
 use axum::extract::{ConnectInfo, Path, Query, State};
+use futures_util::future::try_join_all;
 use hyper::HeaderMap;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use std::{collections::HashMap, net::SocketAddr, sync::Arc};
 use thiserror::Error;
 use tracing::error;
@@ -22,6 +28,9 @@ use yttrium::wallet_service_api::{
 
 #[derive(Error, Debug)]
 pub enum GetAssetsError {
+    #[error("Invalid additional account: {0}")]
+    InvalidAccount(String),
+
     #[error("Internal error")]
     InternalError(GetAssetsErrorInternalError),
 }
@@ -45,14 +54,68 @@ pub struct QueryParams {
     pub sdk_info: SdkInfoParams,
 }
 
+/// ERC-7811 request extended by the accounts of the non-EVM namespaces
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct GetAssetsRequest {
+    #[serde(flatten)]
+    pub params: GetAssetsParams,
+    /// CAIP-10 accounts of the other namespaces, e.g. Solana, each account
+    /// assets are looked up on its chain only
+    #[serde(default)]
+    pub additional_accounts: Vec<String>,
+}
+
+/// ERC-7811 assets by the hex chain ID and the other namespaces assets by
+/// the CAIP-2 chain ID
+#[derive(Debug, Serialize)]
+pub struct GetAssetsResponse {
+    #[serde(flatten)]
+    pub evm: GetAssetsResult,
+    #[serde(flatten)]
+    pub namespaces: HashMap<String, Vec<NamespaceAsset>>,
+}
+
+/// Asset of the non-EVM namespace in the ERC-7811 asset format
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NamespaceAsset {
+    /// Token address or `native` for the native token
+    pub address: String,
+    pub balance: U256,
+    #[serde(rename = "type")]
+    pub asset_type: NamespaceAssetType,
+    pub metadata: NamespaceAssetMetadata,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NamespaceAssetType {
+    Native,
+    Token,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NamespaceAssetMetadata {
+    pub name: String,
+    pub symbol: String,
+    pub decimals: u8,
+    pub icon_url: String,
+    pub price: f64,
+    pub value: Option<f64>,
+}
+
+const NATIVE_ASSET_ADDRESS: &str = "native";
+
 pub async fn handler(
     state: State<Arc<AppState>>,
     project_id: String,
-    request: GetAssetsParams,
+    request: GetAssetsRequest,
     connect_info: ConnectInfo<SocketAddr>,
     headers: HeaderMap,
     query: Query<QueryParams>,
-) -> Result<GetAssetsResult, GetAssetsError> {
+) -> Result<GetAssetsResponse, GetAssetsError> {
     handler_internal(state, project_id, request, connect_info, headers, query)
         .with_metrics(future_metrics!("handler_task", "name" => "wallet_get_assets"))
         .await
@@ -62,28 +125,130 @@ pub async fn handler(
 async fn handler_internal(
     state: State<Arc<AppState>>,
     project_id: String,
-    request: GetAssetsParams,
+    request: GetAssetsRequest,
     ConnectInfo(connect_info): ConnectInfo<SocketAddr>,
     headers: HeaderMap,
     query: Query<QueryParams>,
-) -> Result<GetAssetsResult, GetAssetsError> {
-    let balance = handlers::balance::handler(
-        state,
-        Query(BalanceQueryParams {
-            project_id,
-            currency: SupportedCurrencies::USD,
-            chain_id: None,
-            force_update: None,
-            sdk_info: query.sdk_info.clone(),
-        }),
-        ConnectInfo(connect_info),
-        headers,
-        Path(request.account.to_string()),
+) -> Result<GetAssetsResponse, GetAssetsError> {
+    let additional_accounts = request
+        .additional_accounts
+        .iter()
+        .map(|account| parse_additional_account(account))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // The EVM account balance is looked up on all of the EVM chains and the
+    // other accounts balances on their chains by the namespace providers
+    let get_balance = |chain_id: Option<String>, address: String| {
+        handlers::balance::handler(
+            state.clone(),
+            Query(BalanceQueryParams {
+                project_id: project_id.clone(),
+                currency: SupportedCurrencies::USD,
+                chain_id,
+                force_update: None,
+                tokens: None,
+                sdk_info: query.sdk_info.clone(),
+            }),
+            ConnectInfo(connect_info),
+            headers.clone(),
+            Path(address),
+        )
+    };
+    let (balance, namespaces_balances) = tokio::try_join!(
+        get_balance(None, request.params.account.to_string()),
+        try_join_all(
+            additional_accounts
+                .into_iter()
+                .map(|(chain_id, address)| get_balance(Some(chain_id), address)),
+        ),
     )
-    .await
     .map_err(|e| GetAssetsError::InternalError(GetAssetsErrorInternalError::GetBalance(e)))?;
 
-    get_assets(balance.0, request.filters)
+    let namespaces = create_namespaces_response(filter_namespaces_balances(
+        namespaces_balances
+            .into_iter()
+            .flat_map(|balance| balance.0.balances)
+            .collect(),
+        &request.params.filters,
+    ));
+    Ok(GetAssetsResponse {
+        evm: get_assets(balance.0, request.params.filters)?,
+        namespaces,
+    })
+}
+
+/// Parse the CAIP-10 account of the non-EVM namespace to the CAIP-2 chain ID
+/// and the address
+fn parse_additional_account(account: &str) -> Result<(String, String), GetAssetsError> {
+    let (namespace, chain_id, address) = disassemble_caip10(account)
+        .map_err(|e| GetAssetsError::InvalidAccount(format!("{account}: {e}")))?;
+    match namespace {
+        CaipNamespaces::Eip155 | CaipNamespaces::Rootstock => Err(GetAssetsError::InvalidAccount(
+            format!("{account}: EVM assets are looked up by the account"),
+        )),
+        namespace => Ok((format!("{namespace}:{chain_id}"), address)),
+    }
+}
+
+fn create_namespaces_response(balances: Vec<BalanceItem>) -> HashMap<String, Vec<NamespaceAsset>> {
+    let mut result = HashMap::new();
+    for balance in sort_balances_by_value(balances) {
+        let Some(chain_id) = balance.chain_id.clone() else {
+            continue;
+        };
+        let (address, asset_type) = match &balance.address {
+            Some(address) => (
+                address
+                    .rsplit(':')
+                    .next()
+                    .unwrap_or(address.as_str())
+                    .to_owned(),
+                NamespaceAssetType::Token,
+            ),
+            None => (NATIVE_ASSET_ADDRESS.to_owned(), NamespaceAssetType::Native),
+        };
+        let Ok(decimals) = balance.quantity.decimals.parse() else {
+            error!("Invalid decimals of the {chain_id} asset {address}");
+            continue;
+        };
+        let Some(asset_balance) = convert_balance_to_hex(&balance.quantity) else {
+            error!("Invalid balance of the {chain_id} asset {address}");
+            continue;
+        };
+        result
+            .entry(chain_id)
+            .or_insert_with(Vec::new)
+            .push(NamespaceAsset {
+                address,
+                balance: asset_balance,
+                asset_type,
+                metadata: NamespaceAssetMetadata {
+                    name: balance.name,
+                    symbol: balance.symbol,
+                    decimals,
+                    icon_url: balance.icon_url,
+                    price: balance.price,
+                    value: balance.value,
+                },
+            });
+    }
+    result
+}
+
+/// Filters the non-EVM namespaces balances as the EVM ones. The asset and
+/// chain filters are listing the EVM assets and chains only, so no namespace
+/// asset is matching them.
+fn filter_namespaces_balances(
+    balances: Vec<BalanceItem>,
+    filters: &GetAssetsFilters,
+) -> Vec<BalanceItem> {
+    if filters.asset_filter.is_some() || filters.chain_filter.is_some() {
+        return Vec::new();
+    }
+    match &filters.asset_type_filter {
+        Some(asset_type_filter) => apply_asset_type_filter(asset_type_filter.clone(), balances),
+        None => balances,
+    }
 }
 
 fn get_assets(
@@ -345,9 +510,26 @@ fn sort_balances_by_value(mut balances: Vec<BalanceItem>) -> Vec<BalanceItem> {
     balances
 }
 
+/// Balance in the smallest units, `None` when the balance or decimals are
+/// invalid or the balance is negative
+fn convert_balance_to_hex(quantity: &BalanceQuantity) -> Option<U256> {
+    let decimals = quantity.decimals.parse::<u8>().ok()?;
+    match parse_units(&quantity.numeric, decimals).ok()? {
+        ParseUnits::U256(balance) => Some(balance),
+        ParseUnits::I256(_) => None,
+    }
+}
+
 fn create_response(balances: Vec<BalanceItem>) -> GetAssetsResult {
     let mut result = HashMap::new();
     for balance in balances {
+        let Some(asset_balance) = convert_balance_to_hex(&balance.quantity) else {
+            error!(
+                "Invalid balance of the {:?} asset {:?}",
+                balance.chain_id, balance.address
+            );
+            continue;
+        };
         result
             .entry(
                 balance
@@ -359,48 +541,37 @@ fn create_response(balances: Vec<BalanceItem>) -> GetAssetsResult {
                     .unwrap(),
             )
             .or_insert_with(Vec::new)
-            .push({
-                fn convert_balance_to_hex(quantity: &BalanceQuantity) -> U256 {
-                    U256::from(
-                        (quantity.numeric.parse::<f64>().unwrap()
-                            * 10f64.powf(quantity.decimals.parse::<f64>().unwrap()))
-                        .round() as u64,
-                    )
-                }
-                let asset_balance = convert_balance_to_hex(&balance.quantity);
-
-                match balance.address {
-                    Some(address) => Asset::Erc20 {
-                        data: AssetData {
-                            address: AddressOrNative::AddressVariant(convert_caip10_to_address(
-                                &address,
-                            )),
-                            balance: asset_balance,
-                            metadata: Erc20Metadata {
-                                name: balance.name,
-                                symbol: balance.symbol,
-                                decimals: balance.quantity.decimals.parse().unwrap(),
-                                icon_url: balance.icon_url,
-                                price: balance.price, // TODO using float here is bad practice
-                                value: balance.value,
-                            },
+            .push(match balance.address {
+                Some(address) => Asset::Erc20 {
+                    data: AssetData {
+                        address: AddressOrNative::AddressVariant(convert_caip10_to_address(
+                            &address,
+                        )),
+                        balance: asset_balance,
+                        metadata: Erc20Metadata {
+                            name: balance.name,
+                            symbol: balance.symbol,
+                            decimals: balance.quantity.decimals.parse().unwrap(),
+                            icon_url: balance.icon_url,
+                            price: balance.price, // TODO using float here is bad practice
+                            value: balance.value,
                         },
                     },
-                    None => Asset::Native {
-                        data: AssetData {
-                            address: AddressOrNative::Native,
-                            balance: asset_balance,
-                            metadata: NativeMetadata {
-                                name: balance.name,
-                                symbol: balance.symbol,
-                                decimals: balance.quantity.decimals.parse().unwrap(),
-                                icon_url: balance.icon_url,
-                                price: balance.price, // TODO using float here is bad practice
-                                value: balance.value,
-                            },
+                },
+                None => Asset::Native {
+                    data: AssetData {
+                        address: AddressOrNative::Native,
+                        balance: asset_balance,
+                        metadata: NativeMetadata {
+                            name: balance.name,
+                            symbol: balance.symbol,
+                            decimals: balance.quantity.decimals.parse().unwrap(),
+                            icon_url: balance.icon_url,
+                            price: balance.price, // TODO using float here is bad practice
+                            value: balance.value,
                         },
                     },
-                }
+                },
             });
     }
     result
@@ -424,6 +595,138 @@ mod tests {
         .unwrap();
         assert!(assets.is_empty());
     }
+
+    #[test]
+    fn namespaces_assets() {
+        let balances = vec![
+            BalanceItem {
+                name: "USD Coin".to_owned(),
+                symbol: "USDC".to_owned(),
+                chain_id: Some("solana:5eykt4UsFv8P8NJdTREpY1vzqKqZKvdp".to_owned()),
+                address: Some(
+                    "solana:5eykt4UsFv8P8NJdTREpY1vzqKqZKvdp:EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v"
+                        .to_owned(),
+                ),
+                value: Some(2.5),
+                price: 1.0,
+                quantity: BalanceQuantity {
+                    decimals: "6".to_owned(),
+                    numeric: "2.5".to_owned(),
+                },
+                icon_url: "https://example.com/usdc.png".to_owned(),
+            },
+            BalanceItem {
+                name: "Solana".to_owned(),
+                symbol: "SOL".to_owned(),
+                chain_id: Some("solana:5eykt4UsFv8P8NJdTREpY1vzqKqZKvdp".to_owned()),
+                address: None,
+                value: Some(150.0),
+                price: 150.0,
+                quantity: BalanceQuantity {
+                    decimals: "9".to_owned(),
+                    numeric: "1".to_owned(),
+                },
+                icon_url: "https://example.com/sol.png".to_owned(),
+            },
+        ];
+
+        let assets = create_namespaces_response(balances);
+        let assets = &assets["solana:5eykt4UsFv8P8NJdTREpY1vzqKqZKvdp"];
+        assert_eq!(assets.len(), 2);
+        // Sorted by the value
+        assert_eq!(assets[0].address, NATIVE_ASSET_ADDRESS);
+        assert_eq!(assets[0].asset_type, NamespaceAssetType::Native);
+        assert_eq!(assets[0].balance, U256::from(1_000_000_000u64));
+        assert_eq!(
+            assets[1].address,
+            "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v"
+        );
+        assert_eq!(assets[1].asset_type, NamespaceAssetType::Token);
+        assert_eq!(assets[1].balance, U256::from(2_500_000u64));
+    }
+
+    const SOLANA_MAINNET: &str = "solana:5eykt4UsFv8P8NJdTREpY1vzqKqZKvdp";
+
+    fn solana_balance(address: Option<&str>, numeric: &str) -> BalanceItem {
+        BalanceItem {
+            name: "Token".to_owned(),
+            symbol: "TKN".to_owned(),
+            chain_id: Some(SOLANA_MAINNET.to_owned()),
+            address: address.map(|address| format!("{SOLANA_MAINNET}:{address}")),
+            value: None,
+            price: 0.0,
+            quantity: BalanceQuantity {
+                decimals: "9".to_owned(),
+                numeric: numeric.to_owned(),
+            },
+            icon_url: "".to_owned(),
+        }
+    }
+
+    #[test]
+    fn namespaces_assets_balances() {
+        let assets = create_namespaces_response(vec![
+            solana_balance(None, "123456789012.123456789"),
+            solana_balance(Some("Invalid"), "not a number"),
+            solana_balance(Some("Negative"), "-1"),
+        ]);
+        let assets = &assets[SOLANA_MAINNET];
+        // Invalid balances are skipped, the large ones are exact
+        assert_eq!(assets.len(), 1);
+        assert_eq!(
+            assets[0].balance,
+            U256::from(123_456_789_012_123_456_789u128)
+        );
+    }
+
+    #[test]
+    fn filters_namespaces_assets() {
+        let balances = vec![
+            solana_balance(None, "1"),
+            solana_balance(Some("EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v"), "2"),
+        ];
+        let filters = |asset_type_filter, chain_filter| GetAssetsFilters {
+            asset_filter: None,
+            asset_type_filter,
+            chain_filter,
+        };
+
+        assert_eq!(
+            filter_namespaces_balances(balances.clone(), &filters(None, None)).len(),
+            2
+        );
+        let native = filter_namespaces_balances(
+            balances.clone(),
+            &filters(Some(vec![AssetType::Native]), None),
+        );
+        assert_eq!(native.len(), 1);
+        assert_eq!(native[0].address, None);
+        // EVM chains filter is not matching the namespaces chains
+        assert!(filter_namespaces_balances(
+            balances,
+            &filters(None, Some(vec![alloy::primitives::U64::from(1)]))
+        )
+        .is_empty());
+    }
+
+    #[test]
+    fn additional_accounts() {
+        assert_eq!(
+            parse_additional_account(
+                "solana:5eykt4UsFv8P8NJdTREpY1vzqKqZKvdp:9WzDXwBbmkg8ZTbNMqUxvQRAyrZzDsGYdLVL9zYtAWWM"
+            )
+            .unwrap(),
+            (
+                "solana:5eykt4UsFv8P8NJdTREpY1vzqKqZKvdp".to_owned(),
+                "9WzDXwBbmkg8ZTbNMqUxvQRAyrZzDsGYdLVL9zYtAWWM".to_owned()
+            )
+        );
+        assert!(
+            parse_additional_account("eip155:1:0xF91D77EcEA92261d8CfBD9B235709d6ff6233fae")
+                .is_err()
+        );
+        assert!(parse_additional_account("solana:invalid").is_err());
+    }
 }
 
 #[cfg(test)]
@@ -185,8 +185,12 @@ fn user_operation_receipt_to_call_receipt(
 mod self_transport {
     use {
         crate::{
-            error::RpcError, handlers::RpcQueryParams, json_rpc::JSON_RPC_VERSION,
-            providers::SupportedBundlerOps, state::AppState, utils::crypto::disassemble_caip2,
+            error::RpcError,
+            handlers::RpcQueryParams,
+            json_rpc::JSON_RPC_VERSION,
+            providers::{BundlerOpsProvider, SupportedBundlerOps},
+            state::AppState,
+            utils::crypto::disassemble_caip2,
         },
         alloy::{
             rpc::json_rpc::{RequestPacket, Response, ResponsePacket},
@@ -263,7 +267,7 @@ mod self_transport {
                 let (_, eip155_chain_id) = disassemble_caip2(&caip2_identifier)
                     .map_err(|_| TransportErrorKind::custom_str("Failed to parse CAIP2 chainId"))?;
                 let response = state
-                    .providers
+                    .providers()
                     .bundler_ops_provider
                     .bundler_rpc_call(
                         &eip155_chain_id,
@@ -367,6 +371,7 @@ mod tests {
                 bundler_url: config.endpoints.bundler.base_url.parse().unwrap(),
                 paymaster_url: config.endpoints.paymaster.base_url.parse().unwrap(),
             }),
+            ..Default::default()
         })
         .await;
         let mut endpoint = url.join("/v1/wallet").unwrap();
@@ -434,6 +439,7 @@ mod tests {
                 bundler_url: config.endpoints.bundler.base_url.parse().unwrap(),
                 paymaster_url: config.endpoints.paymaster.base_url.parse().unwrap(),
             }),
+            ..Default::default()
         })
         .await;
         let mut endpoint = url.join("/v1/wallet").unwrap();
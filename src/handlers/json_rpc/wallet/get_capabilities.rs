@@ -0,0 +1,187 @@
+use {
+    crate::{
+        providers::{BundlerOpsProvider, SupportedBundlerOps},
+        state::AppState,
+        utils::crypto::{disassemble_caip2, CaipNamespaces},
+    },
+    alloy::primitives::{Address, U64},
+    axum::extract::State,
+    serde::{Deserialize, Serialize},
+    std::{collections::HashMap, sync::Arc},
+    wc::metrics::{future_metrics, FutureExt},
+};
+
+/// Chains of the smart accounts with the bundler support and whether the
+/// sponsoring paymaster is available on the chain
+const SMART_ACCOUNTS_CHAINS: &[(&str, bool)] = &[
+    ("1", true),        // Ethereum
+    ("10", true),       // Optimism
+    ("56", true),       // BNB Smart Chain
+    ("100", true),      // Gnosis
+    ("137", true),      // Polygon
+    ("8453", true),     // Base
+    ("42161", true),    // Arbitrum
+    ("42220", true),    // Celo
+    ("43114", true),    // Avalanche C-Chain
+    ("59144", true),    // Linea
+    ("534352", true),   // Scroll
+    ("80002", true),    // Polygon Amoy
+    ("84532", true),    // Base Sepolia
+    ("421614", true),   // Arbitrum Sepolia
+    ("11155111", true), // Ethereum Sepolia
+    ("11155420", true), // Optimism Sepolia
+];
+
+/// EIP-5792 `wallet_getCapabilities` request params with the optional
+/// list of the requested chains
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum GetCapabilitiesRequest {
+    WithChains(Address, Vec<U64>),
+    AccountOnly((Address,)),
+}
+
+/// Capabilities keyed by the hex encoded chain ID
+pub type GetCapabilitiesResponse = HashMap<String, Capabilities>;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Capabilities {
+    pub atomic: AtomicCapability,
+    pub paymaster_service: SupportedCapability,
+    pub permissions: SupportedCapability,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct AtomicCapability {
+    pub status: AtomicStatus,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AtomicStatus {
+    Supported,
+    Unsupported,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SupportedCapability {
+    pub supported: bool,
+}
+
+pub async fn handler(
+    state: State<Arc<AppState>>,
+    project_id: String,
+    request: GetCapabilitiesRequest,
+) -> GetCapabilitiesResponse {
+    handler_internal(state, project_id, request)
+        .with_metrics(future_metrics!("handler_task", "name" => "wallet_get_capabilities"))
+        .await
+}
+
+#[tracing::instrument(skip(state), level = "debug")]
+async fn handler_internal(
+    State(state): State<Arc<AppState>>,
+    _project_id: String,
+    request: GetCapabilitiesRequest,
+) -> GetCapabilitiesResponse {
+    let requested_chains = match request {
+        GetCapabilitiesRequest::WithChains(_, chains) if !chains.is_empty() => Some(
+            chains
+                .into_iter()
+                .map(|chain| chain.to::<u64>().to_string())
+                .collect::<Vec<_>>(),
+        ),
+        _ => None,
+    };
+
+    let providers = state.providers();
+    let supported_chains = providers
+        .rpc_supported_chains
+        .http
+        .iter()
+        .filter_map(|caip2_chain_id| {
+            let (namespace, chain_id) = disassemble_caip2(caip2_chain_id).ok()?;
+            (namespace == CaipNamespaces::Eip155).then_some(chain_id)
+        })
+        .filter(|chain_id| {
+            requested_chains
+                .as_ref()
+                .is_none_or(|requested| requested.contains(chain_id))
+        });
+
+    let mut response = HashMap::new();
+    for chain_id in supported_chains {
+        let Ok(chain_id_number) = chain_id.parse::<u64>() else {
+            continue;
+        };
+        response.insert(
+            format!("{chain_id_number:#x}"),
+            get_chain_capabilities(&state, &chain_id),
+        );
+    }
+    response
+}
+
+/// Derive the chain capabilities from the smart accounts chains support and
+/// the configured bundler and paymaster providers
+fn get_chain_capabilities(state: &AppState, chain_id: &str) -> Capabilities {
+    let chain_support = SMART_ACCOUNTS_CHAINS
+        .iter()
+        .find(|(supported_chain_id, _)| *supported_chain_id == chain_id)
+        .map(|(_, paymaster)| *paymaster);
+    let bundler = &state.providers().bundler_ops_provider;
+    let atomic_supported = chain_support.is_some()
+        && bundler.supports_bundler_op(chain_id, &SupportedBundlerOps::EthSendUserOperation);
+    let paymaster_supported = atomic_supported
+        && chain_support == Some(true)
+        && bundler.supports_bundler_op(chain_id, &SupportedBundlerOps::PmGetPaymasterData)
+        && bundler.supports_bundler_op(chain_id, &SupportedBundlerOps::PmGetPaymasterStubData);
+    // Session keys are co-signed by the sessions service which requires IRN
+    let permissions_supported = atomic_supported && state.persistent_storage.is_some();
+
+    Capabilities {
+        atomic: AtomicCapability {
+            status: if atomic_supported {
+                AtomicStatus::Supported
+            } else {
+                AtomicStatus::Unsupported
+            },
+        },
+        paymaster_service: SupportedCapability {
+            supported: paymaster_supported,
+        },
+        permissions: SupportedCapability {
+            supported: permissions_supported,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, alloy::primitives::address};
+
+    #[test]
+    fn deserialize_request() {
+        let request: GetCapabilitiesRequest = serde_json::from_value(serde_json::json!([
+            "0xd46e8dd67c5d32be8058bb8eb970870f07244567"
+        ]))
+        .unwrap();
+        assert!(matches!(
+            request,
+            GetCapabilitiesRequest::AccountOnly((account,))
+                if account == address!("d46e8dd67c5d32be8058bb8eb970870f07244567")
+        ));
+
+        let request: GetCapabilitiesRequest = serde_json::from_value(serde_json::json!([
+            "0xd46e8dd67c5d32be8058bb8eb970870f07244567",
+            ["0x1", "0x2105"]
+        ]))
+        .unwrap();
+        assert!(matches!(
+            request,
+            GetCapabilitiesRequest::WithChains(_, chains)
+                if chains == vec![U64::from(1), U64::from(8453)]
+        ));
+    }
+}
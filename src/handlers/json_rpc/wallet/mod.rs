@@ -1,6 +1,8 @@
+pub mod build_approval;
 pub mod call_id;
 pub mod get_assets;
 pub mod get_calls_status;
+pub mod get_capabilities;
 pub mod prepare_calls;
 pub mod send_prepared_calls;
 mod types;
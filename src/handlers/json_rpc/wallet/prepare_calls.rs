@@ -97,6 +97,9 @@ pub struct Permissions {
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct PaymasterService {
+    /// Client-supplied ERC-7677 paymaster. The project's sponsorship policy is
+    /// enforced only when it's the `/v1/bundler` endpoint of the project, the
+    /// other paymasters are sponsoring by their own policies.
     url: Url,
 }
 
@@ -105,9 +108,9 @@ pub type PrepareCallsResponse = Vec<PrepareCallsResponseItem>;
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct PrepareCallsResponseItem {
-    prepared_calls: PreparedCalls,
+    pub prepared_calls: PreparedCalls,
     signature_request: SignatureRequest,
-    context: Uuid,
+    pub context: Uuid,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -199,8 +202,8 @@ pub enum PrepareCallsInternalError {
     #[error("Compress session enabled: {0}")]
     CompressSessionEnabled(fastlz_rs::CompressError),
 
-    #[error("IRN not configured")]
-    IrnNotConfigured,
+    #[error("Persistent storage not configured")]
+    StorageNotConfigured,
 
     #[error("Get session context: {0}")]
     GetSessionContextError(InternalGetSessionContextError),
@@ -265,14 +268,16 @@ async fn handler_internal(
             .expect("Failed to parse provider URL"),
         );
 
-        let irn_client = state.irn.as_ref().ok_or(PrepareCallsError::InternalError(
-            PrepareCallsInternalError::IrnNotConfigured,
-        ))?;
+        let irn_client = state
+            .persistent_storage
+            .as_ref()
+            .ok_or(PrepareCallsError::InternalError(
+                PrepareCallsInternalError::StorageNotConfigured,
+            ))?;
         let context = get_session_context(
             format!("{}:{}", chain_id.caip2_identifier(), request.from),
             request.capabilities.permissions.context,
             irn_client,
-            &state.metrics,
         )
         .await
         .map_err(|e| match e {
@@ -314,7 +319,7 @@ async fn handler_internal(
         let pimlico_client = BundlerClient::new(BundlerConfig::new(bundler_url.clone()));
         let bundler_provider = BundlerRpcClient::new(bundler_url);
 
-        // TODO cache this
+        // Cached per chain by the bundler endpoint
         let gas_price = pimlico_client
             .estimate_user_operation_gas_price()
             .await
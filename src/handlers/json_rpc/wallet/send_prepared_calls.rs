@@ -52,9 +52,9 @@ pub type SendPreparedCallsRequest = Vec<SendPreparedCallsRequestItem>;
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct SendPreparedCallsRequestItem {
-    prepared_calls: PreparedCalls,
-    signature: Bytes,
-    context: Uuid,
+    pub prepared_calls: PreparedCalls,
+    pub signature: Bytes,
+    pub context: Uuid,
 }
 
 pub type SendPreparedCallsResponse = Vec<CallId>;
@@ -115,8 +115,8 @@ pub enum SendPreparedCallsError {
 
 #[derive(Error, Debug)]
 pub enum SendPreparedCallsInternalError {
-    #[error("IRN not configured")]
-    IrnNotConfigured,
+    #[error("Persistent storage not configured")]
+    StorageNotConfigured,
 
     #[error("Cosign: {0}")]
     Cosign(String),
@@ -381,10 +381,10 @@ async fn handler_internal(
         );
 
         let irn_client = state
-            .irn
+            .persistent_storage
             .as_ref()
             .ok_or(SendPreparedCallsError::InternalError(
-                SendPreparedCallsInternalError::IrnNotConfigured,
+                SendPreparedCallsInternalError::StorageNotConfigured,
             ))?;
         let context = get_session_context(
             format!(
@@ -394,7 +394,6 @@ async fn handler_internal(
             ),
             request.context,
             irn_client,
-            &state.metrics,
         )
         .await
         .map_err(|e| match e {
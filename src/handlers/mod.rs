@@ -1,32 +1,56 @@
 use {
-    crate::{analytics::MessageSource, error::RpcError, state::AppState, utils::network},
+    crate::{
+        analytics::{MessageSource, RateLimitedInfo},
+        error::{ErrorCode, ProblemDetails, RpcError},
+        state::AppState,
+        utils::{
+            crypto, network, project_allowlist::validate_project_allowlist, project_jwt,
+            project_signature,
+            rate_limit::{RateLimitExceeded, RateLimitStatus},
+        },
+    },
     axum::{
-        extract::{MatchedPath, Request, State},
+        body::{to_bytes, Body},
+        extract::{ConnectInfo, MatchedPath, Query, Request, State},
+        http::{header, HeaderValue, StatusCode},
         middleware::Next,
         response::{IntoResponse, Response},
     },
     serde::{Deserialize, Serialize},
-    std::{fmt::Display, sync::Arc, time::Instant},
-    tracing::error,
+    std::{fmt::Display, net::SocketAddr, sync::Arc, time::Instant},
+    tracing::{debug, error},
 };
 
 pub mod balance;
 pub mod bundler;
+pub mod bundler_wait;
+pub mod cache_invalidation;
 pub mod chain_agnostic;
 pub mod convert;
 pub mod fungible_price;
 pub mod generators;
+pub mod graphql;
 pub mod health;
 pub mod history;
 pub mod identity;
 pub mod json_rpc;
 pub mod onramp;
+pub mod payment_links;
 pub mod portfolio;
+pub mod pos_webhook;
+pub mod pprof;
 pub mod profile;
+pub mod providers_health;
+pub mod providers_overrides;
+pub mod providers_weights;
 pub mod proxy;
 pub mod self_provider;
 pub mod sessions;
+pub mod shadow;
+pub mod sponsorship;
+pub mod subscriptions;
 pub mod supported_chains;
+pub mod token_metadata;
 pub mod ws_proxy;
 
 // TODO: Remove this once Dune Rootstock support is fixed
@@ -34,6 +58,18 @@ pub mod ws_proxy;
 pub const ROOTSTOCK_MAINNET_CHAIN_ID: &str = "eip155:30";
 pub const ROOTSTOCK_TESTNET_CHAIN_ID: &str = "eip155:31";
 
+const PROBLEM_JSON_CONTENT_TYPE: &str = "application/problem+json";
+/// Error response bodies are small, the larger bodies are not converted
+const ERROR_BODY_MAX_BYTES: usize = 64 * 1024;
+/// Maximum body size of the signed requests buffered for the verification
+const SIGNED_REQUEST_BODY_MAX_BYTES: usize = 1024 * 1024;
+/// Paths of the JSON-RPC handler multiplexing the wallet, pay and POS methods,
+/// which are rate-limited by the method
+const JSON_RPC_METHODS_PATHS: [&str; 2] = ["/v1/wallet", "/v1/json-rpc"];
+/// Maximum body size of the JSON-RPC requests buffered for the rate limiting,
+/// same as the default body limit of the handlers
+const JSON_RPC_REQUEST_BODY_MAX_BYTES: usize = 2 * 1024 * 1024;
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct SdkInfoParams {
@@ -91,8 +127,22 @@ impl Display for SupportedCurrencies {
     }
 }
 
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ProjectQueryParams {
+    project_id: Option<String>,
+    chain_id: Option<String>,
+}
+
 /// Rate limit middleware that uses `rate_limiting`` token bucket sub crate
-/// from the `utils-rs`. IP address and matched path are used as the token key.
+/// from the `utils-rs`. IP address and matched path are used as the token key,
+/// the JSON-RPC handler paths are keyed by the request method as well.
+/// Requests of the validated projects are also limited by the project's token
+/// bucket after the IP one, the project is validated by the project JWT or by
+/// the cached project data only. The unvalidated project IDs are charged to
+/// the IP bucket only to not drain the buckets of the other projects. Projects
+/// of the plan tiers with the configured limits are using the tier's token
+/// bucket and the projects above the plan limits are rejected.
 pub async fn rate_limit_middleware(
     State(state): State<Arc<AppState>>,
     req: Request,
@@ -119,9 +169,13 @@ pub async fn rate_limit_middleware(
             return next.run(req).await;
         }
     };
-    // TODO: Get the project ID from the request path and add analytics for the
-    // rate-limited requests for project ID.
-    let project_id = None;
+    let (project_id, chain_id) = Query::<ProjectQueryParams>::try_from_uri(req.uri())
+        .map(|Query(query_params)| (query_params.project_id, query_params.chain_id))
+        .unwrap_or_default();
+    let jwt_project_id = req
+        .extensions()
+        .get::<project_jwt::VerifiedProjectId>()
+        .map(|verified| verified.0.clone());
 
     let rate_limit = match state.rate_limit.as_ref() {
         Some(rate_limit) => rate_limit,
@@ -133,15 +187,446 @@ pub async fn rate_limit_middleware(
             return next.run(req).await;
         }
     };
+    if rate_limit.is_whitelisted(&ip) {
+        return next.run(req).await;
+    }
+
+    // JSON-RPC methods are multiplexed by the same path, so the method of the
+    // buffered request body is the part of the token key
+    let (req, endpoint) = if JSON_RPC_METHODS_PATHS.contains(&path.as_str()) {
+        let (parts, body) = req.into_parts();
+        let body = match to_bytes(body, JSON_RPC_REQUEST_BODY_MAX_BYTES).await {
+            Ok(body) => body,
+            Err(e) => return RpcError::InvalidParameter(e.to_string()).into_response(),
+        };
+        let endpoint = json_rpc::handler::rate_limit_endpoint(path.as_str(), &body);
+        (Request::from_parts(parts, Body::from(body)), endpoint)
+    } else {
+        (req, path.as_str().to_owned())
+    };
 
-    let is_rate_limited_result = rate_limit
-        .is_rate_limited(path.as_str(), &ip, project_id)
-        .await;
+    let rate_limited = |e: RateLimitExceeded| {
+        if let Some(project_id) = &project_id {
+            state.analytics.rate_limited(RateLimitedInfo::new(
+                project_id.clone(),
+                endpoint.clone(),
+                ip.clone(),
+                e.bucket.to_string(),
+            ));
+        }
+        RpcError::from(e)
+    };
+    let is_rate_limited_result = async {
+        let ip_status = rate_limit
+            .is_ip_rate_limited(&endpoint, &ip)
+            .await
+            .map_err(rate_limited)?;
+        let Some(project_id) = project_id.as_deref() else {
+            return Ok(ip_status);
+        };
+        let project = state
+            .registry
+            .cached_project_data(project_id)
+            .await
+            .filter(|project| project.data.validate_access(project_id, None).is_ok());
+        if project.is_none() && jwt_project_id.as_deref() != Some(project_id) {
+            return Ok(ip_status);
+        }
+        let limits = project.and_then(|project| project.limits);
+
+        // Projects above the plan limits are rejected with the upgrade hint
+        // before spending the project bucket
+        if let Some(limits) = &limits {
+            let skip_quota = chain_id
+                .as_ref()
+                .is_some_and(|chain_id| state.config.server.skip_quota_chains.contains(chain_id));
+            if state.config.server.validate_project_id && limits.is_above_rpc_limit && !skip_quota
+            {
+                state.metrics.add_rate_limited_response();
+                return Err(RpcError::PlanLimitReached(limits.tier.clone()));
+            }
+        }
+
+        let tier = limits.map(|limits| limits.tier);
+        let project_status = rate_limit
+            .is_project_rate_limited(&endpoint, project_id, tier.as_deref())
+            .await
+            .map_err(rate_limited)?;
+        Ok(RateLimitStatus::most_exhausted(ip_status, project_status))
+    }
+    .await;
 
     match is_rate_limited_result {
+        Ok(Some(status)) if status.is_nearly_exhausted() => {
+            let mut response = next.run(req).await;
+            response
+                .headers_mut()
+                .extend(status.headers(chrono::Utc::now().timestamp() as u64));
+            response
+        }
         Ok(_) => next.run(req).await,
-        Err(e) => RpcError::from(e).into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+/// Geo-blocking middleware that rejects the requests from the blocked
+/// countries. The blocked countries list is reloadable, requests with the
+/// unresolved location are allowed. The chains and endpoints restricted in the
+/// country and the project's countries policy are evaluated after the global
+/// blocked countries.
+pub async fn geoblock_middleware(
+    State(state): State<Arc<AppState>>,
+    req: Request,
+    next: Next,
+) -> Response {
+    let ip = network::get_forwarded_ip(req.headers()).or_else(|| {
+        req.extensions()
+            .get::<ConnectInfo<SocketAddr>>()
+            .map(|connect_info| connect_info.0.ip())
+    });
+    let country = ip
+        .and_then(|ip| state.analytics.lookup_geo_data(ip))
+        .and_then(|geo| geo.country);
+
+    let query_params = Query::<ProjectQueryParams>::try_from_uri(req.uri())
+        .ok()
+        .map(|Query(query_params)| query_params);
+    if let Some(country) = &country {
+        let config = state.reloadable_config();
+        if config
+            .blocked_countries
+            .iter()
+            .any(|blocked| blocked.eq_ignore_ascii_case(country))
+        {
+            debug!("Blocked request from the country: {country}");
+            return StatusCode::UNAUTHORIZED.into_response();
+        }
+
+        let chain_id = query_params
+            .as_ref()
+            .and_then(|query_params| query_params.chain_id.as_deref());
+        if let Some(target) =
+            config
+                .geo_restrictions
+                .restricted_target(country, chain_id, req.uri().path())
+        {
+            debug!("Restricted request to {target} from the country: {country}");
+            return RpcError::RestrictedInCountry(target.to_owned()).into_response();
+        }
+    }
+
+    if let Some(project_id) = query_params.and_then(|query_params| query_params.project_id) {
+        if let Err(e) = state
+            .validate_project_country(&project_id, country.as_deref())
+            .await
+        {
+            return e.into_response();
+        }
+    }
+    next.run(req).await
+}
+
+/// Admin endpoints authentication middleware, the `Authorization: Bearer`
+/// token must match the configured admin API token
+pub async fn admin_auth_middleware(
+    State(state): State<Arc<AppState>>,
+    req: Request,
+    next: Next,
+) -> Response {
+    let Some(admin_api_token) = &state.config.server.admin_api_token else {
+        return RpcError::InvalidAdminToken.into_response();
+    };
+    let is_authorized = project_jwt::bearer_token(req.headers())
+        .is_some_and(|token| crypto::constant_time_eq(token, admin_api_token));
+    if !is_authorized {
+        return RpcError::InvalidAdminToken.into_response();
+    }
+    next.run(req).await
+}
+
+/// Profiling endpoints authentication middleware, the `Authorization: Bearer`
+/// token must match the configured profiler secret
+pub async fn profiler_auth_middleware(
+    State(state): State<Arc<AppState>>,
+    req: Request,
+    next: Next,
+) -> Response {
+    let Some(secret) = &state.config.profiler.secret else {
+        return RpcError::InvalidProfilerSecret.into_response();
+    };
+    let is_authorized = project_jwt::bearer_token(req.headers())
+        .is_some_and(|token| crypto::constant_time_eq(token, secret));
+    if !is_authorized {
+        return RpcError::InvalidProfilerSecret.into_response();
+    }
+    next.run(req).await
+}
+
+/// Project allowlist middleware that rejects the requests with the origin,
+/// bundle ID or package name not matching the project's configuration.
+/// Project data is served from the registry cache and the registry errors are
+/// skipping the check, the project access is validated by the handlers.
+pub async fn project_allowlist_middleware(
+    State(state): State<Arc<AppState>>,
+    req: Request,
+    next: Next,
+) -> Response {
+    let Some(project_id) = Query::<ProjectQueryParams>::try_from_uri(req.uri())
+        .ok()
+        .and_then(|Query(query_params)| query_params.project_id)
+    else {
+        return next.run(req).await;
+    };
+
+    match state.registry.project_data(&project_id).await {
+        Ok(project) => {
+            if let Err(e) = validate_project_allowlist(&project.data, req.headers()) {
+                debug!("Denied access for project: {project_id}, with reason: {e}");
+                state.metrics.add_rejected_project();
+                return e.into_response();
+            }
+        }
+        Err(e) => {
+            debug!("Failed to get project data in project allowlist middleware: {e}");
+        }
+    }
+    next.run(req).await
+}
+
+/// Project IP allowlist middleware that rejects the requests from the source
+/// IP addresses not in the project's IP ranges allowlist, so the server-side
+/// project keys can't be used from the other networks when leaked.
+pub async fn project_ip_allowlist_middleware(
+    State(state): State<Arc<AppState>>,
+    req: Request,
+    next: Next,
+) -> Response {
+    let Some(project_id) = Query::<ProjectQueryParams>::try_from_uri(req.uri())
+        .ok()
+        .and_then(|Query(query_params)| query_params.project_id)
+    else {
+        return next.run(req).await;
+    };
+
+    let ip = network::get_forwarded_ip(req.headers()).or_else(|| {
+        req.extensions()
+            .get::<ConnectInfo<SocketAddr>>()
+            .map(|connect_info| connect_info.0.ip())
+    });
+    if let Err(e) = state.validate_project_ip(&project_id, ip).await {
+        return e.into_response();
     }
+    next.run(req).await
+}
+
+/// Project JWT authentication middleware, the `Authorization: Bearer` project
+/// JWT is an alternative to the `projectId` query parameter to not leak the
+/// project ID in the URLs. The verified project ID is added to the request
+/// query for the handlers and the following middlewares.
+pub async fn project_jwt_middleware(
+    State(state): State<Arc<AppState>>,
+    mut req: Request,
+    next: Next,
+) -> Response {
+    let Some(token) = project_jwt::bearer_token(req.headers()).map(ToOwned::to_owned) else {
+        return next.run(req).await;
+    };
+    let project_id = match state.validate_project_jwt(&token).await {
+        Ok(Some(project_id)) => project_id,
+        // Not a project JWT, leaving the authorization to the handler
+        Ok(None) => return next.run(req).await,
+        Err(e) => return e.into_response(),
+    };
+
+    let query_project_id = Query::<ProjectQueryParams>::try_from_uri(req.uri())
+        .ok()
+        .and_then(|Query(query_params)| query_params.project_id);
+    match query_project_id {
+        Some(query_project_id) if query_project_id != project_id => {
+            return RpcError::InvalidProjectJwt(
+                "token subject doesn't match the projectId query parameter".to_owned(),
+            )
+            .into_response();
+        }
+        Some(_) => {}
+        None => match project_jwt::with_project_id(req.uri(), &project_id) {
+            Ok(uri) => *req.uri_mut() = uri,
+            Err(e) => return e.into_response(),
+        },
+    }
+    req.extensions_mut()
+        .insert(project_jwt::VerifiedProjectId(project_id));
+    next.run(req).await
+}
+
+/// Project request signature middleware of the sensitive endpoints, the
+/// requests must be authorized by the project JWT or signed by the project
+/// key when required by the config. Must be applied after the project JWT
+/// middleware, which rejects the invalid tokens.
+pub async fn project_signature_middleware(
+    State(state): State<Arc<AppState>>,
+    req: Request,
+    next: Next,
+) -> Response {
+    let has_project_jwt = project_jwt::bearer_token(req.headers())
+        .and_then(project_jwt::decode_unverified)
+        .is_some();
+    if has_project_jwt {
+        return next.run(req).await;
+    }
+
+    let headers = match project_signature::signature_headers(req.headers()) {
+        Some(Ok(headers)) => headers,
+        Some(Err(e)) => return e.into_response(),
+        None if state.config.server.require_project_signature => {
+            return RpcError::InvalidProjectSignature("request is not signed".to_owned())
+                .into_response()
+        }
+        None => return next.run(req).await,
+    };
+    let Some(project_id) = Query::<ProjectQueryParams>::try_from_uri(req.uri())
+        .ok()
+        .and_then(|Query(query_params)| query_params.project_id)
+    else {
+        return RpcError::InvalidProjectSignature("missing projectId".to_owned()).into_response();
+    };
+    let project = match state.registry.project_data(&project_id).await {
+        Ok(project) => project,
+        Err(e) => return e.into_response(),
+    };
+
+    let (parts, body) = req.into_parts();
+    let body = match to_bytes(body, SIGNED_REQUEST_BODY_MAX_BYTES).await {
+        Ok(body) => body,
+        Err(e) => return RpcError::InvalidParameter(e.to_string()).into_response(),
+    };
+    let path_and_query = parts
+        .uri
+        .path_and_query()
+        .map(|path_and_query| path_and_query.as_str())
+        .unwrap_or_else(|| parts.uri.path());
+    if let Err(e) = project_signature::verify(
+        &project.data,
+        &headers,
+        chrono::Utc::now().timestamp(),
+        parts.method.as_str(),
+        path_and_query,
+        &body,
+    ) {
+        debug!("Denied signed request for project: {project_id}, with reason: {e}");
+        state.metrics.add_rejected_project();
+        return e.into_response();
+    }
+    next.run(Request::from_parts(parts, Body::from(body))).await
+}
+
+/// Request body size limit middleware of the RPC proxy and bundler
+/// endpoints, the limit depends on the project's plan tier. The body is
+/// buffered up to the limit, so the oversized payloads are rejected before
+/// reaching the handlers. The requests are passed as is when the tier's limit
+/// is not configured.
+pub async fn request_body_limit_middleware(
+    State(state): State<Arc<AppState>>,
+    req: Request,
+    next: Next,
+) -> Response {
+    if !state.config.server.request_body_limited() {
+        return next.run(req).await;
+    }
+
+    let project_id = Query::<ProjectQueryParams>::try_from_uri(req.uri())
+        .ok()
+        .and_then(|Query(query_params)| query_params.project_id);
+    // The project access is validated by the handlers, so the registry errors
+    // are falling back to the free plan limit
+    let mut tier = None;
+    if let Some(project_id) = project_id {
+        match state.registry.project_data(&project_id).await {
+            Ok(project) => tier = Some(project.limits.tier),
+            Err(e) => debug!("Failed to get project data in request body limit middleware: {e}"),
+        }
+    }
+    let Some(limit) = state.config.server.request_body_max_bytes(tier.as_deref()) else {
+        return next.run(req).await;
+    };
+
+    let content_length = req
+        .headers()
+        .get(header::CONTENT_LENGTH)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse::<usize>().ok());
+    if content_length.is_some_and(|content_length| content_length > limit) {
+        state.metrics.add_request_body_too_large();
+        return RpcError::RequestBodyTooLarge(limit).into_response();
+    }
+
+    let (parts, body) = req.into_parts();
+    let body = match to_bytes(body, limit).await {
+        Ok(body) => body,
+        Err(e) => {
+            debug!("Failed to read the request body in request body limit middleware: {e}");
+            state.metrics.add_request_body_too_large();
+            return RpcError::RequestBodyTooLarge(limit).into_response();
+        }
+    };
+    next.run(Request::from_parts(parts, Body::from(body))).await
+}
+
+/// Opt-in RFC 7807 error responses middleware, the handlers errors are served
+/// as the `application/problem+json` to the clients accepting it and in the
+/// legacy shape to the current clients
+pub async fn problem_json_middleware(req: Request, next: Next) -> Response {
+    let accepts_problem_json = req
+        .headers()
+        .get_all(header::ACCEPT)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .any(|value| value.contains(PROBLEM_JSON_CONTENT_TYPE));
+    if !accepts_problem_json {
+        return next.run(req).await;
+    }
+
+    // Query is not included to not leak the project ID
+    let instance = req.uri().path().to_owned();
+    let response = next.run(req).await;
+    let Some(code) = response.extensions().get::<ErrorCode>().copied() else {
+        return response;
+    };
+
+    let (mut parts, body) = response.into_parts();
+    let body = to_bytes(body, ERROR_BODY_MAX_BYTES)
+        .await
+        .unwrap_or_default();
+    let problem = ProblemDetails::new(parts.status, code, &body, instance);
+    let body = match serde_json::to_vec(&problem) {
+        Ok(body) => body,
+        Err(e) => {
+            error!("Failed to serialize the problem details: {e}");
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+    parts.headers.remove(header::CONTENT_LENGTH);
+    parts.headers.insert(
+        header::CONTENT_TYPE,
+        HeaderValue::from_static(PROBLEM_JSON_CONTENT_TYPE),
+    );
+    parts
+        .headers
+        .append(header::VARY, HeaderValue::from_static("accept"));
+    Response::from_parts(parts, Body::from(body))
+}
+
+/// Returns the chain ID and its namespace metrics labels, the chains out of
+/// the supported chains set are reported as `unsupported`
+fn bounded_chain_labels(state: &AppState, chain_id: String) -> (String, String) {
+    let supported_chains = &state.providers().rpc_supported_chains;
+    if !supported_chains.http.contains(&chain_id) && !supported_chains.ws.contains(&chain_id) {
+        return ("unsupported".to_owned(), "unsupported".to_owned());
+    }
+    let namespace = chain_id
+        .split_once(':')
+        .map_or_else(|| chain_id.clone(), |(namespace, _)| namespace.to_owned());
+    (chain_id, namespace)
 }
 
 /// Endpoints latency and response status metrics middleware
@@ -155,21 +640,43 @@ pub async fn status_latency_metrics_middleware(
         .extensions()
         .get::<MatchedPath>()
         .map_or("/unknown".to_string(), |mp| mp.as_str().to_string());
+    let project_query_params = Query::<ProjectQueryParams>::try_from_uri(req.uri())
+        .ok()
+        .map(|Query(query_params)| query_params);
     let request_started = Instant::now();
 
     // Execute the request and get the response.
     let response = next.run(req).await;
     let request_latency = request_started.elapsed();
 
+    // Only the successfully served requests are counted for the usage reporting
+    if let (Some(usage), Some(query_params)) = (&state.usage, &project_query_params) {
+        if let Some(project_id) = &query_params.project_id {
+            if response.status().is_success() {
+                usage.record(project_id, &path, query_params.chain_id.as_deref());
+            }
+        }
+    }
+
     // Record metrics async
     let state_clone = state.clone();
     let path_clone = path.clone();
     let status = response.status().as_u16();
     let latency_secs = request_latency.as_secs_f64();
+    let chain_id = project_query_params.and_then(|query_params| query_params.chain_id);
     tokio::spawn(async move {
         state_clone
             .metrics
             .add_http_call(status, path_clone.clone());
+        if let Some(chain_id) = chain_id {
+            let (chain_id, namespace) = bounded_chain_labels(&state_clone, chain_id);
+            state_clone.metrics.add_chain_http_call(
+                status,
+                path_clone.clone(),
+                chain_id,
+                namespace,
+            );
+        }
         state_clone
             .metrics
             .add_http_latency(status, path_clone, latency_secs);
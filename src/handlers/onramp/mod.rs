@@ -3,4 +3,5 @@ pub mod options;
 pub mod properties;
 pub mod providers;
 pub mod quotes;
+pub mod session;
 pub mod widget;
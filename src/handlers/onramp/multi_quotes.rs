@@ -67,7 +67,7 @@ async fn handler_internal(
 
     let exclude_providers = request_payload.exclude_providers.clone();
     let mut quotes = state
-        .providers
+        .providers()
         .onramp_multi_provider
         .get_quotes(request_payload, state.metrics.clone())
         .await
@@ -87,7 +87,7 @@ async fn handler_internal(
         .await?;
 
     let buy_options = state
-        .providers
+        .providers()
         .onramp_provider
         .get_buy_options(query.0, state.metrics.clone())
         .await
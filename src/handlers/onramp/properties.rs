@@ -53,7 +53,7 @@ async fn handler_internal(
         .await?;
 
     let providers_properties = state
-        .providers
+        .providers()
         .onramp_multi_provider
         .get_providers_properties(query.0, state.metrics.clone())
         .await
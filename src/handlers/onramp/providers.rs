@@ -58,7 +58,7 @@ async fn handler_internal(
         .await?;
 
     let providers_response = state
-        .providers
+        .providers()
         .onramp_multi_provider
         .get_providers(query.0, state.metrics.clone())
         .await
@@ -83,7 +83,7 @@ async fn handler_internal(
         .await?;
 
     let buy_quotes = state
-        .providers
+        .providers()
         .onramp_provider
         .get_buy_quotes(query.0, state.metrics.clone())
         .await
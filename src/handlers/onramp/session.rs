@@ -0,0 +1,91 @@
+use {
+    crate::{
+        database::onramp_sessions::{self, OnrampSession},
+        error::RpcError,
+        state::AppState,
+    },
+    axum::{
+        extract::{Path, Query, State},
+        response::{IntoResponse, Response},
+        Json,
+    },
+    chrono::Utc,
+    serde::{Deserialize, Serialize},
+    std::sync::Arc,
+    wc::metrics::{future_metrics, FutureExt},
+};
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct QueryParams {
+    pub project_id: String,
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum OnrampSessionStatus {
+    Active,
+    Expired,
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct OnrampSessionResponse {
+    pub id: String,
+    pub status: OnrampSessionStatus,
+    pub service_provider: String,
+    pub session_data: serde_json::Value,
+    /// The widget URL is returned for the active sessions only
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub widget_url: Option<String>,
+    pub expires_at: i64,
+    pub created_at: i64,
+}
+
+impl From<OnrampSession> for OnrampSessionResponse {
+    fn from(session: OnrampSession) -> Self {
+        let status = if session.expires_at > Utc::now() {
+            OnrampSessionStatus::Active
+        } else {
+            OnrampSessionStatus::Expired
+        };
+        Self {
+            widget_url: (status == OnrampSessionStatus::Active).then_some(session.widget_url),
+            status,
+            id: session.id,
+            service_provider: session.service_provider,
+            session_data: session.session_data.0,
+            expires_at: session.expires_at.timestamp(),
+            created_at: session.created_at.timestamp(),
+        }
+    }
+}
+
+pub async fn handler(
+    state: State<Arc<AppState>>,
+    id: Path<String>,
+    query: Query<QueryParams>,
+) -> Result<Response, RpcError> {
+    handler_internal(state, id, query)
+        .with_metrics(future_metrics!("handler_task", "name" => "onramp_session"))
+        .await
+}
+
+#[tracing::instrument(skip(state), level = "debug")]
+async fn handler_internal(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+    Query(query): Query<QueryParams>,
+) -> Result<Response, RpcError> {
+    state
+        .validate_project_access_and_quota(&query.project_id)
+        .await?;
+
+    // Sessions of the other projects are not disclosed
+    let session = onramp_sessions::get_onramp_session(&state.postgres, &id)
+        .await?
+        .filter(|session| session.project_id == query.project_id)
+        .ok_or_else(|| RpcError::OnrampSessionNotFound(id.clone()))?;
+
+    Ok(Json(OnrampSessionResponse::from(session)).into_response())
+}
@@ -1,10 +1,16 @@
 use {
-    crate::{error::RpcError, state::AppState, utils::simple_request_json::SimpleRequestJson},
+    crate::{
+        database::onramp_sessions::{self, NewOnrampSession},
+        error::RpcError,
+        state::AppState,
+        utils::{generate_random_string, simple_request_json::SimpleRequestJson},
+    },
     axum::{
         extract::State,
         response::{IntoResponse, Response},
         Json,
     },
+    chrono::Utc,
     serde::{Deserialize, Serialize},
     std::sync::Arc,
     tap::TapFallible,
@@ -12,11 +18,20 @@ use {
     wc::metrics::{future_metrics, FutureExt},
 };
 
+/// Length of the onramp session random ID
+pub const ONRAMP_SESSION_ID_LENGTH: usize = 16;
+
+/// Time the onramp session can be resumed for
+pub const ONRAMP_SESSION_TTL: chrono::Duration = chrono::Duration::hours(1);
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct QueryParams {
     pub project_id: String,
     pub session_data: SessionData,
+    /// Onramp session ID passed to the provider as the external session ID
+    #[serde(skip)]
+    pub session_id: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -49,6 +64,16 @@ pub struct WidgetResponse {
     pub widget_url: String,
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct WidgetSessionResponse {
+    #[serde(flatten)]
+    pub widget: WidgetResponse,
+    pub session_id: String,
+    /// Unix timestamp in seconds until the session can be resumed
+    pub expires_at: i64,
+}
+
 pub async fn handler(
     state: State<Arc<AppState>>,
     SimpleRequestJson(request_payload): SimpleRequestJson<QueryParams>,
@@ -61,20 +86,43 @@ pub async fn handler(
 #[tracing::instrument(skip_all, level = "debug")]
 async fn handler_internal(
     state: State<Arc<AppState>>,
-    request_payload: QueryParams,
+    mut request_payload: QueryParams,
 ) -> Result<Response, RpcError> {
     state
         .validate_project_access_and_quota(&request_payload.project_id)
         .await?;
 
+    let session_id = generate_random_string(ONRAMP_SESSION_ID_LENGTH);
+    request_payload.session_id = Some(session_id.clone());
     let widget_response = state
-        .providers
+        .providers()
         .onramp_multi_provider
-        .get_widget(request_payload, state.metrics.clone())
+        .get_widget(request_payload.clone(), state.metrics.clone())
         .await
         .tap_err(|e| {
             error!("Failed to call onramp widget with {e}");
         })?;
 
-    Ok(Json(widget_response).into_response())
+    let session_data = serde_json::to_value(&request_payload.session_data)
+        .map_err(|e| RpcError::Other(anyhow::anyhow!("Failed to serialize session data: {e}")))?;
+    let session = onramp_sessions::insert_onramp_session(
+        &state.postgres,
+        NewOnrampSession {
+            id: &session_id,
+            project_id: &request_payload.project_id,
+            wallet_address: &request_payload.session_data.wallet_address.to_lowercase(),
+            service_provider: &request_payload.session_data.service_provider,
+            session_data,
+            widget_url: &widget_response.widget_url,
+            expires_at: Utc::now() + ONRAMP_SESSION_TTL,
+        },
+    )
+    .await?;
+
+    Ok(Json(WidgetSessionResponse {
+        widget: widget_response,
+        session_id: session.id,
+        expires_at: session.expires_at.timestamp(),
+    })
+    .into_response())
 }
@@ -0,0 +1,90 @@
+use {
+    super::{
+        get_project_payment_link, payment_link_status, pos_error_to_rpc_error, PaymentLinkStatus,
+        QueryParams,
+    },
+    crate::{
+        database::payment_links,
+        error::RpcError,
+        handlers::json_rpc::pos::{build_transactions, BuildTransactionParams, PaymentIntent},
+        state::AppState,
+        utils::simple_request_json::SimpleRequestJson,
+    },
+    axum::{
+        extract::{Path, Query, State},
+        response::{IntoResponse, Response},
+        Json,
+    },
+    serde::Deserialize,
+    std::sync::Arc,
+    wc::metrics::{future_metrics, FutureExt},
+};
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct BuildPaymentLinkRequest {
+    /// CAIP-10 payer account
+    pub sender: String,
+    pub capabilities: Option<serde_json::Value>,
+}
+
+pub async fn handler(
+    state: State<Arc<AppState>>,
+    id: Path<String>,
+    query_params: Query<QueryParams>,
+    SimpleRequestJson(request_payload): SimpleRequestJson<BuildPaymentLinkRequest>,
+) -> Result<Response, RpcError> {
+    handler_internal(state, id, query_params, request_payload)
+        .with_metrics(future_metrics!("handler_task", "name" => "payment_links_build"))
+        .await
+}
+
+#[tracing::instrument(skip(state), level = "debug")]
+async fn handler_internal(
+    state: State<Arc<AppState>>,
+    Path(id): Path<String>,
+    Query(query_params): Query<QueryParams>,
+    request_payload: BuildPaymentLinkRequest,
+) -> Result<Response, RpcError> {
+    let project_id = query_params.project_id;
+    state.validate_project_access_and_quota(&project_id).await?;
+
+    let link = get_project_payment_link(&state, &id, &project_id).await?;
+    match payment_link_status(&link) {
+        PaymentLinkStatus::Pending => {}
+        PaymentLinkStatus::Paid => {
+            return Err(RpcError::PaymentLinkNotPayable(format!(
+                "payment link {id} is already paid"
+            )))
+        }
+        PaymentLinkStatus::Expired => {
+            return Err(RpcError::PaymentLinkNotPayable(format!(
+                "payment link {id} is expired"
+            )))
+        }
+    }
+
+    let result = build_transactions::handler(
+        state.clone(),
+        project_id,
+        BuildTransactionParams {
+            payment_intents: vec![PaymentIntent {
+                asset: link.asset,
+                amount: link.amount,
+                recipient: link.recipient,
+                sender: request_payload.sender,
+                price: None,
+            }],
+            capabilities: request_payload.capabilities,
+        },
+    )
+    .await
+    .map_err(|e| pos_error_to_rpc_error(e.is_internal(), e))?;
+
+    // Tracking the latest built transaction to check the link fulfillment
+    if let Some(transaction) = result.transactions.first() {
+        payment_links::set_transaction_id(&state.postgres, &id, &transaction.id).await?;
+    }
+
+    Ok(Json(result).into_response())
+}
@@ -0,0 +1,84 @@
+use {
+    super::{
+        get_payment_link, get_project_payment_link, pos_error_to_rpc_error, PaymentLinkResponse,
+        QueryParams,
+    },
+    crate::{
+        database::payment_links,
+        error::RpcError,
+        handlers::json_rpc::pos::{
+            check_transaction, CheckTransactionParams, CheckTransactionResult, TransactionStatus,
+        },
+        state::AppState,
+        utils::simple_request_json::SimpleRequestJson,
+    },
+    axum::{
+        extract::{Path, Query, State},
+        response::{IntoResponse, Response},
+        Json,
+    },
+    serde::Serialize,
+    std::sync::Arc,
+    tracing::info,
+    wc::metrics::{future_metrics, FutureExt},
+};
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CheckPaymentLinkResponse {
+    #[serde(flatten)]
+    pub transaction: CheckTransactionResult,
+    pub payment_link: PaymentLinkResponse,
+}
+
+pub async fn handler(
+    state: State<Arc<AppState>>,
+    id: Path<String>,
+    query_params: Query<QueryParams>,
+    SimpleRequestJson(request_payload): SimpleRequestJson<CheckTransactionParams>,
+) -> Result<Response, RpcError> {
+    handler_internal(state, id, query_params, request_payload)
+        .with_metrics(future_metrics!("handler_task", "name" => "payment_links_check"))
+        .await
+}
+
+#[tracing::instrument(skip(state), level = "debug")]
+async fn handler_internal(
+    state: State<Arc<AppState>>,
+    Path(id): Path<String>,
+    Query(query_params): Query<QueryParams>,
+    request_payload: CheckTransactionParams,
+) -> Result<Response, RpcError> {
+    let project_id = query_params.project_id;
+    state.validate_project_access_and_quota(&project_id).await?;
+
+    let link = get_project_payment_link(&state, &id, &project_id).await?;
+    if link.transaction_id.as_deref() != Some(request_payload.id.as_str()) {
+        return Err(RpcError::InvalidParameter(format!(
+            "Transaction {} was not built for the payment link {id}",
+            request_payload.id
+        )));
+    }
+
+    let result = check_transaction::handler(state.clone(), project_id, request_payload)
+        .await
+        .map_err(|e| pos_error_to_rpc_error(e.is_internal(), e))?;
+
+    if matches!(
+        result.status,
+        TransactionStatus::Confirmed | TransactionStatus::Overpaid
+    ) && payment_links::mark_paid(&state.postgres, &id, result.txid.as_deref()).await?
+    {
+        info!(
+            "Payment link {id} is paid by the transaction {:?}",
+            result.txid
+        );
+    }
+
+    let link = get_payment_link(&state, &id).await?;
+    Ok(Json(CheckPaymentLinkResponse {
+        transaction: result,
+        payment_link: PaymentLinkResponse::from(link),
+    })
+    .into_response())
+}
@@ -0,0 +1,137 @@
+use {
+    super::{PaymentLinkResponse, QueryParams, PAYMENT_LINK_ID_LENGTH},
+    crate::{
+        database::payment_links::{self, NewPaymentLink},
+        error::RpcError,
+        handlers::json_rpc::pos::SupportedNamespaces,
+        state::AppState,
+        utils::{
+            crypto::{disassemble_caip10_with_namespace, Caip19Asset},
+            generate_random_string,
+            simple_request_json::SimpleRequestJson,
+        },
+    },
+    alloy::primitives::utils::{parse_units, ParseUnits},
+    axum::{
+        extract::{Query, State},
+        response::{IntoResponse, Response},
+        Json,
+    },
+    chrono::{DateTime, Utc},
+    serde::Deserialize,
+    std::{str::FromStr, sync::Arc},
+    wc::metrics::{future_metrics, FutureExt},
+};
+
+/// Max fractional digits of the amount, the largest of the supported assets
+/// decimals (NEAR)
+const MAX_AMOUNT_DECIMALS: u8 = 24;
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CreatePaymentLinkRequest {
+    /// CAIP-19 asset ID
+    pub asset: String,
+    /// Amount in the asset units
+    pub amount: String,
+    /// CAIP-10 recipient account
+    pub recipient: String,
+    /// Expiration unix timestamp in seconds
+    pub expires_at: Option<i64>,
+}
+
+pub async fn handler(
+    state: State<Arc<AppState>>,
+    query_params: Query<QueryParams>,
+    SimpleRequestJson(request_payload): SimpleRequestJson<CreatePaymentLinkRequest>,
+) -> Result<Response, RpcError> {
+    handler_internal(state, query_params, request_payload)
+        .with_metrics(future_metrics!("handler_task", "name" => "payment_links_create"))
+        .await
+}
+
+#[tracing::instrument(skip(state), level = "debug")]
+async fn handler_internal(
+    State(state): State<Arc<AppState>>,
+    Query(query_params): Query<QueryParams>,
+    request_payload: CreatePaymentLinkRequest,
+) -> Result<Response, RpcError> {
+    let project_id = query_params.project_id;
+    state.validate_project_access_and_quota(&project_id).await?;
+
+    let asset = Caip19Asset::parse(&request_payload.asset)?;
+    let asset_namespace = SupportedNamespaces::from_str(asset.chain_id().namespace())
+        .map_err(|_| RpcError::AssetNotSupported(request_payload.asset.clone()))?;
+
+    let (recipient_namespace, recipient_chain_id, _) =
+        disassemble_caip10_with_namespace::<SupportedNamespaces>(&request_payload.recipient)?;
+    if recipient_namespace != asset_namespace || recipient_chain_id != asset.chain_id().reference()
+    {
+        return Err(RpcError::InvalidParameter(
+            "Recipient namespace and chain ID must match the asset".to_string(),
+        ));
+    }
+
+    if !is_positive_amount(&request_payload.amount) {
+        return Err(RpcError::InvalidParameter(format!(
+            "Invalid amount: {}",
+            request_payload.amount
+        )));
+    }
+
+    let expires_at = request_payload
+        .expires_at
+        .map(|expires_at| {
+            DateTime::<Utc>::from_timestamp(expires_at, 0)
+                .filter(|expires_at| *expires_at > Utc::now())
+                .ok_or_else(|| {
+                    RpcError::InvalidParameter(format!(
+                        "Expiration must be a future timestamp: {expires_at}"
+                    ))
+                })
+        })
+        .transpose()?;
+
+    let id = generate_random_string(PAYMENT_LINK_ID_LENGTH);
+    let link = payment_links::insert_payment_link(
+        &state.postgres,
+        NewPaymentLink {
+            id: &id,
+            project_id: &project_id,
+            asset: &request_payload.asset,
+            amount: &request_payload.amount,
+            recipient: &request_payload.recipient,
+            expires_at,
+        },
+    )
+    .await?;
+
+    Ok(Json(PaymentLinkResponse::from(link)).into_response())
+}
+
+/// Check the amount is the positive decimal number, the exact amount is
+/// parsed with the asset decimals when the transaction is built
+fn is_positive_amount(amount: &str) -> bool {
+    matches!(
+        parse_units(amount, MAX_AMOUNT_DECIMALS),
+        Ok(ParseUnits::U256(amount)) if !amount.is_zero()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn positive_amounts() {
+        assert!(is_positive_amount("1"));
+        assert!(is_positive_amount("0.000000000000000000000001"));
+        assert!(is_positive_amount("123456789012345678901234567890.5"));
+        assert!(!is_positive_amount("0"));
+        assert!(!is_positive_amount("0.0"));
+        assert!(!is_positive_amount("-1"));
+        assert!(!is_positive_amount("1e3"));
+        assert!(!is_positive_amount("NaN"));
+        assert!(!is_positive_amount(""));
+    }
+}
@@ -0,0 +1,26 @@
+use {
+    super::{get_payment_link, PaymentLinkResponse},
+    crate::{error::RpcError, state::AppState},
+    axum::{
+        extract::{Path, State},
+        response::{IntoResponse, Response},
+        Json,
+    },
+    std::sync::Arc,
+    wc::metrics::{future_metrics, FutureExt},
+};
+
+pub async fn handler(state: State<Arc<AppState>>, id: Path<String>) -> Result<Response, RpcError> {
+    handler_internal(state, id)
+        .with_metrics(future_metrics!("handler_task", "name" => "payment_links_get"))
+        .await
+}
+
+#[tracing::instrument(skip(state), level = "debug")]
+async fn handler_internal(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> Result<Response, RpcError> {
+    let link = get_payment_link(&state, &id).await?;
+    Ok(Json(PaymentLinkResponse::from(link)).into_response())
+}
@@ -0,0 +1,108 @@
+use {
+    crate::{
+        database::payment_links::{self, PaymentLink},
+        error::RpcError,
+        state::AppState,
+    },
+    chrono::Utc,
+    serde::{Deserialize, Serialize},
+};
+
+pub mod build;
+pub mod check;
+pub mod create;
+pub mod get;
+
+/// Length of the payment link short ID
+pub const PAYMENT_LINK_ID_LENGTH: usize = 12;
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct QueryParams {
+    pub project_id: String,
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum PaymentLinkStatus {
+    Pending,
+    Paid,
+    Expired,
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PaymentLinkResponse {
+    pub id: String,
+    pub asset: String,
+    pub amount: String,
+    pub recipient: String,
+    pub status: PaymentLinkStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub txid: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub paid_at: Option<i64>,
+    pub created_at: i64,
+}
+
+impl From<PaymentLink> for PaymentLinkResponse {
+    fn from(link: PaymentLink) -> Self {
+        Self {
+            status: payment_link_status(&link),
+            id: link.id,
+            asset: link.asset,
+            amount: link.amount,
+            recipient: link.recipient,
+            expires_at: link.expires_at.map(|expires_at| expires_at.timestamp()),
+            txid: link.txid,
+            paid_at: link.paid_at.map(|paid_at| paid_at.timestamp()),
+            created_at: link.created_at.timestamp(),
+        }
+    }
+}
+
+pub fn payment_link_status(link: &PaymentLink) -> PaymentLinkStatus {
+    if link.paid_at.is_some() {
+        PaymentLinkStatus::Paid
+    } else if link
+        .expires_at
+        .is_some_and(|expires_at| expires_at <= Utc::now())
+    {
+        PaymentLinkStatus::Expired
+    } else {
+        PaymentLinkStatus::Pending
+    }
+}
+
+/// Get the payment link by ID or return the not found error
+pub async fn get_payment_link(state: &AppState, id: &str) -> Result<PaymentLink, RpcError> {
+    payment_links::get_payment_link(&state.postgres, id)
+        .await?
+        .ok_or_else(|| RpcError::PaymentLinkNotFound(id.to_string()))
+}
+
+/// Get the payment link of the project, the links of the other projects are
+/// not found since the project is charged for the link transactions
+pub async fn get_project_payment_link(
+    state: &AppState,
+    id: &str,
+    project_id: &str,
+) -> Result<PaymentLink, RpcError> {
+    let link = get_payment_link(state, id).await?;
+    if link.project_id != project_id {
+        return Err(RpcError::PaymentLinkNotFound(id.to_string()));
+    }
+    Ok(link)
+}
+
+/// Convert the pos transaction error into the response error keeping the
+/// internal errors as the internal server error
+pub fn pos_error_to_rpc_error(is_internal: bool, error: impl std::fmt::Display) -> RpcError {
+    if is_internal {
+        RpcError::Other(anyhow::anyhow!("Payment transaction error: {error}"))
+    } else {
+        RpcError::PaymentTransactionError(error.to_string())
+    }
+}
@@ -1,7 +1,8 @@
 use {
     crate::{error::RpcError, state::AppState},
+    async_graphql::SimpleObject,
     axum::{
-        extract::{ConnectInfo, MatchedPath, Path, Query, State},
+        extract::{ConnectInfo, Path, Query, State},
         response::{IntoResponse, Response},
         Json,
     },
@@ -19,15 +20,59 @@ use {
 pub struct PortfolioQueryParams {
     pub project_id: String,
     pub currency: Option<String>,
+    /// Include the NFT holdings summary
+    pub include_nfts: Option<bool>,
 }
 
 #[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct PortfolioResponseBody {
     pub data: Vec<PortfolioPosition>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nfts: Option<PortfolioNfts>,
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+/// NFT holdings summary by the collections
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, SimpleObject)]
+#[serde(rename_all = "camelCase")]
+pub struct PortfolioNfts {
+    pub total_count: usize,
+    /// Sum of the collections floor values in the requested currency
+    pub estimated_floor_value: f64,
+    pub collections: Vec<PortfolioNftCollection>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, SimpleObject)]
+#[serde(rename_all = "camelCase")]
+pub struct PortfolioNftCollection {
+    pub id: String,
+    pub name: Option<String>,
+    pub icon_url: Option<String>,
+    pub count: usize,
+    /// Floor value of the held NFTs of the collection, not available for the
+    /// collections without the market data
+    pub floor_value: Option<f64>,
+}
+
+impl PortfolioNfts {
+    pub fn from_collections(mut collections: Vec<PortfolioNftCollection>) -> Self {
+        collections.sort_by(|a, b| {
+            b.floor_value
+                .unwrap_or_default()
+                .total_cmp(&a.floor_value.unwrap_or_default())
+        });
+        Self {
+            total_count: collections.iter().map(|collection| collection.count).sum(),
+            estimated_floor_value: collections
+                .iter()
+                .filter_map(|collection| collection.floor_value)
+                .sum(),
+            collections,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, SimpleObject)]
 #[serde(rename_all = "camelCase")]
 pub struct PortfolioPosition {
     pub id: String,
@@ -39,11 +84,10 @@ pub async fn handler(
     state: State<Arc<AppState>>,
     connect_info: ConnectInfo<SocketAddr>,
     query: Query<PortfolioQueryParams>,
-    path: MatchedPath,
     headers: HeaderMap,
     address: Path<String>,
 ) -> Result<Response, RpcError> {
-    handler_internal(state, connect_info, query, path, headers, address)
+    handler_internal(state, connect_info, query, headers, address)
         .with_metrics(future_metrics!("handler_task", "name" => "portfolio"))
         .await
 }
@@ -53,7 +97,6 @@ async fn handler_internal(
     state: State<Arc<AppState>>,
     _connect_info: ConnectInfo<SocketAddr>,
     query: Query<PortfolioQueryParams>,
-    _path: MatchedPath,
     _headers: HeaderMap,
     Path(address): Path<String>,
 ) -> Result<Response, RpcError> {
@@ -65,14 +108,57 @@ async fn handler_internal(
 
     state.validate_project_access_and_quota(&project_id).await?;
 
-    let response = state
-        .providers
-        .portfolio_provider
-        .get_portfolio(address, query.0, state.metrics.clone())
-        .await
-        .tap_err(|e| {
-            error!("Failed to call portfolio with {e}");
-        })?;
+    let portfolio_provider = state.providers().portfolio_provider.clone();
+    let include_nfts = query.include_nfts.unwrap_or(false);
+    let (response, nfts) = tokio::try_join!(
+        portfolio_provider.get_portfolio(address.clone(), query.0.clone(), state.metrics.clone()),
+        async {
+            if !include_nfts {
+                return Ok(None);
+            }
+            portfolio_provider
+                .get_nft_holdings(address.clone(), query.0.clone(), state.metrics.clone())
+                .await
+                .map(Some)
+        }
+    )
+    .tap_err(|e| {
+        error!("Failed to call portfolio with {e}");
+    })?;
+    let response = PortfolioResponseBody { nfts, ..response };
 
     Ok(Json(response).into_response())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn collection(id: &str, count: usize, floor_value: Option<f64>) -> PortfolioNftCollection {
+        PortfolioNftCollection {
+            id: id.to_owned(),
+            name: None,
+            icon_url: None,
+            count,
+            floor_value,
+        }
+    }
+
+    #[test]
+    fn nfts_summary() {
+        let nfts = PortfolioNfts::from_collections(vec![
+            collection("a", 2, Some(1.5)),
+            collection("b", 10, None),
+            collection("c", 1, Some(3.0)),
+        ]);
+        assert_eq!(nfts.total_count, 13);
+        assert_eq!(nfts.estimated_floor_value, 4.5);
+        assert_eq!(
+            nfts.collections
+                .iter()
+                .map(|collection| collection.id.as_str())
+                .collect::<Vec<_>>(),
+            vec!["c", "a", "b"]
+        );
+    }
+}
@@ -0,0 +1,118 @@
+use {
+    crate::{
+        database::pos_payment_intents,
+        error::RpcError,
+        state::AppState,
+        utils::{project_jwt::VerifiedProjectId, simple_request_json::SimpleRequestJson},
+        webhooks,
+    },
+    axum::{
+        extract::{Query, State},
+        http::StatusCode,
+        Extension,
+    },
+    serde::Deserialize,
+    std::sync::Arc,
+    tracing::info,
+    wc::metrics::{future_metrics, FutureExt},
+};
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct QueryParams {
+    pub project_id: String,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SetWebhookRequest {
+    /// HTTPS callback URL of the confirmed payments
+    pub url: String,
+}
+
+/// Sets the merchant callback URL of the project's confirmed payments
+pub async fn set_handler(
+    state: State<Arc<AppState>>,
+    query_params: Query<QueryParams>,
+    verified_project_id: Option<Extension<VerifiedProjectId>>,
+    SimpleRequestJson(request_payload): SimpleRequestJson<SetWebhookRequest>,
+) -> Result<StatusCode, RpcError> {
+    set_handler_internal(state, query_params, verified_project_id, request_payload)
+        .with_metrics(future_metrics!("handler_task", "name" => "pos_webhook_set"))
+        .await
+}
+
+#[tracing::instrument(skip(state), level = "debug")]
+async fn set_handler_internal(
+    State(state): State<Arc<AppState>>,
+    Query(query_params): Query<QueryParams>,
+    verified_project_id: Option<Extension<VerifiedProjectId>>,
+    request_payload: SetWebhookRequest,
+) -> Result<StatusCode, RpcError> {
+    let project_id = query_params.project_id;
+    authorize(verified_project_id.as_deref(), &project_id)?;
+    state.validate_project_access(&project_id).await?;
+
+    let url = webhooks::validate_url(&request_payload.url)?;
+    pos_payment_intents::set_merchant_webhook_url(&state.postgres, &project_id, url.as_str())
+        .await?;
+    info!("Merchant webhook URL is set for the project {project_id}");
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Clears the merchant callback URL, the project's payments are not notified
+pub async fn delete_handler(
+    state: State<Arc<AppState>>,
+    query_params: Query<QueryParams>,
+    verified_project_id: Option<Extension<VerifiedProjectId>>,
+) -> Result<StatusCode, RpcError> {
+    delete_handler_internal(state, query_params, verified_project_id)
+        .with_metrics(future_metrics!("handler_task", "name" => "pos_webhook_delete"))
+        .await
+}
+
+#[tracing::instrument(skip(state), level = "debug")]
+async fn delete_handler_internal(
+    State(state): State<Arc<AppState>>,
+    Query(query_params): Query<QueryParams>,
+    verified_project_id: Option<Extension<VerifiedProjectId>>,
+) -> Result<StatusCode, RpcError> {
+    let project_id = query_params.project_id;
+    authorize(verified_project_id.as_deref(), &project_id)?;
+    state.validate_project_access(&project_id).await?;
+
+    if pos_payment_intents::delete_merchant_webhook_url(&state.postgres, &project_id).await? {
+        info!("Merchant webhook URL is cleared for the project {project_id}");
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// The webhook URL receives the payments of the project, so it's changed by
+/// the project JWT only, the project ID alone is public
+fn authorize(
+    verified_project_id: Option<&VerifiedProjectId>,
+    project_id: &str,
+) -> Result<(), RpcError> {
+    match verified_project_id {
+        Some(VerifiedProjectId(verified)) if verified == project_id => Ok(()),
+        Some(_) => Err(RpcError::InvalidProjectJwt(
+            "token subject doesn't match the projectId query parameter".to_owned(),
+        )),
+        None => Err(RpcError::InvalidProjectJwt(
+            "project JWT is required".to_owned(),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn requires_project_jwt() {
+        let verified = VerifiedProjectId("project".to_owned());
+        assert!(authorize(Some(&verified), "project").is_ok());
+        assert!(authorize(Some(&verified), "other").is_err());
+        assert!(authorize(None, "project").is_err());
+    }
+}
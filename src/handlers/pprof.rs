@@ -0,0 +1,58 @@
+use {
+    crate::{
+        error::RpcError,
+        profiler::{self, CpuProfileFormat},
+    },
+    axum::{
+        extract::Query,
+        http::header,
+        response::{IntoResponse, Response},
+    },
+    serde::Deserialize,
+    std::time::Duration,
+    tracing::info,
+};
+
+const DEFAULT_CPU_PROFILE_SECONDS: u64 = 30;
+const MAX_CPU_PROFILE_SECONDS: u64 = 120;
+
+#[derive(Debug, Deserialize)]
+pub struct CpuProfileQueryParams {
+    pub seconds: Option<u64>,
+    #[serde(default)]
+    pub format: CpuProfileFormat,
+}
+
+/// Collects the CPU profile of the instance, served on the private port only
+pub async fn profile_handler(
+    Query(query): Query<CpuProfileQueryParams>,
+) -> Result<Response, RpcError> {
+    let seconds = query.seconds.unwrap_or(DEFAULT_CPU_PROFILE_SECONDS);
+    if seconds == 0 || seconds > MAX_CPU_PROFILE_SECONDS {
+        return Err(RpcError::InvalidParameter(format!(
+            "seconds must be in the 1..={MAX_CPU_PROFILE_SECONDS} range"
+        )));
+    }
+    info!("Collecting the {seconds}s CPU profile");
+    let profile = profiler::cpu_profile(Duration::from_secs(seconds), query.format)
+        .await
+        .map_err(RpcError::ProfilingError)?;
+    let content_type = match query.format {
+        CpuProfileFormat::Pprof => "application/octet-stream",
+        CpuProfileFormat::Flamegraph => "image/svg+xml",
+    };
+    Ok(([(header::CONTENT_TYPE, content_type)], profile).into_response())
+}
+
+/// Dumps the heap profile of the instance, served on the private port only
+pub async fn heap_handler() -> Result<Response, RpcError> {
+    info!("Dumping the heap profile");
+    let profile = profiler::heap_profile()
+        .await
+        .map_err(RpcError::ProfilingError)?;
+    Ok((
+        [(header::CONTENT_TYPE, "application/octet-stream")],
+        profile,
+    )
+        .into_response())
+}
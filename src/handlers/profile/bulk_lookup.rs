@@ -0,0 +1,61 @@
+use {
+    super::{BulkLookupRequest, BulkLookupResponse},
+    crate::{
+        database::helpers::get_addresses_by_names,
+        error::RpcError,
+        names::utils::{is_name_format_correct, is_name_in_allowed_zones, is_name_length_correct},
+        state::AppState,
+    },
+    axum::{
+        extract::State,
+        response::{IntoResponse, Response},
+        Json,
+    },
+    std::sync::Arc,
+    wc::metrics::{future_metrics, FutureExt},
+};
+
+/// Maximum number of the names in the bulk lookup request
+const MAX_NAMES: usize = 100;
+
+pub async fn handler(
+    state: State<Arc<AppState>>,
+    Json(request_payload): Json<BulkLookupRequest>,
+) -> Result<Response, RpcError> {
+    handler_internal(state, request_payload)
+        .with_metrics(future_metrics!("handler_task", "name" => "profile_bulk_lookup"))
+        .await
+}
+
+#[tracing::instrument(skip(state), level = "debug")]
+async fn handler_internal(
+    state: State<Arc<AppState>>,
+    mut request_payload: BulkLookupRequest,
+) -> Result<Response, RpcError> {
+    let allowed_zones = state.config.names.allowed_zones.as_ref().ok_or_else(|| {
+        RpcError::InvalidConfiguration("Names allowed zones are not defined".to_string())
+    })?;
+
+    request_payload.names.sort();
+    request_payload.names.dedup();
+    if request_payload.names.is_empty() || request_payload.names.len() > MAX_NAMES {
+        return Err(RpcError::InvalidParameter(format!(
+            "Names list must contain from 1 to {MAX_NAMES} names"
+        )));
+    }
+
+    for name in &request_payload.names {
+        if !is_name_format_correct(name) {
+            return Err(RpcError::InvalidNameFormat(name.clone()));
+        }
+        if !is_name_length_correct(name) {
+            return Err(RpcError::InvalidNameLength(name.clone()));
+        }
+        if !is_name_in_allowed_zones(name, allowed_zones.clone()) {
+            return Err(RpcError::InvalidNameZone(name.clone()));
+        }
+    }
+
+    let names = get_addresses_by_names(&request_payload.names, &state.postgres).await?;
+    Ok(Json(BulkLookupResponse { names }).into_response())
+}
@@ -1,10 +1,12 @@
 use {
+    crate::database::types::ENSIP11AddressesMap,
     serde::{Deserialize, Serialize},
     std::collections::HashMap,
 };
 
 pub mod address;
 pub mod attributes;
+pub mod bulk_lookup;
 pub mod lookup;
 pub mod register;
 pub mod reverse;
@@ -79,3 +81,17 @@ pub struct SuggestionsParams {
     /// Optional zone to use for name suggestions
     pub zone: Option<String>,
 }
+
+/// Bulk forward lookup request
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BulkLookupRequest {
+    /// Names to resolve the addresses of
+    pub names: Vec<String>,
+}
+
+/// Bulk forward lookup response, the names that are not registered are not
+/// included
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BulkLookupResponse {
+    pub names: HashMap<String, ENSIP11AddressesMap>,
+}
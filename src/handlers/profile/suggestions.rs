@@ -1,9 +1,10 @@
 use {
     super::SuggestionsParams,
     crate::{
+        database::helpers::get_registered_names,
         error::RpcError,
-        names::suggestions::dictionary_suggestions,
-        names::utils::{is_name_format_correct, is_name_registered},
+        names::suggestions::{suggestion_candidates, KeyboardLayout},
+        names::utils::is_name_format_correct,
         state::AppState,
     },
     axum::{
@@ -11,6 +12,7 @@ use {
         response::{IntoResponse, Response},
         Json,
     },
+    hyper::{header::ACCEPT_LANGUAGE, HeaderMap},
     serde::{Deserialize, Serialize},
     std::sync::Arc,
     wc::metrics::{future_metrics, FutureExt},
@@ -34,17 +36,19 @@ pub async fn handler(
     state: State<Arc<AppState>>,
     name: Path<String>,
     query: Query<SuggestionsParams>,
+    headers: HeaderMap,
 ) -> Result<Response, RpcError> {
-    handler_internal(state, name, query)
+    handler_internal(state, name, query, headers)
         .with_metrics(future_metrics!("handler_task", "name" => "name_suggestions"))
         .await
 }
 
-#[tracing::instrument(skip(state), level = "debug")]
+#[tracing::instrument(skip(state, headers), level = "debug")]
 async fn handler_internal(
     state: State<Arc<AppState>>,
     Path(name): Path<String>,
     Query(query): Query<SuggestionsParams>,
+    headers: HeaderMap,
 ) -> Result<Response, RpcError> {
     if name.len() < MIN_NAME_LENGTH {
         return Err(RpcError::InvalidNameLength(name));
@@ -53,9 +57,6 @@ async fn handler_internal(
         return Err(RpcError::InvalidNameFormat(name));
     }
 
-    let mut suggestions = Vec::new();
-    let candidates = dictionary_suggestions(&name);
-
     // Use the `zone` query parameter if it is provided for the new AppKit versions
     // Otherwise, use the first zone in the allowed zones list for the backward compatibility
     // with the old AppKit versions
@@ -67,30 +68,34 @@ async fn handler_internal(
     })?;
     let zone = query.zone.unwrap_or_else(|| default_zone.to_string());
 
-    // Adding the exact match for the main zone to check if it is
-    // registered
-    let exact_name_with_zone = format!("{name}.{zone}");
-    suggestions.push(NameSuggestion {
-        name: exact_name_with_zone.clone(),
-        registered: is_name_registered(exact_name_with_zone, &state.postgres).await,
-    });
-
-    // Iterate found dictionary candidates and check if they are registered
-    for suggested_name in candidates {
-        // Get name suggestion for the main zone if the name is free
-        let name_with_zone = format!("{suggested_name}.{zone}");
-        let is_registered = is_name_registered(name_with_zone.clone(), &state.postgres).await;
+    // Keyboard typos are suggested by the layout of the preferred language
+    let keyboard_layout = headers
+        .get(ACCEPT_LANGUAGE)
+        .and_then(|value| value.to_str().ok())
+        .map(KeyboardLayout::from_accept_language)
+        .unwrap_or_default();
+    let candidates = suggestion_candidates(&name, &zone, allowed_zones, keyboard_layout);
+    let registered = get_registered_names(&candidates, &state.postgres).await?;
 
-        if !is_registered {
-            suggestions.push(NameSuggestion {
+    // The exact match for the main zone goes first to check if it is
+    // registered, the rest of the candidates are suggested if they are free
+    let mut candidates = candidates.into_iter();
+    let mut suggestions = Vec::with_capacity(SUGGESTION_OPTIONS);
+    if let Some(exact_name_with_zone) = candidates.next() {
+        suggestions.push(NameSuggestion {
+            registered: registered.contains(&exact_name_with_zone),
+            name: exact_name_with_zone,
+        });
+    }
+    suggestions.extend(
+        candidates
+            .filter(|name_with_zone| !registered.contains(name_with_zone))
+            .take(SUGGESTION_OPTIONS.saturating_sub(suggestions.len()))
+            .map(|name_with_zone| NameSuggestion {
                 name: name_with_zone,
                 registered: false,
-            });
-        }
-        if suggestions.len() == SUGGESTION_OPTIONS {
-            break;
-        }
-    }
+            }),
+    );
 
     Ok(Json(NameSuggestionsResponse { suggestions }).into_response())
 }
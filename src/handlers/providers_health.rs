@@ -0,0 +1,29 @@
+use {
+    crate::{chain_quarantine::QuarantinedChain, providers::ProviderHealth, state::AppState},
+    axum::{extract::State, Json},
+    serde::Serialize,
+    std::sync::Arc,
+};
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProvidersHealthResponse {
+    pub providers: Vec<ProviderHealth>,
+    pub open_circuit_breakers: Vec<&'static str>,
+    pub quarantined_chains: Vec<QuarantinedChain>,
+}
+
+/// Routing state of the RPC providers for the operators, served on the
+/// private port only
+pub async fn handler(State(state): State<Arc<AppState>>) -> Json<ProvidersHealthResponse> {
+    let mut open_circuit_breakers = Vec::new();
+    if state.registry.is_circuit_open() {
+        open_circuit_breakers.push("registry");
+    }
+
+    Json(ProvidersHealthResponse {
+        providers: state.providers().rpc_providers_health(),
+        open_circuit_breakers,
+        quarantined_chains: state.chain_quarantine.quarantined(),
+    })
+}
@@ -0,0 +1,51 @@
+use {
+    crate::{
+        error::RpcError,
+        providers::{DisabledProvider, ProviderKind},
+        state::AppState,
+    },
+    axum::{
+        extract::{Path, Query, State},
+        Json,
+    },
+    serde::Deserialize,
+    std::sync::Arc,
+};
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProviderOverrideQueryParams {
+    /// Provider is overridden for all chains when not set
+    pub chain_id: Option<String>,
+}
+
+/// Providers disabled at runtime, served on the private port only
+pub async fn handler(State(state): State<Arc<AppState>>) -> Json<Vec<DisabledProvider>> {
+    Json(state.providers().disabled_providers())
+}
+
+/// Drains the provider for the incident response without redeploys
+pub async fn disable_handler(
+    State(state): State<Arc<AppState>>,
+    Path(provider): Path<String>,
+    Query(query): Query<ProviderOverrideQueryParams>,
+) -> Result<Json<Vec<DisabledProvider>>, RpcError> {
+    let providers = state.providers();
+    providers.disable_provider(parse_provider_kind(&provider)?, query.chain_id)?;
+    Ok(Json(providers.disabled_providers()))
+}
+
+pub async fn enable_handler(
+    State(state): State<Arc<AppState>>,
+    Path(provider): Path<String>,
+    Query(query): Query<ProviderOverrideQueryParams>,
+) -> Result<Json<Vec<DisabledProvider>>, RpcError> {
+    let providers = state.providers();
+    providers.enable_provider(&parse_provider_kind(&provider)?, query.chain_id.as_deref())?;
+    Ok(Json(providers.disabled_providers()))
+}
+
+fn parse_provider_kind(provider: &str) -> Result<ProviderKind, RpcError> {
+    ProviderKind::from_str(provider)
+        .ok_or_else(|| RpcError::UnsupportedProvider(provider.to_owned()))
+}
@@ -0,0 +1,11 @@
+use {
+    crate::{providers::WeightsSnapshot, state::AppState},
+    axum::{extract::State, Json},
+    std::sync::Arc,
+};
+
+/// Current providers weights with the Prometheus-derived inputs of the latest
+/// weights updates, served on the private port only
+pub async fn handler(State(state): State<Arc<AppState>>) -> Json<WeightsSnapshot> {
+    Json(state.providers().weights_snapshot())
+}
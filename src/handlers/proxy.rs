@@ -1,34 +1,31 @@
 use {
-    super::RpcQueryParams,
+    super::{shadow::ShadowRequest, RpcQueryParams},
     crate::{
-        analytics::MessageInfo,
+        analytics::{MessageInfo, ProviderCallInfo},
         error::RpcError,
         json_rpc::JsonRpcRequest,
         providers::{
-            is_internal_error_rpc_code, is_known_rpc_error_message, is_node_error_rpc_message,
+            forwarded_headers::with_forwarded_headers, is_internal_error_rpc_code,
+            is_known_rpc_error_message, is_node_error_rpc_message,
             is_rate_limited_error_rpc_message, ProviderKind,
         },
         state::AppState,
         utils::{
-            batch_json_rpc_request::MaybeBatchRequest, crypto, json_rpc_cache::is_cached_response,
+            crypto,
+            eth_call_cache::EthCallCache,
+            json_rpc_cache::{is_cached_method, is_cached_response},
             network,
+            partial_json_rpc::{MaybeBatchRequestMethod, MaybeBatchResponse, RequestMethod},
         },
     },
     axum::{
         body::{to_bytes, Bytes},
-        extract::{ConnectInfo, Query, State},
+        extract::{ConnectInfo, Query, RawQuery, State},
         response::{IntoResponse, Response},
     },
     hyper::{http, HeaderMap},
-    std::{
-        borrow::Borrow,
-        collections::HashSet,
-        net::SocketAddr,
-        sync::Arc,
-        time::{Duration, SystemTime},
-    },
-    tap::TapFallible,
-    tokio::time::timeout,
+    std::{borrow::Borrow, collections::HashSet, net::SocketAddr, sync::Arc, time::SystemTime},
+    tokio::time::{sleep, timeout},
     tracing::{
         log::{debug, error, warn},
         Span,
@@ -36,8 +33,6 @@ use {
     wc::metrics::{future_metrics, FutureExt},
 };
 
-const PROVIDER_PROXY_MAX_CALLS: usize = 5;
-const PROVIDER_PROXY_CALL_TIMEOUT: Duration = Duration::from_secs(10);
 const DEFAULT_CONTENT_TYPE: (&str, &str) = ("content-type", "application/json");
 pub const PROVIDER_RESPONSE_MAX_BYTES: usize = 10 * 1024 * 1024; // 10 Mb
 
@@ -45,10 +40,11 @@ pub async fn handler(
     state: State<Arc<AppState>>,
     addr: ConnectInfo<SocketAddr>,
     query_params: Query<RpcQueryParams>,
+    raw_query: RawQuery,
     headers: HeaderMap,
     body: Bytes,
 ) -> Result<Response, RpcError> {
-    handler_internal(state, addr, query_params, headers, body)
+    handler_internal(state, addr, query_params, raw_query, headers, body)
         .with_metrics(future_metrics!("handler_task", "name" => "proxy"))
         .await
 }
@@ -58,6 +54,7 @@ async fn handler_internal(
     State(state): State<Arc<AppState>>,
     ConnectInfo(addr): ConnectInfo<SocketAddr>,
     Query(query_params): Query<RpcQueryParams>,
+    RawQuery(raw_query): RawQuery,
     headers: HeaderMap,
     body: Bytes,
 ) -> Result<Response, RpcError> {
@@ -77,8 +74,21 @@ async fn handler_internal(
             .validate_project_access_and_quota(&query_params.project_id.clone())
             .await?;
     };
-
-    rpc_call(state, addr, query_params, headers, body).await
+    state
+        .validate_project_chain(&query_params.project_id, &query_params.chain_id)
+        .await?;
+
+    // Internal calls without the raw query are not mirrored
+    let Some(shadow_request) = raw_query.and_then(|query| {
+        ShadowRequest::sample(&state, &query_params.chain_id, &query, &headers, &body)
+    }) else {
+        return rpc_call(state, addr, query_params, headers, body).await;
+    };
+    let response = rpc_call(state.clone(), addr, query_params, headers, body)
+        .await
+        .unwrap_or_else(IntoResponse::into_response);
+    shadow_request.spawn(state, response.status());
+    Ok(response)
 }
 
 #[tracing::instrument(skip(state), level = "debug")]
@@ -91,26 +101,53 @@ pub async fn rpc_call(
 ) -> Result<Response, RpcError> {
     let chain_id = query_params.chain_id.clone();
 
-    // Deserializing the request body to a JSON-RPC request schema and
-    // check if a cached response can be returned
-    // TODO: Optimize this to remove the second deserialization during the provider analytics
-    match serde_json::from_slice::<JsonRpcRequest>(&body) {
-        Ok(request) => {
-            if let Some(response) =
-                is_cached_response(&chain_id, &request, &state.metrics, &state.moka_cache).await
-            {
-                return Ok((
-                    http::StatusCode::OK,
-                    [DEFAULT_CONTENT_TYPE],
-                    serde_json::to_string(&response)?,
-                )
-                    .into_response());
-            }
+    // Deserializing the request method and check if a cached response can be
+    // returned, the full request is deserialized only for the cached methods
+    let is_cached = match serde_json::from_slice::<RequestMethod>(&body) {
+        Ok(request_method) => {
+            is_cached_method(&request_method.method)
+                || (state.eth_call_cache.is_some()
+                    && EthCallCache::is_cached_method(&request_method.method))
         }
         Err(e) => {
             error!("Failed to deserialize JSON-RPC request: {e}");
+            false
         }
     };
+    let request = if is_cached {
+        serde_json::from_slice::<JsonRpcRequest>(&body)
+            .map_err(|e| error!("Failed to deserialize JSON-RPC request: {e}"))
+            .ok()
+    } else {
+        None
+    };
+    let mut eth_call_cache_key = None;
+    if let Some(request) = request {
+        if let Some(response) =
+            is_cached_response(&chain_id, &request, &state.metrics, &state.moka_cache).await
+        {
+            return Ok((
+                http::StatusCode::OK,
+                [DEFAULT_CONTENT_TYPE],
+                serde_json::to_string(&response)?,
+            )
+                .into_response());
+        }
+
+        if let Some(eth_call_cache) = &state.eth_call_cache {
+            if let Some(key) = eth_call_cache.cache_key(&chain_id, &request) {
+                if let Some(response) = eth_call_cache.get(&key, &request.id).await {
+                    state
+                        .metrics
+                        .add_rpc_cached_call(chain_id, request.method.to_string());
+                    return Ok(
+                        (http::StatusCode::OK, [DEFAULT_CONTENT_TYPE], response).into_response()
+                    );
+                }
+                eth_call_cache_key = Some(key);
+            }
+        }
+    }
 
     if query_params.session_id.is_some() {
         let provider_kind = match chain_id.as_str() {
@@ -127,7 +164,7 @@ pub async fn rpc_call(
 
         if let Some(provider_kind) = provider_kind {
             let provider = state
-                .providers
+                .providers()
                 .get_rpc_provider_by_provider_kind(&provider_kind)
                 .ok_or_else(|| RpcError::UnsupportedProvider(provider_kind.to_string()))?;
             let response = rpc_provider_call(
@@ -137,6 +174,7 @@ pub async fn rpc_call(
                 headers.clone(),
                 body.clone(),
                 provider.clone(),
+                0,
             )
             .await;
 
@@ -162,12 +200,23 @@ pub async fn rpc_call(
     // Start timing the total chain request (including retries)
     let chain_request_start = SystemTime::now();
 
+    // Request method for the providers routing and the request ids to
+    // validate the providers responses, only the JSON-RPC schema of the
+    // responses is validated for the unparseable requests
+    let request_method = MaybeBatchRequestMethod::from_slice(&body).ok();
+    let routing_method = match &request_method {
+        Some(MaybeBatchRequestMethod::Single(request)) => Some(request.method.as_ref()),
+        _ => None,
+    };
+
+    let retry_policy = state.providers().retry_policies.policy(&chain_id);
+
     // Exact provider proxy request for testing suite
     // This request is allowed only for the RPC_PROXY_TESTING_PROJECT_ID
     let providers = match query_params.provider_id.clone() {
         Some(provider_id) => {
             let provider = vec![state
-                .providers
+                .providers()
                 .get_rpc_provider_by_provider_id(&provider_id)
                 .ok_or_else(|| RpcError::UnsupportedProvider(provider_id.clone()))?];
 
@@ -187,12 +236,44 @@ pub async fn rpc_call(
 
             provider
         }
-        None => state
-            .providers
-            .get_rpc_provider_for_chain_id(&chain_id, PROVIDER_PROXY_MAX_CALLS)?,
+        None => {
+            // Quarantined chains are failing by all providers, the requests
+            // are rejected without the providers calls until the probe
+            // releases the chain
+            if state.chain_quarantine.is_quarantined(&chain_id) {
+                state
+                    .metrics
+                    .add_quarantined_chain_request(chain_id.clone());
+                return Err(RpcError::ChainTemporarilyUnavailable(chain_id));
+            }
+            state.providers().get_rpc_provider_for_chain_id(
+                &chain_id,
+                routing_method,
+                retry_policy.max_attempts,
+            )?
+        }
     };
 
+    let mut retry_budget = state.providers().retry_policies.start_request(&chain_id);
+    let mut retry_budget_exhausted = false;
     for (i, provider) in providers.iter().enumerate() {
+        // Retries are limited by the chain retry budget to not multiply the
+        // load of the degraded providers by the retries storm
+        if i > 0 {
+            if !retry_budget.try_retry() {
+                debug!("Retry budget of the chain {chain_id} is exhausted");
+                state
+                    .metrics
+                    .add_rpc_retry_budget_exhausted(chain_id.clone());
+                retry_budget_exhausted = true;
+                break;
+            }
+            let backoff = retry_policy.backoff(i);
+            if !backoff.is_zero() {
+                sleep(backoff).await;
+            }
+        }
+
         let provider_call = rpc_provider_call(
             state.clone(),
             addr,
@@ -200,6 +281,7 @@ pub async fn rpc_call(
             headers.clone(),
             body.clone(),
             provider.clone(),
+            i,
         )
         .await;
 
@@ -239,42 +321,78 @@ pub async fn rpc_call(
                     }
                 };
 
-            // Check the JSON-RPC response schema and possible internal error codes
-            match serde_json::from_slice::<jsonrpc::Response>(&body_bytes) {
-                Ok(json_response) => {
-                    if let Some(error) = &json_response.error {
-                        let error_code = error.code;
-                        let error_message = error.message.clone();
-
-                        // Internal error codes range -32000..-32099 https://www.jsonrpc.org/specification#error_object
-                        if is_internal_error_rpc_code(error_code) {
-                            // Retry to another provider if the error is a rate limited or node error
-                            if is_rate_limited_error_rpc_message(&error_message)
-                                || is_node_error_rpc_message(&error_message)
-                            {
-                                state
-                                    .metrics
-                                    .add_rpc_call_retries(i as u64, chain_id.clone());
-                                continue;
-                            }
+            // Retry the malformed responses, e.g. the HTML error pages or the
+            // truncated JSON, and the responses to the other requests with the
+            // next provider
+            let json_response = match MaybeBatchResponse::from_slice(&body_bytes) {
+                Ok(json_response)
+                    if request_method
+                        .as_ref()
+                        .is_none_or(|request| json_response.matches_request(request)) =>
+                {
+                    json_response
+                }
+                Ok(_) => {
+                    warn!("Provider {provider_kind} returned a JSON-RPC response to another request, trying the next provider");
+                    state
+                        .metrics
+                        .add_malformed_provider_response(chain_id.clone(), provider.borrow());
+                    state
+                        .metrics
+                        .add_rpc_call_retries(i as u64, chain_id.clone());
+                    continue;
+                }
+                Err(e) => {
+                    error!("Failed to parse JSON-RPC response from provider {provider_kind}: {e}, trying the next provider. Message: {}", String::from_utf8_lossy(&body_bytes));
+                    state
+                        .metrics
+                        .add_malformed_provider_response(chain_id.clone(), provider.borrow());
+                    state
+                        .metrics
+                        .add_rpc_call_retries(i as u64, chain_id.clone());
+                    continue;
+                }
+            };
 
-                            // Log an error, increment the metrics for unknown error codes and continue
-                            // without retrying since it can be a contract execution error.
-                            // We should catch unknown errors by alarm for the metrics
-                            // and investigate it first without retrying.
-                            if !is_known_rpc_error_message(&error_message) {
-                                error!("Provider {provider_kind} returned an error code: {error_code} and the message: {error_message}");
-                                state.metrics.add_internal_error_code_for_provider(
-                                    provider_kind,
-                                    chain_id.clone(),
-                                    error.code,
-                                );
-                            }
+            // Check the possible internal error codes
+            if let MaybeBatchResponse::Single(json_response) = json_response {
+                if let Some(error) = &json_response.error {
+                    let error_code = error.code;
+                    let error_message = &error.message;
+
+                    // Internal error codes range -32000..-32099 https://www.jsonrpc.org/specification#error_object
+                    if is_internal_error_rpc_code(error_code) {
+                        // Retry to another provider if the error is a rate limited or node error
+                        if is_rate_limited_error_rpc_message(error_message)
+                            || is_node_error_rpc_message(error_message)
+                        {
+                            state
+                                .metrics
+                                .add_rpc_call_retries(i as u64, chain_id.clone());
+                            continue;
+                        }
+
+                        // Log an error, increment the metrics for unknown error codes and continue
+                        // without retrying since it can be a contract execution error.
+                        // We should catch unknown errors by alarm for the metrics
+                        // and investigate it first without retrying.
+                        if !is_known_rpc_error_message(error_message) {
+                            error!("Provider {provider_kind} returned an error code: {error_code} and the message: {error_message}");
+                            state.metrics.add_internal_error_code_for_provider(
+                                provider_kind,
+                                chain_id.clone(),
+                                error.code,
+                            );
                         }
                     }
-                }
-                Err(e) => {
-                    error!("Failed to parse JSON-RPC response from provider {provider_kind}: {e}. Message: {}", String::from_utf8_lossy(&body_bytes));
+                } else if status.is_success() {
+                    if let (Some(eth_call_cache), Some(key), Some(result)) = (
+                        &state.eth_call_cache,
+                        eth_call_cache_key.take(),
+                        json_response.result,
+                    ) {
+                        eth_call_cache.insert(key, result.to_owned()).await;
+                    }
                 }
             }
 
@@ -289,6 +407,7 @@ pub async fn rpc_call(
                 chain_request_start,
                 chain_id.clone(),
             );
+            state.chain_quarantine.record_success(&chain_id);
             return Ok((status, [DEFAULT_CONTENT_TYPE], body_bytes).into_response());
         }
 
@@ -304,6 +423,17 @@ pub async fn rpc_call(
 
     state.metrics.add_no_providers_for_chain(chain_id.clone());
     debug!("All providers failed for chain_id: {chain_id}");
+    // The exact provider requests and the requests over the retry budget
+    // don't try all of the chain providers
+    if query_params.provider_id.is_none()
+        && !retry_budget_exhausted
+        && state.chain_quarantine.record_failure(&chain_id)
+    {
+        warn!("Chain {chain_id} is quarantined, all providers are failing");
+        state
+            .metrics
+            .record_chain_quarantined(chain_id.clone(), true);
+    }
     Err(RpcError::ChainTemporarilyUnavailable(chain_id))
 }
 
@@ -317,6 +447,7 @@ pub async fn rpc_provider_call(
     headers: HeaderMap,
     body: Bytes,
     provider: Arc<dyn crate::providers::RpcProvider>,
+    retry_index: usize,
 ) -> Result<Response, RpcError> {
     Span::current().record("provider", provider.provider_kind().to_string());
     let chain_id = query_params.chain_id.clone();
@@ -334,13 +465,19 @@ pub async fn rpc_provider_call(
         .map(|geo| (geo.country, geo.continent, geo.region))
         .unwrap_or((None, None, None));
 
-    match serde_json::from_slice::<MaybeBatchRequest>(&body) {
-        Ok(body) => {
-            let rpcs = match &body {
-                MaybeBatchRequest::Single(req) => {
+    // Method name for the provider calls analytics
+    let mut call_method = "unknown".to_owned();
+    match MaybeBatchRequestMethod::from_slice(&body) {
+        Ok(request) => {
+            call_method = match &request {
+                MaybeBatchRequestMethod::Single(req) => req.method.to_string(),
+                MaybeBatchRequestMethod::Batch(_) => "batch".to_owned(),
+            };
+            let rpcs = match &request {
+                MaybeBatchRequestMethod::Single(req) => {
                     vec![(req.id.to_string(), req.method.to_string())]
                 }
-                MaybeBatchRequest::Batch(reqs) => {
+                MaybeBatchRequestMethod::Batch(reqs) => {
                     {
                         // Validate unique RPC IDs
                         let mut ids = HashSet::new();
@@ -350,7 +487,7 @@ pub async fn rpc_provider_call(
                                 error!(
                                     "Duplicate RPC ID: {:?} for body {}",
                                     req.id,
-                                    serde_json::to_string(&body).unwrap_or_default()
+                                    String::from_utf8_lossy(&body)
                                 );
                             }
                         }
@@ -393,25 +530,64 @@ pub async fn rpc_provider_call(
     // Start timing external provider added time
     let external_call_start = SystemTime::now();
 
-    let proxy_fut = provider.proxy(&chain_id, body);
-    let timeout_fut = timeout(PROVIDER_PROXY_CALL_TIMEOUT, proxy_fut);
-    let mut response = timeout_fut
-        .await
-        .tap_err(|e| {
+    let forwarded_headers = state
+        .providers()
+        .forwarded_headers
+        .filter(&provider.provider_kind(), &headers);
+    let proxy_fut = with_forwarded_headers(forwarded_headers, provider.proxy(&chain_id, body));
+    let call_timeout = state.providers().retry_policies.policy(&chain_id).timeout;
+    let timeout_fut = timeout(call_timeout, proxy_fut);
+    let proxy_result = timeout_fut.await;
+    let call_latency = external_call_start.elapsed().unwrap_or_default();
+    let provider_call_info = |status: Option<u16>, error: Option<String>| {
+        ProviderCallInfo::new(
+            project_id.clone(),
+            chain_id.clone(),
+            &provider.provider_kind(),
+            call_method.clone(),
+            call_latency,
+            status,
+            error,
+            retry_index,
+        )
+    };
+    let mut response = match proxy_result {
+        Ok(Ok(response)) => response,
+        Ok(Err(e)) => {
             warn!(
-                "Timeout calling provider: {} with {}",
+                "Failed call to provider: {} with {}",
                 provider.provider_kind(),
                 e
             );
-        })
-        .map_err(RpcError::ProxyTimeoutError)?
-        .tap_err(|e| {
+            state
+                .analytics
+                .provider_call(provider_call_info(None, Some(e.to_string())));
+            state.providers().record_rpc_call(
+                &provider.provider_kind(),
+                &chain_id,
+                false,
+                call_latency,
+            );
+            return Err(e);
+        }
+        Err(e) => {
             warn!(
-                "Failed call to provider: {} with {}",
+                "Timeout calling provider: {} with {}",
                 provider.provider_kind(),
                 e
             );
-        })?;
+            state
+                .analytics
+                .provider_call(provider_call_info(None, Some("timeout".to_owned())));
+            state.providers().record_rpc_call(
+                &provider.provider_kind(),
+                &chain_id,
+                false,
+                call_latency,
+            );
+            return Err(RpcError::ProxyTimeoutError(e));
+        }
+    };
 
     state.metrics.add_status_code_for_provider(
         &provider.provider_kind(),
@@ -420,7 +596,23 @@ pub async fn rpc_provider_call(
         None,
     );
 
-    if provider.is_rate_limited(&mut response).await {
+    let is_rate_limited = provider.is_rate_limited(&mut response).await;
+    state.analytics.provider_call(provider_call_info(
+        Some(response.status().as_u16()),
+        is_rate_limited.then(|| "rate_limited".to_owned()),
+    ));
+    // Same availability criteria as for the Prometheus-driven weights
+    let is_available = !is_rate_limited
+        && (response.status().is_success()
+            || response.status() == http::StatusCode::BAD_REQUEST
+            || response.status() == http::StatusCode::NOT_FOUND);
+    state.providers().record_rpc_call(
+        &provider.provider_kind(),
+        &chain_id,
+        is_available,
+        call_latency,
+    );
+    if is_rate_limited {
         state
             .metrics
             .add_rate_limited_call(provider.borrow(), project_id);
@@ -107,6 +107,7 @@ impl Service<RequestPacket> for SelfRpcTransport {
                 axum::extract::State(state),
                 axum::extract::ConnectInfo(connect_info),
                 axum::extract::Query(query),
+                axum::extract::RawQuery(None),
                 headers,
                 body,
             )
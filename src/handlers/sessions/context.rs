@@ -3,14 +3,13 @@ use {
     crate::{
         error::RpcError,
         state::AppState,
-        storage::irn::OperationType,
         utils::{crypto::disassemble_caip10, simple_request_json::SimpleRequestJson},
     },
     axum::{
         extract::{Path, Query, State},
         response::{IntoResponse, Response},
     },
-    std::{sync::Arc, time::SystemTime},
+    std::sync::Arc,
     wc::metrics::{future_metrics, FutureExt},
 };
 
@@ -35,22 +34,21 @@ async fn handler_internal(
     let project_id = query_params.project_id.clone();
     state.validate_project_access_and_quota(&project_id).await?;
 
-    let irn_client = state.irn.as_ref().ok_or(RpcError::IrnNotConfigured)?;
+    let irn_client = state
+        .persistent_storage
+        .as_ref()
+        .ok_or(RpcError::StorageNotConfigured)?;
 
     // Checking the CAIP-10 address format
     disassemble_caip10(&address)?;
 
     // Get the PCI object from the IRN
-    let irn_call_start = SystemTime::now();
     let storage_permissions_item = irn_client
         .hget(address.clone(), request_payload.pci.clone())
         .await?
         .ok_or_else(|| {
             RpcError::PermissionNotFound(address.clone(), request_payload.pci.clone())
         })?;
-    state
-        .metrics
-        .add_irn_latency(irn_call_start, OperationType::Hget);
     let mut storage_permissions_item =
         serde_json::from_slice::<StoragePermissionsItem>(&storage_permissions_item)?;
 
@@ -58,17 +56,14 @@ async fn handler_internal(
     storage_permissions_item.context = Some(request_payload.context);
 
     // Store it back to the IRN database
-    let irn_call_start = SystemTime::now();
     irn_client
         .hset(
             address,
             request_payload.pci,
             serde_json::to_vec(&storage_permissions_item)?,
+            state.config.storage.sessions_ttl(),
         )
         .await?;
-    state
-        .metrics
-        .add_irn_latency(irn_call_start, OperationType::Hset);
 
     Ok(().into_response())
 }
@@ -1,22 +1,24 @@
 use {
     super::{CoSignRequest, StoragePermissionsItem},
     crate::{
+        database::sessions_spending,
         error::RpcError,
         state::AppState,
-        storage::irn::OperationType,
         utils::{
             crypto::{
                 abi_encode_two_bytes_arrays, call_get_user_op_hash, disassemble_caip10,
-                is_address_valid, pack_signature, to_eip191_message, CaipNamespaces, ChainId,
-                UserOperation,
+                is_address_valid, pack_signature, to_eip191_message,
+                verify_webauthn_p256_signature, CaipNamespaces, ChainId, UserOperation,
             },
             permissions::{
-                native_token_transfer_permission_check, ContractCallPermissionData,
-                NativeTokenAllowancePermissionData, PermissionType,
+                contract_allowlist_policy_check, native_token_transfer_permission_check,
+                spending_limit_policy_check, spending_reservations, ContractAllowlistPolicyData,
+                ContractCallPermissionData, NativeTokenAllowancePermissionData, PermissionType,
+                PolicySpendings, PolicyType, SignerKeyType, SpendingLimitPolicyData,
             },
             sessions::{
                 extract_contract_call_addresses_from_execution_batch,
-                extract_execution_batch_components,
+                extract_execution_batch_components, extract_spendings_from_execution_batch,
             },
             simple_request_json::SimpleRequestJson,
             validators::is_ownable_validator_address,
@@ -37,6 +39,7 @@ use {
     serde::{Deserialize, Serialize},
     serde_json::json,
     std::{collections::HashSet, str::FromStr, sync::Arc, time::SystemTime},
+    tracing::error,
     wc::metrics::{future_metrics, FutureExt},
 };
 
@@ -92,8 +95,65 @@ async fn handler_internal(
     let project_id = query_payload.project_id.clone();
     state.validate_project_access_and_quota(&project_id).await?;
 
+    let chain_id_caip2 = validate_cosigner_address(&caip10_address)?;
+    let rpc_project_id = get_cosigner_rpc_project_id(&state)?;
+    let now = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs() as usize;
+
+    let storage_permissions_item =
+        get_active_permission_item(&state, &caip10_address, &request_payload.pci, now).await?;
+    let mut policy_spendings = PolicySpendings::new();
+
+    let checked_user_op = check_user_op(
+        rpc_project_id,
+        &chain_id_caip2,
+        &storage_permissions_item,
+        request_payload.user_op,
+        &mut policy_spendings,
+        state
+            .config
+            .server
+            .sessions_passkey_user_verification_required,
+    )
+    .await?;
+
+    // The spendings are reserved before the cosigning, so the concurrent
+    // cosignings of the permission can't exceed the limits together
+    reserve_spendings(
+        &state,
+        &caip10_address,
+        &storage_permissions_item,
+        &policy_spendings,
+        now,
+    )
+    .await?;
+    let signature = match sign_user_op(&storage_permissions_item, checked_user_op) {
+        Ok(signature) => signature,
+        Err(e) => {
+            release_spendings(
+                &state,
+                &caip10_address,
+                &storage_permissions_item,
+                &policy_spendings,
+                now,
+            )
+            .await;
+            return Err(e);
+        }
+    };
+
+    Ok(Json(json!({
+        "signature": format!("0x{}", hex::encode(signature)),
+    }))
+    .into_response())
+}
+
+/// Validate the CAIP-10 address for the cosigner and return the CAIP-2 chain ID
+pub(crate) fn validate_cosigner_address(caip10_address: &str) -> Result<String, RpcError> {
     // Checking the CAIP-10 address format
-    let (namespace, chain_id, address) = disassemble_caip10(&caip10_address)?;
+    let (namespace, chain_id, address) = disassemble_caip10(caip10_address)?;
     if namespace != CaipNamespaces::Eip155 {
         return Err(RpcError::UnsupportedNamespace(namespace));
     }
@@ -109,70 +169,151 @@ async fn handler_internal(
         return Err(RpcError::UnsupportedChain(chain_id.clone()));
     }
 
-    let chain_id_caip2 = format!("{namespace}:{chain_id}");
-    let mut user_op = request_payload.user_op.clone();
+    Ok(format!("{namespace}:{chain_id}"))
+}
 
-    // Project ID for internal json-rpc calls
-    let rpc_project_id = state
+/// Project ID for internal json-rpc calls
+pub(super) fn get_cosigner_rpc_project_id(state: &AppState) -> Result<&str, RpcError> {
+    state
         .config
         .server
         .testing_project_id
-        .as_ref()
+        .as_deref()
         .ok_or_else(|| {
             RpcError::InvalidConfiguration(
                 "Missing testing project id in the configuration for the cosigner RPC calls"
                     .to_string(),
             )
-        })?;
-
-    // Get the userOp hash
-    let contract_address = ENTRY_POINT_V07_CONTRACT_ADDRESS
-        .parse::<H160>()
-        .map_err(|_| RpcError::InvalidAddress)?;
-    let user_op_hash = call_get_user_op_hash(
-        rpc_project_id,
-        &chain_id_caip2,
-        contract_address,
-        user_op.clone(),
-        None,
-    )
-    .await?;
-    let eip191_user_op_hash = to_eip191_message(&user_op_hash);
-
-    // Get the PCI object from the IRN
-    let irn_client = state.irn.as_ref().ok_or(RpcError::IrnNotConfigured)?;
-    let irn_call_start = SystemTime::now();
+        })
+}
 
+/// Get the PCI object from the IRN and check it's not revoked or expired
+pub(crate) async fn get_active_permission_item(
+    state: &AppState,
+    caip10_address: &str,
+    pci: &str,
+    now: usize,
+) -> Result<StoragePermissionsItem, RpcError> {
+    let irn_client = state
+        .persistent_storage
+        .as_ref()
+        .ok_or(RpcError::StorageNotConfigured)?;
     let storage_permissions_item = irn_client
-        .hget(caip10_address.clone(), request_payload.pci.clone())
+        .hget(caip10_address.to_string(), pci.to_string())
         .await?
-        .ok_or_else(|| {
-            RpcError::PermissionNotFound(caip10_address.clone(), request_payload.pci.clone())
-        })?;
-    state
-        .metrics
-        .add_irn_latency(irn_call_start, OperationType::Hget);
+        .ok_or_else(|| RpcError::PermissionNotFound(caip10_address.to_string(), pci.to_string()))?;
     let storage_permissions_item =
         serde_json::from_slice::<StoragePermissionsItem>(&storage_permissions_item)?;
 
     // Check if the permission is revoked
     if storage_permissions_item.revoked_at.is_some() {
-        return Err(RpcError::RevokedPermission(request_payload.pci.clone()));
+        return Err(RpcError::RevokedPermission(pci.to_string()));
     }
 
     // Check if the permission is expired
-    if storage_permissions_item.expiry
-        < SystemTime::now()
-            .duration_since(SystemTime::UNIX_EPOCH)
-            .unwrap_or_default()
-            .as_secs() as usize
+    if storage_permissions_item.expiry < now {
+        return Err(RpcError::PermissionExpired(pci.to_string()));
+    }
+
+    Ok(storage_permissions_item)
+}
+
+/// Reserve the request policies spendings in the permission cumulative usage
+/// for the whole permission lifetime, rejects when any limit is exceeded
+pub(super) async fn reserve_spendings(
+    state: &AppState,
+    caip10_address: &str,
+    storage_permissions_item: &StoragePermissionsItem,
+    policy_spendings: &PolicySpendings,
+    now: usize,
+) -> Result<(), RpcError> {
+    let reservations = spending_reservations(policy_spendings);
+    if reservations.is_empty() {
+        return Ok(());
+    }
+    let reserved = sessions_spending::reserve_spendings(
+        &state.postgres,
+        caip10_address,
+        &storage_permissions_item.pci,
+        &reservations,
+        now as i64,
+        storage_permissions_item.expiry as i64,
+    )
+    .await?;
+    if !reserved {
+        return Err(RpcError::CosignerPermissionDenied(
+            "Execution spendings exceed the cumulative spending limit".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Release the spendings reserved by `reserve_spendings` when the request
+/// isn't cosigned. The failed release is logged only, the original error is
+/// returned to the client.
+pub(super) async fn release_spendings(
+    state: &AppState,
+    caip10_address: &str,
+    storage_permissions_item: &StoragePermissionsItem,
+    policy_spendings: &PolicySpendings,
+    now: usize,
+) {
+    let reservations = spending_reservations(policy_spendings);
+    if reservations.is_empty() {
+        return;
+    }
+    if let Err(e) = sessions_spending::release_spendings(
+        &state.postgres,
+        caip10_address,
+        &storage_permissions_item.pci,
+        &reservations,
+        now as i64,
+    )
+    .await
     {
-        return Err(RpcError::PermissionExpired(request_payload.pci.clone()));
+        error!(
+            "Failed to release the spendings of the permission {}: {e}",
+            storage_permissions_item.pci
+        );
     }
+}
+
+/// UserOp checked against the permissions and policies to be cosigned
+pub(super) struct CheckedUserOp {
+    eip191_user_op_hash: Vec<u8>,
+    signature: Bytes,
+    validator_address: Address,
+}
+
+/// Check the userOp against the permissions and policies. The `spending-limit`
+/// policies spendings are added to `policy_spendings`, reserving them before
+/// the cosigning is up to the caller.
+pub(super) async fn check_user_op(
+    rpc_project_id: &str,
+    chain_id_caip2: &str,
+    storage_permissions_item: &StoragePermissionsItem,
+    user_op: UserOperation,
+    policy_spendings: &mut PolicySpendings,
+    passkey_user_verification_required: bool,
+) -> Result<CheckedUserOp, RpcError> {
+    let pci = storage_permissions_item.pci.clone();
+
+    // Get the userOp hash
+    let contract_address = ENTRY_POINT_V07_CONTRACT_ADDRESS
+        .parse::<H160>()
+        .map_err(|_| RpcError::InvalidAddress)?;
+    let user_op_hash = call_get_user_op_hash(
+        rpc_project_id,
+        chain_id_caip2,
+        contract_address,
+        user_op.clone(),
+        None,
+    )
+    .await?;
+    let eip191_user_op_hash = to_eip191_message(&user_op_hash);
 
-    let call_data = request_payload.user_op.call_data.clone();
     // Extract the batch components
-    let execution_batch = extract_execution_batch_components(&call_data)?;
+    let execution_batch = extract_execution_batch_components(&user_op.call_data)?;
 
     // Check the permissions length
     if storage_permissions_item.permissions.is_empty() {
@@ -232,14 +373,98 @@ async fn handler_internal(
         ));
     }
 
+    // Policies evaluation, every policy must be satisfied. Unknown policies are
+    // rejected as the cosigner can't enforce them.
+    let mut spending_limits = Vec::new();
+    for (policy_index, policy) in storage_permissions_item.policies.iter().enumerate() {
+        match PolicyType::from_str(policy.r#type.as_str()) {
+            Ok(PolicyType::ContractAllowlist) => {
+                contract_allowlist_policy_check(
+                    execution_batch.clone(),
+                    &serde_json::from_value::<ContractAllowlistPolicyData>(policy.data.clone())?,
+                )?;
+            }
+            Ok(PolicyType::SpendingLimit) => spending_limits.push((
+                policy_index,
+                serde_json::from_value::<SpendingLimitPolicyData>(policy.data.clone())?,
+            )),
+            Err(_) => return Err(RpcError::CosignerUnsupportedPolicy(policy.r#type.clone())),
+        }
+    }
+    if !spending_limits.is_empty() {
+        let limited_tokens = spending_limits
+            .iter()
+            .filter_map(|(_, policy_data)| policy_data.token)
+            .collect::<HashSet<_>>();
+        let spendings =
+            extract_spendings_from_execution_batch(execution_batch.clone(), &limited_tokens)?;
+        for (policy_index, policy_data) in spending_limits {
+            spending_limit_policy_check(&spendings, policy_index, policy_data, policy_spendings)?;
+        }
+    }
+
     // Check and get the permission context if it's updated
     let permission_context = storage_permissions_item
         .context
         .clone()
-        .ok_or_else(|| RpcError::PermissionContextNotUpdated(request_payload.pci.clone()))?;
+        .ok_or_else(|| RpcError::PermissionContextNotUpdated(pci.clone()))?;
+
+    // Extract validator address from permission context (first 20 bytes)
+    let validator_address = if permission_context.len() >= 20 {
+        Address::from_slice(&permission_context[..20])
+    } else {
+        return Err(RpcError::PermissionContextNotUpdated(pci));
+    };
+
+    // Passkey signers verification. Signatures of secp256k1 and mixed signers
+    // are verified on-chain by the validator
+    let signer_keys = storage_permissions_item.signer.signer_keys()?;
+    if !signer_keys.is_empty()
+        && signer_keys
+            .iter()
+            .all(|key| key.r#type == SignerKeyType::Secp256r1)
+    {
+        if is_ownable_validator_address(validator_address) {
+            return Err(RpcError::CosignerPermissionDenied(
+                "OwnableValidator does not support passkey signers".to_string(),
+            ));
+        }
+        let is_verified = signer_keys.iter().any(|key| {
+            verify_webauthn_p256_signature(
+                &user_op_hash,
+                &user_op.signature,
+                &key.public_key,
+                passkey_user_verification_required,
+            )
+            .is_ok()
+        });
+        if !is_verified {
+            return Err(RpcError::CosignerPermissionDenied(
+                "Passkey signature verification failed".to_string(),
+            ));
+        }
+    }
+
+    Ok(CheckedUserOp {
+        eip191_user_op_hash,
+        signature: user_op.signature,
+        validator_address,
+    })
+}
+
+/// Cosign the checked userOp with the permission signing key
+pub(super) fn sign_user_op(
+    storage_permissions_item: &StoragePermissionsItem,
+    checked_user_op: CheckedUserOp,
+) -> Result<Bytes, RpcError> {
+    let CheckedUserOp {
+        eip191_user_op_hash,
+        signature: user_op_signature,
+        validator_address,
+    } = checked_user_op;
 
     // Sign the userOp hash with the permission signing key
-    let signing_key_bytes = hex::decode(storage_permissions_item.signing_key)
+    let signing_key_bytes = hex::decode(&storage_permissions_item.signing_key)
         .map_err(|e| RpcError::WrongHexFormat(e.to_string()))?;
 
     // Create signer from private key
@@ -249,22 +474,13 @@ async fn handler_internal(
     // Create a LocalWallet for signing and signing the hashed message
     let wallet = LocalWallet::from(signer);
 
-    let message_hash = H256::from(&keccak256(eip191_user_op_hash.clone()));
+    let message_hash = H256::from(&keccak256(eip191_user_op_hash));
     let signature = wallet
         .sign_hash(message_hash)
         .map_err(|e| RpcError::SignatureFormatError(e.to_string()))?;
 
     let packed_signature = pack_signature(&signature);
 
-    // Extract validator address from permission context (first 20 bytes)
-    let validator_address = if permission_context.len() >= 20 {
-        Address::from_slice(&permission_context[..20])
-    } else {
-        return Err(RpcError::PermissionContextNotUpdated(
-            request_payload.pci.clone(),
-        ));
-    };
-
     // Determine signature format based on validator address
     let concatenated_signature = if is_ownable_validator_address(validator_address) {
         // For OwnableValidator: concatenate signatures directly (no ABI encoding)
@@ -281,21 +497,15 @@ async fn handler_internal(
         // It does NOT support passkeys. For passkey support, use MultiKeySigner
         // instead.
         let mut concatenated_signature = Vec::new();
-        concatenated_signature.extend_from_slice(&user_op.signature); // Frontend signature
+        concatenated_signature.extend_from_slice(&user_op_signature); // Frontend signature
         concatenated_signature.extend_from_slice(&packed_signature); // Backend signature
         Bytes::from(concatenated_signature)
     } else {
         // For MultiKeySigner: ABI encode the signatures
-        abi_encode_two_bytes_arrays(&packed_signature, &user_op.signature)
+        abi_encode_two_bytes_arrays(&packed_signature, &user_op_signature)
     };
 
-    // Update the userOp with the signature
-    user_op.signature = concatenated_signature;
-
-    Ok(Json(json!({
-        "signature": format!("0x{}", hex::encode(user_op.signature)),
-    }))
-    .into_response())
+    Ok(concatenated_signature)
 }
 
 #[cfg(test)]
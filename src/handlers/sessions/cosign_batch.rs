@@ -0,0 +1,193 @@
+use {
+    super::{
+        cosign::{
+            check_user_op, get_active_permission_item, get_cosigner_rpc_project_id,
+            release_spendings, reserve_spendings, sign_user_op, validate_cosigner_address,
+            CoSignQueryParams,
+        },
+        CoSignBatchRequest,
+    },
+    crate::{
+        error::RpcError,
+        state::AppState,
+        utils::{permissions::PolicySpendings, simple_request_json::SimpleRequestJson},
+    },
+    axum::{
+        extract::{Path, Query, State},
+        response::{IntoResponse, Response},
+        Json,
+    },
+    serde::{Deserialize, Serialize},
+    std::{sync::Arc, time::SystemTime},
+    wc::metrics::{future_metrics, FutureExt},
+};
+
+/// Maximum number of userOps in a single batch
+const MAX_BATCH_SIZE: usize = 20;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CoSignBatchItemStatus {
+    /// The userOp is cosigned
+    Signed,
+    /// The userOp failed the validation
+    Failed,
+    /// The userOp is valid but was not signed since other items in the batch failed
+    Aborted,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CoSignBatchItem {
+    pub status: CoSignBatchItemStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CoSignBatchResponse {
+    pub results: Vec<CoSignBatchItem>,
+}
+
+pub async fn handler(
+    state: State<Arc<AppState>>,
+    address: Path<String>,
+    query_payload: Query<CoSignQueryParams>,
+    SimpleRequestJson(request_payload): SimpleRequestJson<CoSignBatchRequest>,
+) -> Result<Response, RpcError> {
+    handler_internal(state, address, request_payload, query_payload)
+        .with_metrics(future_metrics!("handler_task", "name" => "sessions_co_sign_batch"))
+        .await
+}
+
+#[tracing::instrument(skip(state), level = "debug")]
+async fn handler_internal(
+    state: State<Arc<AppState>>,
+    Path(caip10_address): Path<String>,
+    request_payload: CoSignBatchRequest,
+    query_payload: Query<CoSignQueryParams>,
+) -> Result<Response, RpcError> {
+    let project_id = query_payload.project_id.clone();
+    state.validate_project_access_and_quota(&project_id).await?;
+
+    if request_payload.user_ops.is_empty() || request_payload.user_ops.len() > MAX_BATCH_SIZE {
+        return Err(RpcError::InvalidParameter(format!(
+            "userOps batch size must be between 1 and {MAX_BATCH_SIZE}"
+        )));
+    }
+
+    let chain_id_caip2 = validate_cosigner_address(&caip10_address)?;
+    let rpc_project_id = get_cosigner_rpc_project_id(&state)?;
+    let now = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs() as usize;
+
+    let storage_permissions_item =
+        get_active_permission_item(&state, &caip10_address, &request_payload.pci, now).await?;
+
+    // The spendings are accumulated across the batch items so the limits are
+    // applied to the whole batch
+    let mut policy_spendings = PolicySpendings::new();
+    let mut checked_user_ops = Vec::with_capacity(request_payload.user_ops.len());
+    for user_op in request_payload.user_ops {
+        checked_user_ops.push(
+            check_user_op(
+                rpc_project_id,
+                &chain_id_caip2,
+                &storage_permissions_item,
+                user_op,
+                &mut policy_spendings,
+                state
+                    .config
+                    .server
+                    .sessions_passkey_user_verification_required,
+            )
+            .await,
+        );
+    }
+
+    // The batch is atomic, signatures are returned only if all items are valid
+    // and the whole batch spendings are reserved before the cosigning
+    let all_checked = checked_user_ops.iter().all(Result::is_ok);
+    let reservation = if all_checked {
+        reserve_spendings(
+            &state,
+            &caip10_address,
+            &storage_permissions_item,
+            &policy_spendings,
+            now,
+        )
+        .await
+    } else {
+        Ok(())
+    };
+
+    let results = if all_checked && reservation.is_ok() {
+        let signatures = checked_user_ops
+            .into_iter()
+            .flatten()
+            .map(|checked_user_op| sign_user_op(&storage_permissions_item, checked_user_op))
+            .collect::<Vec<_>>();
+        // Nothing is returned signed when any item failed the signing, so the
+        // reserved batch spendings are released
+        let all_signed = signatures.iter().all(Result::is_ok);
+        if !all_signed {
+            release_spendings(
+                &state,
+                &caip10_address,
+                &storage_permissions_item,
+                &policy_spendings,
+                now,
+            )
+            .await;
+        }
+        signatures
+            .into_iter()
+            .map(|signature| match signature {
+                Ok(_) if !all_signed => CoSignBatchItem {
+                    status: CoSignBatchItemStatus::Aborted,
+                    signature: None,
+                    error: None,
+                },
+                Ok(signature) => CoSignBatchItem {
+                    status: CoSignBatchItemStatus::Signed,
+                    signature: Some(format!("0x{}", hex::encode(signature))),
+                    error: None,
+                },
+                Err(e) => CoSignBatchItem {
+                    status: CoSignBatchItemStatus::Failed,
+                    signature: None,
+                    error: Some(e.to_string()),
+                },
+            })
+            .collect()
+    } else {
+        checked_user_ops
+            .into_iter()
+            .map(|checked_user_op| match (checked_user_op, &reservation) {
+                (Ok(_), _) if !all_checked => CoSignBatchItem {
+                    status: CoSignBatchItemStatus::Aborted,
+                    signature: None,
+                    error: None,
+                },
+                // The checked batch is not signed only when the reservation failed
+                (Ok(_), reservation) => CoSignBatchItem {
+                    status: CoSignBatchItemStatus::Failed,
+                    signature: None,
+                    error: reservation.as_ref().err().map(ToString::to_string),
+                },
+                (Err(e), _) => CoSignBatchItem {
+                    status: CoSignBatchItemStatus::Failed,
+                    signature: None,
+                    error: Some(e.to_string()),
+                },
+            })
+            .collect()
+    };
+
+    Ok(Json(CoSignBatchResponse { results }).into_response())
+}
@@ -1,9 +1,10 @@
 use {
-    super::{NewPermissionPayload, QueryParams, StoragePermissionsItem},
+    super::{
+        NewPermissionPayload, QueryParams, StoragePermissionsItem, SESSIONS_ADDRESSES_INDEX_KEY,
+    },
     crate::{
         error::RpcError,
         state::AppState,
-        storage::irn::OperationType,
         utils::{crypto::disassemble_caip10, simple_request_json::SimpleRequestJson},
     },
     axum::{
@@ -60,11 +61,17 @@ async fn handler_internal(
         .validate_project_access_and_quota(&project_id.clone())
         .await?;
 
-    let irn_client = state.irn.as_ref().ok_or(RpcError::IrnNotConfigured)?;
+    let irn_client = state
+        .persistent_storage
+        .as_ref()
+        .ok_or(RpcError::StorageNotConfigured)?;
 
     // Checking the CAIP-10 address format
     disassemble_caip10(&address)?;
 
+    // Checking the signer keys format
+    request_payload.signer.signer_keys()?;
+
     // Generate a unique permission control identifier
     let pci = uuid::Uuid::new_v4().to_string();
 
@@ -96,17 +103,24 @@ async fn handler_internal(
         revoked_at: None,
     };
 
-    let irn_call_start = SystemTime::now();
     irn_client
         .hset(
             address.clone(),
             pci.clone(),
             serde_json::to_vec(&storage_permissions_item)?,
+            state.config.storage.sessions_ttl(),
+        )
+        .await?;
+
+    // Add the address to the index used by the expired permissions GC
+    irn_client
+        .hset(
+            SESSIONS_ADDRESSES_INDEX_KEY.into(),
+            address.clone(),
+            Vec::new(),
+            state.config.storage.sessions_ttl(),
         )
         .await?;
-    state
-        .metrics
-        .add_irn_latency(irn_call_start, OperationType::Hset);
 
     // Format public key based on API version
     let public_key = match query_params.api_version {
@@ -0,0 +1,213 @@
+use {
+    super::{StoragePermissionsItem, SESSIONS_ADDRESSES_INDEX_KEY},
+    crate::{
+        database::sessions_spending, error::RpcError, state::AppState, storage::PersistentStorage,
+    },
+    std::{
+        sync::Arc,
+        time::{Duration, SystemTime},
+    },
+    tokio::time::{interval, MissedTickBehavior},
+    tracing::{debug, info, warn},
+    uuid::Uuid,
+};
+
+const GC_INTERVAL: Duration = Duration::from_secs(60 * 60); // 1 hour
+const SCAN_BATCH_SIZE: u32 = 255;
+/// Grace period to keep expired or revoked permissions listed before the removal
+const REMOVAL_GRACE_PERIOD_SECS: usize = 60 * 60 * 24; // 1 day
+const LEADER_LOCK_KEY: &str = "sessions_gc/leader";
+
+/// Background job that removes expired and revoked permissions from the
+/// persistent storage. Permissions stored before the addresses index existed
+/// are not found by the job and are left to the storage TTL.
+pub async fn run(state: Arc<AppState>) {
+    let Some(irn_client) = state.persistent_storage.as_ref() else {
+        warn!("Persistent storage is not configured, expired permissions GC is disabled");
+        return;
+    };
+
+    let instance_id = Uuid::new_v4().to_string();
+    let mut poll = interval(GC_INTERVAL);
+    poll.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    loop {
+        poll.tick().await;
+        if !is_leader(&state, &instance_id).await {
+            continue;
+        }
+        debug!("starting expired permissions garbage collection");
+        let started = SystemTime::now();
+        let now = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs() as usize;
+
+        let mut cleaned = 0u64;
+        let mut cursor = None;
+        loop {
+            let (addresses, next_cursor) = match irn_client
+                .hscan(SESSIONS_ADDRESSES_INDEX_KEY.into(), SCAN_BATCH_SIZE, cursor)
+                .await
+            {
+                Ok(result) => result,
+                Err(e) => {
+                    warn!(error = %e, "failed to scan the sessions addresses index");
+                    break;
+                }
+            };
+
+            for (address, _) in addresses {
+                match collect_address(irn_client, &address, now).await {
+                    Ok(count) => cleaned += count,
+                    Err(e) => {
+                        warn!(address, error = %e, "failed to collect expired permissions");
+                    }
+                }
+            }
+
+            cursor = next_cursor;
+            if cursor.is_none() {
+                break;
+            }
+        }
+
+        // Postgres storage doesn't expire the values by itself
+        match irn_client.purge_expired().await {
+            Ok(purged) if purged > 0 => debug!("purged {purged} expired persistent storage values"),
+            Ok(_) => {}
+            Err(e) => warn!(error = %e, "failed to purge the expired persistent storage values"),
+        }
+        match sessions_spending::delete_expired(&state.postgres).await {
+            Ok(deleted) if deleted > 0 => debug!("removed {deleted} expired spendings usages"),
+            Ok(_) => {}
+            Err(e) => warn!(error = %e, "failed to remove the expired spendings usages"),
+        }
+
+        state.metrics.add_sessions_gc_cleaned(cleaned);
+        state.metrics.add_sessions_gc_latency(started);
+        info!("expired permissions garbage collection removed {cleaned} items");
+    }
+}
+
+/// Only the replica holding the lock runs the GC. Every replica is the leader
+/// when the lock storage is not configured.
+async fn is_leader(state: &AppState, instance_id: &str) -> bool {
+    let Some(lock_storage) = &state.lock_storage else {
+        return true;
+    };
+    match lock_storage
+        .acquire_lock(LEADER_LOCK_KEY, instance_id, GC_INTERVAL * 2)
+        .await
+    {
+        Ok(acquired) => acquired,
+        Err(e) => {
+            warn!(error = %e, "failed to acquire the sessions GC leader lock");
+            false
+        }
+    }
+}
+
+/// Remove expired and revoked permissions for the address and drop the address
+/// from the index when no permissions are left
+async fn collect_address(
+    irn_client: &dyn PersistentStorage,
+    address: &str,
+    now: usize,
+) -> Result<u64, RpcError> {
+    let mut cleaned = 0u64;
+    let mut remaining = 0u64;
+    let mut cursor = None;
+    loop {
+        let (pcis, next_cursor) = irn_client
+            .hscan(address.to_string(), SCAN_BATCH_SIZE, cursor)
+            .await?;
+
+        for (pci, entity) in pcis {
+            let collectable = match serde_json::from_slice::<StoragePermissionsItem>(&entity) {
+                Ok(item) => is_collectable(&item, now),
+                Err(e) => {
+                    // Items that can't be deserialized can't be used by the cosigner anyway
+                    debug!(address, pci, error = %e, "removing malformed permission item");
+                    true
+                }
+            };
+
+            if !collectable {
+                remaining += 1;
+                continue;
+            }
+
+            irn_client.hdel(address.to_string(), pci).await?;
+            cleaned += 1;
+        }
+
+        cursor = next_cursor;
+        if cursor.is_none() {
+            break;
+        }
+    }
+
+    if remaining == 0 {
+        irn_client
+            .hdel(SESSIONS_ADDRESSES_INDEX_KEY.into(), address.to_string())
+            .await?;
+    }
+
+    Ok(cleaned)
+}
+
+/// Check if the permission item is past its expiry or revoked for longer
+/// than the grace period
+fn is_collectable(item: &StoragePermissionsItem, now: usize) -> bool {
+    let expired = item.expiry.saturating_add(REMOVAL_GRACE_PERIOD_SECS) < now;
+    let revoked = item
+        .revoked_at
+        .is_some_and(|revoked_at| revoked_at.saturating_add(REMOVAL_GRACE_PERIOD_SECS) < now);
+    expired || revoked
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, crate::handlers::sessions::PermissionTypeData, serde_json::json};
+
+    fn permission_item(expiry: usize, revoked_at: Option<usize>) -> StoragePermissionsItem {
+        StoragePermissionsItem {
+            pci: "pci".into(),
+            expiry,
+            created_at: 0,
+            project_id: "project".into(),
+            signer: PermissionTypeData {
+                r#type: "keys".into(),
+                data: json!({}),
+            },
+            permissions: vec![],
+            policies: vec![],
+            context: None,
+            verification_key: "".into(),
+            signing_key: "".into(),
+            revoked_at,
+        }
+    }
+
+    #[test]
+    fn collectable_permissions() {
+        let now = 10 * REMOVAL_GRACE_PERIOD_SECS;
+
+        // Active permission
+        assert!(!is_collectable(&permission_item(now + 1, None), now));
+        // Expired but still in the grace period
+        assert!(!is_collectable(&permission_item(now - 1, None), now));
+        // Expired past the grace period
+        assert!(is_collectable(
+            &permission_item(now - REMOVAL_GRACE_PERIOD_SECS - 1, None),
+            now
+        ));
+        // Revoked past the grace period
+        assert!(is_collectable(
+            &permission_item(now + 1, Some(now - REMOVAL_GRACE_PERIOD_SECS - 1)),
+            now
+        ));
+        // Revoked recently
+        assert!(!is_collectable(&permission_item(now + 1, Some(now)), now));
+    }
+}
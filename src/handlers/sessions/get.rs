@@ -2,12 +2,8 @@ use {
     super::StoragePermissionsItem,
     crate::{
         error::RpcError,
-        metrics::Metrics,
         state::AppState,
-        storage::{
-            error::StorageError,
-            irn::{Irn, OperationType},
-        },
+        storage::{error::StorageError, PersistentStorage},
         utils::crypto::disassemble_caip10,
     },
     alloy::primitives::Bytes,
@@ -18,7 +14,7 @@ use {
     },
     serde::{Deserialize, Serialize},
     serde_json::json,
-    std::{sync::Arc, time::SystemTime},
+    std::sync::Arc,
     uuid::Uuid,
     wc::metrics::{future_metrics, FutureExt},
 };
@@ -49,26 +45,24 @@ async fn handler_internal(
     let project_id = query_params.project_id.clone();
     state.validate_project_access_and_quota(&project_id).await?;
 
-    let irn_client = state.irn.as_ref().ok_or(RpcError::IrnNotConfigured)?;
+    let irn_client = state
+        .persistent_storage
+        .as_ref()
+        .ok_or(RpcError::StorageNotConfigured)?;
 
     // Checking the CAIP-10 address format
     disassemble_caip10(&address.clone())?;
 
-    let context = get_session_context(
-        address.clone(),
-        query_params.pci,
-        irn_client,
-        &state.metrics,
-    )
-    .await
-    .map_err(|e| match e {
-        GetSessionContextError::PermissionNotFound(address, pci) => {
-            RpcError::PermissionNotFound(address.to_string(), pci.to_string())
-        }
-        GetSessionContextError::InternalGetSessionContextError(e) => {
-            RpcError::InternalGetSessionContextError(e)
-        }
-    })?;
+    let context = get_session_context(address.clone(), query_params.pci, irn_client)
+        .await
+        .map_err(|e| match e {
+            GetSessionContextError::PermissionNotFound(address, pci) => {
+                RpcError::PermissionNotFound(address.to_string(), pci.to_string())
+            }
+            GetSessionContextError::InternalGetSessionContextError(e) => {
+                RpcError::InternalGetSessionContextError(e)
+            }
+        })?;
 
     let response = json!({"context": context});
 
@@ -96,10 +90,8 @@ pub enum InternalGetSessionContextError {
 pub async fn get_session_context(
     address: String,
     pci: Uuid,
-    irn_client: &Irn,
-    metrics: &Metrics,
+    irn_client: &dyn PersistentStorage,
 ) -> Result<Option<Bytes>, GetSessionContextError> {
-    let irn_call_start = SystemTime::now();
     let storage_permissions_item = irn_client
         .hget(address.clone(), pci.to_string())
         .await
@@ -109,7 +101,6 @@ pub async fn get_session_context(
             )
         })?
         .ok_or(GetSessionContextError::PermissionNotFound(address, pci))?;
-    metrics.add_irn_latency(irn_call_start, OperationType::Hget);
 
     let storage_permissions_item = serde_json::from_slice::<StoragePermissionsItem>(
         &storage_permissions_item,
@@ -1,9 +1,6 @@
 use {
     super::{PermissionTypeData, QueryParams, StoragePermissionsItem},
-    crate::{
-        error::RpcError, state::AppState, storage::irn::OperationType,
-        utils::crypto::disassemble_caip10,
-    },
+    crate::{error::RpcError, state::AppState, utils::crypto::disassemble_caip10},
     alloy::primitives::Bytes,
     axum::{
         extract::{Path, Query, State},
@@ -11,7 +8,7 @@ use {
         Json,
     },
     serde::{Deserialize, Serialize},
-    std::{sync::Arc, time::SystemTime},
+    std::sync::Arc,
     wc::metrics::{future_metrics, FutureExt},
 };
 
@@ -65,19 +62,18 @@ async fn handler_internal(
     let project_id = query_params.project_id.clone();
     state.validate_project_access_and_quota(&project_id).await?;
 
-    let irn_client = state.irn.as_ref().ok_or(RpcError::IrnNotConfigured)?;
+    let irn_client = state
+        .persistent_storage
+        .as_ref()
+        .ok_or(RpcError::StorageNotConfigured)?;
 
     // Checking the CAIP-10 address format
     disassemble_caip10(&address.clone())?;
 
     // get all permission control identifiers for the address
-    let irn_call_start = SystemTime::now();
     let (pcis, _) = irn_client
         .hscan(address.clone(), MAX_PCIS_COUNT, None)
         .await?;
-    state
-        .metrics
-        .add_irn_latency(irn_call_start, OperationType::Hscan);
 
     let mut result_pcis: Vec<Pci> = Vec::new();
     for (_, entity) in pcis {
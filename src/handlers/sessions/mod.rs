@@ -1,5 +1,11 @@
 use {
-    crate::utils::crypto::UserOperation,
+    crate::{
+        error::RpcError,
+        utils::{
+            crypto::{is_p256_public_key_valid, UserOperation},
+            permissions::{KeysSignerData, SignerKey, SignerKeyType},
+        },
+    },
     alloy::primitives::Bytes,
     serde::{Deserialize, Serialize},
     serde_json::Value,
@@ -7,11 +13,16 @@ use {
 
 pub mod context;
 pub mod cosign;
+pub mod cosign_batch;
 pub mod create;
+pub mod gc;
 pub mod get;
 pub mod list;
 pub mod revoke;
 
+/// IRN hashmap key for the index of addresses that have stored permissions
+pub const SESSIONS_ADDRESSES_INDEX_KEY: &str = "sessions_addresses_index";
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct QueryParams {
@@ -36,6 +47,30 @@ pub struct PermissionTypeData {
     pub data: Value,
 }
 
+impl PermissionTypeData {
+    /// Get the signer keys for the `keys` and `key` signer types,
+    /// other signer types have no keys to verify
+    pub fn signer_keys(&self) -> Result<Vec<SignerKey>, RpcError> {
+        let keys = match self.r#type.as_str() {
+            "keys" => serde_json::from_value::<KeysSignerData>(self.data.clone())?.keys,
+            "key" => vec![serde_json::from_value::<SignerKey>(self.data.clone())?],
+            _ => return Ok(vec![]),
+        };
+
+        for key in &keys {
+            if key.r#type == SignerKeyType::Secp256r1 && !is_p256_public_key_valid(&key.public_key)
+            {
+                return Err(RpcError::KeyFormatError(format!(
+                    "Invalid secp256r1 public key: {}",
+                    key.public_key
+                )));
+            }
+        }
+
+        Ok(keys)
+    }
+}
+
 /// Permissions Context item schema
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -61,7 +96,7 @@ pub struct StoragePermissionsItem {
     pci: String,
     expiry: usize,
     created_at: usize,
-    project_id: String,
+    pub(crate) project_id: String,
     signer: PermissionTypeData,
     permissions: Vec<PermissionTypeData>,
     policies: Vec<PermissionTypeData>,
@@ -76,6 +111,17 @@ pub struct StoragePermissionsItem {
 #[serde(rename_all = "camelCase")]
 pub struct PermissionRevokeRequest {
     pci: String,
+    /// Account owner EIP-191/EIP-1271 signature of the revoke message
+    signature: Option<String>,
+    /// Unixtime of the signed revoke message
+    timestamp: Option<u64>,
+}
+
+/// Message to be signed by the account owner to revoke the permission
+pub fn revoke_permission_message(caip10_address: &str, pci: &str, timestamp: u64) -> String {
+    format!(
+        "Revoke session permission\nAddress: {caip10_address}\nPCI: {pci}\nTimestamp: {timestamp}"
+    )
 }
 
 /// Co-sign request schema
@@ -85,3 +131,11 @@ pub struct CoSignRequest {
     pub pci: String,
     pub user_op: UserOperation,
 }
+
+/// Batch co-sign request schema
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CoSignBatchRequest {
+    pub pci: String,
+    pub user_ops: Vec<UserOperation>,
+}
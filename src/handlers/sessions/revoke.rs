@@ -1,10 +1,19 @@
 use {
-    super::{PermissionRevokeRequest, QueryParams, StoragePermissionsItem},
+    super::{
+        revoke_permission_message, PermissionRevokeRequest, QueryParams, StoragePermissionsItem,
+    },
     crate::{
+        analytics::MessageSource,
         error::RpcError,
+        names::utils::is_timestamp_within_interval,
         state::AppState,
-        storage::irn::OperationType,
-        utils::{crypto::disassemble_caip10, simple_request_json::SimpleRequestJson},
+        utils::{
+            crypto::{
+                disassemble_caip10, normalize_to_checksum, verify_message_signature,
+                CaipNamespaces,
+            },
+            simple_request_json::SimpleRequestJson,
+        },
     },
     axum::{
         extract::{Path, Query, State},
@@ -14,6 +23,9 @@ use {
     wc::metrics::{future_metrics, FutureExt},
 };
 
+/// Allowed clock drift in seconds for the signed revoke message timestamp
+const REVOKE_SIGNATURE_TIMESTAMP_THRESHOLD: u64 = 300;
+
 pub async fn handler(
     state: State<Arc<AppState>>,
     address: Path<String>,
@@ -35,22 +47,71 @@ async fn handler_internal(
     let project_id = query_params.project_id.clone();
     state.validate_project_access_and_quota(&project_id).await?;
 
-    let irn_client = state.irn.as_ref().ok_or(RpcError::IrnNotConfigured)?;
+    let irn_client = state
+        .persistent_storage
+        .as_ref()
+        .ok_or(RpcError::StorageNotConfigured)?;
 
     // Checking the CAIP-10 address format
-    disassemble_caip10(&address)?;
+    let (namespace, chain_id, account) = disassemble_caip10(&address)?;
+
+    // Check the account owner signature if provided or required
+    match (&request_payload.signature, request_payload.timestamp) {
+        (Some(signature), Some(timestamp)) => {
+            if namespace != CaipNamespaces::Eip155 {
+                return Err(RpcError::UnsupportedNamespace(namespace));
+            }
+            if !signature.starts_with("0x") {
+                return Err(RpcError::SignatureFormatError(
+                    "Signature must be 0x-prefixed hex".into(),
+                ));
+            }
+            if !is_timestamp_within_interval(timestamp, REVOKE_SIGNATURE_TIMESTAMP_THRESHOLD) {
+                return Err(RpcError::ExpiredTimestamp(timestamp));
+            }
+            let rpc_project_id = state
+                .config
+                .server
+                .testing_project_id
+                .as_ref()
+                .ok_or_else(|| {
+                    RpcError::InvalidConfiguration(
+                        "Missing testing project id in the configuration for eip1271 lookups"
+                            .to_string(),
+                    )
+                })?;
+            let signature_check = verify_message_signature(
+                &revoke_permission_message(&address, &request_payload.pci, timestamp),
+                signature,
+                &normalize_to_checksum(&account)?,
+                &format!("{namespace}:{chain_id}"),
+                rpc_project_id,
+                MessageSource::SessionRevokeSigValidate,
+                None,
+            )
+            .await
+            .map_err(|_| RpcError::SignatureValidationError("Invalid signature".into()))?;
+            if !signature_check {
+                return Err(RpcError::SignatureValidationError(
+                    "Signature verification error".into(),
+                ));
+            }
+        }
+        (None, None) if !state.config.server.sessions_revoke_signature_required => {}
+        _ => {
+            return Err(RpcError::SignatureValidationError(
+                "Account owner signature and timestamp are required".into(),
+            ))
+        }
+    }
 
     // Get the PCI object from the IRN
-    let irn_call_start = SystemTime::now();
     let storage_permissions_item = irn_client
         .hget(address.clone(), request_payload.pci.clone())
         .await?
         .ok_or_else(|| {
             RpcError::PermissionNotFound(address.clone(), request_payload.pci.clone())
         })?;
-    state
-        .metrics
-        .add_irn_latency(irn_call_start, OperationType::Hget);
     let mut storage_permissions_item =
         serde_json::from_slice::<StoragePermissionsItem>(&storage_permissions_item)?;
 
@@ -67,17 +128,14 @@ async fn handler_internal(
     );
 
     // Store it back to the IRN database
-    let irn_call_start = SystemTime::now();
     irn_client
         .hset(
             address,
             request_payload.pci,
             serde_json::to_vec(&storage_permissions_item)?,
+            state.config.storage.sessions_ttl(),
         )
         .await?;
-    state
-        .metrics
-        .add_irn_latency(irn_call_start, OperationType::Hset);
 
     Ok(().into_response())
 }
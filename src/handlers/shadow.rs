@@ -0,0 +1,94 @@
+use {
+    crate::state::AppState,
+    axum::body::Bytes,
+    hyper::{header, HeaderMap, StatusCode},
+    rand::Rng,
+    std::{sync::Arc, time::Duration},
+    tracing::debug,
+};
+
+const SHADOW_CALL_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Headers forwarded to the shadow deployment, the authorization and cookie
+/// headers are never mirrored
+const FORWARDED_HEADERS: [&str; 7] = [
+    "content-type",
+    "user-agent",
+    "origin",
+    "referer",
+    "solana-client",
+    "x-sdk-type",
+    "x-sdk-version",
+];
+
+/// Copy of the `/v1` request mirrored to the shadow deployment to compare
+/// the status codes before the provider and routing changes are rolled out
+pub struct ShadowRequest {
+    url: String,
+    chain_id: String,
+    headers: HeaderMap,
+    body: Bytes,
+}
+
+impl ShadowRequest {
+    /// Samples the request by the configured mirroring percentage
+    pub fn sample(
+        state: &AppState,
+        chain_id: &str,
+        query: &str,
+        headers: &HeaderMap,
+        body: &Bytes,
+    ) -> Option<Self> {
+        let server = &state.config.server;
+        let base_url = server.shadow_base_url.as_deref()?;
+        if rand::thread_rng().gen_range(0..100) >= server.shadow_percentage {
+            return None;
+        }
+
+        let url = format!("{}/v1?{query}", base_url.trim_end_matches('/'));
+        let headers = headers
+            .iter()
+            .filter(|(name, _)| FORWARDED_HEADERS.contains(&name.as_str()))
+            .map(|(name, value)| (name.clone(), value.clone()))
+            .collect();
+        Some(Self {
+            url,
+            chain_id: chain_id.to_owned(),
+            headers,
+            body: body.clone(),
+        })
+    }
+
+    /// Sends the request in the background and records whether the shadow
+    /// status code diverged from the primary one
+    pub fn spawn(self, state: Arc<AppState>, primary_status: StatusCode) {
+        tokio::spawn(async move {
+            let mut headers = self.headers;
+            headers
+                .entry(header::CONTENT_TYPE)
+                .or_insert(header::HeaderValue::from_static("application/json"));
+            let shadow_status = state
+                .http_client
+                .post(&self.url)
+                .headers(headers)
+                .body(self.body)
+                .timeout(SHADOW_CALL_TIMEOUT)
+                .send()
+                .await
+                .map(|response| response.status().as_u16());
+            match shadow_status {
+                Ok(shadow_status) => state.metrics.add_shadow_request(
+                    &self.chain_id,
+                    primary_status.as_u16(),
+                    Some(shadow_status),
+                ),
+                Err(e) => {
+                    debug!("Shadow request failed: {e}");
+                    state
+                        .metrics
+                        .add_shadow_request(&self.chain_id, primary_status.as_u16(), None);
+                }
+            }
+        });
+    }
+}
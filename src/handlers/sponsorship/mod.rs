@@ -0,0 +1,247 @@
+use {
+    crate::{
+        database::sponsorship::{self, SponsoredOp, SponsorshipPolicy},
+        error::RpcError,
+        providers::SupportedBundlerOps,
+        state::AppState,
+        utils::sessions::{
+            extract_addresses_from_execution_batch, extract_execution_batch_components,
+        },
+    },
+    alloy::primitives::{Address, Bytes, U256},
+    chrono::{Datelike, NaiveDate, Utc},
+    std::str::FromStr,
+    tracing::{debug, warn},
+};
+
+pub mod usage;
+
+/// User operation gas limits fields that are summed up as the sponsored gas
+const USER_OP_GAS_FIELDS: &[&str] = &[
+    "callGasLimit",
+    "verificationGasLimit",
+    "preVerificationGas",
+    "paymasterVerificationGasLimit",
+    "paymasterPostOpGasLimit",
+];
+
+/// Whether the paymaster operation is sponsoring the user operation
+pub fn is_sponsorship_op(op: &SupportedBundlerOps) -> bool {
+    matches!(
+        op,
+        SupportedBundlerOps::PmSponsorUserOperation | SupportedBundlerOps::PmGetPaymasterData
+    )
+}
+
+/// Returns the first day of the current month used as the usage period
+pub fn current_period() -> NaiveDate {
+    let now = Utc::now().date_naive();
+    now.with_day(1).unwrap_or(now)
+}
+
+/// Sum the user operation gas limits, the values from the `overrides`
+/// (paymaster response) take precedence over the user operation ones
+fn get_user_op_gas(user_op: &serde_json::Value, overrides: Option<&serde_json::Value>) -> U256 {
+    USER_OP_GAS_FIELDS
+        .iter()
+        .filter_map(|field| {
+            overrides
+                .and_then(|overrides| overrides.get(field))
+                .or_else(|| user_op.get(field))
+                .and_then(serde_json::Value::as_str)
+                .and_then(|value| U256::from_str(value).ok())
+        })
+        .fold(U256::ZERO, |acc, gas| acc.saturating_add(gas))
+}
+
+/// Check the project's sponsorship policy before sponsoring the user operation.
+/// Returns the policy if the project has it configured.
+pub async fn check_sponsorship_policy(
+    state: &AppState,
+    project_id: &str,
+    chain_id: &str,
+    params: &serde_json::Value,
+) -> Result<Option<SponsorshipPolicy>, RpcError> {
+    let Some(policy) = sponsorship::get_policy(&state.postgres, project_id).await? else {
+        return Ok(None);
+    };
+    let user_op = params.get(0).ok_or_else(|| {
+        RpcError::InvalidParameter("User operation is missing in params".to_string())
+    })?;
+
+    if let Some(allowed_chains) = &policy.allowed_chains {
+        if !allowed_chains.iter().any(|allowed| allowed == chain_id) {
+            return Err(RpcError::SponsorshipPolicyViolation(format!(
+                "chain {chain_id} is not allowed"
+            )));
+        }
+    }
+
+    if let Some(allowed_contracts) = &policy.allowed_contracts {
+        let call_data = user_op
+            .get("callData")
+            .and_then(serde_json::Value::as_str)
+            .and_then(|call_data| Bytes::from_str(call_data).ok())
+            .ok_or_else(|| {
+                RpcError::InvalidParameter("Invalid user operation callData".to_string())
+            })?;
+        let execution_batch = extract_execution_batch_components(&call_data)?;
+        for address in extract_addresses_from_execution_batch(execution_batch)? {
+            let address = address.to_string().to_lowercase();
+            if !allowed_contracts.contains(&address) {
+                return Err(RpcError::SponsorshipPolicyViolation(format!(
+                    "contract {address} is not allowed"
+                )));
+            }
+        }
+    }
+
+    if let Some(max_gas_per_op) = policy.max_gas_per_op {
+        let gas = get_user_op_gas(user_op, None);
+        if gas > U256::from(max_gas_per_op) {
+            return Err(RpcError::SponsorshipPolicyViolation(format!(
+                "user operation gas {gas} exceeds the limit of {max_gas_per_op}"
+            )));
+        }
+    }
+
+    Ok(Some(policy))
+}
+
+/// Sender and nonce identifying the user operation or its receipt
+fn get_sponsored_op(chain_id: &str, value: &serde_json::Value) -> Option<SponsoredOp> {
+    let sender = value
+        .get("sender")
+        .and_then(serde_json::Value::as_str)
+        .and_then(|sender| Address::from_str(sender).ok())?;
+    let nonce = value
+        .get("nonce")
+        .and_then(serde_json::Value::as_str)
+        .and_then(|nonce| U256::from_str(nonce).ok())?;
+    Some(SponsoredOp {
+        chain_id: chain_id.to_string(),
+        sender: sender.to_string().to_lowercase(),
+        nonce: nonce.to_string(),
+    })
+}
+
+/// Check the sponsored user operation gas from the paymaster response against
+/// the policy and charge it to the project's sponsorship usage within the
+/// monthly gas budget. The paymaster response must not be returned when the
+/// charge fails.
+pub async fn charge_sponsored_user_op(
+    state: &AppState,
+    project_id: &str,
+    chain_id: &str,
+    policy: Option<&SponsorshipPolicy>,
+    params: &serde_json::Value,
+    response: &serde_json::Value,
+) -> Result<(), RpcError> {
+    let Some(result) = response.get("result").filter(|result| !result.is_null()) else {
+        debug!("Paymaster didn't sponsor the user operation, skipping usage tracking");
+        return Ok(());
+    };
+    let user_op = params.get(0).unwrap_or(&serde_json::Value::Null);
+    let op = get_sponsored_op(chain_id, user_op).ok_or_else(|| {
+        RpcError::InvalidParameter("Invalid user operation sender or nonce".to_string())
+    })?;
+    let gas = get_user_op_gas(user_op, Some(result));
+
+    if let Some(max_gas_per_op) = policy.and_then(|policy| policy.max_gas_per_op) {
+        if gas > U256::from(max_gas_per_op) {
+            return Err(RpcError::SponsorshipPolicyViolation(format!(
+                "user operation gas {gas} exceeds the limit of {max_gas_per_op}"
+            )));
+        }
+    }
+
+    let gas = i64::try_from(gas).unwrap_or(i64::MAX);
+    let charged = sponsorship::charge_op(
+        &state.postgres,
+        project_id,
+        current_period(),
+        &op,
+        gas,
+        policy.and_then(|policy| policy.monthly_gas_budget),
+    )
+    .await?;
+    if !charged {
+        return Err(RpcError::SponsorshipPolicyViolation(
+            "monthly gas budget is exhausted".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Replace the charged gas limits of the sponsored user operation with the
+/// actual gas used when its receipt is requested by the project
+pub async fn settle_sponsored_user_op(
+    state: &AppState,
+    project_id: &str,
+    chain_id: &str,
+    response: &serde_json::Value,
+) -> Result<(), RpcError> {
+    let Some(receipt) = response.get("result").filter(|result| !result.is_null()) else {
+        return Ok(());
+    };
+    let is_sponsored = receipt
+        .get("paymaster")
+        .and_then(serde_json::Value::as_str)
+        .and_then(|paymaster| Address::from_str(paymaster).ok())
+        .is_some_and(|paymaster| !paymaster.is_zero());
+    if !is_sponsored {
+        return Ok(());
+    }
+    let (Some(op), Some(actual_gas)) = (
+        get_sponsored_op(chain_id, receipt),
+        receipt
+            .get("actualGasUsed")
+            .and_then(serde_json::Value::as_str)
+            .and_then(|gas| U256::from_str(gas).ok()),
+    ) else {
+        warn!("Invalid user operation receipt, skipping the sponsorship settlement");
+        return Ok(());
+    };
+
+    let actual_gas = i64::try_from(actual_gas).unwrap_or(i64::MAX);
+    sponsorship::settle_op(&state.postgres, project_id, &op, actual_gas).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_sponsored_op() {
+        let receipt = serde_json::json!({
+            "sender": "0xA0b86991c6218b36c1d19d4a2e9eB0cE3606eB48",
+            "nonce": "0x10",
+        });
+        let op = get_sponsored_op("eip155:1", &receipt).unwrap();
+        assert_eq!(op.sender, "0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48");
+        assert_eq!(op.nonce, "16");
+
+        assert!(get_sponsored_op("eip155:1", &serde_json::json!({ "nonce": "0x10" })).is_none());
+    }
+
+    #[test]
+    fn test_get_user_op_gas() {
+        let user_op = serde_json::json!({
+            "callGasLimit": "0x100",
+            "verificationGasLimit": "0x100",
+            "preVerificationGas": "0x100",
+        });
+        assert_eq!(get_user_op_gas(&user_op, None), U256::from(0x300));
+
+        let paymaster_result = serde_json::json!({
+            "callGasLimit": "0x200",
+            "paymasterVerificationGasLimit": "0x50",
+            "paymasterPostOpGasLimit": "0x50",
+        });
+        assert_eq!(
+            get_user_op_gas(&user_op, Some(&paymaster_result)),
+            U256::from(0x4a0)
+        );
+    }
+}
@@ -0,0 +1,84 @@
+use {
+    super::current_period,
+    crate::{database::sponsorship, error::RpcError, state::AppState},
+    axum::{
+        extract::{Path, Query, State},
+        response::{IntoResponse, Response},
+        Json,
+    },
+    chrono::NaiveDate,
+    serde::{Deserialize, Serialize},
+    std::sync::Arc,
+    wc::metrics::{future_metrics, FutureExt},
+};
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageQueryParams {
+    /// Usage month in the `YYYY-MM` format, current month is used by default
+    pub period: Option<String>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageResponse {
+    pub period: String,
+    pub monthly_gas_budget: Option<i64>,
+    pub sponsored_ops: i64,
+    pub sponsored_gas: i64,
+    pub chains: Vec<ChainUsage>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ChainUsage {
+    pub chain_id: String,
+    pub sponsored_ops: i64,
+    pub sponsored_gas: i64,
+}
+
+/// Project's sponsorship usage, served on the private port only since the
+/// usage is not authorized by the project
+pub async fn handler(
+    state: State<Arc<AppState>>,
+    project_id: Path<String>,
+    query: Query<UsageQueryParams>,
+) -> Result<Response, RpcError> {
+    handler_internal(state, project_id, query)
+        .with_metrics(future_metrics!("handler_task", "name" => "sponsorship_usage"))
+        .await
+}
+
+#[tracing::instrument(skip(state), level = "debug")]
+async fn handler_internal(
+    State(state): State<Arc<AppState>>,
+    Path(project_id): Path<String>,
+    Query(query): Query<UsageQueryParams>,
+) -> Result<Response, RpcError> {
+    let period = match &query.period {
+        Some(period) => NaiveDate::parse_from_str(&format!("{period}-01"), "%Y-%m-%d")
+            .map_err(|e| RpcError::InvalidParameter(format!("Invalid period: {e}")))?,
+        None => current_period(),
+    };
+
+    let policy = sponsorship::get_policy(&state.postgres, &project_id).await?;
+    let usage = sponsorship::get_usage(&state.postgres, &project_id, period).await?;
+
+    let chains = usage
+        .into_iter()
+        .map(|usage| ChainUsage {
+            chain_id: usage.chain_id,
+            sponsored_ops: usage.sponsored_ops,
+            sponsored_gas: usage.sponsored_gas,
+        })
+        .collect::<Vec<_>>();
+
+    Ok(Json(UsageResponse {
+        period: period.format("%Y-%m").to_string(),
+        monthly_gas_budget: policy.and_then(|policy| policy.monthly_gas_budget),
+        sponsored_ops: chains.iter().map(|chain| chain.sponsored_ops).sum(),
+        sponsored_gas: chains.iter().map(|chain| chain.sponsored_gas).sum(),
+        chains,
+    })
+    .into_response())
+}
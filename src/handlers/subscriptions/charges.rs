@@ -0,0 +1,48 @@
+use {
+    super::{get_project_subscription, ChargeResponse, QueryParams},
+    crate::{database::subscriptions, error::RpcError, state::AppState},
+    axum::{
+        extract::{Path, Query, State},
+        response::{IntoResponse, Response},
+        Json,
+    },
+    serde::Serialize,
+    std::sync::Arc,
+    wc::metrics::{future_metrics, FutureExt},
+};
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ListChargesResponse {
+    pub charges: Vec<ChargeResponse>,
+}
+
+pub async fn handler(
+    state: State<Arc<AppState>>,
+    id: Path<String>,
+    query_params: Query<QueryParams>,
+) -> Result<Response, RpcError> {
+    handler_internal(state, id, query_params)
+        .with_metrics(future_metrics!("handler_task", "name" => "subscriptions_charges"))
+        .await
+}
+
+#[tracing::instrument(skip(state), level = "debug")]
+async fn handler_internal(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+    Query(query_params): Query<QueryParams>,
+) -> Result<Response, RpcError> {
+    state
+        .validate_project_access_and_quota(&query_params.project_id)
+        .await?;
+
+    let subscription = get_project_subscription(&state, &id, &query_params.project_id).await?;
+    let charges = subscriptions::list_charges(&state.postgres, &subscription.id)
+        .await?
+        .into_iter()
+        .map(ChargeResponse::from)
+        .collect();
+
+    Ok(Json(ListChargesResponse { charges }).into_response())
+}
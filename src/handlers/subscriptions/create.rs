@@ -0,0 +1,160 @@
+use {
+    super::{QueryParams, SubscriptionResponse, MIN_SUBSCRIPTION_INTERVAL_SECS},
+    crate::{
+        database::subscriptions::{self, NewSubscription},
+        error::RpcError,
+        handlers::{
+            json_rpc::pos::{evm::AssetNamespace, PaymentIntent, ValidatedPaymentIntent},
+            payment_links::pos_error_to_rpc_error,
+            sessions::cosign::{get_active_permission_item, validate_cosigner_address},
+        },
+        state::AppState,
+        utils::simple_request_json::SimpleRequestJson,
+    },
+    axum::{
+        extract::{Query, State},
+        response::{IntoResponse, Response},
+        Json,
+    },
+    chrono::{DateTime, Utc},
+    serde::Deserialize,
+    std::sync::Arc,
+    uuid::Uuid,
+    wc::metrics::{future_metrics, FutureExt},
+};
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateSubscriptionRequest {
+    /// CAIP-10 smart account that granted the session permission
+    pub account: String,
+    /// Permission control identifier of the granted session permission
+    pub pci: String,
+    /// CAIP-19 asset ID
+    pub asset: String,
+    /// Amount charged every period in the asset units
+    pub amount: String,
+    /// CAIP-10 recipient account
+    pub recipient: String,
+    /// Charge period in seconds
+    pub interval: i64,
+    /// Unix timestamp in seconds of the first charge, charged immediately if not set
+    pub start_at: Option<i64>,
+    /// Maximum number of charges, charged until cancelled if not set
+    pub max_charges: Option<i32>,
+}
+
+pub async fn handler(
+    state: State<Arc<AppState>>,
+    query_params: Query<QueryParams>,
+    SimpleRequestJson(request_payload): SimpleRequestJson<CreateSubscriptionRequest>,
+) -> Result<Response, RpcError> {
+    handler_internal(state, query_params, request_payload)
+        .with_metrics(future_metrics!("handler_task", "name" => "subscriptions_create"))
+        .await
+}
+
+#[tracing::instrument(skip(state), level = "debug")]
+async fn handler_internal(
+    State(state): State<Arc<AppState>>,
+    Query(query_params): Query<QueryParams>,
+    request_payload: CreateSubscriptionRequest,
+) -> Result<Response, RpcError> {
+    let project_id = query_params.project_id;
+    state.validate_project_access_and_quota(&project_id).await?;
+
+    // Subscriptions are charged by the smart account userOps on EVM chains only
+    let account_chain_id = validate_cosigner_address(&request_payload.account)?;
+    Uuid::parse_str(&request_payload.pci)
+        .map_err(|_| RpcError::InvalidParameter(format!("Invalid PCI: {}", request_payload.pci)))?;
+
+    // The permission must be active and granted for the same project
+    let now = Utc::now();
+    let permission = get_active_permission_item(
+        &state,
+        &request_payload.account,
+        &request_payload.pci,
+        now.timestamp() as usize,
+    )
+    .await?;
+    if permission.project_id != project_id {
+        return Err(RpcError::PermissionNotFound(
+            request_payload.account.clone(),
+            request_payload.pci.clone(),
+        ));
+    }
+
+    let intent = ValidatedPaymentIntent::<AssetNamespace>::validate_params(&PaymentIntent {
+        asset: request_payload.asset.clone(),
+        amount: request_payload.amount.clone(),
+        recipient: request_payload.recipient.clone(),
+        sender: request_payload.account.clone(),
+        price: None,
+    })
+    .map_err(|e| pos_error_to_rpc_error(e.is_internal(), e))?;
+    if intent.asset.chain_id().to_string() != account_chain_id {
+        return Err(RpcError::InvalidParameter(
+            "Asset chain ID must match the account chain ID".to_string(),
+        ));
+    }
+
+    if !request_payload
+        .amount
+        .parse::<f64>()
+        .is_ok_and(|amount| amount.is_finite() && amount > 0.0)
+    {
+        return Err(RpcError::InvalidParameter(format!(
+            "Invalid amount: {}",
+            request_payload.amount
+        )));
+    }
+
+    if request_payload.interval < MIN_SUBSCRIPTION_INTERVAL_SECS {
+        return Err(RpcError::InvalidParameter(format!(
+            "Interval must be at least {MIN_SUBSCRIPTION_INTERVAL_SECS} seconds"
+        )));
+    }
+
+    if request_payload
+        .max_charges
+        .is_some_and(|max_charges| max_charges <= 0)
+    {
+        return Err(RpcError::InvalidParameter(
+            "Maximum charges must be positive".to_string(),
+        ));
+    }
+
+    let next_charge_at = request_payload
+        .start_at
+        .map(|start_at| {
+            DateTime::<Utc>::from_timestamp(start_at, 0)
+                .filter(|start_at| *start_at >= now)
+                .ok_or_else(|| {
+                    RpcError::InvalidParameter(format!(
+                        "Start must be a future timestamp: {start_at}"
+                    ))
+                })
+        })
+        .transpose()?
+        .unwrap_or(now);
+
+    let id = Uuid::new_v4().to_string();
+    let subscription = subscriptions::insert_subscription(
+        &state.postgres,
+        NewSubscription {
+            id: &id,
+            project_id: &project_id,
+            account: &request_payload.account,
+            pci: &request_payload.pci,
+            asset: &request_payload.asset,
+            amount: &request_payload.amount,
+            recipient: &request_payload.recipient,
+            interval_secs: request_payload.interval,
+            next_charge_at,
+            max_charges: request_payload.max_charges,
+        },
+    )
+    .await?;
+
+    Ok(Json(SubscriptionResponse::from(subscription)).into_response())
+}
@@ -0,0 +1,228 @@
+use {
+    crate::{
+        database::subscriptions::{self as db, ChargeStatus, NewSubscriptionCharge, Subscription},
+        error::RpcError,
+        handlers::{
+            json_rpc::{
+                pos::{evm::EvmTransactionBuilder, PaymentIntent, TransactionBuilder},
+                wallet::{
+                    prepare_calls::{self, CallShim, PrepareCallsRequestItem},
+                    send_prepared_calls::{self, SendPreparedCallsRequestItem},
+                },
+            },
+            sessions::cosign::get_active_permission_item,
+        },
+        state::AppState,
+        utils::crypto::disassemble_caip10,
+    },
+    alloy::primitives::{Address, Bytes, U256, U64},
+    axum::extract::State,
+    chrono::Utc,
+    serde::Deserialize,
+    serde_json::json,
+    std::{sync::Arc, time::Duration},
+    tokio::time::{interval, MissedTickBehavior},
+    tracing::{debug, info, warn},
+};
+
+const POLL_INTERVAL: Duration = Duration::from_secs(60);
+const CLAIM_BATCH_SIZE: i64 = 50;
+
+/// Transaction call built by the pos EVM builder
+#[derive(Debug, Deserialize)]
+struct ChargeCall {
+    to: Address,
+    #[serde(default)]
+    value: U256,
+    #[serde(default)]
+    input: Bytes,
+}
+
+/// Background job that charges the due subscriptions by the userOps
+/// cosigned with the granted session permission
+pub async fn run(state: Arc<AppState>) {
+    debug!("starting subscriptions executor");
+    let mut poll = interval(POLL_INTERVAL);
+    poll.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    loop {
+        poll.tick().await;
+        let subscriptions = match db::claim_due_batch(&state.postgres, CLAIM_BATCH_SIZE).await {
+            Ok(subscriptions) => subscriptions,
+            Err(e) => {
+                warn!(error = %e, "failed to claim due subscriptions");
+                continue;
+            }
+        };
+        if subscriptions.is_empty() {
+            continue;
+        }
+        debug!("charging {} due subscriptions", subscriptions.len());
+
+        let mut rate = interval(Duration::from_millis(200));
+        rate.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        for subscription in subscriptions {
+            rate.tick().await;
+            if let Err(e) = process_subscription(&state, &subscription).await {
+                warn!(
+                    subscription_id = subscription.id,
+                    error = %e,
+                    "failed to process the subscription charge"
+                );
+            }
+        }
+    }
+}
+
+/// Charge the subscription for the due period, record the charge and move
+/// the schedule to the next period
+async fn process_subscription(
+    state: &Arc<AppState>,
+    subscription: &Subscription,
+) -> Result<(), RpcError> {
+    // Cancel the subscription when the permission is no longer usable
+    let now = Utc::now().timestamp() as usize;
+    if let Err(e) =
+        get_active_permission_item(state, &subscription.account, &subscription.pci, now).await
+    {
+        if matches!(
+            e,
+            RpcError::PermissionNotFound(_, _)
+                | RpcError::RevokedPermission(_)
+                | RpcError::PermissionExpired(_)
+        ) {
+            info!(
+                subscription_id = subscription.id,
+                "cancelling the subscription with the inactive permission: {e}"
+            );
+            record_charge(state, subscription, Err(e.to_string()), false).await?;
+            db::cancel_subscription(&state.postgres, &subscription.id).await?;
+            return Ok(());
+        }
+        return Err(e);
+    }
+
+    let result = charge(state, subscription).await;
+    if let Err(reason) = &result {
+        warn!(
+            subscription_id = subscription.id,
+            "subscription charge failed: {reason}"
+        );
+    }
+    let charged = result.is_ok();
+    record_charge(state, subscription, result, true).await?;
+    if charged {
+        info!(subscription_id = subscription.id, "subscription charged");
+    }
+    Ok(())
+}
+
+/// Record the charge of the due period and optionally advance the schedule
+async fn record_charge(
+    state: &AppState,
+    subscription: &Subscription,
+    result: Result<String, String>,
+    advance: bool,
+) -> Result<(), RpcError> {
+    let (status, call_id, failure_reason) = match &result {
+        Ok(call_id) => (ChargeStatus::Submitted, Some(call_id.as_str()), None),
+        Err(reason) => (ChargeStatus::Failed, None, Some(reason.as_str())),
+    };
+
+    let mut db_tx = state.postgres.begin().await?;
+    db::insert_charge(
+        &mut *db_tx,
+        NewSubscriptionCharge {
+            subscription_id: &subscription.id,
+            period_at: subscription.next_charge_at,
+            status,
+            call_id,
+            failure_reason,
+        },
+    )
+    .await?;
+    if advance {
+        db::advance_schedule(&mut *db_tx, &subscription.id, result.is_ok()).await?;
+    }
+    db_tx.commit().await?;
+    Ok(())
+}
+
+/// Build the transfer with the pos builder, prepare the userOp and send it
+/// cosigned by the session permission. Returns the submitted call ID or the
+/// failure reason.
+async fn charge(state: &Arc<AppState>, subscription: &Subscription) -> Result<String, String> {
+    let (_, chain_id, address) =
+        disassemble_caip10(&subscription.account).map_err(|e| e.to_string())?;
+    let chain_id = chain_id
+        .parse::<u64>()
+        .map_err(|e| format!("Invalid account chain ID: {e}"))?;
+
+    let transaction = EvmTransactionBuilder
+        .validate_and_build(
+            State(state.clone()),
+            subscription.project_id.clone(),
+            PaymentIntent {
+                asset: subscription.asset.clone(),
+                amount: subscription.amount.clone(),
+                recipient: subscription.recipient.clone(),
+                sender: subscription.account.clone(),
+                price: None,
+            },
+        )
+        .await
+        .map_err(|e| format!("Failed to build the transfer: {e}"))?;
+    let call = transaction
+        .params
+        .get(0)
+        .cloned()
+        .ok_or_else(|| "Built transfer has no transaction params".to_string())
+        .and_then(|params| {
+            serde_json::from_value::<ChargeCall>(params)
+                .map_err(|e| format!("Invalid built transfer params: {e}"))
+        })?;
+
+    let prepare_request = serde_json::from_value::<PrepareCallsRequestItem>(json!({
+        "from": address,
+        "chainId": U64::from(chain_id),
+        "calls": [CallShim {
+            to: call.to,
+            value: call.value,
+            data: call.input,
+        }],
+        "capabilities": {
+            "permissions": {
+                "context": subscription.pci,
+            },
+        },
+    }))
+    .map_err(|e| format!("Invalid prepare calls request: {e}"))?;
+    let prepared = prepare_calls::handler(
+        State(state.clone()),
+        subscription.project_id.clone(),
+        vec![prepare_request],
+    )
+    .await
+    .map_err(|e| format!("Failed to prepare the userOp: {e}"))?
+    .pop()
+    .ok_or_else(|| "No prepared userOp".to_string())?;
+
+    // The charge userOp is signed by the permission cosigner key only
+    let call_id = send_prepared_calls::handler(
+        State(state.clone()),
+        subscription.project_id.clone(),
+        vec![SendPreparedCallsRequestItem {
+            prepared_calls: prepared.prepared_calls,
+            signature: Bytes::new(),
+            context: prepared.context,
+        }],
+    )
+    .await
+    .map_err(|e| format!("Failed to send the userOp: {e}"))?
+    .pop()
+    .ok_or_else(|| "No submitted userOp".to_string())?;
+
+    serde_json::to_value(&call_id)
+        .ok()
+        .and_then(|call_id| call_id.as_str().map(str::to_string))
+        .ok_or_else(|| "Failed to serialize the call ID".to_string())
+}
@@ -0,0 +1,35 @@
+use {
+    super::{get_project_subscription, QueryParams, SubscriptionResponse},
+    crate::{error::RpcError, state::AppState},
+    axum::{
+        extract::{Path, Query, State},
+        response::{IntoResponse, Response},
+        Json,
+    },
+    std::sync::Arc,
+    wc::metrics::{future_metrics, FutureExt},
+};
+
+pub async fn handler(
+    state: State<Arc<AppState>>,
+    id: Path<String>,
+    query_params: Query<QueryParams>,
+) -> Result<Response, RpcError> {
+    handler_internal(state, id, query_params)
+        .with_metrics(future_metrics!("handler_task", "name" => "subscriptions_get"))
+        .await
+}
+
+#[tracing::instrument(skip(state), level = "debug")]
+async fn handler_internal(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+    Query(query_params): Query<QueryParams>,
+) -> Result<Response, RpcError> {
+    state
+        .validate_project_access_and_quota(&query_params.project_id)
+        .await?;
+
+    let subscription = get_project_subscription(&state, &id, &query_params.project_id).await?;
+    Ok(Json(SubscriptionResponse::from(subscription)).into_response())
+}
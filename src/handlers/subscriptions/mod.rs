@@ -0,0 +1,106 @@
+use {
+    crate::{
+        database::subscriptions::{
+            self, ChargeStatus, Subscription, SubscriptionCharge, SubscriptionStatus,
+        },
+        error::RpcError,
+        state::AppState,
+    },
+    serde::{Deserialize, Serialize},
+};
+
+pub mod cancel;
+pub mod charges;
+pub mod create;
+pub mod executor;
+pub mod get;
+
+/// Minimum subscription charge period in seconds
+pub const MIN_SUBSCRIPTION_INTERVAL_SECS: i64 = 60 * 60; // 1 hour
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct QueryParams {
+    pub project_id: String,
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SubscriptionResponse {
+    pub id: String,
+    pub account: String,
+    pub pci: String,
+    pub asset: String,
+    pub amount: String,
+    pub recipient: String,
+    /// Charge period in seconds
+    pub interval: i64,
+    pub next_charge_at: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_charges: Option<i32>,
+    pub charges_count: i32,
+    pub status: SubscriptionStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cancelled_at: Option<i64>,
+    pub created_at: i64,
+}
+
+impl From<Subscription> for SubscriptionResponse {
+    fn from(subscription: Subscription) -> Self {
+        Self {
+            id: subscription.id,
+            account: subscription.account,
+            pci: subscription.pci,
+            asset: subscription.asset,
+            amount: subscription.amount,
+            recipient: subscription.recipient,
+            interval: subscription.interval_secs,
+            next_charge_at: subscription.next_charge_at.timestamp(),
+            max_charges: subscription.max_charges,
+            charges_count: subscription.charges_count,
+            status: subscription.status,
+            cancelled_at: subscription
+                .cancelled_at
+                .map(|cancelled_at| cancelled_at.timestamp()),
+            created_at: subscription.created_at.timestamp(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ChargeResponse {
+    /// Scheduled time of the charged period
+    pub period_at: i64,
+    pub status: ChargeStatus,
+    /// Call ID of the submitted user operation to be used with `wallet_getCallsStatus`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub call_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub failure_reason: Option<String>,
+    pub created_at: i64,
+}
+
+impl From<SubscriptionCharge> for ChargeResponse {
+    fn from(charge: SubscriptionCharge) -> Self {
+        Self {
+            period_at: charge.period_at.timestamp(),
+            status: charge.status,
+            call_id: charge.call_id,
+            failure_reason: charge.failure_reason,
+            created_at: charge.created_at.timestamp(),
+        }
+    }
+}
+
+/// Get the project subscription by ID or return the not found error
+pub async fn get_project_subscription(
+    state: &AppState,
+    id: &str,
+    project_id: &str,
+) -> Result<Subscription, RpcError> {
+    subscriptions::get_subscription(&state.postgres, id)
+        .await?
+        .filter(|subscription| subscription.project_id == project_id)
+        .ok_or_else(|| RpcError::SubscriptionNotFound(id.to_string()))
+}
@@ -26,7 +26,7 @@ async fn handler_internal(State(state): State<Arc<AppState>>) -> Result<Response
             CACHE_CONTROL,
             format!("public, max-age={ttl_secs}, s-maxage={ttl_secs}"),
         )],
-        Json(state.providers.rpc_supported_chains.clone()),
+        Json(state.providers().rpc_supported_chains.clone()),
     )
         .into_response())
 }
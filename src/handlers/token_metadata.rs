@@ -0,0 +1,150 @@
+use {
+    super::{
+        balance::{token_metadata_cache_keys, TokenMetadataCacheItem, ERC20_ASSET_NAMESPACE},
+        self_provider::SelfProviderPool,
+        SdkInfoParams,
+    },
+    crate::{
+        analytics::MessageSource,
+        error::RpcError,
+        state::AppState,
+        utils::crypto::{Caip19Asset, Caip2ChainId},
+    },
+    alloy::{primitives::Address, sol},
+    axum::{
+        extract::{ConnectInfo, Query, State},
+        Json,
+    },
+    hyper::HeaderMap,
+    serde::{Deserialize, Serialize},
+    std::{net::SocketAddr, str::FromStr, sync::Arc},
+    tracing::debug,
+    wc::metrics::{future_metrics, FutureExt},
+};
+
+sol! {
+    #[sol(rpc)]
+    interface ERC20Metadata {
+        function name() external view returns (string);
+        function symbol() external view returns (string);
+        function decimals() external view returns (uint8);
+    }
+}
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenMetadataQueryParams {
+    pub project_id: String,
+    /// CAIP-19 asset ID of the token
+    pub caip19: String,
+    #[serde(flatten)]
+    pub sdk_info: SdkInfoParams,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenMetadataResponseBody {
+    pub asset: String,
+    pub name: String,
+    pub symbol: String,
+    pub decimals: u8,
+    /// Not available for the tokens resolved on-chain
+    pub icon_url: Option<String>,
+}
+
+pub async fn handler(
+    state: State<Arc<AppState>>,
+    connect_info: ConnectInfo<SocketAddr>,
+    query: Query<TokenMetadataQueryParams>,
+    headers: HeaderMap,
+) -> Result<Json<TokenMetadataResponseBody>, RpcError> {
+    handler_internal(state, connect_info, query, headers)
+        .with_metrics(future_metrics!("handler_task", "name" => "token_metadata"))
+        .await
+}
+
+#[tracing::instrument(skip_all, level = "debug")]
+async fn handler_internal(
+    State(state): State<Arc<AppState>>,
+    ConnectInfo(connect_info): ConnectInfo<SocketAddr>,
+    Query(query): Query<TokenMetadataQueryParams>,
+    headers: HeaderMap,
+) -> Result<Json<TokenMetadataResponseBody>, RpcError> {
+    state
+        .validate_project_access_and_quota(&query.project_id)
+        .await?;
+
+    let asset = Caip19Asset::parse(&query.caip19)
+        .map_err(|e| RpcError::InvalidParameter(format!("Invalid CAIP-19 asset ID: {e}")))?;
+
+    let token_metadata_cache = state.providers().token_metadata_cache.clone();
+    for cache_key in token_metadata_cache_keys(&asset)? {
+        if let Some(metadata) = token_metadata_cache.get_metadata(&cache_key).await? {
+            return Ok(Json(response_body(&asset, metadata)));
+        }
+    }
+
+    // Fallback to the on-chain metadata of the tokens unknown to the providers
+    let chain_id = asset.chain_id();
+    if chain_id.namespace() != "eip155" || asset.asset_namespace() != ERC20_ASSET_NAMESPACE {
+        return Err(RpcError::TokenMetadataNotFound(query.caip19));
+    }
+    let token_address = Address::from_str(asset.asset_reference())
+        .map_err(|_| RpcError::InvalidParameter("Invalid ERC-20 token address".to_string()))?;
+    let provider_pool = SelfProviderPool {
+        state: state.clone(),
+        connect_info,
+        headers,
+        project_id: query.project_id.as_str().into(),
+        sdk_info: query.sdk_info,
+        session_id: None,
+    };
+    let metadata = get_onchain_metadata(&provider_pool, chain_id, token_address)
+        .await
+        .ok_or_else(|| RpcError::TokenMetadataNotFound(query.caip19))?;
+
+    token_metadata_cache
+        .set_metadata(&format!("{chain_id}:{token_address}"), &metadata)
+        .await?;
+    Ok(Json(response_body(&asset, metadata)))
+}
+
+/// ERC-20 token metadata by the contract calls through the proxy, `None` when
+/// the contract doesn't implement the optional metadata methods
+async fn get_onchain_metadata(
+    provider_pool: &SelfProviderPool,
+    chain_id: &Caip2ChainId,
+    token_address: Address,
+) -> Option<TokenMetadataCacheItem> {
+    let provider = provider_pool.get_provider(chain_id.to_string(), MessageSource::TokenMetadata);
+    let erc20 = ERC20Metadata::new(token_address, &provider);
+    let name = erc20.name();
+    let symbol = erc20.symbol();
+    let decimals = erc20.decimals();
+    let result = tokio::try_join!(name.call(), symbol.call(), decimals.call());
+    match result {
+        Ok((name, symbol, decimals)) => Some(TokenMetadataCacheItem {
+            name: name._0,
+            symbol: symbol._0,
+            icon_url: String::new(),
+            decimals: decimals._0,
+        }),
+        Err(e) => {
+            debug!("Failed to get the on-chain metadata of {chain_id}:{token_address}: {e}");
+            None
+        }
+    }
+}
+
+fn response_body(
+    asset: &Caip19Asset,
+    metadata: TokenMetadataCacheItem,
+) -> TokenMetadataResponseBody {
+    TokenMetadataResponseBody {
+        asset: asset.to_string(),
+        name: metadata.name,
+        symbol: metadata.symbol,
+        decimals: metadata.decimals,
+        icon_url: Some(metadata.icon_url).filter(|icon_url| !icon_url.is_empty()),
+    }
+}
@@ -1,12 +1,12 @@
 use {
     super::RpcQueryParams,
-    crate::{error::RpcError, state::AppState},
+    crate::{error::RpcError, state::AppState, ws::WsLimits},
     axum::{
         extract::{ws::WebSocketUpgrade, Query, State},
         http::HeaderMap,
         response::Response,
     },
-    std::sync::Arc,
+    std::{sync::Arc, time::Duration},
     wc::metrics::{future_metrics, FutureExt},
 };
 
@@ -35,16 +35,31 @@ async fn handler_internal(
     state
         .validate_project_access_and_quota(&query_params.project_id)
         .await?;
+    state
+        .validate_project_chain(&query_params.project_id, &query_params.chain_id)
+        .await?;
 
     let chain_id = query_params.chain_id.clone();
     let provider = state
-        .providers
+        .providers()
         .get_ws_provider_for_chain_id(&chain_id)
         .ok_or(RpcError::UnsupportedChain(chain_id.clone()))?;
 
+    let server_config = &state.config.server;
+    let limits = WsLimits {
+        max_messages_per_sec: server_config.ws_max_messages_per_sec,
+        max_subscriptions: server_config.ws_max_subscriptions,
+        idle_timeout: Duration::from_secs(server_config.ws_idle_timeout_secs),
+    };
+    // New upgrades are rejected while the active sessions are drained
+    let session = state
+        .ws_sessions
+        .start(limits)
+        .ok_or(RpcError::WebSocketShuttingDown)?;
+
     state.metrics.add_websocket_connection(chain_id);
 
-    provider.proxy(ws, query_params).await
+    provider.proxy(ws, query_params, session).await
 }
 
 /// Check if the request is a WebSocket upgrade request
@@ -2,13 +2,27 @@ use {
     crate::{
         env::{Config, GenericConfig},
         handlers::{
-            balance::BalanceResponseBody, identity::IdentityResponse, rate_limit_middleware,
+            admin_auth_middleware,
+            balance::BalanceResponseBody,
+            bundler::CachedGasPrice,
+            geoblock_middleware,
+            identity::IdentityResponse,
+            json_rpc::{exchanges::ExchangeAsset, pos::PosQuote},
+            problem_json_middleware, profiler_auth_middleware, project_allowlist_middleware,
+            project_ip_allowlist_middleware, project_jwt_middleware, project_signature_middleware,
+            rate_limit_middleware, request_body_limit_middleware,
             status_latency_metrics_middleware,
         },
         metrics::Metrics,
         project::Registry,
         providers::ProvidersConfig,
-        storage::{irn, redis, KeyValueStorage},
+        storage::{
+            dual::DualStorage,
+            irn::{self, MigrationMode},
+            local_cache::LocalCache,
+            postgres::PostgresStorage,
+            redis, KeyValueStorage, LockStorage, PersistentStorage,
+        },
     },
     anyhow::Context,
     aws_config::meta::region::RegionProviderChain,
@@ -16,7 +30,7 @@ use {
     axum::body::Body,
     axum::{
         middleware,
-        routing::{get, post},
+        routing::{delete, get, post, put},
         Router,
     },
     env::{
@@ -27,19 +41,20 @@ use {
         TrongridConfig, UnichainConfig, WemixConfig, XrplConfig, ZKSyncConfig, ZerionConfig,
         ZoraConfig,
     },
-    error::RpcResult,
+    error::{RpcError, RpcResult},
     http::Request,
     hyper::{header::HeaderName, http},
     metrics_exporter_prometheus::PrometheusBuilder,
     providers::{
+        localnet::{LocalnetConfig, LocalnetProvider},
         AllnodesProvider, AllnodesWsProvider, ArbitrumProvider, AuroraProvider, BaseProvider,
         BinanceProvider, BlastProvider, CallStaticProvider, DrpcProvider, DuneProvider,
         GenericProvider, HiroProvider, MantleProvider, MonadProvider, MoonbeamProvider,
         MorphProvider, NearProvider, PoktProvider, ProviderRepository, PublicnodeProvider,
-        QuicknodeProvider, QuicknodeWsProvider, RootstockProvider, SolScanProvider, SuiProvider,
-        SyndicaProvider, SyndicaWsProvider, TheRpcProvider, ToncenterApiProvider, TrongridProvider,
-        UnichainProvider, WemixProvider, XrplProvider, ZKSyncProvider, ZerionProvider,
-        ZoraProvider, ZoraWsProvider,
+        PublicnodeWsProvider, QuicknodeProvider, QuicknodeWsProvider, RootstockProvider,
+        SolScanProvider, SuiProvider, SyndicaProvider, SyndicaWsProvider, TheRpcProvider,
+        ToncenterApiProvider, TrongridProvider, UnichainProvider, WemixProvider, XrplProvider,
+        ZKSyncProvider, ZerionProvider, ZoraProvider, ZoraWsProvider,
     },
     sqlx::postgres::PgPoolOptions,
     std::{
@@ -56,11 +71,8 @@ use {
         ServiceBuilderExt,
     },
     tracing::{error, info, log::warn},
-    utils::rate_limit::RateLimit,
-    wc::geoip::{
-        block::{middleware::GeoBlockLayer, BlockingPolicy},
-        MaxMindResolver,
-    },
+    utils::rate_limit::{RateLimit, RateLimits},
+    wc::geoip::MaxMindResolver,
 };
 
 const DB_STATS_POLLING_INTERVAL: Duration = Duration::from_secs(3600);
@@ -68,23 +80,36 @@ const GRACEFUL_SHUTDOWN_DELAY: Duration = Duration::from_secs(5);
 
 mod analytics;
 pub mod chain_config;
+mod chain_quarantine;
 pub mod database;
 pub mod env;
 pub mod error;
+pub mod grpc;
 pub mod handlers;
 mod json_rpc;
 mod metrics;
+pub mod migrate_storage;
 pub mod names;
+pub mod otel;
 pub mod profiler;
 mod project;
 pub mod providers;
+mod reload;
+mod scam_tokens;
+mod secrets;
 mod state;
 mod storage;
 pub mod test_helpers;
+mod token_metadata_backfill;
+mod usage;
 pub mod utils;
+pub mod validate_config;
+pub mod webhooks;
 mod ws;
 
-pub async fn bootstrap(config: Config) -> RpcResult<()> {
+pub async fn bootstrap(mut config: Config) -> RpcResult<()> {
+    secrets::resolve(&mut config).await?;
+
     let prometheus_handler = PrometheusBuilder::new()
         .install_recorder()
         .context("failed to initialize prometheus")?;
@@ -93,7 +118,7 @@ pub async fn bootstrap(config: Config) -> RpcResult<()> {
     let geoip_resolver = get_geoip_resolver(&config, &s3_client).await;
 
     let metrics = Arc::new(Metrics::new());
-    let registry = Registry::new(&config.registry, &config.storage)?;
+    let registry = Registry::new(&config.registry, &config.storage, metrics.clone())?;
 
     // Rate limiting construction
     let rate_limiting = match config.storage.rate_limiting_cache_redis_addr() {
@@ -103,25 +128,22 @@ pub async fn bootstrap(config: Config) -> RpcResult<()> {
         }
         Some(redis_addr) => {
             match (
-                config.rate_limiting.max_tokens,
+                RateLimits::from_config(&config.rate_limiting)
+                    .map_err(RpcError::InvalidConfiguration)?,
                 config.rate_limiting.refill_interval_sec,
-                config.rate_limiting.refill_rate,
-                config.rate_limiting.ip_whitelist.clone(),
             ) {
-                (Some(max_tokens), Some(refill_interval_sec), Some(refill_rate), ip_whitelist) => {
+                (Some(limits), Some(refill_interval_sec)) => {
                     info!(
                         "Rate limiting is enabled with the following configuration: \
-                         max_tokens={}, refill_interval_sec={}, refill_rate={}, ip_whitelist={:?}",
-                        max_tokens, refill_interval_sec, refill_rate, ip_whitelist
+                         refill_interval_sec={}, limits={:?}",
+                        refill_interval_sec, limits
                     );
                     RateLimit::new(
                         redis_addr.write(),
                         config.storage.redis_max_connections,
-                        max_tokens,
                         chrono::Duration::seconds(refill_interval_sec as i64),
-                        refill_rate,
+                        limits,
                         metrics.clone(),
-                        ip_whitelist,
                     )
                 }
                 _ => {
@@ -138,6 +160,15 @@ pub async fn bootstrap(config: Config) -> RpcResult<()> {
         .project_data_redis_addr()
         .map(|addr| redis::Redis::new(&addr, config.storage.redis_max_connections))
         .transpose()?
+        .map(|r| {
+            LocalCache::new(
+                r,
+                "identity",
+                config.storage.local_cache_max_capacity,
+                config.storage.local_cache_ttl(),
+                metrics.clone(),
+            )
+        })
         .map(|r| Arc::new(r) as Arc<dyn KeyValueStorage<IdentityResponse> + 'static>);
     let balance_cache = config
         .storage
@@ -145,6 +176,59 @@ pub async fn bootstrap(config: Config) -> RpcResult<()> {
         .map(|addr| redis::Redis::new(&addr, config.storage.redis_max_connections))
         .transpose()?
         .map(|r| Arc::new(r) as Arc<dyn KeyValueStorage<BalanceResponseBody> + 'static>);
+    let pos_quote_cache = config
+        .storage
+        .project_data_redis_addr()
+        .map(|addr| redis::Redis::new(&addr, config.storage.redis_max_connections))
+        .transpose()?
+        .map(|r| Arc::new(r) as Arc<dyn KeyValueStorage<PosQuote> + 'static>);
+    let exchange_assets_cache = config
+        .storage
+        .project_data_redis_addr()
+        .map(|addr| redis::Redis::new(&addr, config.storage.redis_max_connections))
+        .transpose()?
+        .map(|r| Arc::new(r) as Arc<dyn KeyValueStorage<Vec<ExchangeAsset>> + 'static>);
+    // Gas prices are kept in memory for their TTL only to bound the staleness
+    let bundler_gas_price_cache = config
+        .storage
+        .project_data_redis_addr()
+        .map(|addr| redis::Redis::new(&addr, config.storage.redis_max_connections))
+        .transpose()?
+        .map(|r| {
+            LocalCache::new(
+                r,
+                "bundler_gas_price",
+                config.storage.local_cache_max_capacity,
+                config.storage.bundler_gas_price_cache_ttl(),
+                metrics.clone(),
+            )
+        })
+        .map(|r| Arc::new(r) as Arc<dyn KeyValueStorage<CachedGasPrice> + 'static>);
+    // Routes are not kept in memory to be invalidated for all instances
+    let ca_route_cache = config
+        .storage
+        .project_data_redis_addr()
+        .map(|addr| redis::Redis::new(&addr, config.storage.redis_max_connections))
+        .transpose()?
+        .map(|r| Arc::new(r) as Arc<dyn KeyValueStorage<String> + 'static>);
+    let scam_tokens_cache = config
+        .storage
+        .project_data_redis_addr()
+        .map(|addr| redis::Redis::new(&addr, config.storage.redis_max_connections))
+        .transpose()?
+        .map(|r| Arc::new(r) as Arc<dyn KeyValueStorage<Vec<String>> + 'static>);
+    let lock_storage = config
+        .storage
+        .project_data_redis_addr()
+        .map(|addr| redis::Redis::new(&addr, config.storage.redis_max_connections))
+        .transpose()?
+        .map(|r| Arc::new(r) as Arc<dyn LockStorage + 'static>);
+    let project_data_redis = config
+        .storage
+        .project_data_redis_addr()
+        .map(|addr| redis::Redis::new(&addr, config.storage.redis_max_connections))
+        .transpose()?
+        .map(Arc::new);
 
     let providers = init_providers(&config.providers);
 
@@ -155,21 +239,13 @@ pub async fn bootstrap(config: Config) -> RpcResult<()> {
 
     let analytics = analytics::RPCAnalytics::new(
         &config.analytics,
-        s3_client,
+        s3_client.clone(),
         geoip_resolver.clone(),
         external_ip,
     )
     .await
     .context("failed to init analytics")?;
 
-    let geoblock = analytics.geoip_resolver().as_ref().map(|resolver| {
-        GeoBlockLayer::new(
-            resolver.clone(),
-            config.server.blocked_countries.clone(),
-            BlockingPolicy::AllowAll,
-        )
-    });
-
     let postgres = PgPoolOptions::new()
         .max_connections(config.postgres.max_connections.into())
         .connect(&config.postgres.uri)
@@ -177,19 +253,59 @@ pub async fn bootstrap(config: Config) -> RpcResult<()> {
     sqlx::migrate!("./migrations").run(&postgres).await?;
 
     let http_client = reqwest::Client::new();
-    let irn_client =
-        if let (Some(nodes), Some(key_base64), Some(namespace), Some(namespace_secret)) = (
+    // Falling back to the Redis persistent storage for the self-hosted
+    // deployments without the IRN nodes
+    let migration_mode = config.irn.migration_mode;
+    let persistent_storage: Option<Arc<dyn PersistentStorage>> =
+        if migration_mode == Some(MigrationMode::Postgres) {
+            Some(Arc::new(PostgresStorage::new(postgres.clone())))
+        } else if let (Some(nodes), Some(key_base64), Some(namespace), Some(namespace_secret)) = (
             config.irn.nodes.clone(),
             config.irn.key.clone(),
             config.irn.namespace.clone(),
             config.irn.namespace_secret.clone(),
         ) {
-            Some(irn::Irn::new(key_base64, nodes, namespace, namespace_secret).await?)
+            let irn: Arc<dyn PersistentStorage> = Arc::new(
+                irn::Irn::new(
+                    key_base64,
+                    nodes,
+                    namespace,
+                    namespace_secret,
+                    metrics.clone(),
+                )
+                .await?,
+            );
+            let postgres_storage = Arc::new(PostgresStorage::new(postgres.clone()));
+            let storage: Arc<dyn PersistentStorage> = match migration_mode {
+                Some(MigrationMode::DualWrite) => {
+                    Arc::new(DualStorage::new(irn, postgres_storage, false))
+                }
+                Some(MigrationMode::DualRead) => {
+                    Arc::new(DualStorage::new(postgres_storage, irn, true))
+                }
+                _ => irn,
+            };
+            Some(storage)
+        } else if let Some(addr) = config.storage.project_data_redis_addr() {
+            warn!("IRN client is disabled, falling back to the Redis persistent storage");
+            Some(Arc::new(redis::Redis::new(
+                &addr,
+                config.storage.redis_max_connections,
+            )?))
         } else {
-            warn!("IRN client is disabled (missing required environment configuration variables)");
+            warn!("Persistent storage is disabled (missing IRN and Redis configuration variables)");
             None
         };
 
+    let usage = config
+        .usage
+        .export_bucket
+        .as_ref()
+        .map(|_| Arc::new(usage::UsageAggregator::default()));
+    if usage.is_none() {
+        warn!("Usage reporting is disabled (missing export bucket configuration)");
+    }
+
     let state = state::new_state(
         config.clone(),
         postgres.clone(),
@@ -199,9 +315,17 @@ pub async fn bootstrap(config: Config) -> RpcResult<()> {
         analytics,
         http_client,
         rate_limiting,
-        irn_client,
+        persistent_storage,
         identity_cache,
         balance_cache,
+        pos_quote_cache,
+        exchange_assets_cache,
+        bundler_gas_price_cache,
+        ca_route_cache,
+        scam_tokens_cache,
+        lock_storage,
+        project_data_redis,
+        usage.clone(),
     );
 
     let port = state.config.server.port;
@@ -212,6 +336,7 @@ pub async fn bootstrap(config: Config) -> RpcResult<()> {
 
     let cors = CorsLayer::new().allow_origin(Any).allow_headers([
         http::header::CONTENT_TYPE,
+        http::header::AUTHORIZATION,
         http::header::USER_AGENT,
         http::header::REFERER,
         http::header::ORIGIN,
@@ -235,12 +360,19 @@ pub async fn bootstrap(config: Config) -> RpcResult<()> {
                     .and_then(|value| value.to_str().ok())
                     .unwrap_or_default()
                     .to_string();
-                tracing::info_span!(
+                let span = tracing::info_span!(
                     "http-request",
                     method = ?request.method(),
                     request_id = ?request_id,
-                    uri = request.uri().path()
-                )
+                    uri = request.uri().path(),
+                    trace_id = tracing::field::Empty,
+                );
+                otel::set_parent_from_headers(&span, request.headers());
+                // Upstream providers receive the trace ID in the `traceparent`
+                if let Some(trace_id) = otel::trace_id(&span) {
+                    span.record("trace_id", trace_id);
+                }
+                span
             }),
         )
         .propagate_x_request_id();
@@ -251,11 +383,20 @@ pub async fn bootstrap(config: Config) -> RpcResult<()> {
         .route("/v1/json-rpc", axum::routing::options(handlers::json_rpc::handler::json_rpc_preflight))
         .route("/v1/json-rpc", post(handlers::json_rpc::handler::json_rpc_with_dynamic_cors));
 
+    // Sensitive endpoints authorized by the project JWT or the signed request:
+    // the sessions cosign and the profile zone names changes
+    let project_signature_layer =
+        middleware::from_fn_with_state(state_arc.clone(), project_signature_middleware);
+
+    // Plan tier request body limits of the RPC proxy and bundler
+    let request_body_limit_layer =
+        middleware::from_fn_with_state(state_arc.clone(), request_body_limit_middleware);
+
     // All other routes with default/open CORS
     let rest_routes = Router::new()
         // HTTP RPC proxy (POST method only) with the trailing slash alias
-        .route("/v1", post(handlers::proxy::handler))
-        .route("/v1/", post(handlers::proxy::handler))
+        .route("/v1", post(handlers::proxy::handler).route_layer(request_body_limit_layer.clone()))
+        .route("/v1/", post(handlers::proxy::handler).route_layer(request_body_limit_layer.clone()))
         // WebSocket RPC proxy (GET method only) with the /ws and trailing slash alias
         .route("/v1", get(handlers::ws_proxy::handler))
         .route("/v1/", get(handlers::ws_proxy::handler))
@@ -278,20 +419,26 @@ pub async fn bootstrap(config: Config) -> RpcResult<()> {
             "/v1/account/{address}/balance",
             get(handlers::balance::handler),
         )
+        .route("/v1/graphql", post(handlers::graphql::handler))
         // Register account name
         .route(
             "/v1/profile/account",
-            post(handlers::profile::register::handler),
+            post(handlers::profile::register::handler).route_layer(project_signature_layer.clone()),
         )
          // Update account name attributes
          .route(
             "/v1/profile/account/{name}/attributes",
-            post(handlers::profile::attributes::handler),
+            post(handlers::profile::attributes::handler).route_layer(project_signature_layer.clone()),
         )
         // Update account name address
         .route(
             "/v1/profile/account/{name}/address",
-            post(handlers::profile::address::handler),
+            post(handlers::profile::address::handler).route_layer(project_signature_layer.clone()),
+        )
+        // Bulk forward address lookup
+        .route(
+            "/v1/profile/lookup",
+            post(handlers::profile::bulk_lookup::handler),
         )
         // Forward address lookup
         .route(
@@ -338,6 +485,10 @@ pub async fn bootstrap(config: Config) -> RpcResult<()> {
             "/v1/onramp/widget",
             post(handlers::onramp::widget::handler),
         )
+        .route(
+            "/v1/onramp/widget/{id}",
+            get(handlers::onramp::session::handler),
+        )
         // Conversion
         .route(
             "/v1/convert/tokens",
@@ -363,20 +514,41 @@ pub async fn bootstrap(config: Config) -> RpcResult<()> {
             "/v1/convert/allowance",
             get(handlers::convert::allowance::handler),
         )
+        .route(
+            "/v1/convert/allowance/batch",
+            post(handlers::convert::batch_allowance::handler),
+        )
         // Fungible price
         .route(
             "/v1/fungible/price",
             post(handlers::fungible_price::handler),
         )
+        .route("/v1/token/metadata", get(handlers::token_metadata::handler))
         // Sessions
         .route("/v1/sessions/{address}", post(handlers::sessions::create::handler))
         .route("/v1/sessions/{address}", get(handlers::sessions::list::handler))
         .route("/v1/sessions/{address}/getcontext", get(handlers::sessions::get::handler))
         .route("/v1/sessions/{address}/activate", post(handlers::sessions::context::handler))
         .route("/v1/sessions/{address}/revoke", post(handlers::sessions::revoke::handler))
-        .route("/v1/sessions/{address}/sign", post(handlers::sessions::cosign::handler))
+        .route("/v1/sessions/{address}/sign", post(handlers::sessions::cosign::handler).route_layer(project_signature_layer.clone()))
+        .route("/v1/sessions/{address}/sign-batch", post(handlers::sessions::cosign_batch::handler).route_layer(project_signature_layer))
+        // Recurring payments charged by the sessions permissions
+        .route("/v1/subscriptions", post(handlers::subscriptions::create::handler))
+        .route("/v1/subscriptions/{id}", get(handlers::subscriptions::get::handler))
+        .route("/v1/subscriptions/{id}/cancel", post(handlers::subscriptions::cancel::handler))
+        .route("/v1/subscriptions/{id}/charges", get(handlers::subscriptions::charges::handler))
         // Bundler
-        .route("/v1/bundler", post(handlers::bundler::handler))
+        .route("/v1/bundler", post(handlers::bundler::handler).route_layer(request_body_limit_layer))
+        .route("/v1/bundler/wait", get(handlers::bundler_wait::handler))
+        // Point of sale payment links
+        .route("/v1/pos/payment-links", post(handlers::payment_links::create::handler))
+        .route("/v1/pos/payment-links/{id}", get(handlers::payment_links::get::handler))
+        .route("/v1/pos/payment-links/{id}/build", post(handlers::payment_links::build::handler))
+        .route("/v1/pos/payment-links/{id}/check", post(handlers::payment_links::check::handler))
+        // Merchant callback URL of the confirmed payments, authorized by the project JWT
+        .route("/v1/pos/webhook", put(handlers::pos_webhook::set_handler).delete(handlers::pos_webhook::delete_handler))
+        // Exchanges transaction status webhooks
+        .route("/v1/exchanges/{exchange_id}/webhook", post(handlers::json_rpc::exchanges::webhook::handler))
         // Wallet
         .route("/v1/wallet", post(handlers::json_rpc::handler::handler))
         // Chain agnostic orchestration
@@ -385,6 +557,7 @@ pub async fn bootstrap(config: Config) -> RpcResult<()> {
         .route("/v1/ca/orchestrator/status", get(handlers::chain_agnostic::status::handler))
         // Health
         .route("/health", get(handlers::health::handler))
+        .route("/ready", get(handlers::health::ready_handler))
         .route_layer(cors);
 
     let app = Router::new()
@@ -399,8 +572,11 @@ pub async fn bootstrap(config: Config) -> RpcResult<()> {
     ));
 
     // GeoBlock middleware
-    let app = if let Some(geoblock) = geoblock {
-        app.route_layer(geoblock)
+    let app = if state_arc.analytics.geoip_resolver().is_some() {
+        app.route_layer(middleware::from_fn_with_state(
+            state_arc.clone(),
+            geoblock_middleware,
+        ))
     } else {
         app
     };
@@ -415,6 +591,32 @@ pub async fn bootstrap(config: Config) -> RpcResult<()> {
         app
     };
 
+    // Project allowlist middleware
+    let app = if state_arc.config.server.validate_project_allowlist {
+        app.route_layer(middleware::from_fn_with_state(
+            state_arc.clone(),
+            project_allowlist_middleware,
+        ))
+    } else {
+        app
+    };
+
+    // Project IP allowlist middleware
+    let app = app.route_layer(middleware::from_fn_with_state(
+        state_arc.clone(),
+        project_ip_allowlist_middleware,
+    ));
+
+    // Project JWT authentication middleware, must be the outermost one to
+    // provide the project ID for the other middlewares
+    let app = app.route_layer(middleware::from_fn_with_state(
+        state_arc.clone(),
+        project_jwt_middleware,
+    ));
+
+    // RFC 7807 error responses for the clients opted-in by the `Accept` header
+    let app = app.layer(middleware::from_fn(problem_json_middleware));
+
     let app = app.with_state(state_arc.clone());
 
     info!("v{}", build_version);
@@ -428,13 +630,83 @@ pub async fn bootstrap(config: Config) -> RpcResult<()> {
 
     info!("Starting metric server on {}", private_addr);
 
+    // Admin endpoints for the incident response without redeploys
+    let admin_routes = Router::new()
+        .route("/config/reload", post(reload::handler))
+        .route(
+            "/providers/{provider}/disable",
+            post(handlers::providers_overrides::disable_handler),
+        )
+        .route(
+            "/providers/{provider}/enable",
+            post(handlers::providers_overrides::enable_handler),
+        )
+        .route(
+            "/cache/identity/{address}",
+            delete(handlers::cache_invalidation::identity_handler),
+        )
+        .route(
+            "/cache/project-data/{project_id}",
+            delete(handlers::cache_invalidation::project_data_handler),
+        )
+        .route(
+            "/cache/balance/{address}",
+            delete(handlers::cache_invalidation::balance_handler),
+        )
+        .route(
+            "/cache/token-metadata/{*asset_id}",
+            delete(handlers::cache_invalidation::token_metadata_handler),
+        )
+        .route(
+            "/sponsorship/{project_id}/usage",
+            get(handlers::sponsorship::usage::handler),
+        )
+        .route_layer(middleware::from_fn_with_state(
+            state_arc.clone(),
+            admin_auth_middleware,
+        ));
+
+    // On-demand profiling endpoints
+    let pprof_routes = Router::new()
+        .route(
+            "/debug/pprof/profile",
+            get(handlers::pprof::profile_handler),
+        )
+        .route("/debug/pprof/heap", get(handlers::pprof::heap_handler))
+        .route_layer(middleware::from_fn_with_state(
+            state_arc.clone(),
+            profiler_auth_middleware,
+        ));
+
     let private_app = Router::new()
         .route(
             "/metrics",
             get(move || async move { prometheus_handler.render() }),
         )
+        .route(
+            "/providers/health",
+            get(handlers::providers_health::handler),
+        )
+        .route(
+            "/providers/weights",
+            get(handlers::providers_weights::handler),
+        )
+        .route(
+            "/providers/disabled",
+            get(handlers::providers_overrides::handler),
+        )
+        .merge(admin_routes)
+        .merge(pprof_routes)
         .with_state(state_arc.clone());
 
+    // Optional gRPC interface dispatching to the same router
+    let grpc_server = state_arc.config.server.grpc_port.map(|grpc_port| {
+        let grpc_addr: SocketAddr = format!("{host}:{grpc_port}")
+            .parse()
+            .expect("Invalid gRPC socket address");
+        grpc::serve(app.clone(), grpc_addr, shutdown_signal())
+    });
+
     let public_server = create_server(app, addr);
     let private_server = create_server(private_app, private_addr);
 
@@ -446,6 +718,11 @@ pub async fn bootstrap(config: Config) -> RpcResult<()> {
                 tokio::select! {
                     _ = interval.tick() => {
                         state_arc.clone().update_provider_weights().await;
+                        // Routing is ready after the first weights update
+                        if !state_arc.is_started() {
+                            state_arc.mark_started();
+                            info!("Startup completed, the instance is ready");
+                        }
                     }
                     _ = signal::ctrl_c() => {
                         info!("Weights updater received shutdown signal");
@@ -483,26 +760,32 @@ pub async fn bootstrap(config: Config) -> RpcResult<()> {
         }
     };
 
+    let config_reloader = reload::run(state_arc.clone());
+
     let profiler = async move {
         if let Err(e) = tokio::spawn(profiler::run()).await {
             warn!("Memory debug stats collection failed with: {e:?}");
         }
         Ok(())
     };
-    let state_for_reconciler = state_arc.clone();
+    // Exchange transactions reconciler, the runs are leader-elected across the
+    // replicas by the Redis lock
+    let exchange_reconciler = {
+        let state = state_arc.clone();
+        async move {
+            handlers::json_rpc::exchanges::reconciler::run(state).await;
+            Ok::<(), std::io::Error>(())
+        }
+    };
 
-    let services = vec![
+    let mut services = vec![
         tokio::spawn(public_server),
         tokio::spawn(private_server),
         tokio::spawn(weights_updater),
         tokio::spawn(system_metrics_updater),
+        tokio::spawn(config_reloader),
         tokio::spawn(profiler),
-        tokio::spawn({
-            async move {
-                handlers::json_rpc::exchanges::reconciler::run(state_for_reconciler).await;
-                Ok::<(), std::io::Error>(())
-            }
-        }),
+        tokio::spawn(exchange_reconciler),
         // Spawning a new task to observe metrics from the database by interval polling
         tokio::spawn({
             let postgres = state_arc.postgres.clone();
@@ -525,6 +808,85 @@ pub async fn bootstrap(config: Config) -> RpcResult<()> {
         }),
     ];
 
+    // Expired permissions garbage collection is only relevant when the persistent storage is configured
+    if state_arc.persistent_storage.is_some() {
+        let state_for_sessions_gc = state_arc.clone();
+        services.push(tokio::spawn(async move {
+            handlers::sessions::gc::run(state_for_sessions_gc).await;
+            Ok::<(), std::io::Error>(())
+        }));
+
+        // Subscriptions are charged by the permissions stored in the persistent storage
+        let state_for_subscriptions = state_arc.clone();
+        services.push(tokio::spawn(async move {
+            handlers::subscriptions::executor::run(state_for_subscriptions).await;
+            Ok::<(), std::io::Error>(())
+        }));
+    }
+
+    // Tokens with the missing metadata are enqueued by the balance and history handlers
+    let state_for_backfill = state_arc.clone();
+    services.push(tokio::spawn(async move {
+        token_metadata_backfill::run(state_for_backfill).await;
+        Ok::<(), std::io::Error>(())
+    }));
+
+    // Scam tokens feeds are refreshed for the balance, history and price filters
+    let state_for_scam_tokens = state_arc.clone();
+    services.push(tokio::spawn(async move {
+        scam_tokens::run(state_for_scam_tokens).await;
+        Ok::<(), std::io::Error>(())
+    }));
+
+    // Chains are quarantined only when the threshold is configured
+    if config.server.chain_quarantine_threshold > 0 {
+        let state = state_arc.clone();
+        let probe_interval =
+            Duration::from_secs(config.server.chain_quarantine_probe_interval_secs);
+        services.push(tokio::spawn(async move {
+            chain_quarantine::run(state, probe_interval).await;
+            Ok::<(), std::io::Error>(())
+        }));
+    }
+
+    if let Some(grpc_server) = grpc_server {
+        services.push(tokio::spawn(grpc_server));
+    }
+
+    // Webhook events are queued by the features and only delivered when signed
+    if let Some(signing_secret) = state_arc.config.server.webhook_signing_secret.clone() {
+        let state = state_arc.clone();
+        services.push(tokio::spawn(async move {
+            webhooks::run(state, signing_secret).await;
+            Ok::<(), std::io::Error>(())
+        }));
+    }
+
+    if let Some(refresh_interval) = config.server.secrets_refresh_interval_secs {
+        let state = state_arc.clone();
+        services.push(tokio::spawn(async move {
+            reload::refresh_secrets(state, Duration::from_secs(refresh_interval)).await;
+            Ok::<(), std::io::Error>(())
+        }));
+    }
+
+    if let (Some(usage), Some(export_bucket)) = (usage, config.usage.export_bucket.clone()) {
+        let report_interval = config.usage.report_interval();
+        let metrics = metrics.clone();
+        services.push(tokio::spawn(async move {
+            usage::run(
+                usage,
+                s3_client,
+                export_bucket,
+                external_ip,
+                report_interval,
+                metrics,
+            )
+            .await;
+            Ok::<(), std::io::Error>(())
+        }));
+    }
+
     // Wait for either services to complete or shutdown signal
     tokio::select! {
         result = futures_util::future::select_all(services) => {
@@ -534,8 +896,15 @@ pub async fn bootstrap(config: Config) -> RpcResult<()> {
         }
         _ = shutdown_signal() => {
             info!("Graceful shutdown initiated, allowing services to complete current work...");
-            // Give services a moment to finish current requests
-            tokio::time::sleep(GRACEFUL_SHUTDOWN_DELAY).await;
+            // Give services a moment to finish current requests and close the
+            // WebSocket sessions with the going away code for the clients to
+            // reconnect to other instances
+            tokio::join!(
+                tokio::time::sleep(GRACEFUL_SHUTDOWN_DELAY),
+                state_arc
+                    .ws_sessions
+                    .drain(Duration::from_secs(config.server.ws_drain_window_secs)),
+            );
             info!("Graceful shutdown completed");
         }
     }
@@ -581,7 +950,7 @@ async fn shutdown_signal() {
     info!("Signal received, starting graceful shutdown");
 }
 
-fn init_providers(config: &ProvidersConfig) -> ProviderRepository {
+pub(crate) fn init_providers(config: &ProvidersConfig) -> ProviderRepository {
     // Redis pool for providers responses caching where needed
     let mut redis_pool = None;
     if let Some(redis_addr) = &config.cache_redis_addr {
@@ -602,9 +971,43 @@ fn init_providers(config: &ProvidersConfig) -> ProviderRepository {
         ));
     };
 
-    // Keep in-sync with SUPPORTED_CHAINS.md
-
     let mut providers = ProviderRepository::new(config);
+    // Test-only local node replaces the RPC providers for the functional tests
+    if let Some(localnet_url) = config.override_localnet_url.clone() {
+        providers.add_rpc_provider::<LocalnetProvider, LocalnetConfig>(LocalnetConfig::new(
+            localnet_url,
+        ));
+    } else {
+        add_rpc_providers(&mut providers, config);
+    }
+
+    providers.add_balance_provider::<ZerionProvider, ZerionConfig>(
+        ZerionConfig::new(
+            config.zerion_api_key.clone(),
+            config.override_api_proxy_url.clone(),
+        ),
+        None,
+    );
+    providers.add_balance_provider::<DuneProvider, DuneConfig>(
+        DuneConfig::new(
+            config.dune_sim_api_key.clone(),
+            config.override_api_proxy_url.clone(),
+        ),
+        None,
+    );
+    providers.add_balance_provider::<SolScanProvider, SolScanConfig>(
+        SolScanConfig::new(
+            config.solscan_api_v2_token.clone(),
+            config.override_api_proxy_url.clone(),
+        ),
+        redis_pool.clone(),
+    );
+
+    providers
+}
+
+/// Adds the RPC and WebSocket providers, keep in-sync with SUPPORTED_CHAINS.md
+fn add_rpc_providers(providers: &mut ProviderRepository, config: &ProvidersConfig) {
     providers.add_rpc_provider::<AuroraProvider, AuroraConfig>(AuroraConfig::default());
     providers.add_rpc_provider::<ArbitrumProvider, ArbitrumConfig>(ArbitrumConfig::default());
     providers.add_rpc_provider::<PoktProvider, PoktConfig>(PoktConfig::new(
@@ -662,6 +1065,8 @@ fn init_providers(config: &ProvidersConfig) -> ProviderRepository {
     providers.add_ws_provider::<QuicknodeWsProvider, QuicknodeConfig>(QuicknodeConfig::new(
         config.quicknode_api_tokens.clone(),
     ));
+    providers
+        .add_ws_provider::<PublicnodeWsProvider, PublicnodeConfig>(PublicnodeConfig::default());
 
     for chain in &chain_config::ACTIVE_CONFIG.chains {
         for provider in &chain.providers {
@@ -672,21 +1077,6 @@ fn init_providers(config: &ProvidersConfig) -> ProviderRepository {
             });
         }
     }
-
-    providers.add_balance_provider::<ZerionProvider, ZerionConfig>(
-        ZerionConfig::new(config.zerion_api_key.clone()),
-        None,
-    );
-    providers.add_balance_provider::<DuneProvider, DuneConfig>(
-        DuneConfig::new(config.dune_sim_api_key.clone()),
-        None,
-    );
-    providers.add_balance_provider::<SolScanProvider, SolScanConfig>(
-        SolScanConfig::new(config.solscan_api_v2_token.clone()),
-        redis_pool.clone(),
-    );
-
-    providers
 }
 
 async fn get_s3_client(config: &Config) -> S3Client {
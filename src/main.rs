@@ -1,8 +1,8 @@
 use {
     dotenv::dotenv,
-    rpc_proxy::{env::Config, error},
+    rpc_proxy::{env::Config, error, otel},
     tracing::level_filters::LevelFilter,
-    tracing_subscriber::{fmt::format::FmtSpan, EnvFilter},
+    tracing_subscriber::{fmt::format::FmtSpan, prelude::*, EnvFilter},
 };
 
 #[global_allocator]
@@ -21,16 +21,62 @@ async fn main() -> error::RpcResult<()> {
         .map_err(|e| dbg!(e))
         .expect("Failed to load config, please ensure all env variables are defined.");
 
-    tracing_subscriber::fmt()
-        .with_env_filter(
+    // Validating the config and the external services access without starting
+    // the servers, the process exit code is set by the validation result
+    if std::env::args().nth(1).as_deref() == Some("validate-config") {
+        let report = rpc_proxy::validate_config::validate(config).await;
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&report).expect("Failed to serialize the report")
+        );
+        std::process::exit(if report.ok { 0 } else { 1 });
+    }
+
+    // Copying the IRN persistent storage records to Postgres, the optional
+    // argument is the file with the orchestration IDs to copy by the lines
+    if std::env::args().nth(1).as_deref() == Some("migrate-irn-storage") {
+        let orchestration_ids = match std::env::args().nth(2) {
+            Some(path) => std::fs::read_to_string(path)
+                .expect("Failed to read the orchestration IDs file")
+                .lines()
+                .map(str::trim)
+                .filter(|id| !id.is_empty())
+                .map(ToOwned::to_owned)
+                .collect(),
+            None => Vec::new(),
+        };
+        let report = rpc_proxy::migrate_storage::migrate(config, orchestration_ids).await?;
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&report).expect("Failed to serialize the report")
+        );
+        std::process::exit(if report.errors.is_empty() { 0 } else { 1 });
+    }
+
+    let tracer_provider =
+        otel::tracer_provider(&config.otel).expect("Failed to create the tracer provider");
+
+    tracing_subscriber::registry()
+        .with(
             EnvFilter::builder()
                 .with_default_directive(LevelFilter::ERROR.into())
                 .parse(&config.server.log_level)
                 .expect("Invalid log level"),
         )
-        .with_span_events(FmtSpan::CLOSE)
-        .with_ansi(false)
+        .with(
+            tracing_subscriber::fmt::layer()
+                .with_span_events(FmtSpan::CLOSE)
+                .with_ansi(false),
+        )
+        .with(otel::layer(&tracer_provider))
         .init();
 
-    rpc_proxy::bootstrap(config).await
+    let result = rpc_proxy::bootstrap(config).await;
+
+    // Flush the pending spans
+    if let Err(e) = tracer_provider.shutdown() {
+        tracing::warn!("Failed to shutdown the tracer provider: {e}");
+    }
+
+    result
 }
@@ -28,7 +28,32 @@ pub enum ExchangeReconciliationQueryType {
     UpdateStatus,
     TouchNonTerminal,
     ClaimDueBatch,
-    ExpireOldPending,
+    DeadLetterUnresolved,
+    GetBySessionId,
+    SetRecipient,
+}
+
+#[derive(Clone, Copy, Debug, strum_macros::Display)]
+pub enum ExchangeReconcilerResult {
+    Succeeded,
+    Failed,
+    Pending,
+    ProviderError,
+    DeadLetter,
+}
+
+#[derive(Clone, Copy, Debug, strum_macros::Display)]
+pub enum WebhookDeliveryResult {
+    Delivered,
+    Retried,
+    Failed,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, strum_macros::Display)]
+pub enum TokenMetadataBackfillResult {
+    Provider,
+    OnChain,
+    Missed,
 }
 
 #[derive(strum_macros::Display)]
@@ -61,6 +86,12 @@ impl Metrics {
             .record(retires_count as f64);
     }
 
+    pub fn add_rpc_retry_budget_exhausted(&self, chain_id: String) {
+        counter!("rpc_retry_budget_exhausted_counter",
+            StringLabel<"chain_id", String> => &chain_id)
+        .increment(1);
+    }
+
     pub fn add_rpc_cached_call(&self, chain_id: String, method: String) {
         counter!("rpc_cached_call_counter", 
             StringLabel<"chain_id", String> => &chain_id, 
@@ -81,6 +112,23 @@ impl Metrics {
         .increment(1);
     }
 
+    /// Calls of the chain-scoped endpoints, the unsupported chains are
+    /// recorded as `unsupported` to keep the labels bounded
+    pub fn add_chain_http_call(
+        &self,
+        code: u16,
+        route: String,
+        chain_id: String,
+        namespace: String,
+    ) {
+        counter!("chain_http_call_counter",
+            StringLabel<"code", String> => &code.to_string(),
+            StringLabel<"route", String> => &route,
+            StringLabel<"chain_id", String> => &chain_id,
+            StringLabel<"namespace", String> => &namespace)
+        .increment(1);
+    }
+
     pub fn add_http_latency(&self, code: u16, route: String, latency: f64) {
         histogram!("http_latency_tracker",
             StringLabel<"code", String> => &code.to_string(),
@@ -139,6 +187,13 @@ impl Metrics {
         .increment(1);
     }
 
+    pub fn add_malformed_provider_response(&self, chain_id: String, provider: &dyn RpcProvider) {
+        counter!("provider_malformed_response_counter", 
+            StringLabel<"chain_id", String> => &chain_id, 
+            StringLabel<"provider", String> => &provider.provider_kind().to_string())
+        .increment(1);
+    }
+
     pub fn add_finished_provider_call(&self, chain_id: String, provider: &dyn RpcProvider) {
         counter!("provider_finished_call_counter", 
             StringLabel<"chain_id", String> => &chain_id, 
@@ -200,6 +255,23 @@ impl Metrics {
             .record(start.elapsed().as_secs_f64());
     }
 
+    pub fn add_exchange_reconciler_run(&self, is_leader: bool, start: Instant) {
+        counter!("exchange_reconciler_runs_counter",
+            StringLabel<"leader", String> => &is_leader.to_string()
+        )
+        .increment(1);
+        if is_leader {
+            histogram!("exchange_reconciler_run_latency").record(start.elapsed().as_secs_f64());
+        }
+    }
+
+    pub fn add_exchange_reconciler_result(&self, result: ExchangeReconcilerResult, count: u64) {
+        counter!("exchange_reconciler_transactions_counter",
+            StringLabel<"result", String> => &result.to_string()
+        )
+        .increment(count);
+    }
+
     pub fn add_exchange_reconciliation_query_latency(
         &self,
         query_type: ExchangeReconciliationQueryType,
@@ -212,6 +284,53 @@ impl Metrics {
         .record(start.elapsed().as_secs_f64());
     }
 
+    pub fn add_webhook_delivery(&self, event_type: &str, result: WebhookDeliveryResult) {
+        counter!("webhook_delivery_counter",
+            StringLabel<"event_type", String> => &event_type.to_owned(),
+            StringLabel<"result", String> => &result.to_string()
+        )
+        .increment(1);
+    }
+
+    pub fn add_webhook_delivery_latency(&self, event_type: &str, start: Instant) {
+        histogram!("webhook_delivery_latency",
+            StringLabel<"event_type", String> => &event_type.to_owned()
+        )
+        .record(start.elapsed().as_secs_f64());
+    }
+
+    pub fn add_token_metadata_backfill_enqueued(&self, source: &str) {
+        counter!("token_metadata_backfill_enqueued_counter",
+            StringLabel<"source", String> => &source.to_owned()
+        )
+        .increment(1);
+    }
+
+    pub fn add_token_metadata_backfill(&self, result: TokenMetadataBackfillResult) {
+        counter!("token_metadata_backfill_counter",
+            StringLabel<"result", String> => &result.to_string()
+        )
+        .increment(1);
+    }
+
+    pub fn add_scam_tokens_feed_fetch(&self, success: bool) {
+        counter!("scam_tokens_feed_fetch_counter",
+            StringLabel<"result", String> => &(if success { "ok" } else { "error" }).to_owned()
+        )
+        .increment(1);
+    }
+
+    pub fn record_scam_tokens(&self, count: usize) {
+        gauge!("scam_tokens_count").set(count as f64);
+    }
+
+    pub fn add_scam_tokens_filtered(&self, source: &str, count: usize) {
+        counter!("scam_tokens_filtered_counter",
+            StringLabel<"source", String> => &source.to_owned()
+        )
+        .increment(count as u64);
+    }
+
     pub fn record_provider_weight(&self, provider: &ProviderKind, chain_id: String, weight: u64) {
         gauge!("provider_weights",
             StringLabel<"provider", String> => &provider.to_string(),
@@ -220,6 +339,40 @@ impl Metrics {
         .set(weight as f64);
     }
 
+    pub fn add_bundler_status_code(
+        &self,
+        provider_kind: &ProviderKind,
+        status: u16,
+        chain_id: String,
+    ) {
+        counter!("bundler_status_code_counter",
+            StringLabel<"provider", String> => &provider_kind.to_string(),
+            StringLabel<"status_code", String> => &status.to_string(),
+            StringLabel<"chain_id", String> => &chain_id)
+        .increment(1);
+    }
+
+    pub fn record_bundler_weight(&self, provider: &ProviderKind, weight: u64) {
+        gauge!("bundler_weights",
+            StringLabel<"provider", String> => &provider.to_string()
+        )
+        .set(weight as f64);
+    }
+
+    pub fn record_chain_quarantined(&self, chain_id: String, quarantined: bool) {
+        gauge!("chain_quarantined",
+            StringLabel<"chain_id", String> => &chain_id
+        )
+        .set(if quarantined { 1.0 } else { 0.0 });
+    }
+
+    pub fn add_quarantined_chain_request(&self, chain_id: String) {
+        counter!("quarantined_chain_request_counter",
+            StringLabel<"chain_id", String> => &chain_id
+        )
+        .increment(1);
+    }
+
     pub fn add_no_providers_for_chain(&self, chain_id: String) {
         counter!("no_providers_for_chain_counter",
             StringLabel<"chain_id", String> => &chain_id
@@ -363,6 +516,15 @@ impl Metrics {
         );
     }
 
+    pub fn add_rate_limiting_script_latency(&self, start: SystemTime) {
+        histogram!("rate_limiting_script_latency_tracker").record(
+            start
+                .elapsed()
+                .unwrap_or(Duration::from_secs(0))
+                .as_secs_f64(),
+        );
+    }
+
     pub fn add_non_rpc_providers_cache_latency(&self, start: SystemTime) {
         histogram!("non_rpc_providers_cache_latency_tracker").record(
             start
@@ -372,10 +534,68 @@ impl Metrics {
         );
     }
 
+    pub fn add_bundler_gas_price_cache_lookup(&self, chain_id: &str, hit: bool) {
+        counter!("bundler_gas_price_cache_lookup_counter",
+            StringLabel<"chain_id", String> => &chain_id.to_string(),
+            StringLabel<"result", String> => &(if hit { "hit" } else { "miss" }).to_string())
+        .increment(1);
+    }
+
+    /// Records the age of the served cached gas price
+    pub fn add_bundler_gas_price_cache_age(&self, chain_id: &str, age: Duration) {
+        histogram!("bundler_gas_price_cache_age",
+            StringLabel<"chain_id", String> => &chain_id.to_string())
+        .record(age.as_secs_f64());
+    }
+
+    pub fn add_local_cache_lookup(&self, cache: &'static str, hit: bool) {
+        counter!("local_cache_lookup_counter",
+            StringLabel<"cache", String> => &cache.to_string(),
+            StringLabel<"result", String> => &(if hit { "hit" } else { "miss" }).to_string())
+        .increment(1);
+    }
+
+    /// Records the mirrored request result, the shadow status is not set when
+    /// the shadow request failed
+    pub fn add_shadow_request(
+        &self,
+        chain_id: &str,
+        primary_status: u16,
+        shadow_status: Option<u16>,
+    ) {
+        let result = match shadow_status {
+            Some(shadow_status) if shadow_status == primary_status => "match",
+            Some(_) => "divergence",
+            None => "error",
+        };
+        counter!("shadow_request_counter",
+            StringLabel<"chain_id", String> => &chain_id.to_string(),
+            StringLabel<"result", String> => &result.to_string())
+        .increment(1);
+        if let Some(shadow_status) = shadow_status.filter(|status| *status != primary_status) {
+            counter!("shadow_divergence_counter",
+                StringLabel<"primary_status", String> => &primary_status.to_string(),
+                StringLabel<"shadow_status", String> => &shadow_status.to_string())
+            .increment(1);
+        }
+    }
+
+    pub fn add_usage_report(&self, success: bool, records: usize) {
+        counter!("usage_reports_counter",
+            StringLabel<"success", String> => &success.to_string()
+        )
+        .increment(1);
+        histogram!("usage_report_records").record(records as f64);
+    }
+
     pub fn add_rate_limited_response(&self) {
         counter!("rate_limited_responses_counter").increment(1);
     }
 
+    pub fn add_request_body_too_large(&self) {
+        counter!("request_body_too_large_counter").increment(1);
+    }
+
     pub fn add_irn_latency(&self, start: SystemTime, operation: OperationType) {
         histogram!("irn_latency_tracker", EnumLabel<"operation", OperationType> => operation)
             .record(
@@ -386,6 +606,24 @@ impl Metrics {
             );
     }
 
+    pub fn add_irn_error(&self, operation: OperationType) {
+        counter!("irn_errors_counter", EnumLabel<"operation", OperationType> => operation)
+            .increment(1);
+    }
+
+    pub fn add_sessions_gc_cleaned(&self, count: u64) {
+        counter!("sessions_gc_cleaned_counter").increment(count);
+    }
+
+    pub fn add_sessions_gc_latency(&self, start: SystemTime) {
+        histogram!("sessions_gc_latency_tracker").record(
+            start
+                .elapsed()
+                .unwrap_or(Duration::from_secs(0))
+                .as_secs_f64(),
+        );
+    }
+
     pub fn add_ca_gas_estimation(
         &self,
         gas: u64,
@@ -407,6 +645,12 @@ impl Metrics {
         counter!("ca_routes_found_counter", StringLabel<"route", String> => &route).increment(1);
     }
 
+    pub fn add_ca_route_cache_lookup(&self, hit: bool) {
+        counter!("ca_route_cache_lookup_counter",
+            StringLabel<"result", String> => &(if hit { "hit" } else { "miss" }).to_string())
+        .increment(1);
+    }
+
     pub fn add_ca_insufficient_funds(&self) {
         counter!("ca_insufficient_funds_counter").increment(1);
     }
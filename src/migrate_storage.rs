@@ -0,0 +1,148 @@
+//! Copies the IRN persistent storage records to the Postgres storage.
+
+use {
+    crate::{
+        env::Config,
+        error::RpcError,
+        handlers::sessions::SESSIONS_ADDRESSES_INDEX_KEY,
+        metrics::Metrics,
+        secrets,
+        storage::{error::StorageError, irn::Irn, postgres::PostgresStorage, PersistentStorage},
+    },
+    serde::Serialize,
+    sqlx::postgres::PgPoolOptions,
+    std::{sync::Arc, time::Duration},
+    tracing::debug,
+};
+
+const SCAN_BATCH_SIZE: u32 = 255;
+
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MigrationReport {
+    pub addresses: u64,
+    pub permissions: u64,
+    pub orchestrations: u64,
+    /// Addresses and orchestrations failed to copy, the command can be rerun
+    /// as the copy is idempotent
+    pub errors: Vec<String>,
+}
+
+/// Copy the sessions permissions and the orchestrations of the given IDs from the IRN to Postgres
+pub async fn migrate(
+    mut config: Config,
+    orchestration_ids: Vec<String>,
+) -> Result<MigrationReport, RpcError> {
+    secrets::resolve(&mut config).await?;
+
+    let (Some(nodes), Some(key), Some(namespace), Some(namespace_secret)) = (
+        config.irn.nodes.clone(),
+        config.irn.key.clone(),
+        config.irn.namespace.clone(),
+        config.irn.namespace_secret.clone(),
+    ) else {
+        return Err(RpcError::InvalidConfiguration(
+            "IRN is not configured".to_owned(),
+        ));
+    };
+    let irn = Irn::new(
+        key,
+        nodes,
+        namespace,
+        namespace_secret,
+        Arc::new(Metrics::new()),
+    )
+    .await?;
+
+    let postgres = PgPoolOptions::new()
+        .max_connections(config.postgres.max_connections.into())
+        .connect(&config.postgres.uri)
+        .await?;
+    sqlx::migrate!("./migrations").run(&postgres).await?;
+    let target = PostgresStorage::new(postgres);
+
+    let sessions_ttl = config.storage.sessions_ttl();
+    let mut report = MigrationReport::default();
+    let mut cursor = None;
+    loop {
+        let (addresses, next_cursor) = irn
+            .hscan(SESSIONS_ADDRESSES_INDEX_KEY.into(), SCAN_BATCH_SIZE, cursor)
+            .await?;
+        for (address, index_value) in addresses {
+            if let Err(e) = copy_address(
+                &irn,
+                &target,
+                &address,
+                index_value,
+                sessions_ttl,
+                &mut report,
+            )
+            .await
+            {
+                report.errors.push(format!("address {address}: {e}"));
+            }
+        }
+
+        cursor = next_cursor;
+        if cursor.is_none() {
+            break;
+        }
+    }
+
+    let orchestrations_ttl = config.storage.orchestrations_ttl();
+    for id in orchestration_ids {
+        let result = match irn.get(id.clone()).await {
+            Ok(Some(value)) => target.set(id.clone(), value, orchestrations_ttl).await,
+            Ok(None) => {
+                debug!(id, "orchestration is missing in the IRN");
+                continue;
+            }
+            Err(e) => Err(e),
+        };
+        match result {
+            Ok(()) => report.orchestrations += 1,
+            Err(e) => report.errors.push(format!("orchestration {id}: {e}")),
+        }
+    }
+
+    Ok(report)
+}
+
+/// Copy the permissions of the address, the address is indexed after the permissions so the GC doesn't miss them
+async fn copy_address(
+    irn: &Irn,
+    target: &PostgresStorage,
+    address: &str,
+    index_value: Vec<u8>,
+    ttl: Duration,
+    report: &mut MigrationReport,
+) -> Result<(), StorageError> {
+    let mut cursor = None;
+    loop {
+        let (pcis, next_cursor) = irn
+            .hscan(address.to_owned(), SCAN_BATCH_SIZE, cursor)
+            .await?;
+        for (pci, value) in pcis {
+            target
+                .hset(address.to_owned(), pci.clone(), value, ttl)
+                .await?;
+            report.permissions += 1;
+        }
+
+        cursor = next_cursor;
+        if cursor.is_none() {
+            break;
+        }
+    }
+
+    target
+        .hset(
+            SESSIONS_ADDRESSES_INDEX_KEY.into(),
+            address.to_owned(),
+            index_value,
+            ttl,
+        )
+        .await?;
+    report.addresses += 1;
+    Ok(())
+}
@@ -1,9 +1,113 @@
+/// The dictionary is a list of words separated by newlines
+const DICTIONARY: &str = include_str!("../../assets/names_dictionary.txt");
+
+/// Maximum number of the suggestion candidates checked for the availability
+const MAX_CANDIDATES: usize = 30;
+
+/// Keyboard layout for the keyboard-distance suggestions
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum KeyboardLayout {
+    #[default]
+    Qwerty,
+    Qwertz,
+    Azerty,
+}
+
+impl KeyboardLayout {
+    /// Keyboard layout by the locale hints of the `Accept-Language` header,
+    /// the most preferred language with the known layout is used
+    pub fn from_accept_language(accept_language: &str) -> Self {
+        let mut languages = accept_language
+            .split(',')
+            .filter_map(|language| {
+                let mut parts = language.split(';');
+                let tag = parts.next()?.trim();
+                let quality = match parts.find_map(|part| part.trim().strip_prefix("q=")) {
+                    Some(quality) => quality.parse::<f32>().ok()?,
+                    None => 1.0,
+                };
+                Some((tag, quality))
+            })
+            .collect::<Vec<_>>();
+        // Stable sort keeps the header order for the same quality
+        languages.sort_by(|a, b| b.1.total_cmp(&a.1));
+        languages
+            .into_iter()
+            .find_map(|(tag, _)| Self::from_language_tag(tag))
+            .unwrap_or_default()
+    }
+
+    fn from_language_tag(tag: &str) -> Option<Self> {
+        let language = tag.split(['-', '_']).next()?.to_ascii_lowercase();
+        match language.as_str() {
+            "de" | "cs" | "sk" | "hu" | "sl" | "hr" => Some(Self::Qwertz),
+            "fr" => Some(Self::Azerty),
+            "en" | "es" | "it" | "pt" | "nl" | "pl" | "sv" | "da" | "no" | "fi" => {
+                Some(Self::Qwerty)
+            }
+            _ => None,
+        }
+    }
+
+    /// Keyboard rows from the top, each row is shifted by the half key to the
+    /// right from the row above
+    fn rows(self) -> [&'static str; 4] {
+        match self {
+            Self::Qwerty => ["1234567890", "qwertyuiop", "asdfghjkl", "zxcvbnm"],
+            Self::Qwertz => ["1234567890", "qwertzuiop", "asdfghjkl", "yxcvbnm"],
+            Self::Azerty => ["1234567890", "azertyuiop", "qsdfghjklm", "wxcvbn"],
+        }
+    }
+
+    fn key_position(self, key: char) -> Option<(usize, usize)> {
+        self.rows()
+            .iter()
+            .enumerate()
+            .find_map(|(row, keys)| keys.find(key).map(|column| (row, column)))
+    }
+
+    /// Whether the keys are next to each other on the keyboard
+    pub fn are_adjacent(self, a: char, b: char) -> bool {
+        let (Some((row_a, column_a)), Some((row_b, column_b))) =
+            (self.key_position(a), self.key_position(b))
+        else {
+            return false;
+        };
+        if row_a == row_b {
+            column_a.abs_diff(column_b) == 1
+        } else if row_b == row_a + 1 {
+            column_b == column_a || column_b + 1 == column_a
+        } else if row_a == row_b + 1 {
+            column_a == column_b || column_a + 1 == column_b
+        } else {
+            false
+        }
+    }
+
+    /// Whether the word is a single adjacent key or transposed keys typo of
+    /// the name
+    fn is_typo(self, name: &str, word: &str) -> bool {
+        if name.len() != word.len() || name == word {
+            return false;
+        }
+        let differences = name
+            .chars()
+            .zip(word.chars())
+            .enumerate()
+            .filter(|(_, (a, b))| a != b)
+            .collect::<Vec<_>>();
+        match differences.as_slice() {
+            [(_, (a, b))] => self.are_adjacent(*a, *b),
+            [(i, (a1, b1)), (j, (a2, b2))] => *j == i + 1 && a1 == b2 && a2 == b1,
+            _ => false,
+        }
+    }
+}
+
 /// Returns suggested words from the dictionary that start with the given
 /// prefix.
-pub fn dictionary_suggestions(start_with: &str) -> Vec<&str> {
-    // The dictionary is a list of words separated by newlines
-    let dictionary_contents = include_str!("../../assets/names_dictionary.txt");
-    let candidates: Vec<&str> = dictionary_contents
+pub fn dictionary_suggestions(start_with: &str) -> Vec<&'static str> {
+    let candidates: Vec<&str> = DICTIONARY
         .lines()
         .filter(|&suggested_name| {
             suggested_name.starts_with(start_with) && suggested_name != start_with
@@ -11,3 +115,105 @@ pub fn dictionary_suggestions(start_with: &str) -> Vec<&str> {
         .collect();
     candidates
 }
+
+/// Returns the dictionary words the name is likely a keyboard typo of
+pub fn keyboard_suggestions(name: &str, layout: KeyboardLayout) -> Vec<&'static str> {
+    DICTIONARY
+        .lines()
+        .filter(|word| layout.is_typo(name, word))
+        .collect()
+}
+
+/// Returns the full names to suggest in the order of the relevance, the
+/// exact name in the zone goes first followed by the same name in the other
+/// allowed zones, the keyboard typo corrections and the dictionary words
+/// starting with the name
+pub fn suggestion_candidates(
+    name: &str,
+    zone: &str,
+    allowed_zones: &[String],
+    layout: KeyboardLayout,
+) -> Vec<String> {
+    let mut candidates = vec![format!("{name}.{zone}")];
+    candidates.extend(
+        allowed_zones
+            .iter()
+            .filter(|allowed_zone| *allowed_zone != zone)
+            .map(|allowed_zone| format!("{name}.{allowed_zone}")),
+    );
+    let words = keyboard_suggestions(name, layout)
+        .into_iter()
+        .chain(dictionary_suggestions(name));
+    for word in words {
+        if candidates.len() >= MAX_CANDIDATES {
+            break;
+        }
+        let candidate = format!("{word}.{zone}");
+        if !candidates.contains(&candidate) {
+            candidates.push(candidate);
+        }
+    }
+    candidates
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keyboard_layout_from_accept_language() {
+        assert_eq!(
+            KeyboardLayout::from_accept_language("de-DE,de;q=0.9,en;q=0.8"),
+            KeyboardLayout::Qwertz
+        );
+        assert_eq!(
+            KeyboardLayout::from_accept_language("en;q=0.5, fr-CH"),
+            KeyboardLayout::Azerty
+        );
+        assert_eq!(
+            KeyboardLayout::from_accept_language("ja,en-US;q=0.7,de;q=0.3"),
+            KeyboardLayout::Qwerty
+        );
+        assert_eq!(
+            KeyboardLayout::from_accept_language("*"),
+            KeyboardLayout::Qwerty
+        );
+    }
+
+    #[test]
+    fn test_keyboard_adjacency() {
+        let qwerty = KeyboardLayout::Qwerty;
+        assert!(qwerty.are_adjacent('q', 'w'));
+        assert!(qwerty.are_adjacent('s', 'w'));
+        assert!(qwerty.are_adjacent('w', 's'));
+        assert!(qwerty.are_adjacent('s', 'z'));
+        assert!(qwerty.are_adjacent('q', '2'));
+        assert!(!qwerty.are_adjacent('q', 's'));
+        assert!(!qwerty.are_adjacent('a', 'p'));
+        assert!(!qwerty.are_adjacent('t', 'z'));
+        assert!(KeyboardLayout::Qwertz.are_adjacent('t', 'z'));
+        assert!(KeyboardLayout::Azerty.are_adjacent('a', 'z'));
+    }
+
+    #[test]
+    fn test_keyboard_typos() {
+        let qwerty = KeyboardLayout::Qwerty;
+        assert!(qwerty.is_typo("hrllo", "hello"));
+        assert!(qwerty.is_typo("hlelo", "hello"));
+        assert!(!qwerty.is_typo("hpllo", "hello"));
+        assert!(!qwerty.is_typo("hello", "hello"));
+        assert!(!qwerty.is_typo("hrlli", "hello"));
+        assert!(!qwerty.is_typo("helo", "hello"));
+    }
+
+    #[test]
+    fn test_suggestion_candidates() {
+        let zones = vec!["reown.id".to_string(), "wcn.id".to_string()];
+        let candidates = suggestion_candidates("aachrn", "reown.id", &zones, Default::default());
+        assert_eq!(
+            candidates[..3],
+            ["aachrn.reown.id", "aachrn.wcn.id", "aachen.reown.id"]
+        );
+        assert!(candidates.len() <= MAX_CANDIDATES);
+    }
+}
@@ -0,0 +1,119 @@
+use {
+    hyper::HeaderMap,
+    opentelemetry::{
+        global,
+        trace::{TraceContextExt, TracerProvider as _},
+        KeyValue,
+    },
+    opentelemetry_http::{HeaderExtractor, HeaderInjector},
+    opentelemetry_otlp::{SpanExporter, WithExportConfig},
+    opentelemetry_sdk::{
+        propagation::TraceContextPropagator,
+        runtime,
+        trace::{Sampler, Tracer, TracerProvider},
+        Resource,
+    },
+    serde_piecewise_default::DeserializePiecewiseDefault,
+    tracing::{Span, Subscriber},
+    tracing_opentelemetry::{OpenTelemetryLayer, OpenTelemetrySpanExt},
+    tracing_subscriber::registry::LookupSpan,
+};
+
+const DEFAULT_SERVICE_NAME: &str = "blockchain-api";
+const DEFAULT_TRACE_SAMPLE_PERCENT: u8 = 100;
+
+#[derive(DeserializePiecewiseDefault, Debug, Clone, Default, PartialEq, Eq)]
+pub struct OtelConfig {
+    /// OTLP/HTTP traces endpoint e.g. `http://localhost:4318/v1/traces`,
+    /// the traces export is disabled when not set
+    pub otlp_endpoint: Option<String>,
+    pub service_name: Option<String>,
+    /// Percentage of the root traces to export, the sampling decision of the
+    /// incoming trace context is respected
+    pub trace_sample_percent: Option<u8>,
+}
+
+/// Creates the tracer provider and installs the W3C trace context propagator,
+/// so the upstream requests carry the trace context correlated with the
+/// request ID. The spans are exported by the OTLP exporter when the endpoint
+/// is configured, otherwise the trace context is generated only.
+pub fn tracer_provider(
+    config: &OtelConfig,
+) -> Result<TracerProvider, opentelemetry::trace::TraceError> {
+    let service_name = config
+        .service_name
+        .clone()
+        .unwrap_or_else(|| DEFAULT_SERVICE_NAME.to_owned());
+    let builder = TracerProvider::builder()
+        .with_resource(Resource::new([KeyValue::new("service.name", service_name)]));
+
+    let provider = match &config.otlp_endpoint {
+        Some(endpoint) => {
+            let exporter = SpanExporter::builder()
+                .with_http()
+                .with_endpoint(endpoint)
+                .build()?;
+            let sample_ratio = config
+                .trace_sample_percent
+                .unwrap_or(DEFAULT_TRACE_SAMPLE_PERCENT)
+                .min(100) as f64
+                / 100.0;
+            builder
+                .with_batch_exporter(exporter, runtime::Tokio)
+                .with_sampler(Sampler::ParentBased(Box::new(Sampler::TraceIdRatioBased(
+                    sample_ratio,
+                ))))
+                .build()
+        }
+        // Spans are not recorded without the exporter, the trace IDs are
+        // still generated for the propagation
+        None => builder.with_sampler(Sampler::AlwaysOff).build(),
+    };
+
+    global::set_text_map_propagator(TraceContextPropagator::new());
+    global::set_tracer_provider(provider.clone());
+    Ok(provider)
+}
+
+/// Tracing layer exporting the spans to the OTLP tracer provider
+pub fn layer<S>(provider: &TracerProvider) -> OpenTelemetryLayer<S, Tracer>
+where
+    S: Subscriber + for<'span> LookupSpan<'span>,
+{
+    tracing_opentelemetry::layer().with_tracer(provider.tracer(DEFAULT_SERVICE_NAME))
+}
+
+/// Continues the trace from the incoming request headers
+pub fn set_parent_from_headers(span: &Span, headers: &HeaderMap) {
+    let context =
+        global::get_text_map_propagator(|propagator| propagator.extract(&HeaderExtractor(headers)));
+    span.set_parent(context);
+}
+
+/// Trace ID of the span to correlate the request ID with the upstream
+/// providers requests, `None` outside of the trace
+pub fn trace_id(span: &Span) -> Option<String> {
+    let context = span.context();
+    let span_context = context.span().span_context().clone();
+    span_context
+        .is_valid()
+        .then(|| span_context.trace_id().to_string())
+}
+
+/// Propagates the current trace context to the upstream requests
+pub trait PropagateTraceContext {
+    fn propagate_trace_context(self) -> Self;
+}
+
+impl PropagateTraceContext for reqwest::RequestBuilder {
+    fn propagate_trace_context(self) -> Self {
+        let mut headers = HeaderMap::new();
+        global::get_text_map_propagator(|propagator| {
+            propagator.inject_context(
+                &Span::current().context(),
+                &mut HeaderInjector(&mut headers),
+            )
+        });
+        self.headers(headers)
+    }
+}
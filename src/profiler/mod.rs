@@ -1,5 +1,18 @@
+use {
+    std::{ffi::CString, time::Duration},
+    tikv_jemalloc_ctl::raw,
+};
+
+const CPU_PROFILE_FREQUENCY: i32 = 99;
+/// Frames of the system libraries excluded from the CPU profiles
+const CPU_PROFILE_BLOCKLIST: &[&str] = &["libc", "libgcc", "pthread", "vdso"];
+
 #[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize)]
-pub struct ProfilerConfig {}
+pub struct ProfilerConfig {
+    /// Bearer secret of the `/debug/pprof` endpoints on the private port, the
+    /// endpoints are disabled when not set
+    pub secret: Option<String>,
+}
 
 pub async fn run() {
     loop {
@@ -10,3 +23,72 @@ pub async fn run() {
         tokio::time::sleep(tokio::time::Duration::from_secs(30)).await;
     }
 }
+
+/// Output format of the CPU profile
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CpuProfileFormat {
+    /// Protobuf profile for the `go tool pprof`
+    #[default]
+    Pprof,
+    /// Flamegraph SVG
+    Flamegraph,
+}
+
+/// Samples the CPU for the duration, only a single profile can be collected
+/// at a time
+pub async fn cpu_profile(duration: Duration, format: CpuProfileFormat) -> Result<Vec<u8>, String> {
+    let guard = pprof::ProfilerGuardBuilder::default()
+        .frequency(CPU_PROFILE_FREQUENCY)
+        .blocklist(CPU_PROFILE_BLOCKLIST)
+        .build()
+        .map_err(|e| format!("failed to start the CPU profiler: {e}"))?;
+    tokio::time::sleep(duration).await;
+    let report = guard
+        .report()
+        .build()
+        .map_err(|e| format!("failed to build the CPU profile: {e}"))?;
+
+    let mut body = Vec::new();
+    match format {
+        CpuProfileFormat::Pprof => {
+            use pprof::protos::Message;
+            report
+                .pprof()
+                .map_err(|e| format!("failed to build the pprof profile: {e}"))?
+                .encode(&mut body)
+                .map_err(|e| format!("failed to encode the pprof profile: {e}"))?;
+        }
+        CpuProfileFormat::Flamegraph => report
+            .flamegraph(&mut body)
+            .map_err(|e| format!("failed to render the flamegraph: {e}"))?,
+    }
+    Ok(body)
+}
+
+/// Dumps the jemalloc heap profile, readable by the `jeprof` including the
+/// `--collapsed` flamegraph input. The heap profiling is enabled at startup
+/// by the `_RJEM_MALLOC_CONF=prof:true` environment variable.
+pub async fn heap_profile() -> Result<Vec<u8>, String> {
+    tokio::task::spawn_blocking(|| {
+        // SAFETY: `opt.prof` is a read-only boolean option
+        let enabled = unsafe { raw::read::<bool>(b"opt.prof\0") }
+            .map_err(|e| format!("failed to read the heap profiling option: {e}"))?;
+        if !enabled {
+            return Err("heap profiling is not enabled, set `_RJEM_MALLOC_CONF=prof:true`".into());
+        }
+
+        let path = std::env::temp_dir().join(format!("heap-{}.prof", uuid::Uuid::new_v4()));
+        let c_path = CString::new(path.to_string_lossy().as_bytes())
+            .map_err(|e| format!("invalid heap profile path: {e}"))?;
+        // SAFETY: `prof.dump` takes the nul-terminated file path which
+        // outlives the call
+        unsafe { raw::write(b"prof.dump\0", c_path.as_ptr()) }
+            .map_err(|e| format!("failed to dump the heap profile: {e}"))?;
+        let profile = std::fs::read(&path);
+        let _ = std::fs::remove_file(&path);
+        profile.map_err(|e| format!("failed to read the heap profile: {e}"))
+    })
+    .await
+    .map_err(|e| format!("heap profile task failed: {e}"))?
+}
@@ -1,11 +1,12 @@
 use {
     crate::{
         error::{RpcError, RpcResult},
+        metrics::Metrics,
         project::{
             metrics::ProjectDataMetrics,
             storage::{Config as StorageConfig, ProjectDataResult, ProjectStorage},
         },
-        storage::{error::StorageError, redis},
+        storage::{error::StorageError, local_cache::LocalCache, redis},
     },
     cerberus::{
         project::{
@@ -56,7 +57,11 @@ impl wc_metrics::Enum for ResponseSource {
 }
 
 impl Registry {
-    pub fn new(cfg_registry: &Config, cfg_storage: &StorageConfig) -> RpcResult<Self> {
+    pub fn new(
+        cfg_registry: &Config,
+        cfg_storage: &StorageConfig,
+        app_metrics: Arc<Metrics>,
+    ) -> RpcResult<Self> {
         let api_url = cfg_registry.api_url.as_ref();
         let api_auth_token = cfg_registry.api_auth_token.as_ref();
         let metrics = ProjectDataMetrics::new();
@@ -78,7 +83,7 @@ impl Registry {
 
             let cache_addr = cfg_storage.project_data_redis_addr();
             let cache = if let Some(cache_addr) = cache_addr {
-                let cache = open_redis(&cache_addr, cfg_storage.redis_max_connections)?;
+                let cache = open_redis(&cache_addr, cfg_storage, app_metrics)?;
 
                 Some(ProjectStorage::new(
                     cache,
@@ -109,7 +114,16 @@ impl Registry {
         self.circuit_base_instant.elapsed().as_millis() as u64
     }
 
-    fn is_circuit_open(&self) -> bool {
+    /// Deletes the cached project data to not wait out the cache TTL, other
+    /// instances may serve the local cache copies up to the local cache TTL
+    pub async fn invalidate_project_data(&self, id: &str) -> RpcResult<()> {
+        if let Some(cache) = &self.cache {
+            cache.invalidate(id).await?;
+        }
+        Ok(())
+    }
+
+    pub fn is_circuit_open(&self) -> bool {
         let last = self.circuit_last_error_ms.load(Ordering::Relaxed);
         !self.circuit_cooldown.is_zero()
             && last != 0
@@ -138,6 +152,18 @@ impl Registry {
         })
     }
 
+    /// Project data from the cache only, the registry is not requested.
+    /// Returns `None` when the project data is not cached or the project is
+    /// not found.
+    pub async fn cached_project_data(&self, id: &str) -> Option<ProjectDataResponse> {
+        let cache = self.cache.as_ref()?;
+        let request = ProjectDataRequest::new(id).include_limits();
+        match cache.fetch(request).await {
+            Ok(Some(Ok(data))) => Some(data),
+            _ => None,
+        }
+    }
+
     pub async fn project_data_request(
         &self,
         request: ProjectDataRequest<'_>,
@@ -235,9 +261,19 @@ impl Registry {
     }
 }
 
+/// Project data is requested on every call, so it's kept in the in-memory tier
+/// in front of Redis
 fn open_redis(
     addr: &redis::Addr<'_>,
-    redis_max_connections: usize,
-) -> Result<Arc<redis::Redis>, StorageError> {
-    redis::Redis::new(addr, redis_max_connections).map(Arc::new)
+    cfg_storage: &StorageConfig,
+    metrics: Arc<Metrics>,
+) -> Result<Arc<LocalCache<redis::Redis>>, StorageError> {
+    let redis = redis::Redis::new(addr, cfg_storage.redis_max_connections)?;
+    Ok(Arc::new(LocalCache::new(
+        redis,
+        "project_data",
+        cfg_storage.local_cache_max_capacity,
+        cfg_storage.local_cache_ttl(),
+        metrics,
+    )))
 }
@@ -1,6 +1,6 @@
 use {
     crate::storage::redis::Addr as RedisAddr, serde::Deserialize,
-    serde_piecewise_default::DeserializePiecewiseDefault,
+    serde_piecewise_default::DeserializePiecewiseDefault, std::time::Duration,
 };
 
 #[derive(DeserializePiecewiseDefault, Debug, Clone, PartialEq, Eq)]
@@ -12,6 +12,21 @@ pub struct Config {
     pub identity_cache_redis_addr_write: Option<String>,
     pub rate_limiting_cache_redis_addr_read: Option<String>,
     pub rate_limiting_cache_redis_addr_write: Option<String>,
+    pub local_cache_max_capacity: u64,
+    pub local_cache_ttl: u64,
+    pub identity_cache_ttl: u64,
+    /// TTL of the identity results without the name and avatar
+    pub identity_negative_cache_ttl: u64,
+    /// Minimum interval between the forced identity refreshes of an address
+    pub identity_refresh_interval: u64,
+    pub balance_cache_ttl: u64,
+    /// TTL of the per chain bundler user operation gas prices
+    pub bundler_gas_price_cache_ttl: u64,
+    /// TTL of the chain abstraction routes for the identical requests, zero
+    /// disables the routes caching
+    pub ca_route_cache_ttl: u64,
+    pub sessions_ttl: u64,
+    pub orchestrations_ttl: u64,
 }
 
 impl Default for Config {
@@ -24,11 +39,59 @@ impl Default for Config {
             identity_cache_redis_addr_write: None,
             rate_limiting_cache_redis_addr_read: None,
             rate_limiting_cache_redis_addr_write: None,
+            local_cache_max_capacity: 10_000,
+            local_cache_ttl: 10,
+            identity_cache_ttl: 60 * 60 * 24,      // 1 day
+            identity_negative_cache_ttl: 60 * 60,  // 1 hour
+            identity_refresh_interval: 60,         // 1 minute
+            balance_cache_ttl: 10,                 // 10 seconds
+            bundler_gas_price_cache_ttl: 5,        // 5 seconds
+            ca_route_cache_ttl: 10,                // 10 seconds
+            sessions_ttl: 60 * 60 * 24 * 30,       // 30 days
+            orchestrations_ttl: 60 * 60 * 24 * 30, // 30 days
         }
     }
 }
 
 impl Config {
+    pub fn local_cache_ttl(&self) -> Duration {
+        Duration::from_secs(self.local_cache_ttl)
+    }
+
+    pub fn identity_cache_ttl(&self) -> Duration {
+        Duration::from_secs(self.identity_cache_ttl)
+    }
+
+    pub fn identity_negative_cache_ttl(&self) -> Duration {
+        Duration::from_secs(self.identity_negative_cache_ttl)
+    }
+
+    pub fn identity_refresh_interval(&self) -> Duration {
+        Duration::from_secs(self.identity_refresh_interval)
+    }
+
+    pub fn balance_cache_ttl(&self) -> Duration {
+        Duration::from_secs(self.balance_cache_ttl)
+    }
+
+    pub fn bundler_gas_price_cache_ttl(&self) -> Duration {
+        Duration::from_secs(self.bundler_gas_price_cache_ttl)
+    }
+
+    pub fn ca_route_cache_ttl(&self) -> Duration {
+        Duration::from_secs(self.ca_route_cache_ttl)
+    }
+
+    /// TTL of the sessions permissions and their spending usage records
+    pub fn sessions_ttl(&self) -> Duration {
+        Duration::from_secs(self.sessions_ttl)
+    }
+
+    /// TTL of the chain abstraction orchestrations status records
+    pub fn orchestrations_ttl(&self) -> Duration {
+        Duration::from_secs(self.orchestrations_ttl)
+    }
+
     pub fn project_data_redis_addr(&self) -> Option<RedisAddr<'_>> {
         match (
             &self.project_data_redis_addr_read,
@@ -62,6 +62,14 @@ impl ProjectStorage {
         Ok(data)
     }
 
+    /// Deletes the cached project data of all the request flags
+    pub async fn invalidate(&self, id: &str) -> StorageResult<()> {
+        for flags in 0..CACHE_KEY_FLAGS_COMBINATIONS {
+            self.cache.del(&cache_key(id, flags)).await?;
+        }
+        Ok(())
+    }
+
     pub async fn set(&self, request: ProjectDataRequest<'_>, data: &ProjectDataResult) {
         let cache_key = build_cache_key(request);
 
@@ -86,10 +94,18 @@ impl ProjectStorage {
     }
 }
 
+/// Amount of the cache key flags combinations per project
+const CACHE_KEY_FLAGS_COMBINATIONS: u8 = 4;
+
 #[inline]
 fn build_cache_key(request: ProjectDataRequest<'_>) -> String {
     let flags = (request.include_limits as u8) | ((request.include_features as u8) << 1);
-    format!("project-data-v3/{}/{}", request.id, flags)
+    cache_key(request.id, flags)
+}
+
+#[inline]
+fn cache_key(id: &str, flags: u8) -> String {
+    format!("project-data-v3/{id}/{flags}")
 }
 
 #[cfg(test)]
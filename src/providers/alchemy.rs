@@ -0,0 +1,103 @@
+use {
+    crate::{
+        error::{RpcError, RpcResult},
+        otel::PropagateTraceContext,
+        providers::{BundlerOpsProvider, SupportedBundlerOps},
+        utils::crypto,
+    },
+    alloy::rpc::json_rpc::Id,
+    async_trait::async_trait,
+    std::sync::Arc,
+};
+
+#[derive(Debug)]
+pub struct AlchemyBundlerProvider {
+    pub api_key: String,
+    http_client: reqwest::Client,
+}
+
+impl AlchemyBundlerProvider {
+    pub fn new(api_key: String) -> Self {
+        Self {
+            api_key,
+            http_client: reqwest::Client::new(),
+        }
+    }
+
+    /// Maps the EVM chain ID to the Alchemy network subdomain
+    fn get_network_name(chain_id: &str) -> Option<&'static str> {
+        match chain_id {
+            "1" => Some("eth-mainnet"),
+            "10" => Some("opt-mainnet"),
+            "137" => Some("polygon-mainnet"),
+            "8453" => Some("base-mainnet"),
+            "42161" => Some("arb-mainnet"),
+            "11155111" => Some("eth-sepolia"),
+            "84532" => Some("base-sepolia"),
+            _ => None,
+        }
+    }
+}
+
+#[async_trait]
+impl BundlerOpsProvider for AlchemyBundlerProvider {
+    async fn bundler_rpc_call(
+        &self,
+        chain_id: &str,
+        id: Id,
+        jsonrpc: Arc<str>,
+        method: &SupportedBundlerOps,
+        params: serde_json::Value,
+    ) -> RpcResult<serde_json::Value> {
+        let network = Self::get_network_name(chain_id)
+            .ok_or_else(|| RpcError::UnsupportedChain(chain_id.to_string()))?;
+        let jsonrpc_request = crypto::JsonRpcRequest {
+            id,
+            jsonrpc,
+            method: self.to_provider_op(method).into(),
+            params,
+        };
+        let url = format!("https://{}.g.alchemy.com/v2/{}", network, self.api_key);
+        let response = self
+            .http_client
+            .post(url)
+            .propagate_trace_context()
+            .json(&jsonrpc_request)
+            .send()
+            .await?
+            .json::<serde_json::Value>()
+            .await?;
+
+        Ok(response)
+    }
+
+    fn to_provider_op(&self, op: &SupportedBundlerOps) -> String {
+        match op {
+            SupportedBundlerOps::EthSendUserOperation => "eth_sendUserOperation".into(),
+            SupportedBundlerOps::EthGetUserOperationReceipt => "eth_getUserOperationReceipt".into(),
+            SupportedBundlerOps::EthGetUserOperationByHash => "eth_getUserOperationByHash".into(),
+            SupportedBundlerOps::EthEstimateUserOperationGas => {
+                "eth_estimateUserOperationGas".into()
+            }
+            SupportedBundlerOps::PmSponsorUserOperation => "pm_sponsorUserOperation".into(),
+            SupportedBundlerOps::PmGetPaymasterData => "pm_getPaymasterData".into(),
+            SupportedBundlerOps::PmGetPaymasterStubData => "pm_getPaymasterStubData".into(),
+            SupportedBundlerOps::PimlicoGetUserOperationGasPrice => {
+                "pimlico_getUserOperationGasPrice".into()
+            }
+        }
+    }
+
+    fn supports_bundler_op(&self, chain_id: &str, op: &SupportedBundlerOps) -> bool {
+        // Alchemy paymaster is using its own Gas Manager API, so only the
+        // bundler operations are supported
+        Self::get_network_name(chain_id).is_some()
+            && matches!(
+                op,
+                SupportedBundlerOps::EthSendUserOperation
+                    | SupportedBundlerOps::EthGetUserOperationReceipt
+                    | SupportedBundlerOps::EthGetUserOperationByHash
+                    | SupportedBundlerOps::EthEstimateUserOperationGas
+            )
+    }
+}
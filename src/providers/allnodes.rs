@@ -1,11 +1,13 @@
 use {
     super::{
-        Provider, ProviderKind, RateLimited, RpcProvider, RpcProviderFactory, RpcQueryParams,
-        RpcWsProvider,
+        http_client::record_http_version, Provider, ProviderKind, RateLimited, RpcProvider,
+        RpcProviderFactory, RpcQueryParams, RpcWsProvider, RpcWsProviderFactory,
     },
     crate::{
         env::AllnodesConfig,
         error::{RpcError, RpcResult},
+        otel::PropagateTraceContext,
+        providers::forwarded_headers::ForwardHeaders,
         ws,
     },
     async_trait::async_trait,
@@ -53,6 +55,7 @@ impl RpcWsProvider for AllnodesWsProvider {
         &self,
         ws: WebSocketUpgrade,
         query_params: RpcQueryParams,
+        session: ws::WsSession,
     ) -> RpcResult<Response> {
         let chain = &self
             .supported_chains
@@ -60,13 +63,14 @@ impl RpcWsProvider for AllnodesWsProvider {
             .ok_or(RpcError::ChainNotFound)?;
 
         let project_id = query_params.project_id;
+        let chain_id = query_params.chain_id;
         let uri = format!("wss://{}.allnodes.me:8546/{}", chain, &self.api_key);
         let (websocket_provider, _) = async_tungstenite::tokio::connect_async(uri)
             .await
             .map_err(|e| RpcError::WebSocketError(e.to_string()))?;
 
         Ok(ws.on_upgrade(move |socket| {
-            ws::proxy(project_id, socket, websocket_provider)
+            ws::proxy(project_id, chain_id, socket, websocket_provider, session)
                 .with_metrics(future_metrics!("ws_proxy_task", "name" => "allnodes"))
         }))
     }
@@ -117,11 +121,14 @@ impl RpcProvider for AllnodesProvider {
         let response = self
             .client
             .post(uri)
+            .propagate_trace_context()
+            .forward_headers()
             .header(reqwest::header::CONTENT_TYPE, "application/json")
             .body(body)
             .send()
             .await?;
         let status = response.status();
+        record_http_version(&self.provider_kind(), response.version());
         let body = response.bytes().await?;
         let mut response = (status, body).into_response();
         response
@@ -133,8 +140,7 @@ impl RpcProvider for AllnodesProvider {
 
 impl RpcProviderFactory<AllnodesConfig> for AllnodesProvider {
     #[tracing::instrument(level = "debug")]
-    fn new(provider_config: &AllnodesConfig) -> Self {
-        let forward_proxy_client = reqwest::Client::new();
+    fn new(provider_config: &AllnodesConfig, http_client: reqwest::Client) -> Self {
         let supported_chains: HashMap<String, String> = provider_config
             .supported_chains
             .iter()
@@ -142,14 +148,14 @@ impl RpcProviderFactory<AllnodesConfig> for AllnodesProvider {
             .collect();
 
         AllnodesProvider {
-            client: forward_proxy_client,
+            client: http_client,
             supported_chains,
             api_key: provider_config.api_key.clone(),
         }
     }
 }
 
-impl RpcProviderFactory<AllnodesConfig> for AllnodesWsProvider {
+impl RpcWsProviderFactory<AllnodesConfig> for AllnodesWsProvider {
     #[tracing::instrument(level = "debug")]
     fn new(provider_config: &AllnodesConfig) -> Self {
         let supported_chains: HashMap<String, String> = provider_config
@@ -1,8 +1,13 @@
 use {
-    super::{Provider, ProviderKind, RateLimited, RpcProvider, RpcProviderFactory},
+    super::{
+        http_client::record_http_version, Provider, ProviderKind, RateLimited, RpcProvider,
+        RpcProviderFactory,
+    },
     crate::{
         env::BaseConfig,
         error::{RpcError, RpcResult},
+        otel::PropagateTraceContext,
+        providers::forwarded_headers::ForwardHeaders,
     },
     async_trait::async_trait,
     axum::{
@@ -55,11 +60,14 @@ impl RpcProvider for BaseProvider {
         let response = self
             .client
             .post(uri)
+            .propagate_trace_context()
+            .forward_headers()
             .header(reqwest::header::CONTENT_TYPE, "application/json")
             .body(body)
             .send()
             .await?;
         let status = response.status();
+        record_http_version(&self.provider_kind(), response.version());
         let body = response.bytes().await?;
         let mut response = (status, body).into_response();
         response
@@ -71,8 +79,7 @@ impl RpcProvider for BaseProvider {
 
 impl RpcProviderFactory<BaseConfig> for BaseProvider {
     #[tracing::instrument(level = "debug")]
-    fn new(provider_config: &BaseConfig) -> Self {
-        let forward_proxy_client = reqwest::Client::new();
+    fn new(provider_config: &BaseConfig, http_client: reqwest::Client) -> Self {
         let supported_chains: HashMap<String, String> = provider_config
             .supported_chains
             .iter()
@@ -80,7 +87,7 @@ impl RpcProviderFactory<BaseConfig> for BaseProvider {
             .collect();
 
         BaseProvider {
-            client: forward_proxy_client,
+            client: http_client,
             supported_chains,
         }
     }
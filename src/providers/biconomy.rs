@@ -0,0 +1,93 @@
+use {
+    crate::{
+        error::RpcResult,
+        otel::PropagateTraceContext,
+        providers::{BundlerOpsProvider, SupportedBundlerOps},
+        utils::crypto,
+    },
+    alloy::rpc::json_rpc::Id,
+    async_trait::async_trait,
+    std::sync::Arc,
+};
+
+#[derive(Debug)]
+pub struct BiconomyProvider {
+    pub api_key: String,
+    pub bundler_api_url: String,
+    pub paymaster_api_url: String,
+    http_client: reqwest::Client,
+}
+
+impl BiconomyProvider {
+    pub fn new(api_key: String) -> Self {
+        Self {
+            api_key,
+            bundler_api_url: "https://bundler.biconomy.io/api/v3".to_string(),
+            paymaster_api_url: "https://paymaster.biconomy.io/api/v2".to_string(),
+            http_client: reqwest::Client::new(),
+        }
+    }
+}
+
+#[async_trait]
+impl BundlerOpsProvider for BiconomyProvider {
+    async fn bundler_rpc_call(
+        &self,
+        chain_id: &str,
+        id: Id,
+        jsonrpc: Arc<str>,
+        method: &SupportedBundlerOps,
+        params: serde_json::Value,
+    ) -> RpcResult<serde_json::Value> {
+        let jsonrpc_request = crypto::JsonRpcRequest {
+            id,
+            jsonrpc,
+            method: self.to_provider_op(method).into(),
+            params,
+        };
+        let base_api_url = match method {
+            SupportedBundlerOps::PmGetPaymasterData
+            | SupportedBundlerOps::PmGetPaymasterStubData => &self.paymaster_api_url,
+            _ => &self.bundler_api_url,
+        };
+        let url = format!("{}/{}/{}", base_api_url, chain_id, self.api_key);
+        let response = self
+            .http_client
+            .post(url)
+            .propagate_trace_context()
+            .json(&jsonrpc_request)
+            .send()
+            .await?
+            .json::<serde_json::Value>()
+            .await?;
+
+        Ok(response)
+    }
+
+    fn to_provider_op(&self, op: &SupportedBundlerOps) -> String {
+        match op {
+            SupportedBundlerOps::EthSendUserOperation => "eth_sendUserOperation".into(),
+            SupportedBundlerOps::EthGetUserOperationReceipt => "eth_getUserOperationReceipt".into(),
+            SupportedBundlerOps::EthGetUserOperationByHash => "eth_getUserOperationByHash".into(),
+            SupportedBundlerOps::EthEstimateUserOperationGas => {
+                "eth_estimateUserOperationGas".into()
+            }
+            SupportedBundlerOps::PmSponsorUserOperation => "pm_sponsorUserOperation".into(),
+            SupportedBundlerOps::PmGetPaymasterData => "pm_getPaymasterData".into(),
+            SupportedBundlerOps::PmGetPaymasterStubData => "pm_getPaymasterStubData".into(),
+            SupportedBundlerOps::PimlicoGetUserOperationGasPrice => {
+                "pimlico_getUserOperationGasPrice".into()
+            }
+        }
+    }
+
+    fn supports_bundler_op(&self, _chain_id: &str, op: &SupportedBundlerOps) -> bool {
+        // Legacy `pm_sponsorUserOperation` and Pimlico-specific gas price
+        // method are not supported by the Biconomy endpoints
+        !matches!(
+            op,
+            SupportedBundlerOps::PmSponsorUserOperation
+                | SupportedBundlerOps::PimlicoGetUserOperationGasPrice
+        )
+    }
+}
@@ -1,8 +1,13 @@
 use {
-    super::{Provider, ProviderKind, RateLimited, RpcProvider, RpcProviderFactory},
+    super::{
+        http_client::record_http_version, Provider, ProviderKind, RateLimited, RpcProvider,
+        RpcProviderFactory,
+    },
     crate::{
         env::CallStaticConfig,
         error::{RpcError, RpcResult},
+        otel::PropagateTraceContext,
+        providers::forwarded_headers::ForwardHeaders,
     },
     async_trait::async_trait,
     axum::{
@@ -55,11 +60,14 @@ impl RpcProvider for CallStaticProvider {
         let response = self
             .client
             .post(uri)
+            .propagate_trace_context()
+            .forward_headers()
             .header(reqwest::header::CONTENT_TYPE, "application/json")
             .body(body)
             .send()
             .await?;
         let status = response.status();
+        record_http_version(&self.provider_kind(), response.version());
         let body = response.bytes().await?;
         let response = (
             status,
@@ -76,8 +84,7 @@ impl RpcProvider for CallStaticProvider {
 
 impl RpcProviderFactory<CallStaticConfig> for CallStaticProvider {
     #[tracing::instrument(level = "debug")]
-    fn new(provider_config: &CallStaticConfig) -> Self {
-        let forward_proxy_client = reqwest::Client::new();
+    fn new(provider_config: &CallStaticConfig, http_client: reqwest::Client) -> Self {
         let supported_chains: HashMap<String, String> = provider_config
             .supported_chains
             .iter()
@@ -85,7 +92,7 @@ impl RpcProviderFactory<CallStaticConfig> for CallStaticProvider {
             .collect();
 
         CallStaticProvider {
-            client: forward_proxy_client,
+            client: http_client,
             supported_chains,
             api_key: provider_config.api_key.clone(),
         }
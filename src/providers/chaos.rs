@@ -0,0 +1,195 @@
+//! Development-only fault injection into the RPC providers calls.
+
+use {
+    super::{Provider, ProviderKind, ProvidersConfig, RateLimited, RpcProvider},
+    crate::error::RpcResult,
+    async_trait::async_trait,
+    axum::response::{IntoResponse, Response},
+    hyper::http::{self, HeaderValue},
+    rand::Rng,
+    std::{io, sync::Arc, time::Duration},
+};
+
+/// Truncated JSON-RPC response returned for the malformed response fault
+const MALFORMED_RESPONSE_BODY: &str = r#"{"jsonrpc":"2.0","id":1,"result":"#;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ChaosConfig {
+    /// Percentage of the calls delayed by the `latency`
+    pub latency_percentage: u8,
+    pub latency: Duration,
+    /// Percentages of the calls failed by the faults, the faults are
+    /// exclusive so the sum is capped at 100
+    pub rate_limit_percentage: u8,
+    pub malformed_response_percentage: u8,
+    pub connection_reset_percentage: u8,
+    /// Providers the faults are injected into, all providers when empty
+    pub providers: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Fault {
+    RateLimit,
+    MalformedResponse,
+    ConnectionReset,
+}
+
+impl ChaosConfig {
+    /// Returns the config when any of the faults is enabled
+    pub fn from_providers_config(config: &ProvidersConfig) -> Option<Self> {
+        let chaos = Self {
+            latency_percentage: config.chaos_latency_percentage.unwrap_or_default(),
+            latency: Duration::from_millis(config.chaos_latency_ms.unwrap_or_default()),
+            rate_limit_percentage: config.chaos_rate_limit_percentage.unwrap_or_default(),
+            malformed_response_percentage: config
+                .chaos_malformed_response_percentage
+                .unwrap_or_default(),
+            connection_reset_percentage: config
+                .chaos_connection_reset_percentage
+                .unwrap_or_default(),
+            providers: config.chaos_providers.clone().unwrap_or_default(),
+        };
+        let enabled = (chaos.latency_percentage > 0 && !chaos.latency.is_zero())
+            || chaos.rate_limit_percentage > 0
+            || chaos.malformed_response_percentage > 0
+            || chaos.connection_reset_percentage > 0;
+        enabled.then_some(chaos)
+    }
+
+    pub fn applies_to(&self, provider_kind: &ProviderKind) -> bool {
+        let provider_kind = provider_kind.to_string();
+        self.providers.is_empty()
+            || self
+                .providers
+                .iter()
+                .any(|provider| provider.eq_ignore_ascii_case(&provider_kind))
+    }
+
+    /// Maps the roll in the `0..100` range to the fault
+    fn fault(&self, roll: u8) -> Option<Fault> {
+        let mut threshold = 0u8;
+        for (percentage, fault) in [
+            (self.rate_limit_percentage, Fault::RateLimit),
+            (self.malformed_response_percentage, Fault::MalformedResponse),
+            (self.connection_reset_percentage, Fault::ConnectionReset),
+        ] {
+            threshold = threshold.saturating_add(percentage);
+            if roll < threshold {
+                return Some(fault);
+            }
+        }
+        None
+    }
+}
+
+/// RPC provider wrapper injecting the configured faults before the call to
+/// the wrapped provider
+#[derive(Debug)]
+pub struct ChaosProvider {
+    inner: Arc<dyn RpcProvider>,
+    config: Arc<ChaosConfig>,
+}
+
+impl ChaosProvider {
+    pub fn new(inner: Arc<dyn RpcProvider>, config: Arc<ChaosConfig>) -> Self {
+        Self { inner, config }
+    }
+}
+
+impl Provider for ChaosProvider {
+    fn supports_caip_chainid(&self, chain_id: &str) -> bool {
+        self.inner.supports_caip_chainid(chain_id)
+    }
+
+    fn supported_caip_chains(&self) -> Vec<String> {
+        self.inner.supported_caip_chains()
+    }
+
+    fn provider_kind(&self) -> ProviderKind {
+        self.inner.provider_kind()
+    }
+}
+
+#[async_trait]
+impl RateLimited for ChaosProvider {
+    async fn is_rate_limited(&self, response: &mut Response) -> bool {
+        response.status() == http::StatusCode::TOO_MANY_REQUESTS
+            || self.inner.is_rate_limited(response).await
+    }
+}
+
+#[async_trait]
+impl RpcProvider for ChaosProvider {
+    async fn proxy(&self, chain_id: &str, body: bytes::Bytes) -> RpcResult<Response> {
+        let (latency_roll, fault_roll) = {
+            let mut rng = rand::thread_rng();
+            (rng.gen_range(0..100), rng.gen_range(0..100))
+        };
+        if latency_roll < self.config.latency_percentage {
+            tokio::time::sleep(self.config.latency).await;
+        }
+
+        match self.config.fault(fault_roll) {
+            Some(Fault::RateLimit) => {
+                Ok((http::StatusCode::TOO_MANY_REQUESTS, "Injected rate limit").into_response())
+            }
+            Some(Fault::MalformedResponse) => {
+                let mut response = (http::StatusCode::OK, MALFORMED_RESPONSE_BODY).into_response();
+                response.headers_mut().insert(
+                    http::header::CONTENT_TYPE,
+                    HeaderValue::from_static("application/json"),
+                );
+                Ok(response)
+            }
+            Some(Fault::ConnectionReset) => Err(anyhow::Error::new(io::Error::new(
+                io::ErrorKind::ConnectionReset,
+                "injected connection reset",
+            ))
+            .into()),
+            None => self.inner.proxy(chain_id, body).await,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fault_by_roll() {
+        let config = ChaosConfig {
+            rate_limit_percentage: 10,
+            malformed_response_percentage: 20,
+            connection_reset_percentage: 30,
+            ..Default::default()
+        };
+        assert_eq!(config.fault(0), Some(Fault::RateLimit));
+        assert_eq!(config.fault(9), Some(Fault::RateLimit));
+        assert_eq!(config.fault(10), Some(Fault::MalformedResponse));
+        assert_eq!(config.fault(29), Some(Fault::MalformedResponse));
+        assert_eq!(config.fault(30), Some(Fault::ConnectionReset));
+        assert_eq!(config.fault(59), Some(Fault::ConnectionReset));
+        assert_eq!(config.fault(60), None);
+        assert_eq!(config.fault(99), None);
+
+        let config = ChaosConfig {
+            rate_limit_percentage: 80,
+            connection_reset_percentage: 80,
+            ..Default::default()
+        };
+        assert_eq!(config.fault(99), Some(Fault::ConnectionReset));
+    }
+
+    #[test]
+    fn applies_to_configured_providers() {
+        let config = ChaosConfig::default();
+        assert!(config.applies_to(&ProviderKind::Pokt));
+
+        let config = ChaosConfig {
+            providers: vec!["pokt".to_owned()],
+            ..Default::default()
+        };
+        assert!(config.applies_to(&ProviderKind::Pokt));
+        assert!(!config.applies_to(&ProviderKind::Quicknode));
+    }
+}
@@ -5,8 +5,9 @@ use {
         handlers::{
             history::{
                 HistoryQueryParams, HistoryResponseBody, HistoryTransaction,
-                HistoryTransactionFungibleInfo, HistoryTransactionMetadata,
-                HistoryTransactionTransfer, HistoryTransactionTransferQuantity,
+                HistoryTransactionFiatAmount, HistoryTransactionFungibleInfo,
+                HistoryTransactionMetadata, HistoryTransactionOnramp, HistoryTransactionTransfer,
+                HistoryTransactionTransferQuantity,
             },
             onramp::{
                 options::{OnRampBuyOptionsParams, OnRampBuyOptionsResponse},
@@ -24,6 +25,9 @@ use {
     url::Url,
 };
 
+/// Onramp provider name of the purchases in the transactions history
+const ONRAMP_PROVIDER_NAME: &str = "coinbase";
+
 #[derive(Debug)]
 pub struct CoinbaseProvider {
     pub provider_kind: ProviderKind,
@@ -86,6 +90,8 @@ pub struct CoinbaseTransaction {
     pub created_at: String,
     pub purchase_network: String,
     pub purchase_amount: CoinbasePurchaseAmount,
+    /// Total fiat amount paid including the fees
+    pub payment_total: Option<CoinbasePurchaseAmount>,
 }
 
 #[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
@@ -144,7 +150,7 @@ impl HistoryProvider for CoinbaseProvider {
                     nonce: 1, // TODO: get nonce from somewhere
                     sent_from: "Coinbase".to_string(),
                     sent_to: address.clone(),
-                    status: f.status,
+                    status: f.status.clone(),
                     application: None,
                     chain: ChainId::to_caip2(&f.purchase_network),
                 },
@@ -153,6 +159,7 @@ impl HistoryProvider for CoinbaseProvider {
                         name: Some(f.purchase_amount.currency.clone()),
                         symbol: Some(f.purchase_amount.currency),
                         icon: None,
+                        address: None,
                     }),
                     direction: "in".to_string(),
                     quantity: HistoryTransactionTransferQuantity {
@@ -162,6 +169,14 @@ impl HistoryProvider for CoinbaseProvider {
                     value: None,
                     price: None,
                 }]),
+                onramp: Some(HistoryTransactionOnramp {
+                    provider: ONRAMP_PROVIDER_NAME.to_string(),
+                    status: f.status,
+                    fiat_amount: f.payment_total.map(|amount| HistoryTransactionFiatAmount {
+                        value: amount.value,
+                        currency: amount.currency,
+                    }),
+                }),
             })
             .collect();
 
@@ -0,0 +1,195 @@
+use {
+    crate::{
+        error::{RpcError, RpcResult},
+        handlers::{fungible_price::FungiblePriceItem, SupportedCurrencies},
+        otel::PropagateTraceContext,
+        providers::{
+            FungiblePriceProvider, PriceResponseBody, ProviderKind, TokenMetadataCacheProvider,
+        },
+        utils::crypto::{CaipNamespaces, NATIVE_ASSET_ADDRESS},
+        Metrics,
+    },
+    async_trait::async_trait,
+    moka::future::Cache,
+    serde::Deserialize,
+    std::{
+        sync::Arc,
+        time::{Duration, SystemTime},
+    },
+    tracing::log::error,
+    url::Url,
+};
+
+const PUBLIC_API_URL: &str = "https://api.coingecko.com/api/v3";
+const PRO_API_URL: &str = "https://pro-api.coingecko.com/api/v3";
+/// Native prices are shared by all requests to stay within the API rate limits
+const PRICE_CACHE_TTL: Duration = Duration::from_secs(60);
+const BITCOIN_MAINNET_CHAIN_ID: &str = "000000000019d6689c085ae165831e93";
+const XRPL_MAINNET_CHAIN_ID: &str = "0";
+
+/// CoinGecko coin ID and the decimals of the mainnet native asset, the testnet
+/// assets have no price
+fn native_asset(namespace: CaipNamespaces, chain_id: &str) -> Option<(&'static str, u8)> {
+    match (namespace, chain_id) {
+        (CaipNamespaces::Sui, "mainnet") => Some(("sui", 9)),
+        (CaipNamespaces::Near, "mainnet") => Some(("near", 24)),
+        (CaipNamespaces::Bip122, BITCOIN_MAINNET_CHAIN_ID) => Some(("bitcoin", 8)),
+        (CaipNamespaces::Xrpl, XRPL_MAINNET_CHAIN_ID) => Some(("ripple", 6)),
+        _ => None,
+    }
+}
+
+/// Native asset prices of the namespace without the token prices provider
+pub struct CoinGeckoProvider {
+    pub provider_kind: ProviderKind,
+    pub namespace: CaipNamespaces,
+    pub api_key: Option<String>,
+    pub base_api_url: String,
+    pub http_client: reqwest::Client,
+    prices: Cache<(String, String), CoinGeckoMarketItem>,
+}
+
+impl CoinGeckoProvider {
+    pub fn new(namespace: CaipNamespaces, api_key: Option<String>) -> Self {
+        let base_api_url = if api_key.is_some() {
+            PRO_API_URL
+        } else {
+            PUBLIC_API_URL
+        };
+        Self {
+            provider_kind: ProviderKind::CoinGecko,
+            namespace,
+            api_key,
+            base_api_url: base_api_url.to_string(),
+            http_client: reqwest::Client::new(),
+            prices: Cache::builder().time_to_live(PRICE_CACHE_TTL).build(),
+        }
+    }
+
+    async fn send_request(&self, url: Url) -> Result<reqwest::Response, reqwest::Error> {
+        let request = self.http_client.get(url).propagate_trace_context();
+        match &self.api_key {
+            Some(api_key) => request.header("x-cg-pro-api-key", api_key).send().await,
+            None => request.send().await,
+        }
+    }
+
+    async fn get_market_item(
+        &self,
+        coin_id: &str,
+        currency: &SupportedCurrencies,
+        metrics: Arc<Metrics>,
+    ) -> RpcResult<CoinGeckoMarketItem> {
+        let cache_key = (coin_id.to_owned(), currency.to_string());
+        if let Some(item) = self.prices.get(&cache_key).await {
+            return Ok(item);
+        }
+
+        let mut url = Url::parse(format!("{}/coins/markets", &self.base_api_url).as_str())
+            .map_err(|_| RpcError::ConversionParseURLError)?;
+        url.query_pairs_mut()
+            .append_pair("vs_currency", &currency.to_string())
+            .append_pair("ids", coin_id);
+
+        let latency_start = SystemTime::now();
+        let response = self.send_request(url).await.map_err(|e| {
+            error!("Error sending request to CoinGecko provider for fungible price: {e:?}");
+            RpcError::FungiblePriceProviderError("Failed to send the price request".to_string())
+        })?;
+        metrics.add_latency_and_status_code_for_provider(
+            &self.provider_kind,
+            response.status().into(),
+            latency_start,
+            Some(self.namespace.to_string()),
+            Some("coins_markets".to_string()),
+        );
+
+        if !response.status().is_success() {
+            error!(
+                "Error on CoinGecko coins markets response. Status is not OK: {:?}",
+                response.status(),
+            );
+            return Err(RpcError::FungiblePriceProviderError(
+                "Token price provider response status is not success".to_string(),
+            ));
+        }
+        let item = response
+            .json::<Vec<CoinGeckoMarketItem>>()
+            .await?
+            .into_iter()
+            .find(|item| item.id == coin_id)
+            .ok_or_else(|| {
+                RpcError::FungiblePriceProviderError(
+                    "Empty price response from the provider".to_string(),
+                )
+            })?;
+        self.prices.insert(cache_key, item.clone()).await;
+        Ok(item)
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CoinGeckoMarketItem {
+    id: String,
+    symbol: String,
+    name: String,
+    image: Option<String>,
+    current_price: Option<f64>,
+}
+
+#[async_trait]
+impl FungiblePriceProvider for CoinGeckoProvider {
+    async fn get_price(
+        &self,
+        chain_id: &str,
+        address: &str,
+        currency: &SupportedCurrencies,
+        _metadata_cache: &Arc<dyn TokenMetadataCacheProvider>,
+        metrics: Arc<Metrics>,
+    ) -> RpcResult<PriceResponseBody> {
+        let caip10_address = format!("{}:{}:{}", self.namespace, chain_id, address);
+        // Only the native assets are priced
+        let (coin_id, decimals) = native_asset(self.namespace, chain_id)
+            .filter(|_| address == NATIVE_ASSET_ADDRESS)
+            .ok_or_else(|| RpcError::AssetNotSupported(caip10_address.clone()))?;
+        let item = self.get_market_item(coin_id, currency, metrics).await?;
+
+        Ok(PriceResponseBody {
+            fungibles: vec![FungiblePriceItem {
+                address: caip10_address,
+                name: item.name,
+                symbol: item.symbol.to_uppercase(),
+                icon_url: item.image.unwrap_or_default(),
+                price: item.current_price.unwrap_or(0.0),
+                decimals,
+            }],
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn native_assets_of_mainnets() {
+        assert_eq!(
+            native_asset(CaipNamespaces::Bip122, BITCOIN_MAINNET_CHAIN_ID),
+            Some(("bitcoin", 8))
+        );
+        assert_eq!(
+            native_asset(CaipNamespaces::Sui, "mainnet"),
+            Some(("sui", 9))
+        );
+        assert_eq!(native_asset(CaipNamespaces::Sui, "testnet"), None);
+        assert_eq!(
+            native_asset(CaipNamespaces::Near, "mainnet"),
+            Some(("near", 24))
+        );
+        assert_eq!(
+            native_asset(CaipNamespaces::Xrpl, XRPL_MAINNET_CHAIN_ID),
+            Some(("ripple", 6))
+        );
+        assert_eq!(native_asset(CaipNamespaces::Eip155, "1"), None);
+    }
+}
@@ -6,9 +6,10 @@ use {
         handlers::balance::{
             BalanceQueryParams, BalanceResponseBody, TokenMetadataCacheItem, H160_EMPTY_ADDRESS,
         },
+        otel::PropagateTraceContext,
         providers::{
             balance::{BalanceItem, BalanceQuantity},
-            ProviderKind, TokenMetadataCacheProvider,
+            proxied_api_url, ProviderKind, TokenMetadataCacheProvider,
         },
         utils::{capitalize_first_letter, crypto},
         Metrics,
@@ -63,12 +64,14 @@ pub struct DuneProvider {
     pub provider_kind: ProviderKind,
     pub api_key: String,
     pub http_client: reqwest::Client,
+    pub api_proxy_url: Option<Url>,
 }
 
 impl DuneProvider {
     async fn send_request(&self, url: Url) -> Result<reqwest::Response, reqwest::Error> {
         self.http_client
-            .get(url)
+            .get(proxied_api_url(url, self.api_proxy_url.as_ref()))
+            .propagate_trace_context()
             .header("X-Sim-Api-Key", self.api_key.clone())
             .send()
             .await
@@ -181,7 +184,11 @@ impl BalanceProvider for DuneProvider {
             crypto::CaipNamespaces::Solana => {
                 self.get_solana_balance(address, metrics.clone()).await?
             }
-            crypto::CaipNamespaces::Ton => {
+            crypto::CaipNamespaces::Ton
+            | crypto::CaipNamespaces::Sui
+            | crypto::CaipNamespaces::Near
+            | crypto::CaipNamespaces::Bip122
+            | crypto::CaipNamespaces::Xrpl => {
                 return Err(RpcError::BalanceProviderError);
             }
         };
@@ -207,8 +214,12 @@ impl BalanceProvider for DuneProvider {
                     crypto::CaipNamespaces::Solana => {
                         format!("{namespace}:5eykt4UsFv8P8NJdTREpY1vzqKqZKvdp")
                     }
-                    crypto::CaipNamespaces::Ton => {
-                        // TON unsupported in Dune balances
+                    crypto::CaipNamespaces::Ton
+                    | crypto::CaipNamespaces::Sui
+                    | crypto::CaipNamespaces::Near
+                    | crypto::CaipNamespaces::Bip122
+                    | crypto::CaipNamespaces::Xrpl => {
+                        // Unsupported in Dune balances
                         return Err(RpcError::BalanceProviderError);
                     }
                 },
@@ -223,8 +234,12 @@ impl BalanceProvider for DuneProvider {
                     crypto::CaipNamespaces::Solana => {
                         format!("{}:{}", caip2_chain_id, crypto::SOLANA_NATIVE_TOKEN_ADDRESS)
                     }
-                    crypto::CaipNamespaces::Ton => {
-                        // Dune does not support TON balances; set empty to be filtered out later
+                    crypto::CaipNamespaces::Ton
+                    | crypto::CaipNamespaces::Sui
+                    | crypto::CaipNamespaces::Near
+                    | crypto::CaipNamespaces::Bip122
+                    | crypto::CaipNamespaces::Xrpl => {
+                        // Dune does not support these balances; set empty to be filtered out later
                         String::new()
                     }
                 }
@@ -319,8 +334,12 @@ impl BalanceProvider for DuneProvider {
                             crypto::CaipNamespaces::Solana => {
                                 Some(crypto::SOLANA_NATIVE_TOKEN_ADDRESS.to_string())
                             }
-                            crypto::CaipNamespaces::Ton => {
-                                // No native mapping for TON in Dune balances
+                            crypto::CaipNamespaces::Ton
+                            | crypto::CaipNamespaces::Sui
+                            | crypto::CaipNamespaces::Near
+                            | crypto::CaipNamespaces::Bip122
+                            | crypto::CaipNamespaces::Xrpl => {
+                                // No native mapping for these namespaces in Dune balances
                                 None
                             }
                         }
@@ -360,6 +379,7 @@ impl BalanceProviderFactory<DuneConfig> for DuneProvider {
             provider_kind: ProviderKind::Dune,
             api_key: provider_config.api_key.clone(),
             http_client,
+            api_proxy_url: provider_config.api_proxy_url.clone(),
         }
     }
 }
@@ -0,0 +1,156 @@
+//! Per-provider allowlist of the request headers forwarded to the RPC providers.
+
+use {
+    super::{ProviderKind, ProvidersConfig},
+    hyper::http::{HeaderMap, HeaderName},
+    std::{collections::HashMap, future::Future, str::FromStr},
+    tracing::log::warn,
+};
+
+/// Headers never forwarded as they carry the credentials or are set by the
+/// providers clients themselves
+const DENIED_HEADERS: [&str; 9] = [
+    "authorization",
+    "proxy-authorization",
+    "cookie",
+    "x-api-key",
+    "host",
+    "connection",
+    "content-length",
+    "content-type",
+    "transfer-encoding",
+];
+
+tokio::task_local! {
+    /// Headers forwarded by the provider call of the current request
+    static FORWARDED_HEADERS: HeaderMap;
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ForwardedHeadersConfig {
+    /// Allowed headers by the lowercase provider kind
+    allowed: HashMap<String, Vec<HeaderName>>,
+}
+
+impl ForwardedHeadersConfig {
+    pub fn from_providers_config(config: &ProvidersConfig) -> Self {
+        Self::parse(
+            config
+                .upstream_forwarded_headers
+                .as_deref()
+                .unwrap_or_default(),
+        )
+    }
+
+    /// Parses the `provider:header` pairs, the invalid and the denied headers
+    /// are skipped
+    fn parse(pairs: &[String]) -> Self {
+        let mut allowed = HashMap::<_, Vec<_>>::new();
+        for pair in pairs {
+            let Some((provider, header)) = pair.split_once(':') else {
+                warn!("Skipping the invalid forwarded header `{pair}`, expected `provider:header`");
+                continue;
+            };
+            let header = match HeaderName::from_str(header.trim()) {
+                Ok(header) if !DENIED_HEADERS.contains(&header.as_str()) => header,
+                _ => {
+                    warn!("Skipping the forwarded header `{pair}` which is invalid or denied");
+                    continue;
+                }
+            };
+            allowed
+                .entry(provider.trim().to_lowercase())
+                .or_default()
+                .push(header);
+        }
+        Self { allowed }
+    }
+
+    /// Inbound headers allowed to be forwarded to the provider
+    pub fn filter(&self, provider_kind: &ProviderKind, headers: &HeaderMap) -> HeaderMap {
+        let mut forwarded = HeaderMap::new();
+        let Some(allowed) = self.allowed.get(&provider_kind.to_string().to_lowercase()) else {
+            return forwarded;
+        };
+        for header in allowed {
+            for value in headers.get_all(header) {
+                forwarded.append(header.clone(), value.clone());
+            }
+        }
+        forwarded
+    }
+}
+
+/// Runs the provider call forwarding the headers by its upstream requests
+pub async fn with_forwarded_headers<F: Future>(headers: HeaderMap, call: F) -> F::Output {
+    FORWARDED_HEADERS.scope(headers, call).await
+}
+
+/// Adds the forwarded headers of the current provider call to the upstream
+/// request, a no-op outside of the provider call
+pub trait ForwardHeaders {
+    fn forward_headers(self) -> Self;
+}
+
+impl ForwardHeaders for reqwest::RequestBuilder {
+    fn forward_headers(self) -> Self {
+        match FORWARDED_HEADERS.try_with(HeaderMap::clone) {
+            Ok(headers) if !headers.is_empty() => self.headers(headers),
+            _ => self,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, hyper::http::HeaderValue};
+
+    #[test]
+    fn filters_allowed_headers() {
+        let config = ForwardedHeadersConfig::parse(&[
+            "Syndica:solana-client".to_owned(),
+            "syndica:Authorization".to_owned(),
+            "Quicknode:x-trace-id".to_owned(),
+            "invalid".to_owned(),
+        ]);
+
+        let mut headers = HeaderMap::new();
+        headers.insert("solana-client", HeaderValue::from_static("js/1.0.0"));
+        headers.insert("authorization", HeaderValue::from_static("Bearer secret"));
+        headers.insert("x-trace-id", HeaderValue::from_static("trace"));
+
+        let forwarded = config.filter(&ProviderKind::Syndica, &headers);
+        assert_eq!(forwarded.len(), 1);
+        assert_eq!(forwarded["solana-client"], "js/1.0.0");
+
+        let forwarded = config.filter(&ProviderKind::Quicknode, &headers);
+        assert_eq!(forwarded.len(), 1);
+        assert_eq!(forwarded["x-trace-id"], "trace");
+
+        assert!(config.filter(&ProviderKind::Pokt, &headers).is_empty());
+    }
+
+    #[tokio::test]
+    async fn forwards_headers_within_call() {
+        let client = reqwest::Client::new();
+        let mut headers = HeaderMap::new();
+        headers.insert("solana-client", HeaderValue::from_static("js/1.0.0"));
+
+        let request = with_forwarded_headers(headers, async {
+            client
+                .post("http://localhost")
+                .forward_headers()
+                .build()
+                .unwrap()
+        })
+        .await;
+        assert_eq!(request.headers()["solana-client"], "js/1.0.0");
+
+        let request = client
+            .post("http://localhost")
+            .forward_headers()
+            .build()
+            .unwrap();
+        assert!(request.headers().is_empty());
+    }
+}
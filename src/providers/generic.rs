@@ -1,11 +1,13 @@
 use {
     super::{
-        Provider, ProviderKind, RateLimited, RpcProvider, RpcProviderFactory, RpcQueryParams,
-        RpcWsProvider,
+        http_client::record_http_version, Provider, ProviderKind, RateLimited, RpcProvider,
+        RpcProviderFactory, RpcQueryParams, RpcWsProvider, RpcWsProviderFactory,
     },
     crate::{
         env::{GenericConfig, ProviderConfig},
         error::{RpcError, RpcResult},
+        otel::PropagateTraceContext,
+        providers::forwarded_headers::ForwardHeaders,
         ws,
     },
     async_trait::async_trait,
@@ -50,6 +52,7 @@ impl RpcWsProvider for GenericWsProvider {
         &self,
         ws: WebSocketUpgrade,
         query_params: RpcQueryParams,
+        session: ws::WsSession,
     ) -> RpcResult<Response> {
         let (websocket_provider, _) =
             async_tungstenite::tokio::connect_async(self.config.provider.url.clone())
@@ -57,8 +60,14 @@ impl RpcWsProvider for GenericWsProvider {
                 .map_err(|e| RpcError::WebSocketError(e.to_string()))?;
 
         Ok(ws.on_upgrade(move |socket| {
-            ws::proxy(query_params.project_id, socket, websocket_provider)
-                .with_metrics(future_metrics!("ws_proxy_task", "name" => "generic"))
+            ws::proxy(
+                query_params.project_id,
+                query_params.chain_id,
+                socket,
+                websocket_provider,
+                session,
+            )
+            .with_metrics(future_metrics!("ws_proxy_task", "name" => "generic"))
         }))
     }
 }
@@ -101,11 +110,14 @@ impl RpcProvider for GenericProvider {
         let response = self
             .client
             .post(self.config.provider.url.clone())
+            .propagate_trace_context()
+            .forward_headers()
             .header(reqwest::header::CONTENT_TYPE, "application/json")
             .body(body)
             .send()
             .await?;
         let status = response.status();
+        record_http_version(&self.provider_kind(), response.version());
         let body = response.bytes().await?;
         let mut response = (status, body).into_response();
         response
@@ -117,17 +129,15 @@ impl RpcProvider for GenericProvider {
 
 impl RpcProviderFactory<GenericConfig> for GenericProvider {
     #[tracing::instrument(level = "debug")]
-    fn new(provider_config: &GenericConfig) -> Self {
-        let forward_proxy_client = reqwest::Client::new();
-
+    fn new(provider_config: &GenericConfig, http_client: reqwest::Client) -> Self {
         Self {
-            client: forward_proxy_client,
+            client: http_client,
             config: provider_config.clone(),
         }
     }
 }
 
-impl RpcProviderFactory<GenericConfig> for GenericWsProvider {
+impl RpcWsProviderFactory<GenericConfig> for GenericWsProvider {
     #[tracing::instrument(level = "debug")]
     fn new(provider_config: &GenericConfig) -> Self {
         Self {
@@ -1,5 +1,8 @@
 use {
-    super::{Provider, ProviderKind, RateLimited, RpcProvider, RpcProviderFactory},
+    super::{
+        http_client::record_http_version, Provider, ProviderKind, RateLimited, RpcProvider,
+        RpcProviderFactory,
+    },
     crate::{
         env::HiroConfig,
         error::{RpcError, RpcResult},
@@ -109,6 +112,7 @@ impl HiroProvider {
             .send()
             .await?;
         let status = response.status();
+        record_http_version(&self.provider_kind(), response.version());
         let body = response.bytes().await?;
 
         if let Ok(response) = serde_json::from_slice::<jsonrpc::Response>(&body) {
@@ -143,6 +147,7 @@ impl HiroProvider {
             .send()
             .await?;
         let status = response.status();
+        record_http_version(&self.provider_kind(), response.version());
         let body = response.bytes().await?;
 
         if let Ok(response) = serde_json::from_slice::<jsonrpc::Response>(&body) {
@@ -189,6 +194,7 @@ impl HiroProvider {
             .send()
             .await?;
         let status = response.status();
+        record_http_version(&self.provider_kind(), response.version());
         let body = response.bytes().await?;
 
         if let Ok(response) = serde_json::from_slice::<jsonrpc::Response>(&body) {
@@ -218,6 +224,7 @@ impl HiroProvider {
 
         let response = self.client.get(uri).send().await?;
         let status = response.status();
+        record_http_version(&self.provider_kind(), response.version());
         let body = response.bytes().await?;
 
         if let Ok(response) = serde_json::from_slice::<jsonrpc::Response>(&body) {
@@ -256,6 +263,7 @@ impl HiroProvider {
             .send()
             .await?;
         let status = response.status();
+        record_http_version(&self.provider_kind(), response.version());
         let body = response.bytes().await?;
 
         if let Ok(response) = serde_json::from_slice::<jsonrpc::Response>(&body) {
@@ -371,8 +379,7 @@ impl RpcProvider for HiroProvider {
 
 impl RpcProviderFactory<HiroConfig> for HiroProvider {
     #[tracing::instrument(level = "debug")]
-    fn new(provider_config: &HiroConfig) -> Self {
-        let forward_proxy_client = reqwest::Client::new();
+    fn new(provider_config: &HiroConfig, http_client: reqwest::Client) -> Self {
         let supported_chains: HashMap<String, String> = provider_config
             .supported_chains
             .iter()
@@ -380,7 +387,7 @@ impl RpcProviderFactory<HiroConfig> for HiroProvider {
             .collect();
 
         HiroProvider {
-            client: forward_proxy_client,
+            client: http_client,
             supported_chains,
         }
     }
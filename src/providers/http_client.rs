@@ -0,0 +1,78 @@
+use {
+    super::{ProviderKind, ProvidersConfig},
+    std::time::Duration,
+    wc::metrics::{counter, StringLabel},
+};
+
+const DEFAULT_POOL_MAX_IDLE_PER_HOST: usize = 64;
+const DEFAULT_POOL_IDLE_TIMEOUT: Duration = Duration::from_secs(90);
+const DEFAULT_CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+const DEFAULT_TCP_KEEPALIVE: Duration = Duration::from_secs(60);
+
+/// Connections settings of the upstream providers HTTP clients
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UpstreamClientConfig {
+    pub pool_max_idle_per_host: usize,
+    pub pool_idle_timeout: Duration,
+    pub connect_timeout: Duration,
+    pub tcp_keepalive: Duration,
+    /// HTTP/2 adaptive flow control window, applies to the connections
+    /// negotiated to HTTP/2 by the ALPN or the prior knowledge
+    pub http2_adaptive_window: bool,
+    /// Providers known to support HTTP/2, connected without the protocol
+    /// negotiation
+    pub http2_prior_knowledge_providers: Vec<String>,
+}
+
+impl UpstreamClientConfig {
+    pub fn from_providers_config(config: &ProvidersConfig) -> Self {
+        Self {
+            pool_max_idle_per_host: config
+                .upstream_pool_max_idle_per_host
+                .unwrap_or(DEFAULT_POOL_MAX_IDLE_PER_HOST),
+            pool_idle_timeout: config
+                .upstream_pool_idle_timeout_secs
+                .map_or(DEFAULT_POOL_IDLE_TIMEOUT, Duration::from_secs),
+            connect_timeout: config
+                .upstream_connect_timeout_ms
+                .map_or(DEFAULT_CONNECT_TIMEOUT, Duration::from_millis),
+            tcp_keepalive: config
+                .upstream_tcp_keepalive_secs
+                .map_or(DEFAULT_TCP_KEEPALIVE, Duration::from_secs),
+            http2_adaptive_window: config.upstream_http2_adaptive_window.unwrap_or(true),
+            http2_prior_knowledge_providers: config
+                .upstream_http2_prior_knowledge_providers
+                .clone()
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Builds the client with its own connections pool for the provider
+    pub fn build(&self, provider_kind: &ProviderKind) -> reqwest::Client {
+        let provider_kind = provider_kind.to_string();
+        let mut builder = reqwest::Client::builder()
+            .pool_max_idle_per_host(self.pool_max_idle_per_host)
+            .pool_idle_timeout(self.pool_idle_timeout)
+            .connect_timeout(self.connect_timeout)
+            .tcp_keepalive(self.tcp_keepalive)
+            .http2_adaptive_window(self.http2_adaptive_window);
+        if self
+            .http2_prior_knowledge_providers
+            .iter()
+            .any(|provider| provider.eq_ignore_ascii_case(&provider_kind))
+        {
+            builder = builder.http2_prior_knowledge();
+        }
+        builder
+            .build()
+            .expect("Failed to build the upstream HTTP client")
+    }
+}
+
+/// Records the HTTP protocol version of the provider response
+pub fn record_http_version(provider_kind: &ProviderKind, version: reqwest::Version) {
+    counter!("provider_http_version_counter",
+        StringLabel<"provider", String> => &provider_kind.to_string(),
+        StringLabel<"version", String> => &format!("{version:?}"))
+    .increment(1);
+}
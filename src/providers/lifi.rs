@@ -2,6 +2,7 @@ use {
     crate::{
         error::{RpcError, RpcResult},
         handlers::{fungible_price::FungiblePriceItem, SupportedCurrencies},
+        otel::PropagateTraceContext,
         providers::{
             FungiblePriceProvider, PriceResponseBody, ProviderKind, TokenMetadataCacheProvider,
         },
@@ -39,11 +40,16 @@ impl LifiProvider {
         if let Some(api_key) = &self.api_key {
             self.http_client
                 .get(url)
+                .propagate_trace_context()
                 .header("x-lifi-api-key", api_key.clone())
                 .send()
                 .await
         } else {
-            self.http_client.get(url).send().await
+            self.http_client
+                .get(url)
+                .propagate_trace_context()
+                .send()
+                .await
         }
     }
 
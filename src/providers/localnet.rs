@@ -0,0 +1,178 @@
+//! Test-only RPC provider of the local EVM node for all EIP-155 chains.
+
+use {
+    super::{Provider, ProviderKind, RateLimited, RpcProvider, RpcProviderFactory},
+    crate::{
+        chain_config::ACTIVE_CONFIG,
+        env::ProviderConfig,
+        error::RpcResult,
+        providers::{Priority, Weight},
+    },
+    async_trait::async_trait,
+    axum::{
+        http::HeaderValue,
+        response::{IntoResponse, Response},
+    },
+    hyper::http,
+    std::collections::HashMap,
+    url::Url,
+};
+
+/// Default chain ID of the anvil node that is not in the supported chains
+const ANVIL_CHAIN_ID: &str = "eip155:31337";
+
+/// Local node URL and the served chains, the chain ID of the requests is not
+/// checked against the node chain ID
+#[derive(Debug, Clone)]
+pub struct LocalnetConfig {
+    pub url: Url,
+    pub chains: Vec<String>,
+}
+
+impl LocalnetConfig {
+    /// Serves all of the supported EIP-155 chains and the anvil chain
+    pub fn new(url: Url) -> Self {
+        let chains = ACTIVE_CONFIG
+            .chains
+            .iter()
+            .map(|chain| chain.caip2.clone())
+            .filter(|chain_id| chain_id.starts_with("eip155:"))
+            .chain(std::iter::once(ANVIL_CHAIN_ID.to_owned()))
+            .collect();
+        Self { url, chains }
+    }
+}
+
+impl ProviderConfig for LocalnetConfig {
+    fn supported_chains(self) -> HashMap<String, (String, Weight)> {
+        self.chains
+            .into_iter()
+            .map(|chain_id| {
+                (
+                    chain_id,
+                    (self.url.to_string(), Weight::new(Priority::Normal).unwrap()),
+                )
+            })
+            .collect()
+    }
+
+    fn supported_ws_chains(self) -> HashMap<String, (String, Weight)> {
+        HashMap::new()
+    }
+
+    fn provider_kind(&self) -> ProviderKind {
+        ProviderKind::Generic("Localnet".to_owned())
+    }
+}
+
+#[derive(Debug)]
+pub struct LocalnetProvider {
+    client: reqwest::Client,
+    config: LocalnetConfig,
+}
+
+impl Provider for LocalnetProvider {
+    fn supports_caip_chainid(&self, chain_id: &str) -> bool {
+        self.config.chains.iter().any(|chain| chain == chain_id)
+    }
+
+    fn supported_caip_chains(&self) -> Vec<String> {
+        self.config.chains.clone()
+    }
+
+    fn provider_kind(&self) -> ProviderKind {
+        self.config.provider_kind()
+    }
+}
+
+#[async_trait]
+impl RateLimited for LocalnetProvider {
+    async fn is_rate_limited(&self, response: &mut Response) -> bool {
+        response.status() == http::StatusCode::TOO_MANY_REQUESTS
+    }
+}
+
+#[async_trait]
+impl RpcProvider for LocalnetProvider {
+    #[tracing::instrument(skip(self, body), fields(provider = %self.provider_kind()), level = "debug")]
+    async fn proxy(&self, _chain_id: &str, body: bytes::Bytes) -> RpcResult<Response> {
+        let response = self
+            .client
+            .post(self.config.url.clone())
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .body(body)
+            .send()
+            .await?;
+        let status = response.status();
+        let body = response.bytes().await?;
+        let mut response = (status, body).into_response();
+        response
+            .headers_mut()
+            .insert("Content-Type", HeaderValue::from_static("application/json"));
+        Ok(response)
+    }
+}
+
+impl RpcProviderFactory<LocalnetConfig> for LocalnetProvider {
+    #[tracing::instrument(level = "debug")]
+    fn new(provider_config: &LocalnetConfig, http_client: reqwest::Client) -> Self {
+        Self {
+            client: http_client,
+            config: provider_config.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn serves_eip155_chains() {
+        let config = LocalnetConfig::new("http://localhost:8545".parse().unwrap());
+        assert!(config
+            .chains
+            .iter()
+            .all(|chain| chain.starts_with("eip155:")));
+        assert!(config.chains.iter().any(|chain| chain == "eip155:11155111"));
+        assert!(config.chains.iter().any(|chain| chain == ANVIL_CHAIN_ID));
+    }
+
+    #[cfg(feature = "test-mock-bundler")]
+    #[tokio::test]
+    async fn proxies_to_local_node() {
+        use {
+            crate::test_helpers::{spawn_blockchain_api_with_params, Params},
+            yttrium::config::Config,
+        };
+
+        let config = Config::local();
+        let url = spawn_blockchain_api_with_params(Params {
+            validate_project_id: false,
+            override_localnet_url: Some(config.endpoints.rpc.base_url.parse().unwrap()),
+            ..Default::default()
+        })
+        .await;
+        let mut endpoint = url.join("/v1").unwrap();
+        endpoint
+            .query_pairs_mut()
+            .append_pair("projectId", "test")
+            .append_pair("chainId", "eip155:11155111");
+
+        let response = reqwest::Client::new()
+            .post(endpoint)
+            .json(&serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": "eth_chainId",
+                "params": [],
+            }))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), http::StatusCode::OK);
+        let response = response.json::<serde_json::Value>().await.unwrap();
+        // The anvil node forks Sepolia
+        assert_eq!(response["result"], "0xaa36a7");
+    }
+}
@@ -1,8 +1,13 @@
 use {
-    super::{Provider, ProviderKind, RateLimited, RpcProvider, RpcProviderFactory},
+    super::{
+        http_client::record_http_version, Provider, ProviderKind, RateLimited, RpcProvider,
+        RpcProviderFactory,
+    },
     crate::{
         env::MantleConfig,
         error::{RpcError, RpcResult},
+        otel::PropagateTraceContext,
+        providers::forwarded_headers::ForwardHeaders,
     },
     async_trait::async_trait,
     axum::{
@@ -52,11 +57,14 @@ impl RpcProvider for MantleProvider {
         let response = self
             .client
             .post(uri)
+            .propagate_trace_context()
+            .forward_headers()
             .header(reqwest::header::CONTENT_TYPE, "application/json")
             .body(body)
             .send()
             .await?;
         let status = response.status();
+        record_http_version(&self.provider_kind(), response.version());
         let body = response.bytes().await?;
         let mut response = (status, body).into_response();
         response
@@ -68,8 +76,7 @@ impl RpcProvider for MantleProvider {
 
 impl RpcProviderFactory<MantleConfig> for MantleProvider {
     #[tracing::instrument(level = "debug")]
-    fn new(provider_config: &MantleConfig) -> Self {
-        let forward_proxy_client = reqwest::Client::new();
+    fn new(provider_config: &MantleConfig, http_client: reqwest::Client) -> Self {
         let supported_chains: HashMap<String, String> = provider_config
             .supported_chains
             .iter()
@@ -77,7 +84,7 @@ impl RpcProviderFactory<MantleConfig> for MantleProvider {
             .collect();
 
         MantleProvider {
-            client: forward_proxy_client,
+            client: http_client,
             supported_chains,
         }
     }
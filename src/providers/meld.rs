@@ -155,6 +155,8 @@ impl MeldProvider {
 pub struct WidgetRequestParams {
     pub session_data: SessionData,
     pub session_type: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub external_session_id: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -357,6 +359,7 @@ impl OnRampMultiProvider for MeldProvider {
                 &WidgetRequestParams {
                     session_type: DEFAULT_SESSION_TYPE.to_string(),
                     session_data: params.session_data,
+                    external_session_id: params.session_id,
                 },
             )
             .await
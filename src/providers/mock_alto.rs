@@ -54,6 +54,7 @@ impl BundlerOpsProvider for MockAltoProvider {
         let bundler_url = match method {
             SupportedBundlerOps::EthSendUserOperation
             | SupportedBundlerOps::EthGetUserOperationReceipt
+            | SupportedBundlerOps::EthGetUserOperationByHash
             | SupportedBundlerOps::EthEstimateUserOperationGas
             | SupportedBundlerOps::PimlicoGetUserOperationGasPrice => self.bundler_url.clone(),
             SupportedBundlerOps::PmSponsorUserOperation
@@ -76,6 +77,7 @@ impl BundlerOpsProvider for MockAltoProvider {
         match op {
             SupportedBundlerOps::EthSendUserOperation => "eth_sendUserOperation".into(),
             SupportedBundlerOps::EthGetUserOperationReceipt => "eth_getUserOperationReceipt".into(),
+            SupportedBundlerOps::EthGetUserOperationByHash => "eth_getUserOperationByHash".into(),
             SupportedBundlerOps::EthEstimateUserOperationGas => {
                 "eth_estimateUserOperationGas".into()
             }
@@ -1,7 +1,14 @@
 use {
-    self::coinbase::CoinbaseProvider,
+    self::{
+        chaos::{ChaosConfig, ChaosProvider},
+        coinbase::CoinbaseProvider,
+        forwarded_headers::ForwardedHeadersConfig,
+        http_client::UpstreamClientConfig,
+        retry_policy::RetryPolicies,
+        weights::{DisabledProviders, LocalAvailability, WeightsHistory},
+    },
     crate::{
-        env::{BalanceProviderConfig, ProviderConfig},
+        env::{BalanceProviderConfig, ChainId, ProviderConfig},
         error::{RpcError, RpcResult},
         handlers::{
             balance::{
@@ -33,10 +40,11 @@ use {
                     QueryParams as OnRampWidgetQueryParams, WidgetResponse as OnRampWidgetResponse,
                 },
             },
-            portfolio::{PortfolioQueryParams, PortfolioResponseBody},
+            portfolio::{PortfolioNfts, PortfolioQueryParams, PortfolioResponseBody},
             RpcQueryParams, SupportedCurrencies,
         },
         utils::crypto::{CaipNamespaces, Erc20FunctionType},
+        ws::WsSession,
         Metrics,
     },
     alloy::{
@@ -45,23 +53,43 @@ use {
     },
     async_trait::async_trait,
     axum::{extract::ws::WebSocketUpgrade, response::Response},
+    chrono::{DateTime, Utc},
     deadpool_redis::Pool,
     hyper::http::HeaderValue,
     mock_alto::{MockAltoProvider, MockAltoUrls},
-    rand::{distributions::WeightedIndex, prelude::Distribution, rngs::OsRng},
+    rand::{distributions::WeightedIndex, prelude::Distribution, rngs::OsRng, seq::IteratorRandom},
     serde::{Deserialize, Serialize},
     serde_json::Value,
     std::{
-        collections::{HashMap, HashSet},
+        collections::{BTreeMap, HashMap, HashSet},
         fmt::{Debug, Display},
         hash::Hash,
         str::FromStr,
         sync::Arc,
+        time::Duration,
     },
     tracing::{debug, error, log::warn},
+    url::Url,
     yttrium::chain_abstraction::api::Transaction,
 };
 
+/// Routes the provider API call through the test-only API proxy when it's set,
+/// the upstream host is kept as the first path segment of the proxied URL
+pub fn proxied_api_url(url: Url, api_proxy_url: Option<&Url>) -> Url {
+    let Some(api_proxy_url) = api_proxy_url else {
+        return url;
+    };
+    let mut proxied = api_proxy_url.clone();
+    proxied.set_path(&format!(
+        "{}/{}{}",
+        api_proxy_url.path().trim_end_matches('/'),
+        url.host_str().unwrap_or_default(),
+        url.path()
+    ));
+    proxied.set_query(url.query());
+    proxied
+}
+
 /// Checks if a JSON-RPC error message indicates common node error
 /// patterns that should be handled specially.
 pub fn is_node_error_rpc_message(error_message: &str) -> bool {
@@ -141,20 +169,27 @@ pub fn is_internal_error_rpc_code(error_code: i32) -> bool {
     (-32099..=-32000).contains(&error_code)
 }
 
+mod alchemy;
 mod allnodes;
 mod arbitrum;
 mod aurora;
 mod base;
+mod biconomy;
 mod binance;
 mod blast;
 mod bungee;
 mod callstatic;
+mod chaos;
 mod coinbase;
+mod coingecko;
 mod drpc;
 mod dune;
+pub mod forwarded_headers;
 pub mod generic;
 mod hiro;
+mod http_client;
 mod lifi;
+pub mod localnet;
 mod mantle;
 mod meld;
 pub mod mock_alto;
@@ -167,6 +202,7 @@ mod pimlico;
 mod pokt;
 mod publicnode;
 mod quicknode;
+mod retry_policy;
 mod rootstock;
 mod solscan;
 mod sui;
@@ -176,22 +212,27 @@ mod therpc;
 mod toncenter;
 mod trongrid;
 mod unichain;
+mod weighted_bundler;
 mod weights;
 mod wemix;
+mod wrapped_native;
 mod xrpl;
 pub mod zerion;
 mod zksync;
 mod zora;
 
 pub use {
+    alchemy::AlchemyBundlerProvider,
     allnodes::{AllnodesProvider, AllnodesWsProvider},
     arbitrum::ArbitrumProvider,
     aurora::AuroraProvider,
     base::BaseProvider,
+    biconomy::BiconomyProvider,
     binance::BinanceProvider,
     blast::BlastProvider,
     bungee::BungeeProvider,
     callstatic::CallStaticProvider,
+    coingecko::CoinGeckoProvider,
     drpc::DrpcProvider,
     dune::DuneProvider,
     generic::GenericProvider,
@@ -206,7 +247,7 @@ pub use {
     one_inch::OneInchProvider,
     pimlico::PimlicoProvider,
     pokt::PoktProvider,
-    publicnode::PublicnodeProvider,
+    publicnode::{PublicnodeProvider, PublicnodeWsProvider},
     quicknode::{QuicknodeProvider, QuicknodeWsProvider},
     rootstock::RootstockProvider,
     solscan::SolScanProvider,
@@ -217,13 +258,21 @@ pub use {
     toncenter::{ToncenterApiProvider, ToncenterBalanceProvider},
     trongrid::TrongridProvider,
     unichain::UnichainProvider,
+    weighted_bundler::WeightedBundlerOpsProvider,
+    weights::{DisabledProvider, WeightsUpdate},
     wemix::WemixProvider,
+    wrapped_native::WrappedNativeConversionProvider,
     xrpl::XrplProvider,
     zerion::ZerionProvider,
     zksync::ZKSyncProvider,
     zora::{ZoraProvider, ZoraWsProvider},
 };
 
+/// The p95 latency is a summary quantile per instance, so averaging it across
+/// the instances and time is an approximation
+const PROVIDERS_LATENCY_QUERY: &str = "avg by (chain_id, provider) \
+    (avg_over_time(http_external_latency_tracker{quantile=\"0.95\", chain_id!=\"\"}[15m]))";
+
 pub type ChainsWeightResolver = HashMap<String, HashMap<ProviderKind, Weight>>;
 pub type NamespacesWeightResolver = HashMap<CaipNamespaces, HashMap<ProviderKind, Weight>>;
 
@@ -249,18 +298,34 @@ pub struct ProvidersConfig {
     pub zerion_api_key: String,
     pub coinbase_api_key: Option<String>,
     pub coinbase_app_id: Option<String>,
+    /// MoonPay widget publishable API key and the secret key signing its URLs
+    pub moonpay_api_key: Option<String>,
+    pub moonpay_secret_key: Option<String>,
+    /// Ramp Network widget host API key and the secret key signing its URLs
+    pub ramp_host_api_key: Option<String>,
+    pub ramp_secret_key: Option<String>,
     pub one_inch_api_key: Option<String>,
     pub one_inch_referrer: Option<String>,
     /// Lifi API key
     pub lifi_api_key: Option<String>,
+    /// CoinGecko Pro API key of the non-EVM native assets prices, the public
+    /// API is used when not set
+    pub coingecko_api_key: Option<String>,
     /// Pimlico API token key
     pub pimlico_api_key: String,
+    /// Biconomy bundler API key, the bundler is used when the key is set
+    pub biconomy_api_key: Option<String>,
+    /// Alchemy bundler API key, the bundler is used when the key is set
+    pub alchemy_api_key: Option<String>,
     /// SolScan API v2 token key
     pub solscan_api_v2_token: String,
     /// Toncenter base URL (e.g., https://toncenter.com)
     pub toncenter_api_url: Option<String>,
     /// Toncenter API key (optional)
     pub toncenter_api_key: Option<String>,
+    /// Esplora indexer base URL of the Bitcoin UTXOs and transactions, e.g.
+    /// https://blockstream.info serving the `/api` and `/testnet/api` paths
+    pub esplora_api_url: Option<String>,
     /// Bungee API key
     pub bungee_api_key: String,
     /// Tenderly API key
@@ -285,6 +350,51 @@ pub struct ProvidersConfig {
     pub blast_api_key: String,
 
     pub override_bundler_urls: Option<MockAltoUrls>,
+    /// Test-only local EVM node, e.g. anvil, replacing the RPC providers and
+    /// serving all of the EIP-155 chains
+    pub override_localnet_url: Option<Url>,
+    /// Test-only proxy of the balance, history and conversion providers API
+    /// calls to record and replay the responses
+    pub override_api_proxy_url: Option<Url>,
+
+    /// Development-only fault injection into the RPC providers calls, the
+    /// percentages of the calls delayed or failed by the injected faults
+    pub chaos_latency_percentage: Option<u8>,
+    pub chaos_latency_ms: Option<u64>,
+    pub chaos_rate_limit_percentage: Option<u8>,
+    pub chaos_malformed_response_percentage: Option<u8>,
+    pub chaos_connection_reset_percentage: Option<u8>,
+    /// Comma-separated providers the faults are injected into, all providers
+    /// when not set
+    pub chaos_providers: Option<Vec<String>>,
+
+    /// Connections pool settings of the RPC providers HTTP clients, each
+    /// provider has its own pool shared by all of its chains
+    pub upstream_pool_max_idle_per_host: Option<usize>,
+    pub upstream_pool_idle_timeout_secs: Option<u64>,
+    pub upstream_connect_timeout_ms: Option<u64>,
+    pub upstream_tcp_keepalive_secs: Option<u64>,
+    /// HTTP/2 adaptive flow control of the RPC providers connections, enabled
+    /// by default
+    pub upstream_http2_adaptive_window: Option<bool>,
+    /// Comma-separated RPC providers connected by HTTP/2 without the protocol
+    /// negotiation
+    pub upstream_http2_prior_knowledge_providers: Option<Vec<String>>,
+    /// Comma-separated `provider:header` pairs of the inbound headers
+    /// forwarded to the RPC providers, no headers are forwarded by default
+    pub upstream_forwarded_headers: Option<Vec<String>>,
+    /// Default timeout, calls including the retries and the first retry
+    /// backoff of the RPC proxy chain requests
+    pub upstream_timeout_ms: Option<u64>,
+    pub upstream_max_attempts: Option<usize>,
+    pub upstream_retry_backoff_ms: Option<u64>,
+    /// Comma-separated `chain_id=timeout_ms:max_attempts:backoff_ms` overrides
+    /// of the defaults by the chain, the empty fields keep the defaults
+    pub upstream_chain_retry_policies: Option<Vec<String>>,
+    /// Percentage of the concurrent chain requests allowed to be retried at
+    /// once and the concurrent retries allowed regardless of the percentage
+    pub upstream_retry_budget_percentage: Option<u8>,
+    pub upstream_retry_budget_min_concurrency: Option<usize>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -293,10 +403,67 @@ pub struct SupportedChains {
     pub ws: HashSet<String>,
 }
 
+/// Current routing state of the RPC provider
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProviderHealth {
+    pub provider: String,
+    /// Ratio of the failed requests within the last weights update window
+    pub error_rate: Option<f64>,
+    pub chains: Vec<ProviderChainHealth>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProviderChainHealth {
+    pub chain_id: String,
+    pub weight: u64,
+    pub error_rate: Option<f64>,
+}
+
+/// Current providers weights by the chain or namespace and the provider
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WeightsSnapshot {
+    pub rpc: BTreeMap<String, BTreeMap<String, u64>>,
+    pub rpc_updated_at: Option<DateTime<Utc>>,
+    /// Balance providers weights are static and never updated
+    pub balance: BTreeMap<String, BTreeMap<String, u64>>,
+    /// RPC weights updates inputs, the latest first
+    pub history: Vec<WeightsUpdate>,
+}
+
+/// Provider weight for the JSON-RPC method by the method weight modifier,
+/// negative and not finite modifiers are ignored
+fn method_weight(weight: u64, modifier: Option<f64>) -> u64 {
+    match modifier {
+        Some(modifier) if modifier.is_finite() && modifier >= 0.0 => {
+            (weight as f64 * modifier).round() as u64
+        }
+        _ => weight,
+    }
+}
+
+fn weight_values(providers: &HashMap<ProviderKind, Weight>) -> BTreeMap<String, u64> {
+    providers
+        .iter()
+        .map(|(provider_kind, weight)| (provider_kind.to_string(), weight.value()))
+        .collect()
+}
+
 pub struct ProviderRepository {
     pub rpc_supported_chains: SupportedChains,
     rpc_providers: HashMap<ProviderKind, Arc<dyn RpcProvider>>,
+    /// Upstream HTTP clients of the RPC providers by the provider kind
+    rpc_http_clients: HashMap<ProviderKind, reqwest::Client>,
+    upstream_client_config: UpstreamClientConfig,
     rpc_weight_resolver: ChainsWeightResolver,
+    /// Weight multipliers of the RPC providers by the JSON-RPC method
+    rpc_method_weight_modifiers: HashMap<ProviderKind, HashMap<String, f64>>,
+    rpc_weights_history: WeightsHistory,
+    rpc_local_availability: LocalAvailability,
+    /// Shared with the reloaded repositories to keep the overrides
+    disabled_providers: Arc<DisabledProviders>,
 
     ws_providers: HashMap<ProviderKind, Arc<dyn RpcWsProvider>>,
     ws_weight_resolver: ChainsWeightResolver,
@@ -313,7 +480,7 @@ pub struct ProviderRepository {
 
     pub conversion_provider: Arc<dyn ConversionProvider>,
     pub fungible_price_providers: HashMap<CaipNamespaces, Arc<dyn FungiblePriceProvider>>,
-    pub bundler_ops_provider: Arc<dyn BundlerOpsProvider>,
+    pub bundler_ops_provider: Arc<WeightedBundlerOpsProvider>,
     pub chain_orchestrator_provider: Arc<dyn ChainOrchestrationProvider>,
     pub simulation_provider: Arc<dyn SimulationProvider>,
 
@@ -321,6 +488,15 @@ pub struct ProviderRepository {
 
     prometheus_client: Option<prometheus_http_query::Client>,
     prometheus_workspace_header: String,
+
+    /// Faults injected into the RPC providers calls when enabled
+    chaos: Option<Arc<ChaosConfig>>,
+
+    /// Inbound headers allowed to be forwarded to the RPC providers
+    pub forwarded_headers: ForwardedHeadersConfig,
+
+    /// Upstream calls timeout and retries of the RPC proxy by the chain
+    pub retry_policies: RetryPolicies,
 }
 
 impl ProviderRepository {
@@ -395,13 +571,19 @@ impl ProviderRepository {
             warn!("ONE_INCH_REFERRER is not set");
         }
 
-        let zerion_provider = Arc::new(ZerionProvider::new(zerion_api_key));
-        let one_inch_provider = Arc::new(OneInchProvider::new(one_inch_api_key, one_inch_referrer));
+        let api_proxy_url = config.override_api_proxy_url.clone();
+        let zerion_provider = Arc::new(ZerionProvider::new(zerion_api_key, api_proxy_url.clone()));
+        let one_inch_provider = Arc::new(OneInchProvider::new(
+            one_inch_api_key,
+            one_inch_referrer,
+            api_proxy_url.clone(),
+        ));
         let lifi_provider = Arc::new(LifiProvider::new(config.lifi_api_key.clone()));
         let portfolio_provider = zerion_provider.clone();
         let solscan_provider = Arc::new(SolScanProvider::new(
             config.solscan_api_v2_token.clone(),
             redis_pool.clone(),
+            api_proxy_url,
         ));
         let toncenter_balance_provider = Arc::new(ToncenterBalanceProvider::new(
             config
@@ -433,18 +615,55 @@ impl ProviderRepository {
             config.meld_api_key.clone(),
         ));
 
-        let bundler_ops_provider: Arc<dyn BundlerOpsProvider> =
-            if let Some(override_bundler_url) = config.override_bundler_urls.clone() {
-                Arc::new(MockAltoProvider::new(override_bundler_url))
-            } else {
-                Arc::new(PimlicoProvider::new(config.pimlico_api_key.clone()))
-            };
+        let mut bundler_ops_provider = WeightedBundlerOpsProvider::new(redis_pool.clone());
+        if let Some(override_bundler_url) = config.override_bundler_urls.clone() {
+            bundler_ops_provider.add_provider(
+                ProviderKind::Generic("MockAlto".to_string()),
+                Arc::new(MockAltoProvider::new(override_bundler_url)),
+                Weight::new(Priority::Normal).expect("Failed to create a bundler weight"),
+            );
+        } else {
+            bundler_ops_provider.add_provider(
+                ProviderKind::Pimlico,
+                Arc::new(PimlicoProvider::new(config.pimlico_api_key.clone())),
+                Weight::new(Priority::High).expect("Failed to create a bundler weight"),
+            );
+            if let Some(biconomy_api_key) = config.biconomy_api_key.clone() {
+                bundler_ops_provider.add_provider(
+                    ProviderKind::Biconomy,
+                    Arc::new(BiconomyProvider::new(biconomy_api_key)),
+                    Weight::new(Priority::Normal).expect("Failed to create a bundler weight"),
+                );
+            }
+            if let Some(alchemy_api_key) = config.alchemy_api_key.clone() {
+                bundler_ops_provider.add_provider(
+                    ProviderKind::Alchemy,
+                    Arc::new(AlchemyBundlerProvider::new(alchemy_api_key)),
+                    Weight::new(Priority::Normal).expect("Failed to create a bundler weight"),
+                );
+            }
+        }
+        let bundler_ops_provider = Arc::new(bundler_ops_provider);
 
         let mut fungible_price_providers: HashMap<CaipNamespaces, Arc<dyn FungiblePriceProvider>> =
             HashMap::new();
         fungible_price_providers.insert(CaipNamespaces::Eip155, one_inch_provider.clone());
         fungible_price_providers.insert(CaipNamespaces::Solana, solscan_provider.clone());
         fungible_price_providers.insert(CaipNamespaces::Rootstock, lifi_provider.clone());
+        for namespace in [
+            CaipNamespaces::Sui,
+            CaipNamespaces::Near,
+            CaipNamespaces::Bip122,
+            CaipNamespaces::Xrpl,
+        ] {
+            fungible_price_providers.insert(
+                namespace,
+                Arc::new(CoinGeckoProvider::new(
+                    namespace,
+                    config.coingecko_api_key.clone(),
+                )),
+            );
+        }
 
         let chain_orchestrator_provider =
             Arc::new(BungeeProvider::new(config.bungee_api_key.clone()));
@@ -457,13 +676,24 @@ impl ProviderRepository {
 
         let token_metadata_cache = Arc::new(TokenMetadataCache::new(redis_pool.clone()));
 
+        let chaos = ChaosConfig::from_providers_config(config).map(Arc::new);
+        if let Some(chaos) = &chaos {
+            warn!("Injecting faults into the RPC providers calls: {chaos:?}");
+        }
+
         Self {
             rpc_supported_chains: SupportedChains {
                 http: HashSet::new(),
                 ws: HashSet::new(),
             },
             rpc_providers: HashMap::new(),
+            rpc_http_clients: HashMap::new(),
+            upstream_client_config: UpstreamClientConfig::from_providers_config(config),
             rpc_weight_resolver: HashMap::new(),
+            rpc_method_weight_modifiers: HashMap::new(),
+            rpc_weights_history: WeightsHistory::default(),
+            rpc_local_availability: LocalAvailability::default(),
+            disabled_providers: Arc::default(),
             ws_providers: HashMap::new(),
             ws_weight_resolver: HashMap::new(),
             balance_supported_namespaces: HashSet::new(),
@@ -476,12 +706,17 @@ impl ProviderRepository {
             coinbase_pay_provider: coinbase_pay_provider.clone(),
             onramp_provider: coinbase_pay_provider,
             onramp_multi_provider: meld_onramp_provider,
-            conversion_provider: one_inch_provider.clone(),
+            conversion_provider: Arc::new(WrappedNativeConversionProvider::new(
+                one_inch_provider.clone(),
+            )),
             fungible_price_providers,
             bundler_ops_provider,
             chain_orchestrator_provider,
             simulation_provider,
             token_metadata_cache,
+            chaos,
+            forwarded_headers: ForwardedHeadersConfig::from_providers_config(config),
+            retry_policies: RetryPolicies::from_providers_config(config),
         }
     }
 
@@ -489,6 +724,7 @@ impl ProviderRepository {
     pub fn get_rpc_provider_for_chain_id(
         &self,
         chain_id: &str,
+        method: Option<&str>,
         max_providers: usize,
     ) -> Result<Vec<Arc<dyn RpcProvider>>, RpcError> {
         let Some(providers) = self.rpc_weight_resolver.get(chain_id) else {
@@ -499,13 +735,25 @@ impl ProviderRepository {
             return Err(RpcError::UnsupportedChain(chain_id.to_string()));
         }
 
-        let weights: Vec<_> = providers
-            .values()
-            .map(|weight| weight.value())
-            .map(|w| w.max(1))
-            .collect();
+        let (keys, weights): (Vec<_>, Vec<_>) = providers
+            .iter()
+            .filter(|(provider_kind, _)| {
+                !self.disabled_providers.is_disabled(provider_kind, chain_id)
+            })
+            .map(|(provider_kind, weight)| {
+                let modifier = method.and_then(|method| {
+                    self.rpc_method_weight_modifiers
+                        .get(provider_kind)?
+                        .get(method)
+                        .copied()
+                });
+                (
+                    provider_kind.clone(),
+                    method_weight(weight.value(), modifier).max(1),
+                )
+            })
+            .unzip();
         let non_zero_weight_providers = weights.iter().filter(|&x| *x > 0).count();
-        let keys = providers.keys().cloned().collect::<Vec<_>>();
 
         match WeightedIndex::new(weights) {
             Ok(mut dist) => {
@@ -665,8 +913,13 @@ impl ProviderRepository {
             return None;
         }
 
-        let weights: Vec<_> = providers.values().map(|weight| weight.value()).collect();
-        let keys = providers.keys().cloned().collect::<Vec<_>>();
+        let (keys, weights): (Vec<_>, Vec<_>) = providers
+            .iter()
+            .filter(|(provider_kind, _)| {
+                !self.disabled_providers.is_disabled(provider_kind, chain_id)
+            })
+            .map(|(provider_kind, weight)| (provider_kind.clone(), weight.value()))
+            .unzip();
         match WeightedIndex::new(weights) {
             Ok(dist) => {
                 let random = dist.sample(&mut OsRng);
@@ -684,7 +937,7 @@ impl ProviderRepository {
     }
 
     pub fn add_ws_provider<
-        T: RpcProviderFactory<C> + RpcWsProvider + 'static,
+        T: RpcWsProviderFactory<C> + RpcWsProvider + 'static,
         C: ProviderConfig,
     >(
         &mut self,
@@ -714,13 +967,31 @@ impl ProviderRepository {
         &mut self,
         provider_config: C,
     ) {
-        let provider = T::new(&provider_config);
-        let arc_provider = Arc::new(provider);
+        let http_client = self
+            .rpc_http_clients
+            .entry(provider_config.provider_kind())
+            .or_insert_with(|| {
+                self.upstream_client_config
+                    .build(&provider_config.provider_kind())
+            })
+            .clone();
+        let provider = T::new(&provider_config, http_client);
+        let mut arc_provider: Arc<dyn RpcProvider> = Arc::new(provider);
+        if let Some(chaos) = &self.chaos {
+            if chaos.applies_to(&provider_config.provider_kind()) {
+                arc_provider = Arc::new(ChaosProvider::new(arc_provider, chaos.clone()));
+            }
+        }
 
         self.rpc_providers
             .insert(provider_config.provider_kind(), arc_provider);
 
         let provider_kind = provider_config.provider_kind();
+        let method_weight_modifiers = provider_config.method_weight_modifiers();
+        if !method_weight_modifiers.is_empty() {
+            self.rpc_method_weight_modifiers
+                .insert(provider_kind.clone(), method_weight_modifiers);
+        }
         let supported_chains = provider_config.supported_chains();
 
         supported_chains
@@ -769,7 +1040,10 @@ impl ProviderRepository {
         debug!("Updating weights");
 
         let Some(prometheus_client) = &self.prometheus_client else {
-            debug!("Prometheus client not configured, skipping weight update");
+            debug!(
+                "Prometheus client not configured, updating weights from the local availability"
+            );
+            self.update_weights_from_local(metrics);
             return;
         };
 
@@ -781,21 +1055,283 @@ impl ProviderRepository {
             return;
         };
 
+        let parsed_latencies = match prometheus_client
+            .query(PROVIDERS_LATENCY_QUERY)
+            .header("host", header_value.clone())
+            .get()
+            .await
+        {
+            Ok(data) => weights::parse_latencies(data),
+            Err(e) => {
+                warn!("Failed to get providers latency from prometheus: {e}");
+                Default::default()
+            }
+        };
+
         match prometheus_client
             .query("round(increase(provider_status_code_counter_total[3h]))")
-            .header("host", header_value)
+            .header("host", header_value.clone())
             .get()
             .await
         {
             Ok(data) => {
                 let parsed_weights = weights::parse_weights(data);
-                weights::update_values(&self.rpc_weight_resolver, parsed_weights);
+                weights::update_values(
+                    &self.rpc_weight_resolver,
+                    parsed_weights.clone(),
+                    &parsed_latencies,
+                );
+                self.apply_disabled_providers();
                 weights::record_values(&self.rpc_weight_resolver, metrics);
+                self.rpc_weights_history
+                    .push(parsed_weights, parsed_latencies);
             }
             Err(e) => {
                 warn!("Failed to update weights from prometheus: {e}");
+                self.update_weights_from_local(metrics);
+            }
+        }
+
+        match prometheus_client
+            .query("round(increase(bundler_status_code_counter_total[3h]))")
+            .header("host", header_value)
+            .get()
+            .await
+        {
+            Ok(data) => {
+                let parsed_weights = weights::parse_weights(data);
+                self.bundler_ops_provider.update_weights(parsed_weights);
+                self.bundler_ops_provider.record_weights(metrics);
+            }
+            Err(e) => {
+                warn!("Failed to update bundlers weights from prometheus: {e}");
+            }
+        }
+    }
+
+    fn update_weights_from_local(&self, metrics: &crate::Metrics) {
+        weights::update_values_from_local(&self.rpc_weight_resolver, &self.rpc_local_availability);
+        self.apply_disabled_providers();
+        weights::record_values(&self.rpc_weight_resolver, metrics);
+    }
+
+    fn apply_disabled_providers(&self) {
+        weights::apply_disabled(&self.rpc_weight_resolver, &self.disabled_providers);
+        weights::apply_disabled(&self.ws_weight_resolver, &self.disabled_providers);
+    }
+
+    /// Disables the RPC provider for all chains or the specific chain, the
+    /// override is kept by the weights updates until the provider is enabled
+    pub fn disable_provider(
+        &self,
+        provider_kind: ProviderKind,
+        chain_id: Option<String>,
+    ) -> Result<(), RpcError> {
+        self.validate_provider_chain(&provider_kind, chain_id.as_deref())?;
+        self.disabled_providers.disable(provider_kind, chain_id);
+        self.apply_disabled_providers();
+        Ok(())
+    }
+
+    /// Enables the disabled RPC provider, the weights are reset to the
+    /// priorities until the next weights update
+    pub fn enable_provider(
+        &self,
+        provider_kind: &ProviderKind,
+        chain_id: Option<&str>,
+    ) -> Result<(), RpcError> {
+        self.validate_provider_chain(provider_kind, chain_id)?;
+        self.disabled_providers.enable(provider_kind, chain_id);
+        for weight_resolver in [&self.rpc_weight_resolver, &self.ws_weight_resolver] {
+            for (provider_chain_id, providers) in weight_resolver {
+                if chain_id.is_some_and(|chain_id| chain_id != provider_chain_id.as_str())
+                    || self
+                        .disabled_providers
+                        .is_disabled(provider_kind, provider_chain_id)
+                {
+                    continue;
+                }
+                if let Some(weight) = providers.get(provider_kind) {
+                    weight.reset();
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn validate_provider_chain(
+        &self,
+        provider_kind: &ProviderKind,
+        chain_id: Option<&str>,
+    ) -> Result<(), RpcError> {
+        if !self.rpc_providers.contains_key(provider_kind)
+            && !self.ws_providers.contains_key(provider_kind)
+        {
+            return Err(RpcError::UnsupportedProvider(provider_kind.to_string()));
+        }
+        if let Some(chain_id) = chain_id {
+            let is_supported = [&self.rpc_weight_resolver, &self.ws_weight_resolver]
+                .iter()
+                .any(|weight_resolver| {
+                    weight_resolver
+                        .get(chain_id)
+                        .is_some_and(|providers| providers.contains_key(provider_kind))
+                });
+            if !is_supported {
+                return Err(RpcError::UnsupportedChain(chain_id.to_owned()));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn disabled_providers(&self) -> Vec<DisabledProvider> {
+        self.disabled_providers.list()
+    }
+
+    /// Keeps the disabled providers overrides of the replaced repository on
+    /// the configuration reload
+    pub fn inherit_disabled_providers(&mut self, previous: &ProviderRepository) {
+        self.disabled_providers = previous.disabled_providers.clone();
+        self.apply_disabled_providers();
+    }
+
+    /// Records the proxied RPC call result for the local weights updates
+    pub fn record_rpc_call(
+        &self,
+        provider_kind: &ProviderKind,
+        chain_id: &str,
+        success: bool,
+        latency: Duration,
+    ) {
+        self.rpc_local_availability
+            .record(provider_kind, chain_id, success, latency);
+    }
+
+    /// Returns the RPC providers with their chains weights and the error rates
+    /// from the last weights update
+    pub fn rpc_providers_health(&self) -> Vec<ProviderHealth> {
+        let availability = self
+            .rpc_weights_history
+            .latest()
+            .map(|update| update.inputs)
+            .unwrap_or_default();
+
+        let mut providers = HashMap::<&ProviderKind, Vec<ProviderChainHealth>>::new();
+        for (chain_id, chain_providers) in &self.rpc_weight_resolver {
+            for (provider_kind, weight) in chain_providers {
+                let error_rate = availability
+                    .get(provider_kind)
+                    .and_then(|(chains, _)| chains.get(&ChainId(chain_id.clone())))
+                    .and_then(|chain_availability| chain_availability.error_rate());
+                providers
+                    .entry(provider_kind)
+                    .or_default()
+                    .push(ProviderChainHealth {
+                        chain_id: chain_id.clone(),
+                        weight: weight.value(),
+                        error_rate,
+                    });
+            }
+        }
+
+        let mut providers = providers
+            .into_iter()
+            .map(|(provider_kind, mut chains)| {
+                chains.sort_by(|a, b| a.chain_id.cmp(&b.chain_id));
+                ProviderHealth {
+                    provider: provider_kind.to_string(),
+                    error_rate: availability
+                        .get(provider_kind)
+                        .and_then(|(_, provider_availability)| provider_availability.error_rate()),
+                    chains,
+                }
+            })
+            .collect::<Vec<_>>();
+        providers.sort_by(|a, b| a.provider.cmp(&b.provider));
+        providers
+    }
+
+    /// Whether any of the namespace chains has an enabled RPC provider that
+    /// did not fail all the calls within the last weights update window
+    pub fn is_rpc_namespace_available(&self, namespace: &str) -> bool {
+        let availability = self
+            .rpc_weights_history
+            .latest()
+            .map(|update| update.inputs)
+            .unwrap_or_default();
+        let chain_prefix = format!("{namespace}:");
+        self.rpc_weight_resolver
+            .iter()
+            .filter(|(chain_id, _)| chain_id.starts_with(&chain_prefix))
+            .any(|(chain_id, providers)| {
+                providers.keys().any(|provider_kind| {
+                    !self.disabled_providers.is_disabled(provider_kind, chain_id)
+                        && availability
+                            .get(provider_kind)
+                            .and_then(|(chains, _)| chains.get(&ChainId(chain_id.clone())))
+                            .and_then(|chain_availability| chain_availability.error_rate())
+                            .is_none_or(|error_rate| error_rate < 1.0)
+                })
+            })
+    }
+
+    /// Returns the random RPC chain of the namespace to sample the providers
+    /// health
+    pub fn sample_rpc_chain(&self, namespace: &str) -> Option<String> {
+        let chain_prefix = format!("{namespace}:");
+        self.rpc_weight_resolver
+            .keys()
+            .filter(|chain_id| chain_id.starts_with(&chain_prefix))
+            .choose(&mut rand::thread_rng())
+            .cloned()
+    }
+
+    /// Returns the RPC providers with one of their supported EVM chains to
+    /// validate the providers API keys by the cheap calls
+    pub fn rpc_providers_eip155_chains(&self) -> Vec<(Arc<dyn RpcProvider>, String)> {
+        let mut chains = HashMap::<&ProviderKind, &String>::new();
+        for (chain_id, chain_providers) in &self.rpc_weight_resolver {
+            if !chain_id.starts_with("eip155:") {
+                continue;
+            }
+            for provider_kind in chain_providers.keys() {
+                chains
+                    .entry(provider_kind)
+                    .and_modify(|chain| *chain = (*chain).min(chain_id))
+                    .or_insert(chain_id);
             }
         }
+        chains
+            .into_iter()
+            .filter_map(|(provider_kind, chain_id)| {
+                let provider = self.rpc_providers.get(provider_kind)?;
+                Some((provider.clone(), chain_id.clone()))
+            })
+            .collect()
+    }
+
+    /// Returns the current RPC and balance weights with the inputs of the
+    /// latest RPC weights updates
+    pub fn weights_snapshot(&self) -> WeightsSnapshot {
+        let rpc = self
+            .rpc_weight_resolver
+            .iter()
+            .map(|(chain_id, providers)| (chain_id.clone(), weight_values(providers)))
+            .collect();
+        let balance = self
+            .balance_weight_resolver
+            .iter()
+            .map(|(namespace, providers)| (namespace.to_string(), weight_values(providers)))
+            .collect();
+        let mut history = self.rpc_weights_history.updates();
+        history.reverse();
+
+        WeightsSnapshot {
+            rpc,
+            rpc_updated_at: history.first().map(|update| update.updated_at),
+            balance,
+            history,
+        }
     }
 
     #[tracing::instrument(skip(self), level = "debug")]
@@ -856,6 +1392,10 @@ pub enum ProviderKind {
     Trongrid,
     Toncenter,
     Xrpl,
+    Pimlico,
+    Biconomy,
+    Alchemy,
+    CoinGecko,
     Generic(String),
 }
 
@@ -902,6 +1442,10 @@ impl Display for ProviderKind {
                 ProviderKind::Trongrid => "Trongrid",
                 ProviderKind::Toncenter => "Toncenter",
                 ProviderKind::Xrpl => "Xrpl",
+                ProviderKind::Pimlico => "Pimlico",
+                ProviderKind::Biconomy => "Biconomy",
+                ProviderKind::Alchemy => "Alchemy",
+                ProviderKind::CoinGecko => "CoinGecko",
                 ProviderKind::Generic(name) => name.as_str(),
             }
         )
@@ -948,6 +1492,10 @@ impl ProviderKind {
             "Trongrid" => Some(Self::Trongrid),
             "Toncenter" => Some(Self::Toncenter),
             "Xrpl" => Some(Self::Xrpl),
+            "Pimlico" => Some(Self::Pimlico),
+            "Biconomy" => Some(Self::Biconomy),
+            "Alchemy" => Some(Self::Alchemy),
+            "CoinGecko" => Some(Self::CoinGecko),
             x => Some(Self::Generic(x.to_string())),
         }
     }
@@ -959,6 +1507,10 @@ pub trait RpcProvider: Provider {
 }
 
 pub trait RpcProviderFactory<T: ProviderConfig>: Provider {
+    fn new(provider_config: &T, http_client: reqwest::Client) -> Self;
+}
+
+pub trait RpcWsProviderFactory<T: ProviderConfig>: Provider {
     fn new(provider_config: &T) -> Self;
 }
 
@@ -968,6 +1520,7 @@ pub trait RpcWsProvider: Provider {
         &self,
         ws: WebSocketUpgrade,
         query_params: RpcQueryParams,
+        session: WsSession,
     ) -> RpcResult<Response>;
 }
 
@@ -1055,6 +1608,17 @@ impl Weight {
         self.value.load(std::sync::atomic::Ordering::SeqCst)
     }
 
+    /// Sets the weight to zero for the disabled providers
+    pub fn disable(&self) {
+        self.value.store(0, std::sync::atomic::Ordering::SeqCst);
+    }
+
+    /// Resets the weight to the priority value until the next weights update
+    pub fn reset(&self) {
+        self.value
+            .store(self.priority.value(), std::sync::atomic::Ordering::SeqCst);
+    }
+
     pub fn update_value(&self, value: u64) {
         self.value.store(
             // Calulate the new value based on the priority, with MAX_PRIORITY/2 being the "normal"
@@ -1106,6 +1670,13 @@ pub trait PortfolioProvider: Send + Sync + Debug {
         params: PortfolioQueryParams,
         metrics: Arc<Metrics>,
     ) -> RpcResult<PortfolioResponseBody>;
+
+    async fn get_nft_holdings(
+        &self,
+        address: String,
+        params: PortfolioQueryParams,
+        metrics: Arc<Metrics>,
+    ) -> RpcResult<PortfolioNfts>;
 }
 
 #[async_trait]
@@ -1223,6 +1794,8 @@ pub trait ConversionProvider: Send + Sync {
 pub enum SupportedBundlerOps {
     #[serde(rename = "eth_getUserOperationReceipt")]
     EthGetUserOperationReceipt,
+    #[serde(rename = "eth_getUserOperationByHash")]
+    EthGetUserOperationByHash,
     #[serde(rename = "eth_sendUserOperation")]
     EthSendUserOperation,
     #[serde(rename = "eth_estimateUserOperationGas")]
@@ -1253,6 +1826,11 @@ pub trait BundlerOpsProvider: Send + Sync + Debug {
 
     /// Maps the operations enum variant to its provider-specific operation string.
     fn to_provider_op(&self, op: &SupportedBundlerOps) -> String;
+
+    /// Whether the operation is supported by the bundler for the chain
+    fn supports_bundler_op(&self, _chain_id: &str, _op: &SupportedBundlerOps) -> bool {
+        true
+    }
 }
 
 /// Provider for the chain orchestrator operations
@@ -1345,18 +1923,54 @@ pub trait TokenMetadataCacheProvider: Send + Sync {
         caip10_token_address: &str,
     ) -> Result<Option<TokenMetadataCacheItem>, RpcError>;
 
+    /// Get the cached metadata for each of the tokens in the same order
+    async fn get_metadata_batch(
+        &self,
+        caip10_token_addresses: &[String],
+    ) -> Result<Vec<Option<TokenMetadataCacheItem>>, RpcError> {
+        let mut items = Vec::with_capacity(caip10_token_addresses.len());
+        for caip10_token_address in caip10_token_addresses {
+            items.push(self.get_metadata(caip10_token_address).await?);
+        }
+        Ok(items)
+    }
+
     /// Save to the cache the metadata for the token
     async fn set_metadata(
         &self,
         caip10_token_address: &str,
         item: &TokenMetadataCacheItem,
     ) -> Result<(), RpcError>;
+
+    /// Delete the cached metadata for the token
+    async fn delete_metadata(&self, caip10_token_address: &str) -> Result<(), RpcError>;
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_proxied_api_url() {
+        let url =
+            Url::parse("https://api.zerion.io/v1/wallets/0x1/positions/?currency=usd").unwrap();
+        assert_eq!(proxied_api_url(url.clone(), None), url);
+        assert_eq!(
+            proxied_api_url(url, Some(&Url::parse("http://127.0.0.1:9000").unwrap())).as_str(),
+            "http://127.0.0.1:9000/api.zerion.io/v1/wallets/0x1/positions/?currency=usd"
+        );
+    }
+
+    #[test]
+    fn test_method_weight() {
+        assert_eq!(method_weight(5000, None), 5000);
+        assert_eq!(method_weight(5000, Some(0.1)), 500);
+        assert_eq!(method_weight(5000, Some(2.5)), 12500);
+        assert_eq!(method_weight(5000, Some(0.0)), 0);
+        assert_eq!(method_weight(5000, Some(-1.0)), 5000);
+        assert_eq!(method_weight(5000, Some(f64::NAN)), 5000);
+    }
+
     #[test]
     fn test_priority_from_str() {
         assert_eq!(Priority::from_str("Max"), Ok(Priority::Max));
@@ -19,9 +19,10 @@ use {
             fungible_price::FungiblePriceItem,
             SupportedCurrencies,
         },
+        otel::PropagateTraceContext,
         providers::{
-            ConversionProvider, FungiblePriceProvider, PriceResponseBody, ProviderKind,
-            TokenMetadataCacheProvider,
+            proxied_api_url, ConversionProvider, FungiblePriceProvider, PriceResponseBody,
+            ProviderKind, TokenMetadataCacheProvider,
         },
         utils::crypto,
         Metrics,
@@ -43,10 +44,11 @@ pub struct OneInchProvider {
     pub referrer: Option<String>,
     pub base_api_url: String,
     pub http_client: reqwest::Client,
+    pub api_proxy_url: Option<Url>,
 }
 
 impl OneInchProvider {
-    pub fn new(api_key: String, referrer: Option<String>) -> Self {
+    pub fn new(api_key: String, referrer: Option<String>, api_proxy_url: Option<Url>) -> Self {
         let base_api_url = "https://api.1inch.dev".to_string();
         let http_client = reqwest::Client::new();
         Self {
@@ -55,12 +57,14 @@ impl OneInchProvider {
             referrer,
             base_api_url,
             http_client,
+            api_proxy_url,
         }
     }
 
     async fn send_request(&self, url: Url) -> Result<reqwest::Response, reqwest::Error> {
         self.http_client
-            .get(url)
+            .get(proxied_api_url(url, self.api_proxy_url.as_ref()))
+            .propagate_trace_context()
             .header("Authorization", format!("Bearer {}", self.api_key))
             .send()
             .await
@@ -237,6 +241,8 @@ struct OneInchTxTransaction {
     data: String,
     gas: usize,
     gas_price: String,
+    #[serde(default)]
+    value: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -645,6 +651,7 @@ impl ConversionProvider for OneInchProvider {
                     gas: (f64::ceil(body.tx.gas as f64 * GAS_ESTIMATION_SLIPPAGE) as usize)
                         .to_string(),
                     gas_price: body.tx.gas_price,
+                    value: body.tx.value,
                 }),
             },
         };
@@ -1,6 +1,7 @@
 use {
     crate::{
         error::RpcResult,
+        otel::PropagateTraceContext,
         providers::{BundlerOpsProvider, SupportedBundlerOps},
         utils::crypto,
     },
@@ -78,6 +79,7 @@ impl BundlerOpsProvider for PimlicoProvider {
         let response = self
             .http_client
             .post(bundler_url.clone())
+            .propagate_trace_context()
             .json(&jsonrpc_send_userop_request)
             .send()
             .await?
@@ -91,6 +93,7 @@ impl BundlerOpsProvider for PimlicoProvider {
         match op {
             SupportedBundlerOps::EthSendUserOperation => "eth_sendUserOperation".into(),
             SupportedBundlerOps::EthGetUserOperationReceipt => "eth_getUserOperationReceipt".into(),
+            SupportedBundlerOps::EthGetUserOperationByHash => "eth_getUserOperationByHash".into(),
             SupportedBundlerOps::EthEstimateUserOperationGas => {
                 "eth_estimateUserOperationGas".into()
             }
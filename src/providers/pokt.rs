@@ -1,8 +1,13 @@
 use {
-    super::{Provider, ProviderKind, RateLimited, RpcProvider, RpcProviderFactory},
+    super::{
+        http_client::record_http_version, Provider, ProviderKind, RateLimited, RpcProvider,
+        RpcProviderFactory,
+    },
     crate::{
         env::PoktConfig,
         error::{RpcError, RpcResult},
+        otel::PropagateTraceContext,
+        providers::forwarded_headers::ForwardHeaders,
     },
     async_trait::async_trait,
     axum::{
@@ -60,11 +65,14 @@ impl RpcProvider for PoktProvider {
         let response = self
             .client
             .post(uri)
+            .propagate_trace_context()
+            .forward_headers()
             .header(reqwest::header::CONTENT_TYPE, "application/json")
             .body(body)
             .send()
             .await?;
         let status = response.status();
+        record_http_version(&self.provider_kind(), response.version());
         let body = response.bytes().await?;
 
         if status.is_success() || status.is_client_error() {
@@ -116,8 +124,7 @@ impl RpcProvider for PoktProvider {
 
 impl RpcProviderFactory<PoktConfig> for PoktProvider {
     #[tracing::instrument(level = "debug")]
-    fn new(provider_config: &PoktConfig) -> Self {
-        let forward_proxy_client = reqwest::Client::new();
+    fn new(provider_config: &PoktConfig, http_client: reqwest::Client) -> Self {
         let supported_chains: HashMap<String, String> = provider_config
             .supported_chains
             .iter()
@@ -125,7 +132,7 @@ impl RpcProviderFactory<PoktConfig> for PoktProvider {
             .collect();
 
         PoktProvider {
-            client: forward_proxy_client,
+            client: http_client,
             project_id: provider_config.project_id.clone(),
             supported_chains,
         }
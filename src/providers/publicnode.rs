@@ -1,16 +1,24 @@
 use {
-    super::{Provider, ProviderKind, RateLimited, RpcProvider, RpcProviderFactory},
+    super::{
+        http_client::record_http_version, Provider, ProviderKind, RateLimited, RpcProvider,
+        RpcProviderFactory, RpcQueryParams, RpcWsProvider, RpcWsProviderFactory,
+    },
     crate::{
         env::PublicnodeConfig,
         error::{RpcError, RpcResult},
+        otel::PropagateTraceContext,
+        providers::forwarded_headers::ForwardHeaders,
+        ws,
     },
     async_trait::async_trait,
     axum::{
+        extract::ws::WebSocketUpgrade,
         http::HeaderValue,
         response::{IntoResponse, Response},
     },
     hyper::http,
     std::collections::HashMap,
+    wc::metrics::{future_metrics, FutureExt},
 };
 
 #[derive(Debug)]
@@ -54,11 +62,14 @@ impl RpcProvider for PublicnodeProvider {
         let response = self
             .client
             .post(uri)
+            .propagate_trace_context()
+            .forward_headers()
             .header(reqwest::header::CONTENT_TYPE, "application/json")
             .body(body)
             .send()
             .await?;
         let status = response.status();
+        record_http_version(&self.provider_kind(), response.version());
         let body = response.bytes().await?;
         let mut response = (status, body).into_response();
         response
@@ -70,8 +81,7 @@ impl RpcProvider for PublicnodeProvider {
 
 impl RpcProviderFactory<PublicnodeConfig> for PublicnodeProvider {
     #[tracing::instrument(level = "debug")]
-    fn new(provider_config: &PublicnodeConfig) -> Self {
-        let forward_proxy_client = reqwest::Client::new();
+    fn new(provider_config: &PublicnodeConfig, http_client: reqwest::Client) -> Self {
         let supported_chains: HashMap<String, String> = provider_config
             .supported_chains
             .iter()
@@ -79,8 +89,75 @@ impl RpcProviderFactory<PublicnodeConfig> for PublicnodeProvider {
             .collect();
 
         PublicnodeProvider {
-            client: forward_proxy_client,
+            client: http_client,
             supported_chains,
         }
     }
 }
+
+#[derive(Debug)]
+pub struct PublicnodeWsProvider {
+    pub supported_chains: HashMap<String, String>,
+}
+
+impl Provider for PublicnodeWsProvider {
+    fn supports_caip_chainid(&self, chain_id: &str) -> bool {
+        self.supported_chains.contains_key(chain_id)
+    }
+
+    fn supported_caip_chains(&self) -> Vec<String> {
+        self.supported_chains.keys().cloned().collect()
+    }
+
+    fn provider_kind(&self) -> ProviderKind {
+        ProviderKind::Publicnode
+    }
+}
+
+#[async_trait]
+impl RateLimited for PublicnodeWsProvider {
+    async fn is_rate_limited(&self, response: &mut Response) -> bool {
+        response.status() == http::StatusCode::TOO_MANY_REQUESTS
+    }
+}
+
+#[async_trait]
+impl RpcWsProvider for PublicnodeWsProvider {
+    #[tracing::instrument(skip_all, fields(provider = %self.provider_kind()), level = "debug")]
+    async fn proxy(
+        &self,
+        ws: WebSocketUpgrade,
+        query_params: RpcQueryParams,
+        session: ws::WsSession,
+    ) -> RpcResult<Response> {
+        let chain = &self
+            .supported_chains
+            .get(&query_params.chain_id)
+            .ok_or(RpcError::ChainNotFound)?;
+
+        let project_id = query_params.project_id;
+        let chain_id = query_params.chain_id;
+        let uri = format!("wss://{chain}.publicnode.com");
+        let (websocket_provider, _) = async_tungstenite::tokio::connect_async(uri)
+            .await
+            .map_err(|e| RpcError::WebSocketError(e.to_string()))?;
+
+        Ok(ws.on_upgrade(move |socket| {
+            ws::proxy(project_id, chain_id, socket, websocket_provider, session)
+                .with_metrics(future_metrics!("ws_proxy_task", "name" => "publicnode"))
+        }))
+    }
+}
+
+impl RpcWsProviderFactory<PublicnodeConfig> for PublicnodeWsProvider {
+    #[tracing::instrument(level = "debug")]
+    fn new(provider_config: &PublicnodeConfig) -> Self {
+        let supported_chains: HashMap<String, String> = provider_config
+            .supported_ws_chains
+            .iter()
+            .map(|(k, v)| (k.clone(), v.0.clone()))
+            .collect();
+
+        PublicnodeWsProvider { supported_chains }
+    }
+}
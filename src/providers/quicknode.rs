@@ -1,12 +1,15 @@
 use {
     super::{
-        Provider, ProviderKind, RateLimited, RpcProvider, RpcProviderFactory, RpcQueryParams,
-        RpcWsProvider, TON_SEND_BOC_METHOD,
+        http_client::record_http_version, Provider, ProviderKind, RateLimited, RpcProvider,
+        RpcProviderFactory, RpcQueryParams, RpcWsProvider, RpcWsProviderFactory,
+        TON_SEND_BOC_METHOD,
     },
     crate::{
         env::QuicknodeConfig,
         error::{RpcError, RpcResult},
         json_rpc::{JsonRpcRequest, JsonRpcResult},
+        otel::PropagateTraceContext,
+        providers::forwarded_headers::ForwardHeaders,
         ws,
     },
     async_trait::async_trait,
@@ -235,6 +238,7 @@ impl QuicknodeProvider {
             .send()
             .await?;
         let status = response.status();
+        record_http_version(&self.provider_kind(), response.version());
         let body = response.bytes().await?;
 
         // Handle the TON API error response which is HTTP 500 with the error structure response
@@ -296,6 +300,7 @@ impl QuicknodeProvider {
             .send()
             .await?;
         let status = response.status();
+        record_http_version(&self.provider_kind(), response.version());
         let body = response.bytes().await?;
 
         // If provider responded with a TON-shaped error body on server error status,
@@ -393,11 +398,14 @@ impl RpcProvider for QuicknodeProvider {
         let response = self
             .client
             .post(uri)
+            .propagate_trace_context()
+            .forward_headers()
             .header(reqwest::header::CONTENT_TYPE, "application/json")
             .body(body)
             .send()
             .await?;
         let status = response.status();
+        record_http_version(&self.provider_kind(), response.version());
         let body = response.bytes().await?;
         let mut response = (status, body).into_response();
         response
@@ -409,8 +417,7 @@ impl RpcProvider for QuicknodeProvider {
 
 impl RpcProviderFactory<QuicknodeConfig> for QuicknodeProvider {
     #[tracing::instrument(level = "debug")]
-    fn new(provider_config: &QuicknodeConfig) -> Self {
-        let forward_proxy_client = reqwest::Client::new();
+    fn new(provider_config: &QuicknodeConfig, http_client: reqwest::Client) -> Self {
         let supported_chains: HashMap<String, String> = provider_config
             .supported_chains
             .iter()
@@ -418,7 +425,7 @@ impl RpcProviderFactory<QuicknodeConfig> for QuicknodeProvider {
             .collect();
 
         QuicknodeProvider {
-            client: forward_proxy_client,
+            client: http_client,
             supported_chains,
             chain_subdomains: provider_config.chain_subdomains.clone(),
         }
@@ -452,6 +459,7 @@ impl RpcWsProvider for QuicknodeWsProvider {
         &self,
         ws: WebSocketUpgrade,
         query_params: RpcQueryParams,
+        session: ws::WsSession,
     ) -> RpcResult<Response> {
         let chain_id = &query_params.chain_id;
         let project_id = query_params.project_id;
@@ -471,8 +479,9 @@ impl RpcWsProvider for QuicknodeWsProvider {
             .await
             .map_err(|e| RpcError::WebSocketError(e.to_string()))?;
 
+        let chain_id = chain_id.clone();
         Ok(ws.on_upgrade(move |socket| {
-            ws::proxy(project_id, socket, websocket_provider)
+            ws::proxy(project_id, chain_id, socket, websocket_provider, session)
                 .with_metrics(future_metrics!("ws_proxy_task", "name" => "quicknode"))
         }))
     }
@@ -485,7 +494,7 @@ impl RateLimited for QuicknodeWsProvider {
     }
 }
 
-impl RpcProviderFactory<QuicknodeConfig> for QuicknodeWsProvider {
+impl RpcWsProviderFactory<QuicknodeConfig> for QuicknodeWsProvider {
     #[tracing::instrument(level = "debug")]
     fn new(provider_config: &QuicknodeConfig) -> Self {
         let supported_chains: HashMap<String, String> = provider_config
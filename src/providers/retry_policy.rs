@@ -0,0 +1,300 @@
+//! Upstream timeouts, retries and retry budget of the RPC proxy by the chain.
+
+use {
+    super::ProvidersConfig,
+    std::{
+        collections::HashMap,
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc, RwLock,
+        },
+        time::Duration,
+    },
+    tracing::log::warn,
+};
+
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+const DEFAULT_MAX_ATTEMPTS: usize = 5;
+const DEFAULT_BACKOFF: Duration = Duration::ZERO;
+/// Backoff doubling is capped by the attempts of the 16x backoff
+const MAX_BACKOFF_EXPONENT: u32 = 4;
+const DEFAULT_RETRY_BUDGET_PERCENTAGE: u8 = 20;
+const DEFAULT_RETRY_BUDGET_MIN_CONCURRENCY: usize = 3;
+
+/// Timeout and retries of the upstream calls of the chain request
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Timeout of each provider call
+    pub timeout: Duration,
+    /// Provider calls of the request including the first one
+    pub max_attempts: usize,
+    /// Delay before the first retry, doubled by each next retry
+    pub backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            timeout: DEFAULT_TIMEOUT,
+            max_attempts: DEFAULT_MAX_ATTEMPTS,
+            backoff: DEFAULT_BACKOFF,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Delay before the retry of the attempt index, the first attempt is not
+    /// delayed
+    pub fn backoff(&self, attempt: usize) -> Duration {
+        if attempt == 0 {
+            return Duration::ZERO;
+        }
+        let exponent = (attempt as u32 - 1).min(MAX_BACKOFF_EXPONENT);
+        self.backoff * 2u32.pow(exponent)
+    }
+
+    /// Parses the `timeout_ms:max_attempts:backoff_ms` overrides of the
+    /// default policy, the empty fields keep the default values
+    fn parse(value: &str, default: &Self) -> Option<Self> {
+        let mut fields = value.split(':').map(str::trim);
+        let mut policy = *default;
+        if let Some(timeout) = fields.next().filter(|field| !field.is_empty()) {
+            policy.timeout = Duration::from_millis(timeout.parse().ok()?);
+        }
+        if let Some(max_attempts) = fields.next().filter(|field| !field.is_empty()) {
+            policy.max_attempts = max_attempts.parse().ok().filter(|max| *max > 0)?;
+        }
+        if let Some(backoff) = fields.next().filter(|field| !field.is_empty()) {
+            policy.backoff = Duration::from_millis(backoff.parse().ok()?);
+        }
+        if fields.next().is_some() {
+            return None;
+        }
+        Some(policy)
+    }
+}
+
+/// Concurrent requests and the concurrent retrying requests of the chain
+#[derive(Debug, Default)]
+struct ChainRetryBudget {
+    requests: AtomicUsize,
+    retries: AtomicUsize,
+}
+
+/// Upstream retry policies by the chain and the chains retry budgets
+#[derive(Debug)]
+pub struct RetryPolicies {
+    default: RetryPolicy,
+    chains: HashMap<String, RetryPolicy>,
+    /// Share of the concurrent requests of the chain allowed to be retried
+    budget_percentage: u8,
+    /// Concurrent retries allowed regardless of the requests share to retry
+    /// the failures of the low traffic chains
+    budget_min_concurrency: usize,
+    budgets: RwLock<HashMap<String, Arc<ChainRetryBudget>>>,
+}
+
+impl RetryPolicies {
+    pub fn from_providers_config(config: &ProvidersConfig) -> Self {
+        let default = RetryPolicy {
+            timeout: config
+                .upstream_timeout_ms
+                .map_or(DEFAULT_TIMEOUT, Duration::from_millis),
+            max_attempts: config
+                .upstream_max_attempts
+                .filter(|max_attempts| *max_attempts > 0)
+                .unwrap_or(DEFAULT_MAX_ATTEMPTS),
+            backoff: config
+                .upstream_retry_backoff_ms
+                .map_or(DEFAULT_BACKOFF, Duration::from_millis),
+        };
+        Self::new(
+            default,
+            config
+                .upstream_chain_retry_policies
+                .as_deref()
+                .unwrap_or_default(),
+            config
+                .upstream_retry_budget_percentage
+                .unwrap_or(DEFAULT_RETRY_BUDGET_PERCENTAGE),
+            config
+                .upstream_retry_budget_min_concurrency
+                .unwrap_or(DEFAULT_RETRY_BUDGET_MIN_CONCURRENCY),
+        )
+    }
+
+    /// Parses the `chain_id=timeout_ms:max_attempts:backoff_ms` chain
+    /// policies, the invalid entries are skipped
+    fn new(
+        default: RetryPolicy,
+        entries: &[String],
+        budget_percentage: u8,
+        budget_min_concurrency: usize,
+    ) -> Self {
+        let mut chains = HashMap::new();
+        for entry in entries {
+            let Some(policy) = entry.rsplit_once('=').and_then(|(chain_id, policy)| {
+                RetryPolicy::parse(policy, &default).map(|policy| (chain_id.trim(), policy))
+            }) else {
+                warn!(
+                    "Skipping the invalid upstream retry policy `{entry}`, expected \
+                     `chain_id=timeout_ms:max_attempts:backoff_ms`"
+                );
+                continue;
+            };
+            chains.insert(policy.0.to_owned(), policy.1);
+        }
+        Self {
+            default,
+            chains,
+            budget_percentage: budget_percentage.min(100),
+            budget_min_concurrency,
+            budgets: RwLock::default(),
+        }
+    }
+
+    /// Retry policy of the chain requests
+    pub fn policy(&self, chain_id: &str) -> RetryPolicy {
+        self.chains.get(chain_id).copied().unwrap_or(self.default)
+    }
+
+    /// Starts the chain request counted by the chain retry budget until the
+    /// guard is dropped
+    pub fn start_request(&self, chain_id: &str) -> RetryBudgetGuard {
+        let budget = self.budget(chain_id);
+        budget.requests.fetch_add(1, Ordering::Relaxed);
+        RetryBudgetGuard {
+            budget,
+            percentage: self.budget_percentage,
+            min_concurrency: self.budget_min_concurrency,
+            retrying: false,
+        }
+    }
+
+    fn budget(&self, chain_id: &str) -> Arc<ChainRetryBudget> {
+        if let Some(budget) = self
+            .budgets
+            .read()
+            .expect("Retry budgets lock is poisoned")
+            .get(chain_id)
+        {
+            return budget.clone();
+        }
+        self.budgets
+            .write()
+            .expect("Retry budgets lock is poisoned")
+            .entry(chain_id.to_owned())
+            .or_default()
+            .clone()
+    }
+}
+
+/// Chain request counted by the retry budget
+#[derive(Debug)]
+pub struct RetryBudgetGuard {
+    budget: Arc<ChainRetryBudget>,
+    percentage: u8,
+    min_concurrency: usize,
+    retrying: bool,
+}
+
+impl RetryBudgetGuard {
+    /// Whether the request is allowed to retry by the chain retry budget, the
+    /// request already retrying keeps its budget share for the next retries
+    pub fn try_retry(&mut self) -> bool {
+        if self.retrying {
+            return true;
+        }
+        let requests = self.budget.requests.load(Ordering::Relaxed);
+        let allowed = (requests * self.percentage as usize / 100).max(self.min_concurrency);
+        self.retrying = self
+            .budget
+            .retries
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |retries| {
+                (retries < allowed).then_some(retries + 1)
+            })
+            .is_ok();
+        self.retrying
+    }
+}
+
+impl Drop for RetryBudgetGuard {
+    fn drop(&mut self) {
+        self.budget.requests.fetch_sub(1, Ordering::Relaxed);
+        if self.retrying {
+            self.budget.retries.fetch_sub(1, Ordering::Relaxed);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_chain_policies() {
+        let default = RetryPolicy::default();
+        let policies = RetryPolicies::new(
+            default,
+            &[
+                "eip155:1=5000:3:100".to_owned(),
+                "eip155:10=:2".to_owned(),
+                "eip155:137=invalid".to_owned(),
+                "eip155:56=1000:0".to_owned(),
+                "invalid".to_owned(),
+            ],
+            20,
+            3,
+        );
+
+        assert_eq!(
+            policies.policy("eip155:1"),
+            RetryPolicy {
+                timeout: Duration::from_millis(5000),
+                max_attempts: 3,
+                backoff: Duration::from_millis(100),
+            }
+        );
+        assert_eq!(
+            policies.policy("eip155:10"),
+            RetryPolicy {
+                max_attempts: 2,
+                ..default
+            }
+        );
+        assert_eq!(policies.policy("eip155:137"), default);
+        assert_eq!(policies.policy("eip155:56"), default);
+    }
+
+    #[test]
+    fn doubles_backoff() {
+        let policy = RetryPolicy {
+            backoff: Duration::from_millis(100),
+            ..Default::default()
+        };
+        assert_eq!(policy.backoff(0), Duration::ZERO);
+        assert_eq!(policy.backoff(1), Duration::from_millis(100));
+        assert_eq!(policy.backoff(3), Duration::from_millis(400));
+        assert_eq!(policy.backoff(10), Duration::from_millis(1600));
+    }
+
+    #[test]
+    fn limits_concurrent_retries() {
+        let policies = RetryPolicies::new(RetryPolicy::default(), &[], 20, 1);
+
+        let mut requests = (0..10)
+            .map(|_| policies.start_request("eip155:1"))
+            .collect::<Vec<_>>();
+        // 20% of the 10 concurrent requests
+        assert!(requests[0].try_retry());
+        assert!(requests[0].try_retry());
+        assert!(requests[1].try_retry());
+        assert!(!requests[2].try_retry());
+        // Budgets are per chain with the minimum concurrency
+        assert!(policies.start_request("eip155:10").try_retry());
+
+        // The finished retrying requests release their budget shares
+        requests.drain(..2);
+        assert!(requests[0].try_retry());
+    }
+}
@@ -1,8 +1,13 @@
 use {
-    super::{Provider, ProviderKind, RateLimited, RpcProvider, RpcProviderFactory},
+    super::{
+        http_client::record_http_version, Provider, ProviderKind, RateLimited, RpcProvider,
+        RpcProviderFactory,
+    },
     crate::{
         env::RootstockConfig,
         error::{RpcError, RpcResult},
+        otel::PropagateTraceContext,
+        providers::forwarded_headers::ForwardHeaders,
     },
     async_trait::async_trait,
     axum::{
@@ -56,11 +61,14 @@ impl RpcProvider for RootstockProvider {
         let response = self
             .client
             .post(uri)
+            .propagate_trace_context()
+            .forward_headers()
             .header(reqwest::header::CONTENT_TYPE, "application/json")
             .body(body)
             .send()
             .await?;
         let status = response.status();
+        record_http_version(&self.provider_kind(), response.version());
         let body = response.bytes().await?;
 
         if let Ok(response) = serde_json::from_slice::<jsonrpc::Response>(&body) {
@@ -82,8 +90,7 @@ impl RpcProvider for RootstockProvider {
 
 impl RpcProviderFactory<RootstockConfig> for RootstockProvider {
     #[tracing::instrument(level = "debug")]
-    fn new(provider_config: &RootstockConfig) -> Self {
-        let forward_proxy_client = reqwest::Client::new();
+    fn new(provider_config: &RootstockConfig, http_client: reqwest::Client) -> Self {
         let supported_chains: HashMap<String, String> = provider_config
             .supported_chains
             .iter()
@@ -91,7 +98,7 @@ impl RpcProviderFactory<RootstockConfig> for RootstockProvider {
             .collect();
 
         RootstockProvider {
-            client: forward_proxy_client,
+            client: http_client,
             supported_chains,
         }
     }
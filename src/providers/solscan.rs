@@ -19,7 +19,10 @@ use {
                 HistoryTransactionURLItem,
             },
         },
-        providers::{BalanceProviderFactory, ProviderKind, TokenMetadataCacheProvider},
+        otel::PropagateTraceContext,
+        providers::{
+            proxied_api_url, BalanceProviderFactory, ProviderKind, TokenMetadataCacheProvider,
+        },
         storage::error::StorageError,
         utils::crypto::{CaipNamespaces, SOLANA_NATIVE_TOKEN_ADDRESS},
         Metrics,
@@ -83,21 +86,28 @@ pub struct SolScanProvider {
     api_v2_token: String,
     http_client: reqwest::Client,
     redis_caching_pool: Option<Arc<Pool>>,
+    api_proxy_url: Option<Url>,
 }
 
 impl SolScanProvider {
-    pub fn new(api_v2_token: String, redis_caching_pool: Option<Arc<Pool>>) -> Self {
+    pub fn new(
+        api_v2_token: String,
+        redis_caching_pool: Option<Arc<Pool>>,
+        api_proxy_url: Option<Url>,
+    ) -> Self {
         Self {
             provider_kind: ProviderKind::SolScan,
             api_v2_token,
             http_client: reqwest::Client::new(),
             redis_caching_pool,
+            api_proxy_url,
         }
     }
 
     async fn send_request_v2(&self, url: Url) -> Result<reqwest::Response, reqwest::Error> {
         self.http_client
-            .get(url)
+            .get(proxied_api_url(url, self.api_proxy_url.as_ref()))
+            .propagate_trace_context()
             .header("token", self.api_v2_token.clone())
             .send()
             .await
@@ -504,6 +514,7 @@ impl BalanceProviderFactory<SolScanConfig> for SolScanProvider {
             api_v2_token: provider_config.api_key.clone(),
             http_client: reqwest::Client::new(),
             redis_caching_pool: cache,
+            api_proxy_url: provider_config.api_proxy_url.clone(),
         }
     }
 }
@@ -586,6 +597,7 @@ impl HistoryProvider for SolScanProvider {
                         icon: Some(HistoryTransactionURLItem {
                             url: token_info.icon.unwrap_or_default(),
                         }),
+                        address: Some(format!("{SOLANA_MAINNET_CHAIN_ID}:{}", item.token_address)),
                     }),
                     nft_info: None,
                     direction: item.flow.to_string(),
@@ -595,6 +607,7 @@ impl HistoryProvider for SolScanProvider {
                     value: Some(decimal_amount * token_info.price),
                     price: Some(token_info.price),
                 }]),
+                onramp: None,
             };
             transactions.push(transaction);
         }
@@ -1,11 +1,13 @@
 use {
     super::{
-        Provider, ProviderKind, RateLimited, RpcProvider, RpcProviderFactory, RpcQueryParams,
-        RpcWsProvider,
+        http_client::record_http_version, Provider, ProviderKind, RateLimited, RpcProvider,
+        RpcProviderFactory, RpcQueryParams, RpcWsProvider, RpcWsProviderFactory,
     },
     crate::{
         env::SyndicaConfig,
         error::{RpcError, RpcResult},
+        otel::PropagateTraceContext,
+        providers::forwarded_headers::ForwardHeaders,
         ws,
     },
     async_trait::async_trait,
@@ -60,11 +62,14 @@ impl RpcProvider for SyndicaProvider {
         let response = self
             .client
             .post(uri)
+            .propagate_trace_context()
+            .forward_headers()
             .header(reqwest::header::CONTENT_TYPE, "application/json")
             .body(body)
             .send()
             .await?;
         let status = response.status();
+        record_http_version(&self.provider_kind(), response.version());
         let body = response.bytes().await?;
 
         if let Ok(response) = serde_json::from_slice::<jsonrpc::Response>(&body) {
@@ -86,8 +91,7 @@ impl RpcProvider for SyndicaProvider {
 
 impl RpcProviderFactory<SyndicaConfig> for SyndicaProvider {
     #[tracing::instrument(level = "debug")]
-    fn new(provider_config: &SyndicaConfig) -> Self {
-        let forward_proxy_client = reqwest::Client::new();
+    fn new(provider_config: &SyndicaConfig, http_client: reqwest::Client) -> Self {
         let supported_chains: HashMap<String, String> = provider_config
             .supported_chains
             .iter()
@@ -95,7 +99,7 @@ impl RpcProviderFactory<SyndicaConfig> for SyndicaProvider {
             .collect();
 
         SyndicaProvider {
-            client: forward_proxy_client,
+            client: http_client,
             supported_chains,
             api_key: provider_config.api_key.clone(),
         }
@@ -129,6 +133,7 @@ impl RpcWsProvider for SyndicaWsProvider {
         &self,
         ws: WebSocketUpgrade,
         query_params: RpcQueryParams,
+        session: ws::WsSession,
     ) -> RpcResult<Response> {
         let base_uri = &self
             .supported_chains
@@ -136,13 +141,14 @@ impl RpcWsProvider for SyndicaWsProvider {
             .ok_or(RpcError::ChainNotFound)?;
 
         let project_id = query_params.project_id;
+        let chain_id = query_params.chain_id;
         let uri = format!("{}/api-key/{}", base_uri, self.api_key);
         let (websocket_provider, _) = async_tungstenite::tokio::connect_async(uri)
             .await
             .map_err(|e| RpcError::WebSocketError(e.to_string()))?;
 
         Ok(ws.on_upgrade(move |socket| {
-            ws::proxy(project_id, socket, websocket_provider)
+            ws::proxy(project_id, chain_id, socket, websocket_provider, session)
                 .with_metrics(future_metrics!("ws_proxy_task", "name" => "syndica"))
         }))
     }
@@ -158,7 +164,7 @@ impl RateLimited for SyndicaWsProvider {
     }
 }
 
-impl RpcProviderFactory<SyndicaConfig> for SyndicaWsProvider {
+impl RpcWsProviderFactory<SyndicaConfig> for SyndicaWsProvider {
     #[tracing::instrument(level = "debug")]
     fn new(provider_config: &SyndicaConfig) -> Self {
         let supported_chains: HashMap<String, String> = provider_config
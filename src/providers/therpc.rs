@@ -1,8 +1,13 @@
 use {
-    super::{Provider, ProviderKind, RateLimited, RpcProvider, RpcProviderFactory},
+    super::{
+        http_client::record_http_version, Provider, ProviderKind, RateLimited, RpcProvider,
+        RpcProviderFactory,
+    },
     crate::{
         env::TheRpcConfig,
         error::{RpcError, RpcResult},
+        otel::PropagateTraceContext,
+        providers::forwarded_headers::ForwardHeaders,
     },
     async_trait::async_trait,
     axum::{
@@ -52,11 +57,14 @@ impl RpcProvider for TheRpcProvider {
         let response = self
             .client
             .post(uri)
+            .propagate_trace_context()
+            .forward_headers()
             .header(reqwest::header::CONTENT_TYPE, "application/json")
             .body(body)
             .send()
             .await?;
         let status = response.status();
+        record_http_version(&self.provider_kind(), response.version());
         let body = response.bytes().await?;
 
         if status.is_success() || status.is_client_error() {
@@ -84,8 +92,7 @@ impl RpcProvider for TheRpcProvider {
 
 impl RpcProviderFactory<TheRpcConfig> for TheRpcProvider {
     #[tracing::instrument(level = "debug")]
-    fn new(provider_config: &TheRpcConfig) -> Self {
-        let forward_proxy_client = reqwest::Client::new();
+    fn new(provider_config: &TheRpcConfig, http_client: reqwest::Client) -> Self {
         let supported_chains: HashMap<String, String> = provider_config
             .supported_chains
             .iter()
@@ -93,7 +100,7 @@ impl RpcProviderFactory<TheRpcConfig> for TheRpcProvider {
             .collect();
 
         TheRpcProvider {
-            client: forward_proxy_client,
+            client: http_client,
             supported_chains,
         }
     }
@@ -1,7 +1,7 @@
 use {
     super::{
-        HistoryProvider, Provider, ProviderKind, RateLimited, RpcProvider, RpcProviderFactory,
-        TokenMetadataCacheProvider, TON_SEND_BOC_METHOD,
+        http_client::record_http_version, HistoryProvider, Provider, ProviderKind, RateLimited,
+        RpcProvider, RpcProviderFactory, TokenMetadataCacheProvider, TON_SEND_BOC_METHOD,
     },
     crate::{
         env::ToncenterV2Config,
@@ -12,6 +12,7 @@ use {
             HistoryTransactionTransferQuantity, HistoryTransactionURLItem,
         },
         json_rpc::{JsonRpcRequest, JsonRpcResult},
+        otel::PropagateTraceContext,
         utils::crypto,
         Metrics,
     },
@@ -98,7 +99,7 @@ impl ToncenterBalanceProvider {
     }
 
     async fn send_request(&self, url: Url) -> Result<reqwest::Response, reqwest::Error> {
-        let mut req = self.http_client.get(url.clone());
+        let mut req = self.http_client.get(url.clone()).propagate_trace_context();
         if let Some(key) = &self.api_key {
             req = req.header("X-Api-Key", key);
         }
@@ -218,6 +219,7 @@ impl HistoryProvider for ToncenterBalanceProvider {
                             icon: Some(HistoryTransactionURLItem {
                                 url: TON_NATIVE_TOKEN_ICON.to_string(),
                             }),
+                            address: None,
                         }),
                         nft_info: None,
                         direction: if to.eq_ignore_ascii_case(&address) {
@@ -256,6 +258,7 @@ impl HistoryProvider for ToncenterBalanceProvider {
                     chain: Some(TON_MAINNET_CHAIN_ID.to_string()),
                 },
                 transfers: transfer_opt.map(|t| vec![t]),
+                onramp: None,
             };
             history.push(tx_item);
         }
@@ -335,7 +338,7 @@ impl ToncenterApiProvider {
             .ok_or_else(|| RpcError::InvalidParameter("boc is not a string".to_string()))?;
 
         let url = self.build_send_boc_url(api_url)?;
-        let mut req = self.http_client.post(url);
+        let mut req = self.http_client.post(url).propagate_trace_context();
         if let Some(key) = &self.api_key {
             req = req.header("X-Api-Key", key);
         }
@@ -448,7 +451,7 @@ impl RpcProvider for ToncenterApiProvider {
 
         let url = self.build_jsonrpc_url(uri)?;
 
-        let mut req = self.http_client.post(url);
+        let mut req = self.http_client.post(url).propagate_trace_context();
         if let Some(key) = &self.api_key {
             req = req.header("X-Api-Key", key);
         }
@@ -459,6 +462,7 @@ impl RpcProvider for ToncenterApiProvider {
             .send()
             .await?;
         let status = response.status();
+        record_http_version(&self.provider_kind(), response.version());
         let body = response.bytes().await?;
 
         let mut response = (status, body).into_response();
@@ -472,7 +476,7 @@ impl RpcProvider for ToncenterApiProvider {
 
 impl RpcProviderFactory<ToncenterV2Config> for ToncenterApiProvider {
     #[tracing::instrument(level = "debug")]
-    fn new(provider_config: &ToncenterV2Config) -> Self {
+    fn new(provider_config: &ToncenterV2Config, http_client: reqwest::Client) -> Self {
         let supported_chains: HashMap<String, String> = provider_config
             .supported_chains
             .iter()
@@ -480,7 +484,7 @@ impl RpcProviderFactory<ToncenterV2Config> for ToncenterApiProvider {
             .collect();
         ToncenterApiProvider {
             api_key: provider_config.api_key.clone(),
-            http_client: reqwest::Client::new(),
+            http_client,
             supported_chains,
         }
     }
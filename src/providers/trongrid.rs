@@ -1,9 +1,14 @@
 use {
-    super::{Provider, ProviderKind, RateLimited, RpcProvider, RpcProviderFactory},
+    super::{
+        http_client::record_http_version, Provider, ProviderKind, RateLimited, RpcProvider,
+        RpcProviderFactory,
+    },
     crate::{
         env::TrongridConfig,
         error::{RpcError, RpcResult},
         json_rpc::JsonRpcRequest,
+        otel::PropagateTraceContext,
+        providers::forwarded_headers::ForwardHeaders,
     },
     async_trait::async_trait,
     axum::{
@@ -225,11 +230,14 @@ impl RpcProvider for TrongridProvider {
         let response = self
             .client
             .post(uri)
+            .propagate_trace_context()
+            .forward_headers()
             .header(reqwest::header::CONTENT_TYPE, "application/json")
             .body(body)
             .send()
             .await?;
         let status = response.status();
+        record_http_version(&self.provider_kind(), response.version());
         let body = response.bytes().await?;
 
         if let Ok(response) = serde_json::from_slice::<jsonrpc::Response>(&body) {
@@ -251,8 +259,7 @@ impl RpcProvider for TrongridProvider {
 
 impl RpcProviderFactory<TrongridConfig> for TrongridProvider {
     #[tracing::instrument(level = "debug")]
-    fn new(provider_config: &TrongridConfig) -> Self {
-        let forward_proxy_client = reqwest::Client::new();
+    fn new(provider_config: &TrongridConfig, http_client: reqwest::Client) -> Self {
         let supported_chains: HashMap<String, String> = provider_config
             .supported_chains
             .iter()
@@ -260,7 +267,7 @@ impl RpcProviderFactory<TrongridConfig> for TrongridProvider {
             .collect();
 
         TrongridProvider {
-            client: forward_proxy_client,
+            client: http_client,
             supported_chains,
         }
     }
@@ -1,8 +1,13 @@
 use {
-    super::{Provider, ProviderKind, RateLimited, RpcProvider, RpcProviderFactory},
+    super::{
+        http_client::record_http_version, Provider, ProviderKind, RateLimited, RpcProvider,
+        RpcProviderFactory,
+    },
     crate::{
         env::UnichainConfig,
         error::{RpcError, RpcResult},
+        otel::PropagateTraceContext,
+        providers::forwarded_headers::ForwardHeaders,
     },
     async_trait::async_trait,
     axum::{
@@ -53,11 +58,14 @@ impl RpcProvider for UnichainProvider {
         let response = self
             .client
             .post(uri)
+            .propagate_trace_context()
+            .forward_headers()
             .header(reqwest::header::CONTENT_TYPE, "application/json")
             .body(body)
             .send()
             .await?;
         let status = response.status();
+        record_http_version(&self.provider_kind(), response.version());
         let body = response.bytes().await?;
 
         if let Ok(response) = serde_json::from_slice::<jsonrpc::Response>(&body) {
@@ -79,8 +87,7 @@ impl RpcProvider for UnichainProvider {
 
 impl RpcProviderFactory<UnichainConfig> for UnichainProvider {
     #[tracing::instrument(level = "debug")]
-    fn new(provider_config: &UnichainConfig) -> Self {
-        let forward_proxy_client = reqwest::Client::new();
+    fn new(provider_config: &UnichainConfig, http_client: reqwest::Client) -> Self {
         let supported_chains: HashMap<String, String> = provider_config
             .supported_chains
             .iter()
@@ -88,7 +95,7 @@ impl RpcProviderFactory<UnichainConfig> for UnichainProvider {
             .collect();
 
         UnichainProvider {
-            client: forward_proxy_client,
+            client: http_client,
             supported_chains,
         }
     }
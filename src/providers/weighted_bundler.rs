@@ -0,0 +1,439 @@
+use {
+    super::{
+        is_internal_error_rpc_code, is_node_error_rpc_message, is_rate_limited_error_rpc_message,
+        weights::{self, ParsedWeights},
+        BundlerOpsProvider, ProviderKind, SupportedBundlerOps, Weight,
+    },
+    crate::{
+        error::{RpcError, RpcResult},
+        storage::error::StorageError,
+        Metrics,
+    },
+    alloy::rpc::json_rpc::Id,
+    async_trait::async_trait,
+    deadpool_redis::{redis::AsyncCommands, Pool},
+    hyper::StatusCode,
+    moka::future::Cache,
+    rand::{
+        distributions::WeightedIndex,
+        prelude::Distribution,
+        rngs::{OsRng, StdRng},
+        RngCore, SeedableRng,
+    },
+    std::{sync::Arc, time::Duration},
+    tracing::{debug, log::warn},
+};
+
+/// Bundler pins TTL covering the userOp flow from the paymaster data to the
+/// receipt lookups
+const PIN_TTL: Duration = Duration::from_secs(60 * 60);
+
+/// Bundler operations provider that is distributing calls between multiple
+/// bundlers according to their weights and failing over to the next bundler
+/// when the selected one is unhealthy. The calls of the same userOp are pinned
+/// to the bundler serving its first call, so the paymaster data, the sending
+/// and the receipt lookups are served by the same bundler.
+#[derive(Debug)]
+pub struct WeightedBundlerOpsProvider {
+    providers: Vec<(ProviderKind, Arc<dyn BundlerOpsProvider>, Weight)>,
+    metrics: Metrics,
+    /// Shared bundler pins, the local pins are used when Redis is not
+    /// configured
+    redis_caching_pool: Option<Arc<Pool>>,
+    local_pins: Cache<String, String>,
+}
+
+impl WeightedBundlerOpsProvider {
+    pub fn new(redis_caching_pool: Option<Arc<Pool>>) -> Self {
+        Self {
+            providers: Vec::new(),
+            metrics: Metrics::new(),
+            redis_caching_pool,
+            local_pins: Cache::builder().time_to_live(PIN_TTL).build(),
+        }
+    }
+
+    pub fn add_provider(
+        &mut self,
+        provider_kind: ProviderKind,
+        provider: Arc<dyn BundlerOpsProvider>,
+        weight: Weight,
+    ) {
+        debug!("Bundler provider added: {}", provider_kind);
+        self.providers.push((provider_kind, provider, weight));
+    }
+
+    /// Update bundlers weights from the parsed bundlers status codes metrics
+    pub fn update_weights(&self, parsed_weights: ParsedWeights) {
+        for (provider_kind, _, weight) in &self.providers {
+            if let Some((_, provider_availability)) = parsed_weights.get(provider_kind) {
+                weight.update_value(weights::calculate_provider_weight(*provider_availability));
+            }
+        }
+    }
+
+    pub fn record_weights(&self, metrics: &Metrics) {
+        for (provider_kind, _, weight) in &self.providers {
+            metrics.record_bundler_weight(provider_kind, weight.value());
+        }
+    }
+
+    /// Returns bundlers that are supporting the operation for the chain ordered
+    /// by the weighted random sampling
+    fn get_ordered_providers(
+        &self,
+        chain_id: &str,
+        op: &SupportedBundlerOps,
+        rng: &mut impl RngCore,
+    ) -> Vec<(&ProviderKind, &Arc<dyn BundlerOpsProvider>)> {
+        let supported = self
+            .providers
+            .iter()
+            .filter(|(_, provider, _)| provider.supports_bundler_op(chain_id, op))
+            .collect::<Vec<_>>();
+
+        // Using the minimal weight of 1 to keep unhealthy bundlers for the
+        // failover at the end of the list
+        let weights = supported
+            .iter()
+            .map(|(_, _, weight)| weight.value().max(1))
+            .collect::<Vec<_>>();
+
+        let mut dist = match WeightedIndex::new(&weights) {
+            Ok(dist) => dist,
+            Err(e) => {
+                warn!("Failed to create bundlers weighted index: {e}");
+                return supported
+                    .into_iter()
+                    .map(|(kind, provider, _)| (kind, provider))
+                    .collect();
+            }
+        };
+
+        let mut ordered = Vec::with_capacity(supported.len());
+        for i in 0..supported.len() {
+            let index = dist.sample(rng);
+            let (kind, provider, _) = supported[index];
+            ordered.push((kind, provider));
+
+            // Remove the sampled bundler from the next sampling, as updating
+            // weights returns an error if all weights are zero
+            if i < supported.len() - 1 {
+                if let Err(e) = dist.update_weights(&[(index, &0)]) {
+                    warn!("Failed to update bundlers weight in sampling iteration: {e}");
+                    break;
+                }
+            }
+        }
+        ordered
+    }
+
+    #[allow(dependency_on_unit_never_type_fallback)]
+    async fn get_pin(&self, key: &str) -> Result<Option<String>, StorageError> {
+        let Some(redis_pool) = &self.redis_caching_pool else {
+            return Ok(self.local_pins.get(key).await);
+        };
+        let mut cache = redis_pool.get().await.map_err(|e| {
+            StorageError::Connection(format!("Error when getting the Redis pool instance {e}"))
+        })?;
+        cache
+            .get(key)
+            .await
+            .map_err(|e| StorageError::Connection(format!("Error when getting cache: {e}")))
+    }
+
+    #[allow(dependency_on_unit_never_type_fallback)]
+    async fn set_pin(&self, key: &str, provider_kind: &ProviderKind) -> Result<(), StorageError> {
+        let Some(redis_pool) = &self.redis_caching_pool else {
+            self.local_pins
+                .insert(key.to_owned(), provider_kind.to_string())
+                .await;
+            return Ok(());
+        };
+        let mut cache = redis_pool.get().await.map_err(|e| {
+            StorageError::Connection(format!("Error when getting the Redis pool instance {e}"))
+        })?;
+        cache
+            .set_ex(key, provider_kind.to_string(), PIN_TTL.as_secs())
+            .await
+            .map_err(|e| StorageError::Connection(format!("Error when seting cache: {e}")))
+    }
+
+    /// Bundlers to call in order. The pinned userOp calls are served by the
+    /// pinned bundler only, the first userOp call bundlers are ordered by the
+    /// userOp sender seeded sampling, so the replicas pick the same bundler.
+    async fn get_call_providers(
+        &self,
+        chain_id: &str,
+        op: &SupportedBundlerOps,
+        pin_key: Option<&str>,
+    ) -> Vec<(&ProviderKind, &Arc<dyn BundlerOpsProvider>)> {
+        let Some(pin_key) = pin_key else {
+            return self.get_ordered_providers(chain_id, op, &mut OsRng);
+        };
+
+        match self.get_pin(pin_key).await {
+            Ok(Some(pinned)) => {
+                let pinned = self
+                    .providers
+                    .iter()
+                    .filter(|(kind, provider, _)| {
+                        kind.to_string() == pinned && provider.supports_bundler_op(chain_id, op)
+                    })
+                    .map(|(kind, provider, _)| (kind, provider))
+                    .collect::<Vec<_>>();
+                if !pinned.is_empty() {
+                    return pinned;
+                }
+            }
+            Ok(None) => {}
+            Err(e) => warn!("Failed to get the bundler pin: {e}"),
+        }
+
+        let seed = sha256::digest(pin_key);
+        let seed = u64::from_str_radix(&seed[..16], 16).unwrap_or_default();
+        self.get_ordered_providers(chain_id, op, &mut StdRng::seed_from_u64(seed))
+    }
+}
+
+/// Key of the bundler pin of the userOp calls. The userOp calls are pinned by
+/// the sender and the lookups by the userOp hash, `None` for the calls not
+/// related to a userOp.
+fn pin_key(
+    chain_id: &str,
+    method: &SupportedBundlerOps,
+    params: &serde_json::Value,
+) -> Option<String> {
+    let value = match method {
+        SupportedBundlerOps::EthSendUserOperation
+        | SupportedBundlerOps::EthEstimateUserOperationGas
+        | SupportedBundlerOps::PmSponsorUserOperation
+        | SupportedBundlerOps::PmGetPaymasterData
+        | SupportedBundlerOps::PmGetPaymasterStubData => params.get(0)?.get("sender")?.as_str()?,
+        SupportedBundlerOps::EthGetUserOperationReceipt
+        | SupportedBundlerOps::EthGetUserOperationByHash => params.get(0)?.as_str()?,
+        SupportedBundlerOps::PimlicoGetUserOperationGasPrice => return None,
+    };
+    Some(user_op_pin_key(chain_id, value))
+}
+
+fn user_op_pin_key(chain_id: &str, sender_or_hash: &str) -> String {
+    format!("bundler_pin/{chain_id}/{}", sender_or_hash.to_lowercase())
+}
+
+/// Checks if the failed call can be retried with the next bundler. The userOp
+/// could be submitted by a bundler failing with an internal error, so the
+/// sending is retried only when it's rejected by the rate limiting.
+fn is_failover_allowed(
+    method: &SupportedBundlerOps,
+    result: &RpcResult<serde_json::Value>,
+) -> bool {
+    if *method != SupportedBundlerOps::EthSendUserOperation {
+        return true;
+    }
+    match result {
+        Ok(response) => response
+            .get("error")
+            .and_then(|error| error.get("message"))
+            .and_then(serde_json::Value::as_str)
+            .is_some_and(is_rate_limited_error_rpc_message),
+        Err(_) => false,
+    }
+}
+
+/// Checks if the bundler JSON-RPC response is indicating a bundler-side issue
+/// and the request should be retried with the next bundler.
+fn is_failover_response(response: &serde_json::Value) -> bool {
+    let Some(error) = response.get("error") else {
+        return false;
+    };
+    let code = error
+        .get("code")
+        .and_then(serde_json::Value::as_i64)
+        .unwrap_or_default() as i32;
+    let message = error
+        .get("message")
+        .and_then(serde_json::Value::as_str)
+        .unwrap_or_default();
+
+    is_internal_error_rpc_code(code)
+        || is_rate_limited_error_rpc_message(message)
+        || is_node_error_rpc_message(message)
+}
+
+#[async_trait]
+impl BundlerOpsProvider for WeightedBundlerOpsProvider {
+    async fn bundler_rpc_call(
+        &self,
+        chain_id: &str,
+        id: Id,
+        jsonrpc: Arc<str>,
+        method: &SupportedBundlerOps,
+        params: serde_json::Value,
+    ) -> RpcResult<serde_json::Value> {
+        let pin_key = pin_key(chain_id, method, &params);
+        let providers = self
+            .get_call_providers(chain_id, method, pin_key.as_deref())
+            .await;
+        if providers.is_empty() {
+            return Err(RpcError::UnsupportedChain(chain_id.to_string()));
+        }
+
+        let mut last_result = None;
+        for (provider_kind, provider) in providers {
+            let result = provider
+                .bundler_rpc_call(
+                    chain_id,
+                    id.clone(),
+                    jsonrpc.clone(),
+                    method,
+                    params.clone(),
+                )
+                .await;
+            let status = match &result {
+                Ok(response) if is_failover_response(response) => StatusCode::SERVICE_UNAVAILABLE,
+                Ok(_) => StatusCode::OK,
+                Err(_) => StatusCode::BAD_GATEWAY,
+            };
+            self.metrics.add_bundler_status_code(
+                provider_kind,
+                status.as_u16(),
+                chain_id.to_string(),
+            );
+
+            if status == StatusCode::OK {
+                if let Some(pin_key) = &pin_key {
+                    if let Err(e) = self.set_pin(pin_key, provider_kind).await {
+                        warn!("Failed to pin the bundler {provider_kind}: {e}");
+                    }
+                }
+                // The receipt lookups are pinned by the sent userOp hash
+                let user_op_hash = result.as_ref().ok().and_then(|response| {
+                    response.get("result").and_then(serde_json::Value::as_str)
+                });
+                if let (SupportedBundlerOps::EthSendUserOperation, Some(user_op_hash)) =
+                    (method, user_op_hash)
+                {
+                    let hash_pin_key = user_op_pin_key(chain_id, user_op_hash);
+                    if let Err(e) = self.set_pin(&hash_pin_key, provider_kind).await {
+                        warn!("Failed to pin the bundler {provider_kind}: {e}");
+                    }
+                }
+                return result;
+            }
+            match &result {
+                Ok(response) => {
+                    warn!("Bundler {provider_kind} responded with an error: {response}")
+                }
+                Err(e) => warn!("Bundler {provider_kind} call failed: {e}"),
+            }
+            if !is_failover_allowed(method, &result) {
+                return result;
+            }
+            last_result = Some(result);
+        }
+
+        last_result.unwrap_or_else(|| Err(RpcError::UnsupportedChain(chain_id.to_string())))
+    }
+
+    fn to_provider_op(&self, op: &SupportedBundlerOps) -> String {
+        match op {
+            SupportedBundlerOps::EthSendUserOperation => "eth_sendUserOperation".into(),
+            SupportedBundlerOps::EthGetUserOperationReceipt => "eth_getUserOperationReceipt".into(),
+            SupportedBundlerOps::EthGetUserOperationByHash => "eth_getUserOperationByHash".into(),
+            SupportedBundlerOps::EthEstimateUserOperationGas => {
+                "eth_estimateUserOperationGas".into()
+            }
+            SupportedBundlerOps::PmSponsorUserOperation => "pm_sponsorUserOperation".into(),
+            SupportedBundlerOps::PmGetPaymasterData => "pm_getPaymasterData".into(),
+            SupportedBundlerOps::PmGetPaymasterStubData => "pm_getPaymasterStubData".into(),
+            SupportedBundlerOps::PimlicoGetUserOperationGasPrice => {
+                "pimlico_getUserOperationGasPrice".into()
+            }
+        }
+    }
+
+    fn supports_bundler_op(&self, chain_id: &str, op: &SupportedBundlerOps) -> bool {
+        self.providers
+            .iter()
+            .any(|(_, provider, _)| provider.supports_bundler_op(chain_id, op))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_failover_response() {
+        assert!(!is_failover_response(&serde_json::json!({
+            "jsonrpc": "2.0", "id": 1, "result": "0x01"
+        })));
+        // ERC-4337 validation errors should be returned to the client
+        assert!(!is_failover_response(&serde_json::json!({
+            "jsonrpc": "2.0", "id": 1,
+            "error": { "code": -32500, "message": "AA21 didn't pay prefund" }
+        })));
+        assert!(is_failover_response(&serde_json::json!({
+            "jsonrpc": "2.0", "id": 1,
+            "error": { "code": -32000, "message": "internal error" }
+        })));
+        assert!(is_failover_response(&serde_json::json!({
+            "jsonrpc": "2.0", "id": 1,
+            "error": { "code": 429, "message": "rate limit exceeded" }
+        })));
+    }
+
+    #[test]
+    fn test_pin_key() {
+        let user_op = serde_json::json!([{ "sender": "0xAbC" }, "0xEntryPoint"]);
+        let expected = Some("bundler_pin/1/0xabc".to_owned());
+        assert_eq!(
+            pin_key("1", &SupportedBundlerOps::PmGetPaymasterStubData, &user_op),
+            expected
+        );
+        assert_eq!(
+            pin_key("1", &SupportedBundlerOps::EthSendUserOperation, &user_op),
+            expected
+        );
+        assert_eq!(
+            pin_key(
+                "1",
+                &SupportedBundlerOps::EthGetUserOperationReceipt,
+                &serde_json::json!(["0xHash"])
+            ),
+            Some("bundler_pin/1/0xhash".to_owned())
+        );
+        assert_eq!(
+            pin_key(
+                "1",
+                &SupportedBundlerOps::PimlicoGetUserOperationGasPrice,
+                &serde_json::json!([])
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn test_is_failover_allowed() {
+        let internal_error = Ok(serde_json::json!({
+            "jsonrpc": "2.0", "id": 1,
+            "error": { "code": -32000, "message": "internal error" }
+        }));
+        let rate_limited = Ok(serde_json::json!({
+            "jsonrpc": "2.0", "id": 1,
+            "error": { "code": 429, "message": "rate limit exceeded" }
+        }));
+        let send = SupportedBundlerOps::EthSendUserOperation;
+        assert!(!is_failover_allowed(&send, &internal_error));
+        assert!(!is_failover_allowed(
+            &send,
+            &Err(RpcError::UnsupportedChain("1".to_owned()))
+        ));
+        assert!(is_failover_allowed(&send, &rate_limited));
+        assert!(is_failover_allowed(
+            &SupportedBundlerOps::PmGetPaymasterStubData,
+            &internal_error
+        ));
+    }
+}
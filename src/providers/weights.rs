@@ -1,19 +1,312 @@
 use {
     super::{ChainsWeightResolver, ProviderKind, WEIGHT_RECALCULATION_EXCLUDED_PROVIDERS},
     crate::env::ChainId,
+    chrono::{DateTime, Utc},
     prometheus_http_query::response::PromqlResult,
-    std::collections::HashMap,
+    serde::{Serialize, Serializer},
+    std::{
+        collections::{BTreeMap, HashMap, HashSet, VecDeque},
+        sync::{Mutex, RwLock},
+        time::Duration,
+    },
     tracing::{debug, log::warn},
 };
 
+/// Amount of the latest weights updates kept for the inspection
+const WEIGHTS_HISTORY_SIZE: usize = 20;
+
+/// Providers with the p95 latency below this value are not deprioritized
+const SLOW_LATENCY_SECS: f64 = 1.0;
+/// Minimal latency factor to keep the slow providers in the rotation
+const MIN_LATENCY_FACTOR: f64 = 0.1;
+
+/// Smoothing factor of the local availability moving averages
+const LOCAL_EWMA_ALPHA: f64 = 0.05;
+/// Minimal amount of the calls before the local availability affects the weight
+const MIN_LOCAL_SAMPLES: u64 = 20;
+
 /// The amount of successful and failed requests to a provider
 ///
 /// Availability(success_counter, failure_counter)
 #[derive(Debug, Copy, Clone)]
 pub struct Availability(u64, u64);
 
+impl Serialize for Availability {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        #[derive(Serialize)]
+        struct Counters {
+            success: u64,
+            failure: u64,
+        }
+
+        Counters {
+            success: self.0,
+            failure: self.1,
+        }
+        .serialize(serializer)
+    }
+}
+
+impl Availability {
+    /// Ratio of the failed requests, `None` when there were no requests
+    pub fn error_rate(&self) -> Option<f64> {
+        let total = self.0 + self.1;
+        (total > 0).then(|| self.1 as f64 / total as f64)
+    }
+}
+
 pub type ParsedWeights = HashMap<ProviderKind, (HashMap<ChainId, Availability>, Availability)>;
 
+/// The p95 latency in seconds of the provider calls per chain
+pub type ParsedLatencies = HashMap<ProviderKind, HashMap<ChainId, f64>>;
+
+/// Prometheus-derived inputs of the weights update
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WeightsUpdate {
+    pub updated_at: DateTime<Utc>,
+    #[serde(serialize_with = "serialize_parsed_weights")]
+    pub inputs: ParsedWeights,
+    #[serde(serialize_with = "serialize_parsed_latencies")]
+    pub latencies: ParsedLatencies,
+}
+
+/// Bounded history of the weights updates, the latest update is the last
+#[derive(Debug, Default)]
+pub struct WeightsHistory(RwLock<VecDeque<WeightsUpdate>>);
+
+impl WeightsHistory {
+    pub fn push(&self, inputs: ParsedWeights, latencies: ParsedLatencies) {
+        match self.0.write() {
+            Ok(mut updates) => {
+                if updates.len() == WEIGHTS_HISTORY_SIZE {
+                    updates.pop_front();
+                }
+                updates.push_back(WeightsUpdate {
+                    updated_at: Utc::now(),
+                    inputs,
+                    latencies,
+                });
+            }
+            Err(e) => warn!("Failed to store the weights update: {e}"),
+        }
+    }
+
+    pub fn latest(&self) -> Option<WeightsUpdate> {
+        self.0
+            .read()
+            .ok()
+            .and_then(|updates| updates.back().cloned())
+    }
+
+    pub fn updates(&self) -> Vec<WeightsUpdate> {
+        self.0
+            .read()
+            .map(|updates| updates.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+}
+
+fn serialize_parsed_weights<S: Serializer>(
+    parsed_weights: &ParsedWeights,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    #[derive(Serialize)]
+    struct ProviderInputs<'a> {
+        #[serde(flatten)]
+        availability: Availability,
+        chains: BTreeMap<&'a str, Availability>,
+    }
+
+    parsed_weights
+        .iter()
+        .map(|(provider, (chains, availability))| {
+            let inputs = ProviderInputs {
+                availability: *availability,
+                chains: chains
+                    .iter()
+                    .map(|(chain_id, availability)| (chain_id.0.as_str(), *availability))
+                    .collect(),
+            };
+            (provider.to_string(), inputs)
+        })
+        .collect::<BTreeMap<_, _>>()
+        .serialize(serializer)
+}
+
+fn serialize_parsed_latencies<S: Serializer>(
+    parsed_latencies: &ParsedLatencies,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    parsed_latencies
+        .iter()
+        .map(|(provider, chains)| {
+            let chains = chains
+                .iter()
+                .map(|(chain_id, latency)| (chain_id.0.as_str(), *latency))
+                .collect::<BTreeMap<_, _>>();
+            (provider.to_string(), chains)
+        })
+        .collect::<BTreeMap<_, _>>()
+        .serialize(serializer)
+}
+
+/// Provider disabled at runtime for all chains or the specific chain
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct DisabledProvider {
+    pub provider: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub chain_id: Option<String>,
+}
+
+/// Providers disabled by the admin API, the weights updates are keeping their
+/// weights at zero until they are re-enabled
+#[derive(Debug, Default)]
+pub struct DisabledProviders(RwLock<HashSet<(ProviderKind, Option<String>)>>);
+
+impl DisabledProviders {
+    pub fn is_disabled(&self, provider: &ProviderKind, chain_id: &str) -> bool {
+        self.0.read().is_ok_and(|disabled| {
+            !disabled.is_empty()
+                && (disabled.contains(&(provider.clone(), None))
+                    || disabled.contains(&(provider.clone(), Some(chain_id.to_owned()))))
+        })
+    }
+
+    pub fn disable(&self, provider: ProviderKind, chain_id: Option<String>) {
+        if let Ok(mut disabled) = self.0.write() {
+            disabled.insert((provider, chain_id));
+        }
+    }
+
+    /// Re-enables the provider for the chain, all the provider overrides are
+    /// removed when the chain is not set
+    pub fn enable(&self, provider: &ProviderKind, chain_id: Option<&str>) {
+        let Ok(mut disabled) = self.0.write() else {
+            return;
+        };
+        match chain_id {
+            Some(chain_id) => {
+                disabled.remove(&(provider.clone(), Some(chain_id.to_owned())));
+            }
+            None => disabled.retain(|(disabled_provider, _)| disabled_provider != provider),
+        }
+    }
+
+    pub fn list(&self) -> Vec<DisabledProvider> {
+        let mut list = self
+            .0
+            .read()
+            .map(|disabled| {
+                disabled
+                    .iter()
+                    .map(|(provider, chain_id)| DisabledProvider {
+                        provider: provider.to_string(),
+                        chain_id: chain_id.clone(),
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+        list.sort_by(|a, b| (&a.provider, &a.chain_id).cmp(&(&b.provider, &b.chain_id)));
+        list
+    }
+}
+
+/// Zeroes the weights of the disabled providers after the weights updates
+pub fn apply_disabled(weight_resolver: &ChainsWeightResolver, disabled: &DisabledProviders) {
+    for (chain_id, providers) in weight_resolver {
+        for (provider, weight) in providers {
+            if disabled.is_disabled(provider, chain_id) {
+                weight.disable();
+            }
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone)]
+struct LocalChainAvailability {
+    success_rate: f64,
+    latency_secs: f64,
+    samples: u64,
+}
+
+/// In-process exponentially weighted moving averages of the provider calls
+/// success rate and latency per chain, used when Prometheus is not available
+#[derive(Debug, Default)]
+pub struct LocalAvailability(Mutex<HashMap<(ProviderKind, String), LocalChainAvailability>>);
+
+impl LocalAvailability {
+    pub fn record(
+        &self,
+        provider: &ProviderKind,
+        chain_id: &str,
+        success: bool,
+        latency: Duration,
+    ) {
+        let Ok(mut availability) = self.0.lock() else {
+            return;
+        };
+        let success = if success { 1.0 } else { 0.0 };
+        let latency = latency.as_secs_f64();
+        availability
+            .entry((provider.clone(), chain_id.to_owned()))
+            .and_modify(|chain_availability| {
+                chain_availability.success_rate +=
+                    LOCAL_EWMA_ALPHA * (success - chain_availability.success_rate);
+                chain_availability.latency_secs +=
+                    LOCAL_EWMA_ALPHA * (latency - chain_availability.latency_secs);
+                chain_availability.samples += 1;
+            })
+            .or_insert(LocalChainAvailability {
+                success_rate: success,
+                latency_secs: latency,
+                samples: 1,
+            });
+    }
+}
+
+#[tracing::instrument(skip_all, level = "debug")]
+pub fn parse_latencies(prometheus_data: PromqlResult) -> ParsedLatencies {
+    let mut latencies_data = ParsedLatencies::new();
+    prometheus_data.data().as_vector().iter().for_each(|v| {
+        for metrics in v.iter() {
+            let metric = metrics.metric();
+            let (Some(chain_id), Some(provider)) = (metric.get("chain_id"), metric.get("provider"))
+            else {
+                warn!("No chain_id or provider found in latency metric: {metric:?}");
+                continue;
+            };
+
+            let Some(provider_kind) = ProviderKind::from_str(provider) else {
+                warn!("Failed to parse provider kind in latency metric: {provider}");
+                continue;
+            };
+
+            let latency = metrics.sample().value();
+            if !latency.is_finite() {
+                continue;
+            }
+            latencies_data
+                .entry(provider_kind)
+                .or_default()
+                .insert(ChainId(chain_id.to_owned()), latency);
+        }
+    });
+    latencies_data
+}
+
+/// Weight multiplier for the provider p95 latency, the providers slower than
+/// `SLOW_LATENCY_SECS` are deprioritized proportionally to their latency
+fn latency_factor(p95_latency_secs: Option<f64>) -> f64 {
+    match p95_latency_secs {
+        Some(latency) if latency > SLOW_LATENCY_SECS => {
+            (SLOW_LATENCY_SECS / latency).max(MIN_LATENCY_FACTOR)
+        }
+        _ => 1.0,
+    }
+}
+
 #[tracing::instrument(skip_all, level = "debug")]
 pub fn parse_weights(prometheus_data: PromqlResult) -> ParsedWeights {
     let mut weights_data = HashMap::new();
@@ -122,8 +415,18 @@ fn calculate_chain_weight(
     weight as u64
 }
 
+/// Calculates the weight for the provider that is not bound to the chain
+/// (e.g. bundlers) based on the provider's total availability
+pub fn calculate_provider_weight(provider_availability: Availability) -> u64 {
+    calculate_chain_weight(provider_availability, Availability(0, 0))
+}
+
 #[tracing::instrument(skip_all, level = "debug")]
-pub fn update_values(weight_resolver: &ChainsWeightResolver, parsed_weights: ParsedWeights) {
+pub fn update_values(
+    weight_resolver: &ChainsWeightResolver,
+    parsed_weights: ParsedWeights,
+    parsed_latencies: &ParsedLatencies,
+) {
     for (provider, (chain_availabilities, provider_availability)) in parsed_weights {
         // Skip weight recalculation for providers in the exclusion list
         // This prevents weight degradation when requests fail, allowing these providers
@@ -137,8 +440,13 @@ pub fn update_values(weight_resolver: &ChainsWeightResolver, parsed_weights: Par
         }
 
         for (chain_id, chain_availability) in chain_availabilities {
+            let p95_latency = parsed_latencies
+                .get(&provider)
+                .and_then(|latencies| latencies.get(&chain_id))
+                .copied();
             let chain_id = chain_id.0;
             let chain_weight = calculate_chain_weight(chain_availability, provider_availability);
+            let chain_weight = (chain_weight as f64 * latency_factor(p95_latency)) as u64;
 
             let Some(provider_chain_weight) = weight_resolver.get(&chain_id) else {
                 warn!("Chain {chain_id} not found in weight resolver: {weight_resolver:?}");
@@ -158,6 +466,37 @@ pub fn update_values(weight_resolver: &ChainsWeightResolver, parsed_weights: Par
     }
 }
 
+/// Updates the weights from the local availability, the average latency is
+/// used in place of the p95 latency
+#[tracing::instrument(skip_all, level = "debug")]
+pub fn update_values_from_local(
+    weight_resolver: &ChainsWeightResolver,
+    local_availability: &LocalAvailability,
+) {
+    let Ok(availability) = local_availability.0.lock() else {
+        return;
+    };
+    for ((provider, chain_id), chain_availability) in availability.iter() {
+        if chain_availability.samples < MIN_LOCAL_SAMPLES
+            || WEIGHT_RECALCULATION_EXCLUDED_PROVIDERS.contains(provider)
+        {
+            continue;
+        }
+
+        let Some(weight) = weight_resolver
+            .get(chain_id)
+            .and_then(|provider_chain_weight| provider_chain_weight.get(provider))
+        else {
+            continue;
+        };
+
+        let chain_weight = chain_availability.success_rate
+            * latency_factor(Some(chain_availability.latency_secs))
+            * 10000.0;
+        weight.update_value(chain_weight as u64);
+    }
+}
+
 pub fn record_values(weight_resolver: &ChainsWeightResolver, metrics: &crate::Metrics) {
     for (chain_id, provider_chain_weight) in weight_resolver {
         for (provider_kind, weight) in provider_chain_weight {
@@ -169,6 +508,67 @@ pub fn record_values(weight_resolver: &ChainsWeightResolver, metrics: &crate::Me
 
 #[cfg(test)]
 mod tests {
+    use std::time::Duration;
+
+    #[test]
+    fn availability_error_rate() {
+        assert_eq!(super::Availability(75, 25).error_rate(), Some(0.25));
+        assert_eq!(super::Availability(0, 0).error_rate(), None);
+    }
+
+    #[test]
+    fn disabled_providers_overrides() {
+        use super::{DisabledProviders, ProviderKind};
+
+        let disabled = DisabledProviders::default();
+        disabled.disable(ProviderKind::Quicknode, None);
+        disabled.disable(ProviderKind::Publicnode, Some("eip155:1".to_owned()));
+        assert!(disabled.is_disabled(&ProviderKind::Quicknode, "eip155:10"));
+        assert!(disabled.is_disabled(&ProviderKind::Publicnode, "eip155:1"));
+        assert!(!disabled.is_disabled(&ProviderKind::Publicnode, "eip155:10"));
+
+        disabled.enable(&ProviderKind::Publicnode, Some("eip155:1"));
+        assert!(!disabled.is_disabled(&ProviderKind::Publicnode, "eip155:1"));
+        disabled.enable(&ProviderKind::Quicknode, None);
+        assert!(disabled.list().is_empty());
+    }
+
+    #[test]
+    fn weights_history_is_bounded() {
+        let history = super::WeightsHistory::default();
+        for _ in 0..super::WEIGHTS_HISTORY_SIZE + 5 {
+            history.push(Default::default(), Default::default());
+        }
+        assert_eq!(history.updates().len(), super::WEIGHTS_HISTORY_SIZE);
+        assert!(history.latest().is_some());
+    }
+
+    #[test]
+    fn latency_factor() {
+        assert_eq!(super::latency_factor(None), 1.0);
+        assert_eq!(super::latency_factor(Some(0.3)), 1.0);
+        assert_eq!(super::latency_factor(Some(2.0)), 0.5);
+        assert_eq!(super::latency_factor(Some(60.0)), super::MIN_LATENCY_FACTOR);
+    }
+
+    #[test]
+    fn local_availability_ewma() {
+        let local_availability = super::LocalAvailability::default();
+        let provider = super::ProviderKind::Pokt;
+        for _ in 0..super::MIN_LOCAL_SAMPLES {
+            local_availability.record(&provider, "eip155:1", true, Duration::from_millis(100));
+        }
+        local_availability.record(&provider, "eip155:1", false, Duration::from_millis(100));
+
+        let availability = local_availability.0.lock().unwrap();
+        let chain_availability = availability
+            .get(&(provider, "eip155:1".to_owned()))
+            .unwrap();
+        assert_eq!(chain_availability.samples, super::MIN_LOCAL_SAMPLES + 1);
+        assert!((chain_availability.success_rate - 0.95).abs() < 1e-9);
+        assert!((chain_availability.latency_secs - 0.1).abs() < 1e-9);
+    }
+
     #[test]
     fn calcaulate_weights() {
         // The chain in this provider has 75% success rate
@@ -0,0 +1,239 @@
+use {
+    super::ConversionProvider,
+    crate::{
+        error::{RpcError, RpcResult},
+        handlers::convert::{
+            allowance::{AllowanceQueryParams, AllowanceResponseBody},
+            approve::{ConvertApproveQueryParams, ConvertApproveResponseBody},
+            gas_price::{GasPriceQueryParams, GasPriceQueryResponseBody},
+            quotes::{ConvertQuoteQueryParams, ConvertQuoteResponseBody, QuoteItem},
+            tokens::{TokensListQueryParams, TokensListResponseBody},
+            transaction::{
+                ConvertTransactionQueryParams, ConvertTransactionResponseBody, ConvertTx,
+                ConvertTxEip155,
+            },
+        },
+        utils::crypto::{self, CaipNamespaces},
+        Metrics,
+    },
+    alloy::{
+        primitives::{address, Address, U256},
+        sol,
+        sol_types::SolCall,
+    },
+    async_trait::async_trait,
+    std::{str::FromStr, sync::Arc},
+};
+
+/// Address of the native token used by the conversion providers
+const NATIVE_TOKEN_ADDRESS: Address = address!("EeeeeEeeeEeEeeEeEeEeeEEEeeeeEeeeeeeeEEeE");
+
+/// Gas limits of the wrapped native token calls with a margin for the chains
+/// where the contracts are more expensive than the canonical WETH9
+const DEPOSIT_GAS: u64 = 60_000;
+const WITHDRAW_GAS: u64 = 60_000;
+
+sol! {
+    function deposit() external payable;
+    function withdraw(uint256 wad) external;
+}
+
+/// Wrapped native token contract by the EVM chain ID
+fn wrapped_native_token(chain_id: &str) -> Option<Address> {
+    match chain_id {
+        // WETH
+        "1" => Some(address!("C02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2")),
+        "10" | "8453" | "7777777" => Some(address!("4200000000000000000000000000000000000006")),
+        "42161" => Some(address!("82aF49447D8a07e3bd95BD0d56f35241523fBab1")),
+        "324" => Some(address!("5AEa5775959fBC2557Cc8789bC1bf90A239D9a91")),
+        "59144" => Some(address!("e5D7C2a44FfDDf6b295A15c148167daaAf5Cf34f")),
+        // WPOL
+        "137" => Some(address!("0d500B1d8E8eF31E21C99d1Db9A6444d3ADf1270")),
+        // WBNB
+        "56" => Some(address!("bb4CdB9CBd36B01bD1cBaEBF2De08d9173bc095c")),
+        // WAVAX
+        "43114" => Some(address!("B31f66AA3C1e785363F0875A1B74E27b85FD66c7")),
+        // WXDAI
+        "100" => Some(address!("e91D153E0b41518A2Ce8Dd3D7944Fa863463a97d")),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum WrapDirection {
+    Wrap,
+    Unwrap,
+}
+
+/// Direction of the conversion between the native token and its wrapped
+/// version, `None` for the other conversions
+fn wrap_direction(chain_id: &str, from: &str, to: &str) -> Option<WrapDirection> {
+    let wrapped = wrapped_native_token(chain_id)?;
+    let from = Address::from_str(from).ok()?;
+    let to = Address::from_str(to).ok()?;
+    if from == NATIVE_TOKEN_ADDRESS && to == wrapped {
+        Some(WrapDirection::Wrap)
+    } else if from == wrapped && to == NATIVE_TOKEN_ADDRESS {
+        Some(WrapDirection::Unwrap)
+    } else {
+        None
+    }
+}
+
+/// Conversion provider building the wrapped native token deposit and
+/// withdraw transactions for the conversions between the native token and
+/// its wrapped version, which are 1:1 without the aggregator route fees.
+/// Other conversions are passed to the wrapped provider.
+pub struct WrappedNativeConversionProvider {
+    inner: Arc<dyn ConversionProvider>,
+}
+
+impl WrappedNativeConversionProvider {
+    pub fn new(inner: Arc<dyn ConversionProvider>) -> Self {
+        Self { inner }
+    }
+}
+
+#[async_trait]
+impl ConversionProvider for WrappedNativeConversionProvider {
+    async fn get_tokens_list(
+        &self,
+        params: TokensListQueryParams,
+        metrics: Arc<Metrics>,
+    ) -> RpcResult<TokensListResponseBody> {
+        self.inner.get_tokens_list(params, metrics).await
+    }
+
+    async fn get_convert_quote(
+        &self,
+        params: ConvertQuoteQueryParams,
+        metrics: Arc<Metrics>,
+    ) -> RpcResult<ConvertQuoteResponseBody> {
+        let (_, chain_id, src_address) = crypto::disassemble_caip10(&params.from)?;
+        let (_, dst_chain_id, dst_address) = crypto::disassemble_caip10(&params.to)?;
+        if dst_chain_id != chain_id
+            || wrap_direction(&chain_id, &src_address, &dst_address).is_none()
+        {
+            return self.inner.get_convert_quote(params, metrics).await;
+        }
+
+        parse_amount(&params.amount)?;
+        Ok(ConvertQuoteResponseBody {
+            quotes: vec![QuoteItem {
+                id: None,
+                from_amount: params.amount.clone(),
+                from_account: params.from,
+                to_amount: params.amount,
+                to_account: params.to,
+            }],
+        })
+    }
+
+    async fn build_approve_tx(
+        &self,
+        params: ConvertApproveQueryParams,
+        metrics: Arc<Metrics>,
+    ) -> RpcResult<ConvertApproveResponseBody> {
+        self.inner.build_approve_tx(params, metrics).await
+    }
+
+    async fn build_convert_tx(
+        &self,
+        params: ConvertTransactionQueryParams,
+        metrics: Arc<Metrics>,
+    ) -> RpcResult<ConvertTransactionResponseBody> {
+        let (_, chain_id, src_address) = crypto::disassemble_caip10(&params.from)?;
+        let (_, dst_chain_id, dst_address) = crypto::disassemble_caip10(&params.to)?;
+        let (_, user_chain_id, user_address) = crypto::disassemble_caip10(&params.user_address)?;
+        let direction = match wrap_direction(&chain_id, &src_address, &dst_address) {
+            Some(direction) if dst_chain_id == chain_id && user_chain_id == chain_id => direction,
+            _ => return self.inner.build_convert_tx(params, metrics).await,
+        };
+
+        let amount = parse_amount(&params.amount)?;
+        let wrapped = wrapped_native_token(&chain_id).ok_or_else(|| {
+            RpcError::ConversionInvalidParameter(format!("chain {chain_id} is not supported"))
+        })?;
+        let (data, value, gas) = match direction {
+            WrapDirection::Wrap => (depositCall {}.abi_encode(), amount, DEPOSIT_GAS),
+            WrapDirection::Unwrap => (
+                withdrawCall { wad: amount }.abi_encode(),
+                U256::ZERO,
+                WITHDRAW_GAS,
+            ),
+        };
+        let gas_price = self
+            .inner
+            .get_gas_price(
+                GasPriceQueryParams {
+                    project_id: params.project_id,
+                    chain_id: format!("{}:{chain_id}", CaipNamespaces::Eip155),
+                },
+                metrics,
+            )
+            .await?;
+
+        Ok(ConvertTransactionResponseBody {
+            tx: ConvertTx {
+                from: crypto::format_to_caip10(CaipNamespaces::Eip155, &chain_id, &user_address),
+                to: crypto::format_to_caip10(
+                    CaipNamespaces::Eip155,
+                    &chain_id,
+                    &wrapped.to_string(),
+                ),
+                data: format!("0x{}", hex::encode(data)),
+                amount: params.amount,
+                eip155: Some(ConvertTxEip155 {
+                    gas: gas.to_string(),
+                    gas_price: gas_price.standard,
+                    value: Some(value.to_string()),
+                }),
+            },
+        })
+    }
+
+    async fn get_gas_price(
+        &self,
+        params: GasPriceQueryParams,
+        metrics: Arc<Metrics>,
+    ) -> RpcResult<GasPriceQueryResponseBody> {
+        self.inner.get_gas_price(params, metrics).await
+    }
+
+    async fn get_allowance(
+        &self,
+        params: AllowanceQueryParams,
+        metrics: Arc<Metrics>,
+    ) -> RpcResult<AllowanceResponseBody> {
+        self.inner.get_allowance(params, metrics).await
+    }
+}
+
+fn parse_amount(amount: &str) -> RpcResult<U256> {
+    U256::from_str_radix(amount, 10)
+        .map_err(|_| RpcError::ConversionInvalidParameter(format!("invalid amount {amount}")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WETH: &str = "0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2";
+    const NATIVE: &str = "0xeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeee";
+
+    #[test]
+    fn wrap_directions() {
+        assert_eq!(wrap_direction("1", NATIVE, WETH), Some(WrapDirection::Wrap));
+        assert_eq!(
+            wrap_direction("1", &WETH.to_lowercase(), NATIVE),
+            Some(WrapDirection::Unwrap)
+        );
+        // WETH of the other chain
+        assert_eq!(wrap_direction("10", NATIVE, WETH), None);
+        assert_eq!(
+            wrap_direction("1", NATIVE, "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48"),
+            None
+        );
+        assert_eq!(wrap_direction("1", NATIVE, NATIVE), None);
+    }
+}
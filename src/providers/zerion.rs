@@ -15,11 +15,15 @@ use {
                 HistoryTransactionTransfer, HistoryTransactionTransferQuantity,
                 HistoryTransactionURLItem, HistoryTransactionURLandContentTypeItem,
             },
-            portfolio::{PortfolioPosition, PortfolioQueryParams, PortfolioResponseBody},
+            portfolio::{
+                PortfolioNftCollection, PortfolioNfts, PortfolioPosition, PortfolioQueryParams,
+                PortfolioResponseBody,
+            },
         },
+        otel::PropagateTraceContext,
         providers::{
             balance::{BalanceItem, BalanceQuantity},
-            ProviderKind, TokenMetadataCacheProvider,
+            proxied_api_url, ProviderKind, TokenMetadataCacheProvider,
         },
         utils::crypto,
         Metrics,
@@ -27,34 +31,39 @@ use {
     async_trait::async_trait,
     deadpool_redis::Pool,
     serde::{Deserialize, Serialize},
-    std::{sync::Arc, time::SystemTime},
+    std::{collections::HashMap, sync::Arc, time::SystemTime},
     tap::TapFallible,
     tracing::log::error,
     url::Url,
 };
 
 const POLYGON_NATIVE_TOKEN_ADDRESS: &str = "0x0000000000000000000000000000000000001010";
+/// Maximum number of the NFT collections pages of the portfolio summary
+const NFT_COLLECTIONS_MAX_PAGES: usize = 5;
 
 #[derive(Debug)]
 pub struct ZerionProvider {
     pub provider_kind: ProviderKind,
     pub api_key: String,
     pub http_client: reqwest::Client,
+    pub api_proxy_url: Option<Url>,
 }
 
 impl ZerionProvider {
-    pub fn new(api_key: String) -> Self {
+    pub fn new(api_key: String, api_proxy_url: Option<Url>) -> Self {
         let http_client = reqwest::Client::new();
         Self {
             provider_kind: ProviderKind::Zerion,
             api_key,
             http_client,
+            api_proxy_url,
         }
     }
 
     async fn send_request(&self, url: Url) -> Result<reqwest::Response, reqwest::Error> {
         self.http_client
-            .get(url)
+            .get(proxied_api_url(url, self.api_proxy_url.as_ref()))
+            .propagate_trace_context()
             .header("authorization", format!("Basic {}", self.api_key))
             .send()
             .await
@@ -87,6 +96,30 @@ pub struct ZerionPortfolioAttributes {
     pub fungible_info: ZerionFungibleInfoAttribute,
 }
 
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+pub struct ZerionNftCollection {
+    pub id: String,
+    pub attributes: ZerionNftCollectionAttributes,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+pub struct ZerionNftCollectionAttributes {
+    pub nfts_count: String,
+    pub total_floor_price: Option<f64>,
+    pub collection_info: Option<ZerionNftCollectionInfo>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Default)]
+pub struct ZerionNftCollectionInfo {
+    pub name: Option<String>,
+    pub content: Option<ZerionNftCollectionContent>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+pub struct ZerionNftCollectionContent {
+    pub icon: Option<ZerionUrlItem>,
+}
+
 #[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
 pub struct ZerionQuantityAttribute {
     pub decimals: usize,
@@ -322,70 +355,86 @@ impl HistoryProvider for ZerionProvider {
         let transactions = body
             .data
             .into_iter()
-            .map(|f| HistoryTransaction {
-                id: f.id,
-                metadata: HistoryTransactionMetadata {
-                    operation_type: f.attributes.operation_type,
-                    hash: f.attributes.hash,
-                    mined_at: f.attributes.mined_at,
-                    nonce: f.attributes.nonce,
-                    sent_from: f.attributes.sent_from,
-                    sent_to: f.attributes.sent_to,
-                    status: f.attributes.status,
-                    application: f.attributes.application_metadata.map(|f| {
-                        HistoryTransactionMetadataApplication {
-                            name: f.name,
-                            icon_url: f.icon.map(|f| f.url),
-                        }
-                    }),
-                    chain: if f.relationships.chain.data.r#type != "chains" {
-                        None
-                    } else {
-                        crypto::ChainId::to_caip2(&f.relationships.chain.data.id)
+            .map(|f| {
+                let chain = if f.relationships.chain.data.r#type != "chains" {
+                    None
+                } else {
+                    crypto::ChainId::to_caip2(&f.relationships.chain.data.id)
+                };
+                let chain_id_human = f.relationships.chain.data.id;
+                HistoryTransaction {
+                    id: f.id,
+                    metadata: HistoryTransactionMetadata {
+                        operation_type: f.attributes.operation_type,
+                        hash: f.attributes.hash,
+                        mined_at: f.attributes.mined_at,
+                        nonce: f.attributes.nonce,
+                        sent_from: f.attributes.sent_from,
+                        sent_to: f.attributes.sent_to,
+                        status: f.attributes.status,
+                        application: f.attributes.application_metadata.map(|f| {
+                            HistoryTransactionMetadataApplication {
+                                name: f.name,
+                                icon_url: f.icon.map(|f| f.url),
+                            }
+                        }),
+                        chain: chain.clone(),
                     },
-                },
-                transfers: f
-                    .attributes
-                    .transfers
-                    .into_iter()
-                    .map(|f| {
-                        Some(HistoryTransactionTransfer {
-                            fungible_info: f.fungible_info.map(|f| {
-                                HistoryTransactionFungibleInfo {
+                    transfers: f
+                        .attributes
+                        .transfers
+                        .into_iter()
+                        .map(|f| {
+                            Some(HistoryTransactionTransfer {
+                                fungible_info: f.fungible_info.map(|f| {
+                                    // Native tokens have no implementation address
+                                    let address = f
+                                        .implementations
+                                        .iter()
+                                        .find(|impl_| impl_.chain_id == chain_id_human)
+                                        .and_then(|impl_| impl_.address.as_ref())
+                                        .zip(chain.as_ref())
+                                        .map(|(address, chain)| format!("{chain}:{address}"));
+                                    HistoryTransactionFungibleInfo {
+                                        name: f.name,
+                                        symbol: Some(f.symbol),
+                                        icon: f
+                                            .icon
+                                            .map(|f| HistoryTransactionURLItem { url: f.url }),
+                                        address,
+                                    }
+                                }),
+                                nft_info: f.nft_info.map(|f| HistoryTransactionNFTInfo {
                                     name: f.name,
-                                    symbol: Some(f.symbol),
-                                    icon: f.icon.map(|f| HistoryTransactionURLItem { url: f.url }),
-                                }
-                            }),
-                            nft_info: f.nft_info.map(|f| HistoryTransactionNFTInfo {
-                                name: f.name,
-                                content: f.content.map(|f| HistoryTransactionNFTContent {
-                                    preview: f.preview.map(|f| {
-                                        HistoryTransactionURLandContentTypeItem {
-                                            url: f.url,
-                                            content_type: f.content_type,
-                                        }
-                                    }),
-                                    detail: f.detail.map(|f| {
-                                        HistoryTransactionURLandContentTypeItem {
-                                            url: f.url,
-                                            content_type: f.content_type,
-                                        }
+                                    content: f.content.map(|f| HistoryTransactionNFTContent {
+                                        preview: f.preview.map(|f| {
+                                            HistoryTransactionURLandContentTypeItem {
+                                                url: f.url,
+                                                content_type: f.content_type,
+                                            }
+                                        }),
+                                        detail: f.detail.map(|f| {
+                                            HistoryTransactionURLandContentTypeItem {
+                                                url: f.url,
+                                                content_type: f.content_type,
+                                            }
+                                        }),
                                     }),
+                                    flags: HistoryTransactionNFTInfoFlags {
+                                        is_spam: f.flags.is_spam,
+                                    },
                                 }),
-                                flags: HistoryTransactionNFTInfoFlags {
-                                    is_spam: f.flags.is_spam,
+                                direction: f.direction,
+                                quantity: HistoryTransactionTransferQuantity {
+                                    numeric: f.quantity.numeric,
                                 },
-                            }),
-                            direction: f.direction,
-                            quantity: HistoryTransactionTransferQuantity {
-                                numeric: f.quantity.numeric,
-                            },
-                            value: f.value,
-                            price: f.price,
+                                value: f.value,
+                                price: f.price,
+                            })
                         })
-                    })
-                    .collect(),
+                        .collect(),
+                    onramp: None,
+                }
             })
             .collect();
 
@@ -453,7 +502,75 @@ impl PortfolioProvider for ZerionProvider {
             })
             .collect();
 
-        Ok(PortfolioResponseBody { data: portfolio })
+        Ok(PortfolioResponseBody {
+            data: portfolio,
+            nfts: None,
+        })
+    }
+
+    #[tracing::instrument(skip(self, params), fields(provider = "Zerion"), level = "debug")]
+    async fn get_nft_holdings(
+        &self,
+        address: String,
+        params: PortfolioQueryParams,
+        metrics: Arc<Metrics>,
+    ) -> RpcResult<PortfolioNfts> {
+        let base = format!(
+            "https://api.zerion.io/v1/wallets/{}/nft-collections/?",
+            &address
+        );
+        let mut url = Url::parse(&base).map_err(|_| RpcError::PortfolioProviderError)?;
+        url.query_pairs_mut()
+            .append_pair("currency", &params.currency.unwrap_or("usd".to_string()));
+        url.query_pairs_mut().append_pair("page[size]", "100");
+
+        let mut collections = Vec::new();
+        let mut next_url = Some(url);
+        for _ in 0..NFT_COLLECTIONS_MAX_PAGES {
+            let Some(url) = next_url.take() else {
+                break;
+            };
+            let latency_start = SystemTime::now();
+            let response = self.send_request(url).await.map_err(|e| {
+                error!("Error on request to zerion nft collections endpoint with {e}");
+                RpcError::PortfolioProviderError
+            })?;
+            metrics.add_latency_and_status_code_for_provider(
+                &self.provider_kind,
+                response.status().into(),
+                latency_start,
+                None,
+                Some("nft_collections".to_string()),
+            );
+
+            if !response.status().is_success() {
+                error!(
+                    "Error on zerion nft collections response. Status is not OK: {:?}",
+                    response.status()
+                );
+                return Err(RpcError::PortfolioProviderError);
+            }
+
+            let body = response
+                .json::<ZerionResponseBody<Vec<ZerionNftCollection>>>()
+                .await?;
+            collections.extend(body.data.into_iter().map(|f| {
+                let collection_info = f.attributes.collection_info.unwrap_or_default();
+                PortfolioNftCollection {
+                    id: f.id,
+                    name: collection_info.name,
+                    icon_url: collection_info
+                        .content
+                        .and_then(|content| content.icon)
+                        .map(|icon| icon.url),
+                    count: f.attributes.nfts_count.parse().unwrap_or_default(),
+                    floor_value: f.attributes.total_floor_price,
+                }
+            }));
+            next_url = body.links.next.and_then(|next| Url::parse(&next).ok());
+        }
+
+        Ok(PortfolioNfts::from_collections(collections))
     }
 }
 
@@ -512,22 +629,44 @@ impl BalanceProvider for ZerionProvider {
             .json::<ZerionResponseBody<Vec<ZerionPosition>>>()
             .await?;
 
-        let mut balances_vec = Vec::new();
-        for f in body.data {
-            let chain_id_human = f.relationships.chain.data.id;
-            let token_address = f
-                .attributes
-                .fungible_info
-                .implementations
-                .iter()
-                .find(|impl_| impl_.chain_id == chain_id_human)
-                .and_then(|impl_| impl_.address.clone());
-
-            let token_address_strict = token_address
-                .clone()
-                .unwrap_or_else(|| H160_EMPTY_ADDRESS.to_string());
-            let chain_id = crypto::ChainId::to_caip2(&chain_id_human);
+        // Resolve the positions tokens to read their cached metadata by a
+        // single batch instead of a cache round trip per position
+        let positions = body
+            .data
+            .into_iter()
+            .map(|f| {
+                let chain_id_human = &f.relationships.chain.data.id;
+                let token_address = f
+                    .attributes
+                    .fungible_info
+                    .implementations
+                    .iter()
+                    .find(|impl_| &impl_.chain_id == chain_id_human)
+                    .and_then(|impl_| impl_.address.clone());
+                let chain_id = crypto::ChainId::to_caip2(chain_id_human);
+                let caip10_token_address = chain_id.as_ref().map(|chain_id| {
+                    let token_address_strict = token_address
+                        .clone()
+                        .unwrap_or_else(|| H160_EMPTY_ADDRESS.to_string());
+                    format!("{chain_id}:{token_address_strict}")
+                });
+                (f, token_address, chain_id, caip10_token_address)
+            })
+            .collect::<Vec<_>>();
+        let cache_keys = positions
+            .iter()
+            .filter_map(|(_, _, _, caip10_token_address)| caip10_token_address.clone())
+            .collect::<Vec<_>>();
+        let cached_metadata = match metadata_cache.get_metadata_batch(&cache_keys).await {
+            Ok(items) => cache_keys.into_iter().zip(items).collect::<HashMap<_, _>>(),
+            Err(e) => {
+                error!("Error getting metadata from cache: {e}");
+                HashMap::new()
+            }
+        };
 
+        let mut balances_vec = Vec::new();
+        for (f, token_address, chain_id, caip10_token_address) in positions {
             // Set the default metadata from the response
             let mut token_metadata = TokenMetadataCacheItem {
                 name: f
@@ -546,11 +685,10 @@ impl BalanceProvider for ZerionProvider {
             };
 
             // Update the token metadata from the cache or update the cache if it's not present
-            if let Some(chain_id) = chain_id.clone() {
-                let caip10_token_address = format!("{chain_id}:{token_address_strict}");
-                match metadata_cache.get_metadata(&caip10_token_address).await {
-                    Ok(Some(cached_metadata)) => token_metadata = cached_metadata,
-                    Ok(None) => {
+            if let Some(caip10_token_address) = caip10_token_address {
+                match cached_metadata.get(&caip10_token_address).cloned() {
+                    Some(Some(cached_metadata)) => token_metadata = cached_metadata,
+                    Some(None) => {
                         let metadata_cache = metadata_cache.clone();
                         let token_metadata_clone = token_metadata.clone();
                         tokio::spawn(async move {
@@ -564,7 +702,8 @@ impl BalanceProvider for ZerionProvider {
                             }
                         });
                     }
-                    Err(e) => error!("Error getting metadata from cache: {e}"),
+                    // The cache batch read failed
+                    None => {}
                 }
             }
 
@@ -614,6 +753,7 @@ impl BalanceProviderFactory<ZerionConfig> for ZerionProvider {
             provider_kind: ProviderKind::Zerion,
             api_key: provider_config.api_key.clone(),
             http_client,
+            api_proxy_url: provider_config.api_proxy_url.clone(),
         }
     }
 }
@@ -1,11 +1,13 @@
 use {
     super::{
-        Provider, ProviderKind, RateLimited, RpcProvider, RpcProviderFactory, RpcQueryParams,
-        RpcWsProvider,
+        http_client::record_http_version, Provider, ProviderKind, RateLimited, RpcProvider,
+        RpcProviderFactory, RpcQueryParams, RpcWsProvider, RpcWsProviderFactory,
     },
     crate::{
         env::ZoraConfig,
         error::{RpcError, RpcResult},
+        otel::PropagateTraceContext,
+        providers::forwarded_headers::ForwardHeaders,
         ws,
     },
     async_trait::async_trait,
@@ -62,6 +64,7 @@ impl RpcWsProvider for ZoraWsProvider {
         &self,
         ws: WebSocketUpgrade,
         query_params: RpcQueryParams,
+        session: ws::WsSession,
     ) -> RpcResult<Response> {
         let uri = self
             .supported_chains
@@ -69,13 +72,14 @@ impl RpcWsProvider for ZoraWsProvider {
             .ok_or(RpcError::ChainNotFound)?;
 
         let project_id = query_params.project_id;
+        let chain_id = query_params.chain_id;
 
         let (websocket_provider, _) = async_tungstenite::tokio::connect_async(uri)
             .await
             .map_err(|e| RpcError::WebSocketError(e.to_string()))?;
 
         Ok(ws.on_upgrade(move |socket| {
-            ws::proxy(project_id, socket, websocket_provider)
+            ws::proxy(project_id, chain_id, socket, websocket_provider, session)
                 .with_metrics(future_metrics!("ws_proxy_task", "name" => "zora"))
         }))
     }
@@ -117,11 +121,14 @@ impl RpcProvider for ZoraProvider {
         let response = self
             .client
             .post(uri)
+            .propagate_trace_context()
+            .forward_headers()
             .header(reqwest::header::CONTENT_TYPE, "application/json")
             .body(body)
             .send()
             .await?;
         let status = response.status();
+        record_http_version(&self.provider_kind(), response.version());
         let body = response.bytes().await?;
 
         if let Ok(response) = serde_json::from_slice::<jsonrpc::Response>(&body) {
@@ -143,8 +150,7 @@ impl RpcProvider for ZoraProvider {
 
 impl RpcProviderFactory<ZoraConfig> for ZoraProvider {
     #[tracing::instrument(level = "debug")]
-    fn new(provider_config: &ZoraConfig) -> Self {
-        let forward_proxy_client = reqwest::Client::new();
+    fn new(provider_config: &ZoraConfig, http_client: reqwest::Client) -> Self {
         let supported_chains: HashMap<String, String> = provider_config
             .supported_chains
             .iter()
@@ -152,13 +158,13 @@ impl RpcProviderFactory<ZoraConfig> for ZoraProvider {
             .collect();
 
         ZoraProvider {
-            client: forward_proxy_client,
+            client: http_client,
             supported_chains,
         }
     }
 }
 
-impl RpcProviderFactory<ZoraConfig> for ZoraWsProvider {
+impl RpcWsProviderFactory<ZoraConfig> for ZoraWsProvider {
     #[tracing::instrument(level = "debug")]
     fn new(provider_config: &ZoraConfig) -> Self {
         let supported_chains: HashMap<String, String> = provider_config
@@ -0,0 +1,94 @@
+use {
+    crate::{
+        env::Config,
+        error::{RpcError, RpcResult},
+        init_providers, secrets,
+        state::{AppState, ReloadableConfig},
+        utils::rate_limit::RateLimits,
+    },
+    axum::{extract::State, http::StatusCode},
+    std::{sync::Arc, time::Duration},
+    tokio::{
+        signal::{
+            self,
+            unix::{signal, SignalKind},
+        },
+        time::{interval, MissedTickBehavior},
+    },
+    tracing::{error, info, warn},
+};
+
+/// Reloads the runtime configuration subset: rate limits, blocked countries,
+/// provider API keys and balance denylists. Values are re-read from the
+/// process environment as at startup and the secrets are resolved again to
+/// pick up the rotated keys.
+pub async fn reload_config(state: &AppState) -> RpcResult<()> {
+    let mut config = Config::from_env()?;
+    secrets::resolve(&mut config).await?;
+
+    if let Some(rate_limit) = &state.rate_limit {
+        match RateLimits::from_config(&config.rate_limiting)
+            .map_err(RpcError::InvalidConfiguration)?
+        {
+            Some(limits) => rate_limit.update_limits(limits),
+            None => warn!(
+                "Rate limiting is disabled in the reloaded config, keeping the current limits"
+            ),
+        }
+    }
+
+    // Weights of the new providers are reset to the priorities until the next
+    // weights update
+    let reloadable = ReloadableConfig::from(&config);
+    let providers = (reloadable.providers != state.reloadable_config().providers).then(|| {
+        let mut providers = init_providers(&reloadable.providers);
+        providers.inherit_disabled_providers(&state.providers());
+        providers
+    });
+    info!(
+        providers_changed = providers.is_some(),
+        "Configuration reloaded"
+    );
+    state.reload(providers, reloadable);
+    Ok(())
+}
+
+/// Reloads the configuration on the SIGHUP signal
+pub async fn run(state: Arc<AppState>) -> Result<(), std::io::Error> {
+    let mut hangup = signal(SignalKind::hangup())?;
+    loop {
+        tokio::select! {
+            _ = hangup.recv() => {
+                info!("Received SIGHUP, reloading the configuration");
+                if let Err(e) = reload_config(&state).await {
+                    error!("Failed to reload the configuration: {e}");
+                }
+            }
+            _ = signal::ctrl_c() => {
+                info!("Config reloader received shutdown signal");
+                break;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Admin endpoint alternative to the SIGHUP, served on the private port only
+pub async fn handler(State(state): State<Arc<AppState>>) -> Result<StatusCode, RpcError> {
+    reload_config(&state).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Periodically reloads the configuration to re-resolve the rotated secrets
+pub async fn refresh_secrets(state: Arc<AppState>, refresh_interval: Duration) {
+    let mut refresh = interval(refresh_interval);
+    refresh.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    // The first tick completes immediately and the secrets are just resolved
+    refresh.tick().await;
+    loop {
+        refresh.tick().await;
+        if let Err(e) = reload_config(&state).await {
+            error!("Failed to refresh the secrets: {e}");
+        }
+    }
+}
@@ -0,0 +1,299 @@
+//! Denylist of the known scam tokens pulled from the external feeds.
+
+use {
+    crate::{
+        handlers::balance::Config as BalanceConfig,
+        state::AppState,
+        storage::KeyValueStorage,
+        utils::crypto::{self, CaipNamespaces},
+    },
+    arc_swap::ArcSwap,
+    futures_util::future::join_all,
+    serde::Deserialize,
+    std::{collections::HashSet, sync::Arc, time::Duration},
+    tokio::time::{interval, MissedTickBehavior},
+    tracing::{debug, warn},
+    uuid::Uuid,
+};
+
+const DEFAULT_REFRESH_INTERVAL: Duration = Duration::from_secs(60 * 60);
+const FEED_FETCH_TIMEOUT: Duration = Duration::from_secs(30);
+/// Feeds tokens are kept while the feed is failing up to the TTL
+const FEED_CACHE_TTL: Duration = Duration::from_secs(60 * 60 * 24 * 7);
+const LEADER_LOCK_KEY: &str = "scam_tokens/leader";
+
+fn feed_cache_key(url: &str) -> String {
+    format!("scam_tokens/feed/{url}")
+}
+
+/// Supported formats of the scam tokens feeds
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum ScamTokensFeed {
+    /// Token list of the https://tokenlists.org standard, the EVM tokens only
+    TokenList { tokens: Vec<TokenListEntry> },
+    /// CAIP-10 token addresses
+    Caip10(Vec<String>),
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TokenListEntry {
+    chain_id: u64,
+    address: String,
+}
+
+impl ScamTokensFeed {
+    /// Normalized CAIP-10 token addresses of the feed, the invalid addresses
+    /// are skipped
+    fn into_tokens(self) -> Vec<String> {
+        let caip10_addresses = match self {
+            Self::TokenList { tokens } => tokens
+                .into_iter()
+                .map(|token| format!("eip155:{}:{}", token.chain_id, token.address))
+                .collect(),
+            Self::Caip10(addresses) => addresses,
+        };
+        caip10_addresses
+            .iter()
+            .filter_map(|address| normalize(address))
+            .collect()
+    }
+}
+
+/// CAIP-10 token address in the denylist format, the EVM addresses are case
+/// insensitive and lowercased
+fn normalize(caip10_token_address: &str) -> Option<String> {
+    let (namespace, chain_id, address) =
+        crypto::disassemble_caip10(caip10_token_address.trim()).ok()?;
+    let address = match namespace {
+        CaipNamespaces::Eip155 | CaipNamespaces::Rootstock => address.to_lowercase(),
+        _ => address,
+    };
+    Some(format!("{namespace}:{chain_id}:{address}"))
+}
+
+/// Local denylist of the scam tokens by the normalized CAIP-10 address
+#[derive(Default)]
+pub struct ScamTokens {
+    tokens: ArcSwap<HashSet<String>>,
+}
+
+impl ScamTokens {
+    /// Denylist applied to the project responses, `None` when the project
+    /// opted out of the filtering
+    pub fn for_project(
+        &self,
+        config: &BalanceConfig,
+        project_id: &str,
+    ) -> Option<ScamTokensFilter> {
+        if config
+            .scam_tokens_opt_out_project_ids
+            .as_ref()
+            .is_some_and(|project_ids| project_ids.iter().any(|id| id == project_id))
+        {
+            return None;
+        }
+        Some(ScamTokensFilter(self.tokens.load_full()))
+    }
+
+    fn replace(&self, tokens: HashSet<String>) {
+        self.tokens.store(Arc::new(tokens));
+    }
+}
+
+/// Snapshot of the denylist for the single response
+pub struct ScamTokensFilter(Arc<HashSet<String>>);
+
+impl ScamTokensFilter {
+    pub fn is_scam(&self, caip10_token_address: &str) -> bool {
+        !self.0.is_empty()
+            && normalize(caip10_token_address).is_some_and(|token| self.0.contains(&token))
+    }
+}
+
+/// Background job refreshing the denylist from the configured feeds
+pub async fn run(state: Arc<AppState>) {
+    let refresh_interval = state
+        .config
+        .balances
+        .scam_tokens_refresh_interval_secs
+        .map(Duration::from_secs)
+        .unwrap_or(DEFAULT_REFRESH_INTERVAL);
+    let instance_id = Uuid::new_v4().to_string();
+    debug!(
+        ?refresh_interval,
+        instance_id, "starting scam tokens refresh"
+    );
+
+    let mut refresh = interval(refresh_interval);
+    refresh.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    loop {
+        refresh.tick().await;
+        // Feeds are read from the reloadable config to be changed without
+        // restarting
+        let feed_urls = state
+            .reloadable_config()
+            .balances
+            .scam_tokens_feed_urls
+            .clone()
+            .unwrap_or_default();
+        if feed_urls.is_empty() {
+            state.scam_tokens.replace(HashSet::new());
+            continue;
+        }
+
+        let Some(cache) = &state.scam_tokens_cache else {
+            // Every replica fetches the feeds without the shared storage
+            let feeds = join_all(feed_urls.iter().map(|url| fetch_feed(&state, url))).await;
+            let tokens = feeds
+                .into_iter()
+                .flatten()
+                .flatten()
+                .collect::<HashSet<_>>();
+            state.metrics.record_scam_tokens(tokens.len());
+            state.scam_tokens.replace(tokens);
+            continue;
+        };
+
+        if is_leader(&state, &instance_id, refresh_interval).await {
+            join_all(
+                feed_urls
+                    .iter()
+                    .map(|url| save_feed(&state, cache.as_ref(), url)),
+            )
+            .await;
+        }
+
+        let keys = feed_urls
+            .iter()
+            .map(|url| feed_cache_key(url))
+            .collect::<Vec<_>>();
+        match cache.mget(&keys).await {
+            Ok(feeds) => {
+                let tokens = feeds
+                    .into_iter()
+                    .flatten()
+                    .flatten()
+                    .collect::<HashSet<_>>();
+                state.metrics.record_scam_tokens(tokens.len());
+                state.scam_tokens.replace(tokens);
+            }
+            Err(e) => {
+                warn!("Failed to load the scam tokens feeds, keeping the current denylist: {e}")
+            }
+        }
+    }
+}
+
+/// Only the replica holding the lock fetches the feeds. Every replica is the
+/// leader when the lock storage is not configured.
+async fn is_leader(state: &AppState, instance_id: &str, refresh_interval: Duration) -> bool {
+    let Some(lock_storage) = &state.lock_storage else {
+        return true;
+    };
+    match lock_storage
+        .acquire_lock(LEADER_LOCK_KEY, instance_id, refresh_interval * 2)
+        .await
+    {
+        Ok(acquired) => acquired,
+        Err(e) => {
+            warn!(error = %e, "failed to acquire the scam tokens leader lock");
+            false
+        }
+    }
+}
+
+/// Saves the fetched feed tokens to the shared storage, the previously saved
+/// tokens are kept when the feed is failing
+async fn save_feed(state: &AppState, cache: &dyn KeyValueStorage<Vec<String>>, url: &str) {
+    let Some(tokens) = fetch_feed(state, url).await else {
+        return;
+    };
+    if let Err(e) = cache
+        .set(&feed_cache_key(url), &tokens, Some(FEED_CACHE_TTL))
+        .await
+    {
+        warn!("Failed to save the scam tokens feed {url}: {e}");
+    }
+}
+
+/// Fetches the feed tokens, `None` when the feed is unavailable or invalid
+async fn fetch_feed(state: &AppState, url: &str) -> Option<Vec<String>> {
+    let result = async {
+        state
+            .http_client
+            .get(url)
+            .timeout(FEED_FETCH_TIMEOUT)
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<ScamTokensFeed>()
+            .await
+    }
+    .await;
+    state.metrics.add_scam_tokens_feed_fetch(result.is_ok());
+    match result {
+        Ok(feed) => {
+            let tokens = feed.into_tokens();
+            debug!("Fetched {} scam tokens from the feed {url}", tokens.len());
+            Some(tokens)
+        }
+        Err(e) => {
+            warn!("Failed to fetch the scam tokens feed {url}: {e}");
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCAM_TOKEN: &str = "eip155:1:0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48";
+
+    fn config(opt_out_project_ids: Option<Vec<String>>) -> BalanceConfig {
+        BalanceConfig {
+            denylist_project_ids: None,
+            scam_tokens_feed_urls: None,
+            scam_tokens_opt_out_project_ids: opt_out_project_ids,
+            scam_tokens_refresh_interval_secs: None,
+        }
+    }
+
+    #[test]
+    fn parses_feeds() {
+        let token_list = serde_json::from_str::<ScamTokensFeed>(
+            r#"{"name":"Scam","tokens":[
+                {"chainId":1,"address":"0xA0b86991c6218b36c1d19d4a2e9eB0cE3606eB48","symbol":"USDC"},
+                {"chainId":10,"address":"invalid"}
+            ]}"#,
+        )
+        .unwrap();
+        assert_eq!(token_list.into_tokens(), vec![SCAM_TOKEN.to_owned()]);
+
+        let caip10 = serde_json::from_str::<ScamTokensFeed>(
+            r#"["solana:5eykt4UsFv8P8NJdTREpY1vzqKqZKvdp:EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v"]"#,
+        )
+        .unwrap();
+        assert_eq!(
+            caip10.into_tokens(),
+            vec!["solana:5eykt4UsFv8P8NJdTREpY1vzqKqZKvdp:EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v"]
+        );
+    }
+
+    #[test]
+    fn filters_denylisted_tokens() {
+        let scam_tokens = ScamTokens::default();
+        scam_tokens.replace(HashSet::from([SCAM_TOKEN.to_owned()]));
+
+        let filter = scam_tokens.for_project(&config(None), "project").unwrap();
+        assert!(filter.is_scam("eip155:1:0xA0b86991c6218b36c1d19d4a2e9eB0cE3606eB48"));
+        assert!(!filter.is_scam("eip155:10:0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48"));
+        assert!(!filter.is_scam("invalid"));
+
+        let opt_out = config(Some(vec!["project".to_owned()]));
+        assert!(scam_tokens.for_project(&opt_out, "project").is_none());
+        assert!(scam_tokens.for_project(&opt_out, "other").is_some());
+    }
+}
@@ -0,0 +1,216 @@
+use {
+    crate::{
+        env::Config,
+        error::{RpcError, RpcResult},
+    },
+    aws_config::{BehaviorVersion, SdkConfig},
+    aws_sdk_secretsmanager::{config::Region, error::DisplayErrorContext, Client},
+    std::collections::HashMap,
+    tracing::info,
+};
+
+/// Prefix of the config values referencing the AWS Secrets Manager secrets
+const SECRET_ARN_PREFIX: &str = "arn:aws:secretsmanager:";
+
+/// AWS Secrets Manager secret reference in the `<arn>[#json_key]` format
+#[derive(Debug, PartialEq, Eq)]
+struct SecretReference<'a> {
+    arn: &'a str,
+    region: &'a str,
+    /// Field of the JSON secret, the whole secret string is used when not set
+    json_key: Option<&'a str>,
+}
+
+impl<'a> SecretReference<'a> {
+    fn parse(value: &'a str) -> Option<Self> {
+        if !value.starts_with(SECRET_ARN_PREFIX) {
+            return None;
+        }
+        let (arn, json_key) = match value.split_once('#') {
+            Some((arn, json_key)) => (arn, Some(json_key)),
+            None => (value, None),
+        };
+        // arn:aws:secretsmanager:<region>:<account>:secret:<name>
+        let region = arn.split(':').nth(3).filter(|region| !region.is_empty())?;
+        Some(Self {
+            arn,
+            region,
+            json_key,
+        })
+    }
+}
+
+/// Replaces the provider and exchange keys referenced as the AWS Secrets
+/// Manager ARNs with the secret values. The AWS config is not loaded when
+/// there are no references.
+pub async fn resolve(config: &mut Config) -> RpcResult<()> {
+    let references = secret_values(config)
+        .into_iter()
+        .filter(|value| value.starts_with(SECRET_ARN_PREFIX))
+        .collect::<Vec<_>>();
+    if references.is_empty() {
+        return Ok(());
+    }
+
+    let mut resolver = SecretsResolver {
+        sdk_config: aws_config::defaults(BehaviorVersion::latest()).load().await,
+        clients: HashMap::new(),
+        secrets: HashMap::new(),
+    };
+    let count = references.len();
+    for value in references {
+        let reference = SecretReference::parse(value).ok_or_else(|| {
+            RpcError::InvalidConfiguration(format!("invalid secret reference: {value}"))
+        })?;
+        *value = resolver.resolve(&reference).await?;
+    }
+    info!("Resolved {count} secrets from the AWS Secrets Manager");
+    Ok(())
+}
+
+struct SecretsResolver {
+    sdk_config: SdkConfig,
+    /// Clients by the secret region
+    clients: HashMap<String, Client>,
+    /// Secret strings by the ARN, JSON secrets are fetched once for all keys
+    secrets: HashMap<String, String>,
+}
+
+impl SecretsResolver {
+    async fn resolve(&mut self, reference: &SecretReference<'_>) -> RpcResult<String> {
+        let secret = match self.secrets.get(reference.arn) {
+            Some(secret) => secret.clone(),
+            None => {
+                let secret = self.fetch(reference).await?;
+                self.secrets
+                    .insert(reference.arn.to_owned(), secret.clone());
+                secret
+            }
+        };
+
+        let Some(json_key) = reference.json_key else {
+            return Ok(secret);
+        };
+        serde_json::from_str::<HashMap<String, String>>(&secret)
+            .map_err(|e| {
+                RpcError::InvalidConfiguration(format!(
+                    "secret {} is not a JSON object: {e}",
+                    reference.arn
+                ))
+            })?
+            .remove(json_key)
+            .ok_or_else(|| {
+                RpcError::InvalidConfiguration(format!(
+                    "secret {} has no {json_key} key",
+                    reference.arn
+                ))
+            })
+    }
+
+    async fn fetch(&mut self, reference: &SecretReference<'_>) -> RpcResult<String> {
+        let client = self
+            .clients
+            .entry(reference.region.to_owned())
+            .or_insert_with(|| {
+                Client::from_conf(
+                    aws_sdk_secretsmanager::config::Builder::from(&self.sdk_config)
+                        .region(Region::new(reference.region.to_owned()))
+                        .build(),
+                )
+            });
+        let output = client
+            .get_secret_value()
+            .secret_id(reference.arn)
+            .send()
+            .await
+            .map_err(|e| {
+                RpcError::InvalidConfiguration(format!(
+                    "failed to get the secret {}: {}",
+                    reference.arn,
+                    DisplayErrorContext(&e)
+                ))
+            })?;
+        output
+            .secret_string()
+            .map(ToOwned::to_owned)
+            .ok_or_else(|| {
+                RpcError::InvalidConfiguration(format!(
+                    "secret {} has no string value",
+                    reference.arn
+                ))
+            })
+    }
+}
+
+/// Config values that can be referenced as the secrets
+fn secret_values(config: &mut Config) -> Vec<&mut String> {
+    let providers = &mut config.providers;
+    let exchanges = &mut config.exchanges;
+    let mut values = vec![
+        &mut providers.pokt_project_id,
+        &mut providers.quicknode_api_tokens,
+        &mut providers.zerion_api_key,
+        &mut providers.pimlico_api_key,
+        &mut providers.solscan_api_v2_token,
+        &mut providers.bungee_api_key,
+        &mut providers.tenderly_api_key,
+        &mut providers.dune_sim_api_key,
+        &mut providers.syndica_api_key,
+        &mut providers.allnodes_api_key,
+        &mut providers.meld_api_key,
+        &mut providers.callstatic_api_key,
+        &mut providers.blast_api_key,
+    ];
+    values.extend(
+        [
+            &mut providers.coinbase_api_key,
+            &mut providers.moonpay_secret_key,
+            &mut providers.ramp_secret_key,
+            &mut providers.one_inch_api_key,
+            &mut providers.lifi_api_key,
+            &mut providers.coingecko_api_key,
+            &mut providers.biconomy_api_key,
+            &mut providers.alchemy_api_key,
+            &mut providers.toncenter_api_key,
+            &mut exchanges.internal_api_coinbase_credentials,
+            &mut exchanges.binance_token,
+            &mut exchanges.binance_key,
+            &mut exchanges.coinbase_webhook_secret,
+            &mut exchanges.okx_api_key,
+            &mut exchanges.okx_secret_key,
+            &mut exchanges.okx_passphrase,
+            &mut config.server.webhook_signing_secret,
+        ]
+        .into_iter()
+        .flatten(),
+    );
+    values
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_secret_references() {
+        let arn = "arn:aws:secretsmanager:eu-central-1:123456789012:secret:rpc/providers-AbCdEf";
+        assert_eq!(
+            SecretReference::parse(arn),
+            Some(SecretReference {
+                arn,
+                region: "eu-central-1",
+                json_key: None,
+            })
+        );
+        assert_eq!(
+            SecretReference::parse(&format!("{arn}#pimlico")),
+            Some(SecretReference {
+                arn,
+                region: "eu-central-1",
+                json_key: Some("pimlico"),
+            })
+        );
+        assert_eq!(SecretReference::parse("raw-api-key"), None);
+        assert_eq!(SecretReference::parse("arn:aws:secretsmanager::"), None);
+    }
+}
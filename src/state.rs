@@ -1,27 +1,98 @@
 use {
     crate::{
         analytics::RPCAnalytics,
+        chain_quarantine::ChainQuarantine,
+        database::{
+            project_chains,
+            project_countries::{self, ProjectCountries},
+            project_ips, project_jwt_keys,
+        },
         env::Config,
         error::RpcError,
-        handlers::{balance::BalanceResponseBody, identity::IdentityResponse},
+        handlers::{
+            balance::{BalanceResponseBody, Config as BalanceConfig},
+            bundler::CachedGasPrice,
+            identity::IdentityResponse,
+            json_rpc::{
+                exchanges::{Config as ExchangesConfig, ExchangeAsset},
+                pos::PosQuote,
+            },
+        },
         metrics::Metrics,
         project::{ProjectDataError, Registry},
-        providers::ProviderRepository,
-        storage::{irn::Irn, KeyValueStorage},
-        utils::{build::CompileInfo, rate_limit::RateLimit},
+        providers::{ProviderRepository, ProvidersConfig},
+        scam_tokens::ScamTokens,
+        storage::{redis::Redis, KeyValueStorage, LockStorage, PersistentStorage},
+        token_metadata_backfill::TokenMetadataBackfill,
+        usage::UsageAggregator,
+        utils::{
+            build::CompileInfo,
+            eth_call_cache::EthCallCache,
+            geo_restrictions::GeoRestrictions,
+            network,
+            project_jwt::{self, ProjectJwtClaims},
+            rate_limit::RateLimit,
+        },
+        ws::WsSessions,
     },
+    arc_swap::ArcSwap,
     cerberus::project::ProjectDataWithLimits,
+    ipnet::IpNet,
     moka::future::Cache,
     sqlx::PgPool,
-    std::sync::Arc,
+    std::{
+        net::IpAddr,
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            Arc,
+        },
+        time::Duration,
+    },
     tap::TapFallible,
     tracing::{debug, error},
 };
 
+/// Projects chains allowlist local cache TTL
+const PROJECT_CHAINS_CACHE_TTL: Duration = Duration::from_secs(60);
+/// Projects IP ranges allowlist local cache TTL
+const PROJECT_IPS_CACHE_TTL: Duration = Duration::from_secs(60);
+/// Projects countries policy local cache TTL
+const PROJECT_COUNTRIES_CACHE_TTL: Duration = Duration::from_secs(60);
+/// Verified project JWTs local cache TTL, revoked project keys are valid for
+/// the cached tokens up to this TTL
+const PROJECT_JWT_CACHE_TTL: Duration = Duration::from_secs(300);
+/// Projects JWT public keys local cache TTL
+const PROJECT_JWT_KEYS_CACHE_TTL: Duration = Duration::from_secs(60);
+
+/// Subset of the runtime configuration that is reloaded without restarting
+#[derive(Debug, Clone)]
+pub struct ReloadableConfig {
+    pub blocked_countries: Vec<String>,
+    pub geo_restrictions: GeoRestrictions,
+    pub balances: BalanceConfig,
+    pub exchanges: ExchangesConfig,
+    /// Providers are re-initialized only when this config is changed
+    pub providers: ProvidersConfig,
+}
+
+impl From<&Config> for ReloadableConfig {
+    fn from(config: &Config) -> Self {
+        Self {
+            blocked_countries: config.server.blocked_countries.clone(),
+            geo_restrictions: GeoRestrictions::from_entries(&config.server.geo_restrictions),
+            balances: config.balances.clone(),
+            exchanges: config.exchanges.clone(),
+            providers: config.providers.clone(),
+        }
+    }
+}
+
 pub struct AppState {
     pub config: Config,
     pub postgres: PgPool,
-    pub providers: ProviderRepository,
+    // Providers are swapped on the configuration reload
+    providers: ArcSwap<ProviderRepository>,
+    reloadable: ArcSwap<ReloadableConfig>,
     pub metrics: Arc<Metrics>,
     pub registry: Registry,
     pub analytics: RPCAnalytics,
@@ -32,13 +103,50 @@ pub struct AppState {
     pub http_client: reqwest::Client,
     // Rate limiting checks
     pub rate_limit: Option<RateLimit>,
-    // IRN client
-    pub irn: Option<Irn>,
+    // Persistent storage, IRN client or the Redis fallback
+    pub persistent_storage: Option<Arc<dyn PersistentStorage>>,
     // Redis caching
     pub identity_cache: Option<Arc<dyn KeyValueStorage<IdentityResponse>>>,
     pub balance_cache: Option<Arc<dyn KeyValueStorage<BalanceResponseBody>>>,
+    pub pos_quote_cache: Option<Arc<dyn KeyValueStorage<PosQuote>>>,
+    pub exchange_assets_cache: Option<Arc<dyn KeyValueStorage<Vec<ExchangeAsset>>>>,
+    pub bundler_gas_price_cache: Option<Arc<dyn KeyValueStorage<CachedGasPrice>>>,
+    // Serialized chain abstraction routes by the request hash
+    pub ca_route_cache: Option<Arc<dyn KeyValueStorage<String>>>,
+    // Scam tokens of the external feeds by the feed URL
+    pub scam_tokens_cache: Option<Arc<dyn KeyValueStorage<Vec<String>>>>,
+    // Redis distributed locks for the background jobs leader election
+    pub lock_storage: Option<Arc<dyn LockStorage>>,
+    // Redis connectivity checks for the readiness probe
+    pub project_data_redis: Option<Arc<Redis>>,
+    // Per project usage counters for the usage reporting
+    pub usage: Option<Arc<UsageAggregator>>,
     // Moka local instance in-memory cache
     pub moka_cache: Cache<String, String>,
+    // Local `eth_call` results cache when enabled
+    pub eth_call_cache: Option<EthCallCache>,
+    // Projects chains allowlist local cache
+    pub project_chains_cache: Cache<String, Arc<Vec<String>>>,
+    // Projects IP ranges allowlist local cache
+    pub project_ips_cache: Cache<String, Arc<Vec<IpNet>>>,
+    // Projects countries policy local cache
+    pub project_countries_cache: Cache<String, Arc<ProjectCountries>>,
+    // Verified project JWTs local cache by the token hash
+    pub project_jwt_cache: Cache<String, ProjectJwtClaims>,
+    // Projects JWT public keys local cache
+    pub project_jwt_keys_cache: Cache<String, Arc<Vec<String>>>,
+    // Addresses of the recent forced identity refreshes
+    pub identity_refreshes: Cache<String, ()>,
+    // Tokens with the missing metadata queued for the backfill
+    pub token_metadata_backfill: TokenMetadataBackfill,
+    // Startup gate of the readiness probe
+    started: AtomicBool,
+    // Active WebSocket proxy sessions drained on the shutdown
+    pub ws_sessions: Arc<WsSessions>,
+    // Chains with all providers failing, responded without the providers calls
+    pub chain_quarantine: ChainQuarantine,
+    // Scam tokens denylist filtering the balance, history and price responses
+    pub scam_tokens: ScamTokens,
 }
 
 #[allow(clippy::too_many_arguments)]
@@ -51,15 +159,46 @@ pub fn new_state(
     analytics: RPCAnalytics,
     http_client: reqwest::Client,
     rate_limit: Option<RateLimit>,
-    irn: Option<Irn>,
+    persistent_storage: Option<Arc<dyn PersistentStorage>>,
     identity_cache: Option<Arc<dyn KeyValueStorage<IdentityResponse>>>,
     balance_cache: Option<Arc<dyn KeyValueStorage<BalanceResponseBody>>>,
+    pos_quote_cache: Option<Arc<dyn KeyValueStorage<PosQuote>>>,
+    exchange_assets_cache: Option<Arc<dyn KeyValueStorage<Vec<ExchangeAsset>>>>,
+    bundler_gas_price_cache: Option<Arc<dyn KeyValueStorage<CachedGasPrice>>>,
+    ca_route_cache: Option<Arc<dyn KeyValueStorage<String>>>,
+    scam_tokens_cache: Option<Arc<dyn KeyValueStorage<Vec<String>>>>,
+    lock_storage: Option<Arc<dyn LockStorage>>,
+    project_data_redis: Option<Arc<Redis>>,
+    usage: Option<Arc<UsageAggregator>>,
 ) -> AppState {
     let moka_cache = Cache::builder().build();
+    let eth_call_cache = (config.server.eth_call_cache_max_capacity > 0).then(|| {
+        EthCallCache::new(
+            config.server.eth_call_cache_max_capacity,
+            Duration::from_secs(config.server.eth_call_cache_block_ttl_secs),
+        )
+    });
+    let project_chains_cache = Cache::builder()
+        .time_to_live(PROJECT_CHAINS_CACHE_TTL)
+        .build();
+    let project_ips_cache = Cache::builder().time_to_live(PROJECT_IPS_CACHE_TTL).build();
+    let project_countries_cache = Cache::builder()
+        .time_to_live(PROJECT_COUNTRIES_CACHE_TTL)
+        .build();
+    let project_jwt_cache = Cache::builder().time_to_live(PROJECT_JWT_CACHE_TTL).build();
+    let project_jwt_keys_cache = Cache::builder()
+        .time_to_live(PROJECT_JWT_KEYS_CACHE_TTL)
+        .build();
+    let identity_refreshes = Cache::builder()
+        .time_to_live(config.storage.identity_refresh_interval())
+        .build();
+    let chain_quarantine = ChainQuarantine::new(config.server.chain_quarantine_threshold);
+    let reloadable = ArcSwap::from_pointee(ReloadableConfig::from(&config));
     AppState {
         config,
         postgres,
-        providers,
+        providers: ArcSwap::from_pointee(providers),
+        reloadable,
         metrics,
         registry,
         analytics,
@@ -67,16 +206,62 @@ pub fn new_state(
         uptime: std::time::Instant::now(),
         http_client,
         rate_limit,
-        irn,
+        persistent_storage,
         identity_cache,
         balance_cache,
+        pos_quote_cache,
+        exchange_assets_cache,
+        bundler_gas_price_cache,
+        ca_route_cache,
+        scam_tokens_cache,
+        lock_storage,
+        project_data_redis,
+        usage,
         moka_cache,
+        eth_call_cache,
+        project_chains_cache,
+        project_ips_cache,
+        project_countries_cache,
+        project_jwt_cache,
+        project_jwt_keys_cache,
+        identity_refreshes,
+        token_metadata_backfill: TokenMetadataBackfill::default(),
+        started: AtomicBool::new(false),
+        ws_sessions: Arc::new(WsSessions::default()),
+        chain_quarantine,
+        scam_tokens: ScamTokens::default(),
     }
 }
 
 impl AppState {
+    pub fn providers(&self) -> Arc<ProviderRepository> {
+        self.providers.load_full()
+    }
+
+    pub fn reloadable_config(&self) -> Arc<ReloadableConfig> {
+        self.reloadable.load_full()
+    }
+
+    /// Swaps the providers and the reloadable configuration, requests in
+    /// flight are finished with the previous values
+    pub fn reload(&self, providers: Option<ProviderRepository>, reloadable: ReloadableConfig) {
+        if let Some(providers) = providers {
+            self.providers.store(Arc::new(providers));
+        }
+        self.reloadable.store(Arc::new(reloadable));
+    }
+
+    /// Opens the readiness gate once the startup initialization is finished
+    pub fn mark_started(&self) {
+        self.started.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_started(&self) -> bool {
+        self.started.load(Ordering::Relaxed)
+    }
+
     pub async fn update_provider_weights(&self) {
-        self.providers.update_weights(&self.metrics).await;
+        self.providers().update_weights(&self.metrics).await;
     }
 
     #[tracing::instrument(skip(self), level = "debug")]
@@ -144,6 +329,178 @@ impl AppState {
             self.metrics.add_quota_limited_project();
         })
     }
+
+    /// Verifies the project JWT and returns the project ID of the token.
+    /// Returns `None` when the token is not a project JWT.
+    #[tracing::instrument(skip_all, level = "debug")]
+    pub async fn validate_project_jwt(&self, token: &str) -> Result<Option<String>, RpcError> {
+        let Some(unverified) = project_jwt::decode_unverified(token) else {
+            return Ok(None);
+        };
+
+        let cache_key = sha256::digest(token);
+        let now = chrono::Utc::now().timestamp() as u64;
+        if let Some(claims) = self.project_jwt_cache.get(&cache_key).await {
+            if claims.exp > now {
+                return Ok(Some(claims.sub));
+            }
+        }
+
+        // Unlike the IP and chains allowlists, the keys lookup errors are not
+        // skipped as the token can't be verified without the keys
+        let public_keys = self
+            .project_jwt_keys_cache
+            .try_get_with(unverified.sub.clone(), async {
+                project_jwt_keys::get_public_keys(&self.postgres, &unverified.sub)
+                    .await
+                    .map(Arc::new)
+            })
+            .await
+            .map_err(|e| {
+                error!(
+                    "Failed to get the JWT public keys for project: {}: {e}",
+                    unverified.sub
+                );
+                RpcError::InvalidProjectJwt("failed to get the project keys".to_owned())
+            })?;
+        let claims = project_jwt::verify(token, &unverified.sub, &public_keys).tap_err(|e| {
+            debug!(
+                "Denied project JWT for project: {}, with reason: {e}",
+                unverified.sub
+            );
+            self.metrics.add_rejected_project();
+        })?;
+        self.project_jwt_cache
+            .insert(cache_key, claims.clone())
+            .await;
+        Ok(Some(claims.sub))
+    }
+
+    /// Validates the chain is in the project's chains allowlist, projects
+    /// without the allowlist are allowed to access all chains
+    #[tracing::instrument(skip(self), level = "debug")]
+    pub async fn validate_project_chain(
+        &self,
+        project_id: &str,
+        chain_id: &str,
+    ) -> Result<(), RpcError> {
+        if !self.config.server.validate_project_id {
+            return Ok(());
+        }
+
+        let allowed_chains = self
+            .project_chains_cache
+            .try_get_with(project_id.to_owned(), async {
+                project_chains::get_allowed_chains(&self.postgres, project_id)
+                    .await
+                    .map(Arc::new)
+            })
+            .await;
+        match allowed_chains {
+            Ok(allowed_chains) => {
+                if allowed_chains.is_empty() || allowed_chains.iter().any(|c| c == chain_id) {
+                    Ok(())
+                } else {
+                    debug!("Denied access for project: {project_id} to the chain: {chain_id}");
+                    self.metrics.add_rejected_project();
+                    Err(RpcError::ChainNotAllowed(chain_id.to_owned()))
+                }
+            }
+            Err(e) => {
+                error!(
+                    "Failed to get the chains allowlist, skipping the chain check for project: \
+                     {project_id}: {e}"
+                );
+                Ok(())
+            }
+        }
+    }
+
+    /// Validates the request source IP is in the project's IP ranges
+    /// allowlist, projects without the allowlist are allowed to make requests
+    /// from any IP address and the requests with the unknown source IP are
+    /// rejected for the projects with the allowlist
+    #[tracing::instrument(skip(self), level = "debug")]
+    pub async fn validate_project_ip(
+        &self,
+        project_id: &str,
+        ip: Option<IpAddr>,
+    ) -> Result<(), RpcError> {
+        if !self.config.server.validate_project_id {
+            return Ok(());
+        }
+
+        let allowed_ranges = self
+            .project_ips_cache
+            .try_get_with(project_id.to_owned(), async {
+                project_ips::get_allowed_ip_ranges(&self.postgres, project_id)
+                    .await
+                    .map(Arc::new)
+            })
+            .await;
+        match allowed_ranges {
+            Ok(allowed_ranges) => {
+                if allowed_ranges.is_empty()
+                    || ip.is_some_and(|ip| network::is_ip_in_ranges(&allowed_ranges, ip))
+                {
+                    Ok(())
+                } else {
+                    let ip = ip.map_or_else(|| "unknown".to_owned(), |ip| ip.to_string());
+                    debug!("Denied access for project: {project_id} from the IP: {ip}");
+                    self.metrics.add_rejected_project();
+                    Err(RpcError::IpNotAllowed(ip))
+                }
+            }
+            Err(e) => {
+                error!(
+                    "Failed to get the IP ranges allowlist, skipping the IP check for project: \
+                     {project_id}: {e}"
+                );
+                Ok(())
+            }
+        }
+    }
+
+    /// Validates the request country by the project's countries policy, the
+    /// policy is evaluated after the global blocked countries
+    #[tracing::instrument(skip(self), level = "debug")]
+    pub async fn validate_project_country(
+        &self,
+        project_id: &str,
+        country: Option<&str>,
+    ) -> Result<(), RpcError> {
+        if !self.config.server.validate_project_id {
+            return Ok(());
+        }
+
+        let countries = self
+            .project_countries_cache
+            .try_get_with(project_id.to_owned(), async {
+                project_countries::get_project_countries(&self.postgres, project_id)
+                    .await
+                    .map(Arc::new)
+            })
+            .await;
+        match countries {
+            Ok(countries) => {
+                if countries.is_allowed(country) {
+                    Ok(())
+                } else {
+                    let country = country.unwrap_or("unknown").to_owned();
+                    debug!("Denied access for project: {project_id} from the country: {country}");
+                    self.metrics.add_rejected_project();
+                    Err(RpcError::CountryNotAllowed(country))
+                }
+            }
+            Err(e) => {
+                error!(
+                    "Failed to get the countries policy, skipping the country check for \
+                     project: {project_id}: {e}"
+                );
+                Ok(())
+            }
+        }
+    }
 }
 
 #[tracing::instrument(level = "debug")]
@@ -0,0 +1,200 @@
+use {
+    super::{PersistentStorage, StorageResult},
+    async_trait::async_trait,
+    std::{sync::Arc, time::Duration},
+    tracing::warn,
+};
+
+/// Persistent storage writing to both of the storages during the migration
+/// between them. Reads are served by the primary storage, the single values
+/// missing in the primary are read from the secondary when the read fallback
+/// is enabled. The hashmaps are scanned from the primary only, so the
+/// existing records must be copied before the primary is switched.
+pub struct DualStorage {
+    primary: Arc<dyn PersistentStorage>,
+    secondary: Arc<dyn PersistentStorage>,
+    read_fallback: bool,
+}
+
+impl DualStorage {
+    pub fn new(
+        primary: Arc<dyn PersistentStorage>,
+        secondary: Arc<dyn PersistentStorage>,
+        read_fallback: bool,
+    ) -> Self {
+        Self {
+            primary,
+            secondary,
+            read_fallback,
+        }
+    }
+}
+
+#[async_trait]
+impl PersistentStorage for DualStorage {
+    async fn set(&self, key: String, value: Vec<u8>, ttl: Duration) -> StorageResult<()> {
+        self.primary.set(key.clone(), value.clone(), ttl).await?;
+        if let Err(e) = self.secondary.set(key.clone(), value, ttl).await {
+            warn!(key, error = %e, "failed to set the secondary storage value");
+        }
+        Ok(())
+    }
+
+    async fn get(&self, key: String) -> StorageResult<Option<Vec<u8>>> {
+        match self.primary.get(key.clone()).await? {
+            None if self.read_fallback => self.secondary.get(key).await,
+            value => Ok(value),
+        }
+    }
+
+    async fn delete(&self, key: String) -> StorageResult<()> {
+        self.primary.delete(key.clone()).await?;
+        if let Err(e) = self.secondary.delete(key.clone()).await {
+            warn!(key, error = %e, "failed to delete the secondary storage value");
+        }
+        Ok(())
+    }
+
+    async fn hset(
+        &self,
+        key: String,
+        field: String,
+        value: Vec<u8>,
+        ttl: Duration,
+    ) -> StorageResult<()> {
+        self.primary
+            .hset(key.clone(), field.clone(), value.clone(), ttl)
+            .await?;
+        if let Err(e) = self
+            .secondary
+            .hset(key.clone(), field.clone(), value, ttl)
+            .await
+        {
+            warn!(key, field, error = %e, "failed to set the secondary storage hashmap value");
+        }
+        Ok(())
+    }
+
+    async fn hget(&self, key: String, field: String) -> StorageResult<Option<Vec<u8>>> {
+        match self.primary.hget(key.clone(), field.clone()).await? {
+            None if self.read_fallback => self.secondary.hget(key, field).await,
+            value => Ok(value),
+        }
+    }
+
+    async fn hdel(&self, key: String, field: String) -> StorageResult<()> {
+        self.primary.hdel(key.clone(), field.clone()).await?;
+        if let Err(e) = self.secondary.hdel(key.clone(), field.clone()).await {
+            warn!(key, field, error = %e, "failed to delete the secondary storage hashmap value");
+        }
+        Ok(())
+    }
+
+    async fn hscan(
+        &self,
+        key: String,
+        count: u32,
+        cursor: Option<Vec<u8>>,
+    ) -> StorageResult<(Vec<(String, Vec<u8>)>, Option<Vec<u8>>)> {
+        self.primary.hscan(key, count, cursor).await
+    }
+
+    async fn purge_expired(&self) -> StorageResult<u64> {
+        let purged = self.primary.purge_expired().await?;
+        Ok(purged + self.secondary.purge_expired().await?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        std::{collections::HashMap, sync::Mutex},
+    };
+
+    /// In-memory storage without the expiration
+    #[derive(Default)]
+    struct MemoryStorage {
+        values: Mutex<HashMap<String, Vec<u8>>>,
+    }
+
+    #[async_trait]
+    impl PersistentStorage for MemoryStorage {
+        async fn set(&self, key: String, value: Vec<u8>, _ttl: Duration) -> StorageResult<()> {
+            self.values.lock().unwrap().insert(key, value);
+            Ok(())
+        }
+
+        async fn get(&self, key: String) -> StorageResult<Option<Vec<u8>>> {
+            Ok(self.values.lock().unwrap().get(&key).cloned())
+        }
+
+        async fn delete(&self, key: String) -> StorageResult<()> {
+            self.values.lock().unwrap().remove(&key);
+            Ok(())
+        }
+
+        async fn hset(
+            &self,
+            key: String,
+            field: String,
+            value: Vec<u8>,
+            ttl: Duration,
+        ) -> StorageResult<()> {
+            self.set(format!("{key}/{field}"), value, ttl).await
+        }
+
+        async fn hget(&self, key: String, field: String) -> StorageResult<Option<Vec<u8>>> {
+            self.get(format!("{key}/{field}")).await
+        }
+
+        async fn hdel(&self, key: String, field: String) -> StorageResult<()> {
+            self.delete(format!("{key}/{field}")).await
+        }
+
+        async fn hscan(
+            &self,
+            _key: String,
+            _count: u32,
+            _cursor: Option<Vec<u8>>,
+        ) -> StorageResult<(Vec<(String, Vec<u8>)>, Option<Vec<u8>>)> {
+            Ok((Vec::new(), None))
+        }
+    }
+
+    const TTL: Duration = Duration::from_secs(60);
+
+    #[tokio::test]
+    async fn writes_both_and_reads_with_fallback() {
+        let primary = Arc::new(MemoryStorage::default());
+        let secondary = Arc::new(MemoryStorage::default());
+        let storage = DualStorage::new(primary.clone(), secondary.clone(), true);
+
+        storage
+            .set("key".into(), b"value".to_vec(), TTL)
+            .await
+            .unwrap();
+        assert_eq!(
+            secondary.get("key".into()).await.unwrap(),
+            Some(b"value".to_vec())
+        );
+
+        // Records written before the dual writes are read from the secondary
+        secondary
+            .hset("map".into(), "field".into(), b"old".to_vec(), TTL)
+            .await
+            .unwrap();
+        assert_eq!(
+            storage.hget("map".into(), "field".into()).await.unwrap(),
+            Some(b"old".to_vec())
+        );
+        let storage = DualStorage::new(primary, secondary.clone(), false);
+        assert_eq!(
+            storage.hget("map".into(), "field".into()).await.unwrap(),
+            None
+        );
+
+        storage.delete("key".into()).await.unwrap();
+        assert_eq!(secondary.get("key".into()).await.unwrap(), None);
+    }
+}
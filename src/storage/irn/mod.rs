@@ -1,7 +1,15 @@
 use {
-    super::StorageError,
+    super::{PersistentStorage, StorageError},
+    crate::metrics::Metrics,
+    async_trait::async_trait,
     serde::Deserialize,
-    std::{collections::HashSet, str::FromStr, time::Duration},
+    std::{
+        collections::HashSet,
+        future::Future,
+        str::FromStr,
+        sync::Arc,
+        time::{Duration, SystemTime},
+    },
     wc::metrics::{self, enum_ordinalize::Ordinalize, Enum},
     wcn_replication::{
         auth::{client_key_from_secret, peer_id, PublicKey},
@@ -13,7 +21,6 @@ use {
 
 const MAX_OPERATION_TIME: Duration = Duration::from_secs(3);
 const CONNECTION_TIMEOUT: Duration = Duration::from_secs(3);
-const RECORDS_TTL: Duration = Duration::from_secs(60 * 60 * 24 * 30); // 30 days
 
 /// IRN storage operation type
 #[derive(Clone, Copy, Debug, Ordinalize)]
@@ -24,6 +31,7 @@ pub enum OperationType {
     Hdel,
     Set,
     Get,
+    Del,
 }
 
 impl metrics::Enum for OperationType {
@@ -35,6 +43,7 @@ impl metrics::Enum for OperationType {
             OperationType::Hdel => "hdel",
             OperationType::Set => "set",
             OperationType::Get => "get",
+            OperationType::Del => "del",
         }
     }
 }
@@ -51,12 +60,28 @@ pub struct Config {
     pub key: Option<String>,
     pub namespace: Option<String>,
     pub namespace_secret: Option<String>,
+    /// Migration of the IRN records to the Postgres persistent storage
+    pub migration_mode: Option<MigrationMode>,
+}
+
+/// Stages of the persistent storage migration from the IRN to Postgres
+#[derive(Debug, Clone, Copy, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum MigrationMode {
+    /// Writes to both, reads from the IRN
+    DualWrite,
+    /// Writes to both, reads from Postgres falling back to the IRN for the
+    /// records missing in Postgres
+    DualRead,
+    /// Postgres only, the IRN is not used
+    Postgres,
 }
 
 #[derive(Clone)]
 pub struct Irn {
     driver: Driver,
     namespace: PublicKey,
+    metrics: Arc<Metrics>,
 }
 
 impl Irn {
@@ -65,6 +90,7 @@ impl Irn {
         nodes: Vec<String>,
         namespace: String,
         namespace_secret: String,
+        metrics: Arc<Metrics>,
     ) -> Result<Self, StorageError> {
         let client_key =
             client_key_from_secret(key.as_bytes()).map_err(StorageError::WcnAuthError)?;
@@ -98,6 +124,7 @@ impl Irn {
         Ok(Self {
             driver,
             namespace: namespace.public_key(),
+            metrics,
         })
     }
 
@@ -116,95 +143,134 @@ impl Irn {
         Key::private(&self.namespace, key)
     }
 
+    /// Record the operation latency and the error if the operation failed
+    async fn measure<T>(
+        &self,
+        operation: OperationType,
+        future: impl Future<Output = Result<T, StorageError>>,
+    ) -> Result<T, StorageError> {
+        let start = SystemTime::now();
+        let result = future.await;
+        self.metrics.add_irn_latency(start, operation);
+        if let Err(e) = &result {
+            self.metrics.add_irn_error(operation);
+            tracing::warn!(operation = operation.as_str(), error = %e, "IRN operation failed");
+        }
+        result
+    }
+}
+
+#[async_trait]
+impl PersistentStorage for Irn {
     /// Set a value in the storage
-    pub async fn set(&self, key: String, value: Vec<u8>) -> Result<(), StorageError> {
-        self.driver
-            .set(Entry::new(
-                self.key(key.as_bytes().into()),
-                value,
-                RECORDS_TTL,
-            ))
-            .await
-            .map_err(StorageError::WcnClientError)
+    async fn set(&self, key: String, value: Vec<u8>, ttl: Duration) -> Result<(), StorageError> {
+        let entry = Entry::new(self.key(key.as_bytes().into()), value, ttl);
+        self.measure(OperationType::Set, async {
+            self.driver
+                .set(entry)
+                .await
+                .map_err(StorageError::WcnClientError)
+        })
+        .await
     }
 
     /// Get a value from the storage
-    pub async fn get(&self, key: String) -> Result<Option<Vec<u8>>, StorageError> {
-        let result = self.driver.get(self.key(key.as_bytes().into())).await;
-
-        match result {
-            Ok(Some(record)) => Ok(Some(record.value)),
-            Ok(None) => Ok(None),
-            Err(e) => Err(e.into()),
-        }
+    async fn get(&self, key: String) -> Result<Option<Vec<u8>>, StorageError> {
+        self.measure(OperationType::Get, async {
+            let result = self.driver.get(self.key(key.as_bytes().into())).await;
+
+            match result {
+                Ok(Some(record)) => Ok(Some(record.value)),
+                Ok(None) => Ok(None),
+                Err(e) => Err(e.into()),
+            }
+        })
+        .await
     }
 
     /// Delete a value from the storage
-    pub async fn delete(&self, key: String) -> Result<(), StorageError> {
-        self.driver
-            .del(self.key(key.as_bytes().into()))
-            .await
-            .map_err(StorageError::WcnClientError)
+    async fn delete(&self, key: String) -> Result<(), StorageError> {
+        self.measure(OperationType::Del, async {
+            self.driver
+                .del(self.key(key.as_bytes().into()))
+                .await
+                .map_err(StorageError::WcnClientError)
+        })
+        .await
     }
 
     /// Set the hasmap value in the storage
-    pub async fn hset(
+    async fn hset(
         &self,
         key: String,
         field: String,
         value: Vec<u8>,
+        ttl: Duration,
     ) -> Result<(), StorageError> {
-        self.driver
-            .hset(MapEntry::new(
-                self.key(key.as_bytes().to_vec()),
-                field.as_bytes(),
-                value,
-                RECORDS_TTL,
-            ))
-            .await
-            .map_err(StorageError::WcnClientError)
+        let entry = MapEntry::new(
+            self.key(key.as_bytes().to_vec()),
+            field.as_bytes(),
+            value,
+            ttl,
+        );
+        self.measure(OperationType::Hset, async {
+            self.driver
+                .hset(entry)
+                .await
+                .map_err(StorageError::WcnClientError)
+        })
+        .await
     }
 
     /// Get the hashmap value from the storage
-    pub async fn hget(&self, key: String, field: String) -> Result<Option<Vec<u8>>, StorageError> {
-        let result = self
-            .driver
-            .hget(self.key(key.as_bytes().into()), field.as_bytes().into())
-            .await;
-
-        match result {
-            Ok(Some(record)) => Ok(Some(record.value)),
-            Ok(None) => Ok(None),
-            Err(e) => Err(e.into()),
-        }
+    async fn hget(&self, key: String, field: String) -> Result<Option<Vec<u8>>, StorageError> {
+        self.measure(OperationType::Hget, async {
+            let result = self
+                .driver
+                .hget(self.key(key.as_bytes().into()), field.as_bytes().into())
+                .await;
+
+            match result {
+                Ok(Some(record)) => Ok(Some(record.value)),
+                Ok(None) => Ok(None),
+                Err(e) => Err(e.into()),
+            }
+        })
+        .await
     }
 
     /// Delete the hashmap value from the storage
-    pub async fn hdel(&self, key: String, field: String) -> Result<(), StorageError> {
-        self.driver
-            .hdel(self.key(key.as_bytes().into()), field.as_bytes().into())
-            .await
-            .map_err(StorageError::WcnClientError)
+    async fn hdel(&self, key: String, field: String) -> Result<(), StorageError> {
+        self.measure(OperationType::Hdel, async {
+            self.driver
+                .hdel(self.key(key.as_bytes().into()), field.as_bytes().into())
+                .await
+                .map_err(StorageError::WcnClientError)
+        })
+        .await
     }
 
     /// Get all the hashmap ((field, value) cursor) from the storage
-    pub async fn hscan(
+    async fn hscan(
         &self,
         key: String,
         count: u32,
         cursor: Option<Vec<u8>>,
     ) -> Result<(Vec<(String, Vec<u8>)>, Option<Vec<u8>>), StorageError> {
         let result = self
-            .driver
-            .hscan(self.key(key.as_bytes().into()), count, cursor)
+            .measure(OperationType::Hscan, async {
+                self.driver
+                    .hscan(self.key(key.as_bytes().into()), count, cursor)
+                    .await
+                    .map_err(StorageError::WcnClientError)
+            })
             .await
             .map(|resp| {
                 let cursor = resp.next_page_cursor().cloned();
                 let records = resp.records.into_iter().map(|rec| (rec.field, rec.value));
 
                 (records, cursor)
-            })
-            .map_err(StorageError::WcnClientError)?;
+            })?;
 
         let (records, next_cursor) = result;
         let fields_values = records
@@ -223,6 +289,8 @@ impl Irn {
 mod tests {
     use super::*;
 
+    const TEST_TTL: Duration = Duration::from_secs(60);
+
     /// Ignoring this test by default to use it for local cluster testing only
     #[ignore]
     #[tokio::test]
@@ -232,13 +300,14 @@ mod tests {
             vec!["/ip4/127.0.0.1/udp/3011/quic-v1".into()],
             "test_namespace".into(),
             "namespace_secret".into(),
+            Arc::new(Metrics::new()),
         )
         .await
         .unwrap();
 
         let key = "test_key".to_string();
         let value = "test_value".to_string().into_bytes();
-        irn.set(key.clone(), value.clone()).await.unwrap();
+        irn.set(key.clone(), value.clone(), TEST_TTL).await.unwrap();
 
         // Get the value from the correct key
         let result = irn.get(key.clone()).await.unwrap().unwrap();
@@ -268,6 +337,7 @@ mod tests {
             vec![addr.into()],
             "test_namespace".into(),
             "namespace_secret".into(),
+            Arc::new(Metrics::new()),
         )
         .await
         .unwrap();
@@ -277,7 +347,7 @@ mod tests {
         let value = "test_value".to_string().into_bytes();
 
         // Set and get the hashmap field value
-        irn.hset(key.clone(), field.clone(), value.clone())
+        irn.hset(key.clone(), field.clone(), value.clone(), TEST_TTL)
             .await
             .unwrap();
         let result = irn.hget(key.clone(), field.clone()).await.unwrap().unwrap();
@@ -0,0 +1,268 @@
+use {
+    crate::{
+        metrics::Metrics,
+        storage::{deserialize, serialize, Data, KeyValueStorage, StorageResult},
+    },
+    async_trait::async_trait,
+    moka::future::Cache,
+    serde::{de::DeserializeOwned, Serialize},
+    std::{fmt::Debug, sync::Arc, time::Duration},
+};
+
+/// Bounded in-memory tier in front of the key-value storage for the hot keys
+/// to save the storage round trips.
+///
+/// Entries are kept in memory for the local TTL only, so updates made by other
+/// instances are observed with at most the local TTL delay.
+#[derive(Debug)]
+pub struct LocalCache<S> {
+    inner: S,
+    local: Cache<String, Data>,
+    local_ttl: Duration,
+    name: &'static str,
+    metrics: Arc<Metrics>,
+}
+
+impl<S> LocalCache<S> {
+    pub fn new(
+        inner: S,
+        name: &'static str,
+        max_capacity: u64,
+        local_ttl: Duration,
+        metrics: Arc<Metrics>,
+    ) -> Self {
+        let local = Cache::builder()
+            .max_capacity(max_capacity)
+            .time_to_live(local_ttl)
+            .build();
+        Self {
+            inner,
+            local,
+            local_ttl,
+            name,
+            metrics,
+        }
+    }
+
+    /// Values with the shorter storage TTL are not kept locally to not outlive
+    /// them in the storage
+    async fn update_local(&self, key: &str, value: &[u8], ttl: Option<Duration>) {
+        if ttl.is_none_or(|ttl| ttl >= self.local_ttl) {
+            self.local.insert(key.to_owned(), value.to_vec()).await;
+        } else {
+            self.local.invalidate(key).await;
+        }
+    }
+}
+
+#[async_trait]
+impl<S, T> KeyValueStorage<T> for LocalCache<S>
+where
+    S: KeyValueStorage<T>,
+    T: Serialize + DeserializeOwned + Send + Sync,
+{
+    async fn get(&self, key: &str) -> StorageResult<Option<T>> {
+        if let Some(data) = self.local.get(key).await {
+            self.metrics.add_local_cache_lookup(self.name, true);
+            return deserialize(&data).map(Some);
+        }
+        self.metrics.add_local_cache_lookup(self.name, false);
+
+        let value = self.inner.get(key).await?;
+        if let Some(value) = &value {
+            self.local.insert(key.to_owned(), serialize(value)?).await;
+        }
+        Ok(value)
+    }
+
+    /// Keys missing in memory are read from the storage by a single batch
+    async fn mget(&self, keys: &[String]) -> StorageResult<Vec<Option<T>>> {
+        let mut values = Vec::with_capacity(keys.len());
+        let mut missed = Vec::new();
+        for (index, key) in keys.iter().enumerate() {
+            match self.local.get(key).await {
+                Some(data) => {
+                    self.metrics.add_local_cache_lookup(self.name, true);
+                    values.push(Some(deserialize(&data)?));
+                }
+                None => {
+                    self.metrics.add_local_cache_lookup(self.name, false);
+                    values.push(None);
+                    missed.push(index);
+                }
+            }
+        }
+        if missed.is_empty() {
+            return Ok(values);
+        }
+
+        let missed_keys = missed
+            .iter()
+            .map(|&index| keys[index].clone())
+            .collect::<Vec<_>>();
+        let missed_values = self.inner.mget(&missed_keys).await?;
+        for (index, value) in missed.into_iter().zip(missed_values) {
+            if let Some(value) = &value {
+                self.local
+                    .insert(keys[index].clone(), serialize(value)?)
+                    .await;
+            }
+            values[index] = value;
+        }
+        Ok(values)
+    }
+
+    async fn set(&self, key: &str, value: &T, ttl: Option<Duration>) -> StorageResult<()> {
+        let data = serialize(value)?;
+        self.inner.set_serialized(key, &data, ttl).await?;
+        self.update_local(key, &data, ttl).await;
+        Ok(())
+    }
+
+    async fn set_serialized(
+        &self,
+        key: &str,
+        value: &[u8],
+        ttl: Option<Duration>,
+    ) -> StorageResult<()> {
+        self.inner.set_serialized(key, value, ttl).await?;
+        self.update_local(key, value, ttl).await;
+        Ok(())
+    }
+
+    async fn del(&self, key: &str) -> StorageResult<()> {
+        self.local.invalidate(key).await;
+        self.inner.del(key).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        std::{
+            collections::HashMap,
+            sync::{
+                atomic::{AtomicUsize, Ordering},
+                Mutex,
+            },
+        },
+    };
+
+    #[derive(Debug, Default)]
+    struct MemoryStorage {
+        data: Mutex<HashMap<String, Data>>,
+        gets: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl KeyValueStorage<String> for MemoryStorage {
+        async fn get(&self, key: &str) -> StorageResult<Option<String>> {
+            self.gets.fetch_add(1, Ordering::SeqCst);
+            self.data
+                .lock()
+                .unwrap()
+                .get(key)
+                .map(|data| deserialize(data))
+                .transpose()
+        }
+
+        async fn set(&self, key: &str, value: &String, ttl: Option<Duration>) -> StorageResult<()> {
+            self.set_serialized(key, &serialize(value)?, ttl).await
+        }
+
+        async fn set_serialized(
+            &self,
+            key: &str,
+            value: &[u8],
+            _ttl: Option<Duration>,
+        ) -> StorageResult<()> {
+            self.data
+                .lock()
+                .unwrap()
+                .insert(key.to_owned(), value.to_vec());
+            Ok(())
+        }
+
+        async fn del(&self, key: &str) -> StorageResult<()> {
+            self.data.lock().unwrap().remove(key);
+            Ok(())
+        }
+    }
+
+    fn local_cache() -> LocalCache<MemoryStorage> {
+        LocalCache::new(
+            MemoryStorage::default(),
+            "test",
+            100,
+            Duration::from_secs(60),
+            Arc::new(Metrics::new()),
+        )
+    }
+
+    #[tokio::test]
+    async fn serves_hot_keys_from_memory() {
+        let cache = local_cache();
+        KeyValueStorage::<String>::set(&cache, "key", &"value".to_owned(), None)
+            .await
+            .unwrap();
+
+        for _ in 0..3 {
+            let value: Option<String> = cache.get("key").await.unwrap();
+            assert_eq!(value, Some("value".to_owned()));
+        }
+        assert_eq!(cache.inner.gets.load(Ordering::SeqCst), 0);
+
+        KeyValueStorage::<String>::del(&cache, "key").await.unwrap();
+        let value: Option<String> = cache.get("key").await.unwrap();
+        assert_eq!(value, None);
+        assert_eq!(cache.inner.gets.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn skips_values_with_shorter_ttl() {
+        let cache = local_cache();
+        KeyValueStorage::<String>::set(
+            &cache,
+            "key",
+            &"value".to_owned(),
+            Some(Duration::from_secs(1)),
+        )
+        .await
+        .unwrap();
+
+        let value: Option<String> = cache.get("key").await.unwrap();
+        assert_eq!(value, Some("value".to_owned()));
+        assert_eq!(cache.inner.gets.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn batch_reads_missed_keys_from_storage() {
+        let cache = local_cache();
+        KeyValueStorage::<String>::set(&cache, "hot", &"hot value".to_owned(), None)
+            .await
+            .unwrap();
+        cache
+            .inner
+            .set_serialized("cold", &serialize(&"cold value".to_owned()).unwrap(), None)
+            .await
+            .unwrap();
+
+        let keys = ["hot", "cold", "missing"].map(String::from);
+        let values: Vec<Option<String>> = cache.mget(&keys).await.unwrap();
+        assert_eq!(
+            values,
+            vec![
+                Some("hot value".to_owned()),
+                Some("cold value".to_owned()),
+                None
+            ]
+        );
+        assert_eq!(cache.inner.gets.load(Ordering::SeqCst), 2);
+
+        // The found value is kept in memory after the batch read
+        let values: Vec<Option<String>> = cache.mget(&keys[..2]).await.unwrap();
+        assert_eq!(values.len(), 2);
+        assert_eq!(cache.inner.gets.load(Ordering::SeqCst), 2);
+    }
+}
@@ -5,8 +5,11 @@ use {
     std::{fmt::Debug, time::Duration},
 };
 
+pub mod dual;
 pub mod error;
 pub mod irn;
+pub mod local_cache;
+pub mod postgres;
 pub mod redis;
 
 /// The Result type returned by Storage functions
@@ -20,6 +23,17 @@ where
     /// Retrieve the data associated with the given key.
     async fn get(&self, key: &str) -> StorageResult<Option<T>>;
 
+    /// Retrieve the data associated with each of the given keys in the same
+    /// order. Storages supporting the batch reads override the sequential
+    /// reads to save the round trips.
+    async fn mget(&self, keys: &[String]) -> StorageResult<Vec<Option<T>>> {
+        let mut values = Vec::with_capacity(keys.len());
+        for key in keys {
+            values.push(self.get(key).await?);
+        }
+        Ok(values)
+    }
+
     /// Set the value for the given key.
     async fn set(&self, key: &str, value: &T, ttl: Option<Duration>) -> StorageResult<()>;
 
@@ -35,6 +49,61 @@ where
     async fn del(&self, key: &str) -> StorageResult<()>;
 }
 
+/// Persistent storage of the sessions permissions and the orchestrations.
+/// Backed by the IRN when it's configured and by Redis otherwise, the IRN
+/// records can be migrated to Postgres by the migration mode.
+#[async_trait]
+pub trait PersistentStorage: 'static + Send + Sync {
+    /// Set a value in the storage
+    async fn set(&self, key: String, value: Vec<u8>, ttl: Duration) -> StorageResult<()>;
+
+    /// Get a value from the storage
+    async fn get(&self, key: String) -> StorageResult<Option<Vec<u8>>>;
+
+    /// Delete a value from the storage
+    async fn delete(&self, key: String) -> StorageResult<()>;
+
+    /// Set the hashmap value in the storage
+    async fn hset(
+        &self,
+        key: String,
+        field: String,
+        value: Vec<u8>,
+        ttl: Duration,
+    ) -> StorageResult<()>;
+
+    /// Get the hashmap value from the storage
+    async fn hget(&self, key: String, field: String) -> StorageResult<Option<Vec<u8>>>;
+
+    /// Delete the hashmap value from the storage
+    async fn hdel(&self, key: String, field: String) -> StorageResult<()>;
+
+    /// Get the hashmap (field, value) pairs page and the next page cursor
+    async fn hscan(
+        &self,
+        key: String,
+        count: u32,
+        cursor: Option<Vec<u8>>,
+    ) -> StorageResult<(Vec<(String, Vec<u8>)>, Option<Vec<u8>>)>;
+
+    /// Remove the expired values, returns the number of the removed values.
+    /// The storages expiring the values by themselves don't need to purge.
+    async fn purge_expired(&self) -> StorageResult<u64> {
+        Ok(0)
+    }
+}
+
+/// Distributed lock shared between the service replicas.
+#[async_trait]
+pub trait LockStorage: 'static + Send + Sync + Debug {
+    /// Acquire the lock for the owner or extend it when it's already held by
+    /// the owner. Returns `false` when the lock is held by another owner.
+    async fn acquire_lock(&self, key: &str, owner: &str, ttl: Duration) -> StorageResult<bool>;
+
+    /// Release the lock if it's held by the owner.
+    async fn release_lock(&self, key: &str, owner: &str) -> StorageResult<()>;
+}
+
 /// Holder the type of data will be serialized to be stored.
 pub type Data = Vec<u8>;
 
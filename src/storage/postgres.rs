@@ -0,0 +1,166 @@
+use {
+    super::{PersistentStorage, StorageError, StorageResult},
+    async_trait::async_trait,
+    sqlx::{PgPool, Postgres},
+    std::time::Duration,
+};
+
+/// Persistent storage backed by the Postgres tables, used to migrate the
+/// sessions and orchestrations off the IRN
+#[derive(Clone)]
+pub struct PostgresStorage {
+    pool: PgPool,
+}
+
+impl PostgresStorage {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+fn storage_error(e: sqlx::Error) -> StorageError {
+    StorageError::Other(format!("{e}"))
+}
+
+#[async_trait]
+impl PersistentStorage for PostgresStorage {
+    async fn set(&self, key: String, value: Vec<u8>, ttl: Duration) -> StorageResult<()> {
+        let query = r#"
+            INSERT INTO persistent_storage_entries (key, value, expires_at)
+            VALUES ($1, $2, NOW() + make_interval(secs => $3))
+            ON CONFLICT (key) DO UPDATE SET
+                value = EXCLUDED.value,
+                expires_at = EXCLUDED.expires_at
+        "#;
+        sqlx::query::<Postgres>(query)
+            .bind(key)
+            .bind(value)
+            .bind(ttl.as_secs_f64())
+            .execute(&self.pool)
+            .await
+            .map_err(storage_error)?;
+        Ok(())
+    }
+
+    async fn get(&self, key: String) -> StorageResult<Option<Vec<u8>>> {
+        let query = r#"
+            SELECT value
+            FROM persistent_storage_entries
+            WHERE key = $1 AND expires_at > NOW()
+        "#;
+        sqlx::query_scalar::<Postgres, Vec<u8>>(query)
+            .bind(key)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(storage_error)
+    }
+
+    async fn delete(&self, key: String) -> StorageResult<()> {
+        let query = "DELETE FROM persistent_storage_entries WHERE key = $1";
+        sqlx::query::<Postgres>(query)
+            .bind(key)
+            .execute(&self.pool)
+            .await
+            .map_err(storage_error)?;
+        Ok(())
+    }
+
+    async fn hset(
+        &self,
+        key: String,
+        field: String,
+        value: Vec<u8>,
+        ttl: Duration,
+    ) -> StorageResult<()> {
+        let query = r#"
+            INSERT INTO persistent_storage_map_entries (key, field, value, expires_at)
+            VALUES ($1, $2, $3, NOW() + make_interval(secs => $4))
+            ON CONFLICT (key, field) DO UPDATE SET
+                value = EXCLUDED.value,
+                expires_at = EXCLUDED.expires_at
+        "#;
+        sqlx::query::<Postgres>(query)
+            .bind(key)
+            .bind(field)
+            .bind(value)
+            .bind(ttl.as_secs_f64())
+            .execute(&self.pool)
+            .await
+            .map_err(storage_error)?;
+        Ok(())
+    }
+
+    async fn hget(&self, key: String, field: String) -> StorageResult<Option<Vec<u8>>> {
+        let query = r#"
+            SELECT value
+            FROM persistent_storage_map_entries
+            WHERE key = $1 AND field = $2 AND expires_at > NOW()
+        "#;
+        sqlx::query_scalar::<Postgres, Vec<u8>>(query)
+            .bind(key)
+            .bind(field)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(storage_error)
+    }
+
+    async fn hdel(&self, key: String, field: String) -> StorageResult<()> {
+        let query = "DELETE FROM persistent_storage_map_entries WHERE key = $1 AND field = $2";
+        sqlx::query::<Postgres>(query)
+            .bind(key)
+            .bind(field)
+            .execute(&self.pool)
+            .await
+            .map_err(storage_error)?;
+        Ok(())
+    }
+
+    /// The cursor is the last field of the page, fields are scanned in order
+    async fn hscan(
+        &self,
+        key: String,
+        count: u32,
+        cursor: Option<Vec<u8>>,
+    ) -> StorageResult<(Vec<(String, Vec<u8>)>, Option<Vec<u8>>)> {
+        let cursor = cursor.map(String::from_utf8).transpose()?;
+        let query = r#"
+            SELECT field, value
+            FROM persistent_storage_map_entries
+            WHERE key = $1
+              AND expires_at > NOW()
+              AND ($2::TEXT IS NULL OR field > $2)
+            ORDER BY field
+            LIMIT $3
+        "#;
+        let fields_values = sqlx::query_as::<Postgres, (String, Vec<u8>)>(query)
+            .bind(key)
+            .bind(cursor)
+            .bind(i64::from(count))
+            .fetch_all(&self.pool)
+            .await
+            .map_err(storage_error)?;
+
+        // The last page is the page shorter than the requested count
+        let next_cursor = fields_values
+            .last()
+            .filter(|_| fields_values.len() == count as usize)
+            .map(|(field, _)| field.clone().into_bytes());
+        Ok((fields_values, next_cursor))
+    }
+
+    async fn purge_expired(&self) -> StorageResult<u64> {
+        let entries = sqlx::query::<Postgres>(
+            "DELETE FROM persistent_storage_entries WHERE expires_at <= NOW()",
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(storage_error)?;
+        let map_entries = sqlx::query::<Postgres>(
+            "DELETE FROM persistent_storage_map_entries WHERE expires_at <= NOW()",
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(storage_error)?;
+        Ok(entries.rows_affected() + map_entries.rows_affected())
+    }
+}
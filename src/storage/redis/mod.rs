@@ -1,12 +1,38 @@
 use {
-    crate::storage::{deserialize, serialize, KeyValueStorage, StorageError, StorageResult},
+    crate::storage::{
+        deserialize, serialize, KeyValueStorage, LockStorage, PersistentStorage, StorageError,
+        StorageResult,
+    },
     async_trait::async_trait,
-    deadpool_redis::{redis::AsyncCommands, Config, Pool},
+    deadpool_redis::{
+        redis::{self, AsyncCommands},
+        Config, Pool,
+    },
     serde::{de::DeserializeOwned, Serialize},
     std::{fmt::Debug, time::Duration},
 };
 
 const LOCAL_REDIS_ADDR: &str = "redis://localhost:6379/0";
+/// Prefix of the persistent storage keys to separate them from the caches
+const PERSISTENT_KEY_PREFIX: &str = "persistent/";
+
+/// Extends the lock TTL only when it's held by the owner
+const EXTEND_LOCK_SCRIPT: &str = r#"
+if redis.call("GET", KEYS[1]) == ARGV[1] then
+    return redis.call("PEXPIRE", KEYS[1], ARGV[2])
+else
+    return 0
+end
+"#;
+
+/// Deletes the lock only when it's held by the owner
+const RELEASE_LOCK_SCRIPT: &str = r#"
+if redis.call("GET", KEYS[1]) == ARGV[1] then
+    return redis.call("DEL", KEYS[1])
+else
+    return 0
+end
+"#;
 
 #[derive(Debug, Clone)]
 pub enum Addr<'a> {
@@ -54,6 +80,31 @@ pub struct Redis {
     write_pool: Pool,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RedisPoolsStatus {
+    pub read: PoolStatus,
+    pub write: PoolStatus,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PoolStatus {
+    pub max_size: usize,
+    pub size: usize,
+    pub available: usize,
+}
+
+impl From<deadpool_redis::Status> for PoolStatus {
+    fn from(status: deadpool_redis::Status) -> Self {
+        Self {
+            max_size: status.max_size,
+            size: status.size,
+            available: status.available,
+        }
+    }
+}
+
 impl Debug for Redis {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("Redis").finish()
@@ -86,6 +137,29 @@ impl Redis {
         })
     }
 
+    /// Connections of the read and write pools
+    pub fn pools_status(&self) -> RedisPoolsStatus {
+        RedisPoolsStatus {
+            read: self.read_pool.status().into(),
+            write: self.write_pool.status().into(),
+        }
+    }
+
+    /// Checks the read and write endpoints connectivity
+    pub async fn ping(&self) -> StorageResult<()> {
+        for pool in [&self.read_pool, &self.write_pool] {
+            let mut conn = pool
+                .get()
+                .await
+                .map_err(|e| StorageError::Connection(format!("{e}")))?;
+            redis::cmd("PING")
+                .query_async::<()>(&mut conn)
+                .await
+                .map_err(|e| StorageError::Connection(format!("{e}")))?;
+        }
+        Ok(())
+    }
+
     #[allow(dependency_on_unit_never_type_fallback)]
     async fn set_internal(
         &self,
@@ -136,6 +210,27 @@ where
             })
     }
 
+    async fn mget(&self, keys: &[String]) -> StorageResult<Vec<Option<T>>> {
+        if keys.is_empty() {
+            return Ok(Vec::new());
+        }
+        let mut conn = self
+            .read_pool
+            .get()
+            .await
+            .map_err(|e| StorageError::Connection(format!("{e}")))?;
+
+        let values: Vec<Option<Vec<u8>>> = redis::cmd("MGET")
+            .arg(keys)
+            .query_async(&mut conn)
+            .await
+            .map_err(|e| StorageError::Other(format!("{e}")))?;
+        values
+            .into_iter()
+            .map(|data| data.map(|data| deserialize(&data)).transpose())
+            .collect()
+    }
+
     async fn set(&self, key: &str, value: &T, ttl: Option<Duration>) -> StorageResult<()> {
         let data = serialize(value)?;
         self.set_internal(key, &data, ttl).await
@@ -160,3 +255,189 @@ where
             .map_err(|e| StorageError::Other(format!("{e}")))
     }
 }
+
+#[async_trait]
+impl LockStorage for Redis {
+    async fn acquire_lock(&self, key: &str, owner: &str, ttl: Duration) -> StorageResult<bool> {
+        let mut conn = self
+            .write_pool
+            .get()
+            .await
+            .map_err(|e| StorageError::Connection(format!("{e}")))?;
+        let ttl_ms = ttl.as_millis() as u64;
+
+        let acquired: Option<String> = redis::cmd("SET")
+            .arg(key)
+            .arg(owner)
+            .arg("NX")
+            .arg("PX")
+            .arg(ttl_ms)
+            .query_async(&mut conn)
+            .await
+            .map_err(|e| StorageError::Other(format!("{e}")))?;
+        if acquired.is_some() {
+            return Ok(true);
+        }
+
+        let extended: i64 = redis::Script::new(EXTEND_LOCK_SCRIPT)
+            .key(key)
+            .arg(owner)
+            .arg(ttl_ms)
+            .invoke_async(&mut conn)
+            .await
+            .map_err(|e| StorageError::Other(format!("{e}")))?;
+        Ok(extended == 1)
+    }
+
+    async fn release_lock(&self, key: &str, owner: &str) -> StorageResult<()> {
+        let mut conn = self
+            .write_pool
+            .get()
+            .await
+            .map_err(|e| StorageError::Connection(format!("{e}")))?;
+
+        let _: i64 = redis::Script::new(RELEASE_LOCK_SCRIPT)
+            .key(key)
+            .arg(owner)
+            .invoke_async(&mut conn)
+            .await
+            .map_err(|e| StorageError::Other(format!("{e}")))?;
+        Ok(())
+    }
+}
+
+fn persistent_key(key: &str) -> String {
+    format!("{PERSISTENT_KEY_PREFIX}{key}")
+}
+
+/// Fallback of the IRN persistent storage, the hashmaps TTL is extended on
+/// every write
+#[async_trait]
+impl PersistentStorage for Redis {
+    async fn set(&self, key: String, value: Vec<u8>, ttl: Duration) -> StorageResult<()> {
+        self.set_internal(&persistent_key(&key), &value, Some(ttl))
+            .await
+    }
+
+    async fn get(&self, key: String) -> StorageResult<Option<Vec<u8>>> {
+        let mut conn = self
+            .read_pool
+            .get()
+            .await
+            .map_err(|e| StorageError::Connection(format!("{e}")))?;
+
+        redis::cmd("GET")
+            .arg(persistent_key(&key))
+            .query_async(&mut conn)
+            .await
+            .map_err(|e| StorageError::Other(format!("{e}")))
+    }
+
+    async fn delete(&self, key: String) -> StorageResult<()> {
+        let mut conn = self
+            .write_pool
+            .get()
+            .await
+            .map_err(|e| StorageError::Connection(format!("{e}")))?;
+
+        let _: i64 = redis::cmd("DEL")
+            .arg(persistent_key(&key))
+            .query_async(&mut conn)
+            .await
+            .map_err(|e| StorageError::Other(format!("{e}")))?;
+        Ok(())
+    }
+
+    async fn hset(
+        &self,
+        key: String,
+        field: String,
+        value: Vec<u8>,
+        ttl: Duration,
+    ) -> StorageResult<()> {
+        let mut conn = self
+            .write_pool
+            .get()
+            .await
+            .map_err(|e| StorageError::Connection(format!("{e}")))?;
+        let key = persistent_key(&key);
+
+        let _: () = redis::pipe()
+            .atomic()
+            .cmd("HSET")
+            .arg(&key)
+            .arg(field)
+            .arg(value)
+            .ignore()
+            .cmd("EXPIRE")
+            .arg(&key)
+            .arg(ttl.as_secs())
+            .ignore()
+            .query_async(&mut conn)
+            .await
+            .map_err(|e| StorageError::Other(format!("{e}")))?;
+        Ok(())
+    }
+
+    async fn hget(&self, key: String, field: String) -> StorageResult<Option<Vec<u8>>> {
+        let mut conn = self
+            .read_pool
+            .get()
+            .await
+            .map_err(|e| StorageError::Connection(format!("{e}")))?;
+
+        redis::cmd("HGET")
+            .arg(persistent_key(&key))
+            .arg(field)
+            .query_async(&mut conn)
+            .await
+            .map_err(|e| StorageError::Other(format!("{e}")))
+    }
+
+    async fn hdel(&self, key: String, field: String) -> StorageResult<()> {
+        let mut conn = self
+            .write_pool
+            .get()
+            .await
+            .map_err(|e| StorageError::Connection(format!("{e}")))?;
+
+        let _: i64 = redis::cmd("HDEL")
+            .arg(persistent_key(&key))
+            .arg(field)
+            .query_async(&mut conn)
+            .await
+            .map_err(|e| StorageError::Other(format!("{e}")))?;
+        Ok(())
+    }
+
+    /// The cursor is the Redis `HSCAN` cursor, which is `0` at the end of the
+    /// iteration
+    async fn hscan(
+        &self,
+        key: String,
+        count: u32,
+        cursor: Option<Vec<u8>>,
+    ) -> StorageResult<(Vec<(String, Vec<u8>)>, Option<Vec<u8>>)> {
+        let mut conn = self
+            .read_pool
+            .get()
+            .await
+            .map_err(|e| StorageError::Connection(format!("{e}")))?;
+        let cursor = match cursor {
+            Some(cursor) => String::from_utf8(cursor)?,
+            None => "0".to_string(),
+        };
+
+        let (next_cursor, fields_values): (String, Vec<(String, Vec<u8>)>) = redis::cmd("HSCAN")
+            .arg(persistent_key(&key))
+            .arg(cursor)
+            .arg("COUNT")
+            .arg(count)
+            .query_async(&mut conn)
+            .await
+            .map_err(|e| StorageError::Other(format!("{e}")))?;
+
+        let next_cursor = (next_cursor != "0").then(|| next_cursor.into_bytes());
+        Ok((fields_values, next_cursor))
+    }
+}
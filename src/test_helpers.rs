@@ -8,9 +8,16 @@ use std::{
 use tokio::runtime::Handle;
 use url::Url;
 
+pub mod vcr;
+
 pub struct Params {
     pub validate_project_id: bool,
     pub override_bundler_urls: Option<MockAltoUrls>,
+    /// Local EVM node replacing the RPC providers, e.g. the anvil of the
+    /// `docker-compose.mock-bundler.yaml`
+    pub override_localnet_url: Option<Url>,
+    /// Balance, history and conversion providers API proxy, see [`vcr`]
+    pub override_api_proxy_url: Option<Url>,
 }
 
 impl Default for Params {
@@ -18,6 +25,8 @@ impl Default for Params {
         Self {
             validate_project_id: true,
             override_bundler_urls: None,
+            override_localnet_url: None,
+            override_api_proxy_url: None,
         }
     }
 }
@@ -47,6 +56,8 @@ pub async fn spawn_blockchain_api_with_params(params: Params) -> Url {
                 ..Default::default()
             };
             config.providers.override_bundler_urls = params.override_bundler_urls;
+            config.providers.override_localnet_url = params.override_localnet_url;
+            config.providers.override_api_proxy_url = params.override_api_proxy_url;
 
             crate::bootstrap(config).await
         })
@@ -0,0 +1,208 @@
+//! Record and replay proxy of the provider API calls for the functional tests.
+
+use {
+    axum::{
+        body::{to_bytes, Body},
+        extract::{Request, State},
+        http::{header, HeaderValue, StatusCode},
+        response::{IntoResponse, Response},
+        Router,
+    },
+    serde::{Deserialize, Serialize},
+    std::{
+        net::{Ipv4Addr, SocketAddr},
+        path::{Path, PathBuf},
+        sync::Arc,
+    },
+    tokio::sync::Mutex,
+    url::Url,
+};
+
+const MAX_BODY_SIZE: usize = 10 * 1024 * 1024;
+/// Request headers not forwarded to the upstream while recording, they are
+/// set by the client
+const SKIPPED_HEADERS: [header::HeaderName; 4] = [
+    header::HOST,
+    header::CONTENT_LENGTH,
+    header::CONNECTION,
+    header::ACCEPT_ENCODING,
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum VcrMode {
+    Record,
+    Replay,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct Interaction {
+    method: String,
+    /// Upstream URL without the scheme
+    uri: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    request_body: Option<String>,
+    status: u16,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    content_type: Option<String>,
+    response_body: String,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Cassette {
+    interactions: Vec<Interaction>,
+}
+
+struct Recorder {
+    mode: VcrMode,
+    path: PathBuf,
+    cassette: Mutex<Cassette>,
+    http_client: reqwest::Client,
+}
+
+/// Starts the proxy for the cassette and returns its URL
+pub async fn start(cassette_name: &str) -> Url {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures/vcr")
+        .join(format!("{cassette_name}.json"));
+    let mode = match std::env::var("VCR_MODE").as_deref() {
+        Ok("record") => VcrMode::Record,
+        Ok("replay") => VcrMode::Replay,
+        _ if path.exists() => VcrMode::Replay,
+        _ => VcrMode::Record,
+    };
+    let cassette = match mode {
+        VcrMode::Replay => {
+            let data = std::fs::read(&path)
+                .unwrap_or_else(|e| panic!("failed to read the cassette {path:?}: {e}"));
+            serde_json::from_slice(&data)
+                .unwrap_or_else(|e| panic!("failed to parse the cassette {path:?}: {e}"))
+        }
+        VcrMode::Record => Cassette::default(),
+    };
+    let recorder = Arc::new(Recorder {
+        mode,
+        path,
+        cassette: Mutex::new(cassette),
+        http_client: reqwest::Client::new(),
+    });
+
+    let listener = tokio::net::TcpListener::bind(SocketAddr::from((Ipv4Addr::LOCALHOST, 0)))
+        .await
+        .expect("failed to bind the VCR listener");
+    let addr = listener.local_addr().unwrap();
+    let app = Router::new().fallback(handler).with_state(recorder);
+    tokio::spawn(async move { axum::serve(listener, app).await });
+
+    format!("http://{addr}").parse().unwrap()
+}
+
+async fn handler(State(recorder): State<Arc<Recorder>>, request: Request) -> Response {
+    let (parts, body) = request.into_parts();
+    let uri = match parts.uri.query() {
+        Some(query) => format!("{}?{query}", parts.uri.path().trim_start_matches('/')),
+        None => parts.uri.path().trim_start_matches('/').to_owned(),
+    };
+    let request_body = match to_bytes(body, MAX_BODY_SIZE).await {
+        Ok(body) if body.is_empty() => None,
+        Ok(body) => Some(String::from_utf8_lossy(&body).into_owned()),
+        Err(e) => return (StatusCode::BAD_REQUEST, e.to_string()).into_response(),
+    };
+
+    let interaction = match recorder.mode {
+        VcrMode::Replay => recorder
+            .cassette
+            .lock()
+            .await
+            .interactions
+            .iter()
+            .find(|interaction| {
+                interaction.method == parts.method.as_str()
+                    && interaction.uri == uri
+                    && interaction.request_body == request_body
+            })
+            .cloned(),
+        VcrMode::Record => {
+            let mut upstream = recorder
+                .http_client
+                .request(parts.method.clone(), format!("https://{uri}"));
+            for (name, value) in &parts.headers {
+                if !SKIPPED_HEADERS.contains(name) {
+                    upstream = upstream.header(name, value);
+                }
+            }
+            if let Some(request_body) = &request_body {
+                upstream = upstream.body(request_body.clone());
+            }
+            match record(
+                upstream,
+                parts.method.to_string(),
+                uri.clone(),
+                request_body,
+            )
+            .await
+            {
+                Ok(interaction) => {
+                    let mut cassette = recorder.cassette.lock().await;
+                    cassette.interactions.push(interaction.clone());
+                    if let Err(e) = save(&recorder.path, &cassette) {
+                        return (StatusCode::INTERNAL_SERVER_ERROR, e).into_response();
+                    }
+                    Some(interaction)
+                }
+                Err(e) => return (StatusCode::BAD_GATEWAY, e.to_string()).into_response(),
+            }
+        }
+    };
+
+    let Some(interaction) = interaction else {
+        return (
+            StatusCode::NOT_IMPLEMENTED,
+            format!("No recorded interaction for {} {uri}", parts.method),
+        )
+            .into_response();
+    };
+    let mut response = Response::new(Body::from(interaction.response_body));
+    *response.status_mut() =
+        StatusCode::from_u16(interaction.status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+    if let Some(content_type) = interaction
+        .content_type
+        .and_then(|content_type| HeaderValue::from_str(&content_type).ok())
+    {
+        response
+            .headers_mut()
+            .insert(header::CONTENT_TYPE, content_type);
+    }
+    response
+}
+
+async fn record(
+    upstream: reqwest::RequestBuilder,
+    method: String,
+    uri: String,
+    request_body: Option<String>,
+) -> Result<Interaction, reqwest::Error> {
+    let response = upstream.send().await?;
+    let status = response.status().as_u16();
+    let content_type = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|content_type| content_type.to_str().ok())
+        .map(ToOwned::to_owned);
+    let response_body = response.text().await?;
+    Ok(Interaction {
+        method,
+        uri,
+        request_body,
+        status,
+        content_type,
+        response_body,
+    })
+}
+
+fn save(path: &Path, cassette: &Cassette) -> Result<(), String> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    }
+    let data = serde_json::to_vec_pretty(cassette).map_err(|e| e.to_string())?;
+    std::fs::write(path, data).map_err(|e| format!("failed to write the cassette {path:?}: {e}"))
+}
@@ -0,0 +1,290 @@
+//! Backfill of the token metadata missing in the providers responses.
+
+use {
+    crate::{
+        analytics::MessageSource,
+        handlers::{balance::TokenMetadataCacheItem, SupportedCurrencies},
+        metrics::TokenMetadataBackfillResult,
+        state::AppState,
+        utils::crypto,
+    },
+    ethers::types::H160,
+    futures_util::future::join_all,
+    moka::future::Cache,
+    std::{
+        collections::HashSet,
+        str::FromStr,
+        sync::{Arc, Mutex},
+        time::Duration,
+    },
+    tokio::time::{interval, MissedTickBehavior},
+    tracing::{debug, warn},
+};
+
+const BACKFILL_INTERVAL: Duration = Duration::from_secs(5);
+const BATCH_SIZE: usize = 20;
+/// New tokens are dropped while the queue is full
+const MAX_QUEUE_SIZE: usize = 1000;
+/// Tokens are not looked up again within the TTL regardless of the result
+const ATTEMPTED_TTL: Duration = Duration::from_secs(60 * 60);
+
+/// Whether the token metadata should be backfilled
+pub fn is_incomplete(name: Option<&str>, symbol: Option<&str>, icon_url: Option<&str>) -> bool {
+    [name, symbol, icon_url]
+        .iter()
+        .any(|field| field.is_none_or(str::is_empty))
+}
+
+/// Queue of the tokens with the missing metadata
+pub struct TokenMetadataBackfill {
+    queue: Mutex<HashSet<String>>,
+    attempted: Cache<String, ()>,
+}
+
+impl Default for TokenMetadataBackfill {
+    fn default() -> Self {
+        Self {
+            queue: Mutex::default(),
+            attempted: Cache::builder().time_to_live(ATTEMPTED_TTL).build(),
+        }
+    }
+}
+
+impl TokenMetadataBackfill {
+    /// Enqueue the token by the CAIP-10 address, returns `false` when the token
+    /// is already queued, was recently looked up or the queue is full
+    pub fn enqueue(&self, caip10_token_address: &str) -> bool {
+        if crypto::disassemble_caip10(caip10_token_address).is_err()
+            || self.attempted.contains_key(caip10_token_address)
+        {
+            return false;
+        }
+        let mut queue = self
+            .queue
+            .lock()
+            .expect("token metadata backfill queue lock is poisoned");
+        if queue.len() >= MAX_QUEUE_SIZE {
+            return false;
+        }
+        queue.insert(caip10_token_address.to_owned())
+    }
+
+    /// Takes the batch of the queued tokens and marks them as attempted
+    async fn take(&self, limit: usize) -> Vec<String> {
+        let batch = {
+            let mut queue = self
+                .queue
+                .lock()
+                .expect("token metadata backfill queue lock is poisoned");
+            let batch = queue.iter().take(limit).cloned().collect::<Vec<_>>();
+            for token in &batch {
+                queue.remove(token);
+            }
+            batch
+        };
+        for token in &batch {
+            self.attempted.insert(token.clone(), ()).await;
+        }
+        batch
+    }
+}
+
+/// Background job looking up the metadata of the queued tokens
+pub async fn run(state: Arc<AppState>) {
+    debug!("starting token metadata backfill");
+    let mut backfill_interval = interval(BACKFILL_INTERVAL);
+    backfill_interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    loop {
+        backfill_interval.tick().await;
+        let batch = state.token_metadata_backfill.take(BATCH_SIZE).await;
+        if batch.is_empty() {
+            continue;
+        }
+        debug!("backfilling the metadata of {} tokens", batch.len());
+
+        let results = join_all(batch.iter().map(|token| backfill(&state, token))).await;
+        for result in results {
+            state.metrics.add_token_metadata_backfill(result);
+        }
+    }
+}
+
+async fn backfill(state: &AppState, caip10_token_address: &str) -> TokenMetadataBackfillResult {
+    let (namespace, chain_id, address) = match crypto::disassemble_caip10(caip10_token_address) {
+        Ok(parts) => parts,
+        Err(e) => {
+            warn!("Invalid token address {caip10_token_address} in the metadata backfill: {e}");
+            return TokenMetadataBackfillResult::Missed;
+        }
+    };
+    let providers = state.providers();
+    let metadata_cache = providers.token_metadata_cache.clone();
+    let mut metadata = match metadata_cache.get_metadata(caip10_token_address).await {
+        Ok(metadata) => metadata,
+        Err(e) => {
+            warn!("Failed to get the cached metadata of {caip10_token_address}: {e}");
+            return TokenMetadataBackfillResult::Missed;
+        }
+    };
+
+    let mut result = TokenMetadataBackfillResult::Missed;
+    if let Some(provider) = providers.fungible_price_providers.get(&namespace) {
+        match provider
+            .get_price(
+                &chain_id,
+                &address,
+                &SupportedCurrencies::USD,
+                &metadata_cache,
+                state.metrics.clone(),
+            )
+            .await
+        {
+            Ok(response) => {
+                if let Some(fungible) = response.fungibles.into_iter().next() {
+                    let lookup = TokenMetadataCacheItem {
+                        name: fungible.name,
+                        symbol: fungible.symbol,
+                        icon_url: fungible.icon_url,
+                        decimals: fungible.decimals,
+                    };
+                    if merge(&mut metadata, lookup) {
+                        result = TokenMetadataBackfillResult::Provider;
+                    }
+                }
+            }
+            Err(e) => debug!("Failed to get the provider metadata of {caip10_token_address}: {e}"),
+        }
+    }
+
+    // The contracts have no icons, so only the name and symbol are backfilled
+    let missing_name = metadata
+        .as_ref()
+        .is_none_or(|metadata| metadata.name.is_empty() || metadata.symbol.is_empty());
+    if namespace == crypto::CaipNamespaces::Eip155 && missing_name {
+        let rpc_project_id = state.config.server.testing_project_id.as_deref();
+        if let (Some(rpc_project_id), Ok(contract)) = (rpc_project_id, H160::from_str(&address)) {
+            match crypto::get_erc20_contract_metadata(
+                &format!("{namespace}:{chain_id}"),
+                contract,
+                rpc_project_id,
+                MessageSource::TokenMetadata,
+            )
+            .await
+            {
+                Ok((name, symbol, decimals)) => {
+                    let lookup = TokenMetadataCacheItem {
+                        name,
+                        symbol,
+                        icon_url: String::new(),
+                        decimals,
+                    };
+                    if merge(&mut metadata, lookup) && result == TokenMetadataBackfillResult::Missed
+                    {
+                        result = TokenMetadataBackfillResult::OnChain;
+                    }
+                }
+                Err(e) => {
+                    debug!("Failed to get the on-chain metadata of {caip10_token_address}: {e}")
+                }
+            }
+        }
+    }
+
+    if result == TokenMetadataBackfillResult::Missed {
+        return result;
+    }
+    if let Some(metadata) = metadata {
+        if let Err(e) = metadata_cache
+            .set_metadata(caip10_token_address, &metadata)
+            .await
+        {
+            warn!("Failed to save the backfilled metadata of {caip10_token_address}: {e}");
+            return TokenMetadataBackfillResult::Missed;
+        }
+    }
+    result
+}
+
+/// Fills the missing metadata fields by the looked up metadata, returns
+/// whether any field was filled
+fn merge(metadata: &mut Option<TokenMetadataCacheItem>, lookup: TokenMetadataCacheItem) -> bool {
+    match metadata {
+        Some(metadata) => {
+            let mut filled = false;
+            for (field, value) in [
+                (&mut metadata.name, lookup.name),
+                (&mut metadata.symbol, lookup.symbol),
+                (&mut metadata.icon_url, lookup.icon_url),
+            ] {
+                if field.is_empty() && !value.is_empty() {
+                    *field = value;
+                    filled = true;
+                }
+            }
+            filled
+        }
+        None => {
+            let filled = !lookup.name.is_empty() && !lookup.symbol.is_empty();
+            if filled {
+                *metadata = Some(lookup);
+            }
+            filled
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(name: &str, symbol: &str, icon_url: &str) -> TokenMetadataCacheItem {
+        TokenMetadataCacheItem {
+            name: name.to_owned(),
+            symbol: symbol.to_owned(),
+            icon_url: icon_url.to_owned(),
+            decimals: 18,
+        }
+    }
+
+    #[test]
+    fn merges_missing_fields() {
+        let mut metadata = None;
+        assert!(!merge(&mut metadata, item("", "", "https://icon")));
+        assert!(metadata.is_none());
+        assert!(merge(&mut metadata, item("Token", "TKN", "")));
+        assert_eq!(metadata, Some(item("Token", "TKN", "")));
+
+        assert!(merge(&mut metadata, item("Other", "OTH", "https://icon")));
+        assert_eq!(metadata, Some(item("Token", "TKN", "https://icon")));
+        assert!(!merge(&mut metadata, item("Other", "OTH", "https://other")));
+    }
+
+    #[tokio::test]
+    async fn enqueues_once_until_expired() {
+        const USDC: &str = "eip155:1:0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48";
+        const USDT: &str = "eip155:1:0xdac17f958d2ee523a2206206994597c13d831ec7";
+
+        let backfill = TokenMetadataBackfill::default();
+        assert!(backfill.enqueue(USDC));
+        assert!(!backfill.enqueue(USDC));
+        assert!(backfill.enqueue(USDT));
+        assert!(!backfill.enqueue("So11111111111111111111111111111111111111112"));
+
+        let mut batch = backfill.take(BATCH_SIZE).await;
+        batch.sort();
+        assert_eq!(batch, vec![USDC, USDT]);
+        assert!(backfill.take(BATCH_SIZE).await.is_empty());
+        assert!(!backfill.enqueue(USDC));
+    }
+
+    #[test]
+    fn incomplete_metadata() {
+        assert!(!is_incomplete(
+            Some("Token"),
+            Some("TKN"),
+            Some("https://icon")
+        ));
+        assert!(is_incomplete(Some("Token"), Some("TKN"), Some("")));
+        assert!(is_incomplete(None, Some("TKN"), Some("https://icon")));
+    }
+}
@@ -0,0 +1,210 @@
+use {
+    crate::metrics::Metrics,
+    aws_sdk_s3::{primitives::ByteStream, Client as S3Client},
+    chrono::{DateTime, Utc},
+    serde::{Deserialize, Serialize},
+    serde_piecewise_default::DeserializePiecewiseDefault,
+    std::{
+        collections::HashMap,
+        mem,
+        net::IpAddr,
+        sync::{Arc, Mutex},
+        time::Duration,
+    },
+    tokio::time::{interval, MissedTickBehavior},
+    tracing::{debug, warn},
+};
+
+const DEFAULT_REPORT_INTERVAL: Duration = Duration::from_secs(60);
+const EXPORT_PREFIX: &str = "blockchain-api/usage";
+
+#[derive(DeserializePiecewiseDefault, Debug, Clone, Default, PartialEq, Eq)]
+pub struct Config {
+    /// S3 bucket for the usage batches, reporting is disabled when not set
+    pub export_bucket: Option<String>,
+    pub report_interval_secs: Option<u64>,
+}
+
+impl Config {
+    pub fn report_interval(&self) -> Duration {
+        self.report_interval_secs
+            .map(Duration::from_secs)
+            .unwrap_or(DEFAULT_REPORT_INTERVAL)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct UsageKey {
+    project_id: String,
+    endpoint: String,
+    chain_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageRecord {
+    pub project_id: String,
+    pub endpoint: String,
+    pub chain_id: Option<String>,
+    pub requests: u64,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct UsageBatch<'a> {
+    node_addr: IpAddr,
+    period_start: DateTime<Utc>,
+    period_end: DateTime<Utc>,
+    records: &'a [UsageRecord],
+}
+
+/// Per project requests counters rolled up by the endpoint and chain between
+/// the usage reports
+#[derive(Debug, Default)]
+pub struct UsageAggregator {
+    counts: Mutex<HashMap<UsageKey, u64>>,
+}
+
+impl UsageAggregator {
+    pub fn record(&self, project_id: &str, endpoint: &str, chain_id: Option<&str>) {
+        self.add(
+            UsageKey {
+                project_id: project_id.to_owned(),
+                endpoint: endpoint.to_owned(),
+                chain_id: chain_id.map(ToOwned::to_owned),
+            },
+            1,
+        );
+    }
+
+    fn add(&self, key: UsageKey, requests: u64) {
+        *self
+            .counts
+            .lock()
+            .expect("usage counters lock is poisoned")
+            .entry(key)
+            .or_default() += requests;
+    }
+
+    /// Takes the rolled up counters and resets them
+    pub fn take(&self) -> Vec<UsageRecord> {
+        let counts = mem::take(&mut *self.counts.lock().expect("usage counters lock is poisoned"));
+        counts
+            .into_iter()
+            .map(|(key, requests)| UsageRecord {
+                project_id: key.project_id,
+                endpoint: key.endpoint,
+                chain_id: key.chain_id,
+                requests,
+            })
+            .collect()
+    }
+
+    /// Returns the not reported counters back to be included in the next
+    /// report
+    fn restore(&self, records: Vec<UsageRecord>) {
+        for record in records {
+            self.add(
+                UsageKey {
+                    project_id: record.project_id,
+                    endpoint: record.endpoint,
+                    chain_id: record.chain_id,
+                },
+                record.requests,
+            );
+        }
+    }
+}
+
+/// Periodically exports the rolled up usage batches to the S3 bucket for the
+/// billing and quotas, independently of the analytics pipeline
+pub async fn run(
+    aggregator: Arc<UsageAggregator>,
+    s3_client: S3Client,
+    export_bucket: String,
+    node_addr: IpAddr,
+    report_interval: Duration,
+    metrics: Arc<Metrics>,
+) {
+    debug!(?report_interval, "starting usage reporter");
+    let mut report = interval(report_interval);
+    report.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    // The first tick completes immediately
+    report.tick().await;
+    let mut period_start = Utc::now();
+    loop {
+        report.tick().await;
+        let records = aggregator.take();
+        if records.is_empty() {
+            continue;
+        }
+
+        let period_end = Utc::now();
+        let batch = UsageBatch {
+            node_addr,
+            period_start,
+            period_end,
+            records: &records,
+        };
+        match export(&s3_client, &export_bucket, &batch).await {
+            Ok(()) => {
+                metrics.add_usage_report(true, records.len());
+                period_start = period_end;
+            }
+            Err(e) => {
+                warn!(error = %e, "failed to export usage batch");
+                metrics.add_usage_report(false, records.len());
+                aggregator.restore(records);
+            }
+        }
+    }
+}
+
+async fn export(
+    s3_client: &S3Client,
+    export_bucket: &str,
+    batch: &UsageBatch<'_>,
+) -> anyhow::Result<()> {
+    let key = format!(
+        "{EXPORT_PREFIX}/{}/{}-{}.json",
+        batch.period_end.format("%Y/%m/%d"),
+        batch.period_end.timestamp_millis(),
+        batch.node_addr
+    );
+    s3_client
+        .put_object()
+        .bucket(export_bucket)
+        .key(key)
+        .content_type("application/json")
+        .body(ByteStream::from(serde_json::to_vec(batch)?))
+        .send()
+        .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rolls_up_and_restores_usage() {
+        let aggregator = UsageAggregator::default();
+        aggregator.record("project", "/v1", Some("eip155:1"));
+        aggregator.record("project", "/v1", Some("eip155:1"));
+        aggregator.record("project", "/v1/identity/{address}", None);
+
+        let mut records = aggregator.take();
+        records.sort_by(|a, b| a.endpoint.cmp(&b.endpoint));
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].chain_id.as_deref(), Some("eip155:1"));
+        assert_eq!(records[0].requests, 2);
+        assert_eq!(records[1].requests, 1);
+        assert!(aggregator.take().is_empty());
+
+        aggregator.restore(records);
+        aggregator.record("project", "/v1", Some("eip155:1"));
+        let records = aggregator.take();
+        let total: u64 = records.iter().map(|record| record.requests).sum();
+        assert_eq!(total, 4);
+    }
+}
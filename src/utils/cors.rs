@@ -28,7 +28,7 @@ pub fn insert_cors_headers(response: &mut Response, origin: &str) {
     headers.insert(
         header::ACCESS_CONTROL_ALLOW_HEADERS,
         header::HeaderValue::from_static(
-            "content-type, user-agent, referer, origin, access-control-request-method, access-control-request-headers, solana-client, sec-fetch-mode, x-sdk-type, x-sdk-version",
+            "content-type, authorization, user-agent, referer, origin, access-control-request-method, access-control-request-headers, solana-client, sec-fetch-mode, x-sdk-type, x-sdk-version",
         ),
     );
 }
@@ -46,7 +46,7 @@ pub fn insert_cors_allow_all_headers(response: &mut Response) {
     headers.insert(
         header::ACCESS_CONTROL_ALLOW_HEADERS,
         header::HeaderValue::from_static(
-            "content-type, user-agent, referer, origin, access-control-request-method, access-control-request-headers, solana-client, sec-fetch-mode, x-sdk-type, x-sdk-version",
+            "content-type, authorization, user-agent, referer, origin, access-control-request-method, access-control-request-headers, solana-client, sec-fetch-mode, x-sdk-type, x-sdk-version",
         ),
     );
 }
@@ -76,6 +76,92 @@ pub fn host_matches_pattern(pattern_lc: &str, host_lc: &str) -> bool {
     pattern_lc == host_lc
 }
 
+/// Match the request origin against the project's allowed origins entries.
+/// Entries are either full origins with the scheme and optional port or
+/// hosts only, both may use the `*.` subdomains wildcard.
+pub fn is_origin_allowed(allowed_origins: &[String], origin: &str) -> bool {
+    let origin_lc = origin.to_ascii_lowercase();
+
+    // Allow default allowed origins by default
+    if CORS_ALLOWED_ORIGINS
+        .iter()
+        .any(|o| o.eq_ignore_ascii_case(&origin_lc))
+    {
+        return true;
+    }
+    // Parse origin URL details if possible
+    let parsed_origin = url::Url::parse(origin).ok();
+    let origin_host = parsed_origin
+        .as_ref()
+        .and_then(|u| u.host_str().map(|h| h.to_ascii_lowercase()));
+    let origin_scheme = parsed_origin
+        .as_ref()
+        .map(|u| u.scheme().to_ascii_lowercase());
+    let origin_effective_port: Option<u16> = {
+        fn default_port_for_scheme(s: &str) -> Option<u16> {
+            match s {
+                "http" => Some(80),
+                "https" => Some(443),
+                _ => None,
+            }
+        }
+        match (&parsed_origin, &origin_scheme) {
+            (Some(u), Some(s)) => u.port().or_else(|| default_port_for_scheme(s)),
+            _ => None,
+        }
+    };
+
+    // Single-pass matcher over allowed entries
+    allowed_origins.iter().any(|entry| {
+        let entry_lc = entry.trim().to_ascii_lowercase();
+
+        // Fast path: exact origin string match
+        if entry_lc == origin_lc {
+            return true;
+        }
+
+        // Full origin pattern with scheme
+        if let Some((scheme_pat, rest)) = entry_lc.split_once("://") {
+            // Scheme must match
+            if origin_scheme.as_deref() != Some(scheme_pat) {
+                return false;
+            }
+
+            // Extract host[:port] (ignore any path if present)
+            let host_port = rest.split('/').next().unwrap_or("");
+            if host_port.is_empty() {
+                return false;
+            }
+            let (host_pat, port_pat_opt) = host_port
+                .split_once(':')
+                .map(|(h, p)| (h, Some(p)))
+                .unwrap_or((host_port, None));
+
+            let Some(ref host_lc) = origin_host else {
+                return false;
+            };
+            if !host_matches_pattern(host_pat, host_lc) {
+                return false;
+            }
+
+            // If port is specified in entry, it must match effective origin port
+            if let Some(port_s) = port_pat_opt {
+                if let Ok(port_num) = port_s.parse::<u16>() {
+                    return origin_effective_port.is_some_and(|p| p == port_num);
+                }
+                return false;
+            }
+            return true;
+        }
+
+        // Host-only entry (wildcard supported)
+        if let Some(ref host_lc) = origin_host {
+            return host_matches_pattern(&entry_lc, host_lc);
+        }
+        false
+    })
+}
+
 pub async fn get_project_allowed_origins(
     state: Arc<AppState>,
     project_id: &str,
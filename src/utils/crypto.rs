@@ -8,7 +8,7 @@ use {
             types::{TransactionInput, TransactionRequest},
         },
         sol,
-        sol_types::SolCall,
+        sol_types::{SolCall, SolType},
     },
     base64::prelude::*,
     bs58,
@@ -25,6 +25,10 @@ use {
     },
     hex::FromHex,
     once_cell::sync::Lazy,
+    p256::ecdsa::{
+        signature::Verifier as P256Verifier, Signature as P256Signature,
+        VerifyingKey as P256VerifyingKey,
+    },
     regex::Regex,
     relay_rpc::auth::cacao::{signature::eip6492::verify_eip6492, CacaoError},
     serde::{Deserialize, Serialize},
@@ -36,6 +40,13 @@ use {
 };
 
 const ENSIP11_MAINNET_COIN_TYPE: u32 = 60;
+/// Flags byte offset of the WebAuthn authenticator data, after the RP ID hash
+const WEBAUTHN_FLAGS_OFFSET: usize = 32;
+/// Minimal WebAuthn authenticator data length of the RP ID hash, flags and
+/// signature counter
+const WEBAUTHN_AUTHENTICATOR_DATA_MIN_LEN: usize = 37;
+const WEBAUTHN_FLAG_USER_PRESENT: u8 = 0x01;
+const WEBAUTHN_FLAG_USER_VERIFIED: u8 = 0x04;
 static CAIP_CHAIN_ID_REGEX: Lazy<Regex> = Lazy::new(|| {
     Regex::new(r"[-a-zA-Z0-9]{1,32}").expect("Failed to initialize regexp for the chain ID format")
 });
@@ -47,6 +58,22 @@ static CAIP_SOLANA_ADDRESS_REGEX: Lazy<Regex> = Lazy::new(|| {
     Regex::new(r"[1-9A-HJ-NP-Za-km-z]{32,44}")
         .expect("Failed to initialize regexp for the solana address format")
 });
+static CAIP_SUI_ADDRESS_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^0x[a-fA-F0-9]{1,64}$")
+        .expect("Failed to initialize regexp for the sui address format")
+});
+static CAIP_NEAR_ADDRESS_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^(([a-z\d]+[-_])*[a-z\d]+\.)*([a-z\d]+[-_])*[a-z\d]+$")
+        .expect("Failed to initialize regexp for the near address format")
+});
+static CAIP_BITCOIN_ADDRESS_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^([13mn2][1-9A-HJ-NP-Za-km-z]{25,34}|(bc|tb|bcrt)1[02-9ac-hj-np-z]{8,87})$")
+        .expect("Failed to initialize regexp for the bitcoin address format")
+});
+static CAIP_XRPL_ADDRESS_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^r[1-9A-HJ-NP-Za-km-z]{24,34}$")
+        .expect("Failed to initialize regexp for the xrpl address format")
+});
 
 // CAIP-19 regex validation patterns
 static CAIP19_ASSET_NAMESPACE_REGEX: Lazy<Regex> = Lazy::new(|| {
@@ -73,6 +100,10 @@ static CAIP2_NAMESPACE_REGEX: Lazy<Regex> = Lazy::new(|| {
 });
 
 pub const SOLANA_NATIVE_TOKEN_ADDRESS: &str = "So11111111111111111111111111111111111111111";
+/// Token address of the native asset in the CAIP-10 addresses of the
+/// namespaces without the native token address convention, e.g.
+/// `bip122:000000000019d6689c085ae165831e93:native`
+pub const NATIVE_ASSET_ADDRESS: &str = "native";
 
 pub const JSON_RPC_VERSION_STR: &str = "2.0";
 pub static JSON_RPC_VERSION: once_cell::sync::Lazy<Arc<str>> =
@@ -293,6 +324,18 @@ sol! {
     function allowance(address _owner, address _spender) external view returns (uint256);
 }
 
+// WebAuthn assertion as ABI-encoded by the passkey validators
+sol! {
+    struct WebAuthnAuth {
+        bytes authenticatorData;
+        string clientDataJSON;
+        uint256 challengeIndex;
+        uint256 typeIndex;
+        uint256 r;
+        uint256 s;
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Erc20FunctionType {
     BalanceOf,
@@ -492,6 +535,87 @@ pub fn verify_secp256k1_signature(
     Ok(())
 }
 
+/// Verify secp256r1 (P-256) WebAuthn passkey signature of the challenge.
+/// The signature is expected to be the ABI-encoded `WebAuthnAuth` and the
+/// public key in SEC1 format or raw 64 bytes of the `x` and `y` coordinates.
+/// The user presence is always required and the user verification only when
+/// `user_verification_required`
+#[tracing::instrument(level = "debug")]
+pub fn verify_webauthn_p256_signature(
+    challenge: &[u8],
+    signature: &[u8],
+    public_key: &[u8],
+    user_verification_required: bool,
+) -> Result<(), RpcError> {
+    let auth = <WebAuthnAuth as SolType>::abi_decode(signature, true)
+        .map_err(|e| RpcError::SignatureFormatError(e.to_string()))?;
+
+    // Client data must be of the assertion type for the expected challenge
+    let expected_challenge = format!(
+        "\"challenge\":\"{}\"",
+        BASE64_URL_SAFE_NO_PAD.encode(challenge)
+    );
+    if !auth.clientDataJSON.contains(&expected_challenge) {
+        return Err(RpcError::SignatureValidationError(
+            "WebAuthn client data challenge mismatch".into(),
+        ));
+    }
+    if !auth.clientDataJSON.contains("\"type\":\"webauthn.get\"") {
+        return Err(RpcError::SignatureValidationError(
+            "WebAuthn client data type is not an assertion".into(),
+        ));
+    }
+
+    if auth.authenticatorData.len() < WEBAUTHN_AUTHENTICATOR_DATA_MIN_LEN {
+        return Err(RpcError::SignatureFormatError(
+            "WebAuthn authenticator data is too short".into(),
+        ));
+    }
+    let flags = auth.authenticatorData[WEBAUTHN_FLAGS_OFFSET];
+    if flags & WEBAUTHN_FLAG_USER_PRESENT == 0 {
+        return Err(RpcError::SignatureValidationError(
+            "WebAuthn user presence flag is not set".into(),
+        ));
+    }
+    if user_verification_required && flags & WEBAUTHN_FLAG_USER_VERIFIED == 0 {
+        return Err(RpcError::SignatureValidationError(
+            "WebAuthn user verification flag is not set".into(),
+        ));
+    }
+
+    let verifying_key = P256VerifyingKey::from_sec1_bytes(&to_p256_sec1_public_key(public_key))
+        .map_err(|e| RpcError::KeyFormatError(e.to_string()))?;
+    let signature =
+        P256Signature::from_scalars(auth.r.to_be_bytes::<32>(), auth.s.to_be_bytes::<32>())
+            .map_err(|e| RpcError::SignatureFormatError(e.to_string()))?;
+
+    // Signed message is the authenticator data followed by the client data hash
+    let client_data_hash = hex::decode(sha256::digest(auth.clientDataJSON.as_bytes()))
+        .map_err(|e| RpcError::WrongHexFormat(e.to_string()))?;
+    let mut message = auth.authenticatorData.to_vec();
+    message.extend_from_slice(&client_data_hash);
+
+    P256Verifier::verify(&verifying_key, &message, &signature)
+        .map_err(|e| RpcError::SignatureValidationError(e.to_string()))
+}
+
+/// Check the secp256r1 (P-256) public key format
+pub fn is_p256_public_key_valid(public_key: &[u8]) -> bool {
+    P256VerifyingKey::from_sec1_bytes(&to_p256_sec1_public_key(public_key)).is_ok()
+}
+
+/// Convert raw 64 bytes `x` and `y` coordinates to the uncompressed SEC1 format
+fn to_p256_sec1_public_key(public_key: &[u8]) -> Vec<u8> {
+    if public_key.len() == 64 {
+        let mut sec1 = Vec::with_capacity(65);
+        sec1.push(0x04);
+        sec1.extend_from_slice(public_key);
+        sec1
+    } else {
+        public_key.to_vec()
+    }
+}
+
 /// Get the balance of the ERC20 token
 #[tracing::instrument(level = "debug")]
 pub async fn get_erc20_balance(
@@ -554,6 +678,42 @@ pub async fn get_erc20_contract_balance(
     Ok(balance)
 }
 
+/// Get the name, symbol and decimals of ERC20 token by calling the contract
+/// address
+#[tracing::instrument(level = "debug")]
+pub async fn get_erc20_contract_metadata(
+    chain_id: &str,
+    contract: H160,
+    rpc_project_id: &str,
+    source: MessageSource,
+) -> Result<(String, String, u8), CryptoUitlsError> {
+    abigen!(
+        ERC20MetadataContract,
+        r#"[
+            function name() external view returns (string)
+            function symbol() external view returns (string)
+            function decimals() external view returns (uint8)
+        ]"#,
+    );
+
+    let provider = EthersProvider::<Http>::try_from(
+        get_rpc_url(chain_id, rpc_project_id, source, None)?.as_str(),
+    )
+    .map_err(|e| CryptoUitlsError::RpcUrlParseError(format!("Failed to parse RPC url: {e}")))?;
+    let provider = Arc::new(provider);
+
+    let contract = ERC20MetadataContract::new(contract, provider);
+    let name = contract.name();
+    let symbol = contract.symbol();
+    let decimals = contract.decimals();
+    tokio::try_join!(name.call(), symbol.call(), decimals.call()).map_err(|e| {
+        CryptoUitlsError::ContractCallError(format!(
+            "Failed to call ERC20 contract {contract:?} in {chain_id:?} for the metadata.\
+            The error: {e}"
+        ))
+    })
+}
+
 /// Get the balance of the native coin
 #[tracing::instrument(level = "debug")]
 pub async fn get_balance(
@@ -771,6 +931,18 @@ fn is_address_valid_impl(address: &str, namespace: &CaipNamespaces) -> bool {
                 && (address.starts_with('E') || address.starts_with('U'))
                 && address.len() >= 36
         }
+        CaipNamespaces::Sui => {
+            address == NATIVE_ASSET_ADDRESS || CAIP_SUI_ADDRESS_REGEX.is_match(address)
+        }
+        CaipNamespaces::Near => {
+            (2..=64).contains(&address.len()) && CAIP_NEAR_ADDRESS_REGEX.is_match(address)
+        }
+        CaipNamespaces::Bip122 => {
+            address == NATIVE_ASSET_ADDRESS || CAIP_BITCOIN_ADDRESS_REGEX.is_match(address)
+        }
+        CaipNamespaces::Xrpl => {
+            address == NATIVE_ASSET_ADDRESS || CAIP_XRPL_ADDRESS_REGEX.is_match(address)
+        }
     }
 }
 
@@ -942,6 +1114,10 @@ pub enum CaipNamespaces {
     Solana,
     Ton,
     Rootstock, // TODO: A temporary solution to support Rootstock
+    Sui,
+    Near,
+    Bip122,
+    Xrpl,
 }
 
 /// A struct representing a CAIP-2 Chain ID with format:
@@ -1634,6 +1810,75 @@ mod tests {
         .is_err());
     }
 
+    #[test]
+    fn test_verify_webauthn_p256_signature() {
+        use p256::ecdsa::{signature::Signer as P256Signer, SigningKey as P256SigningKey};
+
+        let challenge = [7u8; 32];
+        let signing_key = P256SigningKey::random(&mut OsRng);
+        let public_key = signing_key
+            .verifying_key()
+            .to_encoded_point(false)
+            .as_bytes()
+            .to_vec();
+
+        // Assertion signed with the flags byte of the authenticator data
+        let sign_assertion = |flags: u8| {
+            let mut authenticator_data = vec![1u8; 37];
+            authenticator_data[32] = flags;
+            let client_data_json = format!(
+                r#"{{"type":"webauthn.get","challenge":"{}","origin":"https://example.com"}}"#,
+                BASE64_URL_SAFE_NO_PAD.encode(challenge)
+            );
+            let mut message = authenticator_data.clone();
+            message.extend_from_slice(
+                &hex::decode(sha256::digest(client_data_json.as_bytes())).unwrap(),
+            );
+            let signature: P256Signature = P256Signer::sign(&signing_key, &message);
+            let signature_bytes: &[u8] = signature.as_ref();
+
+            <WebAuthnAuth as SolType>::abi_encode(&WebAuthnAuth {
+                authenticatorData: authenticator_data.into(),
+                clientDataJSON: client_data_json,
+                challengeIndex: AlloyU256::from(23),
+                typeIndex: AlloyU256::from(1),
+                r: AlloyU256::from_be_slice(&signature_bytes[..32]),
+                s: AlloyU256::from_be_slice(&signature_bytes[32..]),
+            })
+        };
+        let encoded = sign_assertion(WEBAUTHN_FLAG_USER_PRESENT);
+
+        // Correct signature with the SEC1 and raw public key formats
+        assert!(verify_webauthn_p256_signature(&challenge, &encoded, &public_key, false).is_ok());
+        assert!(
+            verify_webauthn_p256_signature(&challenge, &encoded, &public_key[1..], false).is_ok()
+        );
+
+        // Wrong challenge
+        assert!(verify_webauthn_p256_signature(&[8u8; 32], &encoded, &public_key, false).is_err());
+
+        // Wrong public key
+        let other_public_key = P256SigningKey::random(&mut OsRng)
+            .verifying_key()
+            .to_encoded_point(false)
+            .as_bytes()
+            .to_vec();
+        assert!(
+            verify_webauthn_p256_signature(&challenge, &encoded, &other_public_key, false).is_err()
+        );
+
+        // User presence is always required
+        let not_present = sign_assertion(WEBAUTHN_FLAG_USER_VERIFIED);
+        assert!(
+            verify_webauthn_p256_signature(&challenge, &not_present, &public_key, false).is_err()
+        );
+
+        // User verification is required by the policy
+        assert!(verify_webauthn_p256_signature(&challenge, &encoded, &public_key, true).is_err());
+        let verified = sign_assertion(WEBAUTHN_FLAG_USER_PRESENT | WEBAUTHN_FLAG_USER_VERIFIED);
+        assert!(verify_webauthn_p256_signature(&challenge, &verified, &public_key, true).is_ok());
+    }
+
     #[test]
     fn test_is_address_valid() {
         let valid_eth_address = "0x1234567890123456789012345678901234567890";
@@ -1645,6 +1890,34 @@ mod tests {
 
         assert!(is_address_valid(valid_sol_address, &CaipNamespaces::Solana));
         assert!(!is_address_valid(invalid_address, &CaipNamespaces::Solana));
+
+        assert!(is_address_valid(
+            "0x02a212de6a9dfa3a69e22387acfbafbb1a9e591bd9d636e7895dcfc8de05f331",
+            &CaipNamespaces::Sui
+        ));
+        assert!(is_address_valid("alice.near", &CaipNamespaces::Near));
+        assert!(!is_address_valid("Alice.near", &CaipNamespaces::Near));
+        assert!(is_address_valid(
+            "bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdq",
+            &CaipNamespaces::Bip122
+        ));
+        assert!(is_address_valid(
+            "1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa",
+            &CaipNamespaces::Bip122
+        ));
+        assert!(is_address_valid(
+            "rHb9CJAWyB4rj91VRWn96DkukG4bwdtyTh",
+            &CaipNamespaces::Xrpl
+        ));
+        assert!(!is_address_valid(invalid_address, &CaipNamespaces::Xrpl));
+        for namespace in [
+            CaipNamespaces::Sui,
+            CaipNamespaces::Near,
+            CaipNamespaces::Bip122,
+            CaipNamespaces::Xrpl,
+        ] {
+            assert!(is_address_valid(NATIVE_ASSET_ADDRESS, &namespace));
+        }
     }
 
     #[test]
@@ -0,0 +1,188 @@
+use {
+    crate::json_rpc::{JsonRpcRequest, JsonRpcResult, JSON_RPC_VERSION},
+    moka::{future::Cache, Expiry},
+    serde_json::{value::RawValue, Value},
+    std::time::{Duration, Instant},
+};
+
+const ETH_CALL_METHOD: &str = "eth_call";
+/// Cache TTL of the `latest` block calls on the chains without the known
+/// block time
+const DEFAULT_LATEST_BLOCK_TTL: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Clone)]
+struct CachedResult {
+    result: Box<RawValue>,
+    ttl: Duration,
+}
+
+struct CachedResultExpiry;
+
+impl Expiry<String, CachedResult> for CachedResultExpiry {
+    fn expire_after_create(
+        &self,
+        _key: &String,
+        value: &CachedResult,
+        _created_at: Instant,
+    ) -> Option<Duration> {
+        Some(value.ttl)
+    }
+}
+
+/// Cache key of the cacheable `eth_call` request
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EthCallCacheKey {
+    key: String,
+    ttl: Duration,
+}
+
+/// Local cache of the `eth_call` results. Calls on the explicit block number
+/// or hash are deterministic and cached for the configured TTL, the `latest`
+/// block calls are cached for about a single block time of the chain.
+pub struct EthCallCache {
+    cache: Cache<String, CachedResult>,
+    block_ttl: Duration,
+}
+
+impl EthCallCache {
+    pub fn new(max_capacity: u64, block_ttl: Duration) -> Self {
+        Self {
+            cache: Cache::builder()
+                .max_capacity(max_capacity)
+                .expire_after(CachedResultExpiry)
+                .build(),
+            block_ttl,
+        }
+    }
+
+    pub fn is_cached_method(method: &str) -> bool {
+        method == ETH_CALL_METHOD
+    }
+
+    /// Returns the cache key when the request is a cacheable `eth_call`
+    pub fn cache_key(&self, chain_id: &str, request: &JsonRpcRequest) -> Option<EthCallCacheKey> {
+        if request.method.as_ref() != ETH_CALL_METHOD || !chain_id.starts_with("eip155:") {
+            return None;
+        }
+        let params = request.params.as_array()?;
+        let ttl = match params.get(1) {
+            None => latest_block_ttl(chain_id),
+            Some(Value::String(tag)) if tag == "latest" => latest_block_ttl(chain_id),
+            // Block number or hash
+            Some(Value::String(tag)) if tag.starts_with("0x") => self.block_ttl,
+            // EIP-1898 block number or hash object
+            Some(Value::Object(block))
+                if block.contains_key("blockHash") || block.contains_key("blockNumber") =>
+            {
+                self.block_ttl
+            }
+            // `pending`, `safe`, `finalized` and `earliest` tags are not cached
+            _ => return None,
+        };
+        Some(EthCallCacheKey {
+            key: format!("{chain_id}:{}", request.params),
+            ttl,
+        })
+    }
+
+    /// Returns the serialized JSON-RPC response with the cached result
+    pub async fn get(&self, key: &EthCallCacheKey, id: &Value) -> Option<String> {
+        let cached = self.cache.get(&key.key).await?;
+        serde_json::to_string(&JsonRpcResult {
+            id: id.clone(),
+            jsonrpc: JSON_RPC_VERSION.clone(),
+            result: cached.result,
+        })
+        .ok()
+    }
+
+    pub async fn insert(&self, key: EthCallCacheKey, result: Box<RawValue>) {
+        self.cache
+            .insert(
+                key.key,
+                CachedResult {
+                    result,
+                    ttl: key.ttl,
+                },
+            )
+            .await;
+    }
+}
+
+/// Approximate block times of the chains
+fn latest_block_ttl(chain_id: &str) -> Duration {
+    match chain_id {
+        // Ethereum
+        "eip155:1" | "eip155:11155111" => Duration::from_secs(12),
+        // BNB Smart Chain
+        "eip155:56" => Duration::from_secs(3),
+        // Optimism, Polygon, Base, Unichain
+        "eip155:10" | "eip155:137" | "eip155:8453" | "eip155:130" => Duration::from_secs(2),
+        // Arbitrum One
+        "eip155:42161" => Duration::from_millis(250),
+        _ => DEFAULT_LATEST_BLOCK_TTL,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, serde_json::json, std::sync::Arc};
+
+    fn eth_call(params: Value) -> JsonRpcRequest {
+        JsonRpcRequest::new_with_params(json!(1), Arc::from(ETH_CALL_METHOD), params)
+    }
+
+    #[test]
+    fn cache_key_by_block() {
+        let cache = EthCallCache::new(10, Duration::from_secs(300));
+        let call = json!({"to": "0x0000000000000000000000000000000000000000", "data": "0x"});
+
+        let key = cache
+            .cache_key("eip155:1", &eth_call(json!([call, "0x10"])))
+            .unwrap();
+        assert_eq!(key.ttl, Duration::from_secs(300));
+
+        let key = cache
+            .cache_key("eip155:1", &eth_call(json!([call, {"blockHash": "0x01"}])))
+            .unwrap();
+        assert_eq!(key.ttl, Duration::from_secs(300));
+
+        let key = cache
+            .cache_key("eip155:1", &eth_call(json!([call, "latest"])))
+            .unwrap();
+        assert_eq!(key.ttl, Duration::from_secs(12));
+
+        let key = cache
+            .cache_key("eip155:1", &eth_call(json!([call])))
+            .unwrap();
+        assert_eq!(key.ttl, Duration::from_secs(12));
+
+        assert!(cache
+            .cache_key("eip155:1", &eth_call(json!([call, "pending"])))
+            .is_none());
+        assert!(cache
+            .cache_key("solana:mainnet", &eth_call(json!([call, "0x10"])))
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn cached_response_with_request_id() {
+        let cache = EthCallCache::new(10, Duration::from_secs(300));
+        let request = eth_call(json!([{"to": "0x01"}, "0x10"]));
+        let key = cache.cache_key("eip155:1", &request).unwrap();
+        assert!(cache.get(&key, &json!(1)).await.is_none());
+
+        cache
+            .insert(
+                key.clone(),
+                RawValue::from_string("\"0x2a\"".to_owned()).unwrap(),
+            )
+            .await;
+        let response: Value =
+            serde_json::from_str(&cache.get(&key, &json!(7)).await.unwrap()).unwrap();
+        assert_eq!(
+            response,
+            json!({"id": 7, "jsonrpc": "2.0", "result": "0x2a"})
+        );
+    }
+}
@@ -0,0 +1,100 @@
+//! Compliance restrictions of the chains and endpoints by the request country.
+
+use {std::collections::HashMap, tracing::warn};
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct CountryRestrictions {
+    chain_ids: Vec<String>,
+    path_prefixes: Vec<String>,
+}
+
+/// Restricted chains and endpoints by the uppercase country code
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GeoRestrictions {
+    countries: HashMap<String, CountryRestrictions>,
+}
+
+impl GeoRestrictions {
+    /// Parses the configured entries, the invalid entries are skipped
+    pub fn from_entries(entries: &[String]) -> Self {
+        let mut countries = HashMap::<String, CountryRestrictions>::new();
+        for entry in entries {
+            let Some((country, target)) = entry
+                .split_once('=')
+                .map(|(country, target)| (country.trim(), target.trim()))
+                .filter(|(country, target)| country.len() == 2 && !target.is_empty())
+            else {
+                warn!("Skipping the invalid geo restriction: {entry}");
+                continue;
+            };
+            let restrictions = countries.entry(country.to_ascii_uppercase()).or_default();
+            if target.starts_with('/') {
+                restrictions.path_prefixes.push(target.to_owned());
+            } else {
+                restrictions.chain_ids.push(target.to_owned());
+            }
+        }
+        Self { countries }
+    }
+
+    /// Returns the restricted target of the request from the country
+    pub fn restricted_target<'a>(
+        &'a self,
+        country: &str,
+        chain_id: Option<&str>,
+        path: &str,
+    ) -> Option<&'a str> {
+        let restrictions = self.countries.get(&country.to_ascii_uppercase())?;
+        let chain_id = chain_id.and_then(|chain_id| {
+            restrictions
+                .chain_ids
+                .iter()
+                .find(|restricted| restricted.as_str() == chain_id)
+        });
+        chain_id
+            .or_else(|| {
+                restrictions
+                    .path_prefixes
+                    .iter()
+                    .find(|prefix| path.starts_with(prefix.as_str()))
+            })
+            .map(String::as_str)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn restricts_chains_and_paths_by_country() {
+        let restrictions = GeoRestrictions::from_entries(&[
+            "cu=eip155:1".to_owned(),
+            "CU=/v1/onramp".to_owned(),
+            "RU=solana:5eykt4UsFv8P8NJdTREpY1vzqKqZKvdp".to_owned(),
+            "invalid".to_owned(),
+            "USA=eip155:1".to_owned(),
+        ]);
+
+        assert_eq!(
+            restrictions.restricted_target("CU", Some("eip155:1"), "/v1"),
+            Some("eip155:1")
+        );
+        assert_eq!(
+            restrictions.restricted_target("cu", None, "/v1/onramp/buy/quotes"),
+            Some("/v1/onramp")
+        );
+        assert_eq!(
+            restrictions.restricted_target("CU", Some("eip155:10"), "/v1"),
+            None
+        );
+        assert_eq!(
+            restrictions.restricted_target("RU", Some("eip155:1"), "/v1/onramp"),
+            None
+        );
+        assert_eq!(
+            restrictions.restricted_target("US", Some("eip155:1"), "/v1"),
+            None
+        );
+    }
+}
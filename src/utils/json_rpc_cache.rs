@@ -19,6 +19,11 @@ pub enum CachedMethods {
     NetVersion,
 }
 
+/// Check if the method responses are cached
+pub fn is_cached_method(method: &str) -> bool {
+    method.parse::<CachedMethods>().is_ok()
+}
+
 /// Check if the response is cached and apply caching
 pub async fn is_cached_response(
     caip2_chain_id: &str,
@@ -6,9 +6,15 @@ pub mod cors;
 pub mod crypto;
 pub mod erc4337;
 pub mod erc7677;
+pub mod eth_call_cache;
+pub mod geo_restrictions;
 pub mod json_rpc_cache;
 pub mod network;
+pub mod partial_json_rpc;
 pub mod permissions;
+pub mod project_allowlist;
+pub mod project_jwt;
+pub mod project_signature;
 pub mod rate_limit;
 pub mod sessions;
 pub mod simple_request_json;
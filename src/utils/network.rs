@@ -74,6 +74,13 @@ pub fn get_forwarded_ip(headers: &HeaderMap) -> Option<IpAddr> {
         .and_then(|client_ip| client_ip.trim().parse::<IpAddr>().ok())
 }
 
+/// Checks the IP address is in any of the ranges, the IPv4-mapped IPv6
+/// addresses are matched as the IPv4 ones
+pub fn is_ip_in_ranges(ranges: &[IpNet], ip: IpAddr) -> bool {
+    let ip = ip.to_canonical();
+    ranges.iter().any(|range| range.contains(&ip))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -99,4 +106,19 @@ mod tests {
             "10.128.128.2".parse::<IpAddr>().unwrap()
         );
     }
+
+    #[test]
+    fn test_is_ip_in_ranges() {
+        let ranges = ["203.0.113.7/32", "198.51.100.0/24", "2001:db8::/32"]
+            .map(|range| range.parse::<IpNet>().unwrap());
+        let is_allowed = |ip: &str| is_ip_in_ranges(&ranges, ip.parse().unwrap());
+
+        assert!(is_allowed("203.0.113.7"));
+        assert!(!is_allowed("203.0.113.8"));
+        assert!(is_allowed("198.51.100.42"));
+        assert!(is_allowed("::ffff:198.51.100.42"));
+        assert!(is_allowed("2001:db8::1"));
+        assert!(!is_allowed("2001:db9::1"));
+        assert!(!is_ip_in_ranges(&[], "203.0.113.7".parse().unwrap()));
+    }
 }
@@ -0,0 +1,228 @@
+//! Partial JSON-RPC schemas for the proxy hot path.
+
+use {
+    alloy::rpc::json_rpc::Id,
+    serde::{de::IgnoredAny, Deserialize},
+    serde_json::value::RawValue,
+    std::{borrow::Cow, collections::HashSet},
+};
+
+/// Request fields used by the proxy, the params are skipped without the
+/// allocations
+#[derive(Debug, Deserialize)]
+pub struct RequestMethod<'a> {
+    #[serde(borrow)]
+    pub method: Cow<'a, str>,
+    // id is required for the analytics, see `batch_json_rpc_request::Request`
+    pub id: Id,
+    #[serde(rename = "jsonrpc")]
+    _jsonrpc: IgnoredAny,
+}
+
+#[derive(Debug)]
+pub enum MaybeBatchRequestMethod<'a> {
+    Single(RequestMethod<'a>),
+    Batch(Vec<RequestMethod<'a>>),
+}
+
+impl<'a> MaybeBatchRequestMethod<'a> {
+    /// Deserializes the single or the batch request by the first JSON token,
+    /// the untagged enum would buffer the whole body before borrowing from it
+    pub fn from_slice(body: &'a [u8]) -> serde_json::Result<Self> {
+        match body.iter().find(|byte| !byte.is_ascii_whitespace()) {
+            Some(b'[') => serde_json::from_slice(body).map(Self::Batch),
+            _ => serde_json::from_slice(body).map(Self::Single),
+        }
+    }
+}
+
+/// Response fields used by the proxy, the result is borrowed as the raw JSON
+#[derive(Debug, Deserialize)]
+pub struct PartialResponse<'a> {
+    /// `None` for the `null` id of the rejected requests errors
+    #[serde(default)]
+    pub id: Option<Id>,
+    #[serde(borrow, default)]
+    pub result: Option<&'a RawValue>,
+    #[serde(borrow, default)]
+    pub error: Option<ResponseError<'a>>,
+}
+
+impl PartialResponse<'_> {
+    /// Whether the response is to the request with the id, the providers
+    /// return the errors of the rejected requests with the `null` id
+    fn matches_id(&self, request_id: &Id) -> bool {
+        match &self.id {
+            Some(id) => id == request_id,
+            None => self.error.is_some() || *request_id == Id::None,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum MaybeBatchResponse<'a> {
+    Single(PartialResponse<'a>),
+    Batch(Vec<PartialResponse<'a>>),
+}
+
+impl<'a> MaybeBatchResponse<'a> {
+    /// Deserializes the single or the batch response by the first JSON token
+    pub fn from_slice(body: &'a [u8]) -> serde_json::Result<Self> {
+        match body.iter().find(|byte| !byte.is_ascii_whitespace()) {
+            Some(b'[') => serde_json::from_slice(body).map(Self::Batch),
+            _ => serde_json::from_slice(body).map(Self::Single),
+        }
+    }
+
+    /// Whether the response ids match the request ids, the batch response
+    /// must have a response per request in any order. The whole batch can be
+    /// rejected by the single error response.
+    pub fn matches_request(&self, request: &MaybeBatchRequestMethod) -> bool {
+        match (self, request) {
+            (Self::Single(response), MaybeBatchRequestMethod::Single(request)) => {
+                response.matches_id(&request.id)
+            }
+            (Self::Batch(responses), MaybeBatchRequestMethod::Batch(requests)) => {
+                let mut ids = requests
+                    .iter()
+                    .map(|request| &request.id)
+                    .collect::<HashSet<_>>();
+                responses.len() == requests.len()
+                    && responses.iter().all(|response| match &response.id {
+                        Some(id) => ids.remove(id),
+                        None => response.error.is_some(),
+                    })
+            }
+            (Self::Single(response), MaybeBatchRequestMethod::Batch(_)) => {
+                response.id.is_none() && response.error.is_some()
+            }
+            (Self::Batch(_), MaybeBatchRequestMethod::Single(_)) => false,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ResponseError<'a> {
+    pub code: i32,
+    #[serde(borrow)]
+    pub message: Cow<'a, str>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deserialize_request_method() {
+        let body =
+            br#"{"jsonrpc":"2.0","method":"eth_call","params":[{"to":"0x01"},"latest"],"id":1}"#;
+        let MaybeBatchRequestMethod::Single(request) =
+            MaybeBatchRequestMethod::from_slice(body).unwrap()
+        else {
+            panic!("Expected a single request");
+        };
+        assert_eq!(request.method, "eth_call");
+        assert!(matches!(request.method, Cow::Borrowed(_)));
+        assert_eq!(request.id, Id::Number(1));
+
+        let body = br#"
+            [{"jsonrpc":"2.0","method":"eth_chainId","id":1},
+             {"jsonrpc":"2.0","method":"eth_blockNumber","params":[],"id":"2"}]"#;
+        let MaybeBatchRequestMethod::Batch(requests) =
+            MaybeBatchRequestMethod::from_slice(body).unwrap()
+        else {
+            panic!("Expected a batch request");
+        };
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[1].method, "eth_blockNumber");
+        assert_eq!(requests[1].id, Id::String("2".to_owned()));
+
+        // Same required fields as the full request schema
+        assert!(
+            MaybeBatchRequestMethod::from_slice(br#"{"method":"eth_chainId","id":1}"#).is_err()
+        );
+        assert!(MaybeBatchRequestMethod::from_slice(br#"{"jsonrpc":"2.0","id":1}"#).is_err());
+    }
+
+    #[test]
+    fn deserialize_partial_response() {
+        let body = br#"{"jsonrpc":"2.0","id":1,"result":{"logs":[{"data":"0x"}]}}"#;
+        let response = serde_json::from_slice::<PartialResponse>(body).unwrap();
+        assert_eq!(
+            response.result.unwrap().get(),
+            r#"{"logs":[{"data":"0x"}]}"#
+        );
+        assert!(response.error.is_none());
+
+        let body = br#"{"jsonrpc":"2.0","id":1,"error":{"code":-32005,"message":"rate \"limited\"","data":{}}}"#;
+        let response = serde_json::from_slice::<PartialResponse>(body).unwrap();
+        assert!(response.result.is_none());
+        let error = response.error.unwrap();
+        assert_eq!(error.code, -32005);
+        assert_eq!(error.message, r#"rate "limited""#);
+    }
+
+    #[test]
+    fn response_matches_request() {
+        let single = br#"{"jsonrpc":"2.0","method":"eth_chainId","id":1}"#;
+        let single = MaybeBatchRequestMethod::from_slice(single).unwrap();
+        let batch = br#"[{"jsonrpc":"2.0","method":"eth_chainId","id":1},
+                         {"jsonrpc":"2.0","method":"eth_blockNumber","id":"2"}]"#;
+        let batch = MaybeBatchRequestMethod::from_slice(batch).unwrap();
+        fn matches(body: &[u8], request: &MaybeBatchRequestMethod) -> bool {
+            MaybeBatchResponse::from_slice(body)
+                .unwrap()
+                .matches_request(request)
+        }
+
+        assert!(matches(
+            br#"{"jsonrpc":"2.0","id":1,"result":"0x1"}"#,
+            &single
+        ));
+        assert!(matches(
+            br#"{"jsonrpc":"2.0","id":1,"result":null}"#,
+            &single
+        ));
+        assert!(!matches(
+            br#"{"jsonrpc":"2.0","id":2,"result":"0x1"}"#,
+            &single
+        ));
+        assert!(!matches(
+            br#"{"jsonrpc":"2.0","id":"1","result":"0x1"}"#,
+            &single
+        ));
+        assert!(matches(
+            br#"{"jsonrpc":"2.0","id":null,"error":{"code":-32005,"message":"limited"}}"#,
+            &single
+        ));
+        assert!(!matches(br#"{"jsonrpc":"2.0","result":"0x1"}"#, &single));
+        assert!(!matches(
+            br#"[{"jsonrpc":"2.0","id":1,"result":"0x1"}]"#,
+            &single
+        ));
+
+        assert!(matches(
+            br#"[{"jsonrpc":"2.0","id":"2","result":"0x2"},{"jsonrpc":"2.0","id":1,"result":"0x1"}]"#,
+            &batch
+        ));
+        assert!(!matches(
+            br#"[{"jsonrpc":"2.0","id":1,"result":"0x1"}]"#,
+            &batch
+        ));
+        assert!(!matches(
+            br#"[{"jsonrpc":"2.0","id":1,"result":"0x1"},{"jsonrpc":"2.0","id":1,"result":"0x1"}]"#,
+            &batch
+        ));
+        assert!(matches(
+            br#"{"jsonrpc":"2.0","id":null,"error":{"code":-32600,"message":"too large"}}"#,
+            &batch
+        ));
+    }
+
+    #[test]
+    fn rejects_malformed_response() {
+        assert!(MaybeBatchResponse::from_slice(b"<html>Bad Gateway</html>").is_err());
+        assert!(MaybeBatchResponse::from_slice(br#"{"jsonrpc":"2.0","id":1,"res"#).is_err());
+        assert!(MaybeBatchResponse::from_slice(b"").is_err());
+    }
+}
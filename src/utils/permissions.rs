@@ -1,14 +1,16 @@
 use {
     crate::{
+        database::sessions_spending::SpendingReservation,
         error::RpcError,
         utils::sessions::{
             extract_addresses_from_execution_batch, extract_values_sum_from_execution_batch,
             ExecutionTransaction,
         },
     },
-    alloy::primitives::{Address, U256},
+    alloy::primitives::{Address, Bytes, U256},
     serde::{Deserialize, Serialize},
     serde_json::Value,
+    std::collections::HashMap,
     strum_macros::{Display, EnumIter, EnumString},
     tracing::error,
 };
@@ -21,6 +23,38 @@ pub enum PermissionType {
     NativeTokenRecurringAllowance,
 }
 
+/// Supported policy types
+#[derive(Clone, Copy, Debug, EnumString, EnumIter, Display, PartialEq)]
+#[strum(serialize_all = "kebab-case")]
+pub enum PolicyType {
+    SpendingLimit,
+    ContractAllowlist,
+}
+
+/// Session signer key types
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SignerKeyType {
+    Secp256k1,
+    /// P-256 WebAuthn passkey
+    Secp256r1,
+}
+
+/// Session signer key schema
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SignerKey {
+    pub r#type: SignerKeyType,
+    pub public_key: Bytes,
+}
+
+/// `keys` signer type data schema
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KeysSignerData {
+    pub keys: Vec<SignerKey>,
+}
+
 /// `contract-call` permission type data schema
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -39,6 +73,32 @@ pub struct NativeTokenAllowancePermissionData {
     pub period: usize,
 }
 
+/// `spending-limit` policy type data schema
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SpendingLimitPolicyData {
+    /// ERC-20 token contract address, native token is used when omitted
+    #[serde(default)]
+    pub token: Option<Address>,
+    pub limit: U256,
+    /// Limit reset period in seconds, the limit is for the whole permission
+    /// lifetime when omitted
+    #[serde(default)]
+    pub period: Option<usize>,
+}
+
+/// `contract-allowlist` policy type data schema
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContractAllowlistPolicyData {
+    pub addresses: Vec<Address>,
+}
+
+/// Spendings of the `spending-limit` policies keyed by the policy index in the
+/// permission policies list, reserved in the cumulative usage before the
+/// cosigning
+pub type PolicySpendings = HashMap<usize, (SpendingLimitPolicyData, U256)>;
+
 /// `contract-call` permission type check
 pub fn contract_call_permission_check(
     execution_batch: Vec<ExecutionTransaction>,
@@ -77,3 +137,136 @@ pub fn native_token_transfer_permission_check(
 
     Ok(())
 }
+
+/// `spending-limit` policy type check. Adds the execution spendings to the
+/// policy spendings of the request, the cumulative usage is checked when the
+/// spendings are reserved
+pub fn spending_limit_policy_check(
+    spendings: &HashMap<Option<Address>, U256>,
+    policy_index: usize,
+    policy_data: SpendingLimitPolicyData,
+    policy_spendings: &mut PolicySpendings,
+) -> Result<(), RpcError> {
+    let spent = spendings
+        .get(&policy_data.token)
+        .copied()
+        .unwrap_or(U256::ZERO);
+    let limit = policy_data.limit;
+    let (_, total) = policy_spendings
+        .entry(policy_index)
+        .or_insert((policy_data, U256::ZERO));
+    let total_spent = total.saturating_add(spent);
+    if total_spent > limit {
+        error!(
+            "Execution spendings exceed the spending limit. Spendings: {:?}, Limit: {:?}",
+            total_spent, limit
+        );
+        return Err(RpcError::CosignerPermissionDenied(format!(
+            "Execution spendings exceed the spending limit. Spendings: {total_spent:?}, Limit: {limit:?}"
+        )));
+    }
+    *total = total_spent;
+
+    Ok(())
+}
+
+/// Reservations of the non-zero policy spendings in the cumulative usage
+pub fn spending_reservations(policy_spendings: &PolicySpendings) -> Vec<SpendingReservation> {
+    let mut reservations = policy_spendings
+        .iter()
+        .filter(|(_, (_, spent))| !spent.is_zero())
+        .map(|(policy_index, (policy_data, spent))| SpendingReservation {
+            policy_index: *policy_index as i32,
+            amount: spent.to_string(),
+            limit: policy_data.limit.to_string(),
+            period: policy_data.period.unwrap_or_default() as i64,
+        })
+        .collect::<Vec<_>>();
+    // Reserving in the same order to not deadlock the concurrent reservations
+    reservations.sort_by_key(|reservation| reservation.policy_index);
+    reservations
+}
+
+/// `contract-allowlist` policy type check
+pub fn contract_allowlist_policy_check(
+    execution_batch: Vec<ExecutionTransaction>,
+    policy_data: &ContractAllowlistPolicyData,
+) -> Result<(), RpcError> {
+    for address in extract_addresses_from_execution_batch(execution_batch)? {
+        if !policy_data.addresses.contains(&address) {
+            return Err(RpcError::CosignerPermissionDenied(format!(
+                "Execution address {address:?} is not in the contract allowlist policy"
+            )));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, alloy::primitives::address};
+
+    #[test]
+    fn spending_limit_policy_request_spendings() {
+        let token = address!("aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa");
+        let policy_data = SpendingLimitPolicyData {
+            token: Some(token),
+            limit: U256::from(100),
+            period: Some(10),
+        };
+        let spendings = HashMap::from([(Some(token), U256::from(60))]);
+        let mut policy_spendings = PolicySpendings::new();
+
+        spending_limit_policy_check(&spendings, 0, policy_data.clone(), &mut policy_spendings)
+            .unwrap();
+        assert_eq!(policy_spendings[&0].1, U256::from(60));
+
+        // Exceeds the limit within the same request and keeps the spendings
+        // untouched
+        assert!(spending_limit_policy_check(
+            &spendings,
+            0,
+            policy_data.clone(),
+            &mut policy_spendings
+        )
+        .is_err());
+        assert_eq!(policy_spendings[&0].1, U256::from(60));
+
+        // Native token spendings are not counted for the token policy
+        let native_spendings = HashMap::from([(None, U256::from(1000))]);
+        spending_limit_policy_check(&native_spendings, 0, policy_data, &mut policy_spendings)
+            .unwrap();
+        assert_eq!(policy_spendings[&0].1, U256::from(60));
+    }
+
+    #[test]
+    fn spending_reservations_of_spent_policies() {
+        let policy_data = SpendingLimitPolicyData {
+            token: None,
+            limit: U256::from(100),
+            period: None,
+        };
+        let policy_spendings = PolicySpendings::from([
+            (2, (policy_data.clone(), U256::from(60))),
+            (1, (policy_data.clone(), U256::from(10))),
+            (0, (policy_data, U256::ZERO)),
+        ]);
+        assert_eq!(
+            spending_reservations(&policy_spendings),
+            vec![
+                SpendingReservation {
+                    policy_index: 1,
+                    amount: "10".to_owned(),
+                    limit: "100".to_owned(),
+                    period: 0,
+                },
+                SpendingReservation {
+                    policy_index: 2,
+                    amount: "60".to_owned(),
+                    limit: "100".to_owned(),
+                    period: 0,
+                },
+            ]
+        );
+    }
+}
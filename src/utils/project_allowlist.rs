@@ -0,0 +1,121 @@
+use {
+    crate::{error::RpcError, utils::cors},
+    cerberus::project::ProjectData,
+    hyper::{header, HeaderMap},
+};
+
+/// iOS application bundle ID header sent by the mobile SDKs
+pub const BUNDLE_ID_HEADER: &str = "x-bundle-id";
+/// Android application package name header sent by the mobile SDKs
+pub const PACKAGE_NAME_HEADER: &str = "x-package-name";
+
+/// Validates the request `Origin` and the mobile platform headers against the
+/// project's allowed origins, bundle IDs and package names.
+/// Empty project lists and missing headers are allowing the request, the same
+/// way as the Cloud dashboard treats the not configured allowlists.
+pub fn validate_project_allowlist(
+    project: &ProjectData,
+    headers: &HeaderMap,
+) -> Result<(), RpcError> {
+    if let Some(origin) = header_value(headers, header::ORIGIN.as_str()) {
+        if !project.allowed_origins.is_empty()
+            && !cors::is_origin_allowed(&project.allowed_origins, origin)
+        {
+            return Err(RpcError::OriginNotAllowed(origin.to_owned()));
+        }
+    }
+
+    if let Some(bundle_id) = header_value(headers, BUNDLE_ID_HEADER) {
+        if !is_app_allowed(&project.bundle_ids, bundle_id) {
+            return Err(RpcError::ApplicationNotAllowed(bundle_id.to_owned()));
+        }
+    }
+
+    if let Some(package_name) = header_value(headers, PACKAGE_NAME_HEADER) {
+        if !is_app_allowed(&project.package_names, package_name) {
+            return Err(RpcError::ApplicationNotAllowed(package_name.to_owned()));
+        }
+    }
+
+    Ok(())
+}
+
+fn header_value<'a>(headers: &'a HeaderMap, name: &str) -> Option<&'a str> {
+    headers
+        .get(name)
+        .and_then(|value| value.to_str().ok())
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+}
+
+fn is_app_allowed(allowed: &[String], app_id: &str) -> bool {
+    allowed.is_empty()
+        || allowed
+            .iter()
+            .any(|entry| entry.trim().eq_ignore_ascii_case(app_id))
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, hyper::header::HeaderValue};
+
+    fn project() -> ProjectData {
+        ProjectData {
+            uuid: "".to_owned(),
+            creator: "".to_owned(),
+            name: "".to_owned(),
+            push_url: None,
+            keys: vec![],
+            is_enabled: true,
+            is_verify_enabled: false,
+            is_rate_limited: false,
+            allowed_origins: vec!["https://*.example.com".to_owned()],
+            verified_domains: vec![],
+            bundle_ids: vec!["com.example.app".to_owned()],
+            package_names: vec![],
+        }
+    }
+
+    fn headers(entries: &[(&'static str, &'static str)]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for (name, value) in entries {
+            headers.insert(*name, HeaderValue::from_static(value));
+        }
+        headers
+    }
+
+    #[test]
+    fn allows_matching_headers() {
+        let project = project();
+        assert!(validate_project_allowlist(&project, &headers(&[])).is_ok());
+        assert!(validate_project_allowlist(
+            &project,
+            &headers(&[("origin", "https://app.example.com")])
+        )
+        .is_ok());
+        assert!(validate_project_allowlist(
+            &project,
+            &headers(&[(BUNDLE_ID_HEADER, "com.example.app")])
+        )
+        .is_ok());
+        // Empty package names list allows any package
+        assert!(validate_project_allowlist(
+            &project,
+            &headers(&[(PACKAGE_NAME_HEADER, "com.other.app")])
+        )
+        .is_ok());
+    }
+
+    #[test]
+    fn rejects_mismatched_headers() {
+        let project = project();
+        assert!(matches!(
+            validate_project_allowlist(&project, &headers(&[("origin", "https://example.org")])),
+            Err(RpcError::OriginNotAllowed(_))
+        ));
+        assert!(matches!(
+            validate_project_allowlist(&project, &headers(&[(BUNDLE_ID_HEADER, "com.other.app")])),
+            Err(RpcError::ApplicationNotAllowed(_))
+        ));
+    }
+}
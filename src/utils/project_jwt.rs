@@ -0,0 +1,194 @@
+use {
+    crate::error::RpcError,
+    hyper::{header, HeaderMap, Uri},
+    jsonwebtoken::{decode, Algorithm, DecodingKey, Validation},
+    serde::Deserialize,
+    tracing::error,
+};
+
+/// Project JWT claims, the subject is the project ID
+#[derive(Debug, Clone, Deserialize)]
+pub struct ProjectJwtClaims {
+    pub sub: String,
+    pub exp: u64,
+}
+
+/// Project ID of the verified project JWT, added to the request extensions
+/// by the project JWT middleware
+#[derive(Debug, Clone)]
+pub struct VerifiedProjectId(pub String);
+
+/// Get the bearer token from the `Authorization` header
+pub fn bearer_token(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(str::trim)
+        .filter(|token| !token.is_empty())
+}
+
+/// Decodes the project JWT claims without the signature verification to get
+/// the project which keys the token should be verified with.
+/// Returns `None` when the token is not a project JWT.
+pub fn decode_unverified(token: &str) -> Option<ProjectJwtClaims> {
+    let mut validation = Validation::new(Algorithm::HS256);
+    validation.insecure_disable_signature_validation();
+    validation.validate_exp = false;
+    validation.set_required_spec_claims(&["sub", "exp"]);
+    decode::<ProjectJwtClaims>(token, &DecodingKey::from_secret(&[]), &validation)
+        .ok()
+        .map(|data| data.claims)
+}
+
+/// Verifies the ES256 project JWT signed by the private key of any of the
+/// project's public keys, so the keys can be rotated without invalidating the
+/// issued tokens until the previous key is removed. The project ID is public,
+/// so the tokens of the projects without the keys are rejected.
+pub fn verify(
+    token: &str,
+    project_id: &str,
+    public_keys: &[String],
+) -> Result<ProjectJwtClaims, RpcError> {
+    let mut validation = Validation::new(Algorithm::ES256);
+    validation.set_required_spec_claims(&["sub", "exp"]);
+    validation.sub = Some(project_id.to_owned());
+
+    let mut last_error = None;
+    for public_key in public_keys {
+        let key = match DecodingKey::from_ec_pem(public_key.as_bytes()) {
+            Ok(key) => key,
+            Err(e) => {
+                error!("Invalid project JWT public key for project {project_id}: {e}");
+                continue;
+            }
+        };
+        match decode::<ProjectJwtClaims>(token, &key, &validation) {
+            Ok(data) => return Ok(data.claims),
+            Err(e) => last_error = Some(e),
+        }
+    }
+    Err(RpcError::InvalidProjectJwt(
+        last_error
+            .map(|e| e.to_string())
+            .unwrap_or_else(|| "project has no valid JWT public keys".to_owned()),
+    ))
+}
+
+/// Adds the verified project ID to the request URI query for the handlers
+/// extracting the `projectId` query parameter
+pub fn with_project_id(uri: &Uri, project_id: &str) -> Result<Uri, RpcError> {
+    let project_id =
+        url::form_urlencoded::byte_serialize(project_id.as_bytes()).collect::<String>();
+    let path_and_query = match uri.query().filter(|query| !query.is_empty()) {
+        Some(query) => format!("{}?{query}&projectId={project_id}", uri.path()),
+        None => format!("{}?projectId={project_id}", uri.path()),
+    };
+    let mut parts = uri.clone().into_parts();
+    parts.path_and_query = Some(
+        path_and_query
+            .parse()
+            .map_err(|e| RpcError::InvalidProjectJwt(format!("{e}")))?,
+    );
+    Uri::from_parts(parts).map_err(|e| RpcError::InvalidProjectJwt(format!("{e}")))
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        jsonwebtoken::{encode, EncodingKey, Header},
+        openssl::{
+            ec::{EcGroup, EcKey},
+            nid::Nid,
+            pkey::PKey,
+        },
+        serde::Serialize,
+    };
+
+    #[derive(Serialize)]
+    struct Claims<'a> {
+        sub: &'a str,
+        exp: u64,
+    }
+
+    /// PKCS#8 private key and the public key PEMs of a new P-256 key pair
+    fn key_pair() -> (Vec<u8>, String) {
+        let group = EcGroup::from_curve_name(Nid::X9_62_PRIME256V1).unwrap();
+        let key = PKey::from_ec_key(EcKey::generate(&group).unwrap()).unwrap();
+        (
+            key.private_key_to_pem_pkcs8().unwrap(),
+            String::from_utf8(key.public_key_to_pem().unwrap()).unwrap(),
+        )
+    }
+
+    fn claims(sub: &str) -> Claims<'_> {
+        let exp = chrono::Utc::now().timestamp() as u64 + 600;
+        Claims { sub, exp }
+    }
+
+    fn token(sub: &str, private_key: &[u8]) -> String {
+        encode(
+            &Header::new(Algorithm::ES256),
+            &claims(sub),
+            &EncodingKey::from_ec_pem(private_key).unwrap(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn verifies_with_rotated_keys() {
+        let (old_private, _) = key_pair();
+        let (current_private, current_public) = key_pair();
+        let (next_private, next_public) = key_pair();
+        let public_keys = vec![current_public, next_public];
+
+        let jwt = token("project", &next_private);
+        assert_eq!(decode_unverified(&jwt).unwrap().sub, "project");
+        assert_eq!(
+            verify(&jwt, "project", &public_keys).unwrap().sub,
+            "project"
+        );
+        assert!(verify(&token("project", &current_private), "project", &public_keys).is_ok());
+
+        // Removed key
+        assert!(verify(&token("project", &old_private), "project", &public_keys).is_err());
+        // Another project subject
+        assert!(verify(&token("other", &current_private), "project", &public_keys).is_err());
+        // Project without the keys
+        assert!(verify(&token("project", &current_private), "project", &[]).is_err());
+    }
+
+    #[test]
+    fn rejects_tokens_signed_with_project_id() {
+        let (_, public_key) = key_pair();
+        let jwt = encode(
+            &Header::default(),
+            &claims("project"),
+            &EncodingKey::from_secret(b"project"),
+        )
+        .unwrap();
+        assert_eq!(decode_unverified(&jwt).unwrap().sub, "project");
+        assert!(verify(&jwt, "project", &[public_key]).is_err());
+        assert!(verify(&jwt, "project", &[]).is_err());
+    }
+
+    #[test]
+    fn adds_project_id_to_query() {
+        let uri: Uri = "/v1?chainId=eip155:1".parse().unwrap();
+        assert_eq!(
+            with_project_id(&uri, "project").unwrap(),
+            "/v1?chainId=eip155:1&projectId=project"
+        );
+        let uri: Uri = "/v1/supported-chains".parse().unwrap();
+        assert_eq!(
+            with_project_id(&uri, "project").unwrap(),
+            "/v1/supported-chains?projectId=project"
+        );
+    }
+
+    #[test]
+    fn ignores_non_project_tokens() {
+        assert!(decode_unverified("not-a-jwt").is_none());
+    }
+}
@@ -0,0 +1,140 @@
+use {
+    crate::{error::RpcError, utils::crypto::constant_time_eq},
+    cerberus::project::ProjectData,
+    hyper::HeaderMap,
+    openssl::{hash::MessageDigest, pkey::PKey, sign::Signer},
+};
+
+pub const TIMESTAMP_HEADER: &str = "x-project-timestamp";
+pub const SIGNATURE_HEADER: &str = "x-project-signature";
+/// Maximum clock skew of the signed request timestamp to limit the replays
+pub const MAX_TIMESTAMP_SKEW_SECS: i64 = 300;
+
+/// Request signature headers
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignatureHeaders {
+    pub timestamp: i64,
+    pub signature: String,
+}
+
+/// Get the signature headers, returns `None` when the request is not signed
+pub fn signature_headers(headers: &HeaderMap) -> Option<Result<SignatureHeaders, RpcError>> {
+    let signature = headers.get(SIGNATURE_HEADER)?;
+    let parse = || {
+        let timestamp = headers
+            .get(TIMESTAMP_HEADER)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.parse().ok())
+            .ok_or_else(|| {
+                RpcError::InvalidProjectSignature(format!("missing or invalid {TIMESTAMP_HEADER}"))
+            })?;
+        let signature = signature
+            .to_str()
+            .map_err(|e| RpcError::InvalidProjectSignature(e.to_string()))?
+            .to_owned();
+        Ok(SignatureHeaders {
+            timestamp,
+            signature,
+        })
+    };
+    Some(parse())
+}
+
+/// HMAC-SHA256 hex signature of the `{timestamp}.{method}.{path_and_query}.{body}`
+/// by the project key
+pub fn sign(
+    key: &str,
+    timestamp: i64,
+    method: &str,
+    path_and_query: &str,
+    body: &[u8],
+) -> Result<String, openssl::error::ErrorStack> {
+    let pkey = PKey::hmac(key.as_bytes())?;
+    let mut signer = Signer::new(MessageDigest::sha256(), &pkey)?;
+    signer.update(format!("{timestamp}.{method}.{path_and_query}.").as_bytes())?;
+    signer.update(body)?;
+    Ok(hex::encode(signer.sign_to_vec()?))
+}
+
+/// Verifies the request is signed by any of the project's valid keys, so the
+/// keys can be rotated the same way as for the project JWTs
+pub fn verify(
+    project: &ProjectData,
+    headers: &SignatureHeaders,
+    now: i64,
+    method: &str,
+    path_and_query: &str,
+    body: &[u8],
+) -> Result<(), RpcError> {
+    if (now - headers.timestamp).abs() > MAX_TIMESTAMP_SKEW_SECS {
+        return Err(RpcError::InvalidProjectSignature(
+            "timestamp is outside of the allowed window".to_owned(),
+        ));
+    }
+    for key in project.keys.iter().filter(|key| key.is_valid) {
+        let expected = sign(&key.value, headers.timestamp, method, path_and_query, body)
+            .map_err(|e| RpcError::InvalidProjectSignature(e.to_string()))?;
+        if constant_time_eq(&expected, &headers.signature) {
+            return Ok(());
+        }
+    }
+    Err(RpcError::InvalidProjectSignature(
+        "signature doesn't match the project keys".to_owned(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, cerberus::project::ProjectKey};
+
+    fn project(keys: &[(&str, bool)]) -> ProjectData {
+        ProjectData {
+            uuid: "".to_owned(),
+            creator: "".to_owned(),
+            name: "".to_owned(),
+            push_url: None,
+            keys: keys
+                .iter()
+                .map(|(value, is_valid)| ProjectKey {
+                    value: value.to_string(),
+                    is_valid: *is_valid,
+                })
+                .collect(),
+            is_enabled: true,
+            is_verify_enabled: false,
+            is_rate_limited: false,
+            allowed_origins: vec![],
+            verified_domains: vec![],
+            bundle_ids: vec![],
+            package_names: vec![],
+        }
+    }
+
+    fn signed(key: &str, timestamp: i64) -> SignatureHeaders {
+        SignatureHeaders {
+            timestamp,
+            signature: sign(key, timestamp, "POST", "/v1/sessions/0x1/sign", b"{}").unwrap(),
+        }
+    }
+
+    #[test]
+    fn verifies_with_valid_keys() {
+        let project = project(&[("revoked", false), ("current", true)]);
+        let verify = |headers: &SignatureHeaders, body: &[u8]| {
+            verify(
+                &project,
+                headers,
+                1_700_000_000,
+                "POST",
+                "/v1/sessions/0x1/sign",
+                body,
+            )
+        };
+
+        assert!(verify(&signed("current", 1_700_000_000), b"{}").is_ok());
+        assert!(verify(&signed("current", 1_700_000_100), b"{}").is_ok());
+        assert!(verify(&signed("current", 1_700_000_000), b"{\"a\":1}").is_err());
+        assert!(verify(&signed("revoked", 1_700_000_000), b"{}").is_err());
+        assert!(verify(&signed("current", 1_699_999_000), b"{}").is_err());
+    }
+}
@@ -1,41 +1,223 @@
 use {
     crate::metrics::Metrics,
+    arc_swap::ArcSwap,
     chrono::{Duration, Utc},
-    deadpool_redis::Pool,
+    deadpool_redis::{redis::Script, Pool},
+    hyper::header::{HeaderMap, HeaderValue, RETRY_AFTER},
     moka::future::Cache,
     serde::Deserialize,
-    std::{sync::Arc, time::SystemTime},
+    std::{collections::HashMap, sync::Arc, time::SystemTime},
     tracing::error,
-    wc::rate_limit::{token_bucket, RateLimitError, RateLimitExceeded},
 };
 
+pub const RATE_LIMIT_LIMIT_HEADER: &str = "x-ratelimit-limit";
+pub const RATE_LIMIT_REMAINING_HEADER: &str = "x-ratelimit-remaining";
+
+/// Refills and consumes the token of the bucket atomically, so the concurrent
+/// requests of the different replicas can't race on the bucket state.
+/// Returns the remaining tokens, which is negative when the bucket is empty,
+/// and the next refill timestamp in milliseconds.
+const TOKEN_BUCKET_SCRIPT: &str = r#"
+local max_tokens = tonumber(ARGV[1])
+local interval = tonumber(ARGV[2])
+local refill_rate = tonumber(ARGV[3])
+local now = tonumber(ARGV[4])
+
+local bucket = redis.call("HMGET", KEYS[1], "tokens", "ts")
+local tokens = tonumber(bucket[1])
+local ts = tonumber(bucket[2])
+if tokens == nil or ts == nil then
+    tokens = max_tokens
+    ts = now
+end
+
+local intervals = math.floor(math.max(now - ts, 0) / interval)
+if intervals > 0 then
+    tokens = math.min(max_tokens, tokens + intervals * refill_rate)
+    ts = ts + intervals * interval
+end
+
+local remaining = tokens - 1
+if remaining >= 0 then
+    tokens = remaining
+end
+redis.call("HSET", KEYS[1], "tokens", tokens, "ts", ts)
+-- The bucket is full again when the key expires
+local refills = math.ceil((max_tokens - tokens) / math.max(refill_rate, 1))
+redis.call("PEXPIRE", KEYS[1], math.max(refills, 1) * interval)
+
+return {remaining, ts + interval}
+"#;
+
+/// Remaining tokens share of the bucket size when the rate limit headers are
+/// returned for the not rejected requests
+const NEARLY_EXHAUSTED_RATIO: f64 = 0.1;
+
 #[derive(Debug, Clone, Deserialize, PartialEq, Eq)]
 pub struct RateLimitingConfig {
     pub max_tokens: Option<u32>,
     pub refill_interval_sec: Option<u32>,
     pub refill_rate: Option<u32>,
     pub ip_whitelist: Option<Vec<String>>,
+    /// Per project token bucket, checked in addition to the per IP one
+    pub project_max_tokens: Option<u32>,
+    pub project_refill_rate: Option<u32>,
+    /// Plan tier specific project token buckets in the
+    /// `tier:max_tokens:refill_rate` format
+    pub tiers: Option<Vec<String>>,
+}
+
+/// Token bucket parameters
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BucketLimits {
+    pub max_tokens: u32,
+    pub refill_rate: u32,
+}
+
+/// Token buckets limits, which can be updated on the config reload
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RateLimits {
+    pub ip: BucketLimits,
+    pub ip_whitelist: Option<Vec<String>>,
+    pub project: Option<BucketLimits>,
+    pub tiers: HashMap<String, BucketLimits>,
+}
+
+impl RateLimits {
+    /// Returns `None` when the IP token bucket is not configured
+    pub fn from_config(config: &RateLimitingConfig) -> Result<Option<Self>, String> {
+        let (Some(max_tokens), Some(refill_rate)) = (config.max_tokens, config.refill_rate) else {
+            return Ok(None);
+        };
+        Ok(Some(Self {
+            ip: BucketLimits {
+                max_tokens,
+                refill_rate,
+            },
+            ip_whitelist: config.ip_whitelist.clone(),
+            project: config
+                .project_max_tokens
+                .zip(config.project_refill_rate)
+                .map(|(max_tokens, refill_rate)| BucketLimits {
+                    max_tokens,
+                    refill_rate,
+                }),
+            tiers: parse_tier_limits(config.tiers.as_deref().unwrap_or_default())?,
+        }))
+    }
+}
+
+/// Parse the `tier:max_tokens:refill_rate` formatted tiers token buckets
+pub fn parse_tier_limits(tiers: &[String]) -> Result<HashMap<String, BucketLimits>, String> {
+    tiers
+        .iter()
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| {
+            let mut parts = entry.split(':');
+            match (parts.next(), parts.next(), parts.next(), parts.next()) {
+                (Some(tier), Some(max_tokens), Some(refill_rate), None) if !tier.is_empty() => {
+                    let max_tokens = max_tokens
+                        .parse()
+                        .map_err(|e| format!("Invalid max tokens in the tier {entry}: {e}"))?;
+                    let refill_rate = refill_rate
+                        .parse()
+                        .map_err(|e| format!("Invalid refill rate in the tier {entry}: {e}"))?;
+                    Ok((
+                        tier.to_owned(),
+                        BucketLimits {
+                            max_tokens,
+                            refill_rate,
+                        },
+                    ))
+                }
+                _ => Err(format!("Invalid rate limiting tier format: {entry}")),
+            }
+        })
+        .collect()
+}
+
+/// Token bucket state of the rate limiting key
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimitStatus {
+    /// Token bucket size
+    pub limit: u32,
+    pub remaining: u32,
+    /// Unix timestamp in seconds of the next tokens refill
+    pub reset: u64,
+}
+
+impl RateLimitStatus {
+    fn remaining_ratio(&self) -> f64 {
+        self.remaining as f64 / self.limit.max(1) as f64
+    }
+
+    /// The token bucket state with the lower remaining tokens share
+    pub fn most_exhausted(a: Option<Self>, b: Option<Self>) -> Option<Self> {
+        match (a, b) {
+            (Some(a), Some(b)) => Some(if b.remaining_ratio() < a.remaining_ratio() {
+                b
+            } else {
+                a
+            }),
+            (a, b) => a.or(b),
+        }
+    }
+
+    pub fn is_nearly_exhausted(&self) -> bool {
+        (self.remaining as f64) <= (self.limit as f64) * NEARLY_EXHAUSTED_RATIO
+    }
+
+    /// Standard rate limit response headers, `Retry-After` is only added when
+    /// the bucket is exhausted
+    pub fn headers(&self, now: u64) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(RATE_LIMIT_LIMIT_HEADER, HeaderValue::from(self.limit));
+        headers.insert(
+            RATE_LIMIT_REMAINING_HEADER,
+            HeaderValue::from(self.remaining),
+        );
+        if self.remaining == 0 {
+            // At least a second to not retry before the refill
+            headers.insert(
+                RETRY_AFTER,
+                HeaderValue::from(self.reset.saturating_sub(now).max(1)),
+            );
+        }
+        headers
+    }
+}
+
+/// Token bucket which rejected the request
+#[derive(Debug, Clone, Copy, PartialEq, Eq, strum_macros::Display)]
+#[strum(serialize_all = "lowercase")]
+pub enum RateLimitBucket {
+    Ip,
+    Project,
+}
+
+#[derive(Debug, Clone, thiserror::Error)]
+#[error("Rate limit exceeded. Try again at {}", .status.reset)]
+pub struct RateLimitExceeded {
+    pub status: RateLimitStatus,
+    pub bucket: RateLimitBucket,
 }
 
 pub struct RateLimit {
     mem_cache: Cache<String, u64>,
     redis_pool: Arc<Pool>,
-    max_tokens: u32,
+    limits: ArcSwap<RateLimits>,
     interval: Duration,
-    refill_rate: u32,
     metrics: Arc<Metrics>,
-    ip_whitelist: Option<Vec<String>>,
+    token_bucket_script: Script,
 }
 
 impl RateLimit {
     pub fn new(
         redis_addr: &str,
         redis_pool_max_size: usize,
-        max_tokens: u32,
         interval: Duration,
-        refill_rate: u32,
+        limits: RateLimits,
         metrics: Arc<Metrics>,
-        ip_whitelist: Option<Vec<String>>,
     ) -> Option<Self> {
         let redis_builder = deadpool_redis::Config::from_url(redis_addr)
             .builder()
@@ -67,59 +249,149 @@ impl RateLimit {
         Some(Self {
             mem_cache,
             redis_pool,
-            max_tokens,
+            limits: ArcSwap::from_pointee(limits),
             interval,
-            refill_rate,
             metrics,
-            ip_whitelist,
+            token_bucket_script: Script::new(TOKEN_BUCKET_SCRIPT),
         })
     }
 
+    /// Replaces the token buckets limits, the buckets state is kept
+    pub fn update_limits(&self, limits: RateLimits) {
+        self.limits.store(Arc::new(limits));
+    }
+
     fn format_key(&self, endpoint: &str, ip: &str) -> String {
         format!("rate_limit:{endpoint}:{ip}")
     }
 
-    /// Checks if the given endpoint, ip and project ID is rate limited
+    fn format_project_key(&self, endpoint: &str, project_id: &str) -> String {
+        format!("rate_limit:{endpoint}:project:{project_id}")
+    }
+
+    /// Whitelisted IPs are not rate limited by any token bucket
+    pub fn is_whitelisted(&self, ip: &str) -> bool {
+        self.limits
+            .load()
+            .ip_whitelist
+            .as_ref()
+            .is_some_and(|whitelist| whitelist.iter().any(|whitelisted| whitelisted == ip))
+    }
+
+    /// Checks if the given endpoint and IP is rate limited.
+    /// Returns the token bucket state when the request is not rate limited,
+    /// which is `None` for the rate limiting errors.
     #[tracing::instrument(skip(self), level = "debug")]
-    pub async fn is_rate_limited(
+    pub async fn is_ip_rate_limited(
         &self,
         endpoint: &str,
         ip: &str,
-        _project_id: Option<&str>,
-    ) -> Result<(), RateLimitExceeded> {
-        // Check first if the IP is in the white list
-        if let Some(whitelist) = &self.ip_whitelist {
-            if whitelist.contains(&ip.to_string()) {
-                return Ok(());
-            }
+    ) -> Result<Option<RateLimitStatus>, RateLimitExceeded> {
+        let limits = self.limits.load().ip;
+        self.check_bucket(self.format_key(endpoint, ip), limits, RateLimitBucket::Ip)
+            .await
+    }
+
+    /// Checks if the given endpoint and project ID is rate limited, so the
+    /// project is limited across all of its client IPs. Projects of the plan
+    /// tiers with the configured limits are using the tier's bucket instead of
+    /// the default project bucket.
+    /// The project must be validated by the caller, so the other clients
+    /// can't spend the project's tokens.
+    /// Returns `None` when the project bucket is not configured.
+    #[tracing::instrument(skip(self), level = "debug")]
+    pub async fn is_project_rate_limited(
+        &self,
+        endpoint: &str,
+        project_id: &str,
+        tier: Option<&str>,
+    ) -> Result<Option<RateLimitStatus>, RateLimitExceeded> {
+        let limits = self.limits.load_full();
+        let Some(limits) = tier
+            .and_then(|tier| limits.tiers.get(tier))
+            .or(limits.project.as_ref())
+            .copied()
+        else {
+            return Ok(None);
+        };
+        self.check_bucket(
+            self.format_project_key(endpoint, project_id),
+            limits,
+            RateLimitBucket::Project,
+        )
+        .await
+    }
+
+    async fn check_bucket(
+        &self,
+        key: String,
+        limits: BucketLimits,
+        bucket: RateLimitBucket,
+    ) -> Result<Option<RateLimitStatus>, RateLimitExceeded> {
+        // Check the memory cache of the rate limited keys first to omit the
+        // Redis round trip in case of flood
+        if let Some(reset) = self.mem_cache.get(&key).await {
+            self.metrics.add_rate_limited_response();
+            return Err(RateLimitExceeded {
+                status: RateLimitStatus {
+                    limit: limits.max_tokens,
+                    remaining: 0,
+                    reset,
+                },
+                bucket,
+            });
         }
 
         let call_start_time = SystemTime::now();
-        let result = token_bucket(
-            &self.mem_cache.clone(),
-            &self.redis_pool.clone(),
-            self.format_key(endpoint, ip),
-            self.max_tokens,
-            self.interval,
-            self.refill_rate,
-            Utc::now(),
-        )
-        .await;
+        let result = self.token_bucket(&key, limits).await;
         self.metrics.add_rate_limiting_latency(call_start_time);
 
-        match result {
-            Ok(_) => Ok(()),
-            Err(e) => match e {
-                RateLimitError::RateLimitExceeded(e) => {
-                    self.metrics.add_rate_limited_response();
-                    Err(e)
-                }
-                RateLimitError::Internal(e) => {
-                    error!("Internal rate limiting error: {:?}", e);
-                    Ok(())
-                }
-            },
+        let (remaining, reset) = match result {
+            Ok(bucket) => bucket,
+            Err(e) => {
+                error!("Internal rate limiting error: {:?}", e);
+                return Ok(None);
+            }
+        };
+        // Token bucket reset is in milliseconds
+        let reset = reset / 1000;
+
+        if remaining.is_negative() {
+            self.mem_cache.insert(key, reset).await;
+            self.metrics.add_rate_limited_response();
+            return Err(RateLimitExceeded {
+                status: RateLimitStatus {
+                    limit: limits.max_tokens,
+                    remaining: 0,
+                    reset,
+                },
+                bucket,
+            });
         }
+        Ok(Some(RateLimitStatus {
+            limit: limits.max_tokens,
+            remaining: remaining as u32,
+            reset,
+        }))
+    }
+
+    /// Runs the token bucket script, the script is loaded by `EVALSHA` and
+    /// falls back to `EVAL` when it's not cached by the Redis yet
+    async fn token_bucket(&self, key: &str, limits: BucketLimits) -> anyhow::Result<(i64, u64)> {
+        let mut conn = self.redis_pool.get().await?;
+        let script_start_time = SystemTime::now();
+        let result = self
+            .token_bucket_script
+            .key(key)
+            .arg(limits.max_tokens)
+            .arg(self.interval.num_milliseconds())
+            .arg(limits.refill_rate)
+            .arg(Utc::now().timestamp_millis())
+            .invoke_async(&mut conn)
+            .await;
+        self.metrics
+            .add_rate_limiting_script_latency(script_start_time);
+        Ok(result?)
     }
 
     /// Returns the current rate limited entries count
@@ -128,3 +400,86 @@ impl RateLimit {
         self.mem_cache.entry_count()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rate_limit_headers() {
+        let status = RateLimitStatus {
+            limit: 100,
+            remaining: 5,
+            reset: 1_000,
+        };
+        assert!(status.is_nearly_exhausted());
+        let headers = status.headers(990);
+        assert_eq!(headers.get(RATE_LIMIT_LIMIT_HEADER).unwrap(), "100");
+        assert_eq!(headers.get(RATE_LIMIT_REMAINING_HEADER).unwrap(), "5");
+        assert!(headers.get(RETRY_AFTER).is_none());
+
+        let status = RateLimitStatus {
+            remaining: 0,
+            ..status
+        };
+        assert_eq!(status.headers(990).get(RETRY_AFTER).unwrap(), "10");
+        assert_eq!(status.headers(1_001).get(RETRY_AFTER).unwrap(), "1");
+
+        let status = RateLimitStatus {
+            remaining: 50,
+            ..status
+        };
+        assert!(!status.is_nearly_exhausted());
+    }
+
+    #[test]
+    fn most_exhausted_status() {
+        let ip_status = RateLimitStatus {
+            limit: 100,
+            remaining: 50,
+            reset: 1_000,
+        };
+        let project_status = RateLimitStatus {
+            limit: 1_000,
+            remaining: 100,
+            reset: 1_000,
+        };
+        assert_eq!(
+            RateLimitStatus::most_exhausted(Some(ip_status), Some(project_status)),
+            Some(project_status)
+        );
+        assert_eq!(
+            RateLimitStatus::most_exhausted(Some(ip_status), None),
+            Some(ip_status)
+        );
+        assert_eq!(RateLimitStatus::most_exhausted(None, None), None);
+    }
+
+    #[test]
+    fn parse_tiers() {
+        let tiers = parse_tier_limits(&["free:50:5".into(), "pro:500:50".into()]).unwrap();
+        assert_eq!(
+            tiers.get("free"),
+            Some(&BucketLimits {
+                max_tokens: 50,
+                refill_rate: 5,
+            })
+        );
+        assert_eq!(
+            tiers.get("pro"),
+            Some(&BucketLimits {
+                max_tokens: 500,
+                refill_rate: 50,
+            })
+        );
+    }
+
+    #[test]
+    fn parse_invalid_tiers() {
+        assert!(parse_tier_limits(&["free:50".into()]).is_err());
+        assert!(parse_tier_limits(&["free:50:5:1".into()]).is_err());
+        assert!(parse_tier_limits(&[":50:5".into()]).is_err());
+        assert!(parse_tier_limits(&["free:many:5".into()]).is_err());
+        assert!(parse_tier_limits(&["".into()]).unwrap().is_empty());
+    }
+}
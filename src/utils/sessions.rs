@@ -1,10 +1,14 @@
 use {
-    crate::error::RpcError,
+    crate::{
+        error::RpcError,
+        utils::crypto::{approveCall, transferCall, transferFromCall},
+    },
     alloy::{
         primitives::{Address, Bytes, U256},
         sol,
         sol_types::{SolCall, SolType},
     },
+    std::collections::{HashMap, HashSet},
     yttrium::smart_accounts::safe::Safe7579,
 };
 
@@ -12,6 +16,11 @@ type BatchTransactionType = sol! {
     (address, uint256, bytes)[]
 };
 
+// ERC-20 allowance extension not in the base standard
+sol! {
+    function increaseAllowance(address spender, uint256 addedValue) external returns (bool);
+}
+
 #[derive(Clone)]
 #[allow(dead_code)]
 pub struct ExecutionTransaction {
@@ -128,6 +137,63 @@ pub fn extract_values_sum_from_execution_batch(
     Ok(sum)
 }
 
+/// Decode the ERC-20 token amount spent or allowed to be spent by the call,
+/// `None` when the call is not a token spending call
+fn decode_erc20_spending(call_data: &[u8]) -> Option<U256> {
+    let selector: [u8; 4] = call_data.get(..4)?.try_into().ok()?;
+    match selector {
+        transferCall::SELECTOR => transferCall::abi_decode(call_data, true)
+            .ok()
+            .map(|call| call.value),
+        approveCall::SELECTOR => approveCall::abi_decode(call_data, true)
+            .ok()
+            .map(|call| call._value),
+        increaseAllowanceCall::SELECTOR => increaseAllowanceCall::abi_decode(call_data, true)
+            .ok()
+            .map(|call| call.addedValue),
+        transferFromCall::SELECTOR => transferFromCall::abi_decode(call_data, true)
+            .ok()
+            .map(|call| call._value),
+        _ => None,
+    }
+}
+
+/// Extract the spendings from the bundler's execute calldata execution batch
+/// keyed by the ERC-20 token contract address or `None` for the native token.
+/// The token `transfer`, `transferFrom` and the allowances are counted as the
+/// spendings, other calls to the `limited_tokens` are rejected as their
+/// spendings can't be counted.
+pub fn extract_spendings_from_execution_batch(
+    execution_batch: Vec<ExecutionTransaction>,
+    limited_tokens: &HashSet<Address>,
+) -> Result<HashMap<Option<Address>, U256>, RpcError> {
+    let mut spendings: HashMap<Option<Address>, U256> = HashMap::new();
+    for tx in execution_batch {
+        if !tx.value.is_zero() {
+            let native = spendings.entry(None).or_insert(U256::ZERO);
+            *native = native.saturating_add(tx.value);
+        }
+        if tx.call_data.is_empty() {
+            continue;
+        }
+        match decode_erc20_spending(&tx.call_data) {
+            Some(amount) => {
+                let token = spendings.entry(Some(tx.address)).or_insert(U256::ZERO);
+                *token = token.saturating_add(amount);
+            }
+            None if limited_tokens.contains(&tx.address) => {
+                return Err(RpcError::CosignerPermissionDenied(format!(
+                    "Call to the spending limited token {:?} can't be decoded as the token \
+                     spending",
+                    tx.address
+                )));
+            }
+            None => {}
+        }
+    }
+    Ok(spendings)
+}
+
 #[cfg(test)]
 mod tests {
     use {
@@ -166,4 +232,71 @@ mod tests {
         let decoded_data = extract_execution_batch_components(&encoded_data).unwrap();
         assert_eq!(decoded_data.len(), 2);
     }
+
+    #[test]
+    fn erc20_spendings() {
+        let token = address!("aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa");
+        let other = address!("bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb");
+        let call = |address, call_data: Vec<u8>| ExecutionTransaction {
+            address,
+            value: U256::ZERO,
+            call_data: call_data.into(),
+        };
+        let execution_batch = vec![
+            call(
+                token,
+                transferCall {
+                    to: other,
+                    value: U256::from(1),
+                }
+                .abi_encode(),
+            ),
+            call(
+                token,
+                approveCall {
+                    _spender: other,
+                    _value: U256::from(10),
+                }
+                .abi_encode(),
+            ),
+            call(
+                token,
+                increaseAllowanceCall {
+                    spender: other,
+                    addedValue: U256::from(100),
+                }
+                .abi_encode(),
+            ),
+            call(
+                token,
+                transferFromCall {
+                    _from: other,
+                    _to: other,
+                    _value: U256::from(1000),
+                }
+                .abi_encode(),
+            ),
+            ExecutionTransaction {
+                address: other,
+                value: U256::from(5),
+                call_data: Bytes::new(),
+            },
+        ];
+        let spendings =
+            extract_spendings_from_execution_batch(execution_batch, &HashSet::from([token]))
+                .unwrap();
+        assert_eq!(spendings[&Some(token)], U256::from(1111));
+        assert_eq!(spendings[&None], U256::from(5));
+
+        // Unknown calls are rejected for the limited tokens only
+        let unknown_call = vec![call(token, vec![0xde, 0xad, 0xbe, 0xef])];
+        assert!(
+            extract_spendings_from_execution_batch(unknown_call.clone(), &HashSet::new())
+                .unwrap()
+                .is_empty()
+        );
+        assert!(
+            extract_spendings_from_execution_batch(unknown_call, &HashSet::from([token])).is_err()
+        );
+    }
 }
@@ -0,0 +1,205 @@
+use {
+    crate::{
+        env::Config,
+        init_providers,
+        metrics::Metrics,
+        providers::{Provider, RpcProvider},
+        secrets,
+        storage::{irn::Irn, redis, PersistentStorage},
+    },
+    futures_util::future::join_all,
+    serde::Serialize,
+    sqlx::postgres::PgPoolOptions,
+    std::{future::Future, sync::Arc, time::Duration},
+    tokio::time::timeout,
+};
+
+/// Timeout of the every single check
+const CHECK_TIMEOUT: Duration = Duration::from_secs(10);
+/// Maximum size of the provider ping response body
+const MAX_PING_RESPONSE_SIZE: usize = 1024 * 1024;
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ValidationReport {
+    pub ok: bool,
+    pub checks: Vec<CheckResult>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CheckResult {
+    pub name: String,
+    pub ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Validates the loaded config by checking the connectivity to the Postgres,
+/// Redis and IRN and the provider API keys with the cheap `eth_chainId` calls
+pub async fn validate(mut config: Config) -> ValidationReport {
+    let mut checks = vec![
+        check("secrets", CHECK_TIMEOUT, async {
+            secrets::resolve(&mut config)
+                .await
+                .map_err(|e| e.to_string())
+        })
+        .await,
+    ];
+
+    checks.push(
+        check("postgres", CHECK_TIMEOUT, async {
+            let postgres = PgPoolOptions::new()
+                .max_connections(1)
+                .connect(&config.postgres.uri)
+                .await
+                .map_err(|e| e.to_string())?;
+            sqlx::query("SELECT 1")
+                .execute(&postgres)
+                .await
+                .map(|_| ())
+                .map_err(|e| e.to_string())
+        })
+        .await,
+    );
+
+    let storage = &config.storage;
+    let providers_cache_addr = config
+        .providers
+        .cache_redis_addr
+        .as_deref()
+        .map(redis::Addr::Combined);
+    for (name, addr) in [
+        ("redis/project_data", storage.project_data_redis_addr()),
+        ("redis/identity_cache", storage.identity_cache_redis_addr()),
+        (
+            "redis/rate_limiting",
+            storage.rate_limiting_cache_redis_addr(),
+        ),
+        ("redis/providers_cache", providers_cache_addr),
+    ] {
+        let Some(addr) = addr else {
+            continue;
+        };
+        checks.push(
+            check(name, CHECK_TIMEOUT, async {
+                redis::Redis::new(&addr, 1)
+                    .map_err(|e| e.to_string())?
+                    .ping()
+                    .await
+                    .map_err(|e| e.to_string())
+            })
+            .await,
+        );
+    }
+
+    if let (Some(nodes), Some(key), Some(namespace), Some(namespace_secret)) = (
+        config.irn.nodes.clone(),
+        config.irn.key.clone(),
+        config.irn.namespace.clone(),
+        config.irn.namespace_secret.clone(),
+    ) {
+        checks.push(
+            check("irn", CHECK_TIMEOUT, async {
+                Irn::new(
+                    key,
+                    nodes,
+                    namespace,
+                    namespace_secret,
+                    Arc::new(Metrics::new()),
+                )
+                .await
+                .map_err(|e| e.to_string())?
+                .get("validate-config".to_owned())
+                .await
+                .map(|_| ())
+                .map_err(|e| e.to_string())
+            })
+            .await,
+        );
+    }
+
+    let providers = init_providers(&config.providers);
+    let mut provider_checks = join_all(providers.rpc_providers_eip155_chains().into_iter().map(
+        |(provider, chain_id)| {
+            let name = format!("provider/{}", provider.provider_kind());
+            async move {
+                check(
+                    &name,
+                    CHECK_TIMEOUT,
+                    ping_provider(provider, &chain_id, "eth_chainId"),
+                )
+                .await
+            }
+        },
+    ))
+    .await;
+    provider_checks.sort_by(|a, b| a.name.cmp(&b.name));
+    checks.extend(provider_checks);
+
+    ValidationReport {
+        ok: checks.iter().all(|check| check.ok),
+        checks,
+    }
+}
+
+/// Runs the connectivity check with the timeout, also used by the readiness
+/// probe
+pub async fn check(
+    name: &str,
+    check_timeout: Duration,
+    check: impl Future<Output = Result<(), String>>,
+) -> CheckResult {
+    let result = timeout(check_timeout, check)
+        .await
+        .unwrap_or_else(|_| Err("timed out".to_owned()));
+    CheckResult {
+        name: name.to_owned(),
+        ok: result.is_ok(),
+        error: result.err(),
+    }
+}
+
+/// Cheap parameterless RPC method of the namespace to ping the providers
+pub fn ping_method(namespace: &str) -> Option<&'static str> {
+    match namespace {
+        "eip155" => Some("eth_chainId"),
+        "solana" => Some("getHealth"),
+        _ => None,
+    }
+}
+
+/// Calls the cheap parameterless RPC method of the provider, also used by
+/// the verbose health check
+pub async fn ping_provider(
+    provider: Arc<dyn RpcProvider>,
+    chain_id: &str,
+    method: &str,
+) -> Result<(), String> {
+    let body = serde_json::json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": method,
+        "params": [],
+    });
+    let response = provider
+        .proxy(chain_id, body.to_string().into())
+        .await
+        .map_err(|e| e.to_string())?;
+    let status = response.status();
+    let body = axum::body::to_bytes(response.into_body(), MAX_PING_RESPONSE_SIZE)
+        .await
+        .map_err(|e| e.to_string())?;
+    if !status.is_success() {
+        return Err(format!(
+            "{chain_id} responded with {status}: {}",
+            String::from_utf8_lossy(&body)
+        ));
+    }
+    let response = serde_json::from_slice::<serde_json::Value>(&body)
+        .map_err(|e| format!("{chain_id} responded with the invalid JSON: {e}"))?;
+    match response.get("error") {
+        Some(error) => Err(format!("{chain_id} responded with the error: {error}")),
+        None => Ok(()),
+    }
+}
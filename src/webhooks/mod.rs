@@ -0,0 +1,267 @@
+//! Outbound webhooks dispatcher.
+
+use {
+    crate::{
+        database::webhooks::{self as db, NewWebhookDelivery, WebhookDelivery},
+        error::RpcError,
+        metrics::WebhookDeliveryResult,
+        state::AppState,
+    },
+    chrono::{DateTime, Utc},
+    openssl::{hash::MessageDigest, pkey::PKey, sign::Signer},
+    serde::Serialize,
+    sqlx::PgExecutor,
+    std::{
+        sync::Arc,
+        time::{Duration, Instant},
+    },
+    tokio::time::{interval, MissedTickBehavior},
+    tracing::{debug, info, warn},
+    url::Url,
+    uuid::Uuid,
+};
+
+const POLL_INTERVAL: Duration = Duration::from_secs(10);
+const CLAIM_BATCH_SIZE: i64 = 50;
+const DELIVERY_TIMEOUT: Duration = Duration::from_secs(10);
+/// Deliveries are failed permanently after the attempts
+const MAX_ATTEMPTS: i32 = 10;
+const RETRY_BASE_DELAY: Duration = Duration::from_secs(30);
+const RETRY_MAX_DELAY: Duration = Duration::from_secs(6 * 60 * 60);
+
+pub const ID_HEADER: &str = "x-webhook-id";
+pub const TIMESTAMP_HEADER: &str = "x-webhook-timestamp";
+pub const SIGNATURE_HEADER: &str = "x-webhook-signature";
+const SIGNATURE_VERSION: &str = "v1";
+
+/// Event body delivered to the callback URL
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct WebhookEvent<'a> {
+    id: &'a str,
+    #[serde(rename = "type")]
+    event_type: &'a str,
+    project_id: &'a str,
+    created_at: DateTime<Utc>,
+    data: &'a serde_json::Value,
+}
+
+/// Parse the callback URL, only the HTTPS URLs are allowed
+pub fn validate_url(url: &str) -> Result<Url, RpcError> {
+    let parsed_url = Url::parse(url)
+        .map_err(|e| RpcError::InvalidParameter(format!("Invalid webhook URL: {e}")))?;
+    if parsed_url.scheme() != "https" {
+        return Err(RpcError::InvalidParameter(
+            "Webhook URL must use the https scheme".to_string(),
+        ));
+    }
+    Ok(parsed_url)
+}
+
+/// Enqueue the event for the delivery to the HTTPS callback URL, returns the
+/// event ID. The executor can be the transaction of the feature state change
+/// so the event is only delivered when the change is committed.
+pub async fn enqueue(
+    executor: impl PgExecutor<'_>,
+    project_id: &str,
+    event_type: &str,
+    url: &str,
+    data: &serde_json::Value,
+) -> Result<String, RpcError> {
+    validate_url(url)?;
+
+    let id = Uuid::new_v4().to_string();
+    db::insert_delivery(
+        executor,
+        NewWebhookDelivery {
+            id: &id,
+            project_id,
+            event_type,
+            url,
+            payload: data,
+        },
+    )
+    .await?;
+    debug!(id, event_type, "webhook event enqueued");
+    Ok(id)
+}
+
+/// Background job delivering the pending webhook events
+pub async fn run(state: Arc<AppState>, signing_secret: String) {
+    debug!("starting webhooks dispatcher");
+    let mut poll = interval(POLL_INTERVAL);
+    poll.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    loop {
+        poll.tick().await;
+        let deliveries = match db::claim_due_batch(&state.postgres, CLAIM_BATCH_SIZE).await {
+            Ok(deliveries) => deliveries,
+            Err(e) => {
+                warn!(error = %e, "failed to claim due webhook deliveries");
+                continue;
+            }
+        };
+        if deliveries.is_empty() {
+            continue;
+        }
+        debug!("delivering {} webhook events", deliveries.len());
+
+        for delivery in deliveries {
+            if let Err(e) = process_delivery(&state, &signing_secret, &delivery).await {
+                warn!(
+                    delivery_id = delivery.id,
+                    error = %e,
+                    "failed to record the webhook delivery"
+                );
+            }
+        }
+    }
+}
+
+async fn process_delivery(
+    state: &AppState,
+    signing_secret: &str,
+    delivery: &WebhookDelivery,
+) -> Result<(), RpcError> {
+    let start = Instant::now();
+    let result = deliver(state, signing_secret, delivery).await;
+    state
+        .metrics
+        .add_webhook_delivery_latency(&delivery.event_type, start);
+
+    let (status_code, error) = match result {
+        Ok(status_code) => {
+            db::mark_delivered(&state.postgres, &delivery.id, status_code).await?;
+            state
+                .metrics
+                .add_webhook_delivery(&delivery.event_type, WebhookDeliveryResult::Delivered);
+            return Ok(());
+        }
+        Err(failure) => failure,
+    };
+
+    let attempt = delivery.attempts + 1;
+    let next_attempt_at = (attempt < MAX_ATTEMPTS).then(|| Utc::now() + retry_delay(attempt));
+    match next_attempt_at {
+        Some(next_attempt_at) => {
+            debug!(
+                delivery_id = delivery.id,
+                attempt, "webhook delivery failed, retrying at {next_attempt_at}: {error}"
+            );
+            state
+                .metrics
+                .add_webhook_delivery(&delivery.event_type, WebhookDeliveryResult::Retried);
+        }
+        None => {
+            info!(
+                delivery_id = delivery.id,
+                attempt, "webhook delivery failed permanently: {error}"
+            );
+            state
+                .metrics
+                .add_webhook_delivery(&delivery.event_type, WebhookDeliveryResult::Failed);
+        }
+    }
+    db::record_failed_attempt(
+        &state.postgres,
+        &delivery.id,
+        status_code,
+        &error,
+        next_attempt_at,
+    )
+    .await?;
+    Ok(())
+}
+
+/// Send the signed event, returns the response status code of the delivered
+/// event or the status code and the error of the failed attempt
+async fn deliver(
+    state: &AppState,
+    signing_secret: &str,
+    delivery: &WebhookDelivery,
+) -> Result<i32, (Option<i32>, String)> {
+    let body = serde_json::to_string(&WebhookEvent {
+        id: &delivery.id,
+        event_type: &delivery.event_type,
+        project_id: &delivery.project_id,
+        created_at: delivery.created_at,
+        data: &delivery.payload.0,
+    })
+    .map_err(|e| (None, format!("failed to serialize the event: {e}")))?;
+    let timestamp = Utc::now().timestamp().to_string();
+    let signature = sign(signing_secret, &delivery.id, &timestamp, &body)
+        .map_err(|e| (None, format!("failed to sign the event: {e}")))?;
+
+    let response = state
+        .http_client
+        .post(&delivery.url)
+        .timeout(DELIVERY_TIMEOUT)
+        .header(reqwest::header::CONTENT_TYPE, "application/json")
+        .header(ID_HEADER, &delivery.id)
+        .header(TIMESTAMP_HEADER, &timestamp)
+        .header(SIGNATURE_HEADER, signature)
+        .body(body)
+        .send()
+        .await
+        .map_err(|e| (None, e.to_string()))?;
+
+    let status = response.status();
+    let status_code = i32::from(status.as_u16());
+    if status.is_success() {
+        Ok(status_code)
+    } else {
+        Err((Some(status_code), format!("unsuccessful status {status}")))
+    }
+}
+
+/// Signature header value of the event body
+pub fn sign(
+    secret: &str,
+    id: &str,
+    timestamp: &str,
+    body: &str,
+) -> Result<String, openssl::error::ErrorStack> {
+    let pkey = PKey::hmac(secret.as_bytes())?;
+    let mut signer = Signer::new(MessageDigest::sha256(), &pkey)?;
+    signer.update(format!("{id}.{timestamp}.{body}").as_bytes())?;
+    let signature = signer.sign_to_vec()?;
+    Ok(format!("{SIGNATURE_VERSION}={}", hex::encode(signature)))
+}
+
+/// Exponential backoff of the failed attempt
+fn retry_delay(attempt: i32) -> Duration {
+    let exponent = u32::try_from(attempt.saturating_sub(1)).unwrap_or_default();
+    RETRY_BASE_DELAY
+        .checked_mul(2u32.saturating_pow(exponent))
+        .map_or(RETRY_MAX_DELAY, |delay| delay.min(RETRY_MAX_DELAY))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn signs_the_event() {
+        // HMAC-SHA256 of `id.1700000000.{}` by the `secret` key
+        assert_eq!(
+            sign("secret", "id", "1700000000", "{}").unwrap(),
+            "v1=1817a60734e06caa151062b272ac98dfad792db7496e114b135a1093ecd9fa5a"
+        );
+    }
+
+    #[test]
+    fn validates_url() {
+        assert!(validate_url("https://merchant.example.com/webhooks").is_ok());
+        assert!(validate_url("http://merchant.example.com/webhooks").is_err());
+        assert!(validate_url("merchant.example.com").is_err());
+    }
+
+    #[test]
+    fn retry_delay_backoff() {
+        assert_eq!(retry_delay(1), Duration::from_secs(30));
+        assert_eq!(retry_delay(2), Duration::from_secs(60));
+        assert_eq!(retry_delay(5), Duration::from_secs(480));
+        assert_eq!(retry_delay(MAX_ATTEMPTS - 1), Duration::from_secs(7680));
+        assert_eq!(retry_delay(12), RETRY_MAX_DELAY);
+        assert_eq!(retry_delay(40), RETRY_MAX_DELAY);
+    }
+}
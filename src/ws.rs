@@ -1,23 +1,441 @@
 use {
     async_tungstenite::{tokio::ConnectStream, tungstenite, WebSocketStream},
-    axum::extract::ws::{Message as AxumWsMessage, WebSocket},
+    axum::extract::ws::{close_code, CloseFrame, Message as AxumWsMessage, WebSocket},
     bytes::Bytes,
     futures_util::{SinkExt, StreamExt},
-    tracing::log::debug,
+    serde::Deserialize,
+    std::{
+        sync::{
+            atomic::{AtomicU64, Ordering},
+            Arc,
+        },
+        time::Duration,
+    },
+    tokio::{
+        sync::{watch, Mutex},
+        time::{sleep_until, timeout, Instant},
+    },
+    tracing::log::{debug, info, warn},
+    wc::metrics::{counter, StringLabel},
 };
 
-#[tracing::instrument(skip(client_ws, provider_ws), level = "debug")]
+/// Solana PubSub methods relayed to the providers, the Solana WebSocket
+/// endpoints serve the subscriptions only
+const SOLANA_WS_METHODS: &[&str] = &[
+    "accountSubscribe",
+    "accountUnsubscribe",
+    "logsSubscribe",
+    "logsUnsubscribe",
+    "programSubscribe",
+    "programUnsubscribe",
+    "rootSubscribe",
+    "rootUnsubscribe",
+    "signatureSubscribe",
+    "signatureUnsubscribe",
+    "slotSubscribe",
+    "slotUnsubscribe",
+];
+/// JSON-RPC error code of the messages other than the JSON
+const PARSE_ERROR_CODE: i32 = -32700;
+/// JSON-RPC error code of the JSON messages other than the requests
+const INVALID_REQUEST_CODE: i32 = -32600;
+/// JSON-RPC error code of the method not found
+const METHOD_NOT_FOUND_CODE: i32 = -32601;
+/// JSON-RPC error code of the requests over the session limits
+const LIMIT_EXCEEDED_CODE: i32 = -32005;
+
+#[derive(Debug, PartialEq, Deserialize)]
+struct ClientRequest {
+    jsonrpc: String,
+    id: Option<serde_json::Value>,
+    method: String,
+}
+
+/// Client message check result
+#[derive(Debug, PartialEq)]
+enum ClientMessage {
+    /// Relayed to the provider with the requests of the single or the batch
+    /// JSON-RPC message
+    Relay {
+        requests: Vec<ClientRequest>,
+        is_batch: bool,
+    },
+    /// Responded to the client with the JSON-RPC error without relaying
+    Reject {
+        reason: &'static str,
+        response: String,
+    },
+}
+
+fn error_response(id: Option<serde_json::Value>, code: i32, message: &str) -> serde_json::Value {
+    serde_json::json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": {
+            "code": code,
+            "message": message,
+        },
+    })
+}
+
+/// Error responses to the requests, the batch requests are responded with the
+/// batch of the errors
+fn error_responses(
+    requests: Vec<ClientRequest>,
+    is_batch: bool,
+    code: i32,
+    message: &str,
+) -> String {
+    let mut responses = requests
+        .into_iter()
+        .map(|request| error_response(request.id, code, message));
+    match responses.next() {
+        Some(response) if !is_batch => response.to_string(),
+        first => serde_json::Value::Array(first.into_iter().chain(responses).collect()).to_string(),
+    }
+}
+
+fn is_subscribe_method(method: &str) -> bool {
+    method.ends_with("Subscribe") || method == "eth_subscribe"
+}
+
+fn is_unsubscribe_method(method: &str) -> bool {
+    method.ends_with("Unsubscribe") || method == "eth_unsubscribe"
+}
+
+/// Parses the JSON-RPC request, the invalid requests are responded with the
+/// error to the request id when it's present
+fn parse_client_request(value: serde_json::Value) -> Result<ClientRequest, serde_json::Value> {
+    let id = value.get("id").cloned();
+    match ClientRequest::deserialize(value) {
+        Ok(request) if request.jsonrpc == "2.0" => Ok(request),
+        _ => Err(error_response(id, INVALID_REQUEST_CODE, "Invalid request")),
+    }
+}
+
+/// Checks the client message of the chain as the single or the batch
+/// JSON-RPC request and the requests methods by the chain methods policy, the
+/// rejected messages aren't relayed to the provider
+fn check_client_message(chain_id: &str, payload: &[u8]) -> ClientMessage {
+    let Ok(value) = serde_json::from_slice::<serde_json::Value>(payload) else {
+        return ClientMessage::Reject {
+            reason: "parse_error",
+            response: error_response(None, PARSE_ERROR_CODE, "Parse error").to_string(),
+        };
+    };
+
+    let (values, is_batch) = match value {
+        serde_json::Value::Array(values) if values.is_empty() => {
+            return ClientMessage::Reject {
+                reason: "invalid_request",
+                response: error_response(None, INVALID_REQUEST_CODE, "Invalid request").to_string(),
+            };
+        }
+        serde_json::Value::Array(values) => (values, true),
+        value => (vec![value], false),
+    };
+    let requests = match values
+        .into_iter()
+        .map(parse_client_request)
+        .collect::<Result<Vec<_>, _>>()
+    {
+        Ok(requests) => requests,
+        Err(response) => {
+            return ClientMessage::Reject {
+                reason: "invalid_request",
+                response: response.to_string(),
+            };
+        }
+    };
+
+    if chain_id.starts_with("solana:") {
+        if let Some(request) = requests
+            .iter()
+            .find(|request| !SOLANA_WS_METHODS.contains(&request.method.as_str()))
+        {
+            let message = format!(
+                "Method {} is not supported by the Solana WebSocket RPC",
+                request.method
+            );
+            return ClientMessage::Reject {
+                reason: "method_not_found",
+                response: error_responses(requests, is_batch, METHOD_NOT_FOUND_CODE, &message),
+            };
+        }
+    }
+
+    ClientMessage::Relay { requests, is_batch }
+}
+
+/// Per-connection limits of the WebSocket session, zero disables the limit
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct WsLimits {
+    /// Client messages per second, the messages over the limit are rejected
+    pub max_messages_per_sec: u32,
+    /// Concurrent subscriptions, counted by the subscribe and unsubscribe
+    /// requests relayed to the provider
+    pub max_subscriptions: usize,
+    /// Session without the data messages in both directions is closed after
+    /// the timeout
+    pub idle_timeout: Duration,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LimitExceeded {
+    MessageRate,
+    Subscriptions,
+}
+
+impl LimitExceeded {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::MessageRate => "message_rate",
+            Self::Subscriptions => "subscriptions",
+        }
+    }
+
+    fn message(&self) -> &'static str {
+        match self {
+            Self::MessageRate => "Too many messages per second",
+            Self::Subscriptions => "Too many subscriptions",
+        }
+    }
+}
+
+/// Client messages limiter of the session, the messages rate is limited by
+/// the fixed one second windows
+#[derive(Debug)]
+struct ConnectionLimiter {
+    limits: WsLimits,
+    window_start: Instant,
+    window_messages: u32,
+    subscriptions: usize,
+}
+
+impl ConnectionLimiter {
+    fn new(limits: WsLimits, now: Instant) -> Self {
+        Self {
+            limits,
+            window_start: now,
+            window_messages: 0,
+            subscriptions: 0,
+        }
+    }
+
+    /// Counts the client message with the JSON-RPC methods of the requests
+    fn check(&mut self, now: Instant, methods: &[&str]) -> Result<(), LimitExceeded> {
+        if now.duration_since(self.window_start) >= Duration::from_secs(1) {
+            self.window_start = now;
+            self.window_messages = 0;
+        }
+        if self.limits.max_messages_per_sec > 0
+            && self.window_messages >= self.limits.max_messages_per_sec
+        {
+            return Err(LimitExceeded::MessageRate);
+        }
+        self.window_messages += 1;
+
+        let subscribes = methods
+            .iter()
+            .filter(|method| is_subscribe_method(method))
+            .count();
+        let unsubscribes = methods
+            .iter()
+            .filter(|method| is_unsubscribe_method(method))
+            .count();
+        if subscribes > 0
+            && self.limits.max_subscriptions > 0
+            && self.subscriptions + subscribes > self.limits.max_subscriptions
+        {
+            return Err(LimitExceeded::Subscriptions);
+        }
+        self.subscriptions = (self.subscriptions + subscribes).saturating_sub(unsubscribes);
+        Ok(())
+    }
+}
+
+/// Checks the client message by the JSON-RPC schema, the chain methods and
+/// the session limits. The error is the JSON-RPC error response of the
+/// rejected message.
+fn check_client_payload(
+    chain_id: &str,
+    limiter: &mut ConnectionLimiter,
+    payload: &[u8],
+) -> Result<(), String> {
+    let reject = |reason: &str, response: String| {
+        counter!("websocket_rejected_message_counter",
+            StringLabel<"chain_id", String> => &chain_id.to_owned(),
+            StringLabel<"reason", String> => &reason.to_owned()
+        )
+        .increment(1);
+        Err(response)
+    };
+
+    let (requests, is_batch) = match check_client_message(chain_id, payload) {
+        ClientMessage::Relay { requests, is_batch } => (requests, is_batch),
+        ClientMessage::Reject { reason, response } => return reject(reason, response),
+    };
+
+    let methods = requests
+        .iter()
+        .map(|request| request.method.as_str())
+        .collect::<Vec<_>>();
+    if let Err(exceeded) = limiter.check(Instant::now(), &methods) {
+        return reject(
+            exceeded.as_str(),
+            error_responses(requests, is_batch, LIMIT_EXCEEDED_CODE, exceeded.message()),
+        );
+    }
+
+    for method in methods
+        .into_iter()
+        .filter(|method| is_subscribe_method(method))
+    {
+        counter!("websocket_subscription_counter",
+            StringLabel<"chain_id", String> => &chain_id.to_owned(),
+            StringLabel<"method", String> => &method.to_owned()
+        )
+        .increment(1);
+    }
+    Ok(())
+}
+
+/// Time of the last data message of the session in both directions, the
+/// ping and pong frames don't keep the session active
+#[derive(Debug)]
+struct Activity {
+    started: Instant,
+    last_millis: AtomicU64,
+}
+
+impl Activity {
+    fn new() -> Self {
+        Self {
+            started: Instant::now(),
+            last_millis: AtomicU64::new(0),
+        }
+    }
+
+    fn touch(&self) {
+        let millis = self.started.elapsed().as_millis() as u64;
+        self.last_millis.store(millis, Ordering::Relaxed);
+    }
+
+    /// Resolves when there are no messages for the timeout, never resolves
+    /// for the zero timeout
+    async fn idle(&self, idle_timeout: Duration) {
+        if idle_timeout.is_zero() {
+            return std::future::pending().await;
+        }
+        loop {
+            let last = Duration::from_millis(self.last_millis.load(Ordering::Relaxed));
+            let deadline = self.started + last + idle_timeout;
+            if Instant::now() >= deadline {
+                return;
+            }
+            sleep_until(deadline).await;
+        }
+    }
+}
+
+/// Active WebSocket proxy sessions, closed with the going away code when the
+/// server is shutting down
+#[derive(Debug)]
+pub struct WsSessions {
+    active: watch::Sender<usize>,
+    draining: watch::Sender<bool>,
+}
+
+impl Default for WsSessions {
+    fn default() -> Self {
+        Self {
+            active: watch::Sender::new(0),
+            draining: watch::Sender::new(false),
+        }
+    }
+}
+
+impl WsSessions {
+    /// Registers the new session, new sessions are rejected while draining
+    pub fn start(self: &Arc<Self>, limits: WsLimits) -> Option<WsSession> {
+        if *self.draining.borrow() {
+            return None;
+        }
+        self.active.send_modify(|active| *active += 1);
+        Some(WsSession {
+            sessions: self.clone(),
+            draining: self.draining.subscribe(),
+            limits,
+        })
+    }
+
+    /// Closes the active sessions and waits for them to finish up to the
+    /// drain window
+    pub async fn drain(&self, drain_window: Duration) {
+        self.draining.send_replace(true);
+        let active = *self.active.borrow();
+        if active == 0 {
+            return;
+        }
+        info!("Draining {active} active WebSocket sessions");
+        let mut active = self.active.subscribe();
+        if timeout(drain_window, active.wait_for(|active| *active == 0))
+            .await
+            .is_err()
+        {
+            warn!(
+                "WebSocket sessions drain window elapsed with {} active sessions",
+                *active.borrow()
+            );
+        }
+    }
+}
+
+/// Active session registration, unregistered on drop
+#[derive(Debug)]
+pub struct WsSession {
+    sessions: Arc<WsSessions>,
+    draining: watch::Receiver<bool>,
+    limits: WsLimits,
+}
+
+impl WsSession {
+    async fn draining(&mut self) {
+        // The sender is owned by the sessions and never dropped before
+        let _ = self.draining.wait_for(|draining| *draining).await;
+    }
+}
+
+impl Drop for WsSession {
+    fn drop(&mut self) {
+        self.sessions
+            .active
+            .send_modify(|active| *active = active.saturating_sub(1));
+    }
+}
+
+#[tracing::instrument(skip(client_ws, provider_ws, session), level = "debug")]
 pub async fn proxy(
     project_id: String,
+    chain_id: String,
     client_ws: WebSocket,
     provider_ws: WebSocketStream<ConnectStream>,
+    mut session: WsSession,
 ) {
-    let (mut client_ws_sender, mut client_ws_receiver) = client_ws.split();
+    let (client_ws_sender, mut client_ws_receiver) = client_ws.split();
+    // The client is written by both of the relays for the rejected requests
+    let client_ws_sender = Mutex::new(client_ws_sender);
     let (mut provider_ws_sender, mut provider_ws_receiver) = provider_ws.split();
+    let limits = session.limits;
+    let activity = Activity::new();
 
     // Relay: client -> provider
     let write = async {
+        let mut limiter = ConnectionLimiter::new(limits, Instant::now());
         while let Some(Ok(msg)) = client_ws_receiver.next().await {
+            if matches!(msg, AxumWsMessage::Text(_) | AxumWsMessage::Binary(_)) {
+                activity.touch();
+            }
             let tmsg = match msg {
                 AxumWsMessage::Text(s) => tungstenite::Message::Text(s.to_string()),
                 AxumWsMessage::Binary(b) => tungstenite::Message::Binary(b.to_vec()),
@@ -31,6 +449,25 @@ pub async fn proxy(
                     }))
                 }
             };
+            let payload = match &tmsg {
+                tungstenite::Message::Text(s) => Some(s.as_bytes()),
+                tungstenite::Message::Binary(b) => Some(b.as_slice()),
+                _ => None,
+            };
+            if let Some(Err(response)) =
+                payload.map(|payload| check_client_payload(&chain_id, &mut limiter, payload))
+            {
+                if client_ws_sender
+                    .lock()
+                    .await
+                    .send(AxumWsMessage::Text(response.into()))
+                    .await
+                    .is_err()
+                {
+                    break;
+                }
+                continue;
+            }
             if provider_ws_sender.send(tmsg).await.is_err() {
                 break;
             }
@@ -40,6 +477,12 @@ pub async fn proxy(
     // Relay: provider -> client
     let read = async {
         while let Some(Ok(msg)) = provider_ws_receiver.next().await {
+            if matches!(
+                msg,
+                tungstenite::Message::Text(_) | tungstenite::Message::Binary(_)
+            ) {
+                activity.touch();
+            }
             let amsg = match msg {
                 tungstenite::Message::Text(s) => AxumWsMessage::Text(s.into()),
                 tungstenite::Message::Binary(b) => AxumWsMessage::Binary(Bytes::from(b)),
@@ -56,13 +499,215 @@ pub async fn proxy(
                     continue;
                 }
             };
-            if client_ws_sender.send(amsg).await.is_err() {
+            if client_ws_sender.lock().await.send(amsg).await.is_err() {
                 break;
             }
         }
     };
-    tokio::select! {
-        _ = read => debug!("WebSocket relaying messages to the provider for client {project_id} died."),
-        _ = write => debug!("WebSocket relaying messages from the provider to the client {project_id} died."),
+    let close_frame = tokio::select! {
+        _ = read => {
+            debug!("WebSocket relaying messages to the provider for client {project_id} died.");
+            None
+        }
+        _ = write => {
+            debug!("WebSocket relaying messages from the provider to the client {project_id} died.");
+            None
+        }
+        _ = session.draining() => {
+            debug!("Closing the WebSocket session for client {project_id} on shutdown");
+            Some(CloseFrame {
+                code: close_code::AWAY,
+                reason: "Server is shutting down".into(),
+            })
+        }
+        _ = activity.idle(limits.idle_timeout) => {
+            debug!("Closing the idle WebSocket session for client {project_id}");
+            counter!("websocket_idle_timeout_counter",
+                StringLabel<"chain_id", String> => &chain_id
+            )
+            .increment(1);
+            Some(CloseFrame {
+                code: close_code::NORMAL,
+                reason: "Idle timeout".into(),
+            })
+        }
+    };
+
+    if let Some(close_frame) = close_frame {
+        let _ = client_ws_sender
+            .lock()
+            .await
+            .send(AxumWsMessage::Close(Some(close_frame)))
+            .await;
+        let _ = provider_ws_sender
+            .send(tungstenite::Message::Close(None))
+            .await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn relayed_methods(message: ClientMessage) -> Vec<String> {
+        match message {
+            ClientMessage::Relay { requests, .. } => {
+                requests.into_iter().map(|request| request.method).collect()
+            }
+            ClientMessage::Reject { response, .. } => panic!("unexpected rejection: {response}"),
+        }
+    }
+
+    fn rejection(message: ClientMessage) -> (&'static str, serde_json::Value) {
+        match message {
+            ClientMessage::Reject { reason, response } => {
+                (reason, serde_json::from_str(&response).unwrap())
+            }
+            ClientMessage::Relay { requests, .. } => panic!("unexpected relay: {requests:?}"),
+        }
+    }
+
+    #[test]
+    fn checks_solana_client_messages() {
+        let solana = "solana:5eykt4UsFv8P8NJdTREpY1vzqKqZKvdp";
+        assert_eq!(
+            relayed_methods(check_client_message(
+                solana,
+                br#"{"jsonrpc":"2.0","id":1,"method":"accountSubscribe","params":[]}"#
+            )),
+            vec!["accountSubscribe".to_owned()]
+        );
+
+        let (reason, response) = rejection(check_client_message(
+            solana,
+            br#"{"jsonrpc":"2.0","id":7,"method":"getBalance","params":[]}"#,
+        ));
+        assert_eq!(reason, "method_not_found");
+        assert_eq!(response["id"], 7);
+        assert_eq!(response["error"]["code"], METHOD_NOT_FOUND_CODE);
+
+        // The batch is rejected by any of the requests
+        let (_, response) = rejection(check_client_message(
+            solana,
+            br#"[{"jsonrpc":"2.0","id":1,"method":"slotSubscribe"},{"jsonrpc":"2.0","id":2,"method":"getSlot"}]"#,
+        ));
+        assert_eq!(response[0]["id"], 1);
+        assert_eq!(response[1]["id"], 2);
+        assert_eq!(response[1]["error"]["code"], METHOD_NOT_FOUND_CODE);
+
+        assert_eq!(
+            relayed_methods(check_client_message(
+                "eip155:1",
+                br#"{"jsonrpc":"2.0","id":1,"method":"eth_blockNumber","params":[]}"#
+            )),
+            vec!["eth_blockNumber".to_owned()]
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_json_rpc_messages() {
+        let (reason, response) = rejection(check_client_message("eip155:1", b"not a request"));
+        assert_eq!(reason, "parse_error");
+        assert_eq!(response["id"], serde_json::Value::Null);
+        assert_eq!(response["error"]["code"], PARSE_ERROR_CODE);
+
+        let (reason, response) = rejection(check_client_message(
+            "eip155:1",
+            br#"{"jsonrpc":"1.0","id":3,"method":"eth_blockNumber"}"#,
+        ));
+        assert_eq!(reason, "invalid_request");
+        assert_eq!(response["id"], 3);
+        assert_eq!(response["error"]["code"], INVALID_REQUEST_CODE);
+
+        for message in [
+            &br#"{"jsonrpc":"2.0","id":4}"#[..],
+            br#"{"jsonrpc":"2.0","id":4,"method":5}"#,
+            b"[]",
+            b"42",
+        ] {
+            let (reason, _) = rejection(check_client_message("eip155:1", message));
+            assert_eq!(reason, "invalid_request");
+        }
+
+        // Notifications without the id are relayed
+        assert_eq!(
+            relayed_methods(check_client_message(
+                "eip155:1",
+                br#"[{"jsonrpc":"2.0","method":"eth_subscribe","params":["newHeads"]}]"#
+            )),
+            vec!["eth_subscribe".to_owned()]
+        );
+    }
+
+    #[test]
+    fn limits_messages_rate_and_subscriptions() {
+        let now = Instant::now();
+        let mut limiter = ConnectionLimiter::new(
+            WsLimits {
+                max_messages_per_sec: 3,
+                max_subscriptions: 1,
+                idle_timeout: Duration::ZERO,
+            },
+            now,
+        );
+
+        assert_eq!(limiter.check(now, &["logsSubscribe"]), Ok(()));
+        assert_eq!(
+            limiter.check(now, &["accountSubscribe"]),
+            Err(LimitExceeded::Subscriptions)
+        );
+        assert_eq!(limiter.check(now, &["logsUnsubscribe"]), Ok(()));
+        assert_eq!(
+            limiter.check(now, &["accountSubscribe"]),
+            Err(LimitExceeded::MessageRate)
+        );
+
+        // The messages rate is reset by the next window
+        let now = now + Duration::from_secs(1);
+        assert_eq!(limiter.check(now, &["accountSubscribe"]), Ok(()));
+        assert_eq!(limiter.check(now, &[]), Ok(()));
+    }
+
+    #[test]
+    fn unlimited_with_zero_limits() {
+        let now = Instant::now();
+        let mut limiter = ConnectionLimiter::new(WsLimits::default(), now);
+        for _ in 0..1000 {
+            assert_eq!(limiter.check(now, &["eth_subscribe"]), Ok(()));
+        }
+    }
+
+    #[tokio::test]
+    async fn resolves_idle_after_timeout() {
+        let activity = Activity::new();
+        let idle_timeout = Duration::from_millis(50);
+
+        let started = Instant::now();
+        tokio::time::sleep(idle_timeout / 2).await;
+        activity.touch();
+        timeout(Duration::from_secs(1), activity.idle(idle_timeout))
+            .await
+            .unwrap();
+        // The timeout is extended by the message
+        assert!(started.elapsed() > idle_timeout * 5 / 4);
+    }
+
+    #[tokio::test]
+    async fn drains_active_sessions() {
+        let sessions = Arc::new(WsSessions::default());
+        let mut session = sessions.start(WsLimits::default()).unwrap();
+
+        let drain = tokio::spawn({
+            let sessions = sessions.clone();
+            async move { sessions.drain(Duration::from_secs(10)).await }
+        });
+        session.draining().await;
+        assert!(sessions.start(WsLimits::default()).is_none());
+
+        drop(session);
+        timeout(Duration::from_secs(1), drain)
+            .await
+            .unwrap()
+            .unwrap();
     }
 }
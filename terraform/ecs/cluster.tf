@@ -86,6 +86,7 @@ resource "aws_ecs_task_definition" "app_task" {
         { name = "RPC_PROXY_TESTING_PROJECT_ID", value = var.testing_project_id },
 
         { name = "RPC_PROXY_VALIDATE_PROJECT_ID", value = tostring(var.validate_project_id) },
+        { name = "RPC_PROXY_VALIDATE_PROJECT_ALLOWLIST", value = tostring(var.validate_project_allowlist) },
 
         { name = "RPC_PROXY_BLOCKED_COUNTRIES", value = var.ofac_countries },
 
@@ -138,6 +139,7 @@ resource "aws_ecs_task_definition" "app_task" {
         { name = "RPC_PROXY_RATE_LIMITING_REFILL_INTERVAL_SEC", value = tostring(var.rate_limiting_refill_interval) },
         { name = "RPC_PROXY_RATE_LIMITING_REFILL_RATE", value = tostring(var.rate_limiting_refill_rate) },
         { name = "RPC_PROXY_RATE_LIMITING_IP_WHITELIST", value = var.rate_limiting_ip_whitelist },
+        { name = "RPC_PROXY_RATE_LIMITING_TIERS", value = var.rate_limiting_tiers },
 
         { name = "RPC_PROXY_POSTGRES_URI", value = var.postgres_url },
 
@@ -159,6 +161,13 @@ resource "aws_ecs_task_definition" "app_task" {
         { name = "RPC_PROXY_EXCHANGES_BINANCE_TOKEN", value = var.binance_token },
         { name = "RPC_PROXY_EXCHANGES_BINANCE_KEY", value = var.binance_key },
         { name = "RPC_PROXY_EXCHANGES_BINANCE_HOST", value = var.binance_host },
+        { name = "RPC_PROXY_EXCHANGES_BINANCE_WEBHOOK_PUBLIC_KEY", value = var.binance_webhook_public_key },
+        { name = "RPC_PROXY_EXCHANGES_COINBASE_WEBHOOK_SECRET", value = var.coinbase_webhook_secret },
+        { name = "RPC_PROXY_EXCHANGES_OKX_API_KEY", value = var.okx_api_key },
+        { name = "RPC_PROXY_EXCHANGES_OKX_SECRET_KEY", value = var.okx_secret_key },
+        { name = "RPC_PROXY_EXCHANGES_OKX_PASSPHRASE", value = var.okx_passphrase },
+        { name = "RPC_PROXY_EXCHANGES_OKX_HOST", value = var.okx_host },
+        { name = "RPC_PROXY_EXCHANGES_RECONCILER_INTERVAL_SECS", value = tostring(var.exchange_reconciler_interval) },
         { name = "RPC_PROXY_EXCHANGES_ALLOWED_PROJECT_IDS", value = var.pay_allowed_project_ids },
 
 
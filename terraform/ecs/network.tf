@@ -81,7 +81,7 @@ resource "aws_lb_target_group" "target_group" {
 
   health_check {
     protocol            = "HTTP"
-    path                = "/health" # Blockchain-API health path
+    path                = "/ready" # Blockchain-API readiness path
     port                = var.port
     interval            = 10
     timeout             = 5
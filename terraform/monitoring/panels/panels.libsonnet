@@ -33,6 +33,8 @@ local redis  = panels.aws.redis;
     provider_retries:       (import 'proxy/rpc_retries.libsonnet'               ).new,
     rate_limited_counter:   (import 'proxy/rate_limited_counter.libsonnet'      ).new,
     http_codes:             (import 'proxy/http_codes.libsonnet'                ).new,
+    chain_calls:            (import 'proxy/chain_calls.libsonnet'               ).new,
+    chain_errors:           (import 'proxy/chain_errors.libsonnet'              ).new,
     chains_unavailability:  (import 'proxy/chains_unavailability.libsonnet'     ).new,
     websocket_connections:  (import 'proxy/websocket_connections.libsonnet'     ).new,
     rpc_server_error_codes: (import 'proxy/rpc_server_error_codes.libsonnet'    ).new,
@@ -88,6 +90,7 @@ local redis  = panels.aws.redis;
 
   irn: {
     latency: (import 'irn/latency.libsonnet').new,
+    errors:  (import 'irn/errors.libsonnet').new,
   },
 
   non_rpc: {
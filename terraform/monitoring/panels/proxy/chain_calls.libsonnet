@@ -0,0 +1,24 @@
+local grafana   = import '../../grafonnet-lib/grafana.libsonnet';
+local defaults  = import '../../grafonnet-lib/defaults.libsonnet';
+
+local panels    = grafana.panels;
+local targets   = grafana.targets;
+
+local _configuration = defaults.configuration.timeseries
+  .withUnit('cpm');
+
+{
+  new(ds, vars)::
+    panels.timeseries(
+      title       = 'Calls by Chain',
+      datasource  = ds.prometheus,
+    )
+    .configure(_configuration)
+
+    .addTarget(targets.prometheus(
+      datasource    = ds.prometheus,
+      expr          = 'sum by (chain_id)(rate(chain_http_call_counter_total{route=~"/v1/?"}[5m]))',
+      exemplar      = false,
+      legendFormat  = '__auto',
+    ))
+}
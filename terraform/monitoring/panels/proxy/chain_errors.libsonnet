@@ -0,0 +1,28 @@
+local grafana   = import '../../grafonnet-lib/grafana.libsonnet';
+local defaults  = import '../../grafonnet-lib/defaults.libsonnet';
+
+local panels    = grafana.panels;
+local targets   = grafana.targets;
+
+local _configuration = defaults.configuration.timeseries
+  .withUnit('percent')
+  .withSoftLimit(
+    axisSoftMin = 0,
+    axisSoftMax = 10,
+  );
+
+{
+  new(ds, vars)::
+    panels.timeseries(
+      title       = 'Error Rate by Chain',
+      datasource  = ds.prometheus,
+    )
+    .configure(_configuration)
+
+    .addTarget(targets.prometheus(
+      datasource    = ds.prometheus,
+      expr          = '(sum by (chain_id)(rate(chain_http_call_counter_total{route=~"/v1/?", code=~"5.+"}[5m])) / sum by (chain_id)(rate(chain_http_call_counter_total{route=~"/v1/?"}[5m]))) * 100',
+      exemplar      = false,
+      legendFormat  = '__auto',
+    ))
+}
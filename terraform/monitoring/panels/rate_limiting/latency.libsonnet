@@ -46,4 +46,11 @@ local _configuration = defaults.configuration.timeseries
       refId         = 'RateLimiterLatency',
       legendFormat  = 'Latency',
     ))
+
+    .addTarget(targets.prometheus(
+      datasource    = ds.prometheus,
+      expr          = 'sum(rate(rate_limiting_script_latency_tracker_sum[$__rate_interval])) / sum(rate(rate_limiting_script_latency_tracker_count[$__rate_interval]))',
+      refId         = 'RateLimiterScriptLatency',
+      legendFormat  = 'Script execution',
+    ))
 }
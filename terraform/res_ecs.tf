@@ -99,6 +99,7 @@ module "ecs" {
   rate_limiting_refill_interval = var.rate_limiting_refill_interval
   rate_limiting_refill_rate     = var.rate_limiting_refill_rate
   rate_limiting_ip_whitelist    = var.rate_limiting_ip_whitelist
+  rate_limiting_tiers           = var.rate_limiting_tiers
 
   # IRN Client
   irn_nodes            = var.irn_nodes
@@ -128,7 +129,8 @@ module "ecs" {
   testing_project_id = var.testing_project_id
 
   # Validate project ID
-  validate_project_id = var.validate_project_id
+  validate_project_id        = var.validate_project_id
+  validate_project_allowlist = var.validate_project_allowlist
 
   # Exchanges
   coinbase_project_id               = var.coinbase_project_id
@@ -139,6 +141,13 @@ module "ecs" {
   binance_token                     = var.binance_token
   binance_key                       = var.binance_key
   binance_host                      = var.binance_host
+  binance_webhook_public_key        = var.binance_webhook_public_key
+  coinbase_webhook_secret           = var.coinbase_webhook_secret
+  okx_api_key                       = var.okx_api_key
+  okx_secret_key                    = var.okx_secret_key
+  okx_passphrase                    = var.okx_passphrase
+  okx_host                          = var.okx_host
+  exchange_reconciler_interval      = var.exchange_reconciler_interval
   pay_allowed_project_ids           = var.pay_allowed_project_ids
 
   depends_on = [aws_iam_role.application_role]
@@ -193,6 +193,12 @@ variable "validate_project_id" {
   default     = true
 }
 
+variable "validate_project_allowlist" {
+  description = "Project allowed origins, bundle IDs and package names validation"
+  type        = bool
+  default     = false
+}
+
 variable "allnodes_api_key" {
   description = "Allnodes API key"
   type        = string
@@ -299,6 +305,12 @@ variable "rate_limiting_ip_whitelist" {
   type        = string
 }
 
+variable "rate_limiting_tiers" {
+  description = "Comma separated list of the plan tiers token buckets in the `tier:max_tokens:refill_rate` format"
+  type        = string
+  default     = ""
+}
+
 #-------------------------------------------------------------------------------
 # IRN client configuration
 
@@ -394,6 +406,54 @@ variable "binance_host" {
   default     = ""
 }
 
+variable "binance_webhook_public_key" {
+  description = "Binance Connect webhook signature public key"
+  type        = string
+  sensitive   = true
+  default     = ""
+}
+
+variable "coinbase_webhook_secret" {
+  description = "Coinbase onramp webhook signing secret"
+  type        = string
+  sensitive   = true
+  default     = ""
+}
+
+variable "okx_api_key" {
+  description = "OKX API key"
+  type        = string
+  sensitive   = true
+  default     = ""
+}
+
+variable "okx_secret_key" {
+  description = "OKX secret key"
+  type        = string
+  sensitive   = true
+  default     = ""
+}
+
+variable "okx_passphrase" {
+  description = "OKX API passphrase"
+  type        = string
+  sensitive   = true
+  default     = ""
+}
+
+variable "okx_host" {
+  description = "OKX host"
+  type        = string
+  sensitive   = true
+  default     = ""
+}
+
+variable "exchange_reconciler_interval" {
+  description = "The interval in seconds between the exchange transactions reconciler runs"
+  type        = number
+  default     = 600
+}
+
 variable "pay_allowed_project_ids" {
   description = "Allowed project ids for pay with exchange"
   type        = string
@@ -45,6 +45,7 @@ async fn default_bundler() {
             bundler_url: bundler_server.uri().parse().unwrap(),
             paymaster_url: bundler_server.uri().parse().unwrap(),
         }),
+        ..Default::default()
     })
     .await;
     let mut url = server_url.join("/v1/bundler").unwrap();
@@ -176,3 +177,55 @@ async fn bundler_url() {
         Some("0x1")
     );
 }
+
+#[tokio::test]
+#[ignore]
+async fn wait_user_operation() {
+    let bundler_server = MockServer::start().await;
+
+    let user_op_hash = "0x8b7a9e1b3e2f4e0b8b5b1f4c4d5f0a7f3c9e2d1b0a9f8e7d6c5b4a3928170615";
+    let transaction_hash = "0x1f2e3d4c5b6a79880716253443526170f0e1d2c3b4a5968778695a4b3c2d1e0f";
+    let response = ResponseTemplate::new(200).set_body_json(json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "result": {
+            "userOpHash": user_op_hash,
+            "success": true,
+            "receipt": {
+                "transactionHash": transaction_hash
+            }
+        }
+    }));
+
+    Mock::given(method("POST"))
+        .and(path("/"))
+        .and(body_partial_json(json!({
+            "jsonrpc": "2.0",
+            "method": "eth_getUserOperationReceipt",
+            "params": [user_op_hash]
+        })))
+        .respond_with(response)
+        .mount(&bundler_server)
+        .await;
+
+    let server_url = spawn_blockchain_api_with_params(rpc_proxy::test_helpers::Params {
+        validate_project_id: false,
+        override_bundler_urls: Some(MockAltoUrls {
+            bundler_url: bundler_server.uri().parse().unwrap(),
+            paymaster_url: bundler_server.uri().parse().unwrap(),
+        }),
+        ..Default::default()
+    })
+    .await;
+    let mut url = server_url.join("/v1/bundler/wait").unwrap();
+    url.query_pairs_mut()
+        .append_pair("projectId", "test")
+        .append_pair("chainId", "eip155:1")
+        .append_pair("userOpHash", user_op_hash);
+
+    let response = reqwest::Client::new().get(url).send().await.unwrap();
+    assert_eq!(response.status(), reqwest::StatusCode::OK);
+    let body = response.json::<serde_json::Value>().await.unwrap();
+    assert_eq!(body["transactionHash"].as_str(), Some(transaction_hash));
+    assert_eq!(body["success"].as_bool(), Some(true));
+}
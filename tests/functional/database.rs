@@ -4,10 +4,14 @@ use {
         database::{
             helpers::{
                 delete_address, delete_name, get_account_names_stats, get_addresses_by_name,
-                get_name, get_name_and_addresses_by_name, get_names_by_address,
-                get_names_by_address_and_namespace, insert_name, insert_or_update_address,
-                update_name_attributes,
+                get_addresses_by_names, get_name, get_name_and_addresses_by_name,
+                get_names_by_address, get_names_by_address_and_namespace, get_registered_names,
+                insert_name, insert_or_update_address, update_name_attributes,
             },
+            pos_payment_intents::{
+                delete_merchant_webhook_url, get_merchant_webhook_url, set_merchant_webhook_url,
+            },
+            sessions_spending::{release_spendings, reserve_spendings, SpendingReservation},
             types,
         },
         utils::generate_random_string,
@@ -117,6 +121,80 @@ async fn insert_and_get_names_by_address() {
     assert!(delete_result.is_ok(), "Deleting name should succeed");
 }
 
+#[tokio::test]
+async fn insert_and_get_registered_names() {
+    let pg_pool = get_postgres_pool().await;
+
+    let name = generate_random_name();
+    let free_name = generate_random_name();
+    let insert_result = insert_name(
+        name.clone(),
+        HashMap::new(),
+        types::SupportedNamespaces::Eip155,
+        HashMap::new(),
+        &pg_pool,
+    )
+    .await;
+    assert!(insert_result.is_ok(), "Inserting a new name should succeed");
+
+    let registered = get_registered_names(&[name.clone(), free_name], &pg_pool)
+        .await
+        .unwrap();
+    assert_eq!(registered.len(), 1);
+    assert!(registered.contains(&name));
+
+    // Cleanup
+    let delete_result = delete_name(name, &pg_pool).await;
+    assert!(delete_result.is_ok(), "Deleting name should succeed");
+}
+
+#[tokio::test]
+async fn insert_and_get_addresses_by_names() {
+    let pg_pool = get_postgres_pool().await;
+
+    let name = generate_random_name();
+    let name_without_addresses = generate_random_name();
+    let free_name = generate_random_name();
+    let address = generate_random_address();
+    let addresses = HashMap::from([(
+        1,
+        types::Address {
+            address: address.clone(),
+            created_at: None,
+        },
+    )]);
+    for (name, addresses) in [
+        (name.clone(), addresses),
+        (name_without_addresses.clone(), HashMap::new()),
+    ] {
+        let insert_result = insert_name(
+            name,
+            HashMap::new(),
+            types::SupportedNamespaces::Eip155,
+            addresses,
+            &pg_pool,
+        )
+        .await;
+        assert!(insert_result.is_ok(), "Inserting a new name should succeed");
+    }
+
+    let got_names = get_addresses_by_names(
+        &[name.clone(), name_without_addresses.clone(), free_name],
+        &pg_pool,
+    )
+    .await
+    .unwrap();
+    assert_eq!(got_names.len(), 2);
+    assert_eq!(got_names[&name][&1].address, address);
+    assert!(got_names[&name_without_addresses].is_empty());
+
+    // Cleanup
+    for name in [name, name_without_addresses] {
+        let delete_result = delete_name(name, &pg_pool).await;
+        assert!(delete_result.is_ok(), "Deleting name should succeed");
+    }
+}
+
 #[tokio::test]
 async fn insert_and_get_names_by_address_and_namespace() {
     let pg_pool = get_postgres_pool().await;
@@ -391,3 +469,146 @@ async fn insert_and_check_names_count() {
 
     assert!(stats_after_insert > stats_before_insert);
 }
+
+#[tokio::test]
+async fn set_and_delete_merchant_webhook_url() {
+    let pg_pool = get_postgres_pool().await;
+    let project_id = generate_random_string(32);
+
+    assert_eq!(
+        get_merchant_webhook_url(&pg_pool, &project_id)
+            .await
+            .unwrap(),
+        None
+    );
+
+    set_merchant_webhook_url(&pg_pool, &project_id, "https://merchant.example.com/a")
+        .await
+        .unwrap();
+    set_merchant_webhook_url(&pg_pool, &project_id, "https://merchant.example.com/b")
+        .await
+        .unwrap();
+    assert_eq!(
+        get_merchant_webhook_url(&pg_pool, &project_id)
+            .await
+            .unwrap()
+            .as_deref(),
+        Some("https://merchant.example.com/b")
+    );
+
+    assert!(delete_merchant_webhook_url(&pg_pool, &project_id)
+        .await
+        .unwrap());
+    assert!(!delete_merchant_webhook_url(&pg_pool, &project_id)
+        .await
+        .unwrap());
+    assert_eq!(
+        get_merchant_webhook_url(&pg_pool, &project_id)
+            .await
+            .unwrap(),
+        None
+    );
+}
+
+#[tokio::test]
+async fn release_spendings_of_failed_cosigning() {
+    let pg_pool = get_postgres_pool().await;
+    let address = generate_random_address();
+    let pci = generate_random_string(16);
+    let now = 1_700_000_000;
+    let expires_at = now + 86_400;
+    let reservation = |amount: &str, period: i64| SpendingReservation {
+        policy_index: 0,
+        amount: amount.to_owned(),
+        limit: "100".to_owned(),
+        period,
+    };
+
+    // The whole limit is reserved and the cosigning failed
+    assert!(reserve_spendings(
+        &pg_pool,
+        &address,
+        &pci,
+        &[reservation("100", 0)],
+        now,
+        expires_at
+    )
+    .await
+    .unwrap());
+    assert!(!reserve_spendings(
+        &pg_pool,
+        &address,
+        &pci,
+        &[reservation("1", 0)],
+        now,
+        expires_at
+    )
+    .await
+    .unwrap());
+    release_spendings(&pg_pool, &address, &pci, &[reservation("100", 0)], now)
+        .await
+        .unwrap();
+
+    // The released spendings are available again
+    assert!(reserve_spendings(
+        &pg_pool,
+        &address,
+        &pci,
+        &[reservation("100", 0)],
+        now,
+        expires_at
+    )
+    .await
+    .unwrap());
+}
+
+#[tokio::test]
+async fn release_spendings_keeps_next_period_usage() {
+    let pg_pool = get_postgres_pool().await;
+    let address = generate_random_address();
+    let pci = generate_random_string(16);
+    let now = 1_700_000_000;
+    let period = 3_600;
+    let expires_at = now + 86_400;
+    let reservation = |amount: &str| SpendingReservation {
+        policy_index: 0,
+        amount: amount.to_owned(),
+        limit: "100".to_owned(),
+        period,
+    };
+
+    assert!(reserve_spendings(
+        &pg_pool,
+        &address,
+        &pci,
+        &[reservation("60")],
+        now,
+        expires_at
+    )
+    .await
+    .unwrap());
+    // The next period usage is reserved before the previous period release
+    assert!(reserve_spendings(
+        &pg_pool,
+        &address,
+        &pci,
+        &[reservation("100")],
+        now + period,
+        expires_at
+    )
+    .await
+    .unwrap());
+    release_spendings(&pg_pool, &address, &pci, &[reservation("60")], now)
+        .await
+        .unwrap();
+    assert!(!reserve_spendings(
+        &pg_pool,
+        &address,
+        &pci,
+        &[reservation("1")],
+        now + period,
+        expires_at
+    )
+    .await
+    .unwrap());
+}
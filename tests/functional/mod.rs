@@ -1,5 +1,7 @@
 mod bundler;
 mod database;
 mod http;
+mod profile;
 mod sessions;
+mod vcr;
 mod websocket;
@@ -0,0 +1,33 @@
+use {rpc_proxy::test_helpers::spawn_blockchain_api_with_params, serde_json::json};
+
+#[tokio::test]
+#[ignore]
+async fn test_profile_register_rejects_invalid_project_signature() {
+    let server_url = spawn_blockchain_api_with_params(rpc_proxy::test_helpers::Params {
+        validate_project_id: false,
+        ..Default::default()
+    })
+    .await;
+
+    let url = server_url
+        .join("/v1/profile/account?projectId=test")
+        .unwrap();
+
+    // The signed request without the timestamp is rejected before reaching
+    // the handler
+    let client = reqwest::Client::new();
+    let response = client
+        .post(url)
+        .header("x-project-signature", "00")
+        .json(&json!({
+            "message": "{}",
+            "signature": "0x",
+            "coin_type": 60,
+            "address": "0x1234567890123456789012345678901234567890"
+        }))
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), reqwest::StatusCode::UNAUTHORIZED);
+}
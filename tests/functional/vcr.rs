@@ -0,0 +1,46 @@
+use rpc_proxy::test_helpers::{spawn_blockchain_api_with_params, vcr, Params};
+use serde_json::Value;
+use url::Url;
+
+const TEST_ADDRESS: &str = "0xd8dA6BF26964aF9D7eEd9e03E53415D37aA96045";
+
+/// Spawns the server with the provider API calls routed through the VCR
+/// proxy, the responses are replayed from `tests/fixtures/vcr/{cassette}.json`
+async fn spawn_with_cassette(cassette: &str) -> Url {
+    spawn_blockchain_api_with_params(Params {
+        validate_project_id: false,
+        override_api_proxy_url: Some(vcr::start(cassette).await),
+        ..Default::default()
+    })
+    .await
+}
+
+#[tokio::test]
+#[ignore]
+async fn zerion_provider_history_replay() {
+    let server_url = spawn_with_cassette("zerion_history").await;
+    let mut url = server_url
+        .join(&format!("/v1/account/{TEST_ADDRESS}/history"))
+        .unwrap();
+    url.query_pairs_mut().append_pair("projectId", "test");
+
+    let response = reqwest::get(url).await.unwrap();
+    assert_eq!(response.status(), reqwest::StatusCode::OK);
+    let body = response.json::<Value>().await.unwrap();
+    assert!(body["data"].is_array());
+}
+
+#[tokio::test]
+#[ignore]
+async fn one_inch_provider_tokens_list_replay() {
+    let server_url = spawn_with_cassette("one_inch_tokens").await;
+    let mut url = server_url.join("/v1/convert/tokens").unwrap();
+    url.query_pairs_mut()
+        .append_pair("projectId", "test")
+        .append_pair("chainId", "eip155:1");
+
+    let response = reqwest::get(url).await.unwrap();
+    assert_eq!(response.status(), reqwest::StatusCode::OK);
+    let body = response.json::<Value>().await.unwrap();
+    assert!(!body["tokens"].as_array().unwrap().is_empty());
+}